@@ -47,6 +47,14 @@ impl Accounts {
         }
     }
 
+    /// Returns all accounts ordered by address, e.g. to write a state snapshot.
+    pub fn get_all(&self, txn_option: Option<&db::Transaction>) -> Option<Vec<(Address, Account)>> {
+        match txn_option {
+            Some(txn) => self.tree.get_all(txn),
+            None => self.tree.get_all(&ReadTransaction::new(&self.env)),
+        }
+    }
+
     pub fn get_accounts_proof(&self, txn: &db::Transaction, addresses: &[Address]) -> AccountsProof<Account> {
         self.tree.get_accounts_proof(txn, addresses)
     }
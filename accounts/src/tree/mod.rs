@@ -273,6 +273,17 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
         Some(vec)
     }
 
+    /// Returns all accounts in the tree, ordered by address.
+    pub(crate) fn get_all(&self, txn: &Transaction) -> Option<Vec<(Address, A)>> {
+        self.get_terminal_nodes(txn, &AddressNibbles::empty(), usize::max_value())?
+            .into_iter()
+            .map(|node| match node {
+                AccountsTreeNode::TerminalNode { prefix, account } => Some((prefix.to_address()?, account)),
+                AccountsTreeNode::BranchNode { .. } => None,
+            })
+            .collect()
+    }
+
     fn get_root(&self, txn: &Transaction) -> Option<AccountsTreeNode<A>> {
         txn.get(&self.db, &AddressNibbles::empty())
     }
@@ -358,3 +358,31 @@ fn can_generate_accounts_proof() {
     assert_eq!(None, proof2.get_account(&address_recipient1));
     assert_eq!(Account::Basic(BasicAccount { balance: value2 }), proof2.get_account(&address_recipient2).unwrap());
 }
+
+#[test]
+fn it_can_restore_all_accounts() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+    let genesis_accounts: Vec<(Address, Account)> = (1u8..=5)
+        .map(|i| (Address::from([i; Address::SIZE]), Account::Basic(BasicAccount { balance: Coin::try_from(u64::from(i) * 1000).unwrap() })))
+        .rev()
+        .collect();
+    {
+        let mut txn = WriteTransaction::new(&env);
+        accounts.init(&mut txn, genesis_accounts.clone());
+        txn.commit();
+    }
+
+    let all = accounts.get_all(None).unwrap();
+    assert_eq!(all.len(), genesis_accounts.len());
+    assert!(all.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    let restored_env = VolatileEnvironment::new(10).unwrap();
+    let restored = Accounts::new(restored_env.clone());
+    {
+        let mut txn = WriteTransaction::new(&restored_env);
+        restored.init(&mut txn, all);
+        txn.commit();
+    }
+    assert_eq!(restored.hash(None), accounts.hash(None));
+}
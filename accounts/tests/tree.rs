@@ -2,8 +2,9 @@ use std::convert::TryFrom;
 use hex;
 
 use nimiq_accounts::tree::AccountsTree;
+use nimiq_database::memory::MemoryEnvironment;
 use nimiq_database::volatile::VolatileEnvironment;
-use nimiq_database::WriteTransaction;
+use nimiq_database::{ReadTransaction, WriteTransaction};
 use nimiq_keys::Address;
 use nimiq_account::{Account, BasicAccount};
 use nimiq_primitives::coin::Coin;
@@ -217,3 +218,33 @@ fn it_can_merge_nodes_while_pruning() {
 
     txn.abort();
 }
+
+#[test]
+fn it_has_the_same_root_hash_on_all_backends() {
+    let address1 = Address::from(&hex::decode("0000000000000000000000000000000000000000").unwrap()[..]);
+    let account1 = Account::Basic(BasicAccount { balance: Coin::try_from(5).unwrap() });
+    let address2 = Address::from(&hex::decode("1000000000000000000000000000000000000000").unwrap()[..]);
+    let account2 = Account::Basic(BasicAccount { balance: Coin::try_from(55).unwrap() });
+    let address3 = Address::from(&hex::decode("1200000000000000000000000000000000000000").unwrap()[..]);
+    let account3 = Account::Basic(BasicAccount { balance: Coin::try_from(55555555).unwrap() });
+
+    let root_hashes: Vec<_> = vec![VolatileEnvironment::new(10).unwrap(), MemoryEnvironment::new()].into_iter().map(|env| {
+        let tree = AccountsTree::new(env.clone());
+        let mut txn = WriteTransaction::new(&env);
+        tree.put(&mut txn, &address1, account1.clone());
+        tree.put(&mut txn, &address2, account2.clone());
+        tree.put(&mut txn, &address3, account3.clone());
+        txn.commit();
+
+        let mut txn = WriteTransaction::new(&env);
+        tree.put(&mut txn, &address2, Account::Basic(BasicAccount { balance: Coin::ZERO }));
+        txn.commit();
+
+        let txn = ReadTransaction::new(&env);
+        assert_eq!(tree.get(&txn, &address1), Some(account1.clone()));
+        assert!(tree.get(&txn, &address2).is_none());
+        tree.root_hash(&txn)
+    }).collect();
+
+    assert_eq!(root_hashes[0], root_hashes[1]);
+}
@@ -9,8 +9,9 @@ use quote::quote;
 
 enum FieldAttribute {
     Uvar,
+    Raw,
     Skip(Option<syn::Lit>),
-    LenType(syn::Ident)
+    LenType(syn::Ident, Option<TokenStream>)
 }
 
 #[inline]
@@ -28,6 +29,8 @@ fn parse_field_attribs(field: &syn::Field) -> Option<FieldAttribute> {
     for attr in &field.attrs {
         if let Meta::List(ref meta_list) = attr.parse_meta().unwrap() {
             if cmp_ident(&meta_list.path, "beserial") {
+                let mut len_type: Option<syn::Ident> = None;
+                let mut limit: Option<TokenStream> = None;
                 for nested in meta_list.nested.iter() {
                     if let syn::NestedMeta::Meta(ref item) = nested {
                         match item {
@@ -39,7 +42,7 @@ fn parse_field_attribs(field: &syn::Field) -> Option<FieldAttribute> {
                                                 if !cmp_ident(value, "u8") && !cmp_ident(value, "u16") && !cmp_ident(value, "u32") {
                                                     panic!("beserial(len_type) must be one of [u8, u16, u32], but was {:?}", value);
                                                 }
-                                                return Some(FieldAttribute::LenType(value.get_ident().cloned().unwrap()));
+                                                len_type = value.get_ident().cloned();
                                             }
                                         }
                                     }
@@ -57,11 +60,28 @@ fn parse_field_attribs(field: &syn::Field) -> Option<FieldAttribute> {
                                     return Some(FieldAttribute::Skip(None));
                                 }
                             }
+                            Meta::NameValue(ref meta_name_value) if cmp_ident(&meta_name_value.path, "limit") => {
+                                // Either an integer or a string with the path of a `usize` constant.
+                                limit = match meta_name_value.lit {
+                                    syn::Lit::Int(ref lit_int) => {
+                                        let limit = proc_macro2::Literal::usize_unsuffixed(lit_int.base10_parse::<usize>().unwrap());
+                                        Some(quote! { #limit })
+                                    },
+                                    syn::Lit::Str(ref lit_str) => {
+                                        let path: syn::ExprPath = lit_str.parse()
+                                            .unwrap_or_else(|_| panic!("beserial(limit) must be the path of a constant, but was {:?}", lit_str.value()));
+                                        Some(quote! { #path })
+                                    },
+                                    ref lit => panic!("beserial(limit) must be an integer or a constant, but was {:?}", lit),
+                                }
+                            }
                             Meta::Path(ref path) => {
                                 if cmp_ident(path, "skip") {
                                     return Some(FieldAttribute::Skip(None));
                                 } else if cmp_ident(path, "uvar") {
                                     return Some(FieldAttribute::Uvar);
+                                } else if cmp_ident(path, "raw") {
+                                    return Some(FieldAttribute::Raw);
                                 } else {
                                     panic!("unknown flag for beserial: {:?}", path)
                                 }
@@ -70,15 +90,29 @@ fn parse_field_attribs(field: &syn::Field) -> Option<FieldAttribute> {
                         }
                     }
                 }
+                match len_type {
+                    Some(len_type) => return Some(FieldAttribute::LenType(len_type, limit)),
+                    None if limit.is_some() => panic!("beserial(limit) requires beserial(len_type)"),
+                    None => {},
+                }
             }
         }
     }
     None
 }
 
-fn parse_enum_attribs(ast: &syn::DeriveInput) -> (Option<syn::Ident>, bool) {
+struct EnumAttributes {
+    repr: Option<syn::Ident>,
+    uvar: bool,
+    untagged: bool,
+    tag_type: Option<syn::Ident>,
+}
+
+fn parse_enum_attribs(ast: &syn::DeriveInput) -> EnumAttributes {
     let mut enum_type: Option<syn::Ident> = Option::None;
     let mut uvar = false;
+    let mut untagged = false;
+    let mut tag_type: Option<syn::Ident> = Option::None;
     for attr in &ast.attrs {
         if let Meta::List(ref meta_list) = attr.parse_meta().unwrap() {
             if cmp_ident(&meta_list.path, "repr") {
@@ -88,19 +122,217 @@ fn parse_enum_attribs(ast: &syn::DeriveInput) -> (Option<syn::Ident>, bool) {
             } else if cmp_ident(&meta_list.path, "beserial") {
                 for nested in meta_list.nested.iter() {
                     if let syn::NestedMeta::Meta(ref item) = nested {
-                        if let Meta::Path(ref attr_ident) = item {
-                            if cmp_ident(attr_ident, "uvar") {
-                                uvar = true;
-                            } else {
-                                panic!("unknown flag for beserial: {:?}", attr_ident)
+                        match item {
+                            Meta::Path(ref attr_ident) => {
+                                if cmp_ident(attr_ident, "uvar") {
+                                    uvar = true;
+                                } else if cmp_ident(attr_ident, "untagged") {
+                                    untagged = true;
+                                } else {
+                                    panic!("unknown flag for beserial: {:?}", attr_ident)
+                                }
+                            }
+                            Meta::List(ref meta_list) if cmp_ident(&meta_list.path, "tag_type") => {
+                                tag_type = meta_list.nested.first().and_then(|n| {
+                                    if let syn::NestedMeta::Meta(Meta::Path(ref meta_type)) = n { meta_type.get_ident().cloned() } else { Option::None }
+                                });
+                                match tag_type {
+                                    Some(ref ty) if ty == "u8" || ty == "u16" || ty == "u32" || ty == "u64" => {},
+                                    _ => panic!("beserial(tag_type) must be one of [u8, u16, u32, u64], but was {:?}", meta_list.nested),
+                                }
                             }
+                            _ => panic!("unknown attribute for beserial: {:?}", item)
                         }
                     }
                 }
             }
         }
     }
-    (enum_type, uvar)
+    if untagged && (uvar || tag_type.is_some()) {
+        panic!("beserial(untagged) can not be combined with beserial(uvar) or beserial(tag_type)");
+    }
+    EnumAttributes { repr: enum_type, uvar, untagged, tag_type }
+}
+
+// Returns the tag given by `#[beserial(tag = ...)]` on an enum variant.
+fn parse_variant_tag(variant: &syn::Variant) -> Option<u64> {
+    for attr in &variant.attrs {
+        if let Meta::List(ref meta_list) = attr.parse_meta().unwrap() {
+            if cmp_ident(&meta_list.path, "beserial") {
+                return match meta_list.nested.first() {
+                    Some(syn::NestedMeta::Meta(Meta::NameValue(ref meta_name_value))) if cmp_ident(&meta_name_value.path, "tag") => {
+                        match meta_name_value.lit {
+                            syn::Lit::Int(ref lit_int) => Some(lit_int.base10_parse::<u64>().unwrap()),
+                            ref lit => panic!("beserial(tag) must be an integer, but was {:?}", lit),
+                        }
+                    }
+                    // Only meaningful for untagged enums, see `default_variant`.
+                    Some(syn::NestedMeta::Meta(Meta::Path(ref path))) if cmp_ident(path, "default") => None,
+                    nested => panic!("unknown attribute for beserial: {:?}", nested)
+                };
+            }
+        }
+    }
+    None
+}
+
+// Returns the variant of an untagged enum marked with `#[beserial(default)]`. Since the variant
+// isn't written, untagged enums are always deserialized into this variant.
+fn default_variant<'a>(name: &syn::Ident, data_enum: &'a syn::DataEnum) -> &'a syn::Variant {
+    let is_default = |variant: &&syn::Variant| variant.attrs.iter().any(|attr| {
+        match attr.parse_meta().unwrap() {
+            Meta::List(ref meta_list) if cmp_ident(&meta_list.path, "beserial") => {
+                meta_list.nested.iter().any(|nested| match nested {
+                    syn::NestedMeta::Meta(Meta::Path(ref path)) => cmp_ident(path, "default"),
+                    _ => false,
+                })
+            }
+            _ => false,
+        }
+    });
+    let mut defaults = data_enum.variants.iter().filter(is_default);
+    match (defaults.next(), defaults.next()) {
+        (Some(variant), None) => variant,
+        _ => panic!("Untagged enum {} requires exactly one variant with beserial(default)", name),
+    }
+}
+
+// Whether the enum has to be serialized with a tag followed by the fields of the variant.
+fn is_data_enum(data_enum: &syn::DataEnum, attributes: &EnumAttributes) -> bool {
+    attributes.tag_type.is_some() || attributes.untagged || data_enum.variants.iter().any(|variant| variant.fields != syn::Fields::Unit)
+}
+
+// Returns the tag of every variant. Variants without an explicit tag follow the previous one.
+fn variant_tags(name: &syn::Ident, data_enum: &syn::DataEnum) -> Vec<u64> {
+    let mut tags = Vec::<u64>::new();
+    for variant in data_enum.variants.iter() {
+        let tag = match parse_variant_tag(variant) {
+            Some(tag) => tag,
+            None => tags.last().map(|tag| tag + 1).unwrap_or(0),
+        };
+        if tags.contains(&tag) {
+            panic!("Duplicate beserial(tag) {} in enum {}", tag, name);
+        }
+        tags.push(tag);
+    }
+    tags
+}
+
+// Returns the type of the tag of a data-carrying enum.
+fn tag_type(name: &syn::Ident, attributes: &EnumAttributes) -> Ident {
+    match attributes.tag_type {
+        Some(ref ty) => ty.clone(),
+        None if attributes.uvar => Ident::new("u64", Span::call_site()),
+        None => panic!("Enum {} has variants with fields and requires beserial(tag_type)", name),
+    }
+}
+
+// Returns the bindings for the fields of a variant in a match pattern and the names they are bound to.
+fn variant_bindings(fields: &syn::Fields) -> (TokenStream, Vec<Ident>) {
+    match fields {
+        syn::Fields::Named(ref fields_named) => {
+            let idents: Vec<Ident> = fields_named.named.iter().map(|field| field.ident.clone().unwrap()).collect();
+            (quote! { { #(ref #idents),* } }, idents)
+        }
+        syn::Fields::Unnamed(ref fields_unnamed) => {
+            let idents: Vec<Ident> = (0..fields_unnamed.unnamed.len())
+                .map(|i| Ident::new(&format!("__field{}", i), Span::call_site()))
+                .collect();
+            (quote! { ( #(ref #idents),* ) }, idents)
+        }
+        syn::Fields::Unit => (quote! {}, vec![]),
+    }
+}
+
+// Returns the statements serializing a field and computing its size.
+// `value` must evaluate to a reference to the field.
+fn serialize_field(field: &syn::Field, value: TokenStream) -> Option<(TokenStream, TokenStream)> {
+    match parse_field_attribs(field) {
+        Some(FieldAttribute::Skip(_)) => None,
+        Some(FieldAttribute::LenType(ty, _)) => Some((
+            quote! { size += ::beserial::SerializeWithLength::serialize::<#ty, W>(#value, writer)?; },
+            quote! { size += ::beserial::SerializeWithLength::serialized_size::<#ty>(#value); },
+        )),
+        Some(FieldAttribute::Raw) => Some((
+            quote! {
+                let bytes: &[u8] = ::std::convert::AsRef::<[u8]>::as_ref(#value);
+                ::std::io::Write::write_all(writer, bytes)?;
+                size += bytes.len();
+            },
+            quote! { size += ::std::convert::AsRef::<[u8]>::as_ref(#value).len(); },
+        )),
+        _ => Some((
+            quote! { size += Serialize::serialize(#value, writer)?; },
+            quote! { size += Serialize::serialized_size(#value); },
+        )),
+    }
+}
+
+// Returns the `limit` argument passed to `DeserializeWithLength::deserialize_with_limit`.
+fn limit_tokens(limit: &Option<TokenStream>) -> TokenStream {
+    match limit {
+        Some(limit) => quote! { Some(#limit) },
+        None => quote! { None },
+    }
+}
+
+// Returns the part of a struct or variant expression that follows its path, deserializing all
+// fields.
+fn deserialize_fields(fields: &syn::Fields) -> TokenStream {
+    let mut field_cases = Vec::<TokenStream>::new();
+    for field in fields.iter() {
+        let field_attrib = parse_field_attribs(field);
+
+        match (&field.ident, &field_attrib) {
+            // tuple field, but skip with given default value
+            (None, Some(FieldAttribute::Skip(Some(default_value)))) => {
+                field_cases.push(quote! { #default_value, });
+            },
+            // tuple field, but skip with Default trait
+            (None, Some(FieldAttribute::Skip(None))) => {
+                let ty = &field.ty;
+                field_cases.push(quote! { <#ty>::default(), });
+            },
+
+            // struct field, but skip with given default value
+            (Some(ident), Some(FieldAttribute::Skip(Some(default_value)))) => {
+                field_cases.push(quote! { #ident: #default_value, });
+            },
+            // struct field, but skip with Default trait
+            (Some(ident), Some(FieldAttribute::Skip(None))) => {
+                let ty = &field.ty;
+                field_cases.push(quote! { #ident: <#ty>::default(), });
+            },
+
+            // tuple field with len_type
+            (None, Some(FieldAttribute::LenType(ty, limit))) => {
+                let limit = limit_tokens(limit);
+                field_cases.push(quote! { ::beserial::DeserializeWithLength::deserialize_with_limit::<#ty,R>(reader, #limit)?, })
+            },
+            // tuple field without len_type
+            (None, None) => {
+                field_cases.push(quote! { ::beserial::Deserialize::deserialize(reader)?, })
+            },
+
+            // struct field with len_type
+            (Some(ident), Some(FieldAttribute::LenType(ty, limit))) => {
+                let limit = limit_tokens(limit);
+                field_cases.push(quote! { #ident: ::beserial::DeserializeWithLength::deserialize_with_limit::<#ty,R>(reader, #limit)?, })
+            },
+            // struct field without len_type
+            (Some(ident), None) => {
+                field_cases.push(quote! { #ident: ::beserial::Deserialize::deserialize(reader)?, })
+            },
+            (_, Some(FieldAttribute::Uvar)) => panic!("beserial(uvar) attribute not allowed for struct fields"),
+            (_, Some(FieldAttribute::Raw)) => panic!("beserial(raw) fields have no length and can not be deserialized"),
+        }
+    }
+
+    match fields {
+        syn::Fields::Named(_) => quote! { { #(#field_cases)* } },
+        syn::Fields::Unnamed(_) => quote! { ( #(#field_cases)* ) },
+        syn::Fields::Unit => quote! {},
+    }
 }
 
 fn expr_from_value(value: u64) -> syn::Expr {
@@ -124,8 +356,47 @@ fn impl_serialize(ast: &syn::DeriveInput) -> TokenStream {
     let mut serialized_size_body = Vec::<TokenStream>::new();
 
     match ast.data {
+        Data::Enum(ref data_enum) if is_data_enum(data_enum, &parse_enum_attribs(ast)) => {
+            let attributes = parse_enum_attribs(ast);
+            let tags = if attributes.untagged {
+                vec![0; data_enum.variants.len()]
+            } else {
+                variant_tags(name, data_enum)
+            };
+
+            let mut serialize_arms = Vec::<TokenStream>::new();
+            let mut serialized_size_arms = Vec::<TokenStream>::new();
+            for (variant, tag) in data_enum.variants.iter().zip(tags) {
+                let ident = &variant.ident;
+                let tag = expr_from_value(tag);
+                let (bindings, values) = variant_bindings(&variant.fields);
+
+                let (mut serialize_fields, mut serialized_size_fields) = if attributes.untagged {
+                    (vec![], vec![])
+                } else if attributes.uvar {
+                    (vec![quote! { size += Serialize::serialize(&::beserial::uvar::from(#tag as u64), writer)?; }],
+                     vec![quote! { size += Serialize::serialized_size(&::beserial::uvar::from(#tag as u64)); }])
+                } else {
+                    let ty = tag_type(name, &attributes);
+                    (vec![quote! { size += Serialize::serialize(&(#tag as #ty), writer)?; }],
+                     vec![quote! { size += Serialize::serialized_size(&(#tag as #ty)); }])
+                };
+                for (field, value) in variant.fields.iter().zip(values) {
+                    if let Some((serialize, serialized_size)) = serialize_field(field, quote! { #value }) {
+                        serialize_fields.push(serialize);
+                        serialized_size_fields.push(serialized_size);
+                    }
+                }
+
+                serialize_arms.push(quote! { #name::#ident #bindings => { #(#serialize_fields)* }, });
+                serialized_size_arms.push(quote! { #name::#ident #bindings => { #(#serialized_size_fields)* }, });
+            }
+
+            serialize_body.push(quote! { match *self { #(#serialize_arms)* } });
+            serialized_size_body.push(quote! { match *self { #(#serialized_size_arms)* } });
+        }
         Data::Enum(_) => {
-            let (enum_type, uvar) = parse_enum_attribs(ast);
+            let EnumAttributes { repr: enum_type, uvar, .. } = parse_enum_attribs(ast);
 
             if uvar {
                 let ty = enum_type.unwrap_or_else(|| Ident::new("u64", Span::call_site()));
@@ -139,38 +410,17 @@ fn impl_serialize(ast: &syn::DeriveInput) -> TokenStream {
         }
         Data::Struct(ref data_struct) => {
             for (i, field) in data_struct.fields.iter().enumerate() {
-                let len_type = match parse_field_attribs(&field) {
-                    Some(FieldAttribute::Skip(_)) => continue,
-                    Some(FieldAttribute::LenType(ty)) => Some(ty),
-                    _ => None,
-                };
-
-                match field.ident {
+                let value = match field.ident {
                     None => {
                         let index = Index::from(i);
-                        match len_type {
-                            Some(ty) => {
-                                serialize_body.push(quote! { size += ::beserial::SerializeWithLength::serialize::<#ty, W>(&self.#index, writer)?; });
-                                serialized_size_body.push(quote! { size += ::beserial::SerializeWithLength::serialized_size::<#ty>(&self.#index); });
-                            }
-                            None => {
-                                serialize_body.push(quote! { size += Serialize::serialize(&self.#index, writer)?; });
-                                serialized_size_body.push(quote! { size += Serialize::serialized_size(&self.#index); });
-                            }
-                        }
-                    }
-                    Some(ref ident) => {
-                        match len_type {
-                            Some(ty) => {
-                                serialize_body.push(quote! { size += ::beserial::SerializeWithLength::serialize::<#ty, W>(&self.#ident, writer)?; });
-                                serialized_size_body.push(quote! { size += ::beserial::SerializeWithLength::serialized_size::<#ty>(&self.#ident); });
-                            }
-                            None => {
-                                serialize_body.push(quote! { size += Serialize::serialize(&self.#ident, writer)?; });
-                                serialized_size_body.push(quote! { size += Serialize::serialized_size(&self.#ident); });
-                            }
-                        }
+                        quote! { &self.#index }
                     }
+                    Some(ref ident) => quote! { &self.#ident },
+                };
+
+                if let Some((serialize, serialized_size)) = serialize_field(field, value) {
+                    serialize_body.push(serialize);
+                    serialized_size_body.push(serialized_size);
                 }
             }
         }
@@ -210,8 +460,48 @@ fn impl_deserialize(ast: &syn::DeriveInput) -> TokenStream {
     let deserialize_body;
 
     match ast.data {
+        Data::Enum(ref data_enum) if parse_enum_attribs(ast).untagged => {
+            let variant = default_variant(name, data_enum);
+            let ident = &variant.ident;
+            let fields = deserialize_fields(&variant.fields);
+            deserialize_body = quote! {
+                return Ok(#name::#ident #fields);
+            };
+        }
+        Data::Enum(ref data_enum) if is_data_enum(data_enum, &parse_enum_attribs(ast)) => {
+            let attributes = parse_enum_attribs(ast);
+            let ty = tag_type(name, &attributes);
+            let tags = variant_tags(name, data_enum);
+
+            let mut tag_cases = Vec::<TokenStream>::new();
+            for (variant, tag) in data_enum.variants.iter().zip(tags) {
+                let ident = &variant.ident;
+                let tag = expr_from_value(tag);
+                let fields = deserialize_fields(&variant.fields);
+                tag_cases.push(quote! { #tag => Ok(#name::#ident #fields), });
+            }
+
+            if attributes.uvar {
+                deserialize_body = quote! {
+                    let u: ::beserial::uvar = Deserialize::deserialize(reader)?;
+                    let tag: u64 = u.into();
+                    return match tag {
+                        #(#tag_cases)*
+                        _ => Err(::beserial::SerializingError::InvalidValue)
+                    };
+                };
+            } else {
+                deserialize_body = quote! {
+                    let tag: #ty = Deserialize::deserialize(reader)?;
+                    return match tag {
+                        #(#tag_cases)*
+                        _ => Err(::beserial::SerializingError::InvalidValue)
+                    };
+                };
+            }
+        }
         Data::Enum(ref data_enum) => {
-            let (enum_type, uvar) = parse_enum_attribs(ast);
+            let EnumAttributes { repr: enum_type, uvar, .. } = parse_enum_attribs(ast);
 
             let ty= if uvar {
                 enum_type.unwrap_or_else(|| Ident::new("u64", syn::export::Span::call_site()))
@@ -260,68 +550,10 @@ fn impl_deserialize(ast: &syn::DeriveInput) -> TokenStream {
             }
         }
         Data::Struct(ref data_struct) => {
-            let mut tuple = false;
-            let mut field_cases = Vec::<TokenStream>::new();
-            for field in data_struct.fields.iter() {
-                let field_attrib = parse_field_attribs(&field);
-
-                match (&field.ident, &field_attrib) {
-                    // tuple field, but skip with given default value
-                    (None, Some(FieldAttribute::Skip(Some(default_value)))) => {
-                        field_cases.push(quote! { #default_value, });
-                    },
-                    // tuple field, but skip with Default trait
-                    (None, Some(FieldAttribute::Skip(None))) => {
-                        let ty = &field.ty;
-                        field_cases.push(quote! { <#ty>::default(), });
-                    },
-
-                    // struct field, but skip with given default value
-                    (Some(ident), Some(FieldAttribute::Skip(Some(default_value)))) => {
-                        field_cases.push(quote! { #ident: #default_value, });
-                    },
-                    // struct field, but skip with Default trait
-                    (Some(ident), Some(FieldAttribute::Skip(None))) => {
-                        let ty = &field.ty;
-                        field_cases.push(quote! { #ident: <#ty>::default(), });
-                    },
-
-                    // tuple field with len_type
-                    (None, Some(FieldAttribute::LenType(ty))) => {
-                        tuple = true;
-                        field_cases.push(quote! { ::beserial::DeserializeWithLength::deserialize::<#ty,R>(reader)?, })
-                    },
-                    // tuple field without len_type
-                    (None, None) => {
-                        tuple = true;
-                        field_cases.push(quote! { ::beserial::Deserialize::deserialize(reader)?, })
-                    },
-
-                    // struct field with len_type
-                    (Some(ident), Some(FieldAttribute::LenType(ty))) => {
-                        field_cases.push(quote! { #ident: ::beserial::DeserializeWithLength::deserialize::<#ty,R>(reader)?, })
-                    },
-                    // struct field without len_type
-                    (Some(ident), None) => {
-                        field_cases.push(quote! { #ident: ::beserial::Deserialize::deserialize(reader)?, })
-                    },
-                    (_, Some(FieldAttribute::Uvar)) => panic!("beserial(uvar) attribute not allowed for struct fields")
-                }
-            }
-
-            if tuple {
-                deserialize_body = quote!({
-                    return Ok(#name (
-                        #(#field_cases)*
-                    ));
-                });
-            } else {
-                deserialize_body = quote!({
-                    return Ok(#name {
-                        #(#field_cases)*
-                    });
-                });
-            }
+            let fields = deserialize_fields(&data_struct.fields);
+            deserialize_body = quote!({
+                return Ok(#name #fields);
+            });
         }
         Data::Union(_) => panic!("Deserialize can not be derived for Union {}", name)
     };
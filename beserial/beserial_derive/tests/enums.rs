@@ -87,3 +87,87 @@ fn it_can_handle_value_enums_with_repr_uvar() {
     assert_eq!(reserialize_to_num(TestUVar::D), 9223372036854775808.into());
 }
 
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[beserial(tag_type(u8))]
+enum TestData {
+    #[beserial(tag = 1)]
+    Tuple(u16, u32),
+    Struct {
+        index: u16,
+        #[beserial(len_type(u16))]
+        data: Vec<u8>,
+        #[beserial(skip)]
+        cached: u32,
+    },
+    #[beserial(tag = 7)]
+    Unit,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[beserial(uvar)]
+enum TestDataUVar {
+    #[beserial(tag = 300)]
+    A(u8),
+    B,
+}
+
+#[test]
+fn it_can_handle_data_enums() {
+    fn reserialize(test: &TestData) -> TestData {
+        let v = Serialize::serialize_to_vec(test);
+        assert_eq!(v.len(), Serialize::serialized_size(test));
+        Deserialize::deserialize(&mut &v[..]).unwrap()
+    }
+    let tuple = TestData::Tuple(0x0102, 0x03040506);
+    let with_fields = TestData::Struct { index: 3, data: vec![0xaa, 0xbb], cached: 0 };
+    assert_eq!(reserialize(&tuple), tuple);
+    assert_eq!(reserialize(&with_fields), with_fields);
+    assert_eq!(reserialize(&TestData::Unit), TestData::Unit);
+
+    assert_eq!(Serialize::serialize_to_vec(&tuple), vec![1, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+    assert_eq!(Serialize::serialize_to_vec(&with_fields), vec![2, 0, 3, 0, 2, 0xaa, 0xbb]);
+    assert_eq!(Serialize::serialize_to_vec(&TestData::Unit), vec![7]);
+
+    // Skipped fields are restored with their default value.
+    let cached = TestData::Struct { index: 3, data: vec![], cached: 42 };
+    assert_eq!(reserialize(&cached), TestData::Struct { index: 3, data: vec![], cached: 0 });
+
+    let invalid: Result<TestData, _> = Deserialize::deserialize(&mut &[3u8][..]);
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn it_can_handle_data_enums_with_uvar_tags() {
+    fn reserialize(test: &TestDataUVar) -> TestDataUVar {
+        let v = Serialize::serialize_to_vec(test);
+        Deserialize::deserialize(&mut &v[..]).unwrap()
+    }
+    assert_eq!(reserialize(&TestDataUVar::A(5)), TestDataUVar::A(5));
+    assert_eq!(reserialize(&TestDataUVar::B), TestDataUVar::B);
+
+    let v = Serialize::serialize_to_vec(&TestDataUVar::B);
+    let tag: uvar = Deserialize::deserialize(&mut &v[..]).unwrap();
+    assert_eq!(tag, 301.into());
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[beserial(untagged)]
+enum TestUntagged {
+    Raw(#[beserial(raw)] Vec<u8>),
+    #[beserial(default)]
+    Structured(u16, u8),
+}
+
+#[test]
+fn it_can_handle_untagged_enums() {
+    let structured = TestUntagged::Structured(0x0102, 3);
+    let raw = TestUntagged::Raw(vec![1, 2, 3]);
+    assert_eq!(Serialize::serialize_to_vec(&structured), vec![1, 2, 3]);
+    assert_eq!(Serialize::serialize_to_vec(&raw), vec![1, 2, 3]);
+    assert_eq!(Serialize::serialized_size(&raw), 3);
+
+    // Untagged enums are always deserialized into the default variant.
+    let deserialized: TestUntagged = Deserialize::deserialize(&mut &[1u8, 2, 3][..]).unwrap();
+    assert_eq!(deserialized, structured);
+}
@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate beserial_derive;
 
-use beserial::{Deserialize, Serialize};
+use beserial::{Deserialize, Serialize, SerializingError};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 struct TestStruct {
@@ -27,3 +27,51 @@ fn it_correctly_serializes_and_deserializes_option_of_vec() {
     assert_eq!(test.serialized_size(), 6);
     assert_eq!(reserialize(&test), test);
 }
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct TestLimit {
+    #[beserial(len_type(u16), limit = 3)]
+    test: Vec<u8>,
+    #[beserial(len_type(u8), limit = 2)]
+    test_option: Option<Vec<u16>>,
+}
+
+#[test]
+fn it_enforces_length_limits() {
+    let test = TestLimit { test: vec![1, 2, 3], test_option: Some(vec![4, 5]) };
+    let v = test.serialize_to_vec();
+    assert_eq!(v, vec![0, 3, 1, 2, 3, 1, 2, 0, 4, 0, 5]);
+    assert_eq!(TestLimit::deserialize_from_vec(&v), Ok(test));
+
+    let test = TestLimit { test: vec![1, 2, 3, 4], test_option: None };
+    assert_eq!(TestLimit::deserialize_from_vec(&test.serialize_to_vec()), Err(SerializingError::LimitExceeded));
+
+    let test = TestLimit { test: vec![], test_option: Some(vec![4, 5, 6]) };
+    assert_eq!(TestLimit::deserialize_from_vec(&test.serialize_to_vec()), Err(SerializingError::LimitExceeded));
+}
+
+const MAX_TEST_OPTION: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct TestConstLimit {
+    #[beserial(len_type(u16), limit = "Self::MAX_TEST")]
+    test: Vec<u8>,
+    #[beserial(len_type(u8), limit = "MAX_TEST_OPTION")]
+    test_option: Option<Vec<u16>>,
+}
+
+impl TestConstLimit {
+    const MAX_TEST: usize = 3;
+}
+
+#[test]
+fn it_enforces_constant_length_limits() {
+    let test = TestConstLimit { test: vec![1, 2, 3], test_option: Some(vec![4, 5]) };
+    assert_eq!(TestConstLimit::deserialize_from_vec(&test.serialize_to_vec()), Ok(test));
+
+    let test = TestConstLimit { test: vec![1, 2, 3, 4], test_option: None };
+    assert_eq!(TestConstLimit::deserialize_from_vec(&test.serialize_to_vec()), Err(SerializingError::LimitExceeded));
+
+    let test = TestConstLimit { test: vec![], test_option: Some(vec![4, 5, 6]) };
+    assert_eq!(TestConstLimit::deserialize_from_vec(&test.serialize_to_vec()), Err(SerializingError::LimitExceeded));
+}
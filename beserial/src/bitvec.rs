@@ -47,9 +47,10 @@ impl<C: Cursor> DeserializeWithLength for BitVec<C, u8> {
         let n_bytes = bits_to_bytes(n_bits);
 
         // If number of bits is too large, abort.
-        if limit.map(|l| l > n_bits).unwrap_or(false) {
+        if limit.map(|l| n_bits > l).unwrap_or(false) {
             return Err(SerializingError::LimitExceeded);
         }
+        crate::budget::allocate(n_bytes)?;
 
         // Read bytes into Vec
         let mut data = Vec::with_capacity(n_bytes);
@@ -0,0 +1,58 @@
+use std::cell::Cell;
+
+use crate::SerializingError;
+
+thread_local! {
+    // Remaining number of bytes collections may allocate during deserialization on this thread.
+    static ALLOCATION_BUDGET: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// Restores the enclosing budget, minus everything spent in the inner one, when dropped.
+struct BudgetGuard {
+    previous: Option<usize>,
+    initial: usize,
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        ALLOCATION_BUDGET.with(|budget| {
+            let spent = self.initial - budget.get().unwrap_or(0);
+            budget.set(self.previous.map(|previous| previous.saturating_sub(spent)));
+        });
+    }
+}
+
+/// Runs `f`, allowing the collections it deserializes to allocate at most `max_bytes` in total.
+/// Deserializing a collection whose announced length would exceed the remaining budget fails
+/// with `SerializingError::LimitExceeded` before anything is allocated.
+///
+/// Budgets can be nested, the inner budget is capped by what is left of the outer one.
+pub fn with_allocation_budget<T, F>(max_bytes: usize, f: F) -> Result<T, SerializingError>
+    where F: FnOnce() -> Result<T, SerializingError>
+{
+    let previous = ALLOCATION_BUDGET.with(|budget| budget.get());
+    let initial = previous.map(|previous| previous.min(max_bytes)).unwrap_or(max_bytes);
+    ALLOCATION_BUDGET.with(|budget| budget.set(Some(initial)));
+    let _guard = BudgetGuard { previous, initial };
+    f()
+}
+
+/// Takes `bytes` from the allocation budget of the current thread, if there is one.
+/// Implementations of `Deserialize` call this before allocating memory for a collection.
+pub fn allocate(bytes: usize) -> Result<(), SerializingError> {
+    ALLOCATION_BUDGET.with(|budget| {
+        match budget.get() {
+            Some(remaining) if bytes > remaining => Err(SerializingError::LimitExceeded),
+            Some(remaining) => {
+                budget.set(Some(remaining - bytes));
+                Ok(())
+            },
+            None => Ok(()),
+        }
+    })
+}
+
+/// Takes the memory needed for `len` elements of type `T` from the allocation budget.
+pub fn allocate_elements<T>(len: usize) -> Result<(), SerializingError> {
+    allocate(len.saturating_mul(std::mem::size_of::<T>()))
+}
@@ -7,9 +7,11 @@ pub use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
 use failure::Fail;
 pub use num::{FromPrimitive, ToPrimitive};
 
+pub use crate::budget::with_allocation_budget;
 pub use crate::types::uvar;
 use std::sync::Arc;
 
+pub mod budget;
 mod types;
 #[cfg(feature = "bitvec")]
 mod bitvec;
@@ -223,6 +225,7 @@ impl<T: Deserialize> DeserializeWithLength for Vec<T> {
         if limit.map(|l| len_u > l).unwrap_or(false) {
             return Err(SerializingError::LimitExceeded);
         }
+        budget::allocate_elements::<T>(len_u)?;
 
         let mut v = Vec::with_capacity(len_u);
         for _ in 0..len_u {
@@ -300,6 +303,7 @@ impl<T, H> DeserializeWithLength for HashSet<T, H>
         if limit.map(|l| len_u > l).unwrap_or(false) {
             return Err(SerializingError::LimitExceeded);
         }
+        budget::allocate_elements::<T>(len_u)?;
 
         let mut v = HashSet::with_capacity_and_hasher(len_u, H::default());
         for _ in 0..len_u {
@@ -348,6 +352,7 @@ impl<K, V> DeserializeWithLength for BTreeMap<K, V>
         if limit.map(|l| len_u > l).unwrap_or(false) {
             return Err(SerializingError::LimitExceeded);
         }
+        budget::allocate_elements::<(K, V)>(len_u)?;
 
         let mut v = BTreeMap::new();
         for _ in 0..len_u {
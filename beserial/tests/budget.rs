@@ -0,0 +1,49 @@
+use beserial::{with_allocation_budget, DeserializeWithLength, Serialize, SerializeWithLength, SerializingError};
+
+fn serialize_vec(v: &Vec<u64>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SerializeWithLength::serialized_size::<u16>(v));
+    SerializeWithLength::serialize::<u16, Vec<u8>>(v, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn it_enforces_the_allocation_budget() {
+    let bytes = serialize_vec(&vec![1, 2, 3, 4]);
+
+    let v: Vec<u64> = with_allocation_budget(32, || DeserializeWithLength::deserialize::<u16, _>(&mut &bytes[..])).unwrap();
+    assert_eq!(v, vec![1, 2, 3, 4]);
+
+    let res: Result<Vec<u64>, _> = with_allocation_budget(31, || DeserializeWithLength::deserialize::<u16, _>(&mut &bytes[..]));
+    assert_eq!(res, Err(SerializingError::LimitExceeded));
+
+    // Without a budget, only the length type limits the allocation.
+    let v: Vec<u64> = DeserializeWithLength::deserialize::<u16, _>(&mut &bytes[..]).unwrap();
+    assert_eq!(v, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn it_checks_the_budget_before_allocating() {
+    // Announces u16::MAX elements, but contains none of them.
+    let bytes = 0xffffu16.serialize_to_vec();
+    let res: Result<Vec<u64>, _> = with_allocation_budget(1024, || DeserializeWithLength::deserialize::<u16, _>(&mut &bytes[..]));
+    assert_eq!(res, Err(SerializingError::LimitExceeded));
+}
+
+#[test]
+fn it_shares_the_budget_between_collections() {
+    let mut bytes = serialize_vec(&vec![1, 2]);
+    bytes.extend(serialize_vec(&vec![3, 4]));
+
+    let deserialize_both = |reader: &mut &[u8]| -> Result<(Vec<u64>, Vec<u64>), SerializingError> {
+        Ok((DeserializeWithLength::deserialize::<u16, _>(reader)?, DeserializeWithLength::deserialize::<u16, _>(reader)?))
+    };
+    assert!(with_allocation_budget(32, || deserialize_both(&mut &bytes[..])).is_ok());
+    assert_eq!(with_allocation_budget(24, || deserialize_both(&mut &bytes[..])), Err(SerializingError::LimitExceeded));
+
+    // A nested budget can't exceed what is left of the outer one.
+    let res = with_allocation_budget(24, || {
+        let _: Vec<u64> = DeserializeWithLength::deserialize::<u16, _>(&mut &bytes[..])?;
+        with_allocation_budget(1024, || deserialize_both(&mut &bytes[..]))
+    });
+    assert_eq!(res, Err(SerializingError::LimitExceeded));
+}
@@ -12,11 +12,11 @@ use account::{Account, Inherent, InherentType};
 use account::inherent::AccountInherentInteraction;
 use accounts::Accounts;
 use beserial::Serialize;
-use block::{Block, BlockError, BlockHeader, BlockType, ForkProof, MacroBlock, MacroExtrinsics, MicroBlock, ViewChange, ViewChangeProof, ViewChanges, MacroHeader};
+use block::{Block, BlockError, BlockHeader, BlockType, ForkProof, MacroBlock, MacroExtrinsics, MicroBlock, MicroHeader, MicroJustification, ViewChange, ViewChangeProof, ViewChanges, MacroHeader};
 use blockchain_base::{AbstractBlockchain, BlockchainError, Direction};
 #[cfg(feature = "metrics")]
 use blockchain_base::chain_metrics::BlockchainMetrics;
-use bls::bls12_381::PublicKey;
+use bls::bls12_381::{CompressedSignature, PublicKey};
 use collections::bitset::BitSet;
 use database::{Environment, ReadTransaction, Transaction, WriteTransaction};
 use hash::{Blake2bHash, Hash};
@@ -37,6 +37,7 @@ use vrf::{VrfSeed, VrfUseCase, AliasMethod};
 use crate::chain_info::ChainInfo;
 use crate::chain_store::ChainStore;
 use crate::reward_registry::{EpochStateError, SlashRegistry, SlashedSetSelector};
+use crate::snapshot::{ChainSnapshot, SnapshotAccount, SnapshotError};
 use crate::transaction_cache::TransactionCache;
 
 
@@ -308,6 +309,117 @@ impl Blockchain {
         })
     }
 
+    /// Initializes an empty database from a snapshot and loads the chain from it.
+    ///
+    /// The snapshot must come from a trusted source, e.g. `snapshot` on a node of the caller.
+    /// Its blocks and state are only checked for consistency with its macro block, while the macro
+    /// block itself is not verified against any validators. A forged snapshot can therefore
+    /// contain arbitrary balances and validators, and the chain would follow them.
+    pub fn from_snapshot(env: Environment, snapshot: ChainSnapshot) -> Result<Self, SnapshotError> {
+        let chain_store = Arc::new(ChainStore::new(env.clone()));
+        if chain_store.get_head(None).is_some() {
+            return Err(SnapshotError::DatabaseNotEmpty);
+        }
+
+        let macro_block = snapshot.verify_blocks()?;
+        let state_root = macro_block.header.state_root.clone();
+
+        let network_info = NetworkInfo::from_network_id(snapshot.network_id);
+        let first_block = &snapshot.blocks[0];
+        if first_block.block_number() == 0 && first_block.hash() != *network_info.genesis_hash() {
+            return Err(BlockchainError::InvalidGenesisBlock.into());
+        }
+
+        // Open all stores before starting the write transaction.
+        let accounts = Accounts::new(env.clone());
+        let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store));
+
+        let mut txn = WriteTransaction::new(&env);
+
+        // The genesis block is expected to be on the main chain, even if the snapshot doesn't reach back to it.
+        if first_block.block_number() > 0 {
+            let genesis_info = ChainInfo::initial(network_info.genesis_block::<Block>());
+            chain_store.put_chain_info(&mut txn, network_info.genesis_hash(), &genesis_info, true);
+        }
+
+        let hashes: Vec<Blake2bHash> = snapshot.blocks.iter().map(Block::hash).collect();
+        for (i, block) in snapshot.blocks.into_iter().enumerate() {
+            let mut chain_info = ChainInfo::initial(block);
+            chain_info.main_chain_successor = hashes.get(i + 1).cloned();
+            chain_store.put_chain_info(&mut txn, &hashes[i], &chain_info, true);
+        }
+        let head_hash = hashes.last().unwrap().clone();
+        chain_store.set_head(&mut txn, &head_hash);
+
+        let snapshot_accounts = snapshot.accounts.into_iter()
+            .map(|entry| (entry.address, entry.account))
+            .collect();
+        accounts.init(&mut txn, snapshot_accounts);
+        if accounts.hash(Some(&txn)) != state_root {
+            txn.abort();
+            return Err(SnapshotError::AccountsHashMismatch);
+        }
+
+        slash_registry.restore(&mut txn, &snapshot.registry);
+        txn.commit();
+
+        Ok(Blockchain::load(env, snapshot.network_id, chain_store, head_hash)?)
+    }
+
+    /// Creates a snapshot of the chain at the current macro head.
+    ///
+    /// Micro blocks following the macro head are reverted in a transaction that is never committed.
+    pub fn snapshot(&self) -> Result<ChainSnapshot, SnapshotError> {
+        // Nothing may be pushed while we look at the reverted state.
+        let _push_lock = self.push_lock.lock();
+        let state = self.state.read();
+        let macro_block_number = state.macro_head.header.block_number;
+
+        let mut txn = WriteTransaction::new(&self.env);
+
+        let mut current = self.chain_store.get_block(&state.head_hash, true, Some(&txn))
+            .ok_or_else(|| SnapshotError::MissingBlock(state.main_chain.head.block_number()))?;
+        while let Block::Micro(ref micro_block) = current {
+            let prev_block = self.chain_store.get_block(&micro_block.header.parent_hash, true, Some(&txn))
+                .ok_or_else(|| SnapshotError::MissingBlock(micro_block.header.block_number - 1))?;
+
+            self.revert_accounts(&state.accounts, &mut txn, micro_block, prev_block.next_view_number())
+                .map_err(|_| BlockchainError::InconsistentState)?;
+            state.reward_registry.revert_block(&mut txn, &current)
+                .map_err(|_| BlockchainError::InconsistentState)?;
+
+            current = prev_block;
+        }
+        assert_eq!(current.block_number(), macro_block_number);
+
+        if state.macro_head.header.state_root != state.accounts.hash(Some(&txn)) {
+            return Err(BlockchainError::InconsistentState.into());
+        }
+
+        let accounts = state.accounts.get_all(Some(&txn))
+            .ok_or(BlockchainError::InconsistentState)?
+            .into_iter()
+            .map(|(address, account)| SnapshotAccount { address, account })
+            .collect();
+        let registry = state.reward_registry.snapshot(&txn);
+
+        let mut blocks = Vec::new();
+        for block_number in ChainSnapshot::first_block_number(macro_block_number)..=macro_block_number {
+            let block = self.chain_store.get_block_at(block_number, true, Some(&txn))
+                .ok_or(SnapshotError::MissingBlock(block_number))?;
+            blocks.push(block);
+        }
+
+        txn.abort();
+
+        Ok(ChainSnapshot {
+            network_id: self.network_id,
+            blocks,
+            registry,
+            accounts,
+        })
+    }
+
     fn slots_from_block(block: &MacroBlock) -> Slots {
         block.clone().try_into().unwrap()
     }
@@ -430,6 +542,52 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Verifies the header and justification of a micro block without its body, e.g. of a
+    /// compact block before its missing transactions are requested.
+    pub fn verify_micro_header(&self, header: &MicroHeader, justification: &MicroJustification) -> Result<(), PushError> {
+        let read_txn = ReadTransaction::new(&self.env);
+
+        // Check the predecessor and block number before looking up the slot owner.
+        let prev_info = match self.chain_store.get_chain_info(&header.parent_hash, false, Some(&read_txn)) {
+            Some(prev_info) => prev_info,
+            None => {
+                warn!("Rejecting block - unknown predecessor");
+                return Err(PushError::Orphan);
+            },
+        };
+        if prev_info.head.block_number() + 1 != header.block_number {
+            warn!("Rejecting block - wrong block number ({:?})", header.block_number);
+            return Err(PushError::InvalidSuccessor);
+        }
+
+        let (slot, _) = self.get_slot_at(header.block_number, header.view_number, Some(&read_txn))
+            .ok_or(PushError::InvalidSuccessor)?;
+        let intended_slot_owner = slot.public_key().uncompress_unchecked();
+
+        let block_header = BlockHeader::Micro(header.clone());
+        self.verify_block_header(&block_header, justification.view_change_proof.as_ref().into(), &intended_slot_owner, Some(&read_txn))?;
+        Self::verify_micro_justification(header, &justification.signature, &intended_slot_owner)
+    }
+
+    fn verify_micro_justification(header: &MicroHeader, signature: &CompressedSignature, intended_slot_owner: &PublicKey) -> Result<(), PushError> {
+        let justification = match signature.uncompress() {
+            Ok(justification) => justification,
+            Err(_) => {
+                warn!("Rejecting block - bad justification");
+                return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
+            }
+        };
+
+        if !intended_slot_owner.verify(header, &justification) {
+            warn!("Rejecting block - invalid justification for intended slot owner");
+            debug!("Block hash: {}", header.hash::<Blake2bHash>());
+            debug!("Intended slot owner: {:?}", intended_slot_owner.compress());
+            return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
+        }
+
+        Ok(())
+    }
+
     pub fn push(&self, block: Block) -> Result<PushResult, PushError> {
         self.push_block(block, false)
     }
@@ -576,21 +734,8 @@ impl Blockchain {
         }
 
         if let Block::Micro(ref micro_block) = block {
-            let justification = match micro_block.justification.signature.uncompress() {
-                Ok(justification) => justification,
-                Err(_) => {
-                    warn!("Rejecting block - bad justification");
-                    return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
-                }
-            };
-
             let intended_slot_owner = slot.public_key().uncompress_unchecked();
-            if !intended_slot_owner.verify(&micro_block.header, &justification) {
-                warn!("Rejecting block - invalid justification for intended slot owner");
-                debug!("Block hash: {}", micro_block.header.hash::<Blake2bHash>());
-                debug!("Intended slot owner: {:?}", intended_slot_owner.compress());
-                return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
-            }
+            Self::verify_micro_justification(&micro_block.header, &micro_block.justification.signature, &intended_slot_owner)?;
 
             // Validate slash inherents
             for fork_proof in &micro_block.extrinsics.as_ref().unwrap().fork_proofs {
@@ -1201,7 +1346,22 @@ impl Blockchain {
         self.chain_store.get_blocks(start_block_hash, count, include_body, direction, None)
     }
 
+    /// Whether `block` was loaded with its body, but only its header is left because the body
+    /// has been pruned. Only micro block bodies are pruned.
+    fn is_pruned(block: &Block) -> bool {
+        match block {
+            Block::Micro(ref micro_block) => micro_block.extrinsics.is_none(),
+            Block::Macro(_) => false,
+        }
+    }
+
     pub fn get_epoch_transactions(&self, epoch: u32, txn_option: Option<&Transaction>) -> Option<TransactionsIterator> {
+        // The micro block bodies of this epoch might have been pruned.
+        if let Some(transactions) = self.chain_store.get_epoch_transactions(epoch, txn_option) {
+            let no_blocks: Flatten<Map<IntoIter<Block>, fn(Block) -> Vec<BlockchainTransaction>>> = Vec::new().into_iter().map(Block::unwrap_transactions as fn(_) -> _).flatten();
+            return Some(transactions.into_iter().chain(no_blocks));
+        }
+
         let first_block = policy::first_block_of(epoch);
         let first_block = self.chain_store.get_block_at(first_block, true, txn_option)
             .or_else(|| {
@@ -1631,10 +1791,12 @@ impl AbstractBlockchain for Blockchain {
 
     fn get_block(&self, hash: &Blake2bHash, include_body: bool) -> Option<Self::Block> {
         self.get_block(hash, false, include_body)
+            .filter(|block| !include_body || !Self::is_pruned(block))
     }
 
     fn get_block_at(&self, height: u32, include_body: bool) -> Option<Self::Block> {
         self.get_block_at(height, include_body)
+            .filter(|block| !include_body || !Self::is_pruned(block))
     }
 
     fn get_block_locators(&self, max_count: usize) -> Vec<Blake2bHash> {
@@ -1689,7 +1851,17 @@ impl AbstractBlockchain for Blockchain {
     }
 
     fn get_blocks(&self, start_block_hash: &Blake2bHash, count: u32, include_body: bool, direction: Direction) -> Vec<Self::Block> {
-        self.get_blocks(start_block_hash, count, include_body, direction)
+        let mut blocks = self.get_blocks(start_block_hash, count, include_body, direction);
+        if include_body {
+            if let Some(pruned) = blocks.iter().position(Self::is_pruned) {
+                blocks.truncate(pruned);
+            }
+        }
+        blocks
+    }
+
+    fn has_block_body(&self, hash: &Blake2bHash) -> bool {
+        self.chain_store.has_block_body(hash, None)
     }
 
     fn push(&self, block: Self::Block) -> Result<PushResult, PushError> {
@@ -1700,9 +1872,14 @@ impl AbstractBlockchain for Blockchain {
         self.contains(hash, include_forks)
     }
 
-    #[allow(unused_variables)]
     fn get_accounts_proof(&self, block_hash: &Blake2bHash, addresses: &[Address]) -> Option<AccountsProof<Account>> {
-        unimplemented!()
+        let state = self.state.read();
+        // We only support accounts proofs for the head hash.
+        if block_hash != &state.head_hash {
+            return None;
+        }
+        let txn = ReadTransaction::new(&self.env);
+        Some(state.accounts.get_accounts_proof(&txn, addresses))
     }
 
     #[allow(unused_variables)]
@@ -1,11 +1,16 @@
+use std::borrow::Cow;
+use std::io;
+
 use account::Receipts;
+use beserial::{Deserialize, Serialize};
 use block::Block;
 use blockchain_base::Direction;
-use database::{Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction};
+use database::{AsDatabaseBytes, Database, DatabaseFlags, Environment, FromDatabaseValue, ReadTransaction, Transaction, WriteTransaction};
 use database::cursor::ReadCursor;
 use database::cursor::WriteCursor;
 use hash::Blake2bHash;
 use primitives::policy;
+use transaction::Transaction as BlockchainTransaction;
 
 use crate::chain_info::ChainInfo;
 
@@ -16,6 +21,7 @@ pub struct ChainStore {
     block_db: Database,
     height_idx: Database,
     receipt_db: Database,
+    epoch_transactions_db: Database,
 }
 
 impl ChainStore {
@@ -23,8 +29,15 @@ impl ChainStore {
     const BLOCK_DB_NAME: &'static str = "Block";
     const HEIGHT_IDX_NAME: &'static str = "HeightIdx";
     const RECEIPT_DB_NAME: &'static str = "Receipts";
+    const EPOCH_TRANSACTIONS_DB_NAME: &'static str = "EpochTransactions";
 
     const HEAD_KEY: &'static str = "head";
+    const PRUNED_FORKS_KEY: &'static str = "prunedForks";
+    const PRUNED_BODIES_KEY: &'static str = "prunedBodies";
+
+    /// Name and version of the serialized format of blocks, chain infos and receipts.
+    pub const SCHEMA_NAME: &'static str = "ChainStore";
+    pub const SCHEMA_VERSION: u32 = 2;
 
     pub fn new(env: Environment) -> Self {
         let chain_db = env.open_database(Self::CHAIN_DB_NAME.to_string());
@@ -33,7 +46,16 @@ impl ChainStore {
                                                       DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES);
         let receipt_db = env.open_database_with_flags(Self::RECEIPT_DB_NAME.to_string(),
                                                       DatabaseFlags::UINT_KEYS);
-        ChainStore { env, chain_db, block_db, height_idx, receipt_db }
+        let epoch_transactions_db = env.open_database_with_flags(Self::EPOCH_TRANSACTIONS_DB_NAME.to_string(),
+                                                                 DatabaseFlags::UINT_KEYS);
+        ChainStore { env, chain_db, block_db, height_idx, receipt_db, epoch_transactions_db }
+    }
+
+    /// Whether the store in `env` has never been written to, i.e. has no head yet.
+    pub fn is_new(env: &Environment) -> bool {
+        let chain_db = env.open_database(Self::CHAIN_DB_NAME.to_string());
+        let txn = ReadTransaction::new(env);
+        txn.get::<str, Blake2bHash>(&chain_db, ChainStore::HEAD_KEY).is_none()
     }
 
     pub fn get_head(&self, txn_option: Option<&Transaction>) -> Option<Blake2bHash> {
@@ -66,7 +88,7 @@ impl ChainStore {
             if let Some(block) = txn.get(&self.block_db, hash) {
                 chain_info.head = block;
             } else {
-                warn!("Block body requested but not present");
+                debug!("Block body requested but not present, it might have been pruned");
             }
         }
 
@@ -131,13 +153,34 @@ impl ChainStore {
             if let Some(block) = txn.get(&self.block_db, &block_hash) {
                 chain_info.head = block;
             } else {
-                warn!("Block body requested but not present");
+                debug!("Block body requested but not present, it might have been pruned");
             }
         }
 
         Some(chain_info)
     }
 
+    /// Returns the hashes of all blocks indexed at the given height, including forks.
+    pub fn get_hashes_at(&self, block_height: u32, txn_option: Option<&Transaction>) -> Vec<Blake2bHash> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut cursor = txn.cursor(&self.height_idx);
+        let mut hashes = Vec::new();
+        let mut hash = cursor.seek_key::<u32, Blake2bHash>(&block_height);
+        while let Some(block_hash) = hash {
+            hashes.push(block_hash);
+            hash = cursor.next_duplicate::<u32, Blake2bHash>().map(|(_, hash)| hash);
+        }
+        hashes
+    }
+
     pub fn get_block(&self, hash: &Blake2bHash, include_body: bool, txn_option: Option<&Transaction>) -> Option<Block> {
         let read_txn: ReadTransaction;
         let txn = match txn_option {
@@ -328,4 +371,145 @@ impl ChainStore {
             pos = cursor.next();
         }
     }
+
+    /// Returns the height up to which fork blocks have been pruned.
+    pub fn get_pruned_forks_height(&self, txn_option: Option<&Transaction>) -> u32 {
+        let height: Option<u32> = match txn_option {
+            Some(txn) => txn.get(&self.chain_db, ChainStore::PRUNED_FORKS_KEY),
+            None => ReadTransaction::new(&self.env).get(&self.chain_db, ChainStore::PRUNED_FORKS_KEY),
+        };
+        height.unwrap_or(0)
+    }
+
+    /// Removes all blocks up to `block_height` that are not on the main chain, as well as height
+    /// index entries that refer to blocks which are not stored. Returns the number of removed
+    /// entries.
+    pub fn prune_forks(&self, txn: &mut WriteTransaction, block_height: u32) -> usize {
+        let pruned_height = self.get_pruned_forks_height(Some(&txn));
+        let mut pruned = 0;
+        for height in pruned_height + 1..=block_height {
+            for hash in self.get_hashes_at(height, Some(&txn)) {
+                match self.get_chain_info(&hash, false, Some(&txn)) {
+                    Some(ref chain_info) if chain_info.on_main_chain => continue,
+                    Some(_) => self.remove_chain_info(txn, &hash, height),
+                    None => txn.remove_item(&self.height_idx, &height, &hash),
+                }
+                pruned += 1;
+            }
+        }
+        if block_height > pruned_height {
+            txn.put(&self.chain_db, ChainStore::PRUNED_FORKS_KEY, &block_height);
+        }
+        pruned
+    }
+
+    /// Returns the last epoch that pruning of micro block bodies got to. Epochs that weren't
+    /// stored completely are passed over, see `has_pruned_bodies` for whether an epoch was pruned.
+    pub fn get_pruned_bodies_epoch(&self, txn_option: Option<&Transaction>) -> u32 {
+        let epoch: Option<u32> = match txn_option {
+            Some(txn) => txn.get(&self.chain_db, ChainStore::PRUNED_BODIES_KEY),
+            None => ReadTransaction::new(&self.env).get(&self.chain_db, ChainStore::PRUNED_BODIES_KEY),
+        };
+        epoch.unwrap_or(0)
+    }
+
+    /// Moves the transactions of the micro blocks of `epoch` into the epoch transactions store and
+    /// removes the micro block bodies. The headers stay in the chain store.
+    ///
+    /// Epochs that are not stored completely, e.g. because they were synced as macro blocks only,
+    /// are left untouched. Returns whether the bodies were pruned.
+    pub fn prune_micro_block_bodies(&self, txn: &mut WriteTransaction, epoch: u32) -> bool {
+        let mut hashes = Vec::new();
+        let mut block_sizes = Vec::new();
+        let mut transactions = Vec::new();
+        for block_number in policy::first_block_of(epoch)..policy::macro_block_of(epoch) {
+            let hash = match self.get_chain_info_at(block_number, false, Some(&txn)) {
+                Some(chain_info) => chain_info.head.hash(),
+                None => break,
+            };
+            match self.get_block(&hash, true, Some(&txn)) {
+                Some(Block::Micro(micro_block)) => {
+                    let mut block_transactions = match micro_block.extrinsics {
+                        Some(extrinsics) => extrinsics.transactions,
+                        None => break,
+                    };
+                    block_sizes.push(block_transactions.len() as u16);
+                    transactions.append(&mut block_transactions);
+                },
+                _ => break,
+            }
+            hashes.push(hash);
+        }
+
+        let pruned = hashes.len() as u32 == policy::EPOCH_LENGTH - 1;
+        if pruned {
+            txn.put(&self.epoch_transactions_db, &epoch, &EpochTransactions { block_sizes, transactions });
+            for hash in &hashes {
+                txn.remove(&self.block_db, hash);
+            }
+        }
+        txn.put(&self.chain_db, ChainStore::PRUNED_BODIES_KEY, &epoch);
+        pruned
+    }
+
+    /// Returns whether the micro block bodies of `epoch` have been moved to the epoch transactions store.
+    pub fn has_pruned_bodies(&self, epoch: u32, txn_option: Option<&Transaction>) -> bool {
+        self.get_epoch_transactions(epoch, txn_option).is_some()
+    }
+
+    /// Returns the transactions of an epoch whose micro block bodies have been pruned.
+    pub fn get_epoch_transactions(&self, epoch: u32, txn_option: Option<&Transaction>) -> Option<Vec<BlockchainTransaction>> {
+        let epoch_transactions: Option<EpochTransactions> = match txn_option {
+            Some(txn) => txn.get(&self.epoch_transactions_db, &epoch),
+            None => ReadTransaction::new(&self.env).get(&self.epoch_transactions_db, &epoch),
+        };
+        epoch_transactions.map(|epoch_transactions| epoch_transactions.transactions)
+    }
+
+    /// Returns the transactions of a micro block whose body has been pruned.
+    pub fn get_pruned_transactions(&self, block_number: u32, txn_option: Option<&Transaction>) -> Option<Vec<BlockchainTransaction>> {
+        let epoch = policy::epoch_at(block_number);
+        let epoch_transactions: EpochTransactions = match txn_option {
+            Some(txn) => txn.get(&self.epoch_transactions_db, &epoch),
+            None => ReadTransaction::new(&self.env).get(&self.epoch_transactions_db, &epoch),
+        }?;
+
+        let index = policy::epoch_index_at(block_number) as usize;
+        let size = *epoch_transactions.block_sizes.get(index)? as usize;
+        let start = epoch_transactions.block_sizes[..index].iter().map(|size| *size as usize).sum::<usize>();
+        epoch_transactions.transactions.get(start..start + size).map(<[BlockchainTransaction]>::to_vec)
+    }
+
+    /// Returns whether the body of a block is stored, i.e. it is known and has not been pruned.
+    pub fn has_block_body(&self, hash: &Blake2bHash, txn_option: Option<&Transaction>) -> bool {
+        let block: Option<Block> = match txn_option {
+            Some(txn) => txn.get(&self.block_db, hash),
+            None => ReadTransaction::new(&self.env).get(&self.block_db, hash),
+        };
+        block.is_some()
+    }
+}
+
+/// The transactions of all micro blocks of an epoch, in block order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct EpochTransactions {
+    /// The number of transactions of each micro block.
+    #[beserial(len_type(u16))]
+    block_sizes: Vec<u16>,
+    #[beserial(len_type(u32))]
+    transactions: Vec<BlockchainTransaction>,
+}
+
+impl AsDatabaseBytes for EpochTransactions {
+    fn as_database_bytes(&self) -> Cow<[u8]> {
+        let v = Serialize::serialize_to_vec(&self);
+        Cow::Owned(v)
+    }
+}
+
+impl FromDatabaseValue for EpochTransactions {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
 }
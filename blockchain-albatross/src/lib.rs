@@ -22,7 +22,11 @@ extern crate nimiq_vrf as vrf;
 pub mod blockchain;
 pub mod chain_info;
 pub mod chain_store;
+pub mod pruning;
 pub mod reward_registry;
+pub mod schema;
+pub mod snapshot;
 pub mod transaction_cache;
+pub mod verify;
 
 pub use blockchain::Blockchain;
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use database::WriteTransaction;
+use primitives::policy;
+use utils::observer::{ListenerHandle, weak_listener};
+
+use crate::blockchain::{Blockchain, BlockchainEvent};
+use crate::snapshot::ChainSnapshot;
+
+/// Determines which data is removed from the chain store once a macro block is finalized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruningPolicy {
+    /// Remove blocks up to the last macro block that are not on the main chain. Macro blocks are
+    /// final, so these forks can never become the main chain again.
+    pub prune_forks: bool,
+
+    /// Only keep the micro block bodies of this many epochs before the last macro block. The
+    /// transactions of older epochs are kept, so they can still be served to peers that sync
+    /// macro blocks, but micro blocks of those epochs are only available as headers.
+    ///
+    /// Bodies within the transaction validity window are never pruned. `None` keeps all bodies.
+    pub keep_micro_block_bodies: Option<u32>,
+}
+
+impl Default for PruningPolicy {
+    fn default() -> Self {
+        PruningPolicy {
+            prune_forks: true,
+            keep_micro_block_bodies: None,
+        }
+    }
+}
+
+impl PruningPolicy {
+    /// Prunes the chain store of `blockchain` every time it finalizes a macro block.
+    pub fn register(self, blockchain: &Arc<Blockchain>) -> ListenerHandle {
+        blockchain.notifier.write().register(weak_listener(
+            Arc::downgrade(blockchain),
+            move |blockchain, event: &BlockchainEvent| {
+                if let BlockchainEvent::Finalized(_) = event {
+                    self.prune(&blockchain);
+                }
+            }))
+    }
+
+    /// Prunes everything this policy allows at the current macro head.
+    pub fn prune(&self, blockchain: &Blockchain) {
+        let macro_block_number = blockchain.macro_head().header.block_number;
+        let chain_store = &blockchain.chain_store;
+        let mut txn = WriteTransaction::new(&blockchain.env);
+
+        if self.prune_forks {
+            let pruned = chain_store.prune_forks(&mut txn, macro_block_number);
+            if pruned > 0 {
+                debug!("Pruned {} fork blocks up to #{}", pruned, macro_block_number);
+            }
+        }
+
+        if let Some(keep_epochs) = self.keep_micro_block_bodies {
+            let last_epoch = Self::last_prunable_epoch(macro_block_number, keep_epochs);
+            for epoch in chain_store.get_pruned_bodies_epoch(Some(&txn)) + 1..=last_epoch {
+                if chain_store.prune_micro_block_bodies(&mut txn, epoch) {
+                    debug!("Pruned micro block bodies of epoch {}", epoch);
+                }
+            }
+        }
+
+        txn.commit();
+    }
+
+    /// Returns the last epoch whose micro block bodies may be pruned at the given macro block.
+    fn last_prunable_epoch(macro_block_number: u32, keep_epochs: u32) -> u32 {
+        // The blocks `Blockchain::load` and `Blockchain::snapshot` need must keep their bodies.
+        let window_epoch = ChainSnapshot::first_block_number(macro_block_number) / policy::EPOCH_LENGTH;
+        policy::epoch_at(macro_block_number).saturating_sub(keep_epochs).min(window_epoch)
+    }
+}
@@ -2,6 +2,7 @@ mod reward_pot;
 
 
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::io;
 use std::sync::Arc;
 
@@ -21,6 +22,7 @@ use vrf::VrfUseCase;
 
 use crate::chain_store::ChainStore;
 use crate::reward_registry::reward_pot::RewardPot;
+use crate::verify::Inconsistency;
 use vrf::rng::Rng;
 
 
@@ -66,11 +68,30 @@ struct BlockDescriptor {
     prev_epoch_state: BitSet,
 }
 
+/// The slash and reward state of the registry, as stored in a chain snapshot.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RegistrySnapshot {
+    current_reward_pot: u64,
+    previous_reward_pot: u64,
+    #[beserial(len_type(u32))]
+    descriptors: Vec<RegistryEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RegistryEntry {
+    block_number: u32,
+    descriptor: BlockDescriptor,
+}
+
 // TODO Pass in active validator set + seed through parameters
 //      or always load from chain store?
 impl SlashRegistry {
     const SLASH_REGISTRY_DB_NAME: &'static str = "SlashRegistry";
 
+    /// Name and version of the serialized format of block descriptors.
+    pub const SCHEMA_NAME: &'static str = "SlashRegistry";
+    pub const SCHEMA_VERSION: u32 = 1;
+
     pub fn new(env: Environment, chain_store: Arc<ChainStore>) -> Self {
         let slash_registry_db = env.open_database_with_flags(SlashRegistry::SLASH_REGISTRY_DB_NAME.to_string(), DatabaseFlags::UINT_KEYS);
         let reward_pot = RewardPot::new(env.clone());
@@ -293,6 +314,66 @@ impl SlashRegistry {
         Ok(())
     }
 
+    /// Captures the registry state as seen by `txn`.
+    pub(crate) fn snapshot(&self, txn: &Transaction) -> RegistrySnapshot {
+        let (current_reward_pot, previous_reward_pot) = self.reward_pot.get(txn);
+
+        let mut descriptors = Vec::new();
+        let mut cursor = txn.cursor(&self.slash_registry_db);
+        let mut pos: Option<(u32, BlockDescriptor)> = cursor.first();
+        while let Some((block_number, descriptor)) = pos {
+            descriptors.push(RegistryEntry { block_number, descriptor });
+            pos = cursor.next();
+        }
+
+        RegistrySnapshot { current_reward_pot, previous_reward_pot, descriptors }
+    }
+
+    /// Writes the state of a snapshot into an empty registry.
+    pub(crate) fn restore(&self, txn: &mut WriteTransaction, snapshot: &RegistrySnapshot) {
+        self.reward_pot.restore(snapshot.current_reward_pot, snapshot.previous_reward_pot, txn);
+        for entry in &snapshot.descriptors {
+            txn.put(&self.slash_registry_db, &entry.block_number, &entry.descriptor);
+        }
+    }
+
+    /// Checks that there is a valid descriptor for every main chain block in the registry window
+    /// and no descriptor for any other block.
+    pub(crate) fn verify(&self, txn: &Transaction, head_block_number: u32) -> Vec<Inconsistency> {
+        let mut inconsistencies = Vec::new();
+        // Descriptors are garbage collected when a macro block is committed.
+        let cutoff = policy::first_block_of_registry(policy::epoch_at(policy::last_macro_block(head_block_number)));
+
+        let mut block_numbers = HashSet::new();
+        let mut cursor = txn.cursor(&self.slash_registry_db);
+        let mut pos: Option<(u32, BlockDescriptor)> = cursor.first();
+        while let Some((block_number, descriptor)) = pos {
+            block_numbers.insert(block_number);
+
+            if block_number < cutoff || block_number > head_block_number
+                || self.chain_store.get_chain_info_at(block_number, false, Some(txn)).is_none() {
+                inconsistencies.push(Inconsistency::StaleSlashDescriptor(block_number));
+            } else if descriptor.view_change_epoch_state.iter()
+                .chain(descriptor.fork_proof_epoch_state.iter())
+                .chain(descriptor.prev_epoch_state.iter())
+                .any(|slot_number| slot_number >= policy::SLOTS as usize) {
+                inconsistencies.push(Inconsistency::InvalidSlashDescriptor(block_number));
+            }
+
+            pos = cursor.next();
+        }
+
+        // The genesis block is never committed to the registry.
+        for block_number in cutoff.max(1)..=head_block_number {
+            if !block_numbers.contains(&block_number)
+                && self.chain_store.get_chain_info_at(block_number, false, Some(txn)).is_some() {
+                inconsistencies.push(Inconsistency::MissingSlashDescriptor(block_number));
+            }
+        }
+
+        inconsistencies
+    }
+
     /// Get slot and slot number for a given block and view number
     pub fn get_slot_at(&self, block_number: u32, view_number: u32, slots: &Slots, txn_option: Option<&Transaction>) -> Option<(Slot, u16)> {
         let slot_number = self.get_slot_number_at(block_number, view_number, txn_option)?;
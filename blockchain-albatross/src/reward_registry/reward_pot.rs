@@ -1,5 +1,5 @@
 use block::{MacroBlock, MicroBlock};
-use database::{Database, Environment, ReadTransaction, WriteTransaction};
+use database::{Database, Environment, ReadTransaction, Transaction, WriteTransaction};
 use primitives::coin::Coin;
 use primitives::policy;
 use transaction::Transaction as BlockchainTransaction;
@@ -74,6 +74,17 @@ impl RewardPot {
         txn.put(&self.reward_pot, Self::CURRENT_EPOCH_KEY, &u64::from(reward));
     }
 
+    /// Returns the current and previous reward pot in Luna.
+    pub(super) fn get(&self, txn: &Transaction) -> (u64, u64) {
+        (txn.get(&self.reward_pot, Self::CURRENT_EPOCH_KEY).unwrap_or(0),
+         txn.get(&self.reward_pot, Self::PREVIOUS_EPOCH_KEY).unwrap_or(0))
+    }
+
+    pub(super) fn restore(&self, current: u64, previous: u64, txn: &mut WriteTransaction) {
+        txn.put(&self.reward_pot, Self::CURRENT_EPOCH_KEY, &current);
+        txn.put(&self.reward_pot, Self::PREVIOUS_EPOCH_KEY, &previous);
+    }
+
     fn reward_for_micro_block(block: &MicroBlock) -> Coin {
         // Block reward
         let mut reward = policy::block_reward_at(block.header.block_number);
@@ -0,0 +1,90 @@
+use failure::Fail;
+
+use account::Account;
+use beserial::{Deserialize, Serialize};
+use block::{Block, MacroBlock};
+use blockchain_base::BlockchainError;
+use keys::Address;
+use primitives::networks::NetworkId;
+use primitives::policy;
+
+use crate::reward_registry::RegistrySnapshot;
+
+/// The state of the chain at a macro block, from which a node can start without the blocks
+/// preceding it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSnapshot {
+    pub network_id: NetworkId,
+    /// Main chain blocks with bodies, oldest first, ending with the macro block the snapshot was
+    /// taken at. They cover the transaction validity window and the previous macro block.
+    #[beserial(len_type(u32))]
+    pub blocks: Vec<Block>,
+    pub registry: RegistrySnapshot,
+    /// The accounts at the macro block, ordered by address.
+    #[beserial(len_type(u32))]
+    pub accounts: Vec<SnapshotAccount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotAccount {
+    pub address: Address,
+    pub account: Account,
+}
+
+#[derive(Debug, Fail)]
+pub enum SnapshotError {
+    #[fail(display = "The database already contains a chain")]
+    DatabaseNotEmpty,
+    #[fail(display = "The snapshot does not end with a macro block")]
+    NoMacroBlock,
+    #[fail(display = "The snapshot blocks do not form a chain")]
+    BrokenChain,
+    #[fail(display = "Block #{} is missing from the database", _0)]
+    MissingBlock(u32),
+    #[fail(display = "The snapshot does not cover the transaction validity window")]
+    MissingBlocks,
+    #[fail(display = "The snapshot accounts do not match the state root of the macro block")]
+    AccountsHashMismatch,
+    #[fail(display = "{}", _0)]
+    Blockchain(#[cause] BlockchainError),
+}
+
+impl From<BlockchainError> for SnapshotError {
+    fn from(e: BlockchainError) -> Self {
+        SnapshotError::Blockchain(e)
+    }
+}
+
+impl ChainSnapshot {
+    /// Returns the number of the first block a snapshot at `macro_block_number` must contain.
+    pub fn first_block_number(macro_block_number: u32) -> u32 {
+        let depth = policy::EPOCH_LENGTH.max(policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS - 1);
+        macro_block_number.saturating_sub(depth)
+    }
+
+    /// The macro block the snapshot was taken at.
+    pub fn macro_block(&self) -> Option<&MacroBlock> {
+        match self.blocks.last() {
+            Some(Block::Macro(ref macro_block)) => Some(macro_block),
+            _ => None,
+        }
+    }
+
+    /// Checks that the blocks form a chain that ends with a macro block and covers everything
+    /// `Blockchain::load` needs.
+    pub fn verify_blocks(&self) -> Result<&MacroBlock, SnapshotError> {
+        let macro_block = self.macro_block().ok_or(SnapshotError::NoMacroBlock)?;
+
+        for pair in self.blocks.windows(2) {
+            if pair[1].block_number() != pair[0].block_number() + 1 || *pair[1].parent_hash() != pair[0].hash() {
+                return Err(SnapshotError::BrokenChain);
+            }
+        }
+
+        if self.blocks[0].block_number() > Self::first_block_number(macro_block.header.block_number) {
+            return Err(SnapshotError::MissingBlocks);
+        }
+
+        Ok(macro_block)
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use failure::Fail;
+
+use accounts::Accounts;
+use block::Block;
+use database::{Environment, ReadTransaction, WriteTransaction};
+use hash::{Blake2bHash, Hash};
+use network_primitives::networks::NetworkInfo;
+use primitives::networks::NetworkId;
+use primitives::policy;
+use utils::merkle;
+
+use crate::chain_store::ChainStore;
+use crate::reward_registry::SlashRegistry;
+
+/// An inconsistency in the on-disk state of the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Fail)]
+pub enum Inconsistency {
+    #[fail(display = "No head is stored")]
+    MissingHead,
+    #[fail(display = "The main chain ends in an unknown genesis block {}", _0)]
+    WrongGenesis(Blake2bHash),
+    #[fail(display = "Block {} is referenced by the main chain but not stored", _0)]
+    MissingChainInfo(Blake2bHash),
+    #[fail(display = "Block {} has number #{}, but its successor expects #{}", hash, block_number, expected)]
+    WrongBlockNumber { hash: Blake2bHash, block_number: u32, expected: u32 },
+    #[fail(display = "Main chain block #{} ({}) is not marked as such", _0, _1)]
+    NotOnMainChain(u32, Blake2bHash),
+    #[fail(display = "Main chain block #{} ({}) has the wrong successor", _0, _1)]
+    WrongSuccessor(u32, Blake2bHash),
+    #[fail(display = "The body of block #{} ({}) is missing", _0, _1)]
+    MissingBody(u32, Blake2bHash),
+    #[fail(display = "Block #{} ({}) is missing from the height index", _0, _1)]
+    MissingHeightIndex(u32, Blake2bHash),
+    #[fail(display = "Accounts hash {} does not match the state root {} of the head", actual, expected)]
+    AccountsHashMismatch { expected: Blake2bHash, actual: Blake2bHash },
+    #[fail(display = "The transactions of the epoch do not match the transactions root of macro block #{}", _0)]
+    TransactionsRootMismatch(u32),
+    #[fail(display = "Slash registry descriptor for block #{} is missing", _0)]
+    MissingSlashDescriptor(u32),
+    #[fail(display = "Slash registry descriptor for block #{} does not belong to the main chain or registry window", _0)]
+    StaleSlashDescriptor(u32),
+    #[fail(display = "Slash registry descriptor for block #{} contains invalid slots", _0)]
+    InvalidSlashDescriptor(u32),
+    #[fail(display = "Height index entry #{} refers to block {} that is not stored", _0, _1)]
+    DanglingIndexEntry(u32, Blake2bHash),
+    #[fail(display = "Fork block #{} ({}) can no longer become part of the main chain", _0, _1)]
+    DanglingFork(u32, Blake2bHash),
+    #[fail(display = "Fork block #{} ({}) is marked as part of the main chain", _0, _1)]
+    ConflictingMainChain(u32, Blake2bHash),
+}
+
+impl Inconsistency {
+    /// Whether `verify` can fix this inconsistency when asked to repair the database.
+    pub fn is_repairable(&self) -> bool {
+        match self {
+            Inconsistency::DanglingIndexEntry(..)
+            | Inconsistency::DanglingFork(..)
+            | Inconsistency::ConflictingMainChain(..) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Checks the chain store, accounts tree and slash registry of a stopped node for consistency.
+///
+/// This walks the main chain back from the head and checks parent links, `ChainInfo` fields and
+/// the height index, the accounts hash against the head's state root and the transactions root
+/// of every fully stored or pruned epoch. Fork entries are reported if they refer to missing
+/// blocks, are behind the last macro block or claim to be on the main chain. If `repair` is set,
+/// those fork entries are removed or corrected.
+pub fn verify(env: &Environment, network_id: NetworkId, repair: bool) -> Vec<Inconsistency> {
+    // Open all stores before starting the read transaction.
+    let chain_store = Arc::new(ChainStore::new(env.clone()));
+    let accounts = Accounts::new(env.clone());
+    let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store));
+
+    let mut inconsistencies = Vec::new();
+    let txn = ReadTransaction::new(env);
+
+    let head_hash = match chain_store.get_head(Some(&txn)) {
+        Some(head_hash) => head_hash,
+        None => return vec![Inconsistency::MissingHead],
+    };
+
+    // Walk the main chain backwards and remember the hash at each height.
+    let genesis_hash = NetworkInfo::from_network_id(network_id).genesis_hash().clone();
+    let mut main_chain: Vec<(u32, Blake2bHash)> = Vec::new();
+    let mut hash = head_hash;
+    let mut successor: Option<Blake2bHash> = None;
+    let mut check_successor = true;
+    let mut expected_number: Option<u32> = None;
+    let mut head_state_root = None;
+    loop {
+        let chain_info = match chain_store.get_chain_info(&hash, false, Some(&txn)) {
+            Some(chain_info) => chain_info,
+            None => {
+                inconsistencies.push(Inconsistency::MissingChainInfo(hash));
+                break;
+            },
+        };
+        let block_number = chain_info.head.block_number();
+        if head_state_root.is_none() {
+            head_state_root = Some(chain_info.head.state_root().clone());
+        }
+
+        if let Some(expected) = expected_number {
+            if block_number != expected {
+                inconsistencies.push(Inconsistency::WrongBlockNumber { hash: hash.clone(), block_number, expected });
+                // Don't follow parent links that might lead in a circle.
+                if block_number > expected {
+                    break;
+                }
+            }
+        }
+        if !chain_info.on_main_chain {
+            inconsistencies.push(Inconsistency::NotOnMainChain(block_number, hash.clone()));
+        }
+        if check_successor && chain_info.main_chain_successor != successor {
+            inconsistencies.push(Inconsistency::WrongSuccessor(block_number, hash.clone()));
+        }
+        main_chain.push((block_number, hash.clone()));
+
+        if block_number == 0 {
+            if hash != genesis_hash {
+                inconsistencies.push(Inconsistency::WrongGenesis(hash));
+            }
+            break;
+        }
+
+        let parent_hash = chain_info.head.parent_hash().clone();
+        let parent_missing = chain_store.get_chain_info(&parent_hash, false, Some(&txn)).is_none();
+        match chain_info.head {
+            // Macro block sync doesn't store the micro blocks of an epoch.
+            // The successor of the previous macro block is not tracked in that case.
+            Block::Macro(ref macro_block) if parent_missing => {
+                successor = None;
+                check_successor = false;
+                hash = macro_block.header.parent_macro_hash.clone();
+                expected_number = Some(policy::last_macro_block(block_number - 1));
+            },
+            _ => {
+                successor = Some(hash);
+                check_successor = true;
+                hash = parent_hash;
+                expected_number = Some(block_number - 1);
+            },
+        }
+    }
+    main_chain.reverse();
+
+    if let Some(state_root) = head_state_root {
+        let accounts_hash = accounts.hash(Some(&txn));
+        if accounts_hash != state_root {
+            inconsistencies.push(Inconsistency::AccountsHashMismatch { expected: state_root, actual: accounts_hash });
+        }
+    }
+
+    let head_number = main_chain.last().map(|(block_number, _)| *block_number).unwrap_or(0);
+    let macro_head_number = policy::last_macro_block(head_number);
+
+    // Walk the main chain forwards to check bodies, the height index, transactions roots and forks.
+    let mut repairs = Vec::new();
+    let mut epoch_hashes: Option<Vec<Blake2bHash>> = None;
+    // Whether the micro block bodies of the last epoch we looked at were pruned.
+    let mut pruned_bodies: Option<(u32, bool)> = None;
+    let mut prev_number: Option<u32> = None;
+    for (block_number, hash) in &main_chain {
+        let block_number = *block_number;
+        let block = chain_store.get_block(hash, true, Some(&txn));
+        if prev_number.map(|n| n + 1) != Some(block_number) {
+            epoch_hashes = None;
+        }
+        prev_number = Some(block_number);
+
+        match block {
+            Some(Block::Micro(ref micro_block)) => match micro_block.extrinsics {
+                Some(ref extrinsics) => if let Some(ref mut hashes) = epoch_hashes {
+                    hashes.extend(extrinsics.transactions.iter().map(|tx| tx.hash::<Blake2bHash>()));
+                },
+                None => epoch_hashes = None,
+            },
+            Some(Block::Macro(ref macro_block)) => {
+                let epoch = policy::epoch_at(block_number);
+                if block_number > 0 {
+                    if let Some(transactions) = chain_store.get_epoch_transactions(epoch, Some(&txn)) {
+                        epoch_hashes = Some(transactions.iter().map(|tx| tx.hash::<Blake2bHash>()).collect());
+                    }
+                }
+                if let Some(ref hashes) = epoch_hashes {
+                    let transactions_root = merkle::compute_root_from_hashes::<Blake2bHash>(hashes);
+                    if block_number > 0 && transactions_root != macro_block.header.transactions_root {
+                        inconsistencies.push(Inconsistency::TransactionsRootMismatch(block_number));
+                    }
+                }
+                epoch_hashes = Some(Vec::new());
+            },
+            None => {
+                let epoch = policy::epoch_at(block_number);
+                if pruned_bodies.map(|(pruned_epoch, _)| pruned_epoch) != Some(epoch) {
+                    pruned_bodies = Some((epoch, chain_store.has_pruned_bodies(epoch, Some(&txn))));
+                }
+                // The transactions of pruned micro blocks are checked at the macro block.
+                let pruned = pruned_bodies.map_or(false, |(_, pruned)| pruned);
+                if !policy::is_micro_block_at(block_number) || !pruned {
+                    inconsistencies.push(Inconsistency::MissingBody(block_number, hash.clone()));
+                }
+                epoch_hashes = None;
+            },
+        }
+
+        let indexed_hashes = chain_store.get_hashes_at(block_number, Some(&txn));
+        if !indexed_hashes.contains(hash) {
+            inconsistencies.push(Inconsistency::MissingHeightIndex(block_number, hash.clone()));
+        }
+
+        for fork_hash in indexed_hashes.into_iter().filter(|fork_hash| fork_hash != hash) {
+            let inconsistency = match chain_store.get_chain_info(&fork_hash, false, Some(&txn)) {
+                None => Inconsistency::DanglingIndexEntry(block_number, fork_hash),
+                // Macro blocks are final, so no fork behind them can become the main chain.
+                Some(_) if block_number <= macro_head_number => Inconsistency::DanglingFork(block_number, fork_hash),
+                Some(ref fork_info) if fork_info.on_main_chain => Inconsistency::ConflictingMainChain(block_number, fork_hash),
+                Some(_) => continue,
+            };
+            repairs.push(inconsistency.clone());
+            inconsistencies.push(inconsistency);
+        }
+    }
+
+    inconsistencies.append(&mut slash_registry.verify(&txn, head_number));
+
+    drop(txn);
+    if repair && !repairs.is_empty() {
+        let mut txn = WriteTransaction::new(env);
+        for inconsistency in repairs {
+            match inconsistency {
+                Inconsistency::DanglingIndexEntry(block_number, hash)
+                | Inconsistency::DanglingFork(block_number, hash) => {
+                    chain_store.remove_chain_info(&mut txn, &hash, block_number);
+                },
+                Inconsistency::ConflictingMainChain(_, hash) => {
+                    if let Some(mut fork_info) = chain_store.get_chain_info(&hash, false, Some(&txn)) {
+                        fork_info.on_main_chain = false;
+                        fork_info.main_chain_successor = None;
+                        chain_store.put_chain_info(&mut txn, &hash, &fork_info, false);
+                    }
+                },
+                _ => unreachable!(),
+            }
+        }
+        txn.commit();
+    }
+
+    inconsistencies
+}
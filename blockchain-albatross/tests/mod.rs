@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use beserial::Deserialize;
+use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::{Block, MacroBlock, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage, ViewChangeProof, SignedViewChange, ViewChange, ViewChangeProofBuilder};
 use nimiq_block_production_albatross::BlockProducer;
 use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult, PushError};
@@ -12,9 +12,21 @@ use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_network_primitives::{networks::NetworkId};
 use nimiq_primitives::policy;
 use nimiq_database::Environment;
+use nimiq_blockchain_albatross::pruning::PruningPolicy;
+use nimiq_blockchain_albatross::snapshot::ChainSnapshot;
+use nimiq_blockchain_albatross::verify::{verify, Inconsistency};
+use nimiq_blockchain_albatross::chain_info::ChainInfo;
+use nimiq_blockchain_albatross::chain_store::ChainStore;
+use nimiq_database::{DatabaseFlags, WriteTransaction};
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_transaction::Transaction;
+use nimiq_account::{Account, BasicAccount};
+use nimiq_collections::bitset::BitSet;
 
 mod signed;
 mod macro_block_sync;
+mod snapshot;
 
 /// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
 const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
@@ -214,3 +226,278 @@ fn it_can_rebranch_forks() {
     assert_eq!(temp_producer1.push(fork2d), Ok(PushResult::Extended));
     assert_eq!(temp_producer2.push(fork1d), Err(PushError::Orphan));
 }
+
+#[test]
+fn it_verifies_and_repairs_dangling_forks() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let block = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(block).unwrap();
+
+    let fork = temp_producer1.next_block(0, vec![0x48]);
+    let fork_hash = fork.hash();
+    temp_producer2.next_block(0, vec![]);
+    assert_eq!(temp_producer2.push(fork), Ok(PushResult::Forked));
+
+    // The fork may still become the main chain.
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, false), vec![]);
+
+    // Once the macro block is pushed, it can't.
+    while !policy::is_macro_block_at(temp_producer2.blockchain.head_height()) {
+        temp_producer2.next_block(0, vec![]);
+    }
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, true), vec![Inconsistency::DanglingFork(2, fork_hash)]);
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, false), vec![]);
+
+    // The repaired database still loads.
+    Blockchain::new(temp_producer2.env.clone(), NetworkId::UnitAlbatross).unwrap();
+}
+
+#[test]
+fn it_verifies_and_repairs_dangling_index_entries() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(0, vec![]);
+    temp_producer.next_block(0, vec![]);
+
+    // Add a height index entry for a block that isn't stored.
+    let unknown_hash = Blake2bHash::from([1u8; Blake2bHash::SIZE]);
+    let height_idx = temp_producer.env.open_database_with_flags("HeightIdx".to_string(),
+                                                                DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES);
+    let mut txn = WriteTransaction::new(&temp_producer.env);
+    txn.put(&height_idx, &2u32, &unknown_hash);
+    txn.commit();
+
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, true), vec![Inconsistency::DanglingIndexEntry(2, unknown_hash)]);
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, false), vec![]);
+
+    // The repaired database still loads.
+    Blockchain::new(temp_producer.env.clone(), NetworkId::UnitAlbatross).unwrap();
+}
+
+#[test]
+fn it_verifies_and_repairs_forks_marked_as_main_chain() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let block = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(block).unwrap();
+
+    let fork = temp_producer1.next_block(0, vec![0x48]);
+    let fork_hash = fork.hash();
+    temp_producer2.next_block(0, vec![]);
+    assert_eq!(temp_producer2.push(fork), Ok(PushResult::Forked));
+
+    let chain_store = &temp_producer2.blockchain.chain_store;
+    let mut fork_info = chain_store.get_chain_info(&fork_hash, false, None).unwrap();
+    fork_info.on_main_chain = true;
+    let mut txn = WriteTransaction::new(&temp_producer2.env);
+    chain_store.put_chain_info(&mut txn, &fork_hash, &fork_info, false);
+    txn.commit();
+
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, true), vec![Inconsistency::ConflictingMainChain(2, fork_hash.clone())]);
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, false), vec![]);
+    assert!(!chain_store.get_chain_info(&fork_hash, false, None).unwrap().on_main_chain);
+
+    // The repaired database still loads.
+    Blockchain::new(temp_producer2.env.clone(), NetworkId::UnitAlbatross).unwrap();
+}
+
+#[test]
+fn it_detects_missing_parents() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block = temp_producer.next_block(0, vec![]);
+    temp_producer.next_block(0, vec![]);
+
+    let mut txn = WriteTransaction::new(&temp_producer.env);
+    temp_producer.blockchain.chain_store.remove_chain_info(&mut txn, &block.hash(), 1);
+    txn.commit();
+
+    let inconsistencies = verify(&temp_producer.env, NetworkId::UnitAlbatross, false);
+    assert!(inconsistencies.contains(&Inconsistency::MissingChainInfo(block.hash())));
+}
+
+#[test]
+fn it_detects_accounts_hash_mismatches() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(0, vec![]);
+    let expected = temp_producer.blockchain.head().state_root().clone();
+
+    let mut txn = WriteTransaction::new(&temp_producer.env);
+    temp_producer.blockchain.state().accounts().init(&mut txn, vec![
+        (Address::from([1u8; Address::SIZE]), Account::Basic(BasicAccount { balance: Coin::from_u64_unchecked(1) })),
+    ]);
+    txn.commit();
+    let actual = temp_producer.blockchain.state().accounts().hash(None);
+    assert_ne!(actual, expected);
+
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, false), vec![Inconsistency::AccountsHashMismatch { expected, actual }]);
+}
+
+#[test]
+fn it_detects_transactions_root_mismatches() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..policy::EPOCH_LENGTH {
+        temp_producer.next_block(0, vec![]);
+    }
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, false), vec![]);
+
+    // Add a transaction to a micro block of the first epoch without changing its header.
+    let chain_store = &temp_producer.blockchain.chain_store;
+    let hash = temp_producer.blockchain.get_block_at(2, false).unwrap().hash();
+    let mut chain_info = chain_store.get_chain_info(&hash, true, None).unwrap();
+    if let Block::Micro(ref mut micro_block) = chain_info.head {
+        micro_block.extrinsics.as_mut().unwrap().transactions.push(Transaction::new_basic(
+            Address::from([1u8; Address::SIZE]),
+            Address::from([0u8; Address::SIZE]),
+            Coin::from_u64_unchecked(1),
+            Coin::ZERO,
+            2,
+            NetworkId::UnitAlbatross,
+        ));
+    }
+    let mut txn = WriteTransaction::new(&temp_producer.env);
+    chain_store.put_chain_info(&mut txn, &hash, &chain_info, true);
+    txn.commit();
+
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, false), vec![Inconsistency::TransactionsRootMismatch(policy::EPOCH_LENGTH)]);
+}
+
+#[test]
+fn it_detects_corrupted_slash_descriptors() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..3 {
+        temp_producer.next_block(0, vec![]);
+    }
+    let slash_registry_db = temp_producer.env.open_database_with_flags("SlashRegistry".to_string(), DatabaseFlags::UINT_KEYS);
+
+    // A descriptor consists of the view change, fork proof and previous epoch slot sets.
+    let descriptor = |slashed: BitSet| -> Vec<u8> {
+        let mut bytes = slashed.serialize_to_vec();
+        bytes.append(&mut BitSet::new().serialize_to_vec());
+        bytes.append(&mut BitSet::new().serialize_to_vec());
+        bytes
+    };
+    let mut invalid_slots = BitSet::new();
+    invalid_slots.insert(policy::SLOTS as usize);
+
+    let mut txn = WriteTransaction::new(&temp_producer.env);
+    txn.remove(&slash_registry_db, &1u32);
+    txn.put_reserve(&slash_registry_db, &2u32, &descriptor(invalid_slots)[..]);
+    txn.put_reserve(&slash_registry_db, &4u32, &descriptor(BitSet::new())[..]);
+    txn.commit();
+
+    assert_eq!(verify(&temp_producer.env, NetworkId::UnitAlbatross, false), vec![
+        Inconsistency::InvalidSlashDescriptor(2),
+        Inconsistency::StaleSlashDescriptor(4),
+        Inconsistency::MissingSlashDescriptor(1),
+    ]);
+}
+
+#[test]
+fn it_prunes_forks_and_micro_block_bodies() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    PruningPolicy { prune_forks: true, keep_micro_block_bodies: Some(1) }
+        .register(&temp_producer2.blockchain);
+
+    let block = temp_producer1.next_block(0, vec![]);
+    temp_producer2.push(block).unwrap();
+
+    let fork = temp_producer1.next_block(0, vec![0x48]);
+    let fork_hash = fork.hash();
+    temp_producer2.next_block(0, vec![]);
+    assert_eq!(temp_producer2.push(fork), Ok(PushResult::Forked));
+    assert!(temp_producer2.blockchain.contains(&fork_hash, true));
+
+    // Finalizing the epoch prunes the fork.
+    while !policy::is_macro_block_at(temp_producer2.blockchain.head_height()) {
+        temp_producer2.next_block(0, vec![]);
+    }
+    assert!(!temp_producer2.blockchain.contains(&fork_hash, true));
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, false), vec![]);
+
+    // The bodies of the first epoch are kept as long as they are within the transaction validity
+    // window, even though the policy only keeps one epoch.
+    let blockchain = &temp_producer2.blockchain;
+    let block_hash = blockchain.get_block_at(2, false).unwrap().hash();
+    let transactions_root = blockchain.get_transactions_root(1, None).unwrap();
+    while !policy::is_macro_block_at(blockchain.head_height())
+        || ChainSnapshot::first_block_number(blockchain.head_height()) < policy::EPOCH_LENGTH {
+        assert!(blockchain.chain_store.has_block_body(&block_hash, None));
+        temp_producer2.next_block(0, vec![]);
+    }
+
+    // Pruned bodies leave the headers and transactions of the epoch.
+    match blockchain.get_block_at(2, true) {
+        Some(Block::Micro(micro_block)) => assert!(micro_block.extrinsics.is_none()),
+        _ => panic!("Micro block header is missing"),
+    }
+    assert!(!blockchain.chain_store.has_block_body(&block_hash, None));
+    assert!(AbstractBlockchain::get_block(blockchain.as_ref(), &block_hash, true).is_none());
+    assert_eq!(blockchain.get_transactions_root(1, None), Some(transactions_root));
+    assert_eq!(blockchain.chain_store.get_pruned_transactions(2, None), Some(vec![]));
+
+    // The bodies of the second epoch are still within the transaction validity window.
+    let block_hash = blockchain.get_block_at(policy::EPOCH_LENGTH + 2, false).unwrap().hash();
+    assert!(blockchain.chain_store.has_block_body(&block_hash, None));
+    assert_eq!(verify(&temp_producer2.env, NetworkId::UnitAlbatross, false), vec![]);
+}
+
+#[test]
+fn it_prunes_micro_block_bodies_of_complete_epochs_only() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 1..policy::EPOCH_LENGTH {
+        temp_producer.next_block(0, vec![]);
+    }
+
+    // Store the micro blocks of the first epoch with a different number of transactions each.
+    // The chain store doesn't check the transactions against the headers.
+    let block_transactions = |block_number: u32| -> Vec<Transaction> {
+        (0..block_number % 4).map(|i| Transaction::new_basic(
+            Address::from([block_number as u8; Address::SIZE]),
+            Address::from([0u8; Address::SIZE]),
+            Coin::from_u64_unchecked(u64::from(i) + 1),
+            Coin::ZERO,
+            block_number,
+            NetworkId::UnitAlbatross,
+        )).collect()
+    };
+    let env = VolatileEnvironment::new(10).unwrap();
+    let chain_store = ChainStore::new(env.clone());
+    let store_block = |block_number: u32| {
+        let mut block = temp_producer.blockchain.get_block_at(block_number, true).unwrap().unwrap_micro();
+        block.extrinsics.as_mut().unwrap().transactions = block_transactions(block_number);
+        let mut txn = WriteTransaction::new(&env);
+        chain_store.put_chain_info(&mut txn, &block.header.hash(), &ChainInfo::initial(Block::Micro(block)), true);
+        txn.commit();
+    };
+
+    // An incomplete epoch is left alone.
+    let last_micro_block = policy::EPOCH_LENGTH - 1;
+    for block_number in 1..last_micro_block {
+        store_block(block_number);
+    }
+    let mut txn = WriteTransaction::new(&env);
+    assert!(!chain_store.prune_micro_block_bodies(&mut txn, 1));
+    txn.commit();
+    assert!(!chain_store.has_pruned_bodies(1, None));
+    let block_hash = chain_store.get_block_at(1, false, None).unwrap().hash();
+    assert!(chain_store.has_block_body(&block_hash, None));
+
+    store_block(last_micro_block);
+    let mut txn = WriteTransaction::new(&env);
+    assert!(chain_store.prune_micro_block_bodies(&mut txn, 1));
+    txn.commit();
+    assert!(chain_store.has_pruned_bodies(1, None));
+    assert!(!chain_store.has_block_body(&block_hash, None));
+
+    // Each block gets its own transactions back.
+    let mut epoch_transactions = Vec::new();
+    for block_number in 1..=last_micro_block {
+        let transactions = block_transactions(block_number);
+        assert_eq!(chain_store.get_pruned_transactions(block_number, None), Some(transactions.clone()));
+        epoch_transactions.extend(transactions);
+    }
+    assert_eq!(chain_store.get_epoch_transactions(1, None), Some(epoch_transactions));
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_block_albatross::{Block, MacroBlock, PbftProposal, PbftProofBuilder, PbftPrepareMessage, PbftCommitMessage, SignedPbftPrepareMessage, SignedPbftCommitMessage};
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_blockchain_albatross::snapshot::{ChainSnapshot, SnapshotError};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_primitives::policy;
+use nimiq_blockchain_base::AbstractBlockchain;
+
+/// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+fn push_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>, count: u32) {
+    for _ in 0..count {
+        let height = blockchain.head_height() + 1;
+        let micro_block = producer.next_micro_block(vec![], 1565713920000 + height as u64 * 2000, 0, vec![0x42], None);
+        assert_eq!(blockchain.push(Block::Micro(micro_block)), Ok(PushResult::Extended));
+    }
+}
+
+fn sign_macro_block(proposal: PbftProposal) -> MacroBlock {
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+
+    let block_hash = proposal.header.hash::<Blake2bHash>();
+
+    // create signed prepare and commit
+    let prepare = SignedPbftPrepareMessage::from_message(
+        PbftPrepareMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+    let commit = SignedPbftCommitMessage::from_message(
+        PbftCommitMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+
+    // create proof
+    let mut pbft_proof = PbftProofBuilder::new();
+    pbft_proof.add_prepare_signature(&keypair.public, policy::SLOTS, &prepare);
+    pbft_proof.add_commit_signature(&keypair.public, policy::SLOTS, &commit);
+
+    MacroBlock {
+        header: proposal.header,
+        justification: Some(pbft_proof.build()),
+        extrinsics: None,
+    }
+}
+
+fn produce_macro_blocks(num_macro: usize, producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
+    for _ in 0..num_macro {
+        let init_height = blockchain.head_height();
+        push_micro_blocks(producer, blockchain, policy::macro_block_after(init_height + 1) - init_height - 1);
+
+        let next_block_height = blockchain.head_height() + 1;
+        let (proposal, _extrinsics) = producer.next_macro_block_proposal(1565713920000 + next_block_height as u64 * 2000, 0u32, None);
+
+        let block = sign_macro_block(proposal);
+        assert_eq!(blockchain.push_block(Block::Macro(block), true), Ok(PushResult::Extended));
+    }
+}
+
+#[test]
+fn it_can_restore_a_snapshot() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), keypair);
+
+    produce_macro_blocks(2, &producer, &blockchain);
+    push_micro_blocks(&producer, &blockchain, 3);
+
+    // The snapshot is taken at the macro head, not at the current head.
+    let snapshot = blockchain.snapshot().unwrap();
+    let snapshot = ChainSnapshot::deserialize_from_vec(&snapshot.serialize_to_vec()).unwrap();
+    assert_eq!(snapshot.macro_block().unwrap().hash(), blockchain.macro_head_hash());
+    assert_eq!(snapshot.blocks[0].block_number(), 0);
+
+    let env2 = VolatileEnvironment::new(10).unwrap();
+    let restored = Blockchain::from_snapshot(env2.clone(), snapshot.clone()).unwrap();
+    assert_eq!(restored.head_hash(), blockchain.macro_head_hash());
+    assert_eq!(restored.macro_head_hash(), blockchain.macro_head_hash());
+    assert_eq!(restored.state().accounts().hash(None), blockchain.macro_head().header.state_root);
+
+    // The restored chain accepts the blocks following the macro head.
+    for block_number in (restored.block_number() + 1)..=blockchain.block_number() {
+        let block = blockchain.get_block_at(block_number, true).unwrap();
+        assert_eq!(restored.push(block), Ok(PushResult::Extended));
+    }
+    assert_eq!(restored.head_hash(), blockchain.head_hash());
+
+    // A snapshot can only be restored into an empty database.
+    match Blockchain::from_snapshot(env2, snapshot) {
+        Err(SnapshotError::DatabaseNotEmpty) => {},
+        _ => panic!("Restored a snapshot into a non-empty database"),
+    }
+}
+
+#[test]
+fn it_can_restore_a_snapshot_without_the_genesis_block() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), keypair);
+
+    // The chain needs to be longer than the transaction validity window, so that the snapshot
+    // doesn't reach back to the genesis block.
+    let num_macro = (policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS / policy::EPOCH_LENGTH + 1) as usize;
+    produce_macro_blocks(num_macro, &producer, &blockchain);
+
+    let snapshot = blockchain.snapshot().unwrap();
+    let first_block_number = snapshot.blocks[0].block_number();
+    assert!(first_block_number > 0);
+    assert_eq!(first_block_number, ChainSnapshot::first_block_number(blockchain.block_number()));
+
+    let restored = Blockchain::from_snapshot(VolatileEnvironment::new(10).unwrap(), snapshot).unwrap();
+    assert_eq!(restored.head_hash(), blockchain.macro_head_hash());
+    assert_eq!(restored.state().accounts().hash(None), blockchain.macro_head().header.state_root);
+
+    // The genesis block is on the main chain, the blocks between it and the snapshot are not.
+    let genesis_hash = NetworkInfo::from_network_id(NetworkId::UnitAlbatross).genesis_hash().clone();
+    assert_eq!(restored.get_block_at(0, false).map(|block| block.hash()), Some(genesis_hash));
+    assert!(restored.get_block_at(1, false).is_none());
+    assert!(restored.get_block_at(first_block_number - 1, false).is_none());
+    assert_eq!(restored.get_block_at(first_block_number, false).map(|block| block.hash()),
+               blockchain.get_block_at(first_block_number, false).map(|block| block.hash()));
+
+    // The restored chain accepts new blocks.
+    push_micro_blocks(&producer, &blockchain, 2);
+    for block_number in (restored.block_number() + 1)..=blockchain.block_number() {
+        let block = blockchain.get_block_at(block_number, true).unwrap();
+        assert_eq!(restored.push(block), Ok(PushResult::Extended));
+    }
+    assert_eq!(restored.head_hash(), blockchain.head_hash());
+}
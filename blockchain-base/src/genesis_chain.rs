@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use parking_lot::{Mutex, MutexGuard, RwLock, MappedRwLockReadGuard, RwLockReadGuard};
+
+use account::Account;
+use block_base::Block;
+use database::{Environment, ReadTransaction, Transaction};
+use hash::Blake2bHash;
+use keys::Address;
+use nimiq_network_primitives::networks::NetworkInfo;
+use nimiq_network_primitives::time::NetworkTime;
+use primitives::networks::NetworkId;
+use transaction::{TransactionReceipt, TransactionsProof};
+use transaction::Transaction as BlockchainTransaction;
+use tree_primitives::accounts_proof::AccountsProof;
+use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
+use utils::observer::{Listener, ListenerHandle, Notifier};
+
+use crate::{AbstractBlockchain, BlockchainError, BlockchainEvent, Direction, PushError, PushResult};
+
+/// A blockchain that only knows the genesis block of its network and ignores all other blocks.
+///
+/// This is used by nodes that take part in the network without keeping a chain, e.g. seed
+/// servers. It announces the genesis block as its head, so peers never try to sync from it.
+pub struct GenesisChain<BL: Block> {
+    network_id: NetworkId,
+    genesis_hash: Blake2bHash,
+    genesis_block: RwLock<BL>,
+    notifier: RwLock<Notifier<'static, BlockchainEvent<BL>>>,
+    push_lock: Mutex<()>,
+
+    #[cfg(feature = "metrics")]
+    metrics: crate::chain_metrics::BlockchainMetrics,
+}
+
+impl<BL: Block + Clone + Send + Sync> AbstractBlockchain for GenesisChain<BL> {
+    type Block = BL;
+
+    fn new(_env: Environment, network_id: NetworkId, _network_time: Arc<NetworkTime>) -> Result<Self, BlockchainError> {
+        let network_info = NetworkInfo::from_network_id(network_id);
+        Ok(GenesisChain {
+            network_id,
+            genesis_hash: network_info.genesis_hash().clone(),
+            genesis_block: RwLock::new(network_info.genesis_block::<BL>()),
+            notifier: RwLock::new(Notifier::new()),
+            push_lock: Mutex::new(()),
+
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+        })
+    }
+
+    #[cfg(feature = "metrics")]
+    fn metrics(&self) -> &crate::chain_metrics::BlockchainMetrics {
+        &self.metrics
+    }
+
+    fn network_id(&self) -> NetworkId {
+        self.network_id
+    }
+
+    fn head_block(&self) -> MappedRwLockReadGuard<Self::Block> {
+        RwLockReadGuard::map(self.genesis_block.read(), |block| block)
+    }
+
+    fn head_hash(&self) -> Blake2bHash {
+        self.genesis_hash.clone()
+    }
+
+    fn head_height(&self) -> u32 {
+        self.genesis_block.read().height()
+    }
+
+    fn get_block(&self, hash: &Blake2bHash, _include_body: bool) -> Option<Self::Block> {
+        if hash == &self.genesis_hash {
+            Some(self.genesis_block.read().clone())
+        } else {
+            None
+        }
+    }
+
+    fn get_block_at(&self, height: u32, _include_body: bool) -> Option<Self::Block> {
+        let genesis_block = self.genesis_block.read();
+        if height == genesis_block.height() {
+            Some(genesis_block.clone())
+        } else {
+            None
+        }
+    }
+
+    fn get_block_locators(&self, _max_count: usize) -> Vec<Blake2bHash> {
+        vec![self.genesis_hash.clone()]
+    }
+
+    fn get_blocks(&self, _start_block_hash: &Blake2bHash, _count: u32, _include_body: bool, _direction: Direction) -> Vec<Self::Block> {
+        Vec::new()
+    }
+
+    fn push(&self, _block: Self::Block) -> Result<PushResult, PushError<BL::Error>> {
+        Ok(PushResult::Ignored)
+    }
+
+    fn has_block_body(&self, hash: &Blake2bHash) -> bool {
+        hash == &self.genesis_hash
+    }
+
+    fn contains(&self, hash: &Blake2bHash, _include_forks: bool) -> bool {
+        hash == &self.genesis_hash
+    }
+
+    fn get_accounts_proof(&self, _block_hash: &Blake2bHash, _addresses: &[Address]) -> Option<AccountsProof<Account>> {
+        None
+    }
+
+    fn get_transactions_proof(&self, _block_hash: &Blake2bHash, _addresses: &HashSet<Address>) -> Option<TransactionsProof> {
+        None
+    }
+
+    fn get_transaction_receipts_by_address(&self, _address: &Address, _sender_limit: usize, _recipient_limit: usize) -> Vec<TransactionReceipt> {
+        Vec::new()
+    }
+
+    fn register_listener<T: Listener<BlockchainEvent<Self::Block>> + 'static>(&self, listener: T) -> ListenerHandle {
+        self.notifier.write().register(listener)
+    }
+
+    fn lock(&self) -> MutexGuard<()> {
+        self.push_lock.lock()
+    }
+
+    fn get_account(&self, _address: &Address) -> Account {
+        Account::INITIAL
+    }
+
+    fn contains_tx_in_validity_window(&self, _tx_hash: &Blake2bHash) -> bool {
+        false
+    }
+
+    fn head_hash_from_store(&self, _txn: &ReadTransaction) -> Option<Blake2bHash> {
+        Some(self.genesis_hash.clone())
+    }
+
+    fn get_accounts_chunk(&self, _prefix: &str, _size: usize, _txn_option: Option<&Transaction>) -> Option<AccountsTreeChunk<Account>> {
+        None
+    }
+
+    fn get_epoch_transactions(&self, _epoch: u32, _txn_option: Option<&Transaction>) -> Option<Vec<BlockchainTransaction>> {
+        None
+    }
+}
@@ -32,6 +32,7 @@ use utils::observer::{Listener, ListenerHandle};
 
 #[cfg(feature = "metrics")]
 pub mod chain_metrics;
+pub mod genesis_chain;
 
 pub trait AbstractBlockchain: Sized + Send + Sync {
     // TODO: Should this be `Block + 'static`? Our implementations would satisfy this anyway. And
@@ -61,19 +62,23 @@ pub trait AbstractBlockchain: Sized + Send + Sync {
     fn head_height(&self) -> u32;
 
 
-    /// Get block by hash
+    /// Get block by hash. If `include_body` is set, blocks whose body isn't stored are not returned.
     fn get_block(&self, hash: &Blake2bHash, include_body: bool) -> Option<Self::Block>;
 
-    /// Get block by block number
+    /// Get block by block number. If `include_body` is set, blocks whose body isn't stored are not
+    /// returned.
     fn get_block_at(&self, height: u32, include_body: bool) -> Option<Self::Block>;
 
     /// Get block locators
     fn get_block_locators(&self, max_count: usize) -> Vec<Blake2bHash>;
 
     /// Get `count` blocks starting from `start_block_hash` into `direction` and optionally include
-    /// block bodies.
+    /// block bodies. If bodies are included, this stops at the first block whose body isn't stored.
     fn get_blocks(&self, start_block_hash: &Blake2bHash, count: u32, include_body: bool, direction: Direction) -> Vec<Self::Block>;
 
+    /// Returns whether the body of a block is stored. Blockchains may prune the bodies of old blocks.
+    fn has_block_body(&self, hash: &Blake2bHash) -> bool;
+
 
     /// Verify a block
     //fn verify(&self, block: &Self::Block) -> Self::VerifyResult;
@@ -771,6 +771,11 @@ impl AbstractBlockchain for Blockchain {
         self.get_blocks(start_block_hash, count, include_body, direction)
     }
 
+    fn has_block_body(&self, hash: &Blake2bHash) -> bool {
+        // Block bodies are never pruned.
+        self.contains(hash, true)
+    }
+
     fn push(&self, block: Self::Block) -> Result<PushResult, PushError> {
         self.push(block)
     }
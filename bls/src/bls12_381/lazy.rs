@@ -153,3 +153,12 @@ mod serialization {
         }
     }
 }
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use nimiq_macros::add_serde_hex_fns;
+
+    use super::*;
+
+    add_serde_hex_fns!(LazyPublicKey);
+}
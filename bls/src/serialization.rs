@@ -210,3 +210,17 @@ impl Deserialize for KeyPair {
         Ok(KeyPair::from(secret))
     }
 }
+
+/// JSON representation of keys and signatures: the hex encoding of their compressed form.
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use nimiq_macros::add_serde_hex_fns;
+
+    use super::*;
+
+    add_serde_hex_fns!(CompressedPublicKey);
+    add_serde_hex_fns!(CompressedSignature);
+    add_serde_hex_fns!(PublicKey);
+    add_serde_hex_fns!(Signature);
+    add_serde_hex_fns!(AggregateSignature);
+}
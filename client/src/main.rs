@@ -45,6 +45,11 @@ fn main_inner() -> Result<(), Error> {
     let config = builder.build()?;
     debug!("Final configuration: {:#?}", config);
 
+    // Run as a seeder instead, if configured
+    if config.seeder.is_some() {
+        return run_seeder(config, config_file.log.statistics);
+    }
+
     // We need to instantiate the client when the tokio runtime is already alive, so we use
     // a lazy future for it.
     tokio::run(
@@ -74,15 +79,24 @@ fn main_inner() -> Result<(), Error> {
             // Initialize metrics server
             if let Some(metrics_config) = metrics_config {
                 use nimiq::config::config::ProtocolConfig;
-                use nimiq::extras::metrics_server::initialize_metrics_server;
-                if let ProtocolConfig::Wss{pkcs12_key_file, pkcs12_passphrase, .. } = protocol_config {
-                    let pkcs12_key_file = pkcs12_key_file.to_str()
-                        .unwrap_or_else(|| panic!("Failed to convert path to PKCS#12 key file to string: {}", pkcs12_key_file.display()));
-                    let metrics_server = initialize_metrics_server(&client, metrics_config, pkcs12_key_file, &pkcs12_passphrase)
-                        .expect("Failed to initialize metrics server");
-                    tokio::spawn(metrics_server.into_future());
-                } else {
-                    error!("Cannot provide metrics when running without a certificate");
+                use nimiq::extras::metrics_server::{initialize_metrics_server, TlsIdentity};
+                let tls = match (metrics_config.tls, &protocol_config) {
+                    (Some(false), _) => Ok(None),
+                    (_, ProtocolConfig::Wss{pkcs12_key_file, pkcs12_passphrase, .. }) => {
+                        let pkcs12_key_file = pkcs12_key_file.to_str()
+                            .unwrap_or_else(|| panic!("Failed to convert path to PKCS#12 key file to string: {}", pkcs12_key_file.display()));
+                        Ok(Some(TlsIdentity { pkcs12_key_file, pkcs12_passphrase }))
+                    },
+                    (Some(true), _) => Err(()),
+                    (None, _) => Ok(None),
+                };
+                match tls {
+                    Ok(tls) => {
+                        let metrics_server = initialize_metrics_server(&client, metrics_config, tls)
+                            .expect("Failed to initialize metrics server");
+                        tokio::spawn(metrics_server.into_future());
+                    },
+                    Err(()) => error!("Cannot provide metrics over HTTPS when running without a certificate"),
                 }
             }
 
@@ -134,6 +148,47 @@ fn main_inner() -> Result<(), Error> {
     Ok(())
 }
 
+fn run_seeder(config: ClientConfig, statistics_interval: u64) -> Result<(), Error> {
+    use nimiq::extras::seeder::Seeder;
+
+    tokio::run(
+        future::lazy(move || {
+            info!("Initializing seeder");
+            let seeder = Seeder::new(config)?;
+            seeder.initialize()?;
+
+            // Serve the seed list
+            tokio::spawn(seeder.serve()?);
+
+            info!("Crawling network");
+            seeder.connect();
+
+            Ok(seeder)
+        })
+            .and_then(move |seeder| {
+                // NOTE: This is the "monitor" future, which keeps the Seeder object alive.
+
+                let show_statistics = statistics_interval != 0;
+                let interval = if show_statistics { statistics_interval } else { 10 };
+
+                Interval::new_interval(Duration::from_secs(interval))
+                    .map_err(|e| panic!("Timer failed: {}", e))
+                    .for_each(move |_| {
+
+                        if show_statistics {
+                            let network = seeder.network();
+                            let seed_count = seeder.seed_server().seeds().len();
+                            info!("Known addresses: {}, Seeds: {}, Peers: {}", network.addresses.known_addresses_count(), seed_count, network.peer_count());
+                        }
+
+                        future::ok::<(), Error>(())
+                    })
+            })
+            .map_err(|e: Error| warn!("{}", e)));
+
+    Ok(())
+}
+
 fn main() {
     if let Err(e) = main_inner() {
         log_error_cause_chain(&e);
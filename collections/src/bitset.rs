@@ -348,3 +348,32 @@ impl FromIterator<usize> for BitSet {
         bitset
     }
 }
+
+/// In JSON a `BitSet` is written as the sorted array of the values it contains.
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::{Error, Unexpected};
+
+    use super::BitSet;
+
+    /// Upper bound for values read from JSON, so that a single large value can't make us allocate
+    /// an arbitrarily large store.
+    const MAX_VALUE: usize = 1 << 20;
+
+    impl Serialize for BitSet {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BitSet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let values: Vec<usize> = Deserialize::deserialize(deserializer)?;
+            if let Some(&value) = values.iter().find(|&&value| value >= MAX_VALUE) {
+                return Err(D::Error::invalid_value(Unexpected::Unsigned(value as u64), &"a value below 2^20"));
+            }
+            Ok(values.into_iter().collect())
+        }
+    }
+}
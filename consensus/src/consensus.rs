@@ -74,7 +74,7 @@ impl<P: ConsensusProtocol> Consensus<P> {
         let network_time = Arc::new(NetworkTime::new());
         let blockchain = Arc::new(<P::Blockchain as AbstractBlockchain>::new(env.clone(), network_id, Arc::clone(&network_time))?);
         let mempool = Mempool::new(Arc::clone(&blockchain), mempool_config);
-        let network = Network::new(Arc::clone(&blockchain), network_config, network_time, network_id)?;
+        let network = Network::new(Arc::clone(&blockchain), network_config, network_time, network_id, env.clone())?;
         let accounts_chunk_cache = AccountsChunkCache::new(env.clone(), Arc::clone(&blockchain));
 
         let this = Arc::new(Consensus {
@@ -151,6 +151,7 @@ impl<P: ConsensusProtocol> Consensus<P> {
             match e {
                 ConsensusAgentEvent::Synced => this.on_peer_synced(peer_arc_moved.clone()),
                 ConsensusAgentEvent::OutOfSync => this.on_peer_out_of_sync(peer_arc_moved.clone()),
+                ConsensusAgentEvent::SyncFailed => this.on_peer_sync_failed(peer_arc_moved.clone()),
             }
         });
 
@@ -197,6 +198,22 @@ impl<P: ConsensusProtocol> Consensus<P> {
         self.sync_blockchain();
     }
 
+    fn on_peer_sync_failed(&self, peer: Arc<Peer>) {
+        // Reset syncPeer and continue with another peer.
+        {
+            let mut state = self.state.write();
+            if state.sync_peer.as_ref().map_or(false, |sync_peer| sync_peer == &peer) {
+                debug!("Sync with peer {} failed", peer.peer_address());
+                state.sync_peer = None;
+                drop(state);
+
+                self.notifier.read().notify(ConsensusEvent::SyncFailed);
+            }
+        }
+
+        self.sync_blockchain();
+    }
+
     fn on_peer_out_of_sync(&self, peer: Arc<Peer>) {
         warn!("Peer {} out of sync, re-syncing", peer.peer_address());
         self.sync_blockchain();
@@ -266,12 +283,18 @@ impl<P: ConsensusProtocol> Consensus<P> {
         }
 
         let mut num_synced_full_nodes: usize = 0;
+        let mut num_failed_syncs: usize = 0;
         let candidates: Vec<&Arc<ConsensusAgent<P>>> = state.agents.values()
             .filter(|&agent| {
                 let synced = agent.synced();
                 if synced && agent.peer.peer_address().services.is_full_node() {
                     num_synced_full_nodes += 1;
                 }
+                // Don't retry peers we recently failed to sync with.
+                if !synced && !agent.can_sync() {
+                    num_failed_syncs += 1;
+                    return false;
+                }
                 !synced
             }).collect();
 
@@ -299,6 +322,15 @@ impl<P: ConsensusProtocol> Consensus<P> {
 
             debug!("Syncing blockchain with peer {}", agent.peer.peer_address());
             agent.sync();
+        } else if num_failed_syncs > 0 {
+            // We are not synced with all connected peers yet, retry the peers that failed later.
+            drop(state);
+
+            let weak = self.self_weak.clone();
+            self.timers.reset_delay(ConsensusTimer::Sync, move || {
+                let this = upgrade_weak!(weak);
+                this.sync_blockchain();
+            }, ConsensusAgent::<P>::SYNC_RETRY_DELAY);
         } else {
             // We are synced with all connected peers.
             // Report consensus-established if we are connected to the minimum number of full nodes.
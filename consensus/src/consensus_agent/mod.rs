@@ -1,5 +1,5 @@
 use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
@@ -41,6 +41,7 @@ pub mod sync;
 pub enum ConsensusAgentEvent {
     Synced,
     OutOfSync,
+    SyncFailed,
 }
 
 pub struct ConsensusAgentState {
@@ -65,6 +66,12 @@ pub struct ConsensusAgentState {
     /// The number of failed blockchain sync attempts.
     failed_syncs: u32,
 
+    /// The number of syncs that were aborted because a request to the peer failed.
+    failed_requests: u32,
+
+    /// The time the last sync with the peer was aborted.
+    last_failed_sync: Option<Instant>,
+
     /// Rate limit for GetChainProof messages.
     chain_proof_limit: RateLimit,
 
@@ -114,6 +121,9 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
     const GET_BLOCKS_TIMEOUT: Duration = Duration::from_secs(10);
     const GET_BLOCKS_MAX_RESULTS: u16 = 500;
     const RESYNC_THROTTLE: Duration = Duration::from_secs(3);
+    /// Time to wait before syncing with a peer again after a request to it failed.
+    pub const SYNC_RETRY_DELAY: Duration = Duration::from_secs(30);
+    const FAILED_REQUESTS_MAX: u32 = 3;
 
     const CHAIN_PROOF_RATE_LIMIT: usize = 3; // per minute
     const BLOCK_PROOF_RATE_LIMIT: usize = 60; // per minute
@@ -148,6 +158,8 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
                 num_blocks_extending: 1,
                 num_blocks_forking: 0,
                 failed_syncs: 0,
+                failed_requests: 0,
+                last_failed_sync: None,
 
                 chain_proof_limit: RateLimit::new_per_minute(Self::CHAIN_PROOF_RATE_LIMIT),
                 block_proof_limit: RateLimit::new_per_minute(Self::BLOCK_PROOF_RATE_LIMIT),
@@ -219,6 +231,12 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
         self.state.read().synced
     }
 
+    /// Whether we can sync with the peer, i.e. the last sync with it didn't fail recently.
+    pub fn can_sync(&self) -> bool {
+        self.state.read().last_failed_sync
+            .map_or(true, |time| time.elapsed() >= Self::SYNC_RETRY_DELAY)
+    }
+
     pub fn sync(&self) {
         self.state.write().syncing = true;
         self.sync_protocol.initiate_sync();
@@ -286,6 +304,8 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
             state.num_blocks_forking = 0;
             state.fork_head = None;
             state.failed_syncs = 0;
+            state.failed_requests = 0;
+            state.last_failed_sync = None;
         }
 
         drop(sync_guard);
@@ -339,7 +359,7 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
             InventoryEvent::AllObjectsReceived => self.on_all_objects_received(),
             InventoryEvent::BlockProcessed(hash, result) => self.on_block_processed(hash, result),
             InventoryEvent::TransactionProcessed(hash, result) => self.on_tx_processed(hash, result),
-            InventoryEvent::GetBlocksTimeout => self.on_get_blocks_timeout(),
+            InventoryEvent::SyncFailed => self.on_sync_failed(),
             _ => {}
         }
     }
@@ -465,7 +485,26 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
         self.notifier.read().notify(ConsensusAgentEvent::OutOfSync);
     }
 
-    fn on_get_blocks_timeout(&self) {
-        self.peer.channel.close(CloseType::GetBlocksTimeout);
+    /// A request to the peer failed during the sync. Stop syncing with it, so that the sync can be
+    /// continued with another peer, and give up on the peer if this keeps happening.
+    fn on_sync_failed(&self) {
+        {
+            let mut state = self.state.write();
+            if !state.syncing {
+                return;
+            }
+
+            state.syncing = false;
+            state.failed_requests += 1;
+            state.last_failed_sync = Some(Instant::now());
+            if state.failed_requests >= Self::FAILED_REQUESTS_MAX {
+                drop(state);
+                self.peer.channel.close(CloseType::BlockchainSyncFailed);
+                return;
+            }
+        }
+
+        self.inv_agent.bypass_mgr(false);
+        self.notifier.read().notify(ConsensusAgentEvent::SyncFailed);
     }
 }
@@ -105,7 +105,11 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
         let peer = self.peer.clone();
         let future = get_chunk_future.then(move |chunk_res| {
             let chunk_opt = chunk_res.unwrap_or(None).map(AccountsTreeChunkData::Serialized);
-            peer.channel.send_or_close(Message::AccountsTreeChunk(Box::new(AccountsTreeChunkMessage { block_hash: msg.block_hash, chunk: chunk_opt })));
+            peer.channel.send_or_close(Message::AccountsTreeChunk(Box::new(AccountsTreeChunkMessage {
+                block_hash: msg.block_hash,
+                chunk: chunk_opt,
+                request_identifier: msg.request_identifier,
+            })));
             future::ok::<(), ()>(())
         });
         tokio::spawn(future);
@@ -140,6 +144,7 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
                 get_epoch_transactions_message.epoch,
                 chunk.to_vec(),
                 tx_proof,
+                get_epoch_transactions_message.request_identifier,
             ));
         }
     }
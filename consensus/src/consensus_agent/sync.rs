@@ -4,6 +4,7 @@ use std::mem;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
+use futures::{Future, Stream};
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
 
 use block_albatross::Block as AlbatrossBlock;
@@ -16,19 +17,20 @@ use hash::Blake2bHash;
 use macros::upgrade_weak;
 use network::connection::close_type::CloseType;
 use network::peer::Peer;
-use network_messages::{EpochTransactionsMessage, GetBlocksDirection, GetBlocksMessage, GetEpochTransactionsMessage};
+use network::peer_channel::RequestError;
+use network_messages::{EpochTransactionsMessage, GetBlocksDirection, GetBlocksMessage, GetEpochTransactionsMessage, Message};
 use primitives::policy;
 use transaction::Transaction;
 use utils::merkle::partial::PartialMerkleProofResult;
 use utils::mutable_once::MutableOnce;
-use utils::observer::{PassThroughListener, PassThroughNotifier, weak_listener};
-use utils::timers::Timers;
+use utils::observer::{PassThroughListener, PassThroughNotifier};
 
 pub trait SyncProtocol<B: AbstractBlockchain>: Send + Sync {
     fn new(blockchain: Arc<B>, peer: Arc<Peer>) -> Arc<Self>;
     fn initiate_sync(&self) {}
     fn get_block_locators(&self, max_count: usize) -> Vec<Blake2bHash>;
-    fn request_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16);
+    /// Builds the request for the blocks following `locators`, the peer answers with an inv.
+    fn get_blocks_request(&self, locators: Vec<Blake2bHash>, max_results: u16) -> Message;
     fn on_block(&self, block: B::Block);
     fn on_epoch_transactions(&self, epoch_transactions: EpochTransactionsMessage);
     fn on_no_new_objects_announced(&self) {}
@@ -40,6 +42,8 @@ pub trait SyncProtocol<B: AbstractBlockchain>: Send + Sync {
 #[derive(Debug, PartialEq, Eq)]
 pub enum SyncEvent<BE: BlockError> {
     BlockProcessed(Blake2bHash, Result<PushResult, PushError<BE>>),
+    /// A request to the peer failed, the sync should be continued with another peer.
+    RequestFailed(RequestError),
 }
 
 pub struct FullSync<B: AbstractBlockchain> {
@@ -69,12 +73,12 @@ impl<B: AbstractBlockchain> SyncProtocol<B> for FullSync<B> {
         self.blockchain.get_block_locators(max_count)
     }
 
-    fn request_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16) {
-        self.peer.channel.send_or_close(GetBlocksMessage::new(
+    fn get_blocks_request(&self, locators: Vec<Blake2bHash>, max_results: u16) -> Message {
+        GetBlocksMessage::new(
             locators,
             max_results,
             GetBlocksDirection::Forward,
-        ));
+        )
     }
 
     fn on_block(&self, block: B::Block) {
@@ -96,9 +100,8 @@ impl<B: AbstractBlockchain> SyncProtocol<B> for FullSync<B> {
         self.notifier.read().notify(SyncEvent::BlockProcessed(hash, result));
     }
 
-    fn on_epoch_transactions(&self, _epoch_transactions: EpochTransactionsMessage) {
-        warn!("We didn't expect any epoch transactions from {} - discarding and closing the channel", self.peer.peer_address());
-        self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
+    fn on_epoch_transactions(&self, epoch_transactions: EpochTransactionsMessage) {
+        debug!("We didn't expect any transactions for epoch {} from {} - discarding", epoch_transactions.epoch, self.peer.peer_address());
     }
 
     fn register_listener<L: PassThroughListener<SyncEvent<<B::Block as Block>::Error>> + 'static>(&self, listener: L) {
@@ -148,17 +151,11 @@ impl MacroBlockSyncState {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum MacroBlockSyncTimer {
-    EpochTransactions(u32)
-}
-
 pub struct MacroBlockSync {
     blockchain: Arc<AlbatrossBlockchain>,
     state: RwLock<MacroBlockSyncState>,
     peer: Arc<Peer>,
     notifier: RwLock<PassThroughNotifier<'static, SyncEvent<AlbatrossBlockError>>>,
-    timers: Timers<MacroBlockSyncTimer>,
     self_weak: MutableOnce<Weak<MacroBlockSync>>,
 }
 
@@ -183,22 +180,93 @@ impl MacroBlockSync {
             state.processing_epoch = true;
             let block = state.block_cache.front().unwrap();
             let epoch = policy::epoch_at(block.block_number());
+            drop(state);
 
-            // Set timeout.
+            // The transactions arrive in chunks, the response stream ends after the last one.
             let weak = self.self_weak.clone();
-            self.timers.set_delay(MacroBlockSyncTimer::EpochTransactions(epoch), move || {
-                let this = upgrade_weak!(weak);
-                // TODO: What should happen if we don't receive any response?
-                // For now, just drop the connection with that peer.
-                this.peer.channel.close(CloseType::GetEpochTransactionsTimeout);
-            }, Self::REQUEST_TIMEOUT);
-
-            self.peer.channel.send_or_close(GetEpochTransactionsMessage::new(epoch));
+            let weak1 = self.self_weak.clone();
+            let request = self.peer.channel.request(GetEpochTransactionsMessage::new(epoch), Self::REQUEST_TIMEOUT)
+                .for_each(move |msg| {
+                    if let (Some(this), Message::EpochTransactions(epoch_transactions)) = (weak.upgrade(), msg) {
+                        this.process_epoch_transactions(*epoch_transactions);
+                    }
+                    Ok(())
+                })
+                .map_err(move |e| {
+                    let this = upgrade_weak!(weak1);
+                    this.on_request_failed(epoch, e);
+                });
+            tokio::spawn(request);
+        }
+    }
+
+    fn on_request_failed(&self, epoch: u32, error: RequestError) {
+        // If the channel was closed, the peer is gone and the sync continues with another one.
+        if error == RequestError::ChannelClosed {
+            return;
         }
+
+        debug!("Request for transactions of epoch {} from {} failed: {}", epoch, self.peer.peer_address(), error);
+        *self.state.write() = MacroBlockSyncState::new();
+        self.notifier.read().notify(SyncEvent::RequestFailed(error));
     }
 
-    fn on_close(&self) {
-        self.timers.clear_all();
+    fn process_epoch_transactions(&self, epoch_transactions: EpochTransactionsMessage) {
+        // Validate proof to prevent the peer from spamming us with transactions.
+        let proof = epoch_transactions.tx_proof;
+        let mut transactions = epoch_transactions.transactions;
+
+        let state = self.state.upgradable_read();
+
+        let expected_root;
+        match state.block_cache.front() {
+            Some(AlbatrossBlock::Macro(ref macro_block)) => {
+                if policy::epoch_at(macro_block.header.block_number) != epoch_transactions.epoch {
+                    warn!("We didn't expect any transactions for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
+                    self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
+                    return;
+                }
+
+                expected_root = macro_block.header.transactions_root.clone();
+            },
+            None => {
+                warn!("We didn't expect any transactions for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
+                self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
+                return;
+            },
+            _ => unreachable!(),
+        }
+
+        match proof.compute_root_from_values(&transactions, state.previous_result.as_ref()) {
+            Ok(result) => {
+                let mut state = RwLockUpgradableReadGuard::upgrade(state);
+                // Check that root corresponds to root for this epoch
+                if result.root() != &expected_root {
+                    warn!("We received transactions with an invalid proof for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
+                    self.peer.channel.close(CloseType::InvalidEpochTransactions);
+                    return;
+                }
+
+                // Append transactions.
+                state.transactions_cache.append(&mut transactions);
+                state.previous_result = Some(result);
+
+                if proof.is_empty() {
+                    let transactions = mem::replace(&mut state.transactions_cache, Vec::new());
+                    state.previous_result = None;
+
+                    let block = state.block_cache.pop_front().unwrap();
+
+                    drop(state);
+                    self.complete_epoch(block, &transactions);
+                }
+            },
+            Err(e) => {
+                warn!("We received an invalid merkle proof ({:?}) from {} - discarding and closing the channel", e, self.peer.peer_address());
+                self.peer.channel.close(CloseType::InvalidEpochTransactions);
+                return;
+            },
+        }
     }
 }
 
@@ -209,7 +277,6 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
             blockchain,
             state: RwLock::new(MacroBlockSyncState::new()),
             notifier: RwLock::new(PassThroughNotifier::new()),
-            timers: Timers::new(),
             self_weak: MutableOnce::new(Weak::new()),
         });
 
@@ -219,13 +286,6 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
             this.self_weak.replace(weak);
         }
 
-        {
-            let mut close_notifier = this.peer.channel.close_notifier.write();
-            close_notifier.register(weak_listener(
-                Arc::downgrade(&this),
-                |this, _| this.on_close()));
-        }
-
         this
     }
 
@@ -240,8 +300,8 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
         self.blockchain.get_macro_block_locators(max_count)
     }
 
-    fn request_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16) {
-        let message = match self.state.read().phase {
+    fn get_blocks_request(&self, locators: Vec<Blake2bHash>, max_results: u16) -> Message {
+        match self.state.read().phase {
             MacroBlockSyncPhase::MacroBlocks => GetBlocksMessage::new_with_macro(
                 locators,
                 max_results,
@@ -252,8 +312,7 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
                 max_results,
                 GetBlocksDirection::Forward,
             ),
-        };
-        self.peer.channel.send_or_close(message);
+        }
     }
 
     fn on_block(&self, block: AlbatrossBlock) {
@@ -274,72 +333,9 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
     }
 
     fn on_epoch_transactions(&self, epoch_transactions: EpochTransactionsMessage) {
-        // Validate proof to prevent the peer from spamming us with transactions.
-        let proof = epoch_transactions.tx_proof;
-        let mut transactions = epoch_transactions.transactions;
-
-        let state = self.state.upgradable_read();
-
-        let expected_root;
-        match state.block_cache.front() {
-            Some(AlbatrossBlock::Macro(ref macro_block)) => {
-                if policy::epoch_at(macro_block.header.block_number) != epoch_transactions.epoch {
-                    warn!("We didn't expect any transactions for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
-                    self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
-                    return;
-                }
-
-                expected_root = macro_block.header.transactions_root.clone();
-            },
-            None => {
-                warn!("We didn't expect any transactions for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
-                self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
-                return;
-            },
-            _ => unreachable!(),
-        }
-
-        match proof.compute_root_from_values(&transactions, state.previous_result.as_ref()) {
-            Ok(result) => {
-                let mut state = RwLockUpgradableReadGuard::upgrade(state);
-                // Check that root corresponds to root for this epoch
-                if result.root() != &expected_root {
-                    warn!("We received transactions with an invalid proof for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
-                    self.peer.channel.close(CloseType::InvalidEpochTransactions);
-                    return;
-                }
-
-                // Append transactions.
-                state.transactions_cache.append(&mut transactions);
-                state.previous_result = Some(result);
-
-                if proof.is_empty() {
-                    self.timers.clear_delay(&MacroBlockSyncTimer::EpochTransactions(epoch_transactions.epoch));
-
-                    let transactions = mem::replace(&mut state.transactions_cache, Vec::new());
-                    state.previous_result = None;
-
-                    let block = state.block_cache.pop_front().unwrap();
-
-                    drop(state);
-                    self.complete_epoch(block, &transactions);
-                } else {
-                    // Reset delay to allow for more time.
-                    let weak = self.self_weak.clone();
-                    self.timers.reset_delay(MacroBlockSyncTimer::EpochTransactions(epoch_transactions.epoch), move || {
-                        let this = upgrade_weak!(weak);
-                        // TODO: What should happen if we don't receive any response?
-                        // For now, just drop the connection with that peer.
-                        this.peer.channel.close(CloseType::GetEpochTransactionsTimeout);
-                    }, Self::REQUEST_TIMEOUT);
-                }
-            },
-            Err(e) => {
-                warn!("We received an invalid merkle proof ({:?}) from {} - discarding and closing the channel", e, self.peer.peer_address());
-                self.peer.channel.close(CloseType::InvalidEpochTransactions);
-                return;
-            },
-        }
+        // Epoch transactions we requested are routed to the request, see `start_processing`.
+        // Late responses to requests that timed out end up here.
+        debug!("We didn't request transactions for epoch {} from {} - discarding", epoch_transactions.epoch, self.peer.peer_address());
     }
 
     fn on_no_new_objects_announced(&self) {
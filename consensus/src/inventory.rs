@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
+use futures::{Future, Stream};
 use parking_lot::{Mutex, RwLock};
 use weak_table::PtrWeakHashSet;
 
@@ -16,10 +17,15 @@ use macros::upgrade_weak;
 use mempool::{Mempool, ReturnCode};
 use network::connection::close_type::CloseType;
 use network::Peer;
+use network::peer_channel::RequestError;
 use network_messages::{
+    BlockTransactionsMessage,
+    Capabilities,
+    CompactBlockMessage,
     EpochTransactionsMessage,
     GetBlocksDirection,
     GetBlocksMessage,
+    GetBlockTransactionsMessage,
     InvVector,
     InvVectorType,
     Message,
@@ -153,14 +159,13 @@ pub enum InventoryEvent<BE: BlockError> {
     AllObjectsReceived,
     BlockProcessed(Blake2bHash, Result<PushResult, PushError<BE>>),
     TransactionProcessed(Blake2bHash, ReturnCode),
-    GetBlocksTimeout,
+    SyncFailed,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum InventoryAgentTimer {
     GetDataThrottle,
     GetData,
-    GetBlocks,
     TxInvVectors,
     FreeTxInvVectors,
 }
@@ -223,6 +228,10 @@ struct InventoryAgentState {
     /// All objects that were requested from the peer but not received yet.
     objects_that_flew: HashSet<InvVector>,
 
+    /// Compact blocks waiting for the transactions we requested from the peer.
+    /// Transactions that we found in our mempool are already filled in.
+    pending_compact_blocks: HashMap<Blake2bHash, (CompactBlockMessage, Vec<Option<Transaction>>)>,
+
     /// The rate limit for getblocks messages.
     get_blocks_limit: RateLimit,
 
@@ -313,6 +322,8 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
 
                 objects_that_flew: HashSet::new(),
 
+                pending_compact_blocks: HashMap::new(),
+
                 get_blocks_limit: RateLimit::new_per_minute(Self::GET_BLOCKS_RATE_LIMIT),
 
                 // Initially, we don't announce anything to the peer until it tells us otherwise.
@@ -345,6 +356,12 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         P::MessageAdapter::register_header_listener(msg_notifier, weak_passthru_listener(
             Arc::downgrade(this),
             |this, header| this.on_header(header)));
+        msg_notifier.compact_block_albatross.write().register(weak_passthru_listener(
+            Arc::downgrade(this),
+            |this, msg: CompactBlockMessage| this.on_compact_block(msg)));
+        msg_notifier.block_transactions.write().register(weak_passthru_listener(
+            Arc::downgrade(this),
+            |this, msg: BlockTransactionsMessage| this.on_block_transactions(msg)));
         msg_notifier.tx.write().register(weak_passthru_listener(
             Arc::downgrade(this),
             |this, msg: TxMessage| this.on_tx(msg)));
@@ -361,6 +378,9 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         msg_notifier.get_header.write().register(weak_passthru_listener(
             Arc::downgrade(this),
             |this, vectors: Vec<InvVector>| this.on_get_header(vectors)));
+        msg_notifier.get_block_transactions.write().register(weak_passthru_listener(
+            Arc::downgrade(this),
+            |this, msg: GetBlockTransactionsMessage| this.on_get_block_transactions(msg)));
         msg_notifier.mempool.write().register(weak_passthru_listener(
             Arc::downgrade(this),
             |this, _ | this.on_mempool()));
@@ -379,6 +399,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         |this, event| {
             match event {
                 SyncEvent::BlockProcessed(hash, result) => this.notifier.read().notify(InventoryEvent::BlockProcessed(hash, result)),
+                SyncEvent::RequestFailed(_) => this.notifier.read().notify(InventoryEvent::SyncFailed),
             }
         }));
 
@@ -400,14 +421,27 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
     }
 
     pub fn get_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16, timeout: Duration) {
+        let request = self.sync_protocol.get_blocks_request(locators, max_results);
+
+        // The inv the peer answers with is processed in `on_inv`, we only care about failures here.
         let weak = self.self_weak.clone();
-        self.timers.set_delay(InventoryAgentTimer::GetBlocks, move || {
-            let this = upgrade_weak!(weak);
-            this.timers.clear_delay(&InventoryAgentTimer::GetBlocks);
-            this.notifier.read().notify(InventoryEvent::GetBlocksTimeout);
-        }, timeout);
+        let request = self.peer.channel.request(request, timeout)
+            .for_each(|_| Ok(()))
+            .map_err(move |e| {
+                let this = upgrade_weak!(weak);
+                this.on_get_blocks_failed(e);
+            });
+        tokio::spawn(request);
+    }
+
+    fn on_get_blocks_failed(&self, error: RequestError) {
+        // If the channel was closed, the peer is gone and the sync continues with another one.
+        if error == RequestError::ChannelClosed {
+            return;
+        }
 
-        self.sync_protocol.request_blocks(locators, max_results);
+        debug!("Request for blocks from {} failed: {}", self.peer.peer_address(), error);
+        self.notifier.read().notify(InventoryEvent::SyncFailed);
     }
 
     pub fn mempool(&self) {
@@ -447,9 +481,6 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
             state.waiting_free_tx_inv_vectors.remove(&FreeTransactionVector::from_vector(vector, 0));
         }
 
-        // XXX Clear get_blocks timeout.
-        self.timers.clear_delay(&InventoryAgentTimer::GetBlocks);
-
         // Check which of the advertised objects we know.
         // Request unknown objects, ignore known ones.
         let num_vectors = vectors.len();
@@ -478,6 +509,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
                         self.notifier.read().notify(InventoryEvent::KnownTransactionAnnounced);
                     }
                 }
+                InvVectorType::CompactBlock => (), // Never announced, see `should_request_data`.
                 InvVectorType::Error => () // XXX Why do we have this??
             }
         }
@@ -511,7 +543,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         }
     }
 
-    fn on_block(&self, mut block: <P::Blockchain as AbstractBlockchain>::Block) {
+    fn on_block(&self, block: <P::Blockchain as AbstractBlockchain>::Block) {
         //let lock = self.mutex.lock();
 
         let hash = block.hash();
@@ -527,6 +559,13 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         // Give up read lock before notifying.
         drop(state);
 
+        self.process_block(block, &vector);
+    }
+
+    fn process_block(&self, mut block: <P::Blockchain as AbstractBlockchain>::Block, vector: &InvVector) {
+        // A full block may still arrive after we fell back from a compact block.
+        self.state.write().pending_compact_blocks.remove(&vector.hash);
+
         // Use already known (verified) transactions from mempool to set validity.
         if let Some(ref mut transactions) = block.transactions_mut() {
             for i in 0..transactions.len() {
@@ -540,7 +579,94 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         self.sync_protocol.on_block(block);
 
         // Mark object as received.
-        self.on_object_received(&vector);
+        self.on_object_received(vector);
+    }
+
+    fn on_compact_block(&self, compact_block: CompactBlockMessage) {
+        let hash = compact_block.block_hash();
+        trace!("[COMPACT-BLOCK] #{} ({} txs) from {}", compact_block.header.block_number, compact_block.short_ids.len(), self.peer.peer_address());
+
+        // Check if we have requested this block.
+        let vector = InvVector::from_block_hash(hash.clone());
+        let state = self.state.read();
+        if !state.objects_in_flight.contains(&vector) && !state.objects_that_flew.contains(&vector) {
+            warn!("Unsolicited compact block from {} - discarding", self.peer.peer_address());
+            return;
+        }
+        drop(state);
+
+        // Don't request any transactions for a block we would reject anyway.
+        if let Err(e) = P::verify_compact_block(&self.blockchain, &compact_block) {
+            debug!("Invalid compact block {} from {}", hash, self.peer.peer_address());
+            self.on_object_received(&vector);
+            self.notifier.read().notify(InventoryEvent::BlockProcessed(hash, Err(e)));
+            return;
+        }
+
+        // Fill in the transactions we already have.
+        let transactions: Vec<Option<Transaction>> = self.mempool.get_transactions_by_short_ids(&hash, &compact_block.short_ids)
+            .into_iter()
+            .map(|tx| tx.map(|tx| Transaction::clone(&tx)))
+            .collect();
+
+        let missing: Vec<u16> = transactions.iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.is_none())
+            .map(|(i, _)| i as u16)
+            .collect();
+
+        if missing.is_empty() {
+            let transactions = transactions.into_iter().map(Option::unwrap).collect();
+            self.complete_compact_block(compact_block, transactions, &vector);
+            return;
+        }
+
+        trace!("[COMPACT-BLOCK] Requesting {} missing txs from {}", missing.len(), self.peer.peer_address());
+        self.state.write().pending_compact_blocks.insert(hash.clone(), (compact_block, transactions));
+        // Compact blocks are only requested for new blocks, so don't let the request wait.
+        if self.peer.channel.send_priority(GetBlockTransactionsMessage::new(hash, missing)).is_err() {
+            self.peer.channel.close(CloseType::SendFailed);
+        }
+    }
+
+    fn on_block_transactions(&self, msg: BlockTransactionsMessage) {
+        trace!("[BLOCK-TRANSACTIONS] {} txs from {}", msg.transactions.len(), self.peer.peer_address());
+
+        let pending = self.state.write().pending_compact_blocks.remove(&msg.block_hash);
+        let (compact_block, mut transactions) = match pending {
+            Some(pending) => pending,
+            None => {
+                warn!("Unsolicited block transactions from {} - discarding", self.peer.peer_address());
+                return;
+            },
+        };
+
+        let vector = InvVector::from_block_hash(msg.block_hash);
+        let mut received = msg.transactions.into_iter();
+        for tx in transactions.iter_mut().filter(|tx| tx.is_none()) {
+            *tx = received.next();
+        }
+
+        // The peer has to send exactly the transactions we asked for.
+        let transactions: Option<Vec<Transaction>> = transactions.into_iter().collect();
+        match transactions {
+            Some(transactions) if received.next().is_none() => self.complete_compact_block(compact_block, transactions, &vector),
+            _ => self.request_full_block(&vector),
+        }
+    }
+
+    fn complete_compact_block(&self, compact_block: CompactBlockMessage, transactions: Vec<Transaction>, vector: &InvVector) {
+        match P::MessageAdapter::block_from_compact(compact_block, transactions) {
+            Some(block) => self.process_block(block, vector),
+            None => self.request_full_block(vector),
+        }
+    }
+
+    /// Falls back to requesting the full block if a compact block can't be reconstructed.
+    /// The block stays in flight, so the full block is accepted when it arrives.
+    fn request_full_block(&self, vector: &InvVector) {
+        debug!("Could not reconstruct compact block {} from {}, requesting full block", vector.hash, self.peer.peer_address());
+        self.peer.channel.send_or_close(Message::GetData(vec![vector.clone()]));
     }
 
     fn on_header(&self, header: <<P::Blockchain as AbstractBlockchain>::Block as Block>::Header) {
@@ -621,11 +747,22 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
     fn on_not_found(&self, vectors: Vec<InvVector>) {
         trace!("[NOTFOUND] {} vectors", vectors.len());
 
+        // We keep track of compact block requests as block requests.
+        let vectors: Vec<InvVector> = vectors.into_iter()
+            .map(|vector| match vector.ty {
+                InvVectorType::CompactBlock => InvVector::from_block_hash(vector.hash),
+                _ => vector,
+            })
+            .collect();
+
         // Only consider vectors that we requested.
-        let state = self.state.read();
+        let mut state = self.state.write();
         let expected_vectors = vectors.iter()
             .filter(|vector| state.objects_in_flight.contains(*vector))
             .collect::<Vec<&InvVector>>();
+        for vector in &expected_vectors {
+            state.pending_compact_blocks.remove(&vector.hash);
+        }
         drop(state);
 
         // Report objects as not received.
@@ -641,6 +778,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
 
     fn on_close(&self) {
         self.timers.clear_all();
+        self.state.write().pending_compact_blocks.clear();
     }
 
     fn queue_vector(&self, vector: InvVector) {
@@ -648,7 +786,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         match vector.ty {
             InvVectorType::Block => state.blocks_to_request.enqueue(vector),
             InvVectorType::Transaction => state.txs_to_request.enqueue(vector),
-            InvVectorType::Error => () // XXX Get rid of this!
+            InvVectorType::CompactBlock | InvVectorType::Error => () // XXX Get rid of this!
         }
         self.request_vectors_throttled(&mut *state);
     }
@@ -695,10 +833,20 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         let num_blocks = state.blocks_to_request.len().min(Self::REQUEST_VECTORS_MAX);
         let num_txs = Self::REQUEST_VECTORS_MAX - num_blocks; // `dequeue_multi` takes care of the above comparison
 
+        // Outside of sync, new blocks are requested in compact form from peers that support it.
+        // They are still tracked as blocks.
+        let compact_blocks = P::MessageAdapter::COMPACT_BLOCKS
+            && !state.bypass_mgr
+            && self.peer.capabilities.contains(Capabilities::COMPACT_BLOCKS);
+
         let mut vectors = Vec::new();
         for vector in state.blocks_to_request.dequeue_multi(num_blocks) {
             state.objects_in_flight.insert(vector.clone());
-            vectors.push(vector);
+            if compact_blocks {
+                vectors.push(InvVector::new(InvVectorType::CompactBlock, vector.hash));
+            } else {
+                vectors.push(vector);
+            }
         }
         for vector in state.txs_to_request.dequeue_multi(num_txs) {
             state.objects_in_flight.insert(vector.clone());
@@ -756,6 +904,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         // TODO Optimize
         for vector in &vectors {
             state.objects_that_flew.insert(vector.clone());
+            state.pending_compact_blocks.remove(&vector.hash);
         }
         drop(state);
 
@@ -815,9 +964,12 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
             },
         );
 
-        let vectors = blocks.iter().map(|block| {
-            InvVector::from_block_hash(block.hash())
-        }).collect();
+        // Only announce blocks we can still serve, the bodies of old blocks might have been pruned.
+        let vectors = blocks.iter()
+            .map(|block| block.hash())
+            .take_while(|hash| self.blockchain.has_block_body(hash))
+            .map(InvVector::from_block_hash)
+            .collect();
 
         // Send the vectors back to the requesting peer.
         self.peer.channel.send_or_close(Message::Inv(vectors));
@@ -874,7 +1026,11 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         {
             let mut state = self.state.write();
             for vector in vectors.iter() {
-                state.known_objects.insert(vector.clone());
+                let vector = match vector.ty {
+                    InvVectorType::CompactBlock => InvVector::from_block_hash(vector.hash.clone()),
+                    _ => vector.clone(),
+                };
+                state.known_objects.insert(vector);
             }
         }
 
@@ -885,13 +1041,17 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
 
         for vector in vectors {
             match vector.ty {
-                InvVectorType::Block => {
+                InvVectorType::Block | InvVectorType::CompactBlock => {
                     // TODO raw blocks. Needed?
+                    // Blocks whose body has been pruned are reported as not found.
                     let block_opt = self.blockchain.get_block(&vector.hash, true);
                     match block_opt {
                         Some(block) => {
-                            if self.peer.channel.send(P::MessageAdapter::new_block_message(block)).is_err() {
-                                self.peer.channel.close(CloseType::SendFailed);
+                            let msg = match vector.ty {
+                                InvVectorType::CompactBlock => P::MessageAdapter::new_compact_block_message(block),
+                                _ => P::MessageAdapter::new_block_message(block),
+                            };
+                            if !self.send_block_data(&vector.hash, msg) {
                                 return;
                             }
                         },
@@ -954,6 +1114,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
                     }
                 }
                 InvVectorType::Transaction => {} // XXX JavaScript client errors here
+                InvVectorType::CompactBlock => {}
                 InvVectorType::Error => {} // XXX Why do we have this??
             }
         }
@@ -964,6 +1125,41 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         }
     }
 
+    fn on_get_block_transactions(&self, msg: GetBlockTransactionsMessage) {
+        trace!("[GET-BLOCK-TRANSACTIONS] {} txs of {} from {}", msg.indexes.len(), msg.block_hash, self.peer.peer_address());
+
+        let transactions = self.blockchain.get_block(&msg.block_hash, true).and_then(|block| {
+            let block_transactions = block.transactions()?;
+            msg.indexes.iter()
+                .map(|&i| block_transactions.get(i as usize).cloned())
+                .collect::<Option<Vec<Transaction>>>()
+        });
+
+        match transactions {
+            Some(transactions) => {
+                self.send_block_data(&msg.block_hash, BlockTransactionsMessage::new(msg.block_hash.clone(), transactions));
+            },
+            None => self.peer.channel.send_or_close(Message::NotFound(vec![InvVector::from_block_hash(msg.block_hash)])),
+        }
+    }
+
+    /// Sends data of the block `hash` that the peer requested. Only our head is sent regardless
+    /// of the upload limit, as the peer most likely requested it because we just relayed it.
+    /// Older blocks are served to syncing peers and have to wait.
+    /// Returns `false` if sending failed and the connection was closed.
+    fn send_block_data(&self, hash: &Blake2bHash, msg: Message) -> bool {
+        let result = if *hash == self.blockchain.head_hash() {
+            self.peer.channel.send_priority(msg)
+        } else {
+            self.peer.channel.send(msg)
+        };
+        if result.is_err() {
+            self.peer.channel.close(CloseType::SendFailed);
+            return false;
+        }
+        true
+    }
+
     fn on_epoch_transactions(&self, epoch_transactions_message: EpochTransactionsMessage) {
         self.sync_protocol.on_epoch_transactions(epoch_transactions_message);
     }
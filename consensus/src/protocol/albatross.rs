@@ -1,5 +1,6 @@
 use blockchain_albatross::Blockchain;
-use network_messages::AlbatrossMessageAdapter;
+use blockchain_albatross::blockchain::PushError;
+use network_messages::{AlbatrossMessageAdapter, CompactBlockMessage};
 
 use crate::protocol::ConsensusProtocol;
 use crate::consensus_agent::sync::FullSync;
@@ -9,4 +10,8 @@ impl ConsensusProtocol for AlbatrossConsensusProtocol {
     type Blockchain = Blockchain;
     type MessageAdapter = AlbatrossMessageAdapter;
     type SyncProtocol = FullSync<Self::Blockchain>;
+
+    fn verify_compact_block(blockchain: &Blockchain, compact_block: &CompactBlockMessage) -> Result<(), PushError> {
+        blockchain.verify_micro_header(&compact_block.header, &compact_block.justification)
+    }
 }
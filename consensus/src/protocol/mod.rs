@@ -1,5 +1,6 @@
-use blockchain_base::AbstractBlockchain;
-use network_messages::MessageAdapter;
+use block_base::Block;
+use blockchain_base::{AbstractBlockchain, PushError};
+use network_messages::{CompactBlockMessage, MessageAdapter};
 
 use crate::consensus_agent::sync::SyncProtocol;
 
@@ -10,4 +11,8 @@ pub trait ConsensusProtocol {
     type Blockchain: AbstractBlockchain + 'static;
     type MessageAdapter: MessageAdapter<<Self::Blockchain as AbstractBlockchain>::Block> + 'static;
     type SyncProtocol: SyncProtocol<Self::Blockchain> + 'static;
+
+    /// Verifies the header and justification of a compact block before its missing
+    /// transactions are requested from the peer.
+    fn verify_compact_block(blockchain: &Self::Blockchain, compact_block: &CompactBlockMessage) -> Result<(), PushError<<<Self::Blockchain as AbstractBlockchain>::Block as Block>::Error>>;
 }
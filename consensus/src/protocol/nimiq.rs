@@ -1,5 +1,5 @@
-use blockchain::Blockchain;
-use network_messages::NimiqMessageAdapter;
+use blockchain::{Blockchain, PushError};
+use network_messages::{CompactBlockMessage, NimiqMessageAdapter};
 
 use crate::consensus_agent::sync::FullSync;
 use crate::protocol::ConsensusProtocol;
@@ -9,4 +9,9 @@ impl ConsensusProtocol for NimiqConsensusProtocol {
     type Blockchain = Blockchain;
     type MessageAdapter = NimiqMessageAdapter;
     type SyncProtocol = FullSync<Self::Blockchain>;
+
+    fn verify_compact_block(_blockchain: &Blockchain, _compact_block: &CompactBlockMessage) -> Result<(), PushError> {
+        // Compact blocks are never requested with this protocol, see `NimiqMessageAdapter::COMPACT_BLOCKS`.
+        Ok(())
+    }
 }
@@ -0,0 +1,281 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+
+use beserial::Deserialize;
+use nimiq_block_albatross::{Block, ForkProof, MacroBlock, MacroExtrinsics, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage, SignedViewChange, ViewChange, ViewChangeProof, ViewChangeProofBuilder};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_blockchain_base::AbstractBlockchain;
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_consensus::{AlbatrossConsensusProtocol, Consensus};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_mempool::MempoolConfig;
+use nimiq_network::network_config::{NetworkConfig, Seed};
+use nimiq_network::websocket::LoopbackHub;
+use nimiq_network_primitives::address::PeerUri;
+use nimiq_network_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
+
+/// Secret key of the only validator in `network-primitives/src/genesis/unit-albatross.toml`.
+const SECRET_KEY: &str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+type AlbatrossConsensus = Consensus<AlbatrossConsensusProtocol>;
+
+fn validator_key() -> KeyPair {
+    KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap())
+}
+
+/// Albatross full nodes on the unit genesis that run in this process and connect over a
+/// loopback hub. Node 0 is the seed of all other nodes.
+struct Cluster {
+    runtime: Runtime,
+    nodes: Vec<Arc<AlbatrossConsensus>>,
+}
+
+impl Cluster {
+    fn new(size: usize) -> Self {
+        let hub = LoopbackHub::new();
+        let mut seed_uri = None;
+        let mut nodes = Vec::with_capacity(size);
+
+        for i in 0..size {
+            let host = format!("node{}.loopback", i);
+            let mut network_config = NetworkConfig::new_loopback_network_config(hub.clone(), host.clone(), 8443, true);
+            network_config.set_additional_seeds(seed_uri.iter()
+                .map(|uri: &String| Seed::new_peer(PeerUri::from_str(uri).unwrap()))
+                .collect());
+            network_config.init_volatile();
+            if seed_uri.is_none() {
+                seed_uri = Some(format!("ws://{}:8443/{}", host, network_config.public_key().to_hex()));
+            }
+
+            let env = VolatileEnvironment::new(20).unwrap();
+            nodes.push(AlbatrossConsensus::new(env, NetworkId::UnitAlbatross, network_config, MempoolConfig::default()).unwrap());
+        }
+
+        Cluster {
+            runtime: Runtime::new().unwrap(),
+            nodes,
+        }
+    }
+
+    /// Connects all nodes to node 0 and waits until each of them established consensus.
+    fn start(&mut self) {
+        for node in &self.nodes {
+            let network = Arc::clone(&node.network);
+            self.runtime.block_on(future::lazy(move || {
+                network.initialize().unwrap();
+                network.connect().unwrap();
+                Ok::<(), ()>(())
+            })).unwrap();
+        }
+
+        let size = self.nodes.len();
+        assert!(self.run_until(Duration::from_secs(30), |nodes| {
+            nodes[0].network.peer_count() == size - 1
+                && nodes.iter().all(|node| node.established())
+        }), "Nodes did not establish consensus");
+    }
+
+    /// Runs the runtime until `condition` holds. Returns `false` if it didn't within `timeout`.
+    fn run_until<F: Fn(&[Arc<AlbatrossConsensus>]) -> bool>(&mut self, timeout: Duration, condition: F) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !condition(&self.nodes) {
+            if Instant::now() > deadline {
+                return false;
+            }
+            self.runtime.block_on(Delay::new(Instant::now() + Duration::from_millis(10))).unwrap();
+        }
+        true
+    }
+
+    /// Runs until all nodes have the head of node 0.
+    fn wait_for_sync(&mut self) -> bool {
+        self.run_until(Duration::from_secs(30), |nodes| {
+            let head_hash = nodes[0].blockchain.head_hash();
+            nodes.iter().all(|node| node.blockchain.head_hash() == head_hash)
+        })
+    }
+
+    /// Pushes `block` to node `i` on the runtime, so that it is relayed to the node's peers.
+    fn push(&mut self, i: usize, block: Block) -> PushResult {
+        let blockchain = Arc::clone(&self.nodes[i].blockchain);
+        self.runtime.block_on(future::lazy(move || {
+            Ok::<PushResult, ()>(blockchain.push(block).expect("Block was rejected"))
+        })).unwrap()
+    }
+}
+
+/// Produces blocks on top of a node's chain with the key of the unit genesis validator, which
+/// owns all slots.
+struct Producer {
+    blockchain: Arc<Blockchain>,
+    producer: BlockProducer,
+    key_pair: KeyPair,
+}
+
+impl Producer {
+    fn new(node: &AlbatrossConsensus) -> Self {
+        let key_pair = validator_key();
+        Producer {
+            blockchain: Arc::clone(&node.blockchain),
+            producer: BlockProducer::new_without_mempool(Arc::clone(&node.blockchain), key_pair.clone()),
+            key_pair,
+        }
+    }
+
+    /// Returns the next block in `view_number`, including a view change proof if that isn't
+    /// the next view. The block is not pushed.
+    fn next_block(&self, view_number: u32, fork_proofs: Vec<ForkProof>, extra_data: Vec<u8>) -> Block {
+        let height = self.blockchain.head_height() + 1;
+        let timestamp = 1565713920000 + u64::from(height) * 2000;
+
+        let view_change_proof = if self.blockchain.next_view_number() == view_number {
+            None
+        } else {
+            Some(self.view_change_proof(view_number))
+        };
+
+        if policy::is_macro_block_at(height) {
+            let (proposal, extrinsics) = self.producer.next_macro_block_proposal(timestamp, view_number, view_change_proof);
+            Block::Macro(self.sign_macro_block(proposal, extrinsics))
+        } else {
+            Block::Micro(self.producer.next_micro_block(fork_proofs, timestamp, view_number, extra_data, view_change_proof))
+        }
+    }
+
+    fn sign_macro_block(&self, proposal: PbftProposal, extrinsics: MacroExtrinsics) -> MacroBlock {
+        let block_hash = proposal.header.hash::<Blake2bHash>();
+        let prepare = SignedPbftPrepareMessage::from_message(
+            PbftPrepareMessage { block_hash: block_hash.clone() },
+            &self.key_pair.secret,
+            0);
+        let commit = SignedPbftCommitMessage::from_message(
+            PbftCommitMessage { block_hash },
+            &self.key_pair.secret,
+            0);
+
+        let mut pbft_proof = PbftProofBuilder::new();
+        pbft_proof.add_prepare_signature(&self.key_pair.public, policy::SLOTS, &prepare);
+        pbft_proof.add_commit_signature(&self.key_pair.public, policy::SLOTS, &commit);
+
+        MacroBlock {
+            header: proposal.header,
+            justification: Some(pbft_proof.build()),
+            extrinsics: Some(extrinsics),
+        }
+    }
+
+    fn view_change_proof(&self, view_number: u32) -> ViewChangeProof {
+        let view_change = SignedViewChange::from_message(
+            ViewChange {
+                block_number: self.blockchain.head_height() + 1,
+                new_view_number: view_number,
+                prev_seed: self.blockchain.head().seed().clone(),
+            },
+            &self.key_pair.secret,
+            0);
+
+        let mut view_change_proof = ViewChangeProofBuilder::new();
+        view_change_proof.add_signature(&self.key_pair.public, policy::SLOTS, &view_change);
+        view_change_proof.build()
+    }
+}
+
+#[test]
+fn it_syncs_nodes_that_join_later() {
+    let mut cluster = Cluster::new(3);
+
+    // Produce blocks past the first macro block before anyone connects.
+    let producer = Producer::new(&cluster.nodes[0]);
+    while cluster.nodes[0].blockchain.head_height() < policy::EPOCH_LENGTH + 2 {
+        let block = producer.next_block(0, vec![], vec![]);
+        assert_eq!(cluster.push(0, block), PushResult::Extended);
+    }
+
+    cluster.start();
+    assert!(cluster.wait_for_sync());
+    for node in &cluster.nodes {
+        assert_eq!(node.blockchain.head_height(), policy::EPOCH_LENGTH + 2);
+    }
+}
+
+#[test]
+fn it_relays_new_blocks_to_all_nodes() {
+    let mut cluster = Cluster::new(4);
+    cluster.start();
+
+    let producer = Producer::new(&cluster.nodes[0]);
+    for _ in 0..3 {
+        let block = producer.next_block(0, vec![], vec![]);
+        let block_hash = block.hash();
+        assert_eq!(cluster.push(0, block), PushResult::Extended);
+        assert!(cluster.wait_for_sync());
+        assert!(cluster.nodes.iter().all(|node| node.blockchain.head_hash() == block_hash));
+    }
+}
+
+#[test]
+fn it_rebranches_all_nodes_to_a_view_change() {
+    let mut cluster = Cluster::new(3);
+    cluster.start();
+
+    let producer = Producer::new(&cluster.nodes[0]);
+    let block = producer.next_block(0, vec![], vec![]);
+    assert_eq!(cluster.push(0, block), PushResult::Extended);
+    assert!(cluster.wait_for_sync());
+
+    // Both blocks extend the current head: one in view 0 from node 1, and one after a view
+    // change to view 1 from node 0.
+    let block_view0 = Producer::new(&cluster.nodes[1]).next_block(0, vec![], vec![]);
+    let block_view1 = producer.next_block(1, vec![], vec![]);
+    assert_eq!(block_view0.block_number(), block_view1.block_number());
+
+    assert_eq!(cluster.push(1, block_view0.clone()), PushResult::Extended);
+    assert!(cluster.wait_for_sync());
+    assert_eq!(cluster.nodes[0].blockchain.head_hash(), block_view0.hash());
+
+    // The view change wins on every node.
+    assert_eq!(cluster.push(0, block_view1.clone()), PushResult::Rebranched);
+    assert!(cluster.wait_for_sync());
+    for node in &cluster.nodes {
+        assert_eq!(node.blockchain.head_hash(), block_view1.hash());
+        assert_eq!(node.blockchain.view_number(), 1);
+    }
+}
+
+#[test]
+fn it_relays_fork_proofs_in_blocks() {
+    let mut cluster = Cluster::new(3);
+    cluster.start();
+
+    // The validator signs two different blocks for the same slot.
+    let producer = Producer::new(&cluster.nodes[0]);
+    let block1 = producer.next_block(0, vec![], vec![1]).unwrap_micro();
+    let block2 = producer.next_block(0, vec![], vec![2]).unwrap_micro();
+    let fork_proof = ForkProof {
+        header1: block1.header.clone(),
+        header2: block2.header.clone(),
+        justification1: block1.justification.signature.clone(),
+        justification2: block2.justification.signature.clone(),
+    };
+
+    assert_eq!(cluster.push(0, Block::Micro(block1)), PushResult::Extended);
+    assert!(cluster.wait_for_sync());
+
+    let block = producer.next_block(0, vec![fork_proof.clone()], vec![]);
+    assert_eq!(cluster.push(0, block), PushResult::Extended);
+    assert!(cluster.wait_for_sync());
+
+    // Every node validated the fork proof and stored it with the block.
+    for node in &cluster.nodes {
+        let head = node.blockchain.get_block(&node.blockchain.head_hash(), false, true).unwrap();
+        assert_eq!(head.unwrap_micro().extrinsics.unwrap().fork_proofs, vec![fork_proof.clone()]);
+    }
+}
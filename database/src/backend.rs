@@ -0,0 +1,92 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+
+use super::DatabaseFlags;
+
+/// A storage engine behind an `Environment`.
+///
+/// Backends operate on raw bytes only. Keys and values are converted from and to their typed
+/// representation by the `Environment`, `Database`, `Transaction` and `Cursor` wrappers.
+pub trait Backend: fmt::Debug + Send + Sync {
+    fn open_database(&self, name: String, flags: DatabaseFlags) -> Box<dyn BackendDatabase>;
+
+    fn read_transaction<'env>(&'env self) -> Box<dyn BackendTransaction + 'env>;
+
+    fn write_transaction<'env>(&'env self) -> Box<dyn BackendWriteTransaction + 'env>;
+
+    /// Removes all data stored by this environment.
+    fn drop_database(&self) -> io::Result<()>;
+}
+
+/// A handle to a single database within a `Backend`.
+pub trait BackendDatabase: fmt::Debug + Send + Sync {
+    /// Used by backends to get back their own handle type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Values are borrowed from the transaction where possible. Since every modification requires a
+/// mutable borrow of the transaction, they stay valid for as long as they are borrowed.
+pub trait BackendTransaction: fmt::Debug {
+    fn get(&self, db: &dyn BackendDatabase, key: &[u8]) -> Option<Cow<[u8]>>;
+
+    fn cursor<'txn>(&'txn self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendCursor + 'txn>;
+}
+
+pub trait BackendWriteTransaction: BackendTransaction {
+    /// Reserves `value_size` bytes for the value of `key` and lets `write` fill them in.
+    fn put_reserve(&mut self, db: &dyn BackendDatabase, key: &[u8], value_size: usize, write: &dyn Fn(&mut [u8]));
+
+    fn put(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]);
+
+    fn remove(&mut self, db: &dyn BackendDatabase, key: &[u8]);
+
+    fn remove_item(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]);
+
+    fn commit(self: Box<Self>);
+
+    fn write_cursor<'txn>(&'txn mut self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendWriteCursor + 'txn>;
+}
+
+/// A cursor over raw key/value pairs. The semantics of each method follow those of
+/// `cursor::ReadCursor`. Returned keys and values borrow the cursor until its next operation.
+pub trait BackendCursor {
+    fn first(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn first_duplicate(&mut self) -> Option<Cow<[u8]>>;
+
+    fn last(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn last_duplicate(&mut self) -> Option<Cow<[u8]>>;
+
+    fn seek_key_value(&mut self, key: &[u8], value: &[u8]) -> bool;
+
+    fn seek_key_nearest_value(&mut self, key: &[u8], value: &[u8]) -> Option<Cow<[u8]>>;
+
+    fn get_current(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn next(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn next_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn next_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn prev(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn prev_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn prev_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn seek_key(&mut self, key: &[u8]) -> Option<Cow<[u8]>>;
+
+    fn seek_key_both(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn seek_range_key(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)>;
+
+    fn count_duplicates(&mut self) -> usize;
+}
+
+pub trait BackendWriteCursor: BackendCursor {
+    fn remove(&mut self);
+}
@@ -3,17 +3,22 @@ extern crate log;
 
 use std::borrow::Cow;
 use std::io;
+use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::Arc;
 
 use bitflags::bitflags;
 use lmdb_zero;
 
+use crate::backend::{Backend, BackendCursor, BackendDatabase, BackendTransaction, BackendWriteCursor, BackendWriteTransaction};
 use crate::cursor::{ReadCursor, WriteCursor as WriteCursorTrait};
 pub use crate::traits::{AsDatabaseBytes, FromDatabaseValue, IntoDatabaseValue};
 
-#[macro_use]
+pub mod backend;
 pub mod cursor;
 pub mod lmdb;
+pub mod memory;
+pub mod migration;
 pub mod volatile;
 pub mod traits;
 
@@ -33,84 +38,64 @@ bitflags! {
     }
 }
 
+/// An environment holding a set of named databases on top of a storage `Backend`.
 #[derive(Clone, Debug)]
-pub enum Environment {
-    Volatile(volatile::VolatileEnvironment),
-    Persistent(lmdb::LmdbEnvironment),
+pub struct Environment {
+    backend: Arc<dyn Backend>,
 }
 
 impl Environment {
+    /// Creates an environment from any storage backend.
+    /// The built-in backends are created using `LmdbEnvironment`, `VolatileEnvironment` and `MemoryEnvironment`.
+    pub fn new<B: Backend + 'static>(backend: B) -> Self {
+        Environment { backend: Arc::new(backend) }
+    }
+
     pub fn open_database(&self, name: String) -> Database {
-        match *self {
-            Environment::Volatile(ref env) => { Database::Volatile(env.open_database(name, Default::default())) }
-            Environment::Persistent(ref env) => { Database::Persistent(env.open_database(name, Default::default())) }
-        }
+        self.open_database_with_flags(name, Default::default())
     }
 
     pub fn open_database_with_flags(&self, name: String, flags: DatabaseFlags) -> Database {
-        match *self {
-            Environment::Volatile(ref env) => { Database::Volatile(env.open_database(name, flags)) }
-            Environment::Persistent(ref env) => { Database::Persistent(env.open_database(name, flags)) }
-        }
+        Database { db: self.backend.open_database(name, flags) }
     }
 
     pub fn close(self) {}
 
     pub fn drop_database(self) -> io::Result<()> {
-        match self {
-            Environment::Volatile(env) => { env.drop_database() }
-            Environment::Persistent(env) => { env.drop_database() }
-        }
+        self.backend.drop_database()
     }
 }
 
 #[derive(Debug)]
-pub enum Database {
-    Volatile(volatile::VolatileDatabase),
-    Persistent(lmdb::LmdbDatabase),
+pub struct Database {
+    db: Box<dyn BackendDatabase>,
 }
 
-impl Database {
-    fn volatile(&self) -> Option<&volatile::VolatileDatabase> {
-        if let Database::Volatile(ref db) = self {
-            return Some(db);
-        }
-        None
-    }
-
-    fn persistent(&self) -> Option<&lmdb::LmdbDatabase> {
-        match self {
-            Database::Persistent(ref db) => Some(db),
-            Database::Volatile(ref db) => Some(db.as_lmdb()),
-        }
-    }
+#[derive(Debug)]
+enum TransactionInner<'env> {
+    Read(Box<dyn BackendTransaction + 'env>),
+    Write(Box<dyn BackendWriteTransaction + 'env>),
 }
 
 #[derive(Debug)]
-pub enum Transaction<'env> {
-    VolatileRead(volatile::VolatileReadTransaction<'env>),
-    VolatileWrite(volatile::VolatileWriteTransaction<'env>),
-    PersistentRead(lmdb::LmdbReadTransaction<'env>),
-    PersistentWrite(lmdb::LmdbWriteTransaction<'env>),
-}
+pub struct Transaction<'env>(TransactionInner<'env>);
 
 impl<'env> Transaction<'env> {
     pub fn get<K, V>(&self, db: &Database, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
-        match *self {
-            Transaction::VolatileRead(ref txn) => { txn.get(db.volatile().unwrap(), key) }
-            Transaction::VolatileWrite(ref txn) => { txn.get(db.volatile().unwrap(), key) }
-            Transaction::PersistentRead(ref txn) => { txn.get(db.persistent().unwrap(), key) }
-            Transaction::PersistentWrite(ref txn) => { txn.get(db.persistent().unwrap(), key) }
-        }
+        let key = AsDatabaseBytes::as_database_bytes(key);
+        let result = match self.0 {
+            TransactionInner::Read(ref txn) => { txn.get(&*db.db, key.as_ref()) }
+            TransactionInner::Write(ref txn) => { txn.get(&*db.db, key.as_ref()) }
+        };
+        Some(from_database_bytes(&result?))
     }
 
-    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> {
-        match *self {
-            Transaction::VolatileRead(ref txn) => { Cursor::VolatileCursor(txn.cursor(db)) }
-            Transaction::VolatileWrite(ref txn) => { Cursor::VolatileCursor(txn.cursor(db)) }
-            Transaction::PersistentRead(ref txn) => { Cursor::PersistentCursor(txn.cursor(db)) }
-            Transaction::PersistentWrite(ref txn) => { Cursor::PersistentCursor(txn.cursor(db)) }
-        }
+    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> where 'db: 'txn {
+        let raw = match self.0 {
+            TransactionInner::Read(ref txn) => { txn.cursor(&*db.db) }
+            TransactionInner::Write(ref txn) => { txn.cursor(&*db.db) }
+        };
+        Cursor { raw, _db: PhantomData }
     }
 }
 
@@ -119,10 +104,7 @@ pub struct ReadTransaction<'env>(Transaction<'env>);
 
 impl<'env> ReadTransaction<'env> {
     pub fn new(env: &'env Environment) -> Self {
-        match *env {
-            Environment::Volatile(ref env) => { ReadTransaction(Transaction::VolatileRead(volatile::VolatileReadTransaction::new(env))) }
-            Environment::Persistent(ref env) => { ReadTransaction(Transaction::PersistentRead(lmdb::LmdbReadTransaction::new(env))) }
-        }
+        ReadTransaction(Transaction(TransactionInner::Read(env.backend.read_transaction())))
     }
 
     pub fn get<K, V>(&self, db: &Database, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
@@ -131,7 +113,7 @@ impl<'env> ReadTransaction<'env> {
 
     pub fn close(self) {}
 
-    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> {
+    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> where 'db: 'txn {
         self.0.cursor(db)
     }
 }
@@ -149,9 +131,13 @@ pub struct WriteTransaction<'env>(Transaction<'env>);
 
 impl<'env> WriteTransaction<'env> {
     pub fn new(env: &'env Environment) -> Self {
-        match *env {
-            Environment::Volatile(ref env) => { WriteTransaction(Transaction::VolatileWrite(volatile::VolatileWriteTransaction::new(env))) }
-            Environment::Persistent(ref env) => { WriteTransaction(Transaction::PersistentWrite(lmdb::LmdbWriteTransaction::new(env))) }
+        WriteTransaction(Transaction(TransactionInner::Write(env.backend.write_transaction())))
+    }
+
+    fn backend_mut(&mut self) -> &mut (dyn BackendWriteTransaction + 'env) {
+        match (self.0).0 {
+            TransactionInner::Write(ref mut txn) => &mut **txn,
+            _ => unreachable!(),
         }
     }
 
@@ -163,11 +149,9 @@ impl<'env> WriteTransaction<'env> {
     /// This works best for values that need to be serialised into the reserved space.
     /// This method will panic when called on a database with duplicate keys!
     pub fn put_reserve<K, V>(&mut self, db: &Database, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: IntoDatabaseValue + ?Sized {
-        match self.0 {
-            Transaction::VolatileWrite(ref mut txn) => { txn.put_reserve(db.volatile().unwrap(), key, value) }
-            Transaction::PersistentWrite(ref mut txn) => { txn.put_reserve(db.persistent().unwrap(), key, value) }
-            _ => { unreachable!(); }
-        }
+        let key = AsDatabaseBytes::as_database_bytes(key);
+        let value_size = IntoDatabaseValue::database_byte_size(value);
+        self.backend_mut().put_reserve(&*db.db, key.as_ref(), value_size, &|bytes: &mut [u8]| IntoDatabaseValue::copy_into_database(value, bytes));
     }
 
     /// Puts a key/value pair into the database by passing a reference to a byte slice.
@@ -175,49 +159,37 @@ impl<'env> WriteTransaction<'env> {
     /// and the existing value can be immediately written into the database.
     /// This also works with duplicate key databases.
     pub fn put<K, V>(&mut self, db: &Database, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        match self.0 {
-            Transaction::VolatileWrite(ref mut txn) => { txn.put(db.volatile().unwrap(), key, value) }
-            Transaction::PersistentWrite(ref mut txn) => { txn.put(db.persistent().unwrap(), key, value) }
-            _ => { unreachable!(); }
-        }
+        let key = AsDatabaseBytes::as_database_bytes(key);
+        let value = AsDatabaseBytes::as_database_bytes(value);
+        self.backend_mut().put(&*db.db, key.as_ref(), value.as_ref());
     }
 
     pub fn remove<K>(&mut self, db: &Database, key: &K) where K: AsDatabaseBytes + ?Sized {
-        match self.0 {
-            Transaction::VolatileWrite(ref mut txn) => { txn.remove(db.volatile().unwrap(), key) }
-            Transaction::PersistentWrite(ref mut txn) => { txn.remove(db.persistent().unwrap(), key) }
-            _ => { unreachable!(); }
-        }
+        let key = AsDatabaseBytes::as_database_bytes(key);
+        self.backend_mut().remove(&*db.db, key.as_ref());
     }
 
     pub fn remove_item<K, V>(&mut self, db: &Database, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        match self.0 {
-            Transaction::VolatileWrite(ref mut txn) => { txn.remove_item(db.volatile().unwrap(), key, value) }
-            Transaction::PersistentWrite(ref mut txn) => { txn.remove_item(db.persistent().unwrap(), key, value) }
-            _ => { unreachable!(); }
-        }
+        let key = AsDatabaseBytes::as_database_bytes(key);
+        let value = AsDatabaseBytes::as_database_bytes(value);
+        self.backend_mut().remove_item(&*db.db, key.as_ref(), value.as_ref());
     }
 
     pub fn commit(self) {
-        match self.0 {
-            Transaction::VolatileWrite(txn) => { txn.commit() }
-            Transaction::PersistentWrite(txn) => { txn.commit() }
+        match (self.0).0 {
+            TransactionInner::Write(txn) => { txn.commit() }
             _ => { unreachable!(); }
         }
     }
 
     pub fn abort(self) {}
 
-    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> {
+    pub fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> Cursor<'txn, 'db> where 'db: 'txn {
         self.0.cursor(db)
     }
 
-    pub fn write_cursor<'txn, 'db>(&'txn self, db: &'db Database) -> WriteCursor<'txn, 'db> {
-        match self.0 {
-            Transaction::VolatileWrite(ref txn) => { WriteCursor::VolatileCursor(txn.write_cursor(db)) }
-            Transaction::PersistentWrite(ref txn) => { WriteCursor::PersistentCursor(txn.write_cursor(db)) }
-            _ => unreachable!()
-        }
+    pub fn write_cursor<'txn, 'db>(&'txn mut self, db: &'db Database) -> WriteCursor<'txn, 'db> where 'db: 'txn {
+        WriteCursor { raw: self.backend_mut().write_cursor(&*db.db), _db: PhantomData }
     }
 }
 
@@ -229,191 +201,373 @@ impl<'env> Deref for WriteTransaction<'env> {
     }
 }
 
-pub enum Cursor<'txn, 'db> {
-    VolatileCursor(volatile::VolatileCursor<'txn, 'db>),
-    PersistentCursor(lmdb::LmdbCursor<'txn, 'db>),
+pub struct Cursor<'txn, 'db> {
+    raw: Box<dyn BackendCursor + 'txn>,
+    _db: PhantomData<&'db Database>,
 }
 
-pub enum WriteCursor<'txn, 'db> {
-    VolatileCursor(volatile::VolatileWriteCursor<'txn, 'db>),
-    PersistentCursor(lmdb::LmdbWriteCursor<'txn, 'db>),
+pub struct WriteCursor<'txn, 'db> {
+    raw: Box<dyn BackendWriteCursor + 'txn>,
+    _db: PhantomData<&'db Database>,
 }
 
-macro_rules! gen_cursor_match {
-    ($self: ident, $f: ident, $t: ident) => {
-        match $self {
-            $t::PersistentCursor(ref mut cursor) => {
-                cursor.$f()
-            },
-            $t::VolatileCursor(ref mut cursor) => {
-                cursor.$f()
-            },
-        }
-    };
-    ($self: ident, $f: ident, $k: expr, $t: ident) => {
-        match $self {
-            $t::PersistentCursor(ref mut cursor) => {
-                cursor.$f($k)
-            },
-            $t::VolatileCursor(ref mut cursor) => {
-                cursor.$f($k)
-            },
-        }
-    };
-    ($self: ident, $f: ident, $k: expr, $v: expr, $t: ident) => {
-        match $self {
-            $t::PersistentCursor(ref mut cursor) => {
-                cursor.$f($k, $v)
-            },
-            $t::VolatileCursor(ref mut cursor) => {
-                cursor.$f($k, $v)
-            },
-        }
-    };
+fn from_database_bytes<T: FromDatabaseValue>(bytes: &[u8]) -> T {
+    FromDatabaseValue::copy_from_database(bytes).unwrap()
 }
 
-impl<'txn, 'db> ReadCursor for Cursor<'txn, 'db> {
-    fn first<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, first, Cursor)
-    }
-
-    fn first_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
-        gen_cursor_match!(self, first_duplicate, Cursor)
-    }
-
-    fn last<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, last, Cursor)
-    }
-
-    fn last_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
-        gen_cursor_match!(self, last_duplicate, Cursor)
-    }
-
-    fn seek_key_value<K, V>(&mut self, key: &K, value: &V) -> bool where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        gen_cursor_match!(self, seek_key_value, key, value, Cursor)
-    }
-
-    fn seek_key_nearest_value<K, V>(&mut self, key: &K, value: &V) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + FromDatabaseValue {
-        gen_cursor_match!(self, seek_key_nearest_value, key, value, Cursor)
-    }
-
-    fn get_current<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, get_current, Cursor)
-    }
-
-    fn next<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next, Cursor)
-    }
-
-    fn next_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next_duplicate, Cursor)
-    }
-
-    fn next_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next_no_duplicate, Cursor)
-    }
-
-    fn prev<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev, Cursor)
-    }
-
-    fn prev_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev_duplicate, Cursor)
-    }
+fn from_database_entry<K: FromDatabaseValue, V: FromDatabaseValue>(entry: Option<(Cow<[u8]>, Cow<[u8]>)>) -> Option<(K, V)> {
+    let (key, value) = entry?;
+    Some((from_database_bytes(&key), from_database_bytes(&value)))
+}
 
-    fn prev_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev_no_duplicate, Cursor)
-    }
+macro_rules! impl_read_cursor_from_backend {
+    ($t: ident) => {
+        impl<'txn, 'db> ReadCursor for $t<'txn, 'db> {
+            fn first<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.first())
+            }
+
+            fn first_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
+                Some(from_database_bytes(&self.raw.first_duplicate()?))
+            }
+
+            fn last<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.last())
+            }
+
+            fn last_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
+                Some(from_database_bytes(&self.raw.last_duplicate()?))
+            }
+
+            fn seek_key_value<K, V>(&mut self, key: &K, value: &V) -> bool where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
+                let key = AsDatabaseBytes::as_database_bytes(key);
+                let value = AsDatabaseBytes::as_database_bytes(value);
+                self.raw.seek_key_value(key.as_ref(), value.as_ref())
+            }
+
+            fn seek_key_nearest_value<K, V>(&mut self, key: &K, value: &V) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + FromDatabaseValue {
+                let key = AsDatabaseBytes::as_database_bytes(key);
+                let value = AsDatabaseBytes::as_database_bytes(value);
+                Some(from_database_bytes(&self.raw.seek_key_nearest_value(key.as_ref(), value.as_ref())?))
+            }
+
+            fn get_current<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.get_current())
+            }
+
+            fn next<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.next())
+            }
+
+            fn next_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.next_duplicate())
+            }
+
+            fn next_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.next_no_duplicate())
+            }
+
+            fn prev<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.prev())
+            }
+
+            fn prev_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.prev_duplicate())
+            }
+
+            fn prev_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
+                from_database_entry(self.raw.prev_no_duplicate())
+            }
+
+            fn seek_key<K, V>(&mut self, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
+                let key = AsDatabaseBytes::as_database_bytes(key);
+                Some(from_database_bytes(&self.raw.seek_key(key.as_ref())?))
+            }
+
+            fn seek_key_both<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
+                let key = AsDatabaseBytes::as_database_bytes(key);
+                from_database_entry(self.raw.seek_key_both(key.as_ref()))
+            }
+
+            fn seek_range_key<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
+                let key = AsDatabaseBytes::as_database_bytes(key);
+                from_database_entry(self.raw.seek_range_key(key.as_ref()))
+            }
+
+            fn count_duplicates(&mut self) -> usize {
+                self.raw.count_duplicates()
+            }
+        }
+    };
+}
 
-    fn seek_key<K, V>(&mut self, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_key, key, Cursor)
-    }
+impl_read_cursor_from_backend!(Cursor);
+impl_read_cursor_from_backend!(WriteCursor);
 
-    fn seek_key_both<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_key_both, key, Cursor)
+impl<'txn, 'db> WriteCursorTrait for WriteCursor<'txn, 'db> {
+    fn remove(&mut self) {
+        self.raw.remove()
     }
+}
 
-    fn seek_range_key<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_range_key, key, Cursor)
-    }
+#[cfg(test)]
+mod tests {
+    use crate::lmdb::{LmdbEnvironment, open};
+    use crate::memory::MemoryEnvironment;
+    use crate::volatile::VolatileEnvironment;
+
+    use super::*;
+
+    /// Runs every test against an environment created by `$new_env`, which is passed the test name.
+    macro_rules! backend_tests {
+        ($backend: ident, $new_env: expr) => {
+            mod $backend {
+                use super::*;
+
+                fn run(name: &str, test: fn(&Environment)) {
+                    let env: Environment = ($new_env)(name);
+                    test(&env);
+                    env.drop_database().unwrap();
+                }
+
+                #[test]
+                fn it_can_save_basic_objects() {
+                    run("basic", super::it_can_save_basic_objects);
+                }
+
+                #[test]
+                fn isolation_test() {
+                    run("isolation", super::isolation_test);
+                }
+
+                #[test]
+                fn duplicates_test() {
+                    run("duplicates", super::duplicates_test);
+                }
+
+                #[test]
+                fn cursor_test() {
+                    run("cursor", super::cursor_test);
+                }
+
+                #[test]
+                fn uint_keys_and_write_cursor_test() {
+                    run("uint_keys", super::uint_keys_and_write_cursor_test);
+                }
+            }
+        };
+    }
+
+    backend_tests!(lmdb, |name: &str| LmdbEnvironment::new(&format!("./test-{}", name), 0, 1, open::NOTLS).unwrap());
+    backend_tests!(volatile, |_: &str| VolatileEnvironment::new(1).unwrap());
+    backend_tests!(memory, |_: &str| MemoryEnvironment::new());
+
+    fn it_can_save_basic_objects(env: &Environment) {
+        let db = env.open_database("test".to_string());
+
+        // Read non-existent value.
+        {
+            let tx = ReadTransaction::new(env);
+            assert!(tx.get::<str, String>(&db, "test").is_none());
+        }
 
-    fn count_duplicates(&mut self) -> usize {
-        gen_cursor_match!(self, count_duplicates, Cursor)
-    }
-}
+        // Read non-existent value.
+        let mut tx = WriteTransaction::new(env);
+        assert!(tx.get::<str, String>(&db, "test").is_none());
+
+        // Write and read value.
+        tx.put_reserve(&db, "test", "one");
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
+        // Overwrite and read value.
+        tx.put_reserve(&db, "test", "two");
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("two".to_string()));
+        tx.commit();
+
+        // Read value.
+        let tx = ReadTransaction::new(env);
+        assert_eq!(tx.get::<str, String>(&db, "test"), Some("two".to_string()));
+        tx.close();
+
+        // Remove value.
+        let mut tx = WriteTransaction::new(env);
+        tx.remove(&db, "test");
+        assert!(tx.get::<str, String>(&db, "test").is_none());
+        tx.commit();
+
+        // Check removal.
+        {
+            let tx = ReadTransaction::new(env);
+            assert!(tx.get::<str, String>(&db, "test").is_none());
+        }
 
-impl<'txn, 'db> ReadCursor for WriteCursor<'txn, 'db> {
-    fn first<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, first, WriteCursor)
-    }
+        // Write and abort.
+        let mut tx = WriteTransaction::new(env);
+        tx.put_reserve(&db, "test", "one");
+        tx.abort();
 
-    fn first_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
-        gen_cursor_match!(self, first_duplicate, WriteCursor)
+        // Check aborted transaction.
+        let tx = ReadTransaction::new(env);
+        assert!(tx.get::<str, String>(&db, "test").is_none());
     }
 
-    fn last<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, last, WriteCursor)
-    }
+    fn isolation_test(env: &Environment) {
+        let db = env.open_database("test".to_string());
 
-    fn last_duplicate<V>(&mut self) -> Option<V> where V: FromDatabaseValue {
-        gen_cursor_match!(self, last_duplicate, WriteCursor)
-    }
+        // Read non-existent value.
+        let tx = ReadTransaction::new(env);
+        assert!(tx.get::<str, String>(&db, "test").is_none());
 
-    fn seek_key_value<K, V>(&mut self, key: &K, value: &V) -> bool where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        gen_cursor_match!(self, seek_key_value, key, value, WriteCursor)
-    }
+        // WriteTransaction.
+        let mut txw = WriteTransaction::new(env);
+        assert!(txw.get::<str, String>(&db, "test").is_none());
+        txw.put_reserve(&db, "test", "one");
+        assert_eq!(txw.get::<str, String>(&db, "test"), Some("one".to_string()));
 
-    fn seek_key_nearest_value<K, V>(&mut self, key: &K, value: &V) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + FromDatabaseValue {
-        gen_cursor_match!(self, seek_key_nearest_value, key, value, WriteCursor)
-    }
+        // ReadTransaction should still have the old state.
+        assert!(tx.get::<str, String>(&db, "test").is_none());
 
-    fn get_current<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, get_current, WriteCursor)
-    }
+        // Commit WriteTransaction.
+        txw.commit();
 
-    fn next<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next, WriteCursor)
-    }
+        // ReadTransaction should still have the old state.
+        assert!(tx.get::<str, String>(&db, "test").is_none());
 
-    fn next_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next_duplicate, WriteCursor)
+        // Have a new ReadTransaction read the new state.
+        let tx2 = ReadTransaction::new(env);
+        assert_eq!(tx2.get::<str, String>(&db, "test"), Some("one".to_string()));
     }
 
-    fn next_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, next_no_duplicate, WriteCursor)
-    }
+    fn duplicates_test(env: &Environment) {
+        let db = env.open_database_with_flags("test".to_string(), DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_UINT_VALUES);
 
-    fn prev<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev, WriteCursor)
-    }
+        // Write one value.
+        let mut txw = WriteTransaction::new(env);
+        assert!(txw.get::<str, u32>(&db, "test").is_none());
+        txw.put::<str, u32>(&db, "test", &125);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
+        txw.commit();
 
-    fn prev_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev_duplicate, WriteCursor)
-    }
+        // Have a new ReadTransaction read the new state.
+        {
+            let tx = ReadTransaction::new(env);
+            assert_eq!(tx.get::<str, u32>(&db, "test"), Some(125));
+        }
 
-    fn prev_no_duplicate<K, V>(&mut self) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, prev_no_duplicate, WriteCursor)
-    }
+        // Write a second smaller value.
+        let mut txw = WriteTransaction::new(env);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
+        txw.put::<str, u32>(&db, "test", &12);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(12));
+        txw.commit();
+
+        // Have a new ReadTransaction read the smaller value.
+        {
+            let tx = ReadTransaction::new(env);
+            assert_eq!(tx.get::<str, u32>(&db, "test"), Some(12));
+        }
 
-    fn seek_key<K, V>(&mut self, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_key, key, WriteCursor)
-    }
+        // Remove smaller value and write larger value.
+        let mut txw = WriteTransaction::new(env);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(12));
+        txw.remove_item::<str, u32>(&db, "test", &12);
+        txw.put::<str, u32>(&db, "test", &5783);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
+        txw.commit();
+
+        // Have a new ReadTransaction read the smallest value.
+        {
+            let tx = ReadTransaction::new(env);
+            assert_eq!(tx.get::<str, u32>(&db, "test"), Some(125));
+        }
 
-    fn seek_key_both<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_key_both, key, WriteCursor)
+        // Remove everything.
+        let mut txw = WriteTransaction::new(env);
+        assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
+        txw.remove::<str>(&db, "test");
+        assert!(txw.get::<str, u32>(&db, "test").is_none());
+        txw.commit();
+
+        // Have a new ReadTransaction read the new state.
+        {
+            let tx = ReadTransaction::new(env);
+            assert!(tx.get::<str, u32>(&db, "test").is_none());
+        }
     }
 
-    fn seek_range_key<K, V>(&mut self, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        gen_cursor_match!(self, seek_range_key, key, WriteCursor)
-    }
+    fn cursor_test(env: &Environment) {
+        let db = env.open_database_with_flags("test".to_string(), DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_UINT_VALUES);
+
+        let test1: String = "test1".to_string();
+        let test2: String = "test2".to_string();
+
+        // Write some values.
+        let mut txw = WriteTransaction::new(env);
+        assert!(txw.get::<str, u32>(&db, "test").is_none());
+        txw.put::<str, u32>(&db, "test1", &125);
+        txw.put::<str, u32>(&db, "test1", &12);
+        txw.put::<str, u32>(&db, "test1", &5783);
+        txw.put::<str, u32>(&db, "test2", &5783);
+        txw.commit();
+
+        // Have a new ReadTransaction read the new state.
+        let tx = ReadTransaction::new(env);
+        let mut cursor = tx.cursor(&db);
+        assert_eq!(cursor.first::<String, u32>(), Some((test1.clone(), 12)));
+        assert_eq!(cursor.last::<String, u32>(), Some((test2.clone(), 5783)));
+        assert_eq!(cursor.prev::<String, u32>(), Some((test1.clone(), 5783)));
+        assert_eq!(cursor.first_duplicate::<u32>(), Some(12));
+        assert_eq!(cursor.next_duplicate::<String, u32>(), Some((test1.clone(), 125)));
+        assert_eq!(cursor.prev_duplicate::<String, u32>(), Some((test1.clone(), 12)));
+        assert_eq!(cursor.next_no_duplicate::<String, u32>(), Some((test2.clone(), 5783)));
+        assert!(cursor.seek_key::<str, u32>("test").is_none());
+        assert_eq!(cursor.seek_key::<str, u32>("test1"), Some(12));
+        assert_eq!(cursor.count_duplicates(), 3);
+        assert_eq!(cursor.last_duplicate::<u32>(), Some(5783));
+        assert_eq!(cursor.seek_key_both::<String, u32>(&test1), Some((test1.clone(), 12)));
+        assert!(!cursor.seek_key_value::<str, u32>("test1", &15));
+        assert!(cursor.seek_key_value::<str, u32>("test1", &125));
+        assert_eq!(cursor.get_current::<String, u32>(), Some((test1.clone(), 125)));
+        assert_eq!(cursor.seek_key_nearest_value::<str, u32>("test1", &126), Some(5783));
+        assert_eq!(cursor.get_current::<String, u32>(), Some((test1.clone(), 5783)));
+        assert!(cursor.prev_no_duplicate::<String, u32>().is_none());
+        assert_eq!(cursor.next::<String, u32>(), Some((test2.clone(), 5783)));
+        assert_eq!(cursor.seek_range_key::<String, u32>(&"test".to_string()), Some((test1.clone(), 12)));
+        assert_eq!(cursor.seek_range_key::<String, u32>(&"test15".to_string()), Some((test2.clone(), 5783)));
+        assert!(cursor.seek_range_key::<String, u32>(&"test3".to_string()).is_none());
+    }
+
+    fn uint_keys_and_write_cursor_test(env: &Environment) {
+        let db = env.open_database_with_flags("test".to_string(), DatabaseFlags::UINT_KEYS);
+
+        let mut txw = WriteTransaction::new(env);
+        txw.put::<u32, str>(&db, &256, "256");
+        txw.put::<u32, str>(&db, &1, "1");
+        txw.put::<u32, str>(&db, &65536, "65536");
+        txw.commit();
+
+        // Integer keys are iterated in numeric order.
+        {
+            let tx = ReadTransaction::new(env);
+            let mut cursor = tx.cursor(&db);
+            assert_eq!(cursor.first::<u32, String>(), Some((1, "1".to_string())));
+            assert_eq!(cursor.next::<u32, String>(), Some((256, "256".to_string())));
+            assert_eq!(cursor.next::<u32, String>(), Some((65536, "65536".to_string())));
+            assert!(cursor.next::<u32, String>().is_none());
+        }
 
-    fn count_duplicates(&mut self) -> usize {
-        gen_cursor_match!(self, count_duplicates, WriteCursor)
-    }
-}
+        // Remove the middle entry with a write cursor and continue iterating.
+        let mut txw = WriteTransaction::new(env);
+        {
+            let mut cursor = txw.write_cursor(&db);
+            assert_eq!(cursor.seek_key::<u32, String>(&256), Some("256".to_string()));
+            cursor.remove();
+            assert_eq!(cursor.next::<u32, String>(), Some((65536, "65536".to_string())));
+            assert_eq!(cursor.prev::<u32, String>(), Some((1, "1".to_string())));
+        }
+        txw.commit();
 
-impl<'txn, 'db> WriteCursorTrait for WriteCursor<'txn, 'db> {
-    fn remove(&mut self) {
-        gen_cursor_match!(self, remove, WriteCursor)
+        let tx = ReadTransaction::new(env);
+        assert!(tx.get::<u32, String>(&db, &256).is_none());
+        assert_eq!(tx.get::<u32, String>(&db, &65536), Some("65536".to_string()));
     }
 }
@@ -1,6 +1,10 @@
+use std::any::Any;
+use std::borrow::Cow;
 use std::cmp;
 use std::fmt;
 use std::fs;
+use std::io;
+use std::slice;
 use std::sync::Arc;
 
 use fs2;
@@ -10,7 +14,7 @@ use lmdb_zero::traits::LmdbResultExt;
 use parking_lot;
 use rand::{Rng, thread_rng};
 
-use crate::cursor::{ReadCursor, WriteCursor as WriteCursorTrait, RawReadCursor};
+use crate::backend::{Backend, BackendCursor, BackendDatabase, BackendTransaction, BackendWriteCursor, BackendWriteTransaction};
 
 use super::*;
 
@@ -37,7 +41,7 @@ impl Clone for LmdbEnvironment {
 impl LmdbEnvironment {
     #[allow(clippy::new_ret_no_self)]
     pub fn new(path: &str, size: usize, max_dbs: u32, flags: open::Flags) -> Result<Environment, LmdbError> {
-        Ok(Environment::Persistent(LmdbEnvironment::new_lmdb_environment(path, size, max_dbs, flags)?))
+        Ok(Environment::new(LmdbEnvironment::new_lmdb_environment(path, size, max_dbs, flags)?))
     }
 
     pub(in super) fn new_lmdb_environment(path: &str, size: usize, max_dbs: u32, flags: open::Flags) -> Result<Self, LmdbError> {
@@ -70,7 +74,7 @@ impl LmdbEnvironment {
         Ok(lmdb)
     }
 
-    pub(in super) fn open_database(&self, name: String, flags: DatabaseFlags) -> LmdbDatabase {
+    fn open_lmdb_database(&self, name: String, flags: DatabaseFlags) -> LmdbDatabase {
         // This is an implicit transaction, so take the lock first.
         let _guard = self.creation_gate.read();
         let mut db_flags = lmdb_zero::db::CREATE;
@@ -94,10 +98,6 @@ impl LmdbEnvironment {
         LmdbDatabase { db: lmdb_zero::Database::open(Arc::clone(&self.env), Some(&name), &lmdb_zero::DatabaseOptions::new(db_flags)).unwrap() }
     }
 
-    pub(in super) fn drop_database(self) -> io::Result<()> {
-        fs::remove_dir_all(self.path().as_ref())
-    }
-
     fn path(&self) -> Cow<str> {
         self.env.path().unwrap().to_string_lossy()
     }
@@ -167,11 +167,41 @@ impl LmdbEnvironment {
     }
 }
 
+impl Backend for LmdbEnvironment {
+    fn open_database(&self, name: String, flags: DatabaseFlags) -> Box<dyn BackendDatabase> {
+        Box::new(self.open_lmdb_database(name, flags))
+    }
+
+    fn read_transaction<'env>(&'env self) -> Box<dyn BackendTransaction + 'env> {
+        Box::new(LmdbReadTransaction::new(self))
+    }
+
+    fn write_transaction<'env>(&'env self) -> Box<dyn BackendWriteTransaction + 'env> {
+        Box::new(LmdbWriteTransaction::new(self))
+    }
+
+    fn drop_database(&self) -> io::Result<()> {
+        fs::remove_dir_all(self.path().as_ref())
+    }
+}
+
 #[derive(Debug)]
 pub struct LmdbDatabase {
     db: lmdb_zero::Database<'static>,
 }
 
+impl LmdbDatabase {
+    fn from_backend(db: &dyn BackendDatabase) -> &LmdbDatabase {
+        db.as_any().downcast_ref().expect("Database does not belong to an LMDB environment")
+    }
+}
+
+impl BackendDatabase for LmdbDatabase {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 pub struct LmdbReadTransaction<'env> {
     txn: lmdb_zero::ReadTransaction<'env>,
     #[allow(dead_code)]
@@ -184,21 +214,20 @@ impl<'env> LmdbReadTransaction<'env> {
         let guard = env.creation_gate.read();
         LmdbReadTransaction { txn: lmdb_zero::ReadTransaction::new(Arc::clone(&env.env)).unwrap(), guard }
     }
+}
 
-    pub(in super) fn get<K, V>(&self, db: &LmdbDatabase, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
+impl<'env> BackendTransaction for LmdbReadTransaction<'env> {
+    fn get(&self, db: &dyn BackendDatabase, key: &[u8]) -> Option<Cow<[u8]>> {
         let access = self.txn.access();
-        let result: Option<&[u8]> = access.get(&db.db, AsDatabaseBytes::as_database_bytes(key).as_ref()).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
+        unsafe { borrowed(access.get(&LmdbDatabase::from_backend(db).db, key).to_opt().unwrap()) }
     }
 
-    pub(in super) fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> LmdbCursor<'txn, 'db> {
-        let cursor = self.txn.cursor(&db.persistent().unwrap().db).unwrap();
-        LmdbCursor {
-            raw: RawLmdbCursor {
-                cursor,
-            },
+    fn cursor<'txn>(&'txn self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendCursor + 'txn> {
+        let cursor = self.txn.cursor(&LmdbDatabase::from_backend(db).db).unwrap();
+        Box::new(LmdbCursor {
+            cursor,
             txn: &self.txn,
-        }
+        })
     }
 }
 
@@ -223,62 +252,58 @@ impl<'env> LmdbWriteTransaction<'env> {
         let guard = env.creation_gate.read();
         LmdbWriteTransaction { txn: lmdb_zero::WriteTransaction::new(Arc::clone(&env.env)).unwrap(), guard }
     }
+}
 
-    pub(in super) fn get<K, V>(&self, db: &LmdbDatabase, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
+impl<'env> BackendTransaction for LmdbWriteTransaction<'env> {
+    fn get(&self, db: &dyn BackendDatabase, key: &[u8]) -> Option<Cow<[u8]>> {
         let access = self.txn.access();
-        let result: Option<&[u8]> = access.get(&db.db, AsDatabaseBytes::as_database_bytes(key).as_ref()).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
+        unsafe { borrowed(access.get(&LmdbDatabase::from_backend(db).db, key).to_opt().unwrap()) }
     }
 
-    pub(in super) fn put_reserve<K, V>(&mut self, db: &LmdbDatabase, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: IntoDatabaseValue + ?Sized {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let value_size = IntoDatabaseValue::database_byte_size(value);
+    fn cursor<'txn>(&'txn self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendCursor + 'txn> {
+        let cursor = self.txn.cursor(&LmdbDatabase::from_backend(db).db).unwrap();
+        Box::new(LmdbCursor {
+            cursor,
+            txn: &self.txn,
+        })
+    }
+}
+
+impl<'env> BackendWriteTransaction for LmdbWriteTransaction<'env> {
+    fn put_reserve(&mut self, db: &dyn BackendDatabase, key: &[u8], value_size: usize, write: &dyn Fn(&mut [u8])) {
         unsafe {
             let mut access = self.txn.access();
-            let mut bytes: &mut [u8] = access.put_reserve_unsized(&db.db, key.as_ref(), value_size, lmdb_zero::put::Flags::empty()).unwrap();
-            IntoDatabaseValue::copy_into_database(value, &mut bytes);
+            let bytes: &mut [u8] = access.put_reserve_unsized(&LmdbDatabase::from_backend(db).db, key, value_size, lmdb_zero::put::Flags::empty()).unwrap();
+            write(bytes);
         }
     }
 
-    pub(in super) fn put<K, V>(&mut self, db: &LmdbDatabase, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let value = AsDatabaseBytes::as_database_bytes(value);
+    fn put(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]) {
         let mut access = self.txn.access();
-        access.put(&db.db, key.as_ref(), value.as_ref(), lmdb_zero::put::Flags::empty()).unwrap();
+        access.put(&LmdbDatabase::from_backend(db).db, key, value, lmdb_zero::put::Flags::empty()).unwrap();
     }
 
-    pub(in super) fn remove<K>(&mut self, db: &LmdbDatabase, key: &K) where K: AsDatabaseBytes + ?Sized {
+    fn remove(&mut self, db: &dyn BackendDatabase, key: &[u8]) {
         let mut access = self.txn.access();
-        access.del_key(&db.db, AsDatabaseBytes::as_database_bytes(key).as_ref()).to_opt().unwrap();
+        access.del_key(&LmdbDatabase::from_backend(db).db, key).to_opt().unwrap();
     }
 
-    pub(in super) fn remove_item<K, V>(&mut self, db: &LmdbDatabase, key: &K, value: &V) where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
+    fn remove_item(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]) {
         let mut access = self.txn.access();
-        access.del_item(&db.db, AsDatabaseBytes::as_database_bytes(key).as_ref(), AsDatabaseBytes::as_database_bytes(value).as_ref()).to_opt().unwrap();
+        access.del_item(&LmdbDatabase::from_backend(db).db, key, value).to_opt().unwrap();
     }
 
-    pub(in super) fn commit(self) {
+    fn commit(self: Box<Self>) {
         self.txn.commit().unwrap();
     }
 
-    pub(in super) fn cursor<'txn, 'db>(&'txn self, db: &'db Database) -> LmdbCursor<'txn, 'db> {
-        let cursor = self.txn.cursor(&db.persistent().unwrap().db).unwrap();
-        LmdbCursor {
-            raw: RawLmdbCursor {
-                cursor,
-            },
-            txn: &self.txn,
-        }
-    }
-
-    pub(in super) fn write_cursor<'txn, 'db>(&'txn self, db: &'db Database) -> LmdbWriteCursor<'txn, 'db> {
-        let cursor = self.txn.cursor(&db.persistent().unwrap().db).unwrap();
-        LmdbWriteCursor {
-            raw: RawLmdbCursor {
-                cursor,
-            },
-            txn: &self.txn,
-        }
+    fn write_cursor<'txn>(&'txn mut self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendWriteCursor + 'txn> {
+        let txn = &self.txn;
+        let cursor = txn.cursor(&LmdbDatabase::from_backend(db).db).unwrap();
+        Box::new(LmdbWriteCursor {
+            cursor,
+            txn,
+        })
     }
 }
 
@@ -288,332 +313,125 @@ impl<'env> fmt::Debug for LmdbWriteTransaction<'env> {
     }
 }
 
-pub struct RawLmdbCursor<'txn, 'db> {
-    cursor: lmdb_zero::Cursor<'txn, 'db>,
+/// `lmdb_zero` ties returned data to the accessor it was read through, which only lives for a
+/// single call here. LMDB itself keeps the data valid until the transaction ends or is modified.
+/// The backend traits tie it to the borrow of the transaction or cursor instead, and modifications
+/// need a mutable borrow of either, so the data can safely outlive the accessor.
+unsafe fn borrowed<'a>(value: Option<&[u8]>) -> Option<Cow<'a, [u8]>> {
+    value.map(|value| Cow::Borrowed(slice::from_raw_parts(value.as_ptr(), value.len())))
 }
 
-impl<'txn, 'db> RawReadCursor for RawLmdbCursor<'txn, 'db> {
-    fn first<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.first(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn first_duplicate<V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<V> where V: FromDatabaseValue {
-        let result: Option<&[u8]> = self.cursor.first_dup(&access).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
-    }
-
-    fn last<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.last(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn last_duplicate<V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<V> where V: FromDatabaseValue {
-        let result: Option<&[u8]> = self.cursor.last_dup(&access).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
-    }
-
-    fn seek_key_value<K, V>(&mut self, key: &K, value: &V) -> bool where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + ?Sized {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let value = AsDatabaseBytes::as_database_bytes(value);
-        let result = self.cursor.seek_kv(key.as_ref(), value.as_ref());
-        result.is_ok()
-    }
-
-    fn seek_key_nearest_value<K, V>(&mut self, access: &lmdb_zero::ConstAccessor, key: &K, value: &V) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: AsDatabaseBytes + FromDatabaseValue {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let value = AsDatabaseBytes::as_database_bytes(value);
-        let result: Option<&[u8]> = self.cursor.seek_k_nearest_v(&access, key.as_ref(), value.as_ref()).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
-    }
-
-    fn get_current<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.get_current(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn next<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.next(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn next_duplicate<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.next_dup(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn next_no_duplicate<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.next_nodup(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn prev<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.prev(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn prev_duplicate<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.prev_dup(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn prev_no_duplicate<K, V>(&mut self, access: &lmdb_zero::ConstAccessor) -> Option<(K, V)> where K: FromDatabaseValue, V: FromDatabaseValue {
-        let result: Option<(&[u8], &[u8])> = self.cursor.prev_nodup(&access).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn seek_key<K, V>(&mut self, access: &lmdb_zero::ConstAccessor, key: &K) -> Option<V> where K: AsDatabaseBytes + ?Sized, V: FromDatabaseValue {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let result: Option<&[u8]> = self.cursor.seek_k(&access, key.as_ref()).to_opt().unwrap();
-        Some(FromDatabaseValue::copy_from_database(result?).unwrap())
-    }
-
-    fn seek_key_both<K, V>(&mut self, access: &lmdb_zero::ConstAccessor, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let result: Option<(&[u8], &[u8])> = self.cursor.seek_k_both(&access, key.as_ref()).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn seek_range_key<K, V>(&mut self, access: &lmdb_zero::ConstAccessor, key: &K) -> Option<(K, V)> where K: AsDatabaseBytes + FromDatabaseValue, V: FromDatabaseValue {
-        let key = AsDatabaseBytes::as_database_bytes(key);
-        let result: Option<(&[u8], &[u8])> = self.cursor.seek_range_k(&access, key.as_ref()).to_opt().unwrap();
-        let (key, value) = result?;
-        Some((FromDatabaseValue::copy_from_database(key).unwrap(), FromDatabaseValue::copy_from_database(value).unwrap()))
-    }
-
-    fn count_duplicates(&mut self) -> usize {
-        self.cursor.count().unwrap()
-    }
+unsafe fn borrowed_entry<'a>(entry: Option<(&[u8], &[u8])>) -> Option<(Cow<'a, [u8]>, Cow<'a, [u8]>)> {
+    let (key, value) = entry?;
+    Some((borrowed(Some(key))?, borrowed(Some(value))?))
 }
 
-pub struct LmdbCursor<'txn, 'db> {
-    raw: RawLmdbCursor<'txn, 'db>,
-    txn: &'txn lmdb_zero::ConstTransaction<'txn>,
-}
-
-impl_read_cursor_from_raw!(LmdbCursor<'txn, 'db>, raw, txn);
-
-pub struct LmdbWriteCursor<'txn, 'db> {
-    raw: RawLmdbCursor<'txn, 'db>,
-    txn: &'txn lmdb_zero::WriteTransaction<'txn>,
-}
-
-impl_read_cursor_from_raw!(LmdbWriteCursor<'txn, 'db>, raw, txn);
-
-impl<'txn, 'db> WriteCursorTrait for LmdbWriteCursor<'txn, 'db> {
-    fn remove(&mut self) {
-        let mut access = self.txn.access();
-        self.raw.cursor.del(&mut access, lmdb_zero::del::Flags::empty()).unwrap();
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_can_save_basic_objects() {
-        let env = LmdbEnvironment::new("./test", 0, 1, open::Flags::empty()).unwrap();
-        {
-            let db = env.open_database("test".to_string());
-
-            // Read non-existent value.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert!(tx.get::<str, String>(&db, "test").is_none());
+macro_rules! impl_backend_cursor {
+    ($t: ty) => {
+        impl<'txn> BackendCursor for $t {
+            fn first(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.first(&access).to_opt().unwrap()) }
             }
 
-            // Read non-existent value.
-            let mut tx = WriteTransaction::new(&env);
-            assert!(tx.get::<str, String>(&db, "test").is_none());
-
-            // Write and read value.
-            tx.put_reserve(&db, "test", "one");
-            assert_eq!(tx.get::<str, String>(&db, "test"), Some("one".to_string()));
-            // Overwrite and read value.
-            tx.put_reserve(&db, "test", "two");
-            assert_eq!(tx.get::<str, String>(&db, "test"), Some("two".to_string()));
-            tx.commit();
-
-            // Read value.
-            let tx = ReadTransaction::new(&env);
-            assert_eq!(tx.get::<str, String>(&db, "test"), Some("two".to_string()));
-            tx.close();
-
-            // Remove value.
-            let mut tx = WriteTransaction::new(&env);
-            tx.remove(&db, "test");
-            assert!(tx.get::<str, String>(&db, "test").is_none());
-            tx.commit();
-
-            // Check removal.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert!(tx.get::<str, String>(&db, "test").is_none());
+            fn first_duplicate(&mut self) -> Option<Cow<[u8]>> {
+                let access = self.txn.access();
+                unsafe { borrowed(self.cursor.first_dup(&access).to_opt().unwrap()) }
             }
 
-            // Write and abort.
-            let mut tx = WriteTransaction::new(&env);
-            tx.put_reserve(&db, "test", "one");
-            tx.abort();
+            fn last(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.last(&access).to_opt().unwrap()) }
+            }
 
-            // Check aborted transaction.
-            let tx = ReadTransaction::new(&env);
-            assert!(tx.get::<str, String>(&db, "test").is_none());
-        }
+            fn last_duplicate(&mut self) -> Option<Cow<[u8]>> {
+                let access = self.txn.access();
+                unsafe { borrowed(self.cursor.last_dup(&access).to_opt().unwrap()) }
+            }
 
-        env.drop_database().unwrap();
-    }
+            fn seek_key_value(&mut self, key: &[u8], value: &[u8]) -> bool {
+                self.cursor.seek_kv(key, value).is_ok()
+            }
 
-    #[test]
-    fn isolation_test() {
-        let env = LmdbEnvironment::new("./test2", 0, 1, open::NOTLS).unwrap();
-        {
-            let db = env.open_database("test".to_string());
+            fn seek_key_nearest_value(&mut self, key: &[u8], value: &[u8]) -> Option<Cow<[u8]>> {
+                let access = self.txn.access();
+                unsafe { borrowed(self.cursor.seek_k_nearest_v(&access, key, value).to_opt().unwrap()) }
+            }
 
-            // Read non-existent value.
-            let tx = ReadTransaction::new(&env);
-            assert!(tx.get::<str, String>(&db, "test").is_none());
+            fn get_current(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.get_current(&access).to_opt().unwrap()) }
+            }
 
-            // WriteTransaction.
-            let mut txw = WriteTransaction::new(&env);
-            assert!(txw.get::<str, String>(&db, "test").is_none());
-            txw.put_reserve(&db, "test", "one");
-            assert_eq!(txw.get::<str, String>(&db, "test"), Some("one".to_string()));
+            fn next(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.next(&access).to_opt().unwrap()) }
+            }
 
-            // ReadTransaction should still have the old state.
-            assert!(tx.get::<str, String>(&db, "test").is_none());
+            fn next_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.next_dup(&access).to_opt().unwrap()) }
+            }
 
-            // Commit WriteTransaction.
-            txw.commit();
+            fn next_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.next_nodup(&access).to_opt().unwrap()) }
+            }
 
-            // ReadTransaction should still have the old state.
-            assert!(tx.get::<str, String>(&db, "test").is_none());
+            fn prev(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.prev(&access).to_opt().unwrap()) }
+            }
 
-            // Have a new ReadTransaction read the new state.
-            let tx2 = ReadTransaction::new(&env);
-            assert_eq!(tx2.get::<str, String>(&db, "test"), Some("one".to_string()));
-        }
+            fn prev_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.prev_dup(&access).to_opt().unwrap()) }
+            }
 
-        env.drop_database().unwrap();
-    }
+            fn prev_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.prev_nodup(&access).to_opt().unwrap()) }
+            }
 
-    #[test]
-    fn duplicates_test() {
-        let env = LmdbEnvironment::new("./test3", 0, 1, open::NOTLS).unwrap();
-        {
-            let db = env.open_database_with_flags("test".to_string(), DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_UINT_VALUES);
-
-            // Write one value.
-            let mut txw = WriteTransaction::new(&env);
-            assert!(txw.get::<str, u32>(&db, "test").is_none());
-            txw.put::<str, u32>(&db, "test", &125);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
-            txw.commit();
-
-            // Have a new ReadTransaction read the new state.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert_eq!(tx.get::<str, u32>(&db, "test"), Some(125));
+            fn seek_key(&mut self, key: &[u8]) -> Option<Cow<[u8]>> {
+                let access = self.txn.access();
+                unsafe { borrowed(self.cursor.seek_k(&access, key).to_opt().unwrap()) }
             }
 
-            // Write a second smaller value.
-            let mut txw = WriteTransaction::new(&env);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
-            txw.put::<str, u32>(&db, "test", &12);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(12));
-            txw.commit();
-
-            // Have a new ReadTransaction read the smaller value.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert_eq!(tx.get::<str, u32>(&db, "test"), Some(12));
+            fn seek_key_both(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.seek_k_both(&access, key).to_opt().unwrap()) }
             }
 
-            // Remove smaller value and write larger value.
-            let mut txw = WriteTransaction::new(&env);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(12));
-            txw.remove_item::<str, u32>(&db, "test", &12);
-            txw.put::<str, u32>(&db, "test", &5783);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
-            txw.commit();
-
-            // Have a new ReadTransaction read the smallest value.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert_eq!(tx.get::<str, u32>(&db, "test"), Some(125));
+            fn seek_range_key(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+                let access = self.txn.access();
+                unsafe { borrowed_entry(self.cursor.seek_range_k(&access, key).to_opt().unwrap()) }
             }
 
-            // Remove everything.
-            let mut txw = WriteTransaction::new(&env);
-            assert_eq!(txw.get::<str, u32>(&db, "test"), Some(125));
-            txw.remove::<str>(&db, "test");
-            assert!(txw.get::<str, u32>(&db, "test").is_none());
-            txw.commit();
-
-            // Have a new ReadTransaction read the new state.
-            {
-                let tx = ReadTransaction::new(&env);
-                assert!(tx.get::<str, u32>(&db, "test").is_none());
+            fn count_duplicates(&mut self) -> usize {
+                self.cursor.count().unwrap()
             }
         }
+    };
+}
 
-        env.drop_database().unwrap();
-    }
+pub struct LmdbCursor<'txn> {
+    cursor: lmdb_zero::Cursor<'txn, 'txn>,
+    txn: &'txn lmdb_zero::ConstTransaction<'txn>,
+}
 
-    #[test]
-    fn cursor_test() {
-        let env = LmdbEnvironment::new("./test4", 0, 1, open::NOTLS).unwrap();
-        {
-            let db = env.open_database_with_flags("test".to_string(), DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_UINT_VALUES);
-
-            let test1: String = "test1".to_string();
-            let test2: String = "test2".to_string();
-
-            // Write some values.
-            let mut txw = WriteTransaction::new(&env);
-            assert!(txw.get::<str, u32>(&db, "test").is_none());
-            txw.put::<str, u32>(&db, "test1", &125);
-            txw.put::<str, u32>(&db, "test1", &12);
-            txw.put::<str, u32>(&db, "test1", &5783);
-            txw.put::<str, u32>(&db, "test2", &5783);
-            txw.commit();
-
-            // Have a new ReadTransaction read the new state.
-            let tx = ReadTransaction::new(&env);
-            let mut cursor = tx.cursor(&db);
-            assert_eq!(cursor.first::<String, u32>(), Some((test1.clone(), 12)));
-            assert_eq!(cursor.last::<String, u32>(), Some((test2.clone(), 5783)));
-            assert_eq!(cursor.prev::<String, u32>(), Some((test1.clone(), 5783)));
-            assert_eq!(cursor.first_duplicate::<u32>(), Some(12));
-            assert_eq!(cursor.next_duplicate::<String, u32>(), Some((test1.clone(), 125)));
-            assert_eq!(cursor.prev_duplicate::<String, u32>(), Some((test1.clone(), 12)));
-            assert_eq!(cursor.next_no_duplicate::<String, u32>(), Some((test2.clone(), 5783)));
-            assert!(cursor.seek_key::<str, u32>("test").is_none());
-            assert_eq!(cursor.seek_key::<str, u32>("test1"), Some(12));
-            assert_eq!(cursor.count_duplicates(), 3);
-            assert_eq!(cursor.last_duplicate::<u32>(), Some(5783));
-//            assert_eq!(cursor.seek_key_both::<String, u32>(&test1), Some((test1.clone(), 12)));
-            assert!(!cursor.seek_key_value::<str, u32>("test1", &15));
-            assert!(cursor.seek_key_value::<str, u32>("test1", &125));
-            assert_eq!(cursor.get_current::<String, u32>(), Some((test1.clone(), 125)));
-            assert_eq!(cursor.seek_key_nearest_value::<str, u32>("test1", &126), Some(5783));
-            assert_eq!(cursor.get_current::<String, u32>(), Some((test1.clone(), 5783)));
-            assert!(cursor.prev_no_duplicate::<String, u32>().is_none());
-            assert_eq!(cursor.next::<String, u32>(), Some((test2.clone(), 5783)));
-//            assert_eq!(cursor.seek_range_key::<String, u32>("test"), Some((test1.clone(), 12)));
-        }
+impl_backend_cursor!(LmdbCursor<'txn>);
 
-        env.drop_database().unwrap();
+pub struct LmdbWriteCursor<'txn> {
+    cursor: lmdb_zero::Cursor<'txn, 'txn>,
+    txn: &'txn lmdb_zero::WriteTransaction<'txn>,
+}
+
+impl_backend_cursor!(LmdbWriteCursor<'txn>);
+
+impl<'txn> BackendWriteCursor for LmdbWriteCursor<'txn> {
+    fn remove(&mut self) {
+        let mut access = self.txn.access();
+        self.cursor.del(&mut access, lmdb_zero::del::Flags::empty()).unwrap();
     }
 }
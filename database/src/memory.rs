@@ -0,0 +1,548 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use parking_lot::{Mutex, MutexGuard, RwLock};
+
+use crate::backend::{Backend, BackendCursor, BackendDatabase, BackendTransaction, BackendWriteCursor, BackendWriteTransaction};
+
+use super::*;
+
+/// The values of a key are kept sorted. Databases without duplicate keys have exactly one value per key.
+type Table = BTreeMap<Vec<u8>, Vec<Vec<u8>>>;
+
+/// Changes of a write transaction to a single table. `None` marks a removed key.
+type Changes = BTreeMap<Vec<u8>, Option<Vec<Vec<u8>>>>;
+
+#[derive(Clone, Default)]
+struct Snapshot {
+    tables: Vec<Arc<Table>>,
+}
+
+impl Snapshot {
+    fn table(&self, id: usize) -> Option<&Table> {
+        self.tables.get(id).map(Arc::as_ref)
+    }
+}
+
+/// A pure in-memory storage backend.
+///
+/// Read transactions operate on a snapshot of the committed state, while a single write
+/// transaction at a time collects its changes and applies them on commit.
+#[derive(Default)]
+pub struct MemoryEnvironment {
+    committed: RwLock<Arc<Snapshot>>,
+    databases: Mutex<HashMap<String, MemoryDatabase>>,
+    writer: Mutex<()>,
+}
+
+impl MemoryEnvironment {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Environment {
+        Environment::new(MemoryEnvironment::default())
+    }
+}
+
+impl fmt::Debug for MemoryEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryEnvironment")
+            .field("databases", &*self.databases.lock())
+            .finish()
+    }
+}
+
+impl Backend for MemoryEnvironment {
+    fn open_database(&self, name: String, flags: DatabaseFlags) -> Box<dyn BackendDatabase> {
+        let mut databases = self.databases.lock();
+        if let Some(db) = databases.get(&name) {
+            return Box::new(db.clone());
+        }
+
+        let db = MemoryDatabase { id: databases.len(), flags };
+        Arc::make_mut(&mut *self.committed.write()).tables.push(Arc::new(Table::new()));
+        databases.insert(name, db.clone());
+        Box::new(db)
+    }
+
+    fn read_transaction<'env>(&'env self) -> Box<dyn BackendTransaction + 'env> {
+        Box::new(MemoryReadTransaction { snapshot: Arc::clone(&self.committed.read()) })
+    }
+
+    fn write_transaction<'env>(&'env self) -> Box<dyn BackendWriteTransaction + 'env> {
+        // Only one write transaction at a time, so its changes can never conflict.
+        let guard = self.writer.lock();
+        Box::new(MemoryWriteTransaction {
+            env: self,
+            snapshot: Arc::clone(&self.committed.read()),
+            changes: RefCell::new(HashMap::new()),
+            _guard: guard,
+        })
+    }
+
+    fn drop_database(&self) -> io::Result<()> {
+        *self.committed.write() = Arc::new(Snapshot::default());
+        self.databases.lock().clear();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MemoryDatabase {
+    id: usize,
+    flags: DatabaseFlags,
+}
+
+impl MemoryDatabase {
+    fn from_backend(db: &dyn BackendDatabase) -> &MemoryDatabase {
+        db.as_any().downcast_ref().expect("Database does not belong to a memory environment")
+    }
+
+    fn has_duplicates(&self) -> bool {
+        self.flags.contains(DatabaseFlags::DUPLICATE_KEYS)
+    }
+
+    fn has_uint_values(&self) -> bool {
+        self.has_duplicates() && self.flags.contains(DatabaseFlags::DUP_UINT_VALUES)
+    }
+
+    fn encode_key<'a>(&self, key: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.flags.contains(DatabaseFlags::UINT_KEYS) { uint_to_sortable(key) } else { Cow::Borrowed(key) }
+    }
+
+    fn encode_value<'a>(&self, value: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.has_uint_values() { uint_to_sortable(value) } else { Cow::Borrowed(value) }
+    }
+
+    fn decode_key<'a>(&self, key: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.flags.contains(DatabaseFlags::UINT_KEYS) { sortable_to_uint(key) } else { Cow::Borrowed(key) }
+    }
+
+    fn decode_value<'a>(&self, value: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.has_uint_values() { sortable_to_uint(value) } else { Cow::Borrowed(value) }
+    }
+}
+
+impl BackendDatabase for MemoryDatabase {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Integer keys and values are stored big-endian, so that their byte order matches their numeric order.
+fn uint_to_sortable(bytes: &[u8]) -> Cow<[u8]> {
+    match bytes.len() {
+        4 => {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(bytes);
+            Cow::Owned(u32::from_ne_bytes(buf).to_be_bytes().to_vec())
+        },
+        8 => {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(bytes);
+            Cow::Owned(u64::from_ne_bytes(buf).to_be_bytes().to_vec())
+        },
+        _ => Cow::Borrowed(bytes),
+    }
+}
+
+fn sortable_to_uint(bytes: &[u8]) -> Cow<[u8]> {
+    match bytes.len() {
+        4 => {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(bytes);
+            Cow::Owned(u32::from_be_bytes(buf).to_ne_bytes().to_vec())
+        },
+        8 => {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(bytes);
+            Cow::Owned(u64::from_be_bytes(buf).to_ne_bytes().to_vec())
+        },
+        _ => Cow::Borrowed(bytes),
+    }
+}
+
+/// A table as seen by a transaction: the committed table with the transaction's changes on top.
+struct TableView<'a> {
+    table: Option<&'a Table>,
+    changes: Option<&'a Changes>,
+}
+
+impl<'a> TableView<'a> {
+    fn values(&self, key: &[u8]) -> Option<&'a [Vec<u8>]> {
+        match self.changes.and_then(|changes| changes.get(key)) {
+            Some(values) => values.as_ref().map(Vec::as_slice),
+            None => self.table?.get(key).map(Vec::as_slice),
+        }
+    }
+
+    fn is_changed(&self, key: &[u8]) -> bool {
+        self.changes.map_or(false, |changes| changes.contains_key(key))
+    }
+
+    /// The smallest existing key above `lower`.
+    fn key_after(&self, lower: Bound<&[u8]>) -> Option<&'a [u8]> {
+        let committed = self.table.and_then(|table| {
+            table.range::<[u8], _>((lower, Bound::Unbounded))
+                .map(|(key, _)| key.as_slice())
+                .find(|key| !self.is_changed(key))
+        });
+        let changed = self.changes.and_then(|changes| {
+            changes.range::<[u8], _>((lower, Bound::Unbounded))
+                .find(|(_, values)| values.is_some())
+                .map(|(key, _)| key.as_slice())
+        });
+        match (committed, changed) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// The largest existing key below `upper`.
+    fn key_before(&self, upper: Bound<&[u8]>) -> Option<&'a [u8]> {
+        let committed = self.table.and_then(|table| {
+            table.range::<[u8], _>((Bound::Unbounded, upper))
+                .rev()
+                .map(|(key, _)| key.as_slice())
+                .find(|key| !self.is_changed(key))
+        });
+        let changed = self.changes.and_then(|changes| {
+            changes.range::<[u8], _>((Bound::Unbounded, upper))
+                .rev()
+                .find(|(_, values)| values.is_some())
+                .map(|(key, _)| key.as_slice())
+        });
+        match (committed, changed) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn first_entry_after(&self, lower: Bound<&[u8]>) -> Option<(Vec<u8>, Vec<u8>)> {
+        let key = self.key_after(lower)?;
+        Some((key.to_vec(), self.values(key)?.first()?.clone()))
+    }
+
+    fn last_entry_before(&self, upper: Bound<&[u8]>) -> Option<(Vec<u8>, Vec<u8>)> {
+        let key = self.key_before(upper)?;
+        Some((key.to_vec(), self.values(key)?.last()?.clone()))
+    }
+}
+
+trait MemoryTransaction {
+    fn view<R, F: FnOnce(&TableView) -> R>(&self, db: &MemoryDatabase, f: F) -> R;
+}
+
+struct MemoryReadTransaction {
+    snapshot: Arc<Snapshot>,
+}
+
+impl MemoryTransaction for MemoryReadTransaction {
+    fn view<R, F: FnOnce(&TableView) -> R>(&self, db: &MemoryDatabase, f: F) -> R {
+        f(&TableView { table: self.snapshot.table(db.id), changes: None })
+    }
+}
+
+impl BackendTransaction for MemoryReadTransaction {
+    fn get(&self, db: &dyn BackendDatabase, key: &[u8]) -> Option<Cow<[u8]>> {
+        // The snapshot is immutable, so values can be borrowed from it directly.
+        let db = MemoryDatabase::from_backend(db);
+        let key = db.encode_key(key);
+        let table = self.snapshot.table(db.id)?;
+        Some(db.decode_value(table.get(&*key)?.first()?))
+    }
+
+    fn cursor<'txn>(&'txn self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendCursor + 'txn> {
+        Box::new(MemoryCursor { txn: self, db: MemoryDatabase::from_backend(db), position: None })
+    }
+}
+
+impl fmt::Debug for MemoryReadTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemoryReadTransaction")
+    }
+}
+
+struct MemoryWriteTransaction<'env> {
+    env: &'env MemoryEnvironment,
+    snapshot: Arc<Snapshot>,
+    changes: RefCell<HashMap<usize, Changes>>,
+    _guard: MutexGuard<'env, ()>,
+}
+
+impl<'env> MemoryWriteTransaction<'env> {
+    /// Replaces the values of an already encoded `key`. Returning an empty list removes the key.
+    fn update<F: FnOnce(&[Vec<u8>]) -> Vec<Vec<u8>>>(&self, db: &MemoryDatabase, key: &[u8], f: F) {
+        let mut changes = self.changes.borrow_mut();
+        let changes = changes.entry(db.id).or_insert_with(Changes::new);
+        let values = {
+            let current = match changes.get(key) {
+                Some(values) => values.as_ref().map(Vec::as_slice),
+                None => self.snapshot.table(db.id).and_then(|table| table.get(key)).map(Vec::as_slice),
+            };
+            f(current.unwrap_or(&[]))
+        };
+        changes.insert(key.to_vec(), if values.is_empty() { None } else { Some(values) });
+    }
+
+    fn remove_encoded_item(&self, db: &MemoryDatabase, key: &[u8], value: &[u8]) {
+        if db.has_duplicates() {
+            self.update(db, key, |values| values.iter().filter(|v| v.as_slice() != value).cloned().collect());
+        } else {
+            self.update(db, key, |_| Vec::new());
+        }
+    }
+}
+
+impl<'env> MemoryTransaction for MemoryWriteTransaction<'env> {
+    fn view<R, F: FnOnce(&TableView) -> R>(&self, db: &MemoryDatabase, f: F) -> R {
+        let changes = self.changes.borrow();
+        f(&TableView { table: self.snapshot.table(db.id), changes: changes.get(&db.id) })
+    }
+}
+
+impl<'env> BackendTransaction for MemoryWriteTransaction<'env> {
+    fn get(&self, db: &dyn BackendDatabase, key: &[u8]) -> Option<Cow<[u8]>> {
+        // Changes are behind a `RefCell`, so values are copied out of it.
+        let db = MemoryDatabase::from_backend(db);
+        let key = db.encode_key(key);
+        let value = self.view(db, |view| view.values(&key)?.first().cloned())?;
+        Some(Cow::Owned(db.decode_value(&value).into_owned()))
+    }
+
+    fn cursor<'txn>(&'txn self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendCursor + 'txn> {
+        Box::new(MemoryCursor { txn: self, db: MemoryDatabase::from_backend(db), position: None })
+    }
+}
+
+impl<'env> BackendWriteTransaction for MemoryWriteTransaction<'env> {
+    fn put_reserve(&mut self, db: &dyn BackendDatabase, key: &[u8], value_size: usize, write: &dyn Fn(&mut [u8])) {
+        assert!(!MemoryDatabase::from_backend(db).has_duplicates(), "put_reserve is not supported on databases with duplicate keys");
+        let mut value = vec![0; value_size];
+        write(&mut value);
+        self.put(db, key, &value);
+    }
+
+    fn put(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]) {
+        let db = MemoryDatabase::from_backend(db);
+        let key = db.encode_key(key);
+        let value = db.encode_value(value);
+        if db.has_duplicates() {
+            self.update(db, &key, |values| {
+                let mut values = values.to_vec();
+                if let Err(i) = values.binary_search_by(|v| v.as_slice().cmp(&value)) {
+                    values.insert(i, value.to_vec());
+                }
+                values
+            });
+        } else {
+            self.update(db, &key, |_| vec![value.to_vec()]);
+        }
+    }
+
+    fn remove(&mut self, db: &dyn BackendDatabase, key: &[u8]) {
+        let db = MemoryDatabase::from_backend(db);
+        let key = db.encode_key(key);
+        self.update(db, &key, |_| Vec::new());
+    }
+
+    fn remove_item(&mut self, db: &dyn BackendDatabase, key: &[u8], value: &[u8]) {
+        let db = MemoryDatabase::from_backend(db);
+        let key = db.encode_key(key);
+        let value = db.encode_value(value);
+        self.remove_encoded_item(db, &key, &value);
+    }
+
+    fn commit(self: Box<Self>) {
+        let MemoryWriteTransaction { env, snapshot, changes, _guard } = *self;
+        // Release our snapshot first, so tables that are not shared with readers are updated in place.
+        drop(snapshot);
+
+        let mut committed = env.committed.write();
+        let committed = Arc::make_mut(&mut *committed);
+        for (id, changes) in changes.into_inner() {
+            let table = Arc::make_mut(&mut committed.tables[id]);
+            for (key, values) in changes {
+                match values {
+                    Some(values) => { table.insert(key, values); },
+                    None => { table.remove(&key); },
+                }
+            }
+        }
+    }
+
+    fn write_cursor<'txn>(&'txn mut self, db: &'txn dyn BackendDatabase) -> Box<dyn BackendWriteCursor + 'txn> {
+        Box::new(MemoryCursor { txn: self, db: MemoryDatabase::from_backend(db), position: None })
+    }
+}
+
+impl<'env> fmt::Debug for MemoryWriteTransaction<'env> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemoryWriteTransaction")
+    }
+}
+
+/// The cursor remembers its position by (encoded) key and value, so it stays valid across removals.
+struct MemoryCursor<'txn, T> {
+    txn: &'txn T,
+    db: &'txn MemoryDatabase,
+    position: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<'txn, T: MemoryTransaction> MemoryCursor<'txn, T> {
+    fn view<R, F: FnOnce(&TableView) -> R>(&self, f: F) -> R {
+        self.txn.view(self.db, f)
+    }
+
+    /// Moves the cursor to `entry` and returns it decoded. The position is left unchanged if there is no entry.
+    fn move_to(&mut self, entry: Option<(Vec<u8>, Vec<u8>)>) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        self.position = Some(entry?);
+        self.current()
+    }
+
+    /// The decoded entry at the current position, borrowed from the cursor.
+    fn current(&self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let (key, value) = self.position.as_ref()?;
+        Some((self.db.decode_key(key), self.db.decode_value(value)))
+    }
+
+    fn move_within_key<F>(&mut self, f: F) -> Option<(Cow<[u8]>, Cow<[u8]>)> where F: FnOnce(&[Vec<u8>], &[u8]) -> Option<Vec<u8>> {
+        let (key, value) = self.position.clone()?;
+        let value = self.view(|view| f(view.values(&key)?, &value));
+        self.move_to(value.map(|value| (key, value)))
+    }
+}
+
+impl<'txn, T: MemoryTransaction> BackendCursor for MemoryCursor<'txn, T> {
+    fn first(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let entry = self.view(|view| view.first_entry_after(Bound::Unbounded));
+        self.move_to(entry)
+    }
+
+    fn first_duplicate(&mut self) -> Option<Cow<[u8]>> {
+        self.move_within_key(|values, _| values.first().cloned()).map(|(_, value)| value)
+    }
+
+    fn last(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let entry = self.view(|view| view.last_entry_before(Bound::Unbounded));
+        self.move_to(entry)
+    }
+
+    fn last_duplicate(&mut self) -> Option<Cow<[u8]>> {
+        self.move_within_key(|values, _| values.last().cloned()).map(|(_, value)| value)
+    }
+
+    fn seek_key_value(&mut self, key: &[u8], value: &[u8]) -> bool {
+        let key = self.db.encode_key(key).into_owned();
+        let value = self.db.encode_value(value).into_owned();
+        let exists = self.view(|view| view.values(&key).map_or(false, |values| values.contains(&value)));
+        self.move_to(if exists { Some((key, value)) } else { None }).is_some()
+    }
+
+    fn seek_key_nearest_value(&mut self, key: &[u8], value: &[u8]) -> Option<Cow<[u8]>> {
+        let key = self.db.encode_key(key).into_owned();
+        let value = self.db.encode_value(value);
+        let nearest = self.view(|view| view.values(&key)?.iter().find(|v| v.as_slice() >= &*value).cloned());
+        self.move_to(nearest.map(|nearest| (key, nearest))).map(|(_, value)| value)
+    }
+
+    fn get_current(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let exists = {
+            let (key, value) = self.position.as_ref()?;
+            self.view(|view| view.values(key).map_or(false, |values| values.contains(value)))
+        };
+        if !exists {
+            return None;
+        }
+        self.current()
+    }
+
+    fn next(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let (key, value) = match self.position.clone() {
+            Some(position) => position,
+            None => return self.first(),
+        };
+        let entry = self.view(|view| {
+            view.values(&key)
+                .and_then(|values| values.iter().find(|v| **v > value).cloned())
+                .map(|next| (key.clone(), next))
+                .or_else(|| view.first_entry_after(Bound::Excluded(key.as_slice())))
+        });
+        self.move_to(entry)
+    }
+
+    fn next_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        self.move_within_key(|values, value| values.iter().find(|v| v.as_slice() > value).cloned())
+    }
+
+    fn next_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let (key, _) = match self.position.clone() {
+            Some(position) => position,
+            None => return self.first(),
+        };
+        let entry = self.view(|view| view.first_entry_after(Bound::Excluded(key.as_slice())));
+        self.move_to(entry)
+    }
+
+    fn prev(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let (key, value) = match self.position.clone() {
+            Some(position) => position,
+            None => return self.last(),
+        };
+        let entry = self.view(|view| {
+            view.values(&key)
+                .and_then(|values| values.iter().rev().find(|v| **v < value).cloned())
+                .map(|prev| (key.clone(), prev))
+                .or_else(|| view.last_entry_before(Bound::Excluded(key.as_slice())))
+        });
+        self.move_to(entry)
+    }
+
+    fn prev_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        self.move_within_key(|values, value| values.iter().rev().find(|v| v.as_slice() < value).cloned())
+    }
+
+    fn prev_no_duplicate(&mut self) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let (key, _) = match self.position.clone() {
+            Some(position) => position,
+            None => return self.last(),
+        };
+        let entry = self.view(|view| view.last_entry_before(Bound::Excluded(key.as_slice())));
+        self.move_to(entry)
+    }
+
+    fn seek_key(&mut self, key: &[u8]) -> Option<Cow<[u8]>> {
+        self.seek_key_both(key).map(|(_, value)| value)
+    }
+
+    fn seek_key_both(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let key = self.db.encode_key(key).into_owned();
+        let value = self.view(|view| view.values(&key)?.first().cloned());
+        self.move_to(value.map(|value| (key, value)))
+    }
+
+    fn seek_range_key(&mut self, key: &[u8]) -> Option<(Cow<[u8]>, Cow<[u8]>)> {
+        let key = self.db.encode_key(key);
+        let entry = self.view(|view| view.first_entry_after(Bound::Included(&*key)));
+        self.move_to(entry)
+    }
+
+    fn count_duplicates(&mut self) -> usize {
+        match self.position {
+            Some((ref key, _)) => self.view(|view| view.values(key).map_or(0, <[Vec<u8>]>::len)),
+            None => 0,
+        }
+    }
+}
+
+impl<'txn, 'env> BackendWriteCursor for MemoryCursor<'txn, MemoryWriteTransaction<'env>> {
+    fn remove(&mut self) {
+        if let Some((ref key, ref value)) = self.position {
+            self.txn.remove_encoded_item(self.db, key, value);
+        }
+    }
+}
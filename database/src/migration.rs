@@ -0,0 +1,225 @@
+use std::error::Error;
+use std::fmt;
+
+use super::*;
+
+/// Name of the database holding the schema version of each store.
+pub const SCHEMA_DB_NAME: &str = "Schema";
+
+/// The version of stores that were written before schema versioning was introduced.
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+/// A single upgrade step of a store from `version - 1` to `version`.
+///
+/// A step opens the databases it needs and commits its own write transactions. The new version
+/// is only recorded once the step succeeded, so steps must be safe to run again after an
+/// interruption.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&Environment) -> Result<(), String>,
+}
+
+/// The schema version of a store as written by this binary, together with the steps that
+/// upgrade older versions to it.
+pub struct StoreSchema {
+    pub name: &'static str,
+    pub version: u32,
+    pub migrations: Vec<Migration>,
+    /// Tells whether the store was never written to. New stores are stamped with the current
+    /// version instead of being migrated.
+    pub is_new: fn(&Environment) -> bool,
+}
+
+impl StoreSchema {
+    pub fn new(name: &'static str, version: u32) -> Self {
+        StoreSchema { name, version, migrations: Vec::new(), is_new: |_| false }
+    }
+
+    pub fn with_new_store_check(mut self, is_new: fn(&Environment) -> bool) -> Self {
+        self.is_new = is_new;
+        self
+    }
+
+    pub fn with_migration(mut self, version: u32, description: &'static str, migrate: fn(&Environment) -> Result<(), String>) -> Self {
+        self.migrations.push(Migration { version, description, migrate });
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was written by a newer binary.
+    UnsupportedVersion { store: &'static str, version: u32, supported: u32 },
+    MissingMigration { store: &'static str, version: u32 },
+    MigrationFailed { store: &'static str, version: u32, reason: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::UnsupportedVersion { store, version, supported } => {
+                write!(f, "Store {} has schema version {}, but this binary only supports up to version {}. Please upgrade or use a new data directory.", store, version, supported)
+            },
+            MigrationError::MissingMigration { store, version } => {
+                write!(f, "No migration of store {} to schema version {}", store, version)
+            },
+            MigrationError::MigrationFailed { store, version, reason } => {
+                write!(f, "Migration of store {} to schema version {} failed: {}", store, version, reason)
+            },
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+/// Returns the schema version of a store or `None` if it was never recorded.
+pub fn schema_version(env: &Environment, store: &str) -> Option<u32> {
+    let db = env.open_database(SCHEMA_DB_NAME.to_string());
+    let txn = ReadTransaction::new(env);
+    txn.get(&db, store)
+}
+
+/// Upgrades all given stores to their current schema version.
+///
+/// New stores are stamped with their current version. Other stores without a recorded version
+/// are assumed to be at `INITIAL_SCHEMA_VERSION`. This fails without touching any store if one
+/// of them was written by a newer binary that records schema versions.
+pub fn migrate(env: &Environment, schemas: &[StoreSchema]) -> Result<(), MigrationError> {
+    let db = env.open_database(SCHEMA_DB_NAME.to_string());
+
+    for schema in schemas {
+        let version = schema_version(env, schema.name).unwrap_or(INITIAL_SCHEMA_VERSION);
+        if version > schema.version {
+            return Err(MigrationError::UnsupportedVersion { store: schema.name, version, supported: schema.version });
+        }
+    }
+
+    for schema in schemas {
+        let recorded = schema_version(env, schema.name);
+        let mut version = match recorded {
+            Some(version) => version,
+            None if (schema.is_new)(env) => schema.version,
+            None => INITIAL_SCHEMA_VERSION,
+        };
+
+        while version < schema.version {
+            let next = version + 1;
+            let migration = schema.migrations.iter()
+                .find(|migration| migration.version == next)
+                .ok_or(MigrationError::MissingMigration { store: schema.name, version: next })?;
+
+            info!("Migrating {} to schema version {}: {}", schema.name, next, migration.description);
+            (migration.migrate)(env)
+                .map_err(|reason| MigrationError::MigrationFailed { store: schema.name, version: next, reason })?;
+
+            let mut txn = WriteTransaction::new(env);
+            txn.put(&db, schema.name, &next);
+            txn.commit();
+            version = next;
+        }
+
+        if recorded.is_none() {
+            let mut txn = WriteTransaction::new(env);
+            txn.put(&db, schema.name, &version);
+            txn.commit();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::MemoryEnvironment;
+
+    use super::*;
+
+    fn add_marker(env: &Environment) -> Result<(), String> {
+        let db = env.open_database("test".to_string());
+        let mut txn = WriteTransaction::new(env);
+        let count: u32 = txn.get(&db, "migrations").unwrap_or(0);
+        txn.put(&db, "migrations", &(count + 1));
+        txn.commit();
+        Ok(())
+    }
+
+    fn fail(_env: &Environment) -> Result<(), String> {
+        Err("broken".to_string())
+    }
+
+    fn migrations_run(env: &Environment) -> u32 {
+        let db = env.open_database("test".to_string());
+        ReadTransaction::new(env).get(&db, "migrations").unwrap_or(0)
+    }
+
+    #[test]
+    fn it_records_the_initial_version() {
+        let env = MemoryEnvironment::new();
+        migrate(&env, &[StoreSchema::new("Store", INITIAL_SCHEMA_VERSION)]).unwrap();
+        assert_eq!(schema_version(&env, "Store"), Some(INITIAL_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn it_runs_migrations_in_order() {
+        let env = MemoryEnvironment::new();
+        migrate(&env, &[StoreSchema::new("Store", 1)]).unwrap();
+
+        let schema = StoreSchema::new("Store", 3)
+            .with_migration(3, "third", add_marker)
+            .with_migration(2, "second", add_marker);
+        migrate(&env, &[schema]).unwrap();
+        assert_eq!(schema_version(&env, "Store"), Some(3));
+        assert_eq!(migrations_run(&env), 2);
+
+        // Running again is a no-op.
+        let schema = StoreSchema::new("Store", 3)
+            .with_migration(3, "third", add_marker)
+            .with_migration(2, "second", add_marker);
+        migrate(&env, &[schema]).unwrap();
+        assert_eq!(migrations_run(&env), 2);
+    }
+
+    #[test]
+    fn it_stamps_new_stores_with_the_current_version() {
+        let env = MemoryEnvironment::new();
+        let schema = StoreSchema::new("Store", 3)
+            .with_migration(2, "second", add_marker)
+            .with_migration(3, "third", add_marker)
+            .with_new_store_check(|_| true);
+        migrate(&env, &[schema]).unwrap();
+        assert_eq!(schema_version(&env, "Store"), Some(3));
+        assert_eq!(migrations_run(&env), 0);
+    }
+
+    #[test]
+    fn it_rejects_newer_databases() {
+        let env = MemoryEnvironment::new();
+        migrate(&env, &[StoreSchema::new("Store", 2).with_migration(2, "second", add_marker)]).unwrap();
+
+        match migrate(&env, &[StoreSchema::new("Store", 1)]) {
+            Err(MigrationError::UnsupportedVersion { store: "Store", version: 2, supported: 1 }) => {},
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_version_if_a_migration_fails() {
+        let env = MemoryEnvironment::new();
+        let schema = StoreSchema::new("Store", 3)
+            .with_migration(2, "second", add_marker)
+            .with_migration(3, "third", fail);
+
+        match migrate(&env, &[schema]) {
+            Err(MigrationError::MigrationFailed { store: "Store", version: 3, .. }) => {},
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(schema_version(&env, "Store"), Some(2));
+        assert_eq!(migrations_run(&env), 1);
+
+        match migrate(&env, &[StoreSchema::new("Store", 4)]) {
+            Err(MigrationError::MissingMigration { store: "Store", version: 3 }) => {},
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
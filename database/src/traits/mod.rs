@@ -122,3 +122,9 @@ as_lmdb_bytes!([i64]);
 as_lmdb_bytes!([f32]);
 as_lmdb_bytes!([f64]);
 as_lmdb_bytes!([char]);
+
+impl AsDatabaseBytes for String {
+    fn as_database_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::{Arc, Weak};
 use std::fmt;
+use std::time::{Duration, Instant};
 
 use macros::upgrade_weak;
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
@@ -24,7 +25,9 @@ use crate::sender::Sender;
 #[derive(Clone, Debug)]
 pub enum AggregationEvent {
     Complete { best: MultiSignature },
-    //LevelComplete { level: usize },
+    /// A level received signatures from all of its peers. `elapsed` is the time since the
+    /// aggregation was started.
+    LevelComplete { level: usize, elapsed: Duration },
     //Aborted,
 }
 
@@ -68,6 +71,9 @@ pub struct Aggregation<P: Protocol> {
     /// Weak reference to the Aggregation itself
     self_weak: MutableOnce<Weak<Self>>,
 
+    /// When the aggregation was started
+    started: Instant,
+
     /// Notifications for completion
     pub notifier: RwLock<PassThroughNotifier<'static, AggregationEvent>>,
 }
@@ -91,6 +97,7 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
                 contribution: None,
             }),
             self_weak: MutableOnce::new(Weak::new()),
+            started: Instant::now(),
             notifier: RwLock::new(PassThroughNotifier::new()),
         });
 
@@ -216,6 +223,7 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
         let store = store.upgradable_read();
 
         // check if level is completed
+        let mut completed = false;
         {
             let mut level_state = level.state.write();
 
@@ -231,6 +239,7 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
             if best.len() == level.num_peers() {
                 trace!("Level {} complete", level.id);
                 level_state.receive_completed = true;
+                completed = true;
 
                 if level.id + 1 < self.levels.len() {
                     // activate next level
@@ -248,6 +257,16 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
                 }
             }
         }
+
+        // drop store before notify
+        drop(store);
+
+        if completed {
+            self.notifier.read().notify(AggregationEvent::LevelComplete {
+                level: level.id,
+                elapsed: self.started.elapsed(),
+            });
+        }
     }
 
     /// Check if the best signature is final
@@ -8,6 +8,8 @@ use sha2::{Sha256, Sha512, Digest};
 use beserial::{Serialize, Deserialize};
 use hex::FromHex;
 use nimiq_macros::{add_hex_io_fns_typed_arr, create_typed_array};
+#[cfg(feature = "serde-derive")]
+use nimiq_macros::add_serde_hex_fns;
 
 use std::cmp::Ordering;
 use std::fmt::{Debug, Error, Formatter};
@@ -96,6 +98,8 @@ impl<H> SerializeContent for H where H: HashOutput {
 const BLAKE2B_LENGTH : usize = 32;
 create_typed_array!(Blake2bHash, u8, BLAKE2B_LENGTH);
 add_hex_io_fns_typed_arr!(Blake2bHash, BLAKE2B_LENGTH);
+#[cfg(feature = "serde-derive")]
+add_serde_hex_fns!(Blake2bHash);
 pub struct Blake2bHasher(Blake2b);
 impl HashOutput for Blake2bHash {
     type Builder = Blake2bHasher;
@@ -145,6 +149,8 @@ const NIMIQ_ARGON2_SALT: &str = "nimiqrocks!";
 const DEFAULT_ARGON2_COST : u32 = 512;
 create_typed_array!(Argon2dHash, u8, ARGON2D_LENGTH);
 add_hex_io_fns_typed_arr!(Argon2dHash, ARGON2D_LENGTH);
+#[cfg(feature = "serde-derive")]
+add_serde_hex_fns!(Argon2dHash);
 pub struct Argon2dHasher {
     buf: Vec<u8>,
     config: argon2::Config<'static>,
@@ -212,6 +218,8 @@ impl Hasher for Argon2dHasher {
 const SHA256_LENGTH : usize = 32;
 create_typed_array!(Sha256Hash, u8, SHA256_LENGTH);
 add_hex_io_fns_typed_arr!(Sha256Hash, SHA256_LENGTH);
+#[cfg(feature = "serde-derive")]
+add_serde_hex_fns!(Sha256Hash);
 pub struct Sha256Hasher(Sha256);
 impl HashOutput for Sha256Hash {
     type Builder = Sha256Hasher;
@@ -24,7 +24,12 @@ impl ExtendedPrivateKey {
 
     /// Returns the corresponding master extended private key for a seed.
     pub fn from_seed(seed: Vec<u8>) -> Self {
-        let hash = compute_hmac_sha512(&B_CURVE, seed.as_slice());
+        Self::from_seed_bytes(&seed)
+    }
+
+    /// Like `from_seed`, but borrows the seed, so that callers don't need to copy it.
+    pub fn from_seed_bytes(seed: &[u8]) -> Self {
+        let hash = compute_hmac_sha512(&B_CURVE, seed);
         ExtendedPrivateKey::from(hash)
     }
 
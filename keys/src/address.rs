@@ -124,4 +124,26 @@ impl<'a> From<&'a KeyPair> for Address {
     fn from(key_pair: &'a KeyPair) -> Self {
         Address::from(&key_pair.public)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+
+    use super::Address;
+
+    // Addresses are written in their user friendly format, but hex is accepted as well.
+    impl Serialize for Address {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.to_user_friendly_address())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Address {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let s: String = Deserialize::deserialize(deserializer)?;
+            Address::from_any_str(&s).map_err(D::Error::custom)
+        }
+    }
+}
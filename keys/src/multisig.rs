@@ -241,6 +241,17 @@ impl PublicKey {
     }
 }
 
+/// Computes the aggregated (delinearized) public key of a set of signers.
+/// This is the public key a signature created with `KeyPair::partial_sign` verifies against.
+pub fn aggregate_public_keys(public_keys: &[PublicKey]) -> PublicKey {
+    let public_keys_hash = hash_public_keys(public_keys);
+    let delinearized_pk_sum: EdwardsPoint = public_keys.iter().map(|public_key| { public_key.delinearize(&public_keys_hash) }).sum();
+
+    let mut public_key_bytes: [u8; PublicKey::SIZE] = [0u8; PublicKey::SIZE];
+    public_key_bytes.copy_from_slice(delinearized_pk_sum.compress().as_bytes());
+    PublicKey::from(public_key_bytes)
+}
+
 fn hash_public_keys(public_keys: &[PublicKey]) -> [u8; 64] {
     // 1. Compute hash over public keys public_keys_hash = C = H(P_1 || ... || P_n).
     let mut h: sha2::Sha512 = sha2::Sha512::default();
@@ -4,9 +4,11 @@ use std::fmt::Formatter;
 use std::io;
 use std::str::FromStr;
 
+use clear_on_drop::clear::Clear;
 use ed25519_dalek;
 use hex;
 use hex::FromHex;
+use sha2::{Digest, Sha512};
 
 use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 use hash::{Hash, SerializeContent};
@@ -28,6 +30,33 @@ impl PrivateKey {
 
     #[inline]
     pub fn to_hex(&self) -> String { hex::encode(self.as_bytes()) }
+
+    /// Converts the key into the equivalent (clamped) X25519 secret key, for Diffie-Hellman key exchange.
+    /// This is the scalar Ed25519 derives from the key, so it matches `PublicKey::to_x25519`.
+    pub fn to_x25519(&self) -> X25519SecretKey {
+        let mut hash = Sha512::digest(self.as_bytes());
+        let mut key = X25519SecretKey([0u8; PrivateKey::SIZE]);
+        key.0.copy_from_slice(&hash[..PrivateKey::SIZE]);
+        Clear::clear(hash.as_mut_slice());
+        key.0[0] &= 248;
+        key.0[31] &= 127;
+        key.0[31] |= 64;
+        key
+    }
+}
+
+/// An X25519 secret key derived from a `PrivateKey`. It is cleared from memory when dropped.
+pub struct X25519SecretKey([u8; PrivateKey::SIZE]);
+
+impl X25519SecretKey {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; PrivateKey::SIZE] { &self.0 }
+}
+
+impl Drop for X25519SecretKey {
+    fn drop(&mut self) {
+        Clear::clear(&mut self.0[..]);
+    }
 }
 
 impl SecureGenerate for PrivateKey {
@@ -3,6 +3,7 @@ use std::io;
 use std::fmt;
 use std::str::FromStr;
 
+use curve25519_dalek::edwards::CompressedEdwardsY;
 use hex::FromHex;
 use ed25519_dalek;
 
@@ -36,6 +37,13 @@ impl PublicKey {
     pub fn to_hex(&self) -> String {
         hex::encode(self.as_bytes())
     }
+
+    /// Converts the key into the equivalent X25519 public key, for Diffie-Hellman key exchange.
+    /// Returns `None` if the key is not a valid curve point.
+    pub fn to_x25519(&self) -> Option<[u8; PublicKey::SIZE]> {
+        CompressedEdwardsY(*self.as_bytes()).decompress()
+            .map(|point| point.to_montgomery().to_bytes())
+    }
 }
 
 impl fmt::Display for PublicKey {
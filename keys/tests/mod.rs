@@ -1,3 +1,6 @@
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+
 use nimiq_keys::{Address, KeyPair, PrivateKey, PublicKey, SecureGenerate, Signature};
 
 mod multisig;
@@ -11,6 +14,14 @@ fn verify_created_signature() {
     assert_eq!(true, valid);
 }
 
+#[test]
+fn it_converts_keys_to_x25519() {
+    let key_pair = KeyPair::generate_default_csprng();
+    let scalar = Scalar::from_bits(*key_pair.private.to_x25519().as_bytes());
+    let public = (&scalar * &ED25519_BASEPOINT_TABLE).to_montgomery().to_bytes();
+    assert_eq!(Some(public), key_pair.public.to_x25519());
+}
+
 #[test]
 fn falsify_wrong_signature() {
     let key_pair = KeyPair::generate_default_csprng();
@@ -231,6 +231,8 @@ fn it_can_aggregate_public_keys() {
         public_key_bytes.copy_from_slice(delinearized_pk_sum.compress().as_bytes());
         let aggregated_public_key = PublicKey::from(public_key_bytes);
         assert_eq!(aggregated_public_key, test.agg_pub_key);
+
+        assert_eq!(aggregate_public_keys(&test.pub_keys), test.agg_pub_key);
     }
 }
 
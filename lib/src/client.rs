@@ -23,6 +23,52 @@ pub type Mempool = GenericMempool<Blockchain>;
 pub type Network = GenericNetwork<Blockchain>;
 
 
+/// Creates the network config from the client configuration and initializes the peer key.
+///
+/// TODO: `NetworkConfig` could use some refactoring. So we might as well adapt it to the client
+/// API.
+///
+pub(crate) fn network_config(config: &ClientConfig) -> Result<NetworkConfig, Error> {
+    let mut network_config = match config.protocol.clone() {
+        ProtocolConfig::Dumb => {
+            NetworkConfig::new_dumb_network_config()
+        },
+        ProtocolConfig::Rtc => {
+            panic!("WebRTC is not yet implemented")
+        },
+        ProtocolConfig::Ws { host, port } => {
+            NetworkConfig::new_ws_network_config(host, port, false, config.reverse_proxy.clone())
+        },
+        ProtocolConfig::Wss { host, port, pkcs12_key_file, pkcs12_passphrase } => {
+            let pkcs12_key_file = pkcs12_key_file.to_str()
+                .unwrap_or_else(|| panic!("Failed to convert path to PKCS#12 key file to string: {}", pkcs12_key_file.display()))
+                .to_string();
+            NetworkConfig::new_wss_network_config(host, port, false, pkcs12_key_file, pkcs12_passphrase)
+        },
+        ProtocolConfig::Noise { host, port } => {
+            NetworkConfig::new_noise_network_config(host, port, false)
+        },
+    };
+
+    // Set user agent
+    network_config.set_user_agent(config.user_agent.clone().into());
+
+    // Set custom seeds
+    network_config.set_additional_seeds(config.seeds.clone());
+
+    // Connect to Noise peers
+    network_config.set_connect_noise(config.connect_noise);
+
+    // Set traffic limits
+    network_config.set_traffic_limits(config.traffic_limits.clone());
+
+    // Initialize peer key
+    config.storage.init_key_store(&mut network_config)?;
+
+    Ok(network_config)
+}
+
+
 /// Holds references to the relevant structs. This is then Arc'd in `Client` and a nice API is
 /// exposed.
 ///
@@ -51,36 +97,9 @@ impl TryFrom<ClientConfig> for ClientInner {
     type Error = Error;
 
     fn try_from(config: ClientConfig) -> Result<Self, Self::Error> {
-        // Create network config
-        // TODO: `NetworkConfig` could use some refactoring. So we might as well adapt it to the
-        // client API.
-
-        let mut network_config = match config.protocol {
-            ProtocolConfig::Dumb => {
-                NetworkConfig::new_dumb_network_config()
-            },
-            ProtocolConfig::Rtc => {
-                panic!("WebRTC is not yet implemented")
-            },
-            ProtocolConfig::Ws { host, port } => {
-                NetworkConfig::new_ws_network_config(host, port, false, config.reverse_proxy)
-            },
-            ProtocolConfig::Wss { host, port, pkcs12_key_file, pkcs12_passphrase } => {
-                let pkcs12_key_file = pkcs12_key_file.to_str()
-                    .unwrap_or_else(|| panic!("Failed to convert path to PKCS#12 key file to string: {}", pkcs12_key_file.display()))
-                    .to_string();
-                NetworkConfig::new_wss_network_config(host, port, false, pkcs12_key_file, pkcs12_passphrase)
-            }
-        };
-
-        // Set user agent
-        network_config.set_user_agent(config.user_agent.into());
-
-        // Set custom seeds
-        network_config.set_additional_seeds(config.seeds);
-
-        // Initialize peer key
-        config.storage.init_key_store(&mut network_config)?;
+        // The network config is only modified if the validator is enabled
+        #[allow(unused_mut)]
+        let mut network_config = network_config(&config)?;
 
         // Load validator key (before we give away ownership of the storage config
         #[cfg(feature="validator")]
@@ -98,13 +117,15 @@ impl TryFrom<ClientConfig> for ClientInner {
             }
         }
 
-        // Open database
-        let environment = config.storage.database(config.network, config.consensus, config.database)?;
-
-        // Create Nimiq consensus
         if !config.network.is_albatross() {
             return Err(Error::config_error(&format!("{} is not compatible with Albatross", config.network)));
         }
+
+        // Open database and bring it up to the schema of this binary
+        let environment = config.storage.database(config.network, config.consensus, config.database)?;
+        database::migration::migrate(&environment, &blockchain::schema::schemas())?;
+
+        // Create Nimiq consensus
         let consensus = Consensus::new(
             environment.clone(),
             config.network,
@@ -112,6 +133,9 @@ impl TryFrom<ClientConfig> for ClientInner {
             config.mempool,
         )?;
 
+        // Prune the chain store whenever a macro block is finalized
+        config.pruning.register(&consensus.blockchain);
+
         #[cfg(feature="validator")]
         let validator = config.validator.map(|_config| {
             Validator::new(Arc::clone(&consensus), validator_key)
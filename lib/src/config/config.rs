@@ -7,6 +7,7 @@ use derive_builder::Builder;
 use enum_display_derive::Display;
 use url::Url;
 
+use blockchain::pruning::PruningPolicy;
 #[cfg(feature="validator")]
 use bls::SecureGenerate;
 #[cfg(feature="validator")]
@@ -16,6 +17,7 @@ use database::lmdb::{LmdbEnvironment, open as LmdbFlags};
 use database::volatile::VolatileEnvironment;
 use mempool::filter::Rules as MempoolRules;
 use mempool::MempoolConfig;
+use network::connection::traffic::TrafficLimits;
 use network::network_config::{NetworkConfig, ReverseProxyConfig, Seed};
 use network_primitives::address::{NetAddress, SeedList, PeerUri};
 use primitives::networks::NetworkId;
@@ -107,6 +109,20 @@ pub enum ProtocolConfig {
         pkcs12_passphrase: String,
     },
 
+    /// Accept encrypted TCP connections. Peers authenticate each other with their peer keys
+    /// in a Noise handshake, so no SSL certificate is needed.
+    ///
+    Noise {
+        /// The hostname of your machine. This must be a valid domain name or IP address as it
+        /// will be advertised to other peers in order for them to connect to you.
+        ///
+        host: String,
+
+        /// The port on which Nimiq will listen for incoming connections.
+        ///
+        port: u16,
+    },
+
     /// Accept incoming connections over WebRTC
     ///
     /// # Notes
@@ -253,6 +269,18 @@ impl StorageConfig {
     ///
     pub fn database(&self, network_id: NetworkId, consensus: ConsensusConfig, db_config: DatabaseConfig) -> Result<Environment, Error> {
         let db_name = format!("{}-{}-consensus", network_id, consensus).to_lowercase();
+        self.open_database(db_name, db_config)
+    }
+
+    /// Returns the database environment of a seeder for the given network ID. The seeder only
+    /// stores its peer addresses, so it doesn't share the database with the consensus.
+    ///
+    pub fn seeder_database(&self, network_id: NetworkId, db_config: DatabaseConfig) -> Result<Environment, Error> {
+        let db_name = format!("{}-seeder", network_id).to_lowercase();
+        self.open_database(db_name, db_config)
+    }
+
+    fn open_database(&self, db_name: String, db_config: DatabaseConfig) -> Result<Environment, Error> {
         info!("Opening database: {}", db_name);
 
         Ok(match self {
@@ -439,6 +467,40 @@ pub struct MetricsServerConfig {
     /// If specified, require HTTP basic auth with these credentials
     #[builder(setter(strip_option))]
     pub credentials: Option<Credentials>,
+
+    /// Serve metrics over HTTPS using the certificate of the `Wss` protocol. If this is not
+    /// specified, HTTPS is used if the node runs the `Wss` protocol and plain HTTP otherwise.
+    ///
+    /// Default: `None`
+    ///
+    #[builder(setter(strip_option), default)]
+    pub tls: Option<bool>,
+}
+
+#[cfg(feature="seeder")]
+#[derive(Debug, Clone, Builder)]
+#[builder(setter(into))]
+pub struct SeederConfig {
+    /// Bind the HTTP server that serves the seed list to the specified IP address.
+    ///
+    /// Default: `127.0.0.1`
+    ///
+    #[builder(setter(strip_option))]
+    pub bind_to: Option<IpAddr>,
+
+    /// Bind the server to the specified port.
+    ///
+    /// Default: `8651`
+    ///
+    #[builder(default="consts::SEEDER_DEFAULT_PORT")]
+    pub port: u16,
+
+    /// The maximum number of peers in the seed list.
+    ///
+    /// Default: `100`
+    ///
+    #[builder(default="consts::SEEDER_DEFAULT_MAX_SEEDS")]
+    pub max_seeds: usize,
 }
 
 /// Client configuration
@@ -497,6 +559,13 @@ pub struct ClientConfig {
     #[builder(default)]
     pub database: DatabaseConfig,
 
+    /// Which chain data is pruned after each macro block.
+    ///
+    /// Default prunes forks, but keeps all micro block bodies.
+    ///
+    #[builder(default)]
+    pub pruning: PruningPolicy,
+
     /// The mempool filter rules
     ///
     #[builder(default, setter(custom))]
@@ -507,6 +576,20 @@ pub struct ClientConfig {
     #[builder(setter(custom), default)]
     pub seeds: Vec<Seed>,
 
+    /// Whether to connect to `noise://` peers. Nodes using the Noise protocol always do.
+    ///
+    /// Default is `false`.
+    ///
+    #[builder(default)]
+    pub connect_noise: bool,
+
+    /// Upload and download limits over all connections, in bytes per second.
+    ///
+    /// Default is unlimited.
+    ///
+    #[builder(default)]
+    pub traffic_limits: TrafficLimits,
+
     /// The optional validator configuration
     ///
     #[cfg(feature="validator")]
@@ -530,6 +613,13 @@ pub struct ClientConfig {
     #[cfg(feature="metrics-server")]
     #[builder(default)]
     pub metrics_server: Option<MetricsServerConfig>,
+
+    /// The optional seeder configuration. If this is set, the node runs as a seeder instead of a
+    /// regular client.
+    ///
+    #[cfg(feature="seeder")]
+    #[builder(default)]
+    pub seeder: Option<SeederConfig>,
 }
 
 impl ClientConfig {
@@ -640,6 +730,20 @@ impl ClientConfigBuilder {
         })
     }
 
+    /// Sets the *Noise* (encrypted TCP) protocol
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The hostname at which the client is accepting connections.
+    /// * `port` - The port on which the client is accepting connections.
+    ///
+    pub fn noise<H: Into<String>, P: Into<Option<u16>>>(&mut self, host: H, port: P) -> &mut Self {
+        self.protocol(ProtocolConfig::Noise {
+            host: host.into(),
+            port: port.into().unwrap_or(consts::NOISE_DEFAULT_PORT)
+        })
+    }
+
     /// Sets the reverse proxy configuration. You need to set this if you run your node behind
     /// a reverse proxy.
     ///
@@ -722,6 +826,12 @@ impl ClientConfigBuilder {
                     pkcs12_passphrase: tls.identity_password,
                 }
             },
+            config_file::Protocol::Noise => ProtocolConfig::Noise {
+                host: config_file.network.host.clone()
+                    .ok_or_else(|| Error::config_error("Hostname not set."))?,
+                port: config_file.network.port.clone()
+                    .unwrap_or(consts::NOISE_DEFAULT_PORT),
+            },
             config_file::Protocol::Rtc => ProtocolConfig::Rtc,
         });
 
@@ -729,6 +839,18 @@ impl ClientConfigBuilder {
         config_file.network.user_agent.as_ref()
             .map(|user_agent| self.user_agent(user_agent.clone()));
 
+        // Configure Noise
+        self.connect_noise(config_file.network.connect_noise.unwrap_or(false));
+
+        // Configure traffic limits
+        if config_file.network.upload_limit == Some(0) || config_file.network.download_limit == Some(0) {
+            return Err(Error::config_error("Traffic limits must be positive."));
+        }
+        self.traffic_limits(TrafficLimits {
+            upload: config_file.network.upload_limit.map(|limit| limit.saturating_mul(1000)),
+            download: config_file.network.download_limit.map(|limit| limit.saturating_mul(1000)),
+        });
+
         // Configure consensus
         self.consensus(config_file.consensus.consensus_type);
 
@@ -755,6 +877,12 @@ impl ClientConfigBuilder {
         // Configure database
         self.database(config_file.database.clone());
 
+        // Configure pruning
+        self.pruning(PruningPolicy {
+            prune_forks: config_file.database.prune_forks.unwrap_or(true),
+            keep_micro_block_bodies: config_file.database.keep_micro_block_bodies,
+        });
+
         // Configure reverse proxy config
         config_file.reverse_proxy.as_ref()
             .map(|reverse_proxy| {
@@ -834,6 +962,21 @@ impl ClientConfigBuilder {
                     bind_to,
                     port: metrics_config.port.unwrap_or(consts::METRICS_DEFAULT_PORT),
                     credentials,
+                    tls: metrics_config.tls,
+                }));
+            }
+        }
+
+        // Configure seeder
+        #[cfg(feature="seeder")] {
+            if let Some(seeder_config) = &config_file.seeder {
+                let bind_to = seeder_config.bind.as_ref()
+                    .and_then(|addr| addr.into_ip_address());
+
+                self.seeder = Some(Some(SeederConfig {
+                    bind_to,
+                    port: seeder_config.port.unwrap_or(consts::SEEDER_DEFAULT_PORT),
+                    max_seeds: seeder_config.max_seeds.unwrap_or(consts::SEEDER_DEFAULT_MAX_SEEDS),
                 }));
             }
         }
@@ -861,6 +1004,7 @@ impl ClientConfigBuilder {
             match &mut self.protocol {
                 Some(ProtocolConfig::Ws { host, .. }) => *host = hostname,
                 Some(ProtocolConfig::Wss { host, .. }) => *host = hostname,
+                Some(ProtocolConfig::Noise { host, .. }) => *host = hostname,
                 _ => {} // just ignore this. or return an error?
             }
         });
@@ -870,6 +1014,7 @@ impl ClientConfigBuilder {
             match &mut self.protocol {
                 Some(ProtocolConfig::Ws { port, .. }) => *port = new_port,
                 Some(ProtocolConfig::Wss { port, .. }) => *port = new_port,
+                Some(ProtocolConfig::Noise { port, .. }) => *port = new_port,
                 _ => () // just ignore this. or return an error?
             }
         });
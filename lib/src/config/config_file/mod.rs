@@ -16,8 +16,9 @@ use network_primitives::{address, protocol};
 use network_primitives::address::peer_uri::PeerUriError;
 use network_primitives::networks::NetworkId;
 use network::network_config::{ReverseProxyConfig, Seed as NetworkSeed};
+use primitives::account::AccountType;
 use primitives::coin::Coin;
-use keys::PublicKey;
+use keys::{Address, PublicKey};
 use mempool::{MempoolConfig};
 use mempool::filter::{Rules as MempoolRules, MempoolFilter};
 
@@ -43,6 +44,7 @@ pub struct ConfigFile {
     pub rpc_server: Option<RpcServerSettings>,
     pub ws_rpc_server: Option<WsRpcServerSettings>,
     pub metrics_server: Option<MetricsServerSettings>,
+    pub seeder: Option<SeederSettings>,
     pub reverse_proxy: Option<ReverseProxySettings>,
     #[serde(default)]
     pub log: LogSettings,
@@ -128,6 +130,12 @@ pub struct NetworkSettings {
     pub user_agent: Option<String>,
     pub tls: Option<TlsSettings>,
     pub instant_inbound: Option<bool>,
+    /// Connect to `noise://` peers.
+    pub connect_noise: Option<bool>,
+    /// Upload limit in kB/s.
+    pub upload_limit: Option<u32>,
+    /// Download limit in kB/s.
+    pub download_limit: Option<u32>,
 }
 
 #[derive(Debug, Fail)]
@@ -213,6 +221,7 @@ impl TryFrom<Seed> for NetworkSeed {
 pub enum Protocol {
     Wss,
     Ws,
+    Noise,
     Dumb,
     Rtc,
 }
@@ -229,6 +238,7 @@ impl From<Protocol> for protocol::Protocol {
             Protocol::Dumb => Self::Dumb,
             Protocol::Ws => Self::Ws,
             Protocol::Wss => Self::Wss,
+            Protocol::Noise => Self::Noise,
             Protocol::Rtc => Self::Rtc,
         }
     }
@@ -373,6 +383,18 @@ pub struct MetricsServerSettings {
     pub bind: Option<address::NetAddress>,
     pub port: Option<u16>,
     pub password: Option<String>,
+    pub tls: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct SeederSettings {
+    #[serde(deserialize_with = "deserialize_string_option")]
+    #[serde(default)]
+    pub bind: Option<address::NetAddress>,
+    pub port: Option<u16>,
+    pub max_seeds: Option<usize>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -440,6 +462,8 @@ pub struct DatabaseSettings {
     pub size: Option<usize>,
     pub max_dbs: Option<u32>,
     pub no_lmdb_sync: Option<bool>,
+    pub prune_forks: Option<bool>,
+    pub keep_micro_block_bodies: Option<u32>,
 }
 
 impl Default for DatabaseSettings {
@@ -449,6 +473,8 @@ impl Default for DatabaseSettings {
             size: Some(1024 * 1024 * 50),
             max_dbs: Some(10),
             no_lmdb_sync: None,
+            prune_forks: None,
+            keep_micro_block_bodies: None,
         }
     }
 }
@@ -496,6 +522,15 @@ pub struct MempoolFilterSettings {
     #[serde(deserialize_with = "deserialize_coin")]
     #[serde(default)]
     pub sender_balance: Coin,
+    #[serde(deserialize_with = "deserialize_address_vec")]
+    #[serde(default)]
+    pub deny_addresses: Vec<Address>,
+    #[serde(deserialize_with = "deserialize_address_vec")]
+    #[serde(default)]
+    pub allow_senders: Vec<Address>,
+    #[serde(deserialize_with = "deserialize_string_vec")]
+    #[serde(default)]
+    pub deny_creation_types: Vec<AccountType>,
 }
 
 impl From<MempoolSettings> for MempoolConfig {
@@ -526,6 +561,9 @@ impl From<MempoolFilterSettings> for MempoolRules {
             creation_value: f.creation_value,
             sender_balance: f.sender_balance,
             recipient_balance: f.recipient_balance,
+            denied_addresses: f.deny_addresses.into_iter().collect(),
+            allowed_senders: f.allow_senders.into_iter().collect(),
+            denied_creation_types: f.deny_creation_types.into_iter().collect(),
         }
     }
 }
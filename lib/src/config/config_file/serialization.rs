@@ -5,6 +5,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Deserializer};
 use serde::de::Error;
+use keys::Address;
 use primitives::coin::Coin;
 
 
@@ -21,8 +22,6 @@ pub(crate) fn deserialize_string<'de, D, T>(deserializer: D) -> Result<T, D::Err
     T::from_str(&value).map_err(Error::custom)
 }
 
-// NOTE: This is currently unused, but might be used in future.
-#[allow(dead_code)]
 pub(crate) fn deserialize_string_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
     where D: Deserializer<'de>,
           T: FromStr,
@@ -31,6 +30,11 @@ pub(crate) fn deserialize_string_vec<'de, D, T>(deserializer: D) -> Result<Vec<T
     values.iter().map(|value| T::from_str(value).map_err(Error::custom)).collect()
 }
 
+pub(crate) fn deserialize_address_vec<'de, D>(deserializer: D) -> Result<Vec<Address>, D::Error> where D: Deserializer<'de> {
+    let values = Vec::<String>::deserialize(deserializer)?;
+    values.iter().map(|value| Address::from_any_str(value).map_err(Error::custom)).collect()
+}
+
 pub(crate) fn deserialize_string_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
     where D: Deserializer<'de>,
           T: FromStr,
@@ -4,6 +4,9 @@ use std::net::{IpAddr, Ipv4Addr};
 /// The default port for `ws` and `wss`.
 pub const WS_DEFAULT_PORT: u16 = 8443;
 
+/// The default port for `noise`.
+pub const NOISE_DEFAULT_PORT: u16 = 8445;
+
 /// The default port for the reverse proxy
 pub const REVERSE_PROXY_DEFAULT_PORT: u16 = 8444;
 
@@ -16,6 +19,12 @@ pub const WS_RPC_DEFAULT_PORT: u16 = 8650;
 /// The default port for the metrics server
 pub const METRICS_DEFAULT_PORT: u16 = 8649;
 
+/// The default port on which the seeder serves its seed list
+pub const SEEDER_DEFAULT_PORT: u16 = 8651;
+
+/// The default maximum number of peers in the seed list
+pub const SEEDER_DEFAULT_MAX_SEEDS: usize = 100;
+
 /// Returns the default bind, i.e. localhost
 pub fn default_bind() -> IpAddr {
     IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))
@@ -5,6 +5,7 @@ use failure::Fail;
 use log::SetLoggerError;
 
 use database::lmdb::LmdbError;
+use database::migration::MigrationError;
 use database::volatile::VolatileDatabaseError;
 use network::error::Error as NetworkError;
 use utils::key_store::Error as KeyStoreError;
@@ -13,6 +14,8 @@ use consensus::Error as ConsensusError;
 use validator::error::Error as ValidatorError;
 #[cfg(feature="rpc-server")]
 use rpc_server::error::Error as RpcServerError;
+#[cfg(feature="seeder")]
+use blockchain_base::BlockchainError;
 
 
 #[derive(Debug, Fail)]
@@ -26,6 +29,9 @@ pub enum Error {
     #[fail(display = "I/O error: {}", _0)]
     Io(#[cause] IoError),
 
+    #[fail(display = "Database migration error: {}", _0)]
+    Migration(#[cause] MigrationError),
+
     #[fail(display = "Network error: {}", _0)]
     Network(#[cause] NetworkError),
 
@@ -47,7 +53,11 @@ pub enum Error {
     RpcServer(#[cause] RpcServerError),
 
     #[fail(display = "Logger error: {}", _0)]
-    Logging(#[cause] SetLoggerError)
+    Logging(#[cause] SetLoggerError),
+
+    #[cfg(feature="seeder")]
+    #[fail(display = "Blockchain error: {}", _0)]
+    Blockchain(#[cause] BlockchainError),
 }
 
 impl Error {
@@ -74,6 +84,12 @@ impl From<IoError> for Error {
     }
 }
 
+impl From<MigrationError> for Error {
+    fn from(e: MigrationError) -> Self {
+        Self::Migration(e)
+    }
+}
+
 impl From<VolatileDatabaseError> for Error {
     fn from(e: VolatileDatabaseError) -> Self {
         match e {
@@ -126,3 +142,10 @@ impl From<SetLoggerError> for Error {
         Self::Logging(e)
     }
 }
+
+#[cfg(feature="seeder")]
+impl From<BlockchainError> for Error {
+    fn from(e: BlockchainError) -> Self {
+        Self::Blockchain(e)
+    }
+}
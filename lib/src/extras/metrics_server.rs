@@ -1,15 +1,23 @@
+use std::sync::Arc;
+
 use metrics_server::MetricsServer;
+pub use metrics_server::TlsIdentity;
+use metrics_server::server::Metrics;
 use metrics_server::error::Error;
 use metrics_server::AlbatrossChainMetrics;
+#[cfg(feature="validator")]
+use metrics_server::ValidatorMetrics;
 
 use crate::config::config::MetricsServerConfig;
 use crate::client::Client;
 use crate::config::consts::default_bind;
 
 
-pub fn initialize_metrics_server(client: &Client, config: MetricsServerConfig, pkcs12_key_file: &str, pkcs12_passphrase: &str) -> Result<MetricsServer, Error> {
+/// Initializes the metrics server. Metrics are served over HTTPS if `tls` is set and over plain
+/// HTTP otherwise.
+pub fn initialize_metrics_server(client: &Client, config: MetricsServerConfig, tls: Option<TlsIdentity>) -> Result<MetricsServer, Error> {
     let ip = config.bind_to.unwrap_or_else(default_bind);
-    info!("Initializing metrics server: {}:{}", ip, config.port);
+    info!("Initializing metrics server: {}://{}:{}", if tls.is_some() { "https" } else { "http" }, ip, config.port);
 
     let (username, password) = if let Some(credentials) = config.credentials {
         (Some(credentials.username), Some(credentials.password))
@@ -18,13 +26,21 @@ pub fn initialize_metrics_server(client: &Client, config: MetricsServerConfig, p
         (None, None)
     };
 
-     Ok(MetricsServer::new::<_, AlbatrossChainMetrics>(
+    #[allow(unused_mut)]
+    let mut additional_metrics: Vec<Arc<dyn Metrics>> = Vec::new();
+    #[cfg(feature="validator")] {
+        if let Some(validator) = client.validator() {
+            additional_metrics.push(Arc::new(ValidatorMetrics::new(validator)));
+        }
+    }
+
+    Ok(MetricsServer::new::<_, AlbatrossChainMetrics>(
         ip,
         config.port,
         username,
         password,
-        pkcs12_key_file,
-        pkcs12_passphrase,
-        client.consensus()
+        tls,
+        client.consensus(),
+        additional_metrics
     )?)
 }
@@ -11,6 +11,8 @@ pub mod rpc_server;
 pub mod metrics_server;
 #[cfg(feature = "ws-rpc-server")]
 pub mod ws_rpc_server;
+#[cfg(feature = "seeder")]
+pub mod seeder;
 
 #[cfg(feature = "launcher")]
 pub mod launcher;
@@ -59,6 +59,9 @@ pub fn initialize_rpc_server(client: &Client, config: RpcServerConfig) -> Result
     let network_handler = NetworkHandler::new(&client.consensus());
     handler.add_module(network_handler);
 
+    let network_admin_handler = NetworkAdminHandler::new(&client.consensus());
+    handler.add_admin_module(network_admin_handler);
+
     let wallet_handler = WalletHandler::new(client.environment());
     let wallet_manager = Arc::clone(&wallet_handler.unlocked_wallets);
     handler.add_module(wallet_handler);
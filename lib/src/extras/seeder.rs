@@ -0,0 +1,88 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use futures::Future;
+
+use block_albatross::Block;
+use blockchain_base::AbstractBlockchain;
+use blockchain_base::genesis_chain::GenesisChain;
+use network::Network;
+use network::seed_server::SeedServer;
+use network_primitives::services::{ServiceFlags, Services};
+use network_primitives::time::NetworkTime;
+
+use crate::client::network_config;
+use crate::config::config::{ClientConfig, SeederConfig};
+use crate::config::consts::default_bind;
+use crate::error::Error;
+
+
+/// The seeder doesn't keep a blockchain, it only knows the genesis block.
+pub type SeederChain = GenesisChain<Block>;
+pub type SeederNetwork = Network<SeederChain>;
+
+
+/// A node that runs without a blockchain or consensus. It crawls the network and serves a signed
+/// list of reachable peers over HTTP. Nodes that connect to it receive addresses as usual.
+pub struct Seeder {
+    network: Arc<SeederNetwork>,
+    seed_server: Arc<SeedServer<SeederChain>>,
+    config: SeederConfig,
+}
+
+impl Seeder {
+    /// Creates the seeder from the client configuration. The `seeder` section must be set.
+    pub fn new(config: ClientConfig) -> Result<Self, Error> {
+        let seeder_config = config.seeder.clone()
+            .ok_or_else(|| Error::config_error("No seeder configured"))?;
+
+        if !config.network.is_albatross() {
+            return Err(Error::config_error(&format!("{} is not compatible with Albatross", config.network)));
+        }
+
+        // We don't provide any services, but are looking for full nodes.
+        let mut network_config = network_config(&config)?;
+        network_config.set_services(Services::new(ServiceFlags::NONE, ServiceFlags::FULL));
+
+        let environment = config.storage.seeder_database(config.network, config.database)?;
+        let network_time = Arc::new(NetworkTime::new());
+        let blockchain = Arc::new(SeederChain::new(environment.clone(), config.network, Arc::clone(&network_time))?);
+        let network = Network::new(blockchain, network_config, network_time, config.network, environment)?;
+        let seed_server = SeedServer::new(Arc::clone(&network), seeder_config.max_seeds);
+
+        Ok(Seeder {
+            network,
+            seed_server,
+            config: seeder_config,
+        })
+    }
+
+    /// Initializes the network stack.
+    pub fn initialize(&self) -> Result<(), Error> {
+        self.network.initialize()?;
+        Ok(())
+    }
+
+    /// Returns the future of the HTTP server that serves the seed list. Spawn this on the tokio
+    /// runtime.
+    pub fn serve(&self) -> Result<impl Future<Item=(), Error=()>, Error> {
+        let ip = self.config.bind_to.unwrap_or_else(default_bind);
+        info!("Initializing seed list server: {}:{}", ip, self.config.port);
+        Ok(self.seed_server.serve(&SocketAddr::new(ip, self.config.port))?)
+    }
+
+    /// Starts crawling the network and accepting inbound connections.
+    pub fn connect(&self) {
+        self.seed_server.start();
+    }
+
+    /// Returns a reference to the *Network* stack
+    pub fn network(&self) -> Arc<SeederNetwork> {
+        Arc::clone(&self.network)
+    }
+
+    /// Returns a reference to the seed server
+    pub fn seed_server(&self) -> Arc<SeedServer<SeederChain>> {
+        Arc::clone(&self.seed_server)
+    }
+}
@@ -27,6 +27,11 @@ extern crate nimiq_metrics_server as metrics_server;
 #[cfg(feature="ws-rpc-server")]
 extern crate nimiq_ws_rpc_server as ws_rpc_server;
 
+#[cfg(feature="seeder")]
+extern crate nimiq_block_albatross as block_albatross;
+#[cfg(feature="seeder")]
+extern crate nimiq_blockchain_base as blockchain_base;
+
 
 pub mod config;
 pub mod error;
@@ -94,6 +94,32 @@ macro_rules! add_hex_io_fns_typed_arr {
     };
 }
 
+/// Implements serde's `Serialize` and `Deserialize` for a type with beserial impls, using the hex
+/// encoding of its binary representation. The calling crate must depend on `serde` and `hex`.
+#[macro_export]
+macro_rules! add_serde_hex_fns {
+    ($name: ty) => {
+        impl ::serde::Serialize for $name {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&::hex::encode(::beserial::Serialize::serialize_to_vec(self)))
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+                let bytes = ::hex::decode(&s).map_err(::serde::de::Error::custom)?;
+                let mut reader = &bytes[..];
+                let value = ::beserial::Deserialize::deserialize(&mut reader).map_err(::serde::de::Error::custom)?;
+                if !reader.is_empty() {
+                    return Err(::serde::de::Error::invalid_length(bytes.len(), &"the encoded length"));
+                }
+                Ok(value)
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! upgrade_weak {
     ($weak_ref: expr) => {
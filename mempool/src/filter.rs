@@ -1,5 +1,9 @@
+use std::collections::HashSet;
+
 use collections::LimitHashSet;
+use keys::Address;
 use nimiq_hash::Blake2bHash;
+use primitives::account::AccountType;
 use primitives::coin::Coin;
 use transaction::{Transaction, TransactionFlags};
 
@@ -19,6 +23,17 @@ impl MempoolFilter {
         }
     }
 
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Replaces the filter rules. Since transactions that were filtered under the old rules might
+    /// be accepted by the new ones, this also clears the blacklist.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        self.blacklist.clear();
+    }
+
     pub fn blacklist(&mut self, hash: Blake2bHash) -> &mut Self {
         self.blacklist.insert(hash);
         self
@@ -34,6 +49,19 @@ impl MempoolFilter {
     }
 
     pub fn accepts_transaction(&self, tx: &Transaction) -> bool {
+        // Denied addresses and account types always win, even over the allow list.
+        if self.rules.denied_addresses.contains(&tx.sender) || self.rules.denied_addresses.contains(&tx.recipient) {
+            return false;
+        }
+        if tx.flags.contains(TransactionFlags::CONTRACT_CREATION) && self.rules.denied_creation_types.contains(&tx.recipient_type) {
+            return false;
+        }
+
+        // Allowed senders bypass the fee and value thresholds.
+        if self.rules.allowed_senders.contains(&tx.sender) {
+            return true;
+        }
+
          tx.fee >= self.rules.tx_fee &&
              tx.value >= self.rules.tx_value &&
              // Unchecked addition of coins.
@@ -48,6 +76,10 @@ impl MempoolFilter {
     }
 
     pub fn accepts_recipient_balance(&self, tx: &Transaction, old_balance: Coin, new_balance: Coin) -> bool {
+        if self.rules.allowed_senders.contains(&tx.sender) {
+            return true;
+        }
+
         new_balance >= self.rules.recipient_balance && (
             // XXX This does not precisely capture Account::is_initial() as it will always classify
             // contracts with zero value as non-existent.
@@ -59,7 +91,11 @@ impl MempoolFilter {
         )
     }
 
-    pub fn accepts_sender_balance(&self, _tx: &Transaction, _old_balance: Coin, new_balance: Coin) -> bool {
+    pub fn accepts_sender_balance(&self, tx: &Transaction, _old_balance: Coin, new_balance: Coin) -> bool {
+        if self.rules.allowed_senders.contains(&tx.sender) {
+            return true;
+        }
+
         new_balance >= self.rules.sender_balance ||
             // XXX This does not precisely capture Account::is_initial() || Account.is_to_be_pruned()
             // as it will ignore contracts that will not be pruned with zero value.
@@ -88,6 +124,12 @@ pub struct Rules {
     pub creation_value: Coin,
     pub recipient_balance: Coin,
     pub sender_balance: Coin,
+    /// Transactions from or to any of these addresses are rejected.
+    pub denied_addresses: HashSet<Address>,
+    /// Transactions from these addresses are not subject to the fee, value and balance thresholds.
+    pub allowed_senders: HashSet<Address>,
+    /// Contract creation transactions for these account types are rejected.
+    pub denied_creation_types: HashSet<AccountType>,
 }
 
 impl Default for Rules {
@@ -105,6 +147,9 @@ impl Default for Rules {
             creation_value: Coin::ZERO,
             sender_balance: Coin::ZERO,
             recipient_balance: Coin::ZERO,
+            denied_addresses: HashSet::new(),
+            allowed_senders: HashSet::new(),
+            denied_creation_types: HashSet::new(),
         }
     }
 }
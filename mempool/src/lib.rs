@@ -14,7 +14,7 @@ use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
-use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
+use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard, RwLockWriteGuard};
 
 use account::{Account, AccountTransactionInteraction};
 use beserial::Serialize;
@@ -22,7 +22,7 @@ use block_base::Block;
 use blockchain_base::{AbstractBlockchain, BlockchainEvent};
 use hash::{Blake2bHash, Hash};
 use keys::Address;
-use transaction::{Transaction, TransactionFlags};
+use transaction::{ShortTransactionId, Transaction, TransactionFlags};
 use utils::observer::{Notifier, weak_listener};
 use primitives::networks::NetworkId;
 
@@ -42,9 +42,40 @@ struct MempoolState {
     transactions_by_sender: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_by_recipient: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_sorted_fee: BTreeSet<Arc<Transaction>>, // sorted by fee, ascending
+    /// Index for the short ids of the last compact block, kept up to date as transactions come and go.
+    short_ids: Option<ShortIdIndex>,
     filter: MempoolFilter,
 }
 
+struct ShortIdIndex {
+    block_hash: Blake2bHash,
+    transactions: HashMap<ShortTransactionId, Arc<Transaction>>,
+}
+
+impl ShortIdIndex {
+    fn new(block_hash: Blake2bHash, transactions_by_hash: &HashMap<Blake2bHash, Arc<Transaction>>) -> Self {
+        let transactions = transactions_by_hash.iter()
+            .map(|(hash, tx)| (ShortTransactionId::new(&block_hash, hash), Arc::clone(tx)))
+            .collect();
+        ShortIdIndex {
+            block_hash,
+            transactions,
+        }
+    }
+
+    fn insert(&mut self, hash: &Blake2bHash, tx: Arc<Transaction>) {
+        self.transactions.insert(ShortTransactionId::new(&self.block_hash, hash), tx);
+    }
+
+    fn remove(&mut self, hash: &Blake2bHash, tx: &Transaction) {
+        let short_id = ShortTransactionId::new(&self.block_hash, hash);
+        // Don't remove another transaction with a colliding short id.
+        if self.transactions.get(&short_id).map_or(false, |indexed| indexed.as_ref() == tx) {
+            self.transactions.remove(&short_id);
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub enum MempoolEvent {
     TransactionAdded(Blake2bHash, Arc<Transaction>),
@@ -78,6 +109,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                 transactions_by_sender: HashMap::new(),
                 transactions_by_recipient: HashMap::new(),
                 transactions_sorted_fee: BTreeSet::new(),
+                short_ids: None,
                 filter: MempoolFilter::new(config.filter_rules, config.filter_limit),
             }),
             mut_lock: Mutex::new(()),
@@ -96,6 +128,37 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         self.state.read().filter.blacklisted(hash)
     }
 
+    pub fn filter_rules(&self) -> Rules {
+        self.state.read().filter.rules().clone()
+    }
+
+    /// Replaces the filter rules at runtime and evicts all transactions that are not accepted
+    /// by the new rules anymore.
+    pub fn set_filter_rules(&self, rules: Rules) {
+        // Only one mutating operation at a time.
+        let _lock = self.mut_lock.lock();
+
+        let mut txs_evicted = Vec::new();
+        {
+            let mut state = self.state.write();
+            state.filter.set_rules(rules);
+
+            for tx in state.transactions_sorted_fee.iter() {
+                if !state.filter.accepts_transaction(tx) {
+                    txs_evicted.push(tx.clone());
+                }
+            }
+            for tx in txs_evicted.iter() {
+                Self::remove_transaction(&mut state, tx);
+            }
+        }
+
+        for tx in txs_evicted {
+            trace!("Transaction evicted by filter: {:?}", tx);
+            self.notifier.read().notify(MempoolEvent::TransactionEvicted(tx));
+        }
+    }
+
     pub fn push_transaction(&self, mut transaction: Transaction) -> ReturnCode {
         let hash: Blake2bHash = transaction.hash();
 
@@ -310,6 +373,28 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             .collect()
     }
 
+    /// Looks up the transactions of a compact block by their short ids. Unknown short ids are `None`.
+    ///
+    /// The index is only rebuilt when the block hash changes, i.e. once per compact block, and doesn't
+    /// need to rehash any transactions.
+    pub fn get_transactions_by_short_ids(&self, block_hash: &Blake2bHash, short_ids: &[ShortTransactionId]) -> Vec<Option<Arc<Transaction>>> {
+        let state = self.state.upgradable_read();
+        let is_indexed = state.short_ids.as_ref().map_or(false, |index| &index.block_hash == block_hash);
+        let state = if is_indexed {
+            RwLockUpgradableReadGuard::downgrade(state)
+        } else {
+            let mut state = RwLockUpgradableReadGuard::upgrade(state);
+            let index = ShortIdIndex::new(block_hash.clone(), &state.transactions_by_hash);
+            state.short_ids = Some(index);
+            RwLockWriteGuard::downgrade(state)
+        };
+
+        let index = state.short_ids.as_ref().unwrap();
+        short_ids.iter()
+            .map(|short_id| index.transactions.get(short_id).cloned())
+            .collect()
+    }
+
     pub fn get_transactions_for_block(&self, max_size: usize) -> Vec<Transaction> {
         let mut txs = Vec::new();
         let mut size = 0;
@@ -537,6 +622,9 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
     }
 
     fn add_transaction(state: &mut MempoolState, hash: Blake2bHash, tx: Arc<Transaction>) {
+        if let Some(ref mut index) = state.short_ids {
+            index.insert(&hash, tx.clone());
+        }
         state.transactions_by_hash.insert(hash, tx.clone());
         state.transactions_sorted_fee.insert(tx.clone());
 
@@ -552,7 +640,11 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
     }
 
     fn remove_transaction(state: &mut MempoolState, tx: &Transaction) {
-        state.transactions_by_hash.remove(&tx.hash());
+        let hash: Blake2bHash = tx.hash();
+        if let Some(ref mut index) = state.short_ids {
+            index.remove(&hash, tx);
+        }
+        state.transactions_by_hash.remove(&hash);
         state.transactions_sorted_fee.remove(tx);
 
         let mut remove_key = false;
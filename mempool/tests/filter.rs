@@ -3,6 +3,7 @@ use std::convert::TryFrom;
 use nimiq_hash::{Hash, Blake2bHash};
 use nimiq_keys::Address;
 use nimiq_mempool::filter::{MempoolFilter, Rules};
+use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
 use nimiq_transaction::Transaction;
@@ -47,3 +48,101 @@ fn it_accepts_and_rejects_transactions() {
     tx.fee = Coin::try_from(1).unwrap();
     assert!(f.accepts_transaction(&tx));
 }
+
+#[test]
+fn it_rejects_denied_addresses() {
+    let sender = Address::from([32u8; Address::SIZE]);
+    let recipient = Address::from([213u8; Address::SIZE]);
+
+    let mut s: Rules = Rules::default();
+    s.denied_addresses.insert(recipient.clone());
+
+    let mut f = MempoolFilter::new(s.clone(), MempoolFilter::DEFAULT_BLACKLIST_SIZE);
+
+    let tx = Transaction::new_basic(
+        sender.clone(),
+        recipient.clone(),
+        Coin::try_from(100).unwrap(),
+        Coin::try_from(1).unwrap(),
+        0,
+        NetworkId::Main,
+    );
+    assert!(!f.accepts_transaction(&tx));
+
+    // Denied addresses take precedence over allowed senders.
+    s.denied_addresses.clear();
+    s.denied_addresses.insert(sender.clone());
+    s.allowed_senders.insert(sender);
+    f.set_rules(s);
+    assert!(!f.accepts_transaction(&tx));
+}
+
+#[test]
+fn it_lets_allowed_senders_bypass_thresholds() {
+    let sender = Address::from([32u8; Address::SIZE]);
+
+    let mut s: Rules = Rules::default();
+    s.tx_fee = Coin::try_from(10).unwrap();
+    s.sender_balance = Coin::try_from(1000).unwrap();
+
+    let mut f = MempoolFilter::new(s.clone(), MempoolFilter::DEFAULT_BLACKLIST_SIZE);
+
+    let tx = Transaction::new_basic(
+        sender.clone(),
+        Address::from([213u8; Address::SIZE]),
+        Coin::try_from(100).unwrap(),
+        Coin::try_from(1).unwrap(),
+        0,
+        NetworkId::Main,
+    );
+    assert!(!f.accepts_transaction(&tx));
+    assert!(!f.accepts_sender_balance(&tx, Coin::try_from(200).unwrap(), Coin::try_from(99).unwrap()));
+
+    s.allowed_senders.insert(sender);
+    f.set_rules(s);
+    assert!(f.accepts_transaction(&tx));
+    assert!(f.accepts_sender_balance(&tx, Coin::try_from(200).unwrap(), Coin::try_from(99).unwrap()));
+}
+
+#[test]
+fn it_rejects_denied_contract_creations() {
+    let mut s: Rules = Rules::default();
+    s.denied_creation_types.insert(AccountType::HTLC);
+
+    let f = MempoolFilter::new(s, MempoolFilter::DEFAULT_BLACKLIST_SIZE);
+
+    let htlc = Transaction::new_contract_creation(
+        vec![],
+        Address::from([32u8; Address::SIZE]),
+        AccountType::Basic,
+        AccountType::HTLC,
+        Coin::try_from(100).unwrap(),
+        Coin::try_from(1).unwrap(),
+        0,
+        NetworkId::Main,
+    );
+    assert!(!f.accepts_transaction(&htlc));
+
+    let vesting = Transaction::new_contract_creation(
+        vec![],
+        Address::from([32u8; Address::SIZE]),
+        AccountType::Basic,
+        AccountType::Vesting,
+        Coin::try_from(100).unwrap(),
+        Coin::try_from(1).unwrap(),
+        0,
+        NetworkId::Main,
+    );
+    assert!(f.accepts_transaction(&vesting));
+}
+
+#[test]
+fn it_clears_blacklist_on_rule_change() {
+    let mut f: MempoolFilter = Default::default();
+
+    let hash = Blake2bHash::from([1u8; Blake2bHash::SIZE]);
+    f.blacklist(hash.clone());
+    assert!(f.blacklisted(&hash));
+    f.set_rules(Rules::default());
+    assert!(!f.blacklisted(&hash));
+}
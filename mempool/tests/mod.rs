@@ -9,14 +9,14 @@ use nimiq_block::BlockBody;
 use nimiq_blockchain::Blockchain;
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_database::WriteTransaction;
-use nimiq_hash::Hash;
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{KeyPair, SecureGenerate};
 use nimiq_keys::Address;
 use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
 use nimiq_network_primitives::time::NetworkTime;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
-use nimiq_transaction::{SignatureProof, Transaction};
+use nimiq_transaction::{ShortTransactionId, SignatureProof, Transaction};
 
 const BASIC_TRANSACTION: &str = "000222666efadc937148a6d61589ce6d4aeecca97fda4c32348d294eab582f14a0754d1260f15bea0e8fb07ab18f45301483599e34000000000000c350000000000000008a00019640023fecb82d3aef4be76853d5c5b263754b7d495d9838f6ae5df60cf3addd3512a82988db0056059c7a52ae15285983ef0db8229ae446c004559147686d28f0a30a";
 
@@ -134,6 +134,50 @@ fn push_and_get_two_tx_same_user() {
     assert_eq!(Arc::new(tx2_copy), mempool.get_transaction(&hash2).unwrap());
 }
 
+#[test]
+fn get_transactions_by_short_ids() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+    let address_a = Address::from(&keypair_a.public);
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give address_a balance
+    let body = BlockBody { miner: address_a.clone(), extra_data: Vec::new(), transactions: Vec::new(), receipts: Receipts::default() };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().commit(&mut txn, &body.transactions, &vec![body.get_reward_inherent(1)], 1).unwrap();
+    txn.commit();
+
+    let block_hash = Blake2bHash::from([1u8; Blake2bHash::SIZE]);
+
+    // Push a transaction before the index is built.
+    let mut tx1 = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(10).unwrap(), Coin::try_from(0).unwrap(), 1, NetworkId::Main );
+    let signature_proof1 = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx1.serialize_content()));
+    tx1.proof = signature_proof1.serialize_to_vec();
+    let short_id1 = ShortTransactionId::new(&block_hash, &tx1.hash());
+    assert_eq!(mempool.push_transaction(tx1.clone()), ReturnCode::Accepted);
+
+    let unknown_id = ShortTransactionId::from([0u8; 8]);
+    assert_eq!(mempool.get_transactions_by_short_ids(&block_hash, &[short_id1.clone(), unknown_id.clone()]), vec![Some(Arc::new(tx1.clone())), None]);
+
+    // Push another one after the index was built, it has to be indexed as well.
+    let mut tx2 = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(9).unwrap(), Coin::try_from(0).unwrap(), 1, NetworkId::Main );
+    let signature_proof2 = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx2.serialize_content()));
+    tx2.proof = signature_proof2.serialize_to_vec();
+    let short_id2 = ShortTransactionId::new(&block_hash, &tx2.hash());
+    assert_eq!(mempool.push_transaction(tx2.clone()), ReturnCode::Accepted);
+
+    assert_eq!(mempool.get_transactions_by_short_ids(&block_hash, &[short_id2, short_id1.clone()]), vec![Some(Arc::new(tx2)), Some(Arc::new(tx1.clone()))]);
+
+    // Short ids are salted with the block hash.
+    let other_block_hash = Blake2bHash::from([2u8; Blake2bHash::SIZE]);
+    assert_eq!(mempool.get_transactions_by_short_ids(&other_block_hash, &[short_id1]), vec![None]);
+    let short_id1 = ShortTransactionId::new(&other_block_hash, &tx1.hash());
+    assert_eq!(mempool.get_transactions_by_short_ids(&other_block_hash, &[short_id1]), vec![Some(Arc::new(tx1))]);
+}
+
 #[test]
 fn reject_free_tx_beyond_limit() {
     let env = VolatileEnvironment::new(10).unwrap();
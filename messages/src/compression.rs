@@ -0,0 +1,42 @@
+use std::io;
+use std::io::{Read, Write};
+
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+
+use beserial::{Deserialize, Serialize, SerializingError};
+
+/// Messages smaller than this are never compressed.
+pub const COMPRESSION_THRESHOLD: usize = 1024; // 1 kb
+
+/// The maximum size of a decompressed message content. Anything larger is rejected to protect
+/// against decompression bombs. This matches the maximum message size of the network layer.
+pub const MAX_DECOMPRESSED_SIZE: usize = 1024 * 1024 * 10; // 10 mb
+
+/// Compresses `content`. The result is prefixed with the size of the uncompressed content.
+pub fn compress(content: &[u8]) -> Result<Vec<u8>, SerializingError> {
+    let mut compressed = Vec::with_capacity(content.len() / 2);
+    (content.len() as u32).serialize(&mut compressed)?;
+    let mut encoder = DeflateEncoder::new(compressed, Compression::fast());
+    encoder.write_all(content)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses data produced by `compress`. Fails if the content is larger than
+/// `MAX_DECOMPRESSED_SIZE` or does not match the size it was announced with.
+pub fn decompress(mut compressed: &[u8]) -> Result<Vec<u8>, SerializingError> {
+    let size = u32::deserialize(&mut compressed)? as usize;
+    if size > MAX_DECOMPRESSED_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Decompressed message size exceeded").into());
+    }
+
+    // Never inflate more than announced, no matter what the compressed data says. The buffer
+    // grows while inflating, so announcing a large size doesn't allocate anything by itself.
+    let mut content = Vec::new();
+    DeflateDecoder::new(compressed).take(size as u64 + 1).read_to_end(&mut content)?;
+    if content.len() != size {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Decompressed message size mismatch").into());
+    }
+    Ok(content)
+}
@@ -26,13 +26,13 @@ use rand::Rng;
 use rand::rngs::OsRng;
 
 use account::Account;
-use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, uvar, WriteBytesExt};
+use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, uvar, with_allocation_budget, WriteBytesExt};
 use bitflags::bitflags;
 use block::{Block, BlockHeader};
 use block::proof::ChainProof;
-use block_albatross::{Block as BlockAlbatross, BlockHeader as BlockHeaderAlbatross, ForkProof, PbftCommitMessage, PbftPrepareMessage, SignedPbftProposal, ViewChange, ViewChangeProof};
+use block_albatross::{Block as BlockAlbatross, BlockHeader as BlockHeaderAlbatross, ForkProof, MicroBlock, MicroExtrinsics, MicroHeader, MicroJustification, PbftCommitMessage, PbftPrepareMessage, SignedPbftProposal, ViewChange, ViewChangeProof};
 use handel::update::LevelUpdateMessage;
-use hash::Blake2bHash;
+use hash::{Blake2bHash, Hash};
 use keys::{Address, KeyPair, PublicKey, Signature};
 use macros::create_typed_array;
 use network_primitives::address::{PeerAddress, PeerId};
@@ -41,13 +41,15 @@ use network_primitives::services::ServiceFlags;
 use network_primitives::subscription::Subscription;
 use network_primitives::validator_info::SignedValidatorInfo;
 use network_primitives::version;
-use transaction::{Transaction, TransactionReceipt, TransactionsProof};
+use transaction::{ShortTransactionId, Transaction, TransactionReceipt, TransactionsProof};
 use tree_primitives::accounts_proof::AccountsProof;
 use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
 use utils::crc::Crc32Computer;
 use utils::merkle::partial::Blake2bPartialMerkleProof;
 use utils::observer::{PassThroughListener, PassThroughNotifier};
 
+pub mod compression;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[repr(u64)]
 #[beserial(uvar)]
@@ -93,6 +95,9 @@ pub enum MessageType {
     // Albatross
     BlockAlbatross = 100,
     HeaderAlbatross = 101,
+    CompactBlockAlbatross = 102,
+    GetBlockTransactions = 103,
+    BlockTransactions = 104,
     ViewChange = 105,
     ViewChangeProof = 106,
     ForkProof = 107,
@@ -148,6 +153,9 @@ impl Display for MessageType {
             // Albatross
             Self::BlockAlbatross  => write!(f, "block-albatross"),
             Self::HeaderAlbatross  => write!(f, "header-albatross"),
+            Self::CompactBlockAlbatross  => write!(f, "compact-block-albatross"),
+            Self::GetBlockTransactions  => write!(f, "get-block-transactions"),
+            Self::BlockTransactions  => write!(f, "block-transactions"),
             Self::ViewChange  => write!(f, "view-change"),
             Self::ViewChangeProof  => write!(f, "view-change-proof"),
             Self::ForkProof  => write!(f, "fork-proof"),
@@ -205,6 +213,9 @@ pub enum Message {
     // Albatross
     BlockAlbatross(Box<BlockAlbatross>),
     HeaderAlbatross(Box<BlockHeaderAlbatross>),
+    CompactBlockAlbatross(Box<CompactBlockMessage>),
+    GetBlockTransactions(Box<GetBlockTransactionsMessage>),
+    BlockTransactions(Box<BlockTransactionsMessage>),
     ValidatorInfo(Vec<SignedValidatorInfo>),
     ForkProof(Box<ForkProof>),
     ViewChange(Box<LevelUpdateMessage<ViewChange>>),
@@ -255,6 +266,9 @@ impl Message {
             // Albatross
             Message::BlockAlbatross(_) => MessageType::BlockAlbatross,
             Message::HeaderAlbatross(_) => MessageType::HeaderAlbatross,
+            Message::CompactBlockAlbatross(_) => MessageType::CompactBlockAlbatross,
+            Message::GetBlockTransactions(_) => MessageType::GetBlockTransactions,
+            Message::BlockTransactions(_) => MessageType::BlockTransactions,
             Message::ViewChange(_) => MessageType::ViewChange,
             Message::ViewChangeProof(_) => MessageType::ViewChangeProof,
             Message::ValidatorInfo(_) => MessageType::ValidatorInfo,
@@ -268,6 +282,59 @@ impl Message {
         }
     }
 
+    /// Returns the type of the response if this message is a request.
+    pub fn response_type(&self) -> Option<MessageType> {
+        match self {
+            Message::GetBlocks(_) | Message::GetMacroBlocks(_) => Some(MessageType::Inv),
+            Message::GetAccountsTreeChunk(_) => Some(MessageType::AccountsTreeChunk),
+            Message::GetEpochTransactions(_) => Some(MessageType::EpochTransactions),
+            _ => None,
+        }
+    }
+
+    /// Returns the request identifier of requests and responses that carry one.
+    /// An identifier of `0` means that the sender didn't assign one.
+    pub fn request_identifier(&self) -> Option<u32> {
+        match self {
+            Message::GetAccountsTreeChunk(msg) => Some(msg.request_identifier.0),
+            Message::AccountsTreeChunk(msg) => Some(msg.request_identifier.0),
+            Message::GetEpochTransactions(msg) => Some(msg.request_identifier.0),
+            Message::EpochTransactions(msg) => Some(msg.request_identifier.0),
+            _ => None,
+        }
+    }
+
+    /// Sets the request identifier. Returns `false` if the message doesn't carry one.
+    pub fn set_request_identifier(&mut self, request_identifier: u32) -> bool {
+        let request_identifier = RequestIdentifier(request_identifier);
+        match self {
+            Message::GetAccountsTreeChunk(msg) => msg.request_identifier = request_identifier,
+            Message::AccountsTreeChunk(msg) => msg.request_identifier = request_identifier,
+            Message::GetEpochTransactions(msg) => msg.request_identifier = request_identifier,
+            Message::EpochTransactions(msg) => msg.request_identifier = request_identifier,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether this message is a response that carries the identifier of its request.
+    /// Responses without one, e.g. the `Inv` answering a `GetBlocks`, are matched by order.
+    pub fn is_response(&self) -> bool {
+        match self {
+            Message::AccountsTreeChunk(_) | Message::EpochTransactions(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this is the last response to its request. Responses can be split into several
+    /// messages, e.g. epoch transactions are sent in chunks and the last chunk has an empty proof.
+    pub fn is_final_response(&self) -> bool {
+        match self {
+            Message::EpochTransactions(msg) => msg.tx_proof.is_empty(),
+            _ => true,
+        }
+    }
+
     pub fn peek_length(buffer: &[u8]) -> Result<usize, SerializingError> {
         let mut c = Cursor::new(buffer);
 
@@ -280,9 +347,212 @@ impl Message {
 
         Ok(n as usize)
     }
+
+    /// Returns whether the message in `buffer` has a compressed content, see `serialize_compressed`.
+    pub fn peek_compressed(buffer: &[u8]) -> Result<bool, SerializingError> {
+        let magic = u32::deserialize(&mut &buffer[..])?;
+        Ok(magic == MAGIC_COMPRESSED)
+    }
+
+    /// Deserializes the message content of type `ty`, i.e. the part following the header.
+    fn deserialize_content<R: ReadBytesExt>(ty: MessageType, reader: &mut R) -> Result<Message, SerializingError> {
+        let message = match ty {
+            MessageType::Version => Message::Version(Deserialize::deserialize(reader)?),
+            MessageType::Inv => Message::Inv(DeserializeWithLength::deserialize_with_limit::<u16, R>(reader, Some(InvVector::VECTORS_MAX_COUNT))?),
+            MessageType::GetData => Message::GetData(DeserializeWithLength::deserialize_with_limit::<u16, R>(reader, Some(InvVector::VECTORS_MAX_COUNT))?),
+            MessageType::GetHeader => Message::GetHeader(DeserializeWithLength::deserialize_with_limit::<u16, R>(reader, Some(InvVector::VECTORS_MAX_COUNT))?),
+            MessageType::NotFound => Message::NotFound(DeserializeWithLength::deserialize_with_limit::<u16, R>(reader, Some(InvVector::VECTORS_MAX_COUNT))?),
+            MessageType::Block => Message::Block(Deserialize::deserialize(reader)?),
+            MessageType::Header => Message::Header(Deserialize::deserialize(reader)?),
+            MessageType::Tx => Message::Tx(Deserialize::deserialize(reader)?),
+            MessageType::GetBlocks => Message::GetBlocks(Deserialize::deserialize(reader)?),
+            MessageType::Mempool => Message::Mempool,
+            MessageType::Reject => Message::Reject(Deserialize::deserialize(reader)?),
+            MessageType::Subscribe => Message::Subscribe(Deserialize::deserialize(reader)?),
+            MessageType::Addr => Message::Addr(Deserialize::deserialize(reader)?),
+            MessageType::GetAddr => Message::GetAddr(Deserialize::deserialize(reader)?),
+            MessageType::Ping => Message::Ping(Deserialize::deserialize(reader)?),
+            MessageType::Pong => Message::Pong(Deserialize::deserialize(reader)?),
+            MessageType::Signal => Message::Signal(Deserialize::deserialize(reader)?),
+            MessageType::GetChainProof => Message::GetChainProof,
+            MessageType::ChainProof => Message::ChainProof(Deserialize::deserialize(reader)?),
+            MessageType::GetAccountsProof => Message::GetAccountsProof(Deserialize::deserialize(reader)?),
+            MessageType::AccountsProof => Message::AccountsProof(Deserialize::deserialize(reader)?),
+            MessageType::GetAccountsTreeChunk => Message::GetAccountsTreeChunk(Deserialize::deserialize(reader)?),
+            MessageType::AccountsTreeChunk => Message::AccountsTreeChunk(Deserialize::deserialize(reader)?),
+            MessageType::GetTransactionsProof => Message::GetTransactionsProof(Deserialize::deserialize(reader)?),
+            MessageType::TransactionsProof => Message::TransactionsProof(Deserialize::deserialize(reader)?),
+            MessageType::GetTransactionReceipts => Message::GetTransactionReceipts(Deserialize::deserialize(reader)?),
+            MessageType::TransactionReceipts => Message::TransactionReceipts(Deserialize::deserialize(reader)?),
+            MessageType::GetBlockProof => Message::GetBlockProof(Deserialize::deserialize(reader)?),
+            MessageType::BlockProof => Message::BlockProof(Deserialize::deserialize(reader)?),
+            MessageType::GetHead => Message::GetHead,
+            MessageType::Head => Message::Head(Deserialize::deserialize(reader)?),
+            MessageType::VerAck => Message::VerAck(Deserialize::deserialize(reader)?),
+            // Albatross
+            MessageType::BlockAlbatross => Message::BlockAlbatross(Deserialize::deserialize(reader)?),
+            MessageType::HeaderAlbatross => Message::HeaderAlbatross(Deserialize::deserialize(reader)?),
+            MessageType::CompactBlockAlbatross => Message::CompactBlockAlbatross(Deserialize::deserialize(reader)?),
+            MessageType::GetBlockTransactions => Message::GetBlockTransactions(Deserialize::deserialize(reader)?),
+            MessageType::BlockTransactions => Message::BlockTransactions(Deserialize::deserialize(reader)?),
+            MessageType::ValidatorInfo => Message::ValidatorInfo(DeserializeWithLength::deserialize::<u8, R>(reader)?),
+            MessageType::ForkProof => Message::ForkProof(Deserialize::deserialize(reader)?),
+            MessageType::ViewChange => Message::ViewChange(Deserialize::deserialize(reader)?),
+            MessageType::ViewChangeProof => Message::ViewChangeProof(Deserialize::deserialize(reader)?),
+            MessageType::PbftProposal => Message::PbftProposal(Deserialize::deserialize(reader)?),
+            MessageType::PbftPrepare => Message::PbftPrepare(Deserialize::deserialize(reader)?),
+            MessageType::PbftCommit => Message::PbftCommit(Deserialize::deserialize(reader)?),
+            MessageType::GetMacroBlocks => Message::GetMacroBlocks(Deserialize::deserialize(reader)?),
+            MessageType::GetEpochTransactions => Message::GetEpochTransactions(Deserialize::deserialize(reader)?),
+            MessageType::EpochTransactions => Message::EpochTransactions(Deserialize::deserialize(reader)?),
+        };
+        Ok(message)
+    }
+
+    fn serialize_content<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let size = match self {
+            Message::Version(version_message) => version_message.serialize(writer)?,
+            Message::Inv(inv_vector) => inv_vector.serialize::<u16, W>(writer)?,
+            Message::GetData(inv_vector) => inv_vector.serialize::<u16, W>(writer)?,
+            Message::GetHeader(inv_vector) => inv_vector.serialize::<u16, W>(writer)?,
+            Message::NotFound(inv_vector) => inv_vector.serialize::<u16, W>(writer)?,
+            Message::Block(block) => block.serialize(writer)?,
+            Message::Header(header) => header.serialize(writer)?,
+            Message::Tx(tx) => tx.serialize(writer)?,
+            Message::GetBlocks(get_blocks_message) => get_blocks_message.serialize(writer)?,
+            Message::Mempool => 0,
+            Message::Reject(reject_message) => reject_message.serialize(writer)?,
+            Message::Subscribe(subscribe_message) => subscribe_message.serialize(writer)?,
+            Message::Addr(addr_message) => addr_message.serialize(writer)?,
+            Message::GetAddr(get_addr_message) => get_addr_message.serialize(writer)?,
+            Message::Ping(nonce) => nonce.serialize(writer)?,
+            Message::Pong(nonce) => nonce.serialize(writer)?,
+            Message::Signal(signal_message) => signal_message.serialize(writer)?,
+            Message::GetChainProof => 0,
+            Message::ChainProof(msg) => msg.serialize(writer)?,
+            Message::GetAccountsProof(get_accounts_proof_message) => get_accounts_proof_message.serialize(writer)?,
+            Message::AccountsProof(accounts_proof_message) => accounts_proof_message.serialize(writer)?,
+            Message::GetAccountsTreeChunk(get_accounts_tree_chunk_message) => get_accounts_tree_chunk_message.serialize(writer)?,
+            Message::AccountsTreeChunk(accounts_tree_chunk_message) => accounts_tree_chunk_message.serialize(writer)?,
+            Message::GetTransactionsProof(msg) => msg.serialize(writer)?,
+            Message::TransactionsProof(msg) => msg.serialize(writer)?,
+            Message::GetTransactionReceipts(msg) => msg.serialize(writer)?,
+            Message::TransactionReceipts(msg) => msg.serialize(writer)?,
+            Message::GetBlockProof(msg) => msg.serialize(writer)?,
+            Message::BlockProof(msg) => msg.serialize(writer)?,
+            Message::GetHead => 0,
+            Message::Head(header) => header.serialize(writer)?,
+            Message::VerAck(verack_message) => verack_message.serialize(writer)?,
+            // Albatross
+            Message::BlockAlbatross(block) => block.serialize(writer)?,
+            Message::HeaderAlbatross(header) => header.serialize(writer)?,
+            Message::CompactBlockAlbatross(compact_block) => compact_block.serialize(writer)?,
+            Message::GetBlockTransactions(get_block_transactions) => get_block_transactions.serialize(writer)?,
+            Message::BlockTransactions(block_transactions) => block_transactions.serialize(writer)?,
+            Message::ViewChange(view_change_message) => view_change_message.serialize(writer)?,
+            Message::ViewChangeProof(view_change_proof) => view_change_proof.serialize(writer)?,
+            Message::ValidatorInfo(validator_infos) => validator_infos.serialize::<u8, W>(writer)?,
+            Message::ForkProof(fork_proof) => fork_proof.serialize(writer)?,
+            Message::PbftProposal(pbft_proposal) => pbft_proposal.serialize(writer)?,
+            Message::PbftPrepare(pbft_prepare) => pbft_prepare.serialize(writer)?,
+            Message::PbftCommit(pbft_commit) => pbft_commit.serialize(writer)?,
+            Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialize(writer)?,
+            Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialize(writer)?,
+            Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialize(writer)?,
+        };
+        Ok(size)
+    }
+
+    fn content_size(&self) -> usize {
+        match self {
+            Message::Version(version_message) => version_message.serialized_size(),
+            Message::Inv(inv_vector) => inv_vector.serialized_size::<u16>(),
+            Message::GetData(inv_vector) => inv_vector.serialized_size::<u16>(),
+            Message::GetHeader(inv_vector) => inv_vector.serialized_size::<u16>(),
+            Message::NotFound(inv_vector) => inv_vector.serialized_size::<u16>(),
+            Message::Block(block) => block.serialized_size(),
+            Message::Header(header) => header.serialized_size(),
+            Message::Tx(tx) => tx.serialized_size(),
+            Message::GetBlocks(get_blocks_message) => get_blocks_message.serialized_size(),
+            Message::Mempool => 0,
+            Message::Reject(reject_message) => reject_message.serialized_size(),
+            Message::Subscribe(subscribe_message) => subscribe_message.serialized_size(),
+            Message::Addr(addr_message) => addr_message.serialized_size(),
+            Message::GetAddr(get_addr_message) => get_addr_message.serialized_size(),
+            Message::Ping(nonce) => nonce.serialized_size(),
+            Message::Pong(nonce) => nonce.serialized_size(),
+            Message::Signal(signal_message) => signal_message.serialized_size(),
+            Message::GetChainProof => 0,
+            Message::ChainProof(chain_proof_message) => chain_proof_message.serialized_size(),
+            Message::GetAccountsProof(get_accounts_proof_message) => get_accounts_proof_message.serialized_size(),
+            Message::AccountsProof(accounts_proof_message) => accounts_proof_message.serialized_size(),
+            Message::GetAccountsTreeChunk(get_accounts_tree_chunk_message) => get_accounts_tree_chunk_message.serialized_size(),
+            Message::AccountsTreeChunk(accounts_tree_chunk_message) => accounts_tree_chunk_message.serialized_size(),
+            Message::GetTransactionsProof(msg) => msg.serialized_size(),
+            Message::TransactionsProof(msg) => msg.serialized_size(),
+            Message::GetTransactionReceipts(msg) => msg.serialized_size(),
+            Message::TransactionReceipts(msg) => msg.serialized_size(),
+            Message::GetBlockProof(msg) => msg.serialized_size(),
+            Message::BlockProof(msg) => msg.serialized_size(),
+            Message::GetHead => 0,
+            Message::Head(header) => header.serialized_size(),
+            Message::VerAck(verack_message) => verack_message.serialized_size(),
+            // Albatross
+            Message::BlockAlbatross(block) => block.serialized_size(),
+            Message::HeaderAlbatross(header) => header.serialized_size(),
+            Message::CompactBlockAlbatross(compact_block) => compact_block.serialized_size(),
+            Message::GetBlockTransactions(get_block_transactions) => get_block_transactions.serialized_size(),
+            Message::BlockTransactions(block_transactions) => block_transactions.serialized_size(),
+            Message::ValidatorInfo(validator_info) => validator_info.serialized_size::<u8>(),
+            Message::ForkProof(fork_proof) => fork_proof.serialized_size(),
+            Message::ViewChange(view_change_message) => view_change_message.serialized_size(),
+            Message::ViewChangeProof(view_change_proof) => view_change_proof.serialized_size(),
+            Message::PbftProposal(pbft_proposal) => pbft_proposal.serialized_size(),
+            Message::PbftPrepare(pbft_prepare) => pbft_prepare.serialized_size(),
+            Message::PbftCommit(pbft_commit) => pbft_commit.serialized_size(),
+            Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialized_size(),
+            Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialized_size(),
+            Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialized_size(),
+        }
+    }
+
+    /// Serializes the message with its content compressed, if that makes it smaller.
+    /// Only use this if the receiving peer advertised `Capabilities::COMPRESSION`.
+    pub fn serialize_compressed(&self) -> Vec<u8> {
+        if self.serialized_size() < compression::COMPRESSION_THRESHOLD {
+            return self.serialize_to_vec();
+        }
+
+        let mut content = Vec::with_capacity(self.content_size());
+        self.serialize_content(&mut content).unwrap();
+        let compressed = match compression::compress(&content) {
+            Ok(compressed) if compressed.len() < content.len() => compressed,
+            // Not worth it.
+            _ => return self.serialize_to_vec(),
+        };
+
+        let mut v = Vec::new();
+        MAGIC_COMPRESSED.serialize(&mut v).unwrap();
+        self.ty().serialize(&mut v).unwrap();
+        let length = (v.len() + 4 + 4 + compressed.len()) as u32;
+        length.serialize(&mut v).unwrap();
+        let checksum_start = v.len();
+        0u32.serialize(&mut v).unwrap(); // crc32 placeholder
+        v.extend(compressed);
+
+        let checksum = Crc32Computer::default().update(v.as_slice()).result();
+        v[checksum_start..(4 + checksum_start)].clone_from_slice(&checksum.to_be_bytes());
+        v
+    }
 }
 
 const MAGIC: u32 = 0x4204_2042;
+/// Marks messages whose content is compressed, see `Message::serialize_compressed`.
+const MAGIC_COMPRESSED: u32 = 0x4204_2043;
+/// The maximum number of bytes the collections of a single message may allocate while it is
+/// deserialized. Lengths are read before the elements, so without this a small message could
+/// announce huge nested collections.
+const MAX_ALLOCATION: usize = 4 * compression::MAX_DECOMPRESSED_SIZE;
 
 impl Deserialize for Message {
     fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
@@ -321,7 +591,7 @@ impl Deserialize for Message {
 
         let mut crc32_reader = ReaderComputeCrc32::new(reader);
         let magic: u32 = Deserialize::deserialize(&mut crc32_reader)?;
-        if magic != MAGIC {
+        if magic != MAGIC && magic != MAGIC_COMPRESSED {
             return Err(io::Error::new(io::ErrorKind::InvalidData, "Wrong magic byte").into());
         }
 
@@ -332,53 +602,18 @@ impl Deserialize for Message {
         let checksum: u32 = Deserialize::deserialize(&mut crc32_reader)?;
         crc32_reader.at_checksum = false;
 
-        let message: Message = match ty {
-            MessageType::Version => Message::Version(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Inv => Message::Inv(DeserializeWithLength::deserialize_with_limit::<u16, ReaderComputeCrc32<R>>(&mut crc32_reader, Some(InvVector::VECTORS_MAX_COUNT))?),
-            MessageType::GetData => Message::GetData(DeserializeWithLength::deserialize_with_limit::<u16, ReaderComputeCrc32<R>>(&mut crc32_reader, Some(InvVector::VECTORS_MAX_COUNT))?),
-            MessageType::GetHeader => Message::GetHeader(DeserializeWithLength::deserialize_with_limit::<u16, ReaderComputeCrc32<R>>(&mut crc32_reader, Some(InvVector::VECTORS_MAX_COUNT))?),
-            MessageType::NotFound => Message::NotFound(DeserializeWithLength::deserialize_with_limit::<u16, ReaderComputeCrc32<R>>(&mut crc32_reader, Some(InvVector::VECTORS_MAX_COUNT))?),
-            MessageType::Block => Message::Block(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Header => Message::Header(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Tx => Message::Tx(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetBlocks => Message::GetBlocks(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Mempool => Message::Mempool,
-            MessageType::Reject => Message::Reject(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Subscribe => Message::Subscribe(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Addr => Message::Addr(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetAddr => Message::GetAddr(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Ping => Message::Ping(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Pong => Message::Pong(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::Signal => Message::Signal(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetChainProof => Message::GetChainProof,
-            MessageType::ChainProof => Message::ChainProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetAccountsProof => Message::GetAccountsProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::AccountsProof => Message::AccountsProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetAccountsTreeChunk => Message::GetAccountsTreeChunk(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::AccountsTreeChunk => Message::AccountsTreeChunk(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetTransactionsProof => Message::GetTransactionsProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::TransactionsProof => Message::TransactionsProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetTransactionReceipts => Message::GetTransactionReceipts(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::TransactionReceipts => Message::TransactionReceipts(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetBlockProof => Message::GetBlockProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::BlockProof => Message::BlockProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetHead => Message::GetHead,
-            MessageType::Head => Message::Head(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::VerAck => Message::VerAck(Deserialize::deserialize(&mut crc32_reader)?),
-            // Albatross
-            MessageType::BlockAlbatross => Message::BlockAlbatross(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::HeaderAlbatross => Message::HeaderAlbatross(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::ValidatorInfo => Message::ValidatorInfo(DeserializeWithLength::deserialize::<u8, ReaderComputeCrc32<R>>(&mut crc32_reader)?),
-            MessageType::ForkProof => Message::ForkProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::ViewChange => Message::ViewChange(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::ViewChangeProof => Message::ViewChangeProof(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::PbftProposal => Message::PbftProposal(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::PbftPrepare => Message::PbftPrepare(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::PbftCommit => Message::PbftCommit(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetMacroBlocks => Message::GetMacroBlocks(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::GetEpochTransactions => Message::GetEpochTransactions(Deserialize::deserialize(&mut crc32_reader)?),
-            MessageType::EpochTransactions => Message::EpochTransactions(Deserialize::deserialize(&mut crc32_reader)?),
-        };
+        if magic == MAGIC_COMPRESSED {
+            // The checksum covers the compressed content, verify it before inflating anything.
+            let mut compressed = Vec::new();
+            crc32_reader.read_to_end(&mut compressed)?;
+            if crc32_reader.crc32.result() != checksum {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Message deserialization: Bad checksum").into());
+            }
+            let content = compression::decompress(&compressed)?;
+            return with_allocation_budget(MAX_ALLOCATION, || Message::deserialize_content(ty, &mut &content[..]));
+        }
+
+        let message = with_allocation_budget(MAX_ALLOCATION, || Message::deserialize_content(ty, &mut crc32_reader))?;
 
         // XXX Consume any leftover bytes in the message before computing the checksum.
         // This is consistent with the JS implementation.
@@ -404,53 +639,7 @@ impl Serialize for Message {
         let checksum_start = v.len();
         size += 0u32.serialize(&mut v)?; // crc32 placeholder
 
-        size += match self {
-            Message::Version(version_message) => version_message.serialize(&mut v)?,
-            Message::Inv(inv_vector) => inv_vector.serialize::<u16, Vec<u8>>(&mut v)?,
-            Message::GetData(inv_vector) => inv_vector.serialize::<u16, Vec<u8>>(&mut v)?,
-            Message::GetHeader(inv_vector) => inv_vector.serialize::<u16, Vec<u8>>(&mut v)?,
-            Message::NotFound(inv_vector) => inv_vector.serialize::<u16, Vec<u8>>(&mut v)?,
-            Message::Block(block) => block.serialize(&mut v)?,
-            Message::Header(header) => header.serialize(&mut v)?,
-            Message::Tx(tx) => tx.serialize(&mut v)?,
-            Message::GetBlocks(get_blocks_message) => get_blocks_message.serialize(&mut v)?,
-            Message::Mempool => 0,
-            Message::Reject(reject_message) => reject_message.serialize(&mut v)?,
-            Message::Subscribe(subscribe_message) => subscribe_message.serialize(&mut v)?,
-            Message::Addr(addr_message) => addr_message.serialize(&mut v)?,
-            Message::GetAddr(get_addr_message) => get_addr_message.serialize(&mut v)?,
-            Message::Ping(nonce) => nonce.serialize(&mut v)?,
-            Message::Pong(nonce) => nonce.serialize(&mut v)?,
-            Message::Signal(signal_message) => signal_message.serialize(&mut v)?,
-            Message::GetChainProof => 0,
-            Message::ChainProof(msg) => msg.serialize(&mut v)?,
-            Message::GetAccountsProof(get_accounts_proof_message) => get_accounts_proof_message.serialize(&mut v)?,
-            Message::AccountsProof(accounts_proof_message) => accounts_proof_message.serialize(&mut v)?,
-            Message::GetAccountsTreeChunk(get_accounts_tree_chunk_message) => get_accounts_tree_chunk_message.serialize(&mut v)?,
-            Message::AccountsTreeChunk(accounts_tree_chunk_message) => accounts_tree_chunk_message.serialize(&mut v)?,
-            Message::GetTransactionsProof(msg) => msg.serialize(&mut v)?,
-            Message::TransactionsProof(msg) => msg.serialize(&mut v)?,
-            Message::GetTransactionReceipts(msg) => msg.serialize(&mut v)?,
-            Message::TransactionReceipts(msg) => msg.serialize(&mut v)?,
-            Message::GetBlockProof(msg) => msg.serialize(&mut v)?,
-            Message::BlockProof(msg) => msg.serialize(&mut v)?,
-            Message::GetHead => 0,
-            Message::Head(header) => header.serialize(&mut v)?,
-            Message::VerAck(verack_message) => verack_message.serialize(&mut v)?,
-            // Albatross
-            Message::BlockAlbatross(block) => block.serialize(&mut v)?,
-            Message::HeaderAlbatross(header) => header.serialize(&mut v)?,
-            Message::ViewChange(view_change_message) => view_change_message.serialize(&mut v)?,
-            Message::ViewChangeProof(view_change_proof) => view_change_proof.serialize(&mut v)?,
-            Message::ValidatorInfo(validator_infos) => validator_infos.serialize::<u8, Vec<u8>>(&mut v)?,
-            Message::ForkProof(fork_proof) => fork_proof.serialize(&mut v)?,
-            Message::PbftProposal(pbft_proposal) => pbft_proposal.serialize(&mut v)?,
-            Message::PbftPrepare(pbft_prepare) => pbft_prepare.serialize(&mut v)?,
-            Message::PbftCommit(pbft_commit) => pbft_commit.serialize(&mut v)?,
-            Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialize(&mut v)?,
-            Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialize(&mut v)?,
-            Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialize(&mut v)?,
-        };
+        size += self.serialize_content(&mut v)?;
 
         // write checksum to placeholder
         let mut v_crc = Vec::with_capacity(4);
@@ -465,53 +654,7 @@ impl Serialize for Message {
     fn serialized_size(&self) -> usize {
         let mut size = 4 + 4 + 4; // magic + serialized_size + checksum
         size += self.ty().serialized_size();
-        size += match self {
-            Message::Version(version_message) => version_message.serialized_size(),
-            Message::Inv(inv_vector) => inv_vector.serialized_size::<u16>(),
-            Message::GetData(inv_vector) => inv_vector.serialized_size::<u16>(),
-            Message::GetHeader(inv_vector) => inv_vector.serialized_size::<u16>(),
-            Message::NotFound(inv_vector) => inv_vector.serialized_size::<u16>(),
-            Message::Block(block) => block.serialized_size(),
-            Message::Header(header) => header.serialized_size(),
-            Message::Tx(tx) => tx.serialized_size(),
-            Message::GetBlocks(get_blocks_message) => get_blocks_message.serialized_size(),
-            Message::Mempool => 0,
-            Message::Reject(reject_message) => reject_message.serialized_size(),
-            Message::Subscribe(subscribe_message) => subscribe_message.serialized_size(),
-            Message::Addr(addr_message) => addr_message.serialized_size(),
-            Message::GetAddr(get_addr_message) => get_addr_message.serialized_size(),
-            Message::Ping(nonce) => nonce.serialized_size(),
-            Message::Pong(nonce) => nonce.serialized_size(),
-            Message::Signal(signal_message) => signal_message.serialized_size(),
-            Message::GetChainProof => 0,
-            Message::ChainProof(chain_proof_message) => chain_proof_message.serialized_size(),
-            Message::GetAccountsProof(get_accounts_proof_message) => get_accounts_proof_message.serialized_size(),
-            Message::AccountsProof(accounts_proof_message) => accounts_proof_message.serialized_size(),
-            Message::GetAccountsTreeChunk(get_accounts_tree_chunk_message) => get_accounts_tree_chunk_message.serialized_size(),
-            Message::AccountsTreeChunk(accounts_tree_chunk_message) => accounts_tree_chunk_message.serialized_size(),
-            Message::GetTransactionsProof(msg) => msg.serialized_size(),
-            Message::TransactionsProof(msg) => msg.serialized_size(),
-            Message::GetTransactionReceipts(msg) => msg.serialized_size(),
-            Message::TransactionReceipts(msg) => msg.serialized_size(),
-            Message::GetBlockProof(msg) => msg.serialized_size(),
-            Message::BlockProof(msg) => msg.serialized_size(),
-            Message::GetHead => 0,
-            Message::Head(header) => header.serialized_size(),
-            Message::VerAck(verack_message) => verack_message.serialized_size(),
-            // Albatross
-            Message::BlockAlbatross(block) => block.serialized_size(),
-            Message::HeaderAlbatross(header) => header.serialized_size(),
-            Message::ValidatorInfo(validator_info) => validator_info.serialized_size::<u8>(),
-            Message::ForkProof(fork_proof) => fork_proof.serialized_size(),
-            Message::ViewChange(view_change_message) => view_change_message.serialized_size(),
-            Message::ViewChangeProof(view_change_proof) => view_change_proof.serialized_size(),
-            Message::PbftProposal(pbft_proposal) => pbft_proposal.serialized_size(),
-            Message::PbftPrepare(pbft_prepare) => pbft_prepare.serialized_size(),
-            Message::PbftCommit(pbft_commit) => pbft_commit.serialized_size(),
-            Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialized_size(),
-            Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialized_size(),
-            Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialized_size(),
-        };
+        size += self.content_size();
         size
     }
 }
@@ -553,6 +696,9 @@ pub struct MessageNotifier {
     // Albatross
     pub block_albatross: RwLock<PassThroughNotifier<'static, BlockAlbatross>>,
     pub header_albatross: RwLock<PassThroughNotifier<'static, BlockHeaderAlbatross>>,
+    pub compact_block_albatross: RwLock<PassThroughNotifier<'static, CompactBlockMessage>>,
+    pub get_block_transactions: RwLock<PassThroughNotifier<'static, GetBlockTransactionsMessage>>,
+    pub block_transactions: RwLock<PassThroughNotifier<'static, BlockTransactionsMessage>>,
     pub validator_info: RwLock<PassThroughNotifier<'static, Vec<SignedValidatorInfo>>>,
     pub fork_proof: RwLock<PassThroughNotifier<'static, ForkProof>>,
     pub view_change: RwLock<PassThroughNotifier<'static, LevelUpdateMessage<ViewChange>>>,
@@ -607,6 +753,9 @@ impl MessageNotifier {
             // Albatross
             Message::BlockAlbatross(block) => self.block_albatross.read().notify(*block),
             Message::HeaderAlbatross(header) => self.header_albatross.read().notify(*header),
+            Message::CompactBlockAlbatross(msg) => self.compact_block_albatross.read().notify(*msg),
+            Message::GetBlockTransactions(msg) => self.get_block_transactions.read().notify(*msg),
+            Message::BlockTransactions(msg) => self.block_transactions.read().notify(*msg),
             Message::ValidatorInfo(validator_info) => self.validator_info.read().notify(validator_info),
             Message::ViewChange(view_change) => self.view_change.read().notify(*view_change),
             Message::ViewChangeProof(view_change_proof) => self.view_change_proof.read().notify(*view_change_proof),
@@ -627,6 +776,14 @@ pub trait MessageAdapter<B: block_base::Block> {
     fn register_header_listener<T: PassThroughListener<B::Header> + 'static>(notifier: &MessageNotifier, listener: T);
     fn new_block_message(block: B) -> Message;
     fn new_header_message(header: B::Header) -> Message;
+
+    /// Whether blocks of this chain can be requested as `CompactBlockMessage`s.
+    const COMPACT_BLOCKS: bool;
+    /// Creates the answer to a compact block request. Blocks that can't be sent in compact form are sent in full.
+    fn new_compact_block_message(block: B) -> Message;
+    /// Rebuilds a block from a compact block and its transactions.
+    /// Returns `None` if the transactions don't match the block.
+    fn block_from_compact(compact_block: CompactBlockMessage, transactions: Vec<Transaction>) -> Option<B>;
 }
 
 pub struct NimiqMessageAdapter {}
@@ -646,6 +803,16 @@ impl MessageAdapter<Block> for NimiqMessageAdapter {
     fn new_header_message(header: BlockHeader) -> Message {
         Message::Header(Box::new(header))
     }
+
+    const COMPACT_BLOCKS: bool = false;
+
+    fn new_compact_block_message(block: Block) -> Message {
+        Self::new_block_message(block)
+    }
+
+    fn block_from_compact(_compact_block: CompactBlockMessage, _transactions: Vec<Transaction>) -> Option<Block> {
+        None
+    }
 }
 
 pub struct AlbatrossMessageAdapter {}
@@ -665,6 +832,21 @@ impl MessageAdapter<BlockAlbatross> for AlbatrossMessageAdapter {
     fn new_header_message(header: BlockHeaderAlbatross) -> Message {
         Message::HeaderAlbatross(Box::new(header))
     }
+
+    const COMPACT_BLOCKS: bool = true;
+
+    fn new_compact_block_message(block: BlockAlbatross) -> Message {
+        match block {
+            BlockAlbatross::Micro(ref micro_block) if micro_block.extrinsics.is_some() => {
+                Message::CompactBlockAlbatross(Box::new(CompactBlockMessage::from_block(micro_block)))
+            },
+            block => Self::new_block_message(block),
+        }
+    }
+
+    fn block_from_compact(compact_block: CompactBlockMessage, transactions: Vec<Transaction>) -> Option<BlockAlbatross> {
+        compact_block.into_block(transactions).map(BlockAlbatross::Micro)
+    }
 }
 
 
@@ -686,6 +868,20 @@ pub struct VersionMessage {
     pub head_hash: Blake2bHash,
     pub challenge_nonce: ChallengeNonce,
     pub user_agent: Option<String>,
+    /// Optional protocol features the sender supports. Unknown to older peers, which ignore it.
+    pub capabilities: Capabilities,
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct Capabilities: u32 {
+        /// The peer accepts compressed messages, see `Message::serialize_compressed`.
+        const COMPRESSION = 0b0000_0001;
+        /// The peer understands `InvVectorType::CompactBlock` and the compact block messages.
+        const COMPACT_BLOCKS = 0b0000_0010;
+        /// The peer can parse Noise peer addresses, see `Protocol::Noise`.
+        const NOISE = 0b0000_0100;
+    }
 }
 
 impl Deserialize for VersionMessage {
@@ -697,10 +893,17 @@ impl Deserialize for VersionMessage {
             head_hash: Deserialize::deserialize(reader)?,
             challenge_nonce: Deserialize::deserialize(reader)?,
             user_agent: match DeserializeWithLength::deserialize::<u8, R>(reader) {
+                // An empty user agent is only sent to make room for the capabilities.
+                Ok(ref user_agent) if user_agent.is_empty() => None,
                 Ok(user_agent) => Some(user_agent),
                 Err(SerializingError::IoError(std::io::ErrorKind::UnexpectedEof, _)) => None,
                 Err(e) => return Err(e),
-            }
+            },
+            capabilities: match u32::deserialize(reader) {
+                Ok(bits) => Capabilities::from_bits_truncate(bits),
+                Err(SerializingError::IoError(std::io::ErrorKind::UnexpectedEof, _)) => Capabilities::empty(),
+                Err(e) => return Err(e),
+            },
         })
     }
 }
@@ -715,6 +918,12 @@ impl Serialize for VersionMessage {
         size += Serialize::serialize(&self.challenge_nonce, writer)?;
         if let Some(u) = &self.user_agent {
             size += SerializeWithLength::serialize::<u8, W>(u, writer)?;
+        } else if !self.capabilities.is_empty() {
+            // The capabilities follow the user agent, so send an empty one.
+            size += SerializeWithLength::serialize::<u8, W>(&String::new(), writer)?;
+        }
+        if !self.capabilities.is_empty() {
+            size += self.capabilities.bits().serialize(writer)?;
         }
         Ok(size)
     }
@@ -728,20 +937,26 @@ impl Serialize for VersionMessage {
         size += Serialize::serialized_size(&self.challenge_nonce);
         if let Some(u) = &self.user_agent {
             size += SerializeWithLength::serialized_size::<u8>(u);
+        } else if !self.capabilities.is_empty() {
+            size += SerializeWithLength::serialized_size::<u8>(&String::new());
+        }
+        if !self.capabilities.is_empty() {
+            size += self.capabilities.bits().serialized_size();
         }
         size
     }
 }
 
 impl VersionMessage {
-    pub fn new(peer_address: PeerAddress, head_hash: Blake2bHash, genesis_hash: Blake2bHash, challenge_nonce: ChallengeNonce, user_agent: Option<String>) -> Message {
+    pub fn new(peer_address: PeerAddress, head_hash: Blake2bHash, genesis_hash: Blake2bHash, challenge_nonce: ChallengeNonce, user_agent: Option<String>, capabilities: Capabilities) -> Message {
         Message::Version(Box::new(Self {
             version: version::CODE,
             peer_address,
             genesis_hash,
             head_hash,
             challenge_nonce,
-            user_agent
+            user_agent,
+            capabilities,
         }))
     }
 }
@@ -752,6 +967,9 @@ pub enum InvVectorType {
     Error = 0,
     Transaction = 1,
     Block = 2,
+    /// Requests a block as `CompactBlockMessage` if possible. Only valid in get-data messages
+    /// to peers that advertise `Capabilities::COMPACT_BLOCKS`.
+    CompactBlock = 3,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
@@ -798,7 +1016,7 @@ pub enum GetBlocksDirection {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetBlocksMessage {
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = "Self::LOCATORS_MAX_COUNT")]
     pub locators: Vec<Blake2bHash>,
     pub max_inv_size: u16,
     pub direction: GetBlocksDirection,
@@ -857,7 +1075,7 @@ impl RejectMessage {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AddrMessage {
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = 1000)]
     pub addresses: Vec<PeerAddress>
 }
 
@@ -1000,7 +1218,7 @@ impl Serialize for SignalMessage {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetAccountsProofMessage {
     pub block_hash: Blake2bHash,
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = 256)]
     pub addresses: Vec<Address>
 }
 
@@ -1024,13 +1242,17 @@ pub struct GetAccountsTreeChunkMessage {
     pub block_hash: Blake2bHash,
     #[beserial(len_type(u8))]
     pub start_prefix: String,
+    /// Set by the sender of the request, see `Message::set_request_identifier`.
+    pub request_identifier: RequestIdentifier,
 }
 
 /// We use the following enum to store cached chunks more efficiently.
 /// Upon deserialization, we will always get the structured variant though.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[beserial(untagged)]
 pub enum AccountsTreeChunkData {
-    Serialized(Vec<u8>),
+    Serialized(#[beserial(raw)] Vec<u8>),
+    #[beserial(default)]
     Structured(AccountsTreeChunk<Account>),
 }
 
@@ -1043,47 +1265,18 @@ impl AccountsTreeChunkData {
     }
 }
 
-impl Serialize for AccountsTreeChunkData {
-    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
-        match self {
-            AccountsTreeChunkData::Serialized(ref buf) => {
-                writer.write_all(buf.as_slice())?;
-                Ok(buf.len())
-            },
-            AccountsTreeChunkData::Structured(ref chunk) => {
-                chunk.serialize(writer)
-            },
-        }
-    }
-
-    fn serialized_size(&self) -> usize {
-        match self {
-            AccountsTreeChunkData::Serialized(ref buf) => {
-                buf.len()
-            },
-            AccountsTreeChunkData::Structured(ref chunk) => {
-                chunk.serialized_size()
-            },
-        }
-    }
-}
-
-impl Deserialize for AccountsTreeChunkData {
-    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        Ok(AccountsTreeChunkData::Structured(Deserialize::deserialize(reader)?))
-    }
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AccountsTreeChunkMessage {
     pub block_hash: Blake2bHash,
     pub chunk: Option<AccountsTreeChunkData>,
+    /// The identifier of the `GetAccountsTreeChunk` request this is a response to.
+    pub request_identifier: RequestIdentifier,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetTransactionsProofMessage {
     pub block_hash: Blake2bHash,
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = 256)]
     pub addresses: Vec<Address>
 }
 
@@ -1110,7 +1303,7 @@ pub struct GetTransactionReceiptsMessage {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TransactionReceiptsMessage {
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = "Self::RECEIPTS_MAX_COUNT")]
     pub receipts: Option<Vec<TransactionReceipt>>,
 }
 
@@ -1180,14 +1373,52 @@ pub struct ViewChangeProofMessage {
     pub proof: ViewChangeProof,
 }
 
+/// Identifies a request and the responses to it, see `Message::request_identifier`.
+///
+/// Request identifiers were added to existing messages, so they are always the last field.
+/// Older peers don't send them and ignore them when receiving, in which case the identifier is
+/// `0` and responses are matched to requests by order. An identifier of `0` isn't sent either,
+/// so such messages are unchanged on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RequestIdentifier(pub u32);
+
+impl Deserialize for RequestIdentifier {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        match u32::deserialize(reader) {
+            Ok(request_identifier) => Ok(RequestIdentifier(request_identifier)),
+            Err(SerializingError::IoError(std::io::ErrorKind::UnexpectedEof, _)) => Ok(RequestIdentifier::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Serialize for RequestIdentifier {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        if self.0 == 0 {
+            return Ok(0);
+        }
+        self.0.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        if self.0 == 0 {
+            return 0;
+        }
+        self.0.serialized_size()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetEpochTransactionsMessage {
     pub epoch: u32,
+    /// Set by the sender of the request, see `Message::set_request_identifier`.
+    pub request_identifier: RequestIdentifier,
 }
 impl GetEpochTransactionsMessage {
     pub fn new(epoch: u32) -> Message {
         Message::GetEpochTransactions(Box::new(Self {
             epoch,
+            request_identifier: RequestIdentifier::default(),
         }))
     }
 }
@@ -1195,18 +1426,114 @@ impl GetEpochTransactionsMessage {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EpochTransactionsMessage {
     pub epoch: u32,
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = "Self::MAX_TRANSACTIONS")]
     pub transactions: Vec<Transaction>,
     pub tx_proof: Blake2bPartialMerkleProof,
+    /// The identifier of the `GetEpochTransactions` request this is a response to.
+    pub request_identifier: RequestIdentifier,
 }
 impl EpochTransactionsMessage {
     pub const MAX_TRANSACTIONS: usize = 1000;
 
-    pub fn new(epoch: u32, transactions: Vec<Transaction>, tx_proof: Blake2bPartialMerkleProof) -> Message {
+    pub fn new(epoch: u32, transactions: Vec<Transaction>, tx_proof: Blake2bPartialMerkleProof, request_identifier: RequestIdentifier) -> Message {
         Message::EpochTransactions(Box::new(Self {
             epoch,
             transactions,
             tx_proof,
+            request_identifier,
+        }))
+    }
+}
+
+/// A micro block with its transactions replaced by short ids, salted with the block hash.
+/// The receiver rebuilds the block from its mempool and only requests the transactions
+/// it doesn't know.
+///
+/// Only requested from peers that advertise `Capabilities::COMPACT_BLOCKS`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompactBlockMessage {
+    pub header: MicroHeader,
+    pub justification: MicroJustification,
+    #[beserial(len_type(u8))]
+    pub extra_data: Vec<u8>,
+    #[beserial(len_type(u16), limit = "MicroExtrinsics::MAX_FORK_PROOFS")]
+    pub fork_proofs: Vec<ForkProof>,
+    #[beserial(len_type(u16), limit = "MicroExtrinsics::MAX_TRANSACTIONS")]
+    pub short_ids: Vec<ShortTransactionId>,
+}
+
+impl CompactBlockMessage {
+    /// Panics if the block has no extrinsics.
+    pub fn from_block(block: &MicroBlock) -> Self {
+        let extrinsics = block.extrinsics.as_ref().expect("Compact blocks need the block body");
+        let block_hash = block.hash();
+        CompactBlockMessage {
+            header: block.header.clone(),
+            justification: block.justification.clone(),
+            extra_data: extrinsics.extra_data.clone(),
+            fork_proofs: extrinsics.fork_proofs.clone(),
+            short_ids: extrinsics.transactions.iter()
+                .map(|tx| ShortTransactionId::new(&block_hash, &tx.hash()))
+                .collect(),
+        }
+    }
+
+    pub fn block_hash(&self) -> Blake2bHash {
+        self.header.hash()
+    }
+
+    /// Returns `None` if `transactions` don't hash to the block's extrinsics root,
+    /// e.g. because a short id matched the wrong transaction.
+    pub fn into_block(self, transactions: Vec<Transaction>) -> Option<MicroBlock> {
+        if transactions.len() != self.short_ids.len() {
+            return None;
+        }
+
+        let extrinsics = MicroExtrinsics {
+            extra_data: self.extra_data,
+            fork_proofs: self.fork_proofs,
+            transactions,
+        };
+        if extrinsics.hash::<Blake2bHash>() != self.header.extrinsics_root {
+            return None;
+        }
+
+        Some(MicroBlock {
+            header: self.header,
+            justification: self.justification,
+            extrinsics: Some(extrinsics),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetBlockTransactionsMessage {
+    pub block_hash: Blake2bHash,
+    /// Positions of the requested transactions in the block.
+    #[beserial(len_type(u16), limit = "MicroExtrinsics::MAX_TRANSACTIONS")]
+    pub indexes: Vec<u16>,
+}
+impl GetBlockTransactionsMessage {
+    pub fn new(block_hash: Blake2bHash, indexes: Vec<u16>) -> Message {
+        Message::GetBlockTransactions(Box::new(Self {
+            block_hash,
+            indexes,
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockTransactionsMessage {
+    pub block_hash: Blake2bHash,
+    /// The requested transactions in the order of the request.
+    #[beserial(len_type(u16), limit = "MicroExtrinsics::MAX_TRANSACTIONS")]
+    pub transactions: Vec<Transaction>,
+}
+impl BlockTransactionsMessage {
+    pub fn new(block_hash: Blake2bHash, transactions: Vec<Transaction>) -> Message {
+        Message::BlockTransactions(Box::new(Self {
+            block_hash,
+            transactions,
         }))
     }
 }
@@ -0,0 +1,97 @@
+use beserial::{Deserialize, Serialize};
+use nimiq_block_albatross::{Block, MicroBlock, MicroExtrinsics, MicroHeader, MicroJustification};
+use nimiq_bls::bls12_381::CompressedSignature;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
+use nimiq_messages::*;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::{ShortTransactionId, Transaction};
+use nimiq_vrf::VrfSeed;
+
+fn micro_block(transactions: Vec<Transaction>) -> MicroBlock {
+    let extrinsics = MicroExtrinsics {
+        extra_data: vec![1, 2, 3],
+        fork_proofs: vec![],
+        transactions,
+    };
+    MicroBlock {
+        header: MicroHeader {
+            version: 1,
+            block_number: 42,
+            view_number: 0,
+            parent_hash: Blake2bHash::default(),
+            extrinsics_root: extrinsics.hash(),
+            state_root: Blake2bHash::default(),
+            seed: VrfSeed::default(),
+            timestamp: 0,
+        },
+        justification: MicroJustification {
+            signature: CompressedSignature::default(),
+            view_change_proof: None,
+        },
+        extrinsics: Some(extrinsics),
+    }
+}
+
+fn transaction(value: u64) -> Transaction {
+    Transaction::new_basic(
+        Address::from([1u8; Address::SIZE]),
+        Address::from([2u8; Address::SIZE]),
+        Coin::from_u64_unchecked(value),
+        Coin::ZERO,
+        1,
+        NetworkId::UnitAlbatross,
+    )
+}
+
+#[test]
+fn it_reconstructs_compact_blocks() {
+    let transactions = vec![transaction(1), transaction(2)];
+    let block = micro_block(transactions.clone());
+
+    let compact_block = CompactBlockMessage::from_block(&block);
+    assert_eq!(compact_block.block_hash(), block.hash());
+    assert_eq!(compact_block.short_ids.len(), 2);
+    assert_eq!(compact_block.short_ids[0], ShortTransactionId::new(&block.hash(), &transactions[0].hash()));
+
+    // The compact block survives the wire.
+    let serialized = Message::CompactBlockAlbatross(Box::new(compact_block)).serialize_to_vec();
+    let compact_block = match Message::deserialize_from_vec(&serialized).unwrap() {
+        Message::CompactBlockAlbatross(compact_block) => *compact_block,
+        _ => panic!("Expected a compact block message"),
+    };
+
+    assert_eq!(compact_block.into_block(transactions), Some(block));
+}
+
+#[test]
+fn it_salts_short_ids_with_the_block_hash() {
+    let block1 = micro_block(vec![transaction(1)]);
+    let mut block2 = block1.clone();
+    block2.header.timestamp = 1;
+
+    let compact_block1 = CompactBlockMessage::from_block(&block1);
+    let compact_block2 = CompactBlockMessage::from_block(&block2);
+    assert_ne!(compact_block1.short_ids[0], compact_block2.short_ids[0]);
+}
+
+#[test]
+fn it_rejects_wrong_transactions() {
+    let block = micro_block(vec![transaction(1), transaction(2)]);
+    let compact_block = CompactBlockMessage::from_block(&block);
+
+    assert_eq!(compact_block.clone().into_block(vec![transaction(1), transaction(3)]), None);
+    assert_eq!(compact_block.into_block(vec![transaction(1)]), None);
+}
+
+#[test]
+fn it_falls_back_to_full_blocks_without_a_body() {
+    let mut block = micro_block(vec![transaction(1)]);
+    block.extrinsics = None;
+
+    match AlbatrossMessageAdapter::new_compact_block_message(Block::Micro(block)) {
+        Message::BlockAlbatross(_) => (),
+        _ => panic!("Expected a full block message"),
+    }
+}
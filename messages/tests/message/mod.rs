@@ -1,4 +1,4 @@
-use beserial::{Deserialize, Serialize};
+use beserial::{Deserialize, Serialize, SerializingError};
 use nimiq_messages::*;
 
 const VERSION_MESSAGE: &str = "42042042000000010ee4e19ae300000001040000000400000167aaa7c40d02a84eaf654fe5f3b0bb45d0dd9a70c78fc24d134f5e302aa8270ea107752a6b860053e4c4966637a7de44500e8df82d7b541f578ab25a9e147fed9066361081826337f5511fa27762ecd0e328488e48bcbc4c6e2ded7b552039832768e4f137d809096c6f63616c686f737420fb264aaf8a4f9828a76c550635da078eb466306a189fcc03710bee9f649c869d12c6efcae1d34d135ff562bd75a62ffbcaab81f578ad23da8a02ccf59c7f8b6baa97fabe9dbd9db0acb5e1539bf3155ca1c9565f3363c5c8f1e1cc5b99ba3902c921636f72652d6a732f312e342e3120286e6f64656a733b204c696e75782078363429";
@@ -254,6 +254,28 @@ fn parse_accounts_tree_chunk_message_wochunk() {
     };
 }
 
+#[test]
+fn it_serializes_accounts_tree_chunk_data_as_the_chunk() {
+    // The chunk follows the message header, the block hash and the option tag.
+    let chunk_hex = &ACCOUNTS_TREE_CHUNK_MESSAGE_WCHUNK[92..];
+    let chunk_bytes = ::hex::decode(chunk_hex).unwrap();
+
+    let data: AccountsTreeChunkData = Deserialize::deserialize(&mut &chunk_bytes[..]).unwrap();
+    match data {
+        AccountsTreeChunkData::Structured(ref chunk) => assert_eq!(chunk.nodes.len(), 3),
+        _ => panic!("Expected a structured chunk"),
+    }
+    assert_eq!(::hex::encode(data.serialize_to_vec()), chunk_hex);
+
+    let data = data.into_serialized();
+    match data {
+        AccountsTreeChunkData::Serialized(ref bytes) => assert_eq!(bytes, &chunk_bytes),
+        _ => panic!("Expected a serialized chunk"),
+    }
+    assert_eq!(::hex::encode(data.serialize_to_vec()), chunk_hex);
+    assert_eq!(data.serialized_size(), chunk_bytes.len());
+}
+
 #[test]
 fn parse_get_transactions_proof_message() {
     let vec = ::hex::decode(GET_TRANSACTIONS_PROOF_MESSAGE).unwrap();
@@ -305,3 +327,148 @@ fn reserialize_messages() {
         assert!(message.serialize_to_vec() == vec);
     }
 }
+
+#[test]
+fn it_parses_version_messages_without_capabilities() {
+    let vec = ::hex::decode(VERSION_MESSAGE).unwrap();
+    let message: Message = Deserialize::deserialize(&mut &vec[..]).unwrap();
+    match message {
+        Message::Version(version) => assert!(version.capabilities.is_empty()),
+        _ => assert!(false)
+    };
+}
+
+#[test]
+fn it_serializes_capabilities_without_user_agent() {
+    let vec = ::hex::decode(VERSION_MESSAGE).unwrap();
+    let mut version = match Message::deserialize_from_vec(&vec).unwrap() {
+        Message::Version(version) => version,
+        _ => panic!("Expected a version message"),
+    };
+    version.user_agent = None;
+    version.capabilities = Capabilities::COMPRESSION;
+
+    let message = Message::Version(version);
+    assert_eq!(message.serialized_size(), message.serialize_to_vec().len());
+    match Message::deserialize_from_vec(&message.serialize_to_vec()).unwrap() {
+        Message::Version(version) => {
+            assert_eq!(version.user_agent, None);
+            assert_eq!(version.capabilities, Capabilities::COMPRESSION);
+        },
+        _ => assert!(false)
+    };
+}
+
+#[test]
+fn it_compresses_large_messages() {
+    let vectors: Vec<InvVector> = (0..InvVector::VECTORS_MAX_COUNT)
+        .map(|i| InvVector::from_block_hash(nimiq_hash::Blake2bHash::from([(i % 256) as u8; 32])))
+        .collect();
+    let message = Message::Inv(vectors);
+
+    let compressed = message.serialize_compressed();
+    assert!(compressed.len() < message.serialized_size());
+    assert_eq!(Message::peek_length(&compressed).unwrap(), compressed.len());
+    assert!(Message::peek_compressed(&compressed).unwrap());
+    assert!(!Message::peek_compressed(&message.serialize_to_vec()).unwrap());
+    match Message::deserialize_from_vec(&compressed).unwrap() {
+        Message::Inv(vectors) => assert_eq!(vectors.len(), InvVector::VECTORS_MAX_COUNT),
+        _ => assert!(false)
+    };
+
+    // Small messages are sent as they are.
+    let ping = Message::Ping(2);
+    assert_eq!(ping.serialize_compressed(), ping.serialize_to_vec());
+}
+
+#[test]
+fn it_rejects_decompression_bombs() {
+    let bomb = compression::compress(&vec![0u8; compression::MAX_DECOMPRESSED_SIZE + 1]).unwrap();
+    assert!(bomb.len() < 100 * 1024);
+    assert!(compression::decompress(&bomb).is_err());
+
+    // The announced size must not be exceeded either.
+    let mut lying = compression::compress(&vec![0u8; 4096]).unwrap();
+    lying[..4].copy_from_slice(&1024u32.to_be_bytes());
+    assert!(compression::decompress(&lying).is_err());
+
+    // Announcing a huge size doesn't help either.
+    let mut lying = compression::compress(&vec![0u8; 4096]).unwrap();
+    lying[..4].copy_from_slice(&(compression::MAX_DECOMPRESSED_SIZE as u32).to_be_bytes());
+    assert!(compression::decompress(&lying).is_err());
+}
+
+#[test]
+fn it_matches_epoch_transactions_to_their_request() {
+    let mut request = GetEpochTransactionsMessage::new(3);
+    assert_eq!(request.request_identifier(), Some(0));
+    assert!(request.set_request_identifier(42));
+    assert!(!request.is_response());
+
+    let request = Message::deserialize_from_vec(&request.serialize_to_vec()).unwrap();
+    let request_identifier = match request {
+        Message::GetEpochTransactions(ref msg) => {
+            assert_eq!(msg.epoch, 3);
+            msg.request_identifier
+        },
+        _ => panic!("Expected a GetEpochTransactions message"),
+    };
+    assert_eq!(request_identifier, RequestIdentifier(42));
+    assert_eq!(request.response_type(), Some(MessageType::EpochTransactions));
+
+    // Only the last chunk has an empty proof.
+    let hashes: Vec<nimiq_hash::Blake2bHash> = (0..2u8).map(|i| nimiq_hash::Blake2bHash::from([i; 32])).collect();
+    let mut proofs = nimiq_utils::merkle::partial::PartialMerkleProofBuilder::new(&hashes, 1).unwrap();
+    let last_proof = proofs.pop().unwrap();
+    let first_proof = proofs.pop().unwrap();
+
+    let first = EpochTransactionsMessage::new(3, vec![], first_proof, request_identifier);
+    let last = EpochTransactionsMessage::new(3, vec![], last_proof, request_identifier);
+    for (response, is_final) in vec![(first, false), (last, true)] {
+        let response = Message::deserialize_from_vec(&response.serialize_to_vec()).unwrap();
+        assert!(response.is_response());
+        assert_eq!(response.request_identifier(), Some(42));
+        assert_eq!(response.is_final_response(), is_final);
+    }
+
+    // Messages without request identifiers are left alone.
+    let mut ping = Message::Ping(1);
+    assert_eq!(ping.request_identifier(), None);
+    assert!(!ping.set_request_identifier(1));
+    assert_eq!(ping.response_type(), None);
+
+    // GetBlocks is answered by an inv, which doesn't carry an identifier.
+    let mut get_blocks = GetBlocksMessage::new(vec![], 500, GetBlocksDirection::Forward);
+    assert_eq!(get_blocks.response_type(), Some(MessageType::Inv));
+    assert!(!get_blocks.set_request_identifier(1));
+}
+
+#[test]
+fn it_accepts_requests_without_request_identifier() {
+    // Peers that don't know request identifiers don't send them.
+    let vec = ::hex::decode(GET_ACCOUNTS_TREE_CHUNK_MESSAGE).unwrap();
+    let message: Message = Deserialize::deserialize(&mut &vec[..]).unwrap();
+    assert_eq!(message.request_identifier(), Some(0));
+
+    // Messages without an identifier are unchanged on the wire, the identifier is appended.
+    let mut request = message.clone();
+    assert!(request.set_request_identifier(7));
+    let serialized = request.serialize_to_vec();
+    assert_eq!(serialized.len(), vec.len() + 4);
+    // Skip magic, type, length and checksum.
+    assert_eq!(&serialized[13..serialized.len() - 4], &vec[13..]);
+
+    let request = Message::deserialize_from_vec(&serialized).unwrap();
+    assert_eq!(request.request_identifier(), Some(7));
+}
+
+#[test]
+fn it_limits_collection_lengths() {
+    let locators = |n: usize| (0..n).map(|i| nimiq_hash::Blake2bHash::from([i as u8; 32])).collect::<Vec<_>>();
+
+    let message = GetBlocksMessage::new(locators(GetBlocksMessage::LOCATORS_MAX_COUNT), 500, GetBlocksDirection::Forward);
+    assert!(Message::deserialize_from_vec(&message.serialize_to_vec()).is_ok());
+
+    let message = GetBlocksMessage::new(locators(GetBlocksMessage::LOCATORS_MAX_COUNT + 1), 500, GetBlocksDirection::Forward);
+    assert_eq!(Message::deserialize_from_vec(&message.serialize_to_vec()).err(), Some(SerializingError::LimitExceeded));
+}
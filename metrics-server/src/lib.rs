@@ -8,6 +8,8 @@ extern crate nimiq_mempool as mempool;
 extern crate nimiq_network as network;
 extern crate nimiq_block as block;
 extern crate nimiq_block_albatross as block_albatross;
+#[cfg(feature = "validator")]
+extern crate nimiq_validator as validator;
 
 use std::io;
 use std::io::Read;
@@ -19,6 +21,7 @@ use futures::{future::Future, IntoFuture};
 use futures::stream::Stream;
 use hyper::server::conn::Http;
 use native_tls::{Identity, TlsAcceptor as NativeTlsAcceptor};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
 use tokio_tls::TlsAcceptor as TokioTlsAcceptor;
 
@@ -28,6 +31,8 @@ use crate::error::Error;
 use crate::metrics::mempool::MempoolMetrics;
 use crate::metrics::network::NetworkMetrics;
 pub use crate::metrics::chain::{AbstractChainMetrics, NimiqChainMetrics, AlbatrossChainMetrics};
+#[cfg(feature = "validator")]
+pub use crate::metrics::validator::ValidatorMetrics;
 
 macro_rules! attributes {
     // Empty attributes.
@@ -73,40 +78,68 @@ pub struct MetricsServer {
     future: MetricsServerFuture
 }
 
+/// The PKCS#12 identity used to serve metrics over HTTPS.
+pub struct TlsIdentity<'a> {
+    pub pkcs12_key_file: &'a str,
+    pub pkcs12_passphrase: &'a str,
+}
+
 impl MetricsServer {
-    pub fn new<P, CM>(ip: IpAddr, port: u16, username: Option<String>, password: Option<String>, pkcs12_key_file: &str, pkcs12_passphrase: &str, consensus: Arc<Consensus<P>>) -> Result<MetricsServer, Error>
+    /// Creates a metrics server. If `tls` is `None`, metrics are served over plain HTTP.
+    pub fn new<P, CM>(ip: IpAddr, port: u16, username: Option<String>, password: Option<String>, tls: Option<TlsIdentity>, consensus: Arc<Consensus<P>>, additional_metrics: Vec<Arc<dyn server::Metrics>>) -> Result<MetricsServer, Error>
         where P: ConsensusProtocol + 'static,
               CM: AbstractChainMetrics<P> + server::Metrics + 'static
     {
-        let mut file = File::open(pkcs12_key_file)?;
-        let mut pkcs12 = vec![];
-        file.read_to_end(&mut pkcs12)?;
-        let pkcs12 = Identity::from_pkcs12(&pkcs12, pkcs12_passphrase)?;
-
-        let tls_cx = NativeTlsAcceptor::builder(pkcs12).build()?;
-        let tls_cx = TokioTlsAcceptor::from(tls_cx);
+        let new_service = move || {
+            let mut metrics: Vec<Arc<dyn server::Metrics>> = vec![
+                Arc::new(CM::new(consensus.blockchain.clone())),
+                Arc::new(MempoolMetrics::new(consensus.mempool.clone())),
+                Arc::new(NetworkMetrics::new(consensus.network.clone()))
+            ];
+            metrics.extend(additional_metrics.iter().cloned());
+            server::MetricsServer::new(
+                metrics,
+                attributes! { "peer" => consensus.network.network_config.peer_address() },
+                username.clone(),
+                password.clone())
+        };
 
         let srv = TcpListener::bind(&SocketAddr::new(ip, port))?;
 
-        let future = Box::new(Http::new()
-            .serve_incoming(
-                srv.incoming().and_then(move |socket| {
-                    tls_cx
-                        .accept(socket)
-                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
-                }),
-                move || {
-                    server::MetricsServer::new(
-                        vec![
-                            Arc::new(CM::new(consensus.blockchain.clone())),
-                            Arc::new(MempoolMetrics::new(consensus.mempool.clone())),
-                            Arc::new(NetworkMetrics::new(consensus.network.clone()))
-                        ],
-                        attributes! { "peer" => consensus.network.network_config.peer_address() },
-                        username.clone(),
-                        password.clone())
-                }
-            )
+        let future = match tls {
+            Some(tls) => {
+                let mut file = File::open(tls.pkcs12_key_file)?;
+                let mut pkcs12 = vec![];
+                file.read_to_end(&mut pkcs12)?;
+                let pkcs12 = Identity::from_pkcs12(&pkcs12, tls.pkcs12_passphrase)?;
+
+                let tls_cx = NativeTlsAcceptor::builder(pkcs12).build()?;
+                let tls_cx = TokioTlsAcceptor::from(tls_cx);
+
+                Self::serve(
+                    srv.incoming().and_then(move |socket| {
+                        tls_cx
+                            .accept(socket)
+                            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+                    }),
+                    new_service
+                )
+            },
+            None => Self::serve(srv.incoming(), new_service),
+        };
+
+        Ok(MetricsServer {
+            future,
+        })
+    }
+
+    fn serve<I, F>(incoming: I, new_service: F) -> MetricsServerFuture
+        where I: Stream<Error=io::Error> + Send + Sync + 'static,
+              I::Item: AsyncRead + AsyncWrite + Send + 'static,
+              F: Fn() -> server::MetricsServer + Send + Sync + 'static
+    {
+        Box::new(Http::new()
+            .serve_incoming(incoming, new_service)
             .then(|res| {
                 match res {
                     Ok(conn) => Ok(Some(conn)),
@@ -126,11 +159,7 @@ impl MetricsServer {
 
                 Ok(())
             })
-        );
-
-        Ok(MetricsServer {
-            future,
-        })
+        )
     }
 }
 
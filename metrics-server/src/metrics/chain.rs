@@ -5,10 +5,11 @@ use blockchain::Blockchain;
 use blockchain_base::AbstractBlockchain;
 use blockchain_albatross::Blockchain as AlbatrossBlockchain;
 use block::Difficulty;
+use block_albatross::Block as AlbatrossBlock;
 use consensus::{ConsensusProtocol, AlbatrossConsensusProtocol, NimiqConsensusProtocol};
 
 use crate::server;
-use crate::server::{SerializationType, Metrics};
+use crate::server::{SerializationType, Metrics, MetricType};
 
 
 pub trait AbstractChainMetrics<P: ConsensusProtocol + 'static> {
@@ -18,6 +19,7 @@ pub trait AbstractChainMetrics<P: ConsensusProtocol + 'static> {
 
     fn serialize_blockchain_metrics(&self, blockchain: Arc<P::Blockchain>, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
         let metrics = blockchain.metrics();
+        serializer.describe("chain_block", MetricType::Counter, "Number of blocks pushed to the blockchain, by outcome")?;
         serializer.metric_with_attributes("chain_block", metrics.block_forked_count(), attributes!{"action" => "forked"})?;
         serializer.metric_with_attributes("chain_block", metrics.block_rebranched_count(), attributes!{"action" => "rebranched"})?;
         serializer.metric_with_attributes("chain_block", metrics.block_extended_count(), attributes!{"action" => "extended"})?;
@@ -46,10 +48,14 @@ impl Metrics for NimiqChainMetrics {
         {
             let head = self.blockchain.head();
 
+            serializer.describe("chain_head_height", MetricType::Gauge, "Height of the head block")?;
             serializer.metric("chain_head_height", head.header.height)?;
+            serializer.describe("chain_head_difficulty", MetricType::Gauge, "Difficulty of the head block")?;
             serializer.metric("chain_head_difficulty", Difficulty::from(head.header.n_bits))?;
+            serializer.describe("chain_head_transactions", MetricType::Gauge, "Number of transactions in the head block")?;
             serializer.metric("chain_head_transactions", head.body.as_ref().map(|body| body.transactions.len()).unwrap_or(0))?;
         }
+        serializer.describe("chain_total_work", MetricType::Gauge, "Total work of the main chain")?;
         serializer.metric("chain_total_work", self.blockchain.total_work())?;
 
         self.serialize_blockchain_metrics(Arc::clone(&self.blockchain), serializer)?;
@@ -76,19 +82,28 @@ impl Metrics for AlbatrossChainMetrics {
         {
             let head = self.blockchain.head();
 
+            serializer.describe("chain_head_height", MetricType::Gauge, "Height of the head block")?;
             serializer.metric("chain_head_height", head.block_number())?;
+            serializer.describe("chain_head_view_number", MetricType::Gauge, "View number of the head block")?;
             serializer.metric("chain_head_view_number", head.view_number())?;
 
-            /*match head {
-                AlbatrossBlock::Macro(ref macro_block) => {
-                    // TODO metrics about macro block?
-                    // e.g.: number of signatures in prepare & commit
-                }
-                AlbatrossBlock::Micro(ref micro_block) => {
-                    // TODO: number of transactions in micro block
-                    // other metrics?
-                }
-            }*/
+            let num_transactions = match *head {
+                AlbatrossBlock::Macro(_) => 0,
+                AlbatrossBlock::Micro(ref micro_block) => micro_block.extrinsics.as_ref().map(|extrinsics| extrinsics.transactions.len()).unwrap_or(0),
+            };
+            serializer.describe("chain_head_transactions", MetricType::Gauge, "Number of transactions in the head block")?;
+            serializer.metric("chain_head_transactions", num_transactions)?;
+        }
+
+        // Signers of the last macro block
+        {
+            let macro_head = self.blockchain.macro_head();
+
+            if let Some(ref justification) = macro_head.justification {
+                serializer.describe("chain_macro_head_signers", MetricType::Gauge, "Number of validators that signed the last macro block, by pBFT phase")?;
+                serializer.metric_with_attributes("chain_macro_head_signers", justification.prepare.signers.len(), attributes!{"phase" => "prepare"})?;
+                serializer.metric_with_attributes("chain_macro_head_signers", justification.commit.signers.len(), attributes!{"phase" => "commit"})?;
+            }
         }
 
         self.serialize_blockchain_metrics(Arc::clone(&self.blockchain), serializer)?;
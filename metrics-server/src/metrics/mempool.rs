@@ -6,7 +6,8 @@ use blockchain_base::AbstractBlockchain;
 use mempool::{Mempool, SIZE_MAX};
 
 use crate::server;
-use crate::server::SerializationType;
+use crate::server::{SerializationType, MetricType};
+use crate::server::histogram::Histogram;
 
 pub struct MempoolMetrics<B: AbstractBlockchain + 'static> {
     mempool: Arc<Mempool<B>>,
@@ -24,6 +25,7 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for MempoolMetrics<B> {
     fn metrics(&self, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
         let txs = self.mempool.get_transactions(SIZE_MAX, 0f64);
         let group = [0usize, 1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+        serializer.describe("mempool_transactions", MetricType::Gauge, "Number of transactions in the mempool, by fee per byte")?;
         for i in 1..group.len() {
             let lower_bound = group[i - 1];
             let upper_bound = group[i];
@@ -39,6 +41,15 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for MempoolMetrics<B> {
             txs.iter().filter(|tx| (tx.fee_per_byte() as usize) >= lower_bound).count(),
             attributes!{"fee_per_byte" => format!(">={}", lower_bound)}
         )?;
+
+        let mut fee_histogram = Histogram::new(group.iter().map(|&bound| bound as f64).collect());
+        for tx in txs.iter() {
+            fee_histogram.observe(tx.fee_per_byte());
+        }
+        serializer.describe("mempool_fee_per_byte", MetricType::Histogram, "Fee per byte of the transactions in the mempool")?;
+        serializer.histogram("mempool_fee_per_byte", &fee_histogram, attributes!{})?;
+
+        serializer.describe("mempool_size", MetricType::Gauge, "Total size of the transactions in the mempool in bytes")?;
         serializer.metric(
             "mempool_size",
             txs.iter().map(|tx| tx.serialized_size()).sum::<usize>(),
@@ -1,3 +1,5 @@
 pub(crate) mod chain;
 pub(crate) mod mempool;
 pub(crate) mod network;
+#[cfg(feature = "validator")]
+pub(crate) mod validator;
@@ -6,7 +6,7 @@ use network::network::Network;
 use network::connection::connection_info::ConnectionState;
 
 use crate::server;
-use crate::server::SerializationType;
+use crate::server::{SerializationType, MetricType};
 
 pub struct NetworkMetrics<B: AbstractBlockchain + 'static> {
     network: Arc<Network<B>>,
@@ -24,6 +24,7 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for NetworkMetrics<B> {
     fn metrics(&self, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
         let (message_metrics, network_metrics, peer_metrics) = self.network.connections.metrics();
 
+        serializer.describe("network_peers", MetricType::Gauge, "Number of peer connections, by protocol and state")?;
         for ((protocol, state), count) in peer_metrics.peer_metrics() {
             let str_state = match state {
                 ConnectionState::Established => "established",
@@ -45,6 +46,7 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for NetworkMetrics<B> {
         let num_wss_addresses = self.network.addresses.known_wss_addresses_count();
         let num_rtc_addresses = self.network.addresses.known_rtc_addresses_count();
         let num_dumb_addresses = num_addresses - num_wss_addresses - num_ws_addresses - num_rtc_addresses;
+        serializer.describe("network_known_addresses", MetricType::Gauge, "Number of known peer addresses, by protocol")?;
         serializer.metric_with_attributes(
             "network_known_addresses",
             num_dumb_addresses,
@@ -66,7 +68,9 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for NetworkMetrics<B> {
             attributes!{"type" => "webrtc"}
         )?;
 
+        serializer.describe("network_time_now", MetricType::Gauge, "Current network time in milliseconds")?;
         serializer.metric("network_time_now", self.network.network_time.now())?;
+        serializer.describe("network_bytes", MetricType::Counter, "Number of bytes sent and received")?;
         serializer.metric_with_attributes(
             "network_bytes",
             network_metrics.bytes_sent(),
@@ -78,12 +82,17 @@ impl<B: AbstractBlockchain + 'static> server::Metrics for NetworkMetrics<B> {
             attributes!{"direction" => "received"}
         )?;
 
+        // Samples of a metric need to be grouped together, so we iterate over the message types twice.
+        serializer.describe("message_rx_count", MetricType::Counter, "Number of messages received, by type")?;
         for &ty in message_metrics.message_types() {
             serializer.metric_with_attributes(
                 "message_rx_count",
                 message_metrics.message_occurrences(ty).unwrap_or(0),
                 attributes!{"type" => format!("{}", ty)}
             )?;
+        }
+        serializer.describe("message_rx_processing_time", MetricType::Counter, "Time spent processing received messages in milliseconds, by type")?;
+        for &ty in message_metrics.message_types() {
             serializer.metric_with_attributes(
                 "message_rx_processing_time",
                 message_metrics.message_processing_time(ty).unwrap_or(0) / 1000, // Processing time is recorded in microseconds but should be reported in milliseconds
@@ -0,0 +1,70 @@
+use std::io;
+use std::sync::Arc;
+
+use validator::validator::{Validator, ValidatorStatus};
+
+use crate::server;
+use crate::server::{SerializationType, MetricType};
+
+pub struct ValidatorMetrics {
+    validator: Arc<Validator>,
+}
+
+impl ValidatorMetrics {
+    pub fn new(validator: Arc<Validator>) -> Self {
+        ValidatorMetrics {
+            validator,
+        }
+    }
+}
+
+impl server::Metrics for ValidatorMetrics {
+    fn metrics(&self, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
+        let str_status = match self.validator.status() {
+            ValidatorStatus::None => "none",
+            ValidatorStatus::Synced => "synced",
+            ValidatorStatus::Potential => "potential",
+            ValidatorStatus::Active => "active",
+        };
+        serializer.describe("validator_status", MetricType::Gauge, "Status of the validator")?;
+        serializer.metric_with_attributes("validator_status", 1, attributes!{"status" => str_status})?;
+        serializer.describe("validator_slots", MetricType::Gauge, "Number of slots of the validator in the current epoch")?;
+        serializer.metric("validator_slots", self.validator.slots().unwrap_or(0))?;
+
+        let slashed_slots = self.validator.num_slashed_slots();
+        serializer.describe("validator_slashed_slots", MetricType::Gauge, "Number of slots of the validator that are slashed in the current epoch")?;
+        serializer.metric("validator_slashed_slots", slashed_slots)?;
+        serializer.describe("validator_slashed", MetricType::Gauge, "Whether the validator is slashed in the current epoch")?;
+        serializer.metric("validator_slashed", if slashed_slots > 0 { 1 } else { 0 })?;
+
+        let metrics = self.validator.metrics();
+        serializer.describe("validator_block", MetricType::Counter, "Number of blocks produced, proposed or missed by the validator")?;
+        serializer.metric_with_attributes("validator_block", metrics.micro_block_produced_count(), attributes!{"action" => "produced", "type" => "micro"})?;
+        serializer.metric_with_attributes("validator_block", metrics.macro_block_proposed_count(), attributes!{"action" => "proposed", "type" => "macro"})?;
+        serializer.metric_with_attributes("validator_block", metrics.block_missed_count(), attributes!{"action" => "missed"})?;
+
+        serializer.describe("validator_view_change", MetricType::Counter, "Number of view changes started and completed")?;
+        serializer.metric_with_attributes("validator_view_change", metrics.view_change_started_count(), attributes!{"action" => "started"})?;
+        serializer.metric_with_attributes("validator_view_change", metrics.view_change_completed_count(), attributes!{"action" => "completed"})?;
+
+        serializer.describe("validator_pbft_completed", MetricType::Counter, "Number of completed pBFT phases")?;
+        serializer.metric_with_attributes("validator_pbft_completed", metrics.pbft_prepare_completed_count(), attributes!{"phase" => "prepare"})?;
+        serializer.metric_with_attributes("validator_pbft_completed", metrics.pbft_commit_completed_count(), attributes!{"phase" => "commit"})?;
+        serializer.describe("validator_pbft_latency_ms", MetricType::Gauge, "Time from the proposal until the last pBFT phase completed in milliseconds")?;
+        serializer.metric_with_attributes("validator_pbft_latency_ms", metrics.pbft_prepare_latency(), attributes!{"phase" => "prepare"})?;
+        serializer.metric_with_attributes("validator_pbft_latency_ms", metrics.pbft_commit_latency(), attributes!{"phase" => "commit"})?;
+        serializer.describe("validator_pbft_votes", MetricType::Gauge, "Votes collected in the last completed pBFT phase")?;
+        serializer.metric_with_attributes("validator_pbft_votes", metrics.pbft_prepare_votes(), attributes!{"phase" => "prepare"})?;
+        serializer.metric_with_attributes("validator_pbft_votes", metrics.pbft_commit_votes(), attributes!{"phase" => "commit"})?;
+
+        serializer.describe("validator_handel_level_ms", MetricType::Gauge, "Time until a Handel level completed in the last aggregation in milliseconds")?;
+        for (aggregation, level, time) in metrics.handel_level_completion_times() {
+            serializer.metric_with_attributes("validator_handel_level_ms", time, attributes!{"aggregation" => aggregation.as_str(), "level" => level})?;
+        }
+
+        serializer.describe("validator_fork_proofs", MetricType::Counter, "Number of fork proofs received")?;
+        serializer.metric("validator_fork_proofs", metrics.fork_proof_count())?;
+
+        Ok(())
+    }
+}
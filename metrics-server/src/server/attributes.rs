@@ -49,7 +49,7 @@ impl<'a> From<&'a CachedAttributes> for CombinedAttributes<'a> {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct VecAttributes {
     attributes: Vec<(String, String)>,
 }
@@ -77,7 +77,7 @@ impl VecAttributes {
     fn build_str(&self) -> String {
         self.attributes
             .iter()
-            .map(|(k, v)| format!("{}=\"{}\"", k, v))
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_value(v)))
             .collect::<Vec<String>>().join(",")
     }
 }
@@ -161,3 +161,17 @@ impl<'a> Add<VecAttributes> for &'a CachedAttributes {
         CombinedAttributes::with_attributes(other, self)
     }
 }
+
+/// Escapes a label value for the Prometheus text format.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,53 @@
+/// A histogram with fixed bucket boundaries, as exposed to Prometheus.
+///
+/// Each bucket counts the observations that are less than or equal to its upper bound. The
+/// implicit `+Inf` bucket is the total number of observations.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// Upper bounds of the buckets in increasing order
+    bounds: Vec<f64>,
+    /// Number of observations that fell into each bucket (not cumulative)
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(mut bounds: Vec<f64>) -> Self {
+        bounds.sort_by(|a, b| a.partial_cmp(b).expect("Histogram bounds must not be NaN"));
+        bounds.dedup();
+        let counts = vec![0; bounds.len()];
+        Histogram {
+            bounds,
+            counts,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        if let Some(i) = self.bounds.iter().position(|&bound| value <= bound) {
+            self.counts[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Iterates over `(upper_bound, cumulative_count)` for each bucket, excluding `+Inf`.
+    pub fn buckets<'a>(&'a self) -> impl Iterator<Item=(f64, u64)> + 'a {
+        self.bounds.iter()
+            .zip(self.counts.iter())
+            .scan(0, |total, (&bound, &count)| {
+                *total += count;
+                Some((bound, *total))
+            })
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+use std::fmt;
 use std::fmt::Display;
 use std::io;
 use std::sync::Arc;
@@ -5,19 +7,47 @@ use std::sync::Arc;
 use futures::{future, Future, stream, stream::Stream};
 use hyper::{Body, Request, Response, StatusCode};
 use hyper::Chunk;
-use hyper::header::{AUTHORIZATION, WWW_AUTHENTICATE, LOCATION};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE, LOCATION};
 use base64::encode;
 
-use crate::server::attributes::{CachedAttributes, VecAttributes};
+use crate::server::attributes::{Attributes, CachedAttributes, VecAttributes};
+use crate::server::histogram::Histogram;
 use futures::IntoFuture;
 
 pub mod attributes;
+pub mod histogram;
 
 pub type SerializationType = Vec<u8>;
 
+/// Content type of the Prometheus text exposition format
+pub const CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl Display for MetricType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.write_str(match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+            MetricType::Untyped => "untyped",
+        })
+    }
+}
+
 pub struct MetricsSerializer<W: io::Write + Into<Chunk>> {
     common_attributes: CachedAttributes,
     writer: W,
+    /// Metrics for which `# HELP` and `# TYPE` have already been written
+    described: HashSet<String>,
 }
 
 impl<W: io::Write + Into<Chunk>> MetricsSerializer<W> {
@@ -26,17 +56,54 @@ impl<W: io::Write + Into<Chunk>> MetricsSerializer<W> {
         MetricsSerializer {
             common_attributes: common_attributes.into(),
             writer,
+            described: HashSet::new(),
         }
     }
 
+    /// Writes the `# HELP` and `# TYPE` lines for a metric. This must be called before the first
+    /// sample of the metric is written and is ignored if the metric was already described.
+    pub fn describe<K: Display>(&mut self, key: K, ty: MetricType, help: &str) -> Result<(), io::Error> {
+        let key = key.to_string();
+        if self.described.contains(&key) {
+            return Ok(());
+        }
+        writeln!(self.writer, "# HELP {} {}", key, help.replace('\\', "\\\\").replace('\n', "\\n"))?;
+        writeln!(self.writer, "# TYPE {} {}", key, ty)?;
+        self.described.insert(key);
+        Ok(())
+    }
+
     #[inline]
     pub fn metric<K: Display, V: Display>(&mut self, key: K, value: V) -> Result<(), io::Error> {
-        writeln!(self.writer, "{}{{{}}} {}", key, self.common_attributes, value)
+        write_sample(&mut self.writer, key, &self.common_attributes, value)
     }
 
     #[inline]
     pub fn metric_with_attributes<K: Display, V: Display, A: Into<VecAttributes>>(&mut self, key: K, value: V, attributes: A) -> Result<(), io::Error> {
-        writeln!(self.writer, "{}{{{}}} {}", key, &self.common_attributes + attributes.into(), value)
+        write_sample(&mut self.writer, key, &(&self.common_attributes + attributes.into()), value)
+    }
+
+    /// Writes the `_bucket`, `_sum` and `_count` samples of a histogram.
+    pub fn histogram<K: Display, A: Into<VecAttributes>>(&mut self, key: K, histogram: &Histogram, attributes: A) -> Result<(), io::Error> {
+        let attributes = attributes.into();
+        for (bound, count) in histogram.buckets() {
+            let mut bucket_attributes = attributes.clone();
+            bucket_attributes.add("le", bound);
+            write_sample(&mut self.writer, format_args!("{}_bucket", key), &(&self.common_attributes + bucket_attributes), count)?;
+        }
+        let mut bucket_attributes = attributes.clone();
+        bucket_attributes.add("le", "+Inf");
+        write_sample(&mut self.writer, format_args!("{}_bucket", key), &(&self.common_attributes + bucket_attributes), histogram.count())?;
+        write_sample(&mut self.writer, format_args!("{}_sum", key), &(&self.common_attributes + attributes.clone()), histogram.sum())?;
+        write_sample(&mut self.writer, format_args!("{}_count", key), &(&self.common_attributes + attributes), histogram.count())
+    }
+}
+
+fn write_sample<W: io::Write, K: Display, A: Attributes, V: Display>(writer: &mut W, key: K, attributes: &A, value: V) -> Result<(), io::Error> {
+    if attributes.is_empty() {
+        writeln!(writer, "{} {}", key, value)
+    } else {
+        writeln!(writer, "{}{{{}}} {}", key, attributes, value)
     }
 }
 
@@ -152,6 +219,11 @@ impl hyper::service::Service for MetricsServer {
             ));
         }
 
-        Box::new(future::ok(Response::new(self.serve())))
+        Box::new(future::ok(
+            Response::builder()
+                .header(CONTENT_TYPE, CONTENT_TYPE_TEXT)
+                .body(self.serve())
+                .unwrap()
+        ))
     }
 }
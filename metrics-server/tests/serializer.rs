@@ -0,0 +1,77 @@
+use hyper::Chunk;
+
+use nimiq_metrics_server::server::{MetricsSerializer, MetricType};
+use nimiq_metrics_server::server::attributes::{CachedAttributes, VecAttributes};
+use nimiq_metrics_server::server::histogram::Histogram;
+
+fn serialize<F: FnOnce(&mut MetricsSerializer<Vec<u8>>)>(common_attributes: VecAttributes, f: F) -> String {
+    let mut serializer = MetricsSerializer::new(CachedAttributes::from(common_attributes), Vec::new());
+    f(&mut serializer);
+    let chunk = Chunk::from(serializer);
+    String::from_utf8(chunk.to_vec()).unwrap()
+}
+
+fn attributes(attributes: &[(&str, &str)]) -> VecAttributes {
+    let mut vec_attributes = VecAttributes::new();
+    for (key, value) in attributes {
+        vec_attributes.add(key, value);
+    }
+    vec_attributes
+}
+
+#[test]
+fn it_writes_help_and_type_once() {
+    let output = serialize(VecAttributes::new(), |serializer| {
+        serializer.describe("chain_block", MetricType::Counter, "Number of blocks").unwrap();
+        serializer.metric_with_attributes("chain_block", 3, attributes(&[("action", "extended")])).unwrap();
+        serializer.describe("chain_block", MetricType::Counter, "Number of blocks").unwrap();
+        serializer.metric_with_attributes("chain_block", 1, attributes(&[("action", "forked")])).unwrap();
+        serializer.metric("chain_head_height", 42).unwrap();
+    });
+
+    assert_eq!(output, "\
+# HELP chain_block Number of blocks
+# TYPE chain_block counter
+chain_block{action=\"extended\"} 3
+chain_block{action=\"forked\"} 1
+chain_head_height 42
+");
+}
+
+#[test]
+fn it_combines_and_escapes_attributes() {
+    let output = serialize(attributes(&[("peer", "wss://a.b:8443/abc")]), |serializer| {
+        serializer.metric("chain_head_height", 42).unwrap();
+        serializer.metric_with_attributes("network_peers", 1, attributes(&[("state", "a\"b\\c")])).unwrap();
+    });
+
+    assert_eq!(output, "\
+chain_head_height{peer=\"wss://a.b:8443/abc\"} 42
+network_peers{state=\"a\\\"b\\\\c\",peer=\"wss://a.b:8443/abc\"} 1
+");
+}
+
+#[test]
+fn it_serializes_histograms() {
+    let mut histogram = Histogram::new(vec![10.0, 1.0, 5.0]);
+    histogram.observe(0.5);
+    histogram.observe(1.0);
+    histogram.observe(7.0);
+    histogram.observe(20.0);
+
+    let output = serialize(VecAttributes::new(), |serializer| {
+        serializer.describe("fee", MetricType::Histogram, "Fees").unwrap();
+        serializer.histogram("fee", &histogram, attributes(&[("type", "basic")])).unwrap();
+    });
+
+    assert_eq!(output, "\
+# HELP fee Fees
+# TYPE fee histogram
+fee_bucket{type=\"basic\",le=\"1\"} 2
+fee_bucket{type=\"basic\",le=\"5\"} 2
+fee_bucket{type=\"basic\",le=\"10\"} 3
+fee_bucket{type=\"basic\",le=\"+Inf\"} 4
+fee_sum{type=\"basic\"} 28.5
+fee_count{type=\"basic\"} 4
+");
+}
@@ -15,6 +15,7 @@ use hash::pbkdf2::{compute_pbkdf2_sha512, Pbkdf2Error};
 use macros::{add_hex_io_fns_typed_arr, create_typed_array};
 use utils::bit_vec::IntoChunkedBitVecIterator;
 use utils::crc::Crc8Computer;
+use utils::key_rng::{CryptoRng, Rng, SecureGenerate};
 
 #[cfg(feature = "key-derivation")]
 pub mod key_derivation;
@@ -24,6 +25,14 @@ pub mod key_derivation;
 create_typed_array!(Entropy, u8, 32);
 add_hex_io_fns_typed_arr!(Entropy, 32);
 
+impl SecureGenerate for Entropy {
+    fn generate<R: Rng + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; Entropy::SIZE];
+        rng.fill(&mut bytes);
+        Entropy(bytes)
+    }
+}
+
 impl Entropy {
     const CHECKSUM_SIZE: usize = Self::SIZE * 8 / 32;
 
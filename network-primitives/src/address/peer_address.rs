@@ -21,6 +21,7 @@ pub enum PeerAddressType {
     Ws(String, u16),
     Wss(String, u16),
     Rtc,
+    Noise(String, u16),
 }
 
 impl PeerAddressType {
@@ -29,7 +30,8 @@ impl PeerAddressType {
             PeerAddressType::Dumb => Protocol::Dumb,
             PeerAddressType::Ws(_, _) => Protocol::Ws,
             PeerAddressType::Wss(_, _) => Protocol::Wss,
-            PeerAddressType::Rtc => Protocol::Rtc
+            PeerAddressType::Rtc => Protocol::Rtc,
+            PeerAddressType::Noise(_, _) => Protocol::Noise,
         }
     }
 }
@@ -64,7 +66,8 @@ impl Serialize for PeerAddress {
             PeerAddressType::Dumb => 0,
             PeerAddressType::Ws(host, port) => host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
             PeerAddressType::Wss(host, port) => host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
-            PeerAddressType::Rtc => 0
+            PeerAddressType::Rtc => 0,
+            PeerAddressType::Noise(host, port) => host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
         };
         Ok(size)
     }
@@ -82,7 +85,8 @@ impl Serialize for PeerAddress {
             PeerAddressType::Dumb => 0,
             PeerAddressType::Ws(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
             PeerAddressType::Wss(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
-            PeerAddressType::Rtc => 0
+            PeerAddressType::Rtc => 0,
+            PeerAddressType::Noise(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
         };
         size
     }
@@ -101,7 +105,8 @@ impl Deserialize for PeerAddress {
             Protocol::Dumb => PeerAddressType::Dumb,
             Protocol::Ws => PeerAddressType::Ws(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?),
             Protocol::Wss => PeerAddressType::Wss(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?),
-            Protocol::Rtc => PeerAddressType::Rtc
+            Protocol::Rtc => PeerAddressType::Rtc,
+            Protocol::Noise => PeerAddressType::Noise(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?),
         };
         let peer_id = PeerId::from(&public_key);
         Ok(PeerAddress{ ty: type_special, services, timestamp, net_address, public_key, distance, signature: Some(signature), peer_id})
@@ -131,6 +136,7 @@ impl PeerAddress {
         match self.ty {
             PeerAddressType::Ws(ref host, ref port) => Some(format!("ws://{}:{}/{}", host, port, public_key)),
             PeerAddressType::Wss(ref host, ref port) => Some(format!("wss://{}:{}/{}", host, port, public_key)),
+            PeerAddressType::Noise(ref host, ref port) => Some(format!("noise://{}:{}/{}", host, port, public_key)),
             _ => None, // Seed nodes should never be PeerAddressType::RTC or PeerAddressType::Dumb
         }
     }
@@ -141,7 +147,7 @@ impl PeerAddress {
         res.append(&mut self.timestamp.serialize_to_vec());
 
         match &self.ty {
-            PeerAddressType::Ws(host, port) | PeerAddressType::Wss(host, port) => {
+            PeerAddressType::Ws(host, port) | PeerAddressType::Wss(host, port) | PeerAddressType::Noise(host, port) => {
                 res.append(&mut host.serialize_to_vec::<u8>());
                 res.append(&mut port.serialize_to_vec());
             }
@@ -165,6 +171,7 @@ impl PeerAddress {
             match (age, self.protocol()) {
                 (Some(age), Protocol::Ws) =>  return age > MAX_AGE_WEBSOCKET,
                 (Some(age), Protocol::Wss) =>  return age > MAX_AGE_WEBSOCKET,
+                (Some(age), Protocol::Noise) =>  return age > MAX_AGE_WEBSOCKET,
                 (Some(age), Protocol::Rtc) =>  return age > MAX_AGE_WEBRTC,
                 (Some(age), Protocol::Dumb) =>  return age > MAX_AGE_DUMB,
                 (None, _) => return false,
@@ -175,7 +182,7 @@ impl PeerAddress {
 
     pub fn is_globally_reachable(&self, legacy_mode: bool) -> bool {
         match &self.ty {
-            PeerAddressType::Ws(host, _) => {
+            PeerAddressType::Ws(host, _) | PeerAddressType::Noise(host, _) => {
                 // If host is an ip, check if it's globally reachable
                 if let Ok(ip) = IpAddr::from_str(&host[..]) {
                     if legacy_mode {
@@ -204,7 +211,7 @@ impl PeerAddress {
             _ => {}
         }
         match &self.ty {
-            PeerAddressType::Wss(host, _) | PeerAddressType::Ws(host, _) => {
+            PeerAddressType::Wss(host, _) | PeerAddressType::Ws(host, _) | PeerAddressType::Noise(host, _) => {
                 // "the use of dotless domains is prohibited [in new gTLDs]" [ https://www.icann.org/resources/board-material/resolutions-new-gtld-2013-08-13-en#1 ]. Old gTLDs rarely use them.
                 if !host[1..host.len()-1].contains('.') {
                     return false;
@@ -244,7 +251,8 @@ impl Hash for PeerAddress {
             PeerAddressType::Dumb => format!("dumb:///{}", peer_id),
             PeerAddressType::Ws(_, _) => format!("ws:///{}", peer_id),
             PeerAddressType::Wss(_, _) => format!("wss:///{}", peer_id),
-            PeerAddressType::Rtc => format!("rtc:///{}", peer_id)
+            PeerAddressType::Rtc => format!("rtc:///{}", peer_id),
+            PeerAddressType::Noise(_, _) => format!("noise:///{}", peer_id),
         };
         peer_id_uri.hash(state);
     }
@@ -263,7 +271,8 @@ impl Deserialize for PeerAddressType {
             Protocol::Dumb => Ok(PeerAddressType::Dumb),
             Protocol::Ws => Ok(PeerAddressType::Ws(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?)),
             Protocol::Wss => Ok(PeerAddressType::Wss(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?)),
-            Protocol::Rtc => Ok(PeerAddressType::Rtc)
+            Protocol::Rtc => Ok(PeerAddressType::Rtc),
+            Protocol::Noise => Ok(PeerAddressType::Noise(DeserializeWithLength::deserialize::<u8, R>(reader)?, Deserialize::deserialize(reader)?)),
         }
     }
 }
@@ -274,7 +283,8 @@ impl Serialize for PeerAddressType {
             PeerAddressType::Dumb => Protocol::Dumb.serialize(writer)?,
             PeerAddressType::Ws(host, port) => Protocol::Ws.serialize(writer)? + host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
             PeerAddressType::Wss(host, port) => Protocol::Wss.serialize(writer)? + host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
-            PeerAddressType::Rtc => Protocol::Rtc.serialize(writer)?
+            PeerAddressType::Rtc => Protocol::Rtc.serialize(writer)?,
+            PeerAddressType::Noise(host, port) => Protocol::Noise.serialize(writer)? + host.serialize::<u8, W>(writer)? + port.serialize(writer)?,
         })
     }
 
@@ -282,6 +292,7 @@ impl Serialize for PeerAddressType {
         Protocol::Dumb.serialized_size() + match self {
             PeerAddressType::Ws(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
             PeerAddressType::Wss(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
+            PeerAddressType::Noise(host, port) => host.serialized_size::<u8>() + port.serialized_size(),
             _ => 0
         }
     }
@@ -41,7 +41,7 @@ pub enum PeerUriError {
     InvalidPublicKey(#[cause] keys::ParseError),
     #[fail(display = "Seed node is missing the public key")]
     SeedNodeMissingPublicKey,
-    #[fail(display = "The only allowed protocols for seed nodes are Wss and Ws")]
+    #[fail(display = "The only allowed protocols for seed nodes are Wss, Ws and Noise")]
     SeedNodeWithInvalidProtocol,
 }
 
@@ -72,6 +72,7 @@ impl FromStr for Protocol {
             "ws" => Ok(Protocol::Ws),
             "wss" => Ok(Protocol::Wss),
             "rtc" => Ok(Protocol::Rtc),
+            "noise" => Ok(Protocol::Noise),
             _ => Err(PeerUriError::UnknownProtocol)
         }
     }
@@ -84,6 +85,7 @@ impl fmt::Display for Protocol {
             Protocol::Ws => "ws",
             Protocol::Wss => "wss",
             Protocol::Rtc => "rtc",
+            Protocol::Noise => "noise",
         })
     }
 }
@@ -113,7 +115,7 @@ impl<'a> fmt::Display for PeerUri {
                 write!(f, "{}://{}", self.protocol, self.peer_id()
                     .expect("No peer ID for dumb/rtc URI"))?;
             },
-            Protocol::Ws | Protocol::Wss => {
+            Protocol::Ws | Protocol::Wss | Protocol::Noise => {
                 write!(f, "{}://{}", self.protocol, self.hostname.as_ref().unwrap())?;
                 self.port.map(|p| write!(f, ":{}", p)).transpose()?;
                 self.peer_id().or_else(|| self.public_key()).map(|p| write!(f, "/{}", p)).transpose()?;
@@ -146,7 +148,7 @@ impl PeerUri {
         // or None if there was no path segments at all. If there are multiple segments, returns
         // with an error.
         //
-        // For Dumb and Rtc this must be None (checked later). For Ws and Wss this is the peer_id.
+        // For Dumb and Rtc this must be None (checked later). For Ws, Wss and Noise this is the peer_id.
         let path_segment = url.path_segments()
             .and_then(|segments| {
                 let segments = segments.collect::<Vec<&str>>();
@@ -174,7 +176,7 @@ impl PeerUri {
                     public_key: None
                 })
             },
-            Protocol::Ws | Protocol::Wss => {
+            Protocol::Ws | Protocol::Wss | Protocol::Noise => {
                 let host = String::from(url.host_str().ok_or_else(|| PeerUriError::MissingHostname)?);
                 let (peer_id, public_key) = match path_segment {
                     Some(ref peer_id) if peer_id.len() == 2 * PeerId::SIZE => (path_segment, None),
@@ -231,6 +233,16 @@ impl PeerUri {
                 signature: None,
                 peer_id: PeerId::from(&public_key),
             }),
+            Protocol::Noise => Ok(PeerAddress {
+                ty: PeerAddressType::Noise(self.hostname().expect("Mandatory for Noise").to_string(), self.port().unwrap_or(8445)),
+                services: ServiceFlags::FULL,
+                timestamp: 0,
+                net_address: NetAddress::Unspecified,
+                public_key,
+                distance: 0,
+                signature: None,
+                peer_id: PeerId::from(&public_key),
+            }),
             _ => Err(PeerUriError::SeedNodeWithInvalidProtocol),
         }
     }
@@ -245,7 +257,7 @@ impl From<PeerAddress> for PeerUri {
             PeerAddressType::Dumb | PeerAddressType::Rtc => {
                 PeerUri { protocol, peer_id, hostname: None, port: None, public_key: None }
             },
-            PeerAddressType::Ws(host, port) | PeerAddressType::Wss(host, port) => {
+            PeerAddressType::Ws(host, port) | PeerAddressType::Wss(host, port) | PeerAddressType::Noise(host, port) => {
                 PeerUri { protocol, peer_id, hostname: Some(host), port: Some(port), public_key: None }
             }
         }
@@ -6,7 +6,8 @@ pub enum Protocol {
     Dumb = 0,
     Wss = 1,
     Rtc = 2,
-    Ws = 4
+    Ws = 4,
+    Noise = 8,
 }
 
 impl From<ProtocolFlags> for Vec<Protocol> {
@@ -24,6 +25,9 @@ impl From<ProtocolFlags> for Vec<Protocol> {
         if flags.contains(ProtocolFlags::WS) {
             v.push(Protocol::Ws);
         }
+        if flags.contains(ProtocolFlags::NOISE) {
+            v.push(Protocol::Noise);
+        }
         v
     }
 }
@@ -35,6 +39,7 @@ bitflags! {
         const WSS   = 0b0000_0001;
         const RTC   = 0b0000_0010;
         const WS    = 0b0000_0100;
+        const NOISE = 0b0000_1000;
     }
 }
 
@@ -45,6 +50,7 @@ impl From<Protocol> for ProtocolFlags {
             Protocol::Rtc => ProtocolFlags::RTC,
             Protocol::Wss => ProtocolFlags::WSS,
             Protocol::Ws => ProtocolFlags::WS,
+            Protocol::Noise => ProtocolFlags::NOISE,
         }
     }
 }
@@ -60,9 +66,18 @@ impl From<Vec<Protocol>> for ProtocolFlags {
 }
 
 impl Protocol {
+    /// Whether peers using this protocol have a stable address that others can connect to.
+    pub fn is_connectable(self) -> bool {
+        match self {
+            Protocol::Ws | Protocol::Wss | Protocol::Noise => true,
+            Protocol::Dumb | Protocol::Rtc => false,
+        }
+    }
+
     pub fn default_port(self) -> Option<u16> {
         match self {
             Protocol::Ws | Protocol::Wss => Some(8443),
+            Protocol::Noise => Some(8445),
             _ => None
         }
     }
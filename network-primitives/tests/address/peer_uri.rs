@@ -68,3 +68,13 @@ fn test_parse_uri_ws() {
     assert_eq!(uri.peer_id(), Some(String::from("2b3f0f59334ef71ee7869b451139587f")).as_ref());
 }
 
+
+#[test]
+fn test_parse_uri_noise() {
+    let uri = PeerUri::from_str("noise://seed-20.nimiq.com:8445/2b3f0f59334ef71ee7869b451139587f").unwrap();
+    assert_eq!(uri.protocol(), Protocol::Noise);
+    assert_eq!(uri.hostname(), Some(String::from("seed-20.nimiq.com")).as_ref());
+    assert_eq!(uri.port(), Some(8445));
+    assert_eq!(uri.peer_id(), Some(String::from("2b3f0f59334ef71ee7869b451139587f")).as_ref());
+    assert_eq!(uri.to_string(), "noise://seed-20.nimiq.com:8445/2b3f0f59334ef71ee7869b451139587f");
+}
@@ -1,3 +1,4 @@
-mod peer_address_seeder;
+pub mod peer_address_seeder;
 pub mod peer_address_book;
 pub mod peer_address_state;
+pub mod peer_address_store;
@@ -11,11 +11,13 @@ use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant, SystemTime};
 
+use beserial::Serialize;
+use database::Environment;
 use macros::upgrade_weak;
 use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use rand::{Rng, rngs::OsRng};
 
-use network_primitives::address::net_address::NetAddress;
+use network_primitives::address::net_address::{NetAddress, NetAddressType};
 use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::address::PeerId;
 use network_primitives::networks::{NetworkInfo, NetworkId};
@@ -33,6 +35,7 @@ use crate::peer_channel::PeerChannel;
 use super::peer_address_seeder::{PeerAddressSeeder, PeerAddressSeederEvent};
 use super::peer_address_state::PeerAddressInfo;
 use super::peer_address_state::PeerAddressState;
+use super::peer_address_store::{PeerAddressStore, StoredPeerAddressInfo};
 use crate::error::Error;
 
 pub struct PeerAddressBookState {
@@ -40,8 +43,11 @@ pub struct PeerAddressBookState {
     ws_addresses: HashSet<Arc<PeerAddress>>,
     wss_addresses: HashSet<Arc<PeerAddress>>,
     rtc_addresses: HashSet<Arc<PeerAddress>>,
+    noise_addresses: HashSet<Arc<PeerAddress>>,
     address_by_peer_id: HashMap<PeerId, Arc<PeerAddress>>,
     addresses_by_net_address: HashMap<NetAddress, HashSet<Arc<PeerAddress>>>,
+    num_addresses_by_source_group: HashMap<NetAddress, usize>,
+    num_addresses_by_group: HashMap<NetAddress, usize>,
 }
 
 impl PeerAddressBookState {
@@ -65,6 +71,10 @@ impl PeerAddressBookState {
         self.rtc_addresses.iter()
     }
 
+    pub fn noise_address_iter(&self) -> Iter<Arc<PeerAddress>> {
+        self.noise_addresses.iter()
+    }
+
     pub fn address_iter_for_protocol_mask(&self, protocol_mask: ProtocolFlags) -> QueryIterator {
         if protocol_mask == ProtocolFlags::WSS {
             QueryIterator::Iter(self.wss_address_iter())
@@ -78,6 +88,8 @@ impl PeerAddressBookState {
             QueryIterator::Alternate(Alternate::new(self.rtc_address_iter(), self.ws_address_iter()))
         } else if protocol_mask == ProtocolFlags::RTC | ProtocolFlags::WSS {
             QueryIterator::Alternate(Alternate::new(self.rtc_address_iter(), self.wss_address_iter()))
+        } else if protocol_mask == ProtocolFlags::NOISE {
+            QueryIterator::Iter(self.noise_address_iter())
         } else {
             QueryIterator::Keys(self.address_iter())
         }
@@ -96,6 +108,8 @@ impl PeerAddressBookState {
             self.known_rtc_addresses_count() + self.known_ws_addresses_count()
         } else if protocol_mask == ProtocolFlags::RTC | ProtocolFlags::WSS {
             self.known_rtc_addresses_count() + self.known_wss_addresses_count()
+        } else if protocol_mask == ProtocolFlags::NOISE {
+            self.known_noise_addresses_count()
         } else {
             self.known_addresses_count()
         }
@@ -129,7 +143,16 @@ impl PeerAddressBookState {
         None
     }
 
-    fn add_to_store(&mut self, info: PeerAddressInfo) {
+    fn add_to_store(&mut self, mut info: PeerAddressInfo) {
+        // Count the address in its buckets.
+        info.address_group = address_group(&info.peer_address.net_address);
+        if let Some(group) = info.address_group {
+            *self.num_addresses_by_group.entry(group).or_insert(0) += 1;
+        }
+        if let Some(source_group) = info.source_group {
+            *self.num_addresses_by_source_group.entry(source_group).or_insert(0) += 1;
+        }
+
         // Index by peer id.
         self.address_by_peer_id.insert(info.peer_address.peer_id.clone(), Arc::clone(&info.peer_address));
 
@@ -144,6 +167,9 @@ impl PeerAddressBookState {
             Protocol::Rtc => {
                 self.rtc_addresses.insert(Arc::clone(&info.peer_address));
             },
+            Protocol::Noise => {
+                self.noise_addresses.insert(Arc::clone(&info.peer_address));
+            },
             Protocol::Dumb => { } // Dumb addresses are ignored.
         };
 
@@ -183,6 +209,9 @@ impl PeerAddressBookState {
             Protocol::Rtc => {
                 self.rtc_addresses.remove(&peer_address);
             },
+            Protocol::Noise => {
+                self.noise_addresses.remove(&peer_address);
+            },
             _ => {}
         }
 
@@ -193,10 +222,36 @@ impl PeerAddressBookState {
             }
 
             // Delete the address.
-            self.info_by_address.remove(&peer_address);
+            if let Some(info) = self.info_by_address.remove(&peer_address) {
+                if let Some(group) = info.address_group {
+                    Self::decrement_bucket(&mut self.num_addresses_by_group, group);
+                }
+                if let Some(source_group) = info.source_group {
+                    Self::decrement_bucket(&mut self.num_addresses_by_source_group, source_group);
+                }
+            }
         }
     }
 
+    fn decrement_bucket(buckets: &mut HashMap<NetAddress, usize>, group: NetAddress) {
+        if let Some(count) = buckets.get_mut(&group) {
+            *count -= 1;
+            if *count == 0 {
+                buckets.remove(&group);
+            }
+        }
+    }
+
+    /// The number of known addresses in the address group `group`.
+    pub fn num_addresses_by_group(&self, group: &NetAddress) -> usize {
+        self.num_addresses_by_group.get(group).copied().unwrap_or(0)
+    }
+
+    /// The number of known addresses announced by peers in the address group `source_group`.
+    pub fn num_addresses_by_source_group(&self, source_group: &NetAddress) -> usize {
+        self.num_addresses_by_source_group.get(source_group).copied().unwrap_or(0)
+    }
+
     /// Delete all RTC-only routes that are signalable over the given peer.
     fn remove_by_signal_channel(&mut self, channel: Arc<PeerChannel>) {
         // XXX inefficient linear scan
@@ -233,8 +288,13 @@ impl PeerAddressBookState {
         }
 
         if let Some(info) = self.info_by_address.get_mut(&peer_address) {
+            let banned_until = Instant::now() + duration;
+            // Never shorten an active ban, e.g. one set manually before the peer was disconnected.
+            info.banned_until = match info.banned_until {
+                Some(current) if info.state == PeerAddressState::Banned => Some(cmp::max(current, banned_until)),
+                _ => Some(banned_until),
+            };
             info.state = PeerAddressState::Banned;
-            info.banned_until = Some(Instant::now() + duration);
 
             // Drop all routes to this peer.
             info.signal_router.delete_all_routes();
@@ -258,6 +318,7 @@ impl PeerAddressBookState {
     pub fn known_ws_addresses_count(&self) -> usize { self.ws_addresses.len() }
     pub fn known_wss_addresses_count(&self) -> usize { self.wss_addresses.len() }
     pub fn known_rtc_addresses_count(&self) -> usize { self.rtc_addresses.len() }
+    pub fn known_noise_addresses_count(&self) -> usize { self.noise_addresses.len() }
 }
 
 pub struct PeerAddressBook {
@@ -265,6 +326,11 @@ pub struct PeerAddressBook {
     seeded: AtomicBool,
     network_config: Arc<NetworkConfig>,
     network_id: NetworkId,
+    store: PeerAddressStore,
+    /// The serialized entries in the store, keyed like the store. Used to only write the entries
+    /// that changed since they were last persisted.
+    persisted: Mutex<HashMap<String, Vec<u8>>>,
+    anchors: RwLock<Vec<Arc<PeerAddress>>>,
     timers: Timers<PeerAddressBookTimer>,
     change_lock: Mutex<()>,
     pub notifier: Notifier<'static, PeerAddressBookEvent>,
@@ -282,19 +348,25 @@ pub enum PeerAddressBookEvent {
 }
 
 impl PeerAddressBook {
-    pub fn new(network_config: Arc<NetworkConfig>, network_id: NetworkId) -> Result<Self, Error> {
+    pub fn new(network_config: Arc<NetworkConfig>, network_id: NetworkId, env: Environment) -> Result<Self, Error> {
         let this = Self {
             state: RwLock::new(PeerAddressBookState {
                 info_by_address: HashMap::new(),
                 ws_addresses: HashSet::new(),
                 wss_addresses: HashSet::new(),
                 rtc_addresses: HashSet::new(),
+                noise_addresses: HashSet::new(),
                 address_by_peer_id: HashMap::new(),
                 addresses_by_net_address: HashMap::new(),
+                num_addresses_by_source_group: HashMap::new(),
+                num_addresses_by_group: HashMap::new(),
             }),
             seeded: AtomicBool::new(false),
             network_id,
             network_config,
+            store: PeerAddressStore::new(env),
+            persisted: Mutex::new(HashMap::new()),
+            anchors: RwLock::new(Vec::new()),
             timers: Timers::new(),
             change_lock: Mutex::new(()),
             notifier: Notifier::new(),
@@ -318,6 +390,10 @@ impl PeerAddressBook {
 
     /// Initialises async stuff.
     pub fn initialize(this: &Arc<Self>) -> Result<(), Error> {
+        // Restore the addresses known before the last shutdown.
+        this.restore();
+        this.restore_anchors();
+
         // Setup housekeeping interval.
         let weak = Arc::downgrade(this);
         this.timers.set_interval(PeerAddressBookTimer::Housekeeping, move || {
@@ -380,6 +456,11 @@ impl PeerAddressBook {
                         return false;
                     }
 
+                    // Never return unsigned addresses, e.g. of peers added manually. Peers would reject them.
+                    if peer_address.signature.is_none() {
+                        return false;
+                    }
+
                     // Only return addresses matching the protocol mask.
                     if !protocol_mask.contains(ProtocolFlags::from(peer_address.protocol())) {
                         return false;
@@ -519,6 +600,10 @@ impl PeerAddressBook {
                     if state.rtc_addresses.len() >= MAX_SIZE_RTC {
                         return false;
                     },
+                Protocol::Noise =>
+                    if state.noise_addresses.len() >= MAX_SIZE_NOISE {
+                        return false;
+                    },
                 Protocol::Dumb => {}, // Dumb addresses are only part of global limit.
             }
 
@@ -532,8 +617,23 @@ impl PeerAddressBook {
                 }
             }
 
+            // Limit the number of addresses a single network can announce to us or own in our address book,
+            // so that an attacker controlling one network can't fill the address book with its own nodes.
+            let source_group = net_address.as_ref().and_then(|net_address| address_group(net_address));
+            if let Some(ref source_group) = source_group {
+                if state.num_addresses_by_source_group(source_group) >= MAX_SIZE_PER_SOURCE_GROUP {
+                    return false;
+                }
+            }
+            if let Some(ref group) = address_group(&addr_arc.net_address) {
+                if state.num_addresses_by_group(group) >= MAX_SIZE_PER_GROUP {
+                    return false;
+                }
+            }
+
             // Add new peerAddressState.
-            let new_info = PeerAddressInfo::new(addr_arc.clone());
+            let mut new_info = PeerAddressInfo::new(addr_arc.clone());
+            new_info.source_group = source_group;
             state.add_to_store(new_info);
             changed = true;
         }
@@ -594,7 +694,7 @@ impl PeerAddressBook {
 
     /// Called when a connection to this peerAddress is closed.
     pub fn close(&self, channel: Option<Arc<PeerChannel>>, peer_address: Arc<PeerAddress>, ty: CloseType) {
-        let _guard = self.change_lock.lock();
+        let guard = self.change_lock.lock();
 
         let mut state = self.state.write();
         if let Some(info) = state.info_by_address.get_mut(&peer_address) {
@@ -629,6 +729,13 @@ impl PeerAddressBook {
                 state.remove_from_store(peer_address);
             }
         }
+
+        // Persist bans immediately, so that they survive a restart.
+        drop(state);
+        drop(guard);
+        if ty.is_banning_type() {
+            self.persist();
+        }
     }
 
     /// Called when a message has been returned as unroutable.
@@ -723,15 +830,150 @@ impl PeerAddressBook {
             state.remove_from_store(peer_address);
         }
 
-        // Drop the guard before notifying.
+        // Drop the guard before persisting and notifying.
         drop(state);
         drop(guard);
 
+        // Persist the address book, so we can reconnect to known peers after a restart.
+        self.persist();
+
         if !unbanned_addresses.is_empty() {
             self.notifier.notify(PeerAddressBookEvent::Added(unbanned_addresses));
         }
     }
 
+    /// Adds all stored addresses that are not stale to the address book.
+    fn restore(&self) {
+        let guard = self.change_lock.lock();
+
+        // The persisted entries are always locked before the state.
+        let mut persisted = self.persisted.lock();
+        let mut state = self.state.write();
+        let mut restored_addresses: Vec<PeerAddress> = Vec::new();
+        for stored_info in self.store.load() {
+            persisted.insert(stored_info.peer_address.peer_id.to_hex(), stored_info.serialize_to_vec());
+
+            let info = match stored_info.into_info() {
+                Some(info) => info,
+                None => continue,
+            };
+
+            // Seed addresses and addresses we learned about in the meantime take precedence.
+            if state.info_by_address.contains_key(&info.peer_address) || state.info_by_address.len() >= MAX_SIZE {
+                continue;
+            }
+
+            if info.state != PeerAddressState::Banned {
+                restored_addresses.push(info.peer_address.as_ref().clone());
+            }
+            state.add_to_store(info);
+        }
+
+        debug!("Restored {} peer addresses", restored_addresses.len());
+
+        // Drop the guard before notifying.
+        drop(state);
+        drop(persisted);
+        drop(guard);
+
+        if !restored_addresses.is_empty() {
+            self.notifier.notify(PeerAddressBookEvent::Added(restored_addresses));
+        }
+    }
+
+    /// Restores the anchor connections of the last session, unless they have been banned since.
+    fn restore_anchors(&self) {
+        let _guard = self.change_lock.lock();
+
+        let mut state = self.state.write();
+        let mut anchors = Vec::new();
+        for stored_info in self.store.load_anchors() {
+            let info = match stored_info.into_info() {
+                Some(info) => info,
+                None => continue,
+            };
+
+            let peer_address = match state.get_by_peer_id(&info.peer_address.peer_id) {
+                Some(peer_address) => peer_address,
+                None => {
+                    let peer_address = Arc::clone(&info.peer_address);
+                    state.add_to_store(info);
+                    peer_address
+                },
+            };
+            if !state.is_banned(&peer_address) {
+                anchors.push(peer_address);
+            }
+        }
+
+        debug!("Restored {} anchor peers", anchors.len());
+        *self.anchors.write() = anchors;
+    }
+
+    /// The peers we keep our outbound anchor connections to. Anchor connections are never recycled
+    /// and are re-established first after a restart, so that an attacker can't take over all of our
+    /// outbound connections by flooding us with addresses while we are offline.
+    pub fn anchors(&self) -> Vec<Arc<PeerAddress>> {
+        self.anchors.read().clone()
+    }
+
+    pub fn is_anchor(&self, peer_address: &PeerAddress) -> bool {
+        self.anchors.read().iter().any(|anchor| anchor.as_ref() == peer_address)
+    }
+
+    /// Replaces the anchors and persists them. Nothing is written if the anchors didn't change.
+    pub fn set_anchors(&self, anchors: Vec<Arc<PeerAddress>>) {
+        {
+            let current = self.anchors.read();
+            if current.len() == anchors.len() && anchors.iter().all(|anchor| current.contains(anchor)) {
+                return;
+            }
+        }
+
+        let infos: Vec<StoredPeerAddressInfo> = {
+            let state = self.state.read();
+            anchors.iter()
+                .filter_map(|peer_address| state.get_info(peer_address))
+                .filter_map(StoredPeerAddressInfo::from_info)
+                .collect()
+        };
+        self.store.store_anchors(&infos);
+        *self.anchors.write() = anchors;
+    }
+
+    /// Writes the entries that changed since they were last persisted to the store.
+    /// The store is written after releasing the state lock.
+    fn persist(&self) {
+        // Holding this lock while writing keeps concurrent calls from writing an older state last.
+        let mut persisted = self.persisted.lock();
+
+        let infos: HashMap<String, StoredPeerAddressInfo> = self.state.read().address_info_iter()
+            .filter_map(StoredPeerAddressInfo::from_info)
+            .map(|info| (info.peer_address.peer_id.to_hex(), info))
+            .collect();
+
+        let removed: Vec<String> = persisted.keys()
+            .filter(|key| !infos.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in removed.iter() {
+            persisted.remove(key);
+        }
+
+        let mut changed = Vec::new();
+        for (key, info) in infos {
+            let bytes = info.serialize_to_vec();
+            if persisted.get(&key) != Some(&bytes) {
+                persisted.insert(key, bytes);
+                changed.push(info);
+            }
+        }
+
+        if !changed.is_empty() || !removed.is_empty() {
+            self.store.update(&changed, &removed);
+        }
+    }
+
     pub fn seeded(&self) -> bool {
         self.seeded.load(Ordering::Acquire)
     }
@@ -740,11 +982,39 @@ impl PeerAddressBook {
     pub fn known_ws_addresses_count(&self) -> usize { self.state.read().ws_addresses.len() }
     pub fn known_wss_addresses_count(&self) -> usize { self.state.read().wss_addresses.len() }
     pub fn known_rtc_addresses_count(&self) -> usize { self.state.read().rtc_addresses.len() }
+    pub fn known_noise_addresses_count(&self) -> usize { self.state.read().noise_addresses.len() }
 
     pub fn is_banned(&self, peer_address: &Arc<PeerAddress>) -> bool {
         self.state.read().is_banned(peer_address)
     }
 
+    /// Bans `peer_address` for at least `duration`, adding it to the address book if it is unknown.
+    /// The ban is persisted immediately, so that it survives a restart.
+    pub fn ban(&self, peer_address: Arc<PeerAddress>, duration: Duration) {
+        {
+            let _guard = self.change_lock.lock();
+            self.state.write().ban(peer_address, duration);
+        }
+        self.persist();
+    }
+
+    /// Lifts a ban on `peer_address` and resets its failure backoff.
+    /// Returns whether the address was banned.
+    pub fn unban(&self, peer_address: &Arc<PeerAddress>) -> bool {
+        let _guard = self.change_lock.lock();
+        let mut state = self.state.write();
+        match state.info_by_address.get_mut(peer_address) {
+            Some(info) if info.state == PeerAddressState::Banned => {
+                info.state = PeerAddressState::Tried;
+                info.banned_until = None;
+                info.failed_attempts = 0;
+                info.ban_backoff = INITIAL_FAILED_BACKOFF;
+                true
+            },
+            _ => false,
+        }
+    }
+
     pub fn state(&self) -> RwLockReadGuard<PeerAddressBookState> {
         self.state.read()
     }
@@ -754,6 +1024,26 @@ impl PeerAddressBook {
     }
 }
 
+impl Drop for PeerAddressBook {
+    fn drop(&mut self) {
+        // Persist the changes since the last housekeeping on shutdown.
+        self.persist();
+    }
+}
+
+/// Returns the address group of a reliable net address, i.e. its /16 subnet for IPv4 and /32 subnet for IPv6.
+/// Addresses are bucketed by group, since a single operator usually controls no more than a few groups.
+pub fn address_group(net_address: &NetAddress) -> Option<NetAddress> {
+    if !net_address.is_reliable() {
+        return None;
+    }
+    match net_address.get_type() {
+        NetAddressType::IPv4 => Some(net_address.subnet(16)),
+        NetAddressType::IPv6 => Some(net_address.subnet(32)),
+        _ => None,
+    }
+}
+
 #[derive(Clone)]
 pub enum QueryIterator<'a> {
     Keys(Keys<'a, Arc<PeerAddress>, PeerAddressInfo>),
@@ -798,14 +1088,17 @@ pub const MAX_FAILED_ATTEMPTS_RTC: u32 = 2;
 
 const MAX_TIMESTAMP_DRIFT: Duration = Duration::from_secs(60 * 10); // 10 minutes
 const HOUSEKEEPING_INTERVAL: Duration = Duration::from_secs(60); // 1 minute
-const DEFAULT_BAN_TIME: Duration = Duration::from_secs(60 * 10); // 10 minutes
+pub const DEFAULT_BAN_TIME: Duration = Duration::from_secs(60 * 10); // 10 minutes
 pub const INITIAL_FAILED_BACKOFF: Duration = Duration::from_secs(30); // 30 seconds
 pub const MAX_FAILED_BACKOFF: Duration = Duration::from_secs(60 * 10); // 10 minutes
 
 const MAX_SIZE_WS: usize = 10000; // TODO different for browser
 const MAX_SIZE_WSS: usize = 10000;
 const MAX_SIZE_RTC: usize = 10000;
+const MAX_SIZE_NOISE: usize = 10000;
 const MAX_SIZE: usize = 20500; // Includes dumb peers
 const MAX_SIZE_PER_IP: usize = 250;
+const MAX_SIZE_PER_SOURCE_GROUP: usize = 1000;
+const MAX_SIZE_PER_GROUP: usize = 64;
 
 const SEEDING_TIMEOUT: Duration = Duration::from_secs(3); // 3 seconds
@@ -9,7 +9,7 @@ use parking_lot::Mutex;
 use reqwest::r#async::{Chunk, Client, Response};
 use url::Url;
 
-use keys::Signature;
+use keys::{PublicKey, Signature};
 use crate::network_config::{NetworkConfig, Seed};
 use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::address::peer_uri::{PeerUri, PeerUriError};
@@ -88,66 +88,10 @@ impl PeerAddressSeeder {
 
             trace!("Start processing remote seed list: {}", &seed_list.url());
             let task = Self::fetch(seed_list.url().clone())
-            .and_then(move |response_body| {
-                let mut signature = None;
-                let mut seed_addresses = Vec::new();
-
-                // Process each line of the seed list
-                for line in response_body.lines() {
-                    // Abort if the line can't be read properly
-                    if let Err(e) = line {
-                        return err(PeerAddressSeederError::IoError(e));
-                    }
-                    let line = line.expect("Validated this above");
-
-                    // The signature should always be in the last line
-                    if signature.is_some() {
-                        return err(PeerAddressSeederError::SignatureNotInLastLine);
-                    }
-
-                    // Ignore comments and empty lines
-                    let line = line.trim();
-                    if line.is_empty() || line.starts_with('#') {
-                        continue;
-                    }
-
-                    // Try to parse the line as a seed address, if that fails, fallback to try to parse it as a signature
-                    // TODO: Should we fail if this step fails (i.e. if there is a non-comment/non-empty line that is not
-                    // a seed address neither a signature)?
-                    match PeerUri::from_str(line) {
-                        Ok(seed_address) => {
-                            match seed_address.as_seed_peer_address() {
-                                Ok(peer_address) => seed_addresses.push(peer_address),
-                                Err(e) => return err(PeerAddressSeederError::PeerUriParsingError(e)),
-                            }
-                        },
-                        _ => signature = Signature::from_hex(line).ok(),
-                    }
-                }
-
-                // Error out if we couldn't find any parseable seed address
-                if seed_addresses.is_empty() {
-                    return err(PeerAddressSeederError::EmptySeedAddresses);
-                }
-
-                // Verify the signature if a public key was provided for this seed list
-                if let Some(public_key) = seed_list.public_key() {
-                    if let Some(signature) = signature {
-                        // Serialize the seed addresses for signature verification
-                        let data = seed_addresses.iter().filter_map(PeerAddress::to_seed_string).collect::<Vec<String>>().join("\n");
-                        let data = data.as_bytes();
-
-                        if !public_key.verify(&signature, data) {
-                            return err(PeerAddressSeederError::SignatureVerificationFailed)
-                        }
-                    } else { // No signature was found on the seed list file
-                        return err(PeerAddressSeederError::SignatureMissing);
-                    }
-                }
-
+            .and_then(move |response_body| result(Self::parse_seed_list(&response_body, seed_list.public_key())))
+            .map(move |seed_addresses| {
                 // Notify the Seeds event with the array of seed addresses
                 notifier.lock().notify(PeerAddressSeederEvent::Seeds(seed_addresses));
-                ok(())
             })
             .map_err(move |err| warn!("Failed to retrieve seed list from {}: {}", seed_list_url, err));
 
@@ -157,6 +101,68 @@ impl PeerAddressSeeder {
         self.notifier.lock().notify(PeerAddressSeederEvent::End);
     }
 
+    /// Parses a seed list: one seed peer URI per line, followed by the signature over those
+    /// lines. The signature is only checked if `public_key` is given.
+    pub fn parse_seed_list(response_body: &[u8], public_key: &Option<PublicKey>) -> Result<Vec<PeerAddress>, PeerAddressSeederError> {
+        let mut signature = None;
+        let mut seed_addresses = Vec::new();
+
+        // Process each line of the seed list
+        for line in response_body.lines() {
+            // Abort if the line can't be read properly
+            if let Err(e) = line {
+                return Err(PeerAddressSeederError::IoError(e));
+            }
+            let line = line.expect("Validated this above");
+
+            // The signature should always be in the last line
+            if signature.is_some() {
+                return Err(PeerAddressSeederError::SignatureNotInLastLine);
+            }
+
+            // Ignore comments and empty lines
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Try to parse the line as a seed address, if that fails, fallback to try to parse it as a signature
+            // TODO: Should we fail if this step fails (i.e. if there is a non-comment/non-empty line that is not
+            // a seed address neither a signature)?
+            match PeerUri::from_str(line) {
+                Ok(seed_address) => {
+                    match seed_address.as_seed_peer_address() {
+                        Ok(peer_address) => seed_addresses.push(peer_address),
+                        Err(e) => return Err(PeerAddressSeederError::PeerUriParsingError(e)),
+                    }
+                },
+                _ => signature = Signature::from_hex(line).ok(),
+            }
+        }
+
+        // Error out if we couldn't find any parseable seed address
+        if seed_addresses.is_empty() {
+            return Err(PeerAddressSeederError::EmptySeedAddresses);
+        }
+
+        // Verify the signature if a public key was provided for this seed list
+        if let Some(public_key) = public_key {
+            if let Some(signature) = signature {
+                // Serialize the seed addresses for signature verification
+                let data = seed_addresses.iter().filter_map(PeerAddress::to_seed_string).collect::<Vec<String>>().join("\n");
+                let data = data.as_bytes();
+
+                if !public_key.verify(&signature, data) {
+                    return Err(PeerAddressSeederError::SignatureVerificationFailed)
+                }
+            } else { // No signature was found on the seed list file
+                return Err(PeerAddressSeederError::SignatureMissing);
+            }
+        }
+
+        Ok(seed_addresses)
+    }
+
     // Asynchronously fetches a seed list from a remote location
     fn fetch(url: Url) -> impl Future<Item=Chunk, Error=PeerAddressSeederError> {
         Client::new().get(url).send()
@@ -23,6 +23,11 @@ pub struct PeerAddressInfo {
 
     pub close_types: HashMap<CloseType, usize>,
     pub added_by: HashSet<Arc<NetAddress>>,
+
+    /// The address group (see `address_group`) of the peer that first announced this address.
+    pub source_group: Option<NetAddress>,
+    /// The address group this address belonged to when it was added.
+    pub address_group: Option<NetAddress>,
 }
 
 impl PeerAddressInfo {
@@ -37,13 +42,15 @@ impl PeerAddressInfo {
             ban_backoff: super::peer_address_book::INITIAL_FAILED_BACKOFF,
             close_types: HashMap::new(),
             added_by: HashSet::new(),
+            source_group: None,
+            address_group: None,
         }
     }
 
     pub fn max_failed_attempts(&self) -> u32 {
         match self.peer_address.protocol() {
             Protocol::Rtc => super::peer_address_book::MAX_FAILED_ATTEMPTS_RTC,
-            Protocol::Ws | Protocol::Wss => super::peer_address_book::MAX_FAILED_ATTEMPTS_WS,
+            Protocol::Ws | Protocol::Wss | Protocol::Noise => super::peer_address_book::MAX_FAILED_ATTEMPTS_WS,
             _ => 0
         }
     }
@@ -66,7 +73,8 @@ impl PeerAddressInfo {
     }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[repr(u8)]
 pub enum PeerAddressState {
     New = 1,
     Established = 2,
@@ -0,0 +1,220 @@
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError};
+use database::{Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction};
+use database::cursor::ReadCursor;
+use network_primitives::address::net_address::NetAddress;
+use network_primitives::address::peer_address::PeerAddress;
+use network_primitives::protocol::Protocol;
+use utils::time::{systemtime_to_timestamp, timestamp_to_systemtime};
+
+use super::peer_address_state::{PeerAddressInfo, PeerAddressState};
+
+/// The part of a `PeerAddressInfo` that survives a restart.
+/// All points in time are stored as unix timestamps in milliseconds.
+#[derive(Clone, Debug, Serialize)]
+pub struct StoredPeerAddressInfo {
+    pub peer_address: PeerAddress,
+    pub state: PeerAddressState,
+    pub last_connected: Option<u64>,
+    pub failed_attempts: u32,
+    pub banned_until: Option<u64>,
+    pub ban_backoff: u64,
+    /// The source group is kept, so that restored addresses still count against the limit
+    /// of the peer that announced them.
+    pub source_group: Option<NetAddress>,
+}
+
+impl Deserialize for StoredPeerAddressInfo {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        Ok(StoredPeerAddressInfo {
+            peer_address: Deserialize::deserialize(reader)?,
+            state: Deserialize::deserialize(reader)?,
+            last_connected: Deserialize::deserialize(reader)?,
+            failed_attempts: Deserialize::deserialize(reader)?,
+            banned_until: Deserialize::deserialize(reader)?,
+            ban_backoff: Deserialize::deserialize(reader)?,
+            // Entries stored by earlier versions end here.
+            source_group: match Deserialize::deserialize(reader) {
+                Ok(source_group) => source_group,
+                Err(SerializingError::IoError(io::ErrorKind::UnexpectedEof, _)) => None,
+                Err(e) => return Err(e),
+            },
+        })
+    }
+}
+
+impl StoredPeerAddressInfo {
+    /// Returns `None` for addresses that are not worth persisting:
+    /// seed addresses are re-added on every start, and RTC and dumb addresses
+    /// can't be reached without the connection they were learned from.
+    pub fn from_info(info: &PeerAddressInfo) -> Option<Self> {
+        if info.peer_address.is_seed() {
+            return None;
+        }
+        match info.peer_address.protocol() {
+            Protocol::Ws | Protocol::Wss | Protocol::Noise => {},
+            Protocol::Rtc | Protocol::Dumb => return None,
+        }
+
+        Some(StoredPeerAddressInfo {
+            peer_address: info.peer_address.as_ref().clone(),
+            state: info.state,
+            last_connected: info.last_connected.map(systemtime_to_timestamp),
+            failed_attempts: info.failed_attempts,
+            banned_until: info.banned_until.map(instant_to_timestamp),
+            ban_backoff: info.ban_backoff.as_secs() * 1000 + u64::from(info.ban_backoff.subsec_millis()),
+            source_group: info.source_group,
+        })
+    }
+
+    /// Restores the address info. Returns `None` if the entry is stale, i.e. the address
+    /// exceeds its maximum age or it was banned and the ban has expired.
+    pub fn into_info(self) -> Option<PeerAddressInfo> {
+        let banned_until = self.banned_until.map(timestamp_to_instant);
+        let ban_active = banned_until.map(|banned_until| banned_until > Instant::now()).unwrap_or(false);
+
+        let state = match self.state {
+            PeerAddressState::Banned if !ban_active => return None,
+            PeerAddressState::Banned => PeerAddressState::Banned,
+            _ if self.peer_address.exceeds_age() => return None,
+            // We are not connected to anyone after a restart.
+            PeerAddressState::Established => PeerAddressState::Tried,
+            state => state,
+        };
+
+        let mut info = PeerAddressInfo::new(Arc::new(self.peer_address));
+        info.state = state;
+        info.last_connected = self.last_connected.map(timestamp_to_systemtime);
+        info.failed_attempts = self.failed_attempts;
+        info.banned_until = banned_until;
+        info.ban_backoff = Duration::from_millis(self.ban_backoff);
+        info.source_group = self.source_group;
+        Some(info)
+    }
+}
+
+impl IntoDatabaseValue for StoredPeerAddressInfo {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for StoredPeerAddressInfo {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+/// Persists the peer address book in the node's database.
+/// Entries are keyed by the hex encoded peer id.
+#[derive(Debug)]
+pub struct PeerAddressStore {
+    env: Environment,
+    peer_address_db: Database,
+    anchor_db: Database,
+}
+
+impl PeerAddressStore {
+    const PEER_ADDRESS_DB_NAME: &'static str = "PeerAddressBook";
+    const ANCHOR_DB_NAME: &'static str = "PeerAnchors";
+
+    pub fn new(env: Environment) -> Self {
+        let peer_address_db = env.open_database(Self::PEER_ADDRESS_DB_NAME.to_string());
+        let anchor_db = env.open_database(Self::ANCHOR_DB_NAME.to_string());
+        PeerAddressStore { env, peer_address_db, anchor_db }
+    }
+
+    pub fn load(&self) -> Vec<StoredPeerAddressInfo> {
+        self.load_from(&self.peer_address_db)
+    }
+
+    /// Replaces all stored entries with `infos`.
+    pub fn store(&self, infos: &[StoredPeerAddressInfo]) {
+        self.store_into(&self.peer_address_db, infos);
+    }
+
+    /// Writes `changed` and removes the entries with the given keys, leaving all other entries.
+    /// Keys are hex encoded peer ids.
+    pub fn update(&self, changed: &[StoredPeerAddressInfo], removed: &[String]) {
+        let mut txn = WriteTransaction::new(&self.env);
+        for key in removed {
+            txn.remove(&self.peer_address_db, key.as_str());
+        }
+        for info in changed {
+            txn.put_reserve(&self.peer_address_db, info.peer_address.peer_id.to_hex().as_str(), info);
+        }
+        txn.commit();
+    }
+
+    /// Loads the anchor connections of the last session.
+    pub fn load_anchors(&self) -> Vec<StoredPeerAddressInfo> {
+        self.load_from(&self.anchor_db)
+    }
+
+    /// Replaces the stored anchor connections with `infos`.
+    pub fn store_anchors(&self, infos: &[StoredPeerAddressInfo]) {
+        self.store_into(&self.anchor_db, infos);
+    }
+
+    fn load_from(&self, db: &Database) -> Vec<StoredPeerAddressInfo> {
+        let txn = ReadTransaction::new(&self.env);
+        let mut cursor = txn.cursor(db);
+        let mut infos = Vec::new();
+
+        let mut entry: Option<(String, StoredPeerAddressInfo)> = cursor.first();
+        while let Some((_, info)) = entry {
+            infos.push(info);
+            entry = cursor.next();
+        }
+
+        infos
+    }
+
+    fn store_into(&self, db: &Database, infos: &[StoredPeerAddressInfo]) {
+        let mut txn = WriteTransaction::new(&self.env);
+
+        let mut keys = Vec::new();
+        {
+            let mut cursor = txn.cursor(db);
+            let mut entry: Option<(String, StoredPeerAddressInfo)> = cursor.first();
+            while let Some((key, _)) = entry {
+                keys.push(key);
+                entry = cursor.next();
+            }
+        }
+        for key in keys {
+            txn.remove(db, key.as_str());
+        }
+
+        for info in infos {
+            txn.put_reserve(db, info.peer_address.peer_id.to_hex().as_str(), info);
+        }
+        txn.commit();
+    }
+}
+
+fn instant_to_timestamp(instant: Instant) -> u64 {
+    let now = Instant::now();
+    let time = if instant >= now {
+        SystemTime::now() + (instant - now)
+    } else {
+        SystemTime::now() - (now - instant)
+    };
+    systemtime_to_timestamp(time)
+}
+
+fn timestamp_to_instant(timestamp: u64) -> Instant {
+    let time = timestamp_to_systemtime(timestamp);
+    match time.duration_since(SystemTime::now()) {
+        Ok(duration) => Instant::now() + duration,
+        Err(e) => Instant::now().checked_sub(e.duration()).unwrap_or_else(Instant::now),
+    }
+}
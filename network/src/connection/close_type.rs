@@ -45,6 +45,9 @@ pub enum CloseType {
 
     GetEpochTransactionsTimeout = 41,
 
+    FeelerConnection = 42,
+    SeedServerConnectionExpired = 43,
+
     ManualPeerDisconnect = 90,
 
     // Ban Close Types
@@ -7,6 +7,7 @@ use network_primitives::address::peer_address::PeerAddress;
 
 use crate::connection::network_agent::NetworkAgent;
 use crate::connection::NetworkConnection;
+use crate::connection::traffic::TrafficCounter;
 use crate::Peer;
 use crate::peer_channel::PeerChannel;
 use crate::websocket::websocket_connector::ConnectionHandle;
@@ -31,6 +32,8 @@ pub struct ConnectionInfo<B: AbstractBlockchain + 'static> {
     connection_handle: Option<Arc<ConnectionHandle>>,
     established_since: Option<Instant>,
     statistics: ConnectionStatistics,
+    traffic: Option<Arc<TrafficCounter>>,
+    feeler: bool,
 }
 
 impl<B: AbstractBlockchain + 'static> ConnectionInfo<B> {
@@ -45,6 +48,8 @@ impl<B: AbstractBlockchain + 'static> ConnectionInfo<B> {
             connection_handle: None,
             established_since: None,
             statistics: ConnectionStatistics::new(),
+            traffic: None,
+            feeler: false,
         }
     }
 
@@ -76,9 +81,15 @@ impl<B: AbstractBlockchain + 'static> ConnectionInfo<B> {
     pub fn connection_handle(&self) -> Option<&Arc<ConnectionHandle>> { self.connection_handle.as_ref() }
     pub fn age_established(&self) -> Duration { self.established_since.expect("No peer has been set yet").elapsed() }
     pub fn statistics(&self) -> &ConnectionStatistics { &self.statistics }
+    /// The bytes sent and received over this connection. Kept after the connection is closed.
+    pub fn traffic(&self) -> Option<&Arc<TrafficCounter>> { self.traffic.as_ref() }
+    /// Feeler connections only test whether an address is reachable and are closed after the handshake.
+    pub fn is_feeler(&self) -> bool { self.feeler }
 
     pub fn set_peer_address(&mut self, peer_address: Arc<PeerAddress>) { self.peer_address = Some(peer_address) }
+    pub fn set_feeler(&mut self, feeler: bool) { self.feeler = feeler; }
     pub fn set_network_connection(&mut self, network_connection: NetworkConnection) {
+        self.traffic = Some(Arc::clone(network_connection.traffic()));
         self.network_connection = Some(network_connection);
         self.state = ConnectionState::Connected;
     }
@@ -23,6 +23,7 @@ use crate::connection::{
     network_agent::{NetworkAgent, NetworkAgentEvent},
     NetworkConnection,
     signal_processor::SignalProcessor,
+    traffic::{TrafficCounter, TrafficLimits},
 };
 use crate::error::Error;
 use crate::Network;
@@ -54,6 +55,7 @@ pub struct ConnectionPoolState<B: AbstractBlockchain + 'static> {
 
     pub peer_count_ws: usize,
     pub peer_count_wss: usize,
+    pub peer_count_noise: usize,
     peer_count_rtc: usize,
     peer_count_dumb: usize,
 
@@ -160,7 +162,7 @@ impl<B: AbstractBlockchain + 'static> ConnectionPoolState<B> {
     /// Total peer count.
     #[inline]
     pub fn peer_count(&self) -> usize {
-        self.peer_count_ws + self.peer_count_wss + self.peer_count_rtc + self.peer_count_dumb
+        self.peer_count_ws + self.peer_count_wss + self.peer_count_noise + self.peer_count_rtc + self.peer_count_dumb
     }
 
     /// Add a new connection to the connection pool.
@@ -285,6 +287,16 @@ impl<B: AbstractBlockchain + 'static> ConnectionPoolState<B> {
         }
     }
 
+    /// Lifts a ban on an IP address. Returns whether the address was banned.
+    fn unban_ip(&mut self, net_address: &NetAddress) -> bool {
+        let banned_address = if net_address.get_type() == NetAddressType::IPv4 {
+            *net_address
+        } else {
+            net_address.subnet(64)
+        };
+        self.banned_ips.remove(&banned_address).is_some()
+    }
+
     /// Checks whether an IP address is banned.
     fn is_ip_banned(&self, net_address: &NetAddress) -> bool {
         !net_address.is_pseudo() && self.banned_ips.contains_key(net_address)
@@ -311,6 +323,7 @@ impl<B: AbstractBlockchain + 'static> ConnectionPoolState<B> {
         match peer_address.protocol() {
             Protocol::Wss => update_checked!(self.peer_count_wss, update),
             Protocol::Ws => update_checked!(self.peer_count_ws, update),
+            Protocol::Noise => update_checked!(self.peer_count_noise, update),
             Protocol::Rtc => update_checked!(self.peer_count_rtc, update),
             Protocol::Dumb => update_checked!(self.peer_count_dumb, update),
         }
@@ -325,7 +338,7 @@ impl<B: AbstractBlockchain + 'static> ConnectionPoolState<B> {
 
         if network_connection.outbound() {
             update_checked!(self.peer_count_outbound, update);
-            if peer_address.services.is_full_node() && (peer_address.protocol() == Protocol::Wss || peer_address.protocol() == Protocol::Ws) {
+            if peer_address.services.is_full_node() && peer_address.protocol().is_connectable() {
                 update_checked!(self.peer_count_full_ws_outbound, update);
             }
         }
@@ -386,6 +399,7 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
 
                 peer_count_ws: 0,
                 peer_count_wss: 0,
+                peer_count_noise: 0,
                 peer_count_rtc: 0,
                 peer_count_dumb: 0,
 
@@ -445,6 +459,16 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
 
     /// Initiates a outbound connection.
     pub fn connect_outbound(&self, peer_address: Arc<PeerAddress>) -> bool {
+        self.connect(peer_address, false)
+    }
+
+    /// Opens a short-lived connection to test whether `peer_address` is reachable.
+    /// The connection is closed as soon as the handshake completed.
+    pub fn connect_feeler(&self, peer_address: Arc<PeerAddress>) -> bool {
+        self.connect(peer_address, true)
+    }
+
+    fn connect(&self, peer_address: Arc<PeerAddress>, feeler: bool) -> bool {
         let _guard = self.change_lock.lock();
 
         // All checks in one step.
@@ -466,7 +490,9 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
         // Create fresh ConnectionInfo instance.
         let mut state = self.state.write();
         let connection_id = state.add(ConnectionInfo::outbound(peer_address.clone()));
-        state.connections.get_mut(connection_id).unwrap().set_connection_handle(handle);
+        let info = state.connections.get_mut(connection_id).unwrap();
+        info.set_connection_handle(handle);
+        info.set_feeler(feeler);
 
         state.connecting_count += 1;
 
@@ -700,6 +726,12 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
             if let Some(info) = state.connections.get(connection_id) {
                 let network_connection = info.network_connection().unwrap();
 
+                // The peer is reachable, which is all a feeler connection wants to know.
+                if info.is_feeler() {
+                    Self::close(info.network_connection(), CloseType::FeelerConnection);
+                    return;
+                }
+
                 if network_connection.inbound() {
                     // Re-check allowInboundExchange as it might have changed.
                     if state.peer_count() >= network_primitives::PEER_COUNT_MAX && !state.allow_inbound_exchange {
@@ -883,7 +915,9 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
 
                             debug!("Connection #{} to {} closed pre-handshake: {:?}", connection_id, info.peer_address().unwrap(), ty);
                             // Only sets a timer and won't reenter connection pool.
-                            self.notifier.read().notify(ConnectionPoolEvent::ConnectError(info.peer_address().expect("PeerAddress not set").clone(), ty));
+                            if ty != CloseType::FeelerConnection {
+                                self.notifier.read().notify(ConnectionPoolEvent::ConnectError(info.peer_address().expect("PeerAddress not set").clone(), ty));
+                            }
                         },
                         _ => unreachable!(format!("Invalid state, closing connection #{} with network connection not set", connection_id)),
                     }
@@ -924,6 +958,21 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
         self.state.read().peer_count_outbound
     }
 
+    /// Lifts a ban on an IP address. Returns whether the address was banned.
+    pub fn unban_ip(&self, net_address: &NetAddress) -> bool {
+        let _guard = self.change_lock.lock();
+        self.state.write().unban_ip(net_address)
+    }
+
+    /// The bytes sent and received over all connections.
+    pub fn traffic(&self) -> &TrafficCounter {
+        self.websocket_connector.traffic_shaper().counter()
+    }
+
+    pub fn traffic_limits(&self) -> TrafficLimits {
+        self.websocket_connector.traffic_shaper().limits()
+    }
+
     pub fn allow_inbound_exchange(&self) -> bool {
         self.state.read().allow_inbound_exchange
     }
@@ -980,6 +1029,7 @@ impl<B: AbstractBlockchain + 'static> ConnectionPool<B> {
         match peer_address.protocol() {
             Protocol::Wss => {},
             Protocol::Ws => {},
+            Protocol::Noise => {},
             _ => {
                 error!("Cannot connect to {} - unsupported protocol", peer_address);
                 return false;
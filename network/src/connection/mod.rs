@@ -3,6 +3,7 @@ pub mod connection_info;
 pub mod close_type;
 pub mod network_connection;
 pub mod network_agent;
+pub mod traffic;
 mod signal_processor;
 
 pub use self::network_connection::*;
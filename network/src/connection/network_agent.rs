@@ -15,7 +15,7 @@ use network_messages::*;
 use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::address::PeerId;
 use network_primitives::networks::NetworkInfo;
-use network_primitives::protocol::Protocol;
+use network_primitives::protocol::{Protocol, ProtocolFlags};
 use network_primitives::version;
 use utils::observer::{Notifier, weak_listener, weak_passthru_listener};
 use utils::rate_limit::RateLimit;
@@ -161,16 +161,28 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
             return;
         }
 
+        // Peers that don't know about Noise can't parse a Noise peer address. Unless the connection
+        // itself uses Noise, wait for the peer's capabilities before announcing ours.
+        if !self.version_received && self.network_config.protocol() == Protocol::Noise && !self.peer_supports_noise() {
+            self.set_version_timeout();
+            return;
+        }
+
         // Kick off the handshake by telling the peer our version, network address & blockchain head hash.
         // Firefox sends the data-channel-open event too early, so sending the version message might fail.
         // Try again in this case.
         let network_info = NetworkInfo::from_network_id(self.blockchain.network_id());
+        let mut capabilities = Capabilities::COMPRESSION | Capabilities::NOISE;
+        if self.blockchain.network_id().is_albatross() {
+            capabilities |= Capabilities::COMPACT_BLOCKS;
+        }
         let msg = VersionMessage::new(
-            self.network_config.peer_address(),
+            self.own_peer_address(),
             self.blockchain.head_hash(),
             network_info.genesis_hash().clone(),
             self.challenge_nonce.clone(),
-            self.network_config.user_agent().clone());
+            self.network_config.user_agent().clone(),
+            capabilities);
         if self.channel.send(msg).is_err() {
             self.version_attempts += 1;
             if self.version_attempts >= Self::VERSION_ATTEMPTS_MAX || self.channel.closed() {
@@ -191,14 +203,7 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         // Drop the peer if it doesn't send us a version message.
         // Only do this if we haven't received the peer's version message already.
         if !self.version_received {
-            // TODO Should we ban instead?
-            let weak = self.self_weak.clone();
-            self.timers.set_delay(NetworkAgentTimer::Version, move || {
-                let arc = upgrade_weak!(weak);
-                let agent = arc.read();
-                agent.timers.clear_delay(&NetworkAgentTimer::Version);
-                agent.channel.close(CloseType::VersionTimeout);
-            }, Self::HANDSHAKE_TIMEOUT);
+            self.set_version_timeout();
         } else if self.peer_address_verified {
             self.send_ver_ack();
         }
@@ -212,6 +217,34 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         }, Self::HANDSHAKE_TIMEOUT);
     }
 
+    fn set_version_timeout(&self) {
+        // TODO Should we ban instead?
+        let weak = self.self_weak.clone();
+        self.timers.set_delay(NetworkAgentTimer::Version, move || {
+            let arc = upgrade_weak!(weak);
+            let agent = arc.read();
+            agent.timers.clear_delay(&NetworkAgentTimer::Version);
+            agent.channel.close(CloseType::VersionTimeout);
+        }, Self::HANDSHAKE_TIMEOUT);
+    }
+
+    /// Whether the peer can parse Noise peer addresses. This is the case if we are connected via
+    /// Noise or if the peer advertised `Capabilities::NOISE`.
+    fn peer_supports_noise(&self) -> bool {
+        self.channel.address_info.authenticated_public_key().is_some()
+            || self.peer.as_ref().map(|peer| peer.capabilities.contains(Capabilities::NOISE)).unwrap_or(false)
+    }
+
+    /// Our peer address as announced to this peer. Peers that can't parse our Noise address
+    /// get a dumb address instead, they can't connect to us anyway.
+    fn own_peer_address(&self) -> PeerAddress {
+        if self.network_config.protocol() == Protocol::Noise && !self.peer_supports_noise() {
+            self.network_config.dumb_peer_address()
+        } else {
+            self.network_config.peer_address()
+        }
+    }
+
     fn send_ver_ack(&mut self) {
         assert!(self.peer_address_verified);
         assert!(self.peer_challenge_nonce.is_some());
@@ -271,6 +304,14 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
 
         // TODO Check services?
 
+        // On authenticated transports, the peerAddress must belong to the key the peer proved to hold.
+        if let Some(public_key) = self.channel.address_info.authenticated_public_key() {
+            if msg.peer_address.public_key != public_key {
+                self.channel.close(CloseType::UnexpectedPeerAddressInVersionMessage);
+                return;
+            }
+        }
+
         // Check that the given peerAddress matches the one we expect.
         // In case of inbound WebSocket connections, this is the first time we
         // see the remote peer's peerAddress.
@@ -306,12 +347,18 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
             msg.version,
             msg.head_hash.clone(),
             peer_address.timestamp as i64 - systemtime_to_timestamp(now) as i64,
-            msg.user_agent
+            msg.user_agent,
+            msg.capabilities,
         ));
 
         self.peer_challenge_nonce = Some(msg.challenge_nonce.clone());
         self.version_received = true;
 
+        // Peers that don't advertise compression only ever get uncompressed messages.
+        if msg.capabilities.contains(Capabilities::COMPRESSION) {
+            self.channel.enable_compression();
+        }
+
         // Tell listeners that we received this peer's version information.
         // Listeners registered to this event might close the connection to this peer.
         self.notifier.notify(NetworkAgentEvent::Version(UniquePtr::new(self.peer.as_ref().unwrap())));
@@ -395,7 +442,7 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         self.timers.set_interval(NetworkAgentTimer::AnnounceAddr, move || {
             let arc = upgrade_weak!(weak);
             let agent = arc.read();
-            agent.channel.send_or_close(AddrMessage::new(vec![agent.network_config.peer_address()]));
+            agent.channel.send_or_close(AddrMessage::new(vec![agent.own_peer_address()]));
         }, Self::ANNOUNCE_ADDR_INTERVAL);
 
         // Tell listeners that the handshake with this peer succeeded.
@@ -462,7 +509,7 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
                 return;
             }
 
-            if (address.protocol() == Protocol::Ws || address.protocol() == Protocol::Wss) && !address.is_globally_reachable(true) {
+            if address.protocol().is_connectable() && !address.is_globally_reachable(true) {
                 self.channel.close(CloseType::AddrNotGloballyReachable);
                 return;
             }
@@ -495,9 +542,14 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         }
 
         // Find addresses that match the given protocolMask & serviceMask.
+        // Peers that don't know about Noise can't parse Noise addresses.
+        let mut protocol_mask = msg.protocol_mask;
+        if !self.peer_supports_noise() {
+            protocol_mask.remove(ProtocolFlags::NOISE);
+        }
         let num_results = cmp::min(msg.max_results.unwrap_or(Self::MAX_ADDR_PER_REQUEST), Self::MAX_ADDR_PER_REQUEST);
         let addresses = self.addresses.query(
-            msg.protocol_mask,
+            protocol_mask,
             msg.service_mask,
             num_results
         );
@@ -2,23 +2,24 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use futures::prelude::*;
-use futures::stream::Forward;
 use futures::sync::mpsc::*;
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 
+use keys::PublicKey;
 use network_primitives::address::net_address::NetAddress;
 use network_primitives::address::peer_address::PeerAddress;
 use utils::observer::PassThroughNotifier;
 use utils::unique_id::UniqueId;
 
 use crate::connection::close_type::CloseType;
+use crate::connection::traffic::{ShapedForward, ShapedStream, TrafficCounter, TrafficShaper};
 #[cfg(feature = "metrics")]
 use crate::network_metrics::NetworkMetrics;
 use crate::peer_channel::PeerSink;
 use crate::peer_channel::PeerStream;
 use crate::peer_channel::PeerStreamEvent;
-use crate::websocket::{Message, SharedNimiqMessageStream};
+use crate::websocket::SharedNimiqMessageStream;
 use std::fmt;
 
 #[derive(Debug, Clone, Default)]
@@ -62,22 +63,31 @@ pub struct NetworkConnection {
     address_info: AddressInfo,
     unique_id: UniqueId,
     closed_flag: ClosedFlag,
+    traffic: Arc<TrafficCounter>,
     pub notifier: Arc<RwLock<PassThroughNotifier<'static, PeerStreamEvent>>>,
 }
 
 impl NetworkConnection {
-    pub fn new_connection_setup(stream: SharedNimiqMessageStream, address_info: AddressInfo) -> (Self, ProcessConnectionFuture) {
+    pub fn new_connection_setup(stream: SharedNimiqMessageStream, address_info: AddressInfo, traffic_shaper: Arc<TrafficShaper>) -> (Self, ProcessConnectionFuture) {
         let id = UniqueId::new();
         let closed_flag = ClosedFlag::new();
-        let (tx, rx) = unbounded(); // TODO: use bounded channel?
+        let traffic = Arc::new(TrafficCounter::new());
+        // Priority messages bypass the upload limit and overtake queued bulk messages.
+        let (priority_tx, priority_rx) = unbounded(); // TODO: use bounded channel?
+        let (bulk_tx, bulk_rx) = unbounded();
 
-        let forward_future = rx.forward(stream.clone());
+        let forward_future = ShapedForward::new(priority_rx, bulk_rx, stream.clone(), Arc::clone(&traffic), Arc::clone(&traffic_shaper));
 
         let notifier = Arc::new(RwLock::new(PassThroughNotifier::new()));
-        let peer_stream = PeerStream::new(stream.clone(), notifier.clone(), closed_flag.clone());
+        let shaped_stream = ShapedStream::new(stream.clone(), Arc::clone(&traffic), traffic_shaper);
+        let peer_stream = PeerStream::new(shaped_stream, notifier.clone(), closed_flag.clone());
         let process_connection = ProcessConnectionFuture::new(peer_stream, forward_future, id);
 
-        let peer_sink = PeerSink::new(tx, id, closed_flag.clone());
+        let peer_sink = PeerSink::new(priority_tx, bulk_tx, id, closed_flag.clone());
+
+        if let Some(public_key) = stream.remote_public_key() {
+            address_info.set_authenticated_public_key(*public_key);
+        }
 
         let network_connection = NetworkConnection {
             peer_sink,
@@ -85,6 +95,7 @@ impl NetworkConnection {
             address_info,
             notifier,
             closed_flag,
+            traffic,
             unique_id: id,
         };
 
@@ -121,6 +132,10 @@ impl NetworkConnection {
     }
 
     pub fn peer_sink(&self) -> PeerSink { self.peer_sink.clone() }
+    /// Whether messages to the peer may be compressed.
+    pub fn compression(&self) -> Arc<AtomicBool> { Arc::clone(self.stream.compression()) }
+    /// The bytes sent and received over this connection.
+    pub fn traffic(&self) -> &Arc<TrafficCounter> { &self.traffic }
     pub fn address_info(&self) -> AddressInfo {
         self.address_info.clone()
     }
@@ -136,7 +151,7 @@ pub struct ProcessConnectionFuture {
 }
 
 impl ProcessConnectionFuture {
-    pub fn new(peer_stream: PeerStream, forward_future: Forward<UnboundedReceiver<Message>, SharedNimiqMessageStream>, _id: UniqueId) -> Self {
+    pub fn new(peer_stream: PeerStream, forward_future: ShapedForward, _id: UniqueId) -> Self {
         // `select` required Item/Error to be the same, that's why we need to map them both to ().
         // TODO We're discarding any errors here, especially those coming from the forward future.
         // Results by the peer_stream have been processes already.
@@ -165,6 +180,7 @@ pub struct AddressInfo {
 struct AddressInfoInternal {
     pub peer_address: RwLock<Option<Arc<PeerAddress>>>,
     pub net_address: RwLock<Option<Arc<NetAddress>>>,
+    pub authenticated_public_key: RwLock<Option<PublicKey>>,
 }
 
 impl Clone for AddressInfo {
@@ -181,6 +197,7 @@ impl AddressInfo {
             inner: Arc::new(AddressInfoInternal {
                 peer_address: RwLock::new(peer_address),
                 net_address: RwLock::new(net_address),
+                authenticated_public_key: RwLock::new(None),
             })
         }
     }
@@ -197,6 +214,13 @@ impl AddressInfo {
     pub fn set_net_address(&self, net_address: Arc<NetAddress>) {
         self.inner.net_address.write().replace(net_address);
     }
+    /// The peer key the remote node proved to hold during the transport handshake, if any.
+    pub fn authenticated_public_key(&self) -> Option<PublicKey> {
+        *self.inner.authenticated_public_key.read()
+    }
+    pub fn set_authenticated_public_key(&self, public_key: PublicKey) {
+        self.inner.authenticated_public_key.write().replace(public_key);
+    }
 }
 
 impl fmt::Display for AddressInfo {
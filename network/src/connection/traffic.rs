@@ -0,0 +1,432 @@
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use futures::sync::mpsc::UnboundedReceiver;
+use futures::try_ready;
+use parking_lot::{Mutex, MutexGuard};
+use tokio::timer::Delay;
+
+use network_messages::{Message, MessageType};
+
+use crate::websocket::{Error, Message as WebSocketMessage, SharedNimiqMessageStream};
+
+/// Upload and download limits in bytes per second. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficLimits {
+    pub upload: Option<u32>,
+    pub download: Option<u32>,
+}
+
+/// Counts the bytes sent and received, either over a single connection or over all connections.
+#[derive(Debug, Default)]
+pub struct TrafficCounter {
+    bytes_sent: AtomicUsize,
+    bytes_received: AtomicUsize,
+}
+
+impl TrafficCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn note_bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn note_bytes_received(&self, bytes: usize) {
+        self.bytes_received.fetch_add(bytes, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn bytes_sent(&self) -> usize {
+        self.bytes_sent.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn bytes_received(&self) -> usize {
+        self.bytes_received.load(Ordering::Acquire)
+    }
+}
+
+/// A token bucket that refills at `rate` bytes per second and holds at most one second worth of bytes.
+///
+/// A message is let through as long as the bucket is not empty, even if it is larger than
+/// the remaining tokens. The resulting debt has to be paid off before the next message passes.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: u32,
+    state: Mutex<RateLimiterState>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u32) -> Self {
+        assert!(rate > 0, "Rate limit must be positive");
+        RateLimiter {
+            rate,
+            state: Mutex::new(RateLimiterState {
+                tokens: f64::from(rate),
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Takes `bytes` tokens from the bucket if it is not empty.
+    /// Otherwise nothing is taken and the time until the bucket is refilled is returned.
+    pub fn try_consume(&self, bytes: usize) -> Result<(), Duration> {
+        let mut state = self.refill();
+        if state.tokens <= 0.0 {
+            let wait_secs = (-state.tokens + 1.0) / f64::from(self.rate);
+            return Err(Duration::from_micros((wait_secs * 1_000_000.0).ceil() as u64));
+        }
+        state.tokens -= bytes as f64;
+        Ok(())
+    }
+
+    /// Takes `bytes` tokens from the bucket, regardless of how many are left.
+    /// Used for traffic that must not be delayed, but still counts against the limit.
+    pub fn force_consume(&self, bytes: usize) {
+        self.refill().tokens -= bytes as f64;
+    }
+
+    fn refill(&self) -> MutexGuard<RateLimiterState> {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill);
+        let elapsed_secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_micros()) / 1_000_000.0;
+        state.tokens = (state.tokens + elapsed_secs * f64::from(self.rate)).min(f64::from(self.rate));
+        state.last_refill = now;
+        state
+    }
+}
+
+/// Global traffic accounting and limits, shared by all connections of a node.
+#[derive(Debug, Default)]
+pub struct TrafficShaper {
+    counter: TrafficCounter,
+    upload: Option<RateLimiter>,
+    download: Option<RateLimiter>,
+}
+
+impl TrafficShaper {
+    pub fn new(limits: TrafficLimits) -> Self {
+        TrafficShaper {
+            counter: TrafficCounter::new(),
+            upload: limits.upload.map(RateLimiter::new),
+            download: limits.download.map(RateLimiter::new),
+        }
+    }
+
+    pub fn counter(&self) -> &TrafficCounter {
+        &self.counter
+    }
+
+    pub fn limits(&self) -> TrafficLimits {
+        TrafficLimits {
+            upload: self.upload.as_ref().map(RateLimiter::rate),
+            download: self.download.as_ref().map(RateLimiter::rate),
+        }
+    }
+
+    fn acquire(limiter: &Option<RateLimiter>, bytes: usize, priority: bool) -> Result<(), Duration> {
+        match limiter {
+            Some(limiter) if priority => {
+                limiter.force_consume(bytes);
+                Ok(())
+            },
+            Some(limiter) => limiter.try_consume(bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `Err` with the time to wait if the global upload limit is exhausted.
+    pub fn acquire_upload(&self, bytes: usize, priority: bool) -> Result<(), Duration> {
+        Self::acquire(&self.upload, bytes, priority)
+    }
+
+    /// Returns `Err` with the time to wait if the global download limit is exhausted.
+    pub fn acquire_download(&self, bytes: usize, priority: bool) -> Result<(), Duration> {
+        Self::acquire(&self.download, bytes, priority)
+    }
+}
+
+/// Messages that keep consensus going or control the connection.
+/// These are never held back by the traffic limits.
+///
+/// Blocks are not among them, since syncing peers request them in bulk. Newly relayed blocks
+/// are sent with `PeerSink::send_priority` instead.
+pub fn is_priority(msg: &Message) -> bool {
+    match msg.ty() {
+        MessageType::Ping
+        | MessageType::Pong
+        | MessageType::Reject
+        | MessageType::ViewChange
+        | MessageType::ViewChangeProof
+        | MessageType::ForkProof
+        | MessageType::ValidatorInfo
+        | MessageType::PbftProposal
+        | MessageType::PbftPrepare
+        | MessageType::PbftCommit => true,
+        _ => false,
+    }
+}
+
+fn is_priority_frame(msg: &WebSocketMessage) -> bool {
+    match msg {
+        WebSocketMessage::Message(msg) => is_priority(msg),
+        WebSocketMessage::Resume(_, _) => false,
+        WebSocketMessage::Close(_) => true,
+    }
+}
+
+/// The number of bytes a message takes up on the wire, ignoring the WebSocket framing.
+pub fn message_size(msg: &WebSocketMessage) -> usize {
+    match msg {
+        WebSocketMessage::Message(msg) => beserial::Serialize::serialized_size(msg),
+        WebSocketMessage::Resume(data, _) => data.len(),
+        WebSocketMessage::Close(_) => 0,
+    }
+}
+
+/// Forwards outgoing messages to the stream. Messages on the priority channel are always
+/// sent first, the others wait whenever the global upload limit is exhausted.
+pub struct ShapedForward {
+    priority: Option<UnboundedReceiver<WebSocketMessage>>,
+    bulk: Option<UnboundedReceiver<WebSocketMessage>>,
+    stream: SharedNimiqMessageStream,
+    buffered: Option<WebSocketMessage>,
+    throttled: Option<WebSocketMessage>,
+    delay: Option<Delay>,
+    counter: Arc<TrafficCounter>,
+    shaper: Arc<TrafficShaper>,
+}
+
+impl ShapedForward {
+    pub fn new(priority: UnboundedReceiver<WebSocketMessage>, bulk: UnboundedReceiver<WebSocketMessage>, stream: SharedNimiqMessageStream, counter: Arc<TrafficCounter>, shaper: Arc<TrafficShaper>) -> Self {
+        ShapedForward {
+            priority: Some(priority),
+            bulk: Some(bulk),
+            stream,
+            buffered: None,
+            throttled: None,
+            delay: None,
+            counter,
+            shaper,
+        }
+    }
+
+    fn note_sent(&self, bytes: usize) {
+        self.counter.note_bytes_sent(bytes);
+        self.shaper.counter().note_bytes_sent(bytes);
+    }
+
+    fn poll_priority(&mut self) -> Option<WebSocketMessage> {
+        let msg = match self.priority.as_mut().map(Stream::poll) {
+            Some(Ok(Async::Ready(Some(msg)))) => msg,
+            Some(Ok(Async::Ready(None))) => {
+                self.priority = None;
+                return None;
+            },
+            // Receivers never fail.
+            _ => return None,
+        };
+        let size = message_size(&msg);
+        let _ = self.shaper.acquire_upload(size, true);
+        self.note_sent(size);
+        Some(msg)
+    }
+
+    fn poll_bulk(&mut self) -> Option<WebSocketMessage> {
+        loop {
+            if self.throttled.is_none() {
+                self.throttled = match self.bulk.as_mut().map(Stream::poll) {
+                    Some(Ok(Async::Ready(Some(msg)))) => Some(msg),
+                    Some(Ok(Async::Ready(None))) => {
+                        self.bulk = None;
+                        return None;
+                    },
+                    _ => return None,
+                };
+            }
+
+            if let Some(ref mut delay) = self.delay {
+                match delay.poll() {
+                    Ok(Async::NotReady) => return None,
+                    // A broken timer must not stall the connection.
+                    Ok(Async::Ready(_)) | Err(_) => {},
+                }
+                self.delay = None;
+            }
+
+            let size = message_size(self.throttled.as_ref().unwrap());
+            match self.shaper.acquire_upload(size, false) {
+                Ok(()) => {
+                    self.note_sent(size);
+                    return self.throttled.take();
+                },
+                Err(wait) => {
+                    // Poll the new timer in the next iteration, so that we get woken up.
+                    self.delay = Some(Delay::new(Instant::now() + cmp::max(wait, Duration::from_millis(1))));
+                },
+            }
+        }
+    }
+}
+
+impl Future for ShapedForward {
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<(), Error> {
+        loop {
+            if let Some(msg) = self.buffered.take() {
+                if let AsyncSink::NotReady(msg) = self.stream.start_send(msg)? {
+                    self.buffered = Some(msg);
+                    try_ready!(self.stream.poll_complete());
+                    return Ok(Async::NotReady);
+                }
+            }
+
+            if let Some(msg) = self.poll_priority().or_else(|| self.poll_bulk()) {
+                // Nothing may be sent after a close frame.
+                if let WebSocketMessage::Close(_) = msg {
+                    self.priority = None;
+                    self.bulk = None;
+                    self.throttled = None;
+                    self.delay = None;
+                }
+                self.buffered = Some(msg);
+                continue;
+            }
+            break;
+        }
+
+        if self.priority.is_none() && self.bulk.is_none() && self.throttled.is_none() {
+            try_ready!(self.stream.close());
+            return Ok(Async::Ready(()));
+        }
+
+        try_ready!(self.stream.poll_complete());
+        Ok(Async::NotReady)
+    }
+}
+
+/// Counts incoming messages and holds back bulk messages whenever the global download limit
+/// is exhausted. Reading continues while bulk messages are held back, so that priority messages
+/// aren't stuck behind them. Only when `MAX_HELD_MESSAGES` are held, reading stops.
+pub struct ShapedStream {
+    stream: SharedNimiqMessageStream,
+    held: VecDeque<WebSocketMessage>,
+    delay: Option<Delay>,
+    finished: bool,
+    counter: Arc<TrafficCounter>,
+    shaper: Arc<TrafficShaper>,
+}
+
+impl ShapedStream {
+    const MAX_HELD_MESSAGES: usize = 16;
+
+    pub fn new(stream: SharedNimiqMessageStream, counter: Arc<TrafficCounter>, shaper: Arc<TrafficShaper>) -> Self {
+        ShapedStream {
+            stream,
+            held: VecDeque::new(),
+            delay: None,
+            finished: false,
+            counter,
+            shaper,
+        }
+    }
+
+    /// Releases the oldest held message once the download limit allows it.
+    fn poll_held(&mut self) -> Option<WebSocketMessage> {
+        loop {
+            if self.held.is_empty() {
+                return None;
+            }
+
+            if let Some(ref mut delay) = self.delay {
+                match delay.poll() {
+                    Ok(Async::NotReady) => return None,
+                    // A broken timer must not stall the connection.
+                    Ok(Async::Ready(_)) | Err(_) => {},
+                }
+                self.delay = None;
+            }
+
+            let size = message_size(self.held.front().unwrap());
+            match self.shaper.acquire_download(size, false) {
+                Ok(()) => return self.held.pop_front(),
+                Err(wait) => {
+                    // Poll the new timer in the next iteration, so that we get woken up.
+                    self.delay = Some(Delay::new(Instant::now() + cmp::max(wait, Duration::from_millis(1))));
+                },
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ShapedStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.stream.fmt(f)
+    }
+}
+
+impl Stream for ShapedStream {
+    type Item = WebSocketMessage;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<WebSocketMessage>, Error> {
+        // Once the connection is gone, there is no point in holding anything back.
+        if self.finished {
+            return Ok(Async::Ready(self.held.pop_front()));
+        }
+
+        if let Some(msg) = self.poll_held() {
+            return Ok(Async::Ready(Some(msg)));
+        }
+
+        while self.held.len() < Self::MAX_HELD_MESSAGES {
+            let msg = match try_ready!(self.stream.poll()) {
+                Some(msg) => msg,
+                None => {
+                    self.finished = true;
+                    return Ok(Async::Ready(self.held.pop_front()));
+                },
+            };
+
+            let size = message_size(&msg);
+            self.counter.note_bytes_received(size);
+            self.shaper.counter().note_bytes_received(size);
+
+            if is_priority_frame(&msg) {
+                self.shaper.acquire_download(size, true).ok();
+                return Ok(Async::Ready(Some(msg)));
+            }
+
+            // Bulk messages are delivered in order.
+            self.held.push_back(msg);
+            if let Some(msg) = self.poll_held() {
+                return Ok(Async::Ready(Some(msg)));
+            }
+        }
+
+        // The held messages are released when the timer polled in `poll_held` fires.
+        Ok(Async::NotReady)
+    }
+}
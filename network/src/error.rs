@@ -18,7 +18,10 @@ pub enum Error {
     #[fail(display = "Could not load network info for id {:?}", _0)]
     InvalidNetworkInfo(NetworkId),
     #[fail(display = "Could not add seed node {}", _0)]
-    InvalidSeed(#[cause] SeedError)
+    InvalidSeed(#[cause] SeedError),
+    #[cfg(feature = "seed-server")]
+    #[fail(display = "Could not start seed server: {}", _0)]
+    SeedServerError(#[cause] hyper::Error),
 }
 
 impl From<KeyStoreError> for Error {
@@ -39,6 +42,13 @@ impl From<SeedError> for Error {
     }
 }
 
+#[cfg(feature = "seed-server")]
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::SeedServerError(e)
+    }
+}
+
 #[derive(Debug, Fail)]
 pub enum SeedError {
     #[fail(display = "Invalid peer URI: {}", _0)]
@@ -11,6 +11,7 @@ extern crate nimiq_blockchain_base as blockchain_base;
 extern crate nimiq_hash as hash;
 extern crate nimiq_keys as keys;
 extern crate nimiq_collections as collections;
+extern crate nimiq_database as database;
 
 pub mod address;
 pub mod websocket;
@@ -21,6 +22,8 @@ pub mod peer;
 pub mod network_config;
 pub mod network;
 pub mod error;
+#[cfg(feature = "seed-server")]
+pub mod seed_server;
 #[cfg(feature = "metrics")]
 mod network_metrics;
 
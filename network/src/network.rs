@@ -11,6 +11,8 @@ use rand::Rng;
 use rand::rngs::OsRng;
 
 use blockchain_base::AbstractBlockchain;
+use database::Environment;
+use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::networks::NetworkId;
 use network_primitives::time::NetworkTime;
 use utils::mutable_once::MutableOnce;
@@ -23,6 +25,7 @@ use crate::connection::connection_info::ConnectionState;
 use crate::connection::connection_pool::ConnectionId;
 use crate::connection::connection_pool::ConnectionPool;
 use crate::connection::connection_pool::ConnectionPoolEvent;
+use crate::connection::network_connection::NetworkConnection;
 use crate::error::Error;
 use crate::network_config::NetworkConfig;
 use crate::Peer;
@@ -34,6 +37,7 @@ enum NetworkTimer {
     PeersChanged,
     ConnectError,
     PeerCountCheck,
+    Feeler,
 }
 
 pub enum NetworkEvent {
@@ -69,16 +73,18 @@ impl<B: AbstractBlockchain> Network<B> {
     const CONNECT_THROTTLE: Duration = Duration::from_secs(1);
     const ADDRESS_REQUEST_CUTOFF: usize = 250;
     const ADDRESS_REQUEST_PEERS: usize = 2;
+    const FEELER_INTERVAL: Duration = Duration::from_secs(2 * 60);
+    const ANCHOR_COUNT: usize = 2;
 
     pub const SIGNALING_ENABLED: bool = true;
 
-    pub fn new(blockchain: Arc<B>, network_config: NetworkConfig, network_time: Arc<NetworkTime>, network_id: NetworkId) -> Result<Arc<Self>, Error> {
+    pub fn new(blockchain: Arc<B>, network_config: NetworkConfig, network_time: Arc<NetworkTime>, network_id: NetworkId, env: Environment) -> Result<Arc<Self>, Error> {
         if !network_config.is_initialized() {
             return Err(Error::UninitializedPeerKey);
         }
 
         let net_config = Arc::new(network_config);
-        let addresses = Arc::new(PeerAddressBook::new(net_config.clone(), network_id)?);
+        let addresses = Arc::new(PeerAddressBook::new(net_config.clone(), network_id, env)?);
         let connections = ConnectionPool::new(addresses.clone(), net_config.clone(), blockchain)?;
         let this = Arc::new(Network {
             network_config: net_config.clone(),
@@ -124,13 +130,26 @@ impl<B: AbstractBlockchain> Network<B> {
     pub fn connect(&self) -> Result<(), Error> {
         self.auto_connect.store(true, Ordering::Relaxed);
 
+        let addresses = Arc::clone(&self.addresses);
         let connections = Arc::clone(&self.connections);
         let scorer = Arc::clone(&self.scorer);
 
         self.timers.set_interval(NetworkTimer::Housekeeping, move || {
-            Self::housekeeping(Arc::clone(&connections), Arc::clone(&scorer));
+            Self::housekeeping(Arc::clone(&addresses), Arc::clone(&connections), Arc::clone(&scorer));
         }, Self::HOUSEKEEPING_INTERVAL);
 
+        let weak = self.self_weak.clone();
+        self.timers.set_interval(NetworkTimer::Feeler, move || {
+            let this = upgrade_weak!(weak);
+            this.connect_feeler();
+        }, Self::FEELER_INTERVAL);
+
+        // Reconnect to the anchors of the last session first.
+        for peer_address in self.addresses.anchors() {
+            trace!("Connect to anchor: {}", peer_address);
+            self.connections.connect_outbound(peer_address);
+        }
+
         // Start connecting to peers.
         self.check_peer_count();
         Ok(())
@@ -140,6 +159,7 @@ impl<B: AbstractBlockchain> Network<B> {
         self.auto_connect.store(false, Ordering::Relaxed);
 
         self.timers.clear_interval(&NetworkTimer::Housekeeping);
+        self.timers.clear_interval(&NetworkTimer::Feeler);
 
         self.connections.disconnect();
         self.connections.set_allow_inbound_exchange(false);
@@ -241,6 +261,24 @@ impl<B: AbstractBlockchain> Network<B> {
         self.backoff.store(Self::CONNECT_BACKOFF_INITIAL, Ordering::Relaxed);
     }
 
+    /// Tests a random new address, so that we learn about reachable peers from as many
+    /// different sources as possible, not only from the addresses we pick for our outbound connections.
+    fn connect_feeler(&self) {
+        if !self.auto_connect.load(Ordering::Relaxed)
+            || !self.scorer.read().is_good_peer_set()
+            || self.connections.connecting_count() >= Self::CONNECTING_COUNT_MAX {
+            return;
+        }
+
+        let peer_address = self.scorer.read().pick_feeler_address();
+        if let Some(peer_address) = peer_address {
+            trace!("Connect feeler: {}", peer_address);
+            if !self.connections.connect_feeler(Arc::clone(&peer_address)) {
+                self.addresses.close(None, peer_address, CloseType::ConnectionFailed);
+            }
+        }
+    }
+
     fn update_time_offset(&self) {
         let mut offsets = Vec::new();
         offsets.push(0i64);
@@ -265,7 +303,8 @@ impl<B: AbstractBlockchain> Network<B> {
         self.network_time.set_offset(time_offset);
     }
 
-    fn housekeeping(connections: Arc<ConnectionPool<B>>, scorer: Arc<RwLock<PeerScorer<B>>>) {
+    fn housekeeping(addresses: Arc<PeerAddressBook>, connections: Arc<ConnectionPool<B>>, scorer: Arc<RwLock<PeerScorer<B>>>) {
+        Self::update_anchors(&addresses, &connections);
         scorer.write().score_connections();
 
         // Recycle.
@@ -287,13 +326,37 @@ impl<B: AbstractBlockchain> Network<B> {
         Self::refresh_addresses(connections, scorer);
     }
 
+    /// Picks our longest established outbound connections to full WebSocket nodes as anchors.
+    fn update_anchors(addresses: &PeerAddressBook, connections: &ConnectionPool<B>) {
+        let mut anchors: Vec<(Duration, Arc<PeerAddress>)> = {
+            let state = connections.state();
+            state.connection_iter().into_iter()
+                .filter(|info| info.state() == ConnectionState::Established)
+                .filter(|info| info.network_connection().map(NetworkConnection::outbound).unwrap_or(false))
+                .filter_map(|info| info.peer_address().map(|peer_address| (info.age_established(), peer_address)))
+                .filter(|(_, peer_address)| peer_address.services.is_full_node() && peer_address.protocol().is_connectable())
+                .collect()
+        };
+
+        // Nothing to anchor to, keep the last anchors for the next restart.
+        if anchors.is_empty() {
+            return;
+        }
+
+        anchors.sort_by(|a, b| b.0.cmp(&a.0));
+        addresses.set_anchors(anchors.into_iter()
+            .take(Self::ANCHOR_COUNT)
+            .map(|(_, peer_address)| peer_address)
+            .collect());
+    }
+
     fn refresh_addresses(connections: Arc<ConnectionPool<B>>, scorer: Arc<RwLock<PeerScorer<B>>>) {
         let connection_scores = RwLockReadGuard::map(scorer.read(), |scorer| scorer.connection_scores());
         let mut randrng = OsRng;
         if !connection_scores.is_empty() {
             let state = connections.state();
             let cutoff = cmp::min(
-                (state.peer_count_ws + state.peer_count_wss) * 2,
+                (state.peer_count_ws + state.peer_count_wss + state.peer_count_noise) * 2,
                 Self::ADDRESS_REQUEST_CUTOFF
             );
             let len = cmp::min(
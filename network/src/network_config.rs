@@ -11,7 +11,9 @@ use network_primitives::services::Services;
 use utils::key_store::{Error as KeyStoreError, KeyStore};
 use utils::time::systemtime_to_timestamp;
 
+use crate::connection::traffic::TrafficLimits;
 use crate::error::Error;
+use crate::websocket::LoopbackHub;
 
 // One or multiple seed nodes. Either a peer URI or a http(s) URL to a seed list
 #[derive(Clone, Debug)]
@@ -40,6 +42,7 @@ pub struct NetworkConfig {
     protocol_config: ProtocolConfig,
     user_agent: Option<String>,
     additional_seeds: Vec<Seed>,
+    traffic_limits: TrafficLimits,
     pub instant_inbound: bool,
 }
 
@@ -57,6 +60,7 @@ impl NetworkConfig {
             },
             user_agent: None,
             additional_seeds: Vec::new(),
+            traffic_limits: TrafficLimits::default(),
             instant_inbound,
         }
     }
@@ -75,6 +79,46 @@ impl NetworkConfig {
             },
             user_agent: None,
             additional_seeds: Vec::new(),
+            traffic_limits: TrafficLimits::default(),
+            instant_inbound,
+        }
+    }
+
+    /// Creates a config for a node that accepts Noise-encrypted TCP connections at `host:port`.
+    /// Connections are authenticated with the node's peer key, no certificate is needed.
+    pub fn new_noise_network_config(host: String, port: u16, instant_inbound: bool) -> Self {
+        Self {
+            protocol_mask: ProtocolFlags::WS | ProtocolFlags::WSS | ProtocolFlags::NOISE,
+            key_pair: None,
+            peer_id: None,
+            services: Services::full(),
+            protocol_config: ProtocolConfig::Noise {
+                host,
+                port,
+            },
+            user_agent: None,
+            additional_seeds: Vec::new(),
+            traffic_limits: TrafficLimits::default(),
+            instant_inbound,
+        }
+    }
+
+    /// Creates a config for a node that connects to other nodes in the same process through `hub`
+    /// instead of sockets. The node announces itself as a `ws` node at `host:port`.
+    pub fn new_loopback_network_config(hub: LoopbackHub, host: String, port: u16, instant_inbound: bool) -> Self {
+        Self {
+            protocol_mask: ProtocolFlags::WS | ProtocolFlags::WSS,
+            key_pair: None,
+            peer_id: None,
+            services: Services::full(),
+            protocol_config: ProtocolConfig::Loopback {
+                hub,
+                host,
+                port,
+            },
+            user_agent: None,
+            additional_seeds: Vec::new(),
+            traffic_limits: TrafficLimits::default(),
             instant_inbound,
         }
     }
@@ -88,6 +132,7 @@ impl NetworkConfig {
             protocol_config: ProtocolConfig::Dumb,
             user_agent: None,
             additional_seeds: Vec::new(),
+            traffic_limits: TrafficLimits::default(),
             instant_inbound: true,
         }
     }
@@ -146,6 +191,15 @@ impl NetworkConfig {
         self.services = services;
     }
 
+    /// Whether to connect to Noise peers. Nodes that accept Noise connections always do.
+    pub fn set_connect_noise(&mut self, connect_noise: bool) {
+        if connect_noise || self.protocol() == Protocol::Noise {
+            self.protocol_mask.insert(ProtocolFlags::NOISE);
+        } else {
+            self.protocol_mask.remove(ProtocolFlags::NOISE);
+        }
+    }
+
     pub fn can_connect(&self, protocol: Protocol) -> bool {
         self.protocol_mask.contains(ProtocolFlags::from(protocol))
     }
@@ -166,38 +220,69 @@ impl NetworkConfig {
         self.additional_seeds = seeds
     }
 
+    pub fn traffic_limits(&self) -> TrafficLimits {
+        self.traffic_limits
+    }
+
+    pub fn set_traffic_limits(&mut self, traffic_limits: TrafficLimits) {
+        self.traffic_limits = traffic_limits
+    }
+
     pub fn protocol_config(&self) -> &ProtocolConfig {
         &self.protocol_config
     }
 
     pub fn peer_address(&self) -> PeerAddress {
         // TODO Check PeerAddress globally reachable.
-        let mut addr = PeerAddress {
-            ty: match self.protocol_config {
-                ProtocolConfig::Rtc => PeerAddressType::Rtc,
-                ProtocolConfig::Dumb => PeerAddressType::Dumb,
-                ProtocolConfig::Ws {
-                    ref host,
-                    port,
-                    ref reverse_proxy_config,
-                    ..
-                } => {
-                    if let Some(reverse_proxy_config) = reverse_proxy_config.as_ref() {
-                        if reverse_proxy_config.with_tls_termination {
-                            PeerAddressType::Wss(host.clone(), reverse_proxy_config.port)
-                        } else {
-                            PeerAddressType::Ws(host.clone(), reverse_proxy_config.port)
-                        }
+        let addr = self.signed_peer_address(match self.protocol_config {
+            ProtocolConfig::Rtc => PeerAddressType::Rtc,
+            ProtocolConfig::Dumb => PeerAddressType::Dumb,
+            ProtocolConfig::Ws {
+                ref host,
+                port,
+                ref reverse_proxy_config,
+                ..
+            } => {
+                if let Some(reverse_proxy_config) = reverse_proxy_config.as_ref() {
+                    if reverse_proxy_config.with_tls_termination {
+                        PeerAddressType::Wss(host.clone(), reverse_proxy_config.port)
                     } else {
-                        PeerAddressType::Ws(host.clone(), port)
+                        PeerAddressType::Ws(host.clone(), reverse_proxy_config.port)
                     }
-                },
-                ProtocolConfig::Wss {
-                    ref host,
-                    port,
-                    ..
-                } => PeerAddressType::Wss(host.clone(), port),
+                } else {
+                    PeerAddressType::Ws(host.clone(), port)
+                }
             },
+            ProtocolConfig::Wss {
+                ref host,
+                port,
+                ..
+            } => PeerAddressType::Wss(host.clone(), port),
+            ProtocolConfig::Noise {
+                ref host,
+                port,
+            } => PeerAddressType::Noise(host.clone(), port),
+            ProtocolConfig::Loopback {
+                ref host,
+                port,
+                ..
+            } => PeerAddressType::Ws(host.clone(), port),
+        });
+        if addr.protocol() == Protocol::Wss || addr.protocol() == Protocol::Ws {
+            // TODO Disabled for debugging
+            //assert!(addr.is_globally_reachable(false), "PeerAddress not globally reachable.");
+        }
+        addr
+    }
+
+    /// Our peer address as a dumb node, for peers that can't parse our actual address.
+    pub fn dumb_peer_address(&self) -> PeerAddress {
+        self.signed_peer_address(PeerAddressType::Dumb)
+    }
+
+    fn signed_peer_address(&self, ty: PeerAddressType) -> PeerAddress {
+        let mut addr = PeerAddress {
+            ty,
             services: self.services.provided,
             timestamp: systemtime_to_timestamp(SystemTime::now()),
             net_address: NetAddress::Unspecified,
@@ -206,10 +291,6 @@ impl NetworkConfig {
             signature: None,
             peer_id: self.peer_id.as_ref().expect("NetworkConfig is uninitialized").clone(),
         };
-        if addr.protocol() == Protocol::Wss || addr.protocol() == Protocol::Ws {
-            // TODO Disabled for debugging
-            //assert!(addr.is_globally_reachable(false), "PeerAddress not globally reachable.");
-        }
         addr.signature = Some(self.key_pair.as_ref().expect("NetworkConfig is uninitialized").sign(&addr.get_signature_data()[..]));
         addr
     }
@@ -241,7 +322,18 @@ pub enum ProtocolConfig {
         identity_file: String,
         identity_password: String,
     },
+    /// Encrypted TCP, see `websocket::noise`.
+    Noise {
+        host: String,
+        port: u16,
+    },
     Rtc,
+    /// In-process transport for tests, see `LoopbackHub`.
+    Loopback {
+        hub: LoopbackHub,
+        host: String,
+        port: u16,
+    },
 }
 
 impl From<&ProtocolConfig> for Protocol {
@@ -257,6 +349,8 @@ impl From<&ProtocolConfig> for Protocol {
                 }
             },
             ProtocolConfig::Wss { .. } => Protocol::Wss,
+            ProtocolConfig::Noise { .. } => Protocol::Noise,
+            ProtocolConfig::Loopback { .. } => Protocol::Ws,
         }
     }
 }
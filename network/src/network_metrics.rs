@@ -60,7 +60,7 @@ pub struct MessageMetrics {
 
 impl MessageMetrics {
     // New message types need to be added here to occur in the metrics!
-    const MESSAGE_TYPES: [MessageType; 43] = [
+    const MESSAGE_TYPES: [MessageType; 46] = [
         MessageType::Version,
         MessageType::Inv,
         MessageType::GetData,
@@ -104,6 +104,9 @@ impl MessageMetrics {
         MessageType::PbftProposal,
         MessageType::PbftPrepare,
         MessageType::PbftCommit,
+        MessageType::CompactBlockAlbatross,
+        MessageType::GetBlockTransactions,
+        MessageType::BlockTransactions,
     ];
 
     pub fn new() -> Self {
@@ -169,6 +172,7 @@ pub enum PeerProtocol {
     Wss,
     Rtc,
     Ws,
+    Noise,
     Unknown,
 }
 
@@ -179,6 +183,7 @@ impl Display for PeerProtocol {
             PeerProtocol::Wss => "websocket-secure",
             PeerProtocol::Ws => "websocket",
             PeerProtocol::Rtc => "webrtc",
+            PeerProtocol::Noise => "noise",
             PeerProtocol::Unknown => "unknown",
         })
     }
@@ -191,6 +196,7 @@ impl From<Protocol> for PeerProtocol {
             Protocol::Ws => PeerProtocol::Ws,
             Protocol::Wss => PeerProtocol::Wss,
             Protocol::Rtc => PeerProtocol::Rtc,
+            Protocol::Noise => PeerProtocol::Noise,
         }
     }
 }
@@ -4,6 +4,7 @@ use std::hash::Hasher;
 use std::sync::Arc;
 
 use hash::Blake2bHash;
+use network_messages::Capabilities;
 use network_primitives::address::net_address::NetAddress;
 use network_primitives::address::peer_address::PeerAddress;
 
@@ -16,16 +17,18 @@ pub struct Peer {
     pub head_hash: Blake2bHash,
     pub time_offset: i64,
     pub user_agent: Option<String>,
+    pub capabilities: Capabilities,
 }
 
 impl Peer {
-    pub fn new(channel: Arc<PeerChannel>, version: u32, head_hash: Blake2bHash, time_offset: i64, user_agent: Option<String>) -> Self {
+    pub fn new(channel: Arc<PeerChannel>, version: u32, head_hash: Blake2bHash, time_offset: i64, user_agent: Option<String>, capabilities: Capabilities) -> Self {
         Peer {
             channel,
             version,
             head_hash,
             time_offset,
-            user_agent
+            user_agent,
+            capabilities,
         }
     }
 
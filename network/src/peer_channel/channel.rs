@@ -5,6 +5,7 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use futures::sync::mpsc::*;
 use parking_lot::RwLock;
@@ -20,6 +21,7 @@ use crate::connection::network_connection::NetworkConnection;
 use crate::network_metrics::MessageMetrics;
 use crate::websocket::Message as WebSocketMessage;
 
+use super::requests::{PendingRequests, RequestError, RequestId, ResponseStream};
 use super::sink::PeerSink;
 use super::stream::PeerStreamEvent;
 use std::time::Instant;
@@ -34,6 +36,8 @@ pub struct PeerChannel {
     closed_flag: ClosedFlag,
     pub last_message_received: Arc<Atomic<Instant>>,
     close_event_sent: Arc<AtomicBool>,
+    compression: Arc<AtomicBool>,
+    requests: Arc<PendingRequests>,
 
     #[cfg(feature = "metrics")]
     pub message_metrics: Arc<MessageMetrics>,
@@ -43,6 +47,7 @@ impl PeerChannel {
     pub fn new(network_connection: &NetworkConnection) -> Self {
         let msg_notifier = Arc::new(MessageNotifier::new());
         let close_notifier = Arc::new(RwLock::new(Notifier::new()));
+        let requests = Arc::new(PendingRequests::new());
 
         #[cfg(feature = "metrics")]
         let message_metrics = Arc::new(MessageMetrics::new());
@@ -51,6 +56,7 @@ impl PeerChannel {
         let msg_notifier1 = msg_notifier.clone();
         let close_notifier1 = close_notifier.clone();
         let last_message_received1 = last_message_received.clone();
+        let requests1 = requests.clone();
 
         #[cfg(feature = "metrics")]
         let message_metrics1 = message_metrics.clone();
@@ -65,7 +71,10 @@ impl PeerChannel {
                     let start = Instant::now();
                     last_message_received1.store(Instant::now(), Ordering::Relaxed);
                     let msg_type = msg.ty();
-                    msg_notifier1.notify(msg);
+                    // Responses to our own requests are not dispatched to the message notifier.
+                    if let Some(msg) = requests1.route(msg) {
+                        msg_notifier1.notify(msg);
+                    }
                     #[cfg(feature = "metrics")] {
                         let time: usize = usize::try_from(start.elapsed().as_micros()).expect("Fatal error while converting processing time to usize");
                         trace!("Microseconds elapsed while processing this message: {:?}", time);
@@ -73,12 +82,14 @@ impl PeerChannel {
                     }
                 },
                 PeerStreamEvent::Close(ty) => {
+                    requests1.fail_all(RequestError::ChannelClosed);
                     // Only send close event once, i.e., if close_event_sent was false.
                     if !close_event_sent_inner.swap(true, Ordering::AcqRel) {
                         close_notifier1.read().notify(ty)
                     }
                 },
                 PeerStreamEvent::Error(error) => {
+                    requests1.fail_all(RequestError::ChannelClosed);
                     // Only send close event once, i.e., if close_event_sent was false.
                     if !close_event_sent_inner.swap(true, Ordering::AcqRel) {
                         debug!("Stream with peer closed with error: {} ({})", error.as_ref(), info);
@@ -96,6 +107,8 @@ impl PeerChannel {
             closed_flag: network_connection.closed_flag(),
             last_message_received,
             close_event_sent,
+            compression: network_connection.compression(),
+            requests,
 
             #[cfg(feature = "metrics")]
             message_metrics,
@@ -112,12 +125,60 @@ impl PeerChannel {
         }
     }
 
+    /// Sends `msg` regardless of the traffic limits, see `PeerSink::send_priority`.
+    pub fn send_priority(&self, msg: Message) -> Result<(), SendError<WebSocketMessage>> {
+        self.peer_sink.send_priority(msg)
+    }
+
+    /// Sends `msg` as a request and returns the stream of responses to it.
+    ///
+    /// The stream fails if no response arrives within `timeout` of sending the request or of the
+    /// previous response. Dropping the stream cancels the request.
+    pub fn request(&self, mut msg: Message, timeout: Duration) -> ResponseStream {
+        if self.closed() {
+            return ResponseStream::failed(RequestError::ChannelClosed);
+        }
+
+        let (id, receiver) = match self.requests.register(&mut msg) {
+            Ok(request) => request,
+            Err(e) => return ResponseStream::failed(e),
+        };
+        let stream = ResponseStream::new(id, receiver, timeout, &self.requests);
+
+        if self.peer_sink.send(msg).is_err() {
+            self.peer_sink.close(CloseType::SendFailed, Some("SendFailed".to_string()));
+            self.requests.cancel(id);
+            return ResponseStream::failed(RequestError::SendFailed);
+        }
+        stream
+    }
+
+    /// Cancels an outstanding request.
+    pub fn cancel_request(&self, id: RequestId) {
+        self.requests.cancel(id);
+    }
+
+    /// Returns the number of requests that are waiting for responses.
+    pub fn pending_requests(&self) -> usize {
+        self.requests.len()
+    }
+
     pub fn closed(&self) -> bool {
         self.closed_flag.is_closed()
     }
 
+    /// Compresses large messages from now on. Only call this if the peer supports it.
+    pub fn enable_compression(&self) {
+        self.compression.store(true, Ordering::Release);
+    }
+
+    pub fn compression_enabled(&self) -> bool {
+        self.compression.load(Ordering::Acquire)
+    }
+
     pub fn close(&self, ty: CloseType) {
         self.peer_sink.close(ty, None);
+        self.requests.fail_all(RequestError::ChannelClosed);
         let notifier = self.close_notifier.clone();
         let close_event_sent = self.close_event_sent.clone();
         tokio::spawn(futures::lazy(move || {
@@ -1,8 +1,10 @@
 pub use self::channel::*;
+pub use self::requests::*;
 pub use self::sink::*;
 pub use self::stream::*;
 
 pub mod channel;
+pub mod requests;
 pub mod sink;
 pub mod stream;
 
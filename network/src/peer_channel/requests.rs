@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use failure::Fail;
+use futures::{Async, Poll, Stream};
+use futures::sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use parking_lot::Mutex;
+use tokio::timer::Timeout;
+
+use network_messages::{Message, MessageType};
+
+pub type RequestId = u32;
+
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+pub enum RequestError {
+    #[fail(display = "Request timed out")]
+    Timeout,
+    #[fail(display = "Request was cancelled")]
+    Cancelled,
+    #[fail(display = "Channel closed before the request completed")]
+    ChannelClosed,
+    #[fail(display = "Failed to send request")]
+    SendFailed,
+    #[fail(display = "Too many outstanding requests")]
+    TooManyRequests,
+    #[fail(display = "Message is not a request")]
+    InvalidRequest,
+    #[fail(display = "Timer failed")]
+    TimerError,
+}
+
+type ResponseSender = UnboundedSender<Result<Message, RequestError>>;
+
+#[derive(Clone)]
+struct PendingRequest {
+    response_type: MessageType,
+    sender: ResponseSender,
+}
+
+/// The requests sent over a `PeerChannel` that are waiting for responses.
+///
+/// Requests are assigned an identifier which the peer echoes in its responses. This allows
+/// several requests of the same type to be outstanding at the same time and makes it possible to
+/// discard responses to requests that timed out or were cancelled.
+///
+/// Responses without an identifier, i.e. from peers that don't support them or responses that
+/// can't carry one like the `Inv` answering a `GetBlocks`, are matched to the oldest request
+/// waiting for that type of response. Peers answer requests in order, so this is what we did
+/// before there were identifiers.
+pub struct PendingRequests {
+    next_id: AtomicU32,
+    pending: Mutex<HashMap<RequestId, PendingRequest>>,
+}
+
+impl PendingRequests {
+    /// Maximum number of outstanding requests per peer.
+    pub const MAX_PENDING_REQUESTS: usize = 64;
+
+    pub fn new() -> Self {
+        PendingRequests {
+            // 0 is reserved for messages without a request identifier.
+            next_id: AtomicU32::new(1),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Assigns an identifier to `msg` and registers it as pending.
+    pub(super) fn register(&self, msg: &mut Message) -> Result<(RequestId, UnboundedReceiver<Result<Message, RequestError>>), RequestError> {
+        let response_type = msg.response_type().ok_or(RequestError::InvalidRequest)?;
+
+        let mut pending = self.pending.lock();
+        if pending.len() >= Self::MAX_PENDING_REQUESTS {
+            return Err(RequestError::TooManyRequests);
+        }
+
+        let mut id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        while id == 0 || pending.contains_key(&id) {
+            id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Requests that can't carry an identifier are matched by order, see `route`.
+        msg.set_request_identifier(id);
+
+        let (sender, receiver) = unbounded();
+        pending.insert(id, PendingRequest { response_type, sender });
+        Ok((id, receiver))
+    }
+
+    /// Routes a response to the request it belongs to. Returns the message if it should be
+    /// dispatched as usual, i.e. if it isn't a response to a request made through this channel or
+    /// if it is an `Inv`, which is processed by the inventory agent as well.
+    pub(super) fn route(&self, msg: Message) -> Option<Message> {
+        let mut pending = self.pending.lock();
+        let id = match msg.request_identifier() {
+            Some(id) if id != 0 && msg.is_response() => {
+                if !pending.contains_key(&id) {
+                    // The request timed out or was cancelled.
+                    debug!("Discarding {} response to unknown request {}", msg.ty(), id);
+                    return None;
+                }
+                id
+            },
+            _ => {
+                let ty = msg.ty();
+                let oldest = pending.iter()
+                    .filter(|(_, request)| request.response_type == ty)
+                    .map(|(id, _)| *id)
+                    .min();
+                match oldest {
+                    Some(id) => id,
+                    None => return Some(msg),
+                }
+            },
+        };
+
+        let request = if msg.is_final_response() {
+            // Dropping the sender ends the response stream after this message.
+            pending.remove(&id)
+        } else {
+            pending.get(&id).cloned()
+        };
+        let sender = match request {
+            Some(request) => request.sender,
+            None => return None,
+        };
+        if msg.is_response() {
+            let _ = sender.unbounded_send(Ok(msg));
+            None
+        } else {
+            let _ = sender.unbounded_send(Ok(msg.clone()));
+            Some(msg)
+        }
+    }
+
+    /// Cancels a pending request, its response stream fails with `RequestError::Cancelled`.
+    /// Later responses to it are discarded.
+    pub fn cancel(&self, id: RequestId) {
+        if let Some(request) = self.pending.lock().remove(&id) {
+            let _ = request.sender.unbounded_send(Err(RequestError::Cancelled));
+        }
+    }
+
+    /// Fails all pending requests with `error`.
+    pub(super) fn fail_all(&self, error: RequestError) {
+        for (_, request) in self.pending.lock().drain() {
+            let _ = request.sender.unbounded_send(Err(error.clone()));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().is_empty()
+    }
+}
+
+impl Default for PendingRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The stream of responses to a request. It ends after the final response.
+///
+/// If no response arrives within the timeout of the request, the stream fails with
+/// `RequestError::Timeout`. The timeout restarts with every response. Dropping the stream
+/// cancels the request.
+pub struct ResponseStream {
+    id: RequestId,
+    inner: Result<Timeout<UnboundedReceiver<Result<Message, RequestError>>>, Option<RequestError>>,
+    requests: Weak<PendingRequests>,
+}
+
+impl ResponseStream {
+    pub(super) fn new(id: RequestId, receiver: UnboundedReceiver<Result<Message, RequestError>>, timeout: Duration, requests: &Arc<PendingRequests>) -> Self {
+        ResponseStream {
+            id,
+            inner: Ok(Timeout::new(receiver, timeout)),
+            requests: Arc::downgrade(requests),
+        }
+    }
+
+    pub(super) fn failed(error: RequestError) -> Self {
+        ResponseStream {
+            id: 0,
+            inner: Err(Some(error)),
+            requests: Weak::new(),
+        }
+    }
+
+    /// The identifier of the request, or `0` if it couldn't be sent.
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    fn finish(&mut self) {
+        if let Some(requests) = self.requests.upgrade() {
+            requests.cancel(self.id);
+        }
+        self.requests = Weak::new();
+    }
+}
+
+impl Stream for ResponseStream {
+    type Item = Message;
+    type Error = RequestError;
+
+    fn poll(&mut self) -> Poll<Option<Message>, RequestError> {
+        let result = match self.inner {
+            Ok(ref mut receiver) => receiver.poll(),
+            Err(ref mut error) => return match error.take() {
+                Some(error) => Err(error),
+                None => Ok(Async::Ready(None)),
+            },
+        };
+
+        match result {
+            Ok(Async::Ready(Some(Ok(msg)))) => Ok(Async::Ready(Some(msg))),
+            Ok(Async::Ready(Some(Err(error)))) => {
+                self.finish();
+                Err(error)
+            },
+            Ok(Async::Ready(None)) => {
+                self.finish();
+                Ok(Async::Ready(None))
+            },
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(e) => {
+                self.finish();
+                if e.is_elapsed() {
+                    Err(RequestError::Timeout)
+                } else {
+                    Err(RequestError::TimerError)
+                }
+            },
+        }
+    }
+}
+
+impl Drop for ResponseStream {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
@@ -9,19 +9,23 @@ use network_messages::Message;
 use utils::unique_id::UniqueId;
 
 use crate::connection::close_type::CloseType;
+use crate::connection::traffic::is_priority;
 use crate::websocket::Message as WebSocketMessage;
 use crate::connection::network_connection::ClosedFlag;
 
 #[derive(Clone)]
 pub struct PeerSink {
+    priority_sink: UnboundedSender<WebSocketMessage>,
     sink: UnboundedSender<WebSocketMessage>,
     unique_id: UniqueId,
     closed_flag: ClosedFlag,
 }
 
 impl PeerSink {
-    pub fn new(channel: UnboundedSender<WebSocketMessage>, unique_id: UniqueId, closed_flag: ClosedFlag) -> Self {
+    /// Messages that are critical for consensus go over `priority_channel`, everything else over `channel`.
+    pub fn new(priority_channel: UnboundedSender<WebSocketMessage>, channel: UnboundedSender<WebSocketMessage>, unique_id: UniqueId, closed_flag: ClosedFlag) -> Self {
         PeerSink {
+            priority_sink: priority_channel,
             sink: channel,
             unique_id,
             closed_flag,
@@ -34,7 +38,19 @@ impl PeerSink {
         if self.closed_flag.is_closed() {
             return Ok(());
         }
-        self.sink.unbounded_send(WebSocketMessage::Message(msg))
+        if is_priority(&msg) {
+            self.priority_sink.unbounded_send(WebSocketMessage::Message(msg))
+        } else {
+            self.sink.unbounded_send(WebSocketMessage::Message(msg))
+        }
+    }
+
+    /// Sends `msg` ahead of any queued bulk messages, without waiting for the upload limit.
+    pub fn send_priority(&self, msg: Message) -> Result<(), SendError<WebSocketMessage>> {
+        if self.closed_flag.is_closed() {
+            return Ok(());
+        }
+        self.priority_sink.unbounded_send(WebSocketMessage::Message(msg))
     }
 
     /// Closes the connection.
@@ -47,7 +63,9 @@ impl PeerSink {
         }
         self.closed_flag.set_close_type(ty);
         debug!("Closing connection, reason: {:?} ({:?})", ty, reason);
-        if let Err(error) = self.sink.unbounded_send(WebSocketMessage::Close(None)) {
+        // The close frame isn't held back by the traffic limits. Rejects are priority messages as well,
+        // so a preceding reject is still delivered before the close frame.
+        if let Err(error) = self.priority_sink.unbounded_send(WebSocketMessage::Close(None)) {
             debug!("Error closing connection: {}", error);
         }
 
@@ -12,8 +12,9 @@ use utils::observer::PassThroughNotifier;
 use utils::unique_ptr::UniquePtr;
 
 use crate::connection::close_type::CloseType;
+use crate::connection::traffic::ShapedStream;
 use crate::connection::network_connection::ClosedFlag;
-use crate::websocket::{Error, SharedNimiqMessageStream};
+use crate::websocket::Error;
 use crate::websocket::Message as WebSocketMessage;
 use futures::future;
 
@@ -24,13 +25,13 @@ pub enum PeerStreamEvent {
 }
 
 pub struct PeerStream {
-    stream: SharedNimiqMessageStream,
+    stream: ShapedStream,
     closed_flag: ClosedFlag,
     pub notifier: Arc<RwLock<PassThroughNotifier<'static, PeerStreamEvent>>>,
 }
 
 impl PeerStream {
-    pub fn new(stream: SharedNimiqMessageStream, notifier: Arc<RwLock<PassThroughNotifier<'static, PeerStreamEvent>>>, closed_flag: ClosedFlag) -> Self {
+    pub fn new(stream: ShapedStream, notifier: Arc<RwLock<PassThroughNotifier<'static, PeerStreamEvent>>>, closed_flag: ClosedFlag) -> Self {
         PeerStream {
             stream,
             notifier,
@@ -1,10 +1,12 @@
 use std::{sync::Arc, time::Duration};
+use std::collections::{BTreeMap, HashSet};
 
 use rand::Rng;
 use rand::rngs::OsRng;
 
 use blockchain_base::AbstractBlockchain;
 use network_primitives::{
+    address::net_address::NetAddress,
     address::peer_address::PeerAddress,
     protocol::Protocol,
 };
@@ -12,7 +14,7 @@ use network_primitives::services::ServiceFlags;
 
 use crate::{
     address::{
-        peer_address_book::PeerAddressBook,
+        peer_address_book::{address_group, PeerAddressBook},
         peer_address_state::PeerAddressState,
     },
     connection::{
@@ -39,6 +41,7 @@ impl<B: AbstractBlockchain + 'static> PeerScorer<B> {
     const PEER_COUNT_MIN_FULL_WS_OUTBOUND: usize = 1; // FIXME: this is fixed to the "node.js" value since we don't support browsers in the Rust impl yet
     const PEER_COUNT_MIN_OUTBOUND: usize = 6; // FIXME: this is fixed to the "node.js" value since we don't support browsers in the Rust impl yet
 
+    const PICK_CANDIDATES: usize = 1000;
     const PICK_SELECTION_SIZE: usize = 100;
 
     const MIN_AGE_FULL: Duration = Duration::from_secs(5 * 60); // 5 minutes
@@ -65,28 +68,100 @@ impl<B: AbstractBlockchain + 'static> PeerScorer<B> {
     }
 
     pub fn pick_address(&self) -> Option<Arc<PeerAddress>> {
-        let mut candidates = self.find_candidates(1000, false);
+        self.pick_address_with_rng(&mut OsRng)
+    }
+
+    /// Like `pick_address`, but draws from `rng`.
+    pub fn pick_address_with_rng<R: Rng>(&self, rng: &mut R) -> Option<Arc<PeerAddress>> {
+        let mut candidates = self.find_candidates(Self::PICK_CANDIDATES, false, rng);
         if candidates.is_empty() {
-            candidates = self.find_candidates(1000, true);
-        }
-        if candidates.is_empty() {
-            return None;
+            candidates = self.find_candidates(Self::PICK_CANDIDATES, true, rng);
         }
+
         candidates.sort_by(|a, b| { a.1.cmp(&b.1) });
-        let rand_ind = OsRng.gen_range(0, usize::min(Self::PICK_SELECTION_SIZE, candidates.len()));
-        match candidates.get(rand_ind) {
-            Some((peer_address, _)) => Some(Arc::clone(peer_address)),
-            None => None
+        let candidates = candidates.into_iter()
+            .take(Self::PICK_SELECTION_SIZE)
+            .map(|(peer_address, _)| peer_address)
+            .collect();
+        self.pick_diversified(candidates, rng)
+    }
+
+    /// Picks a new address that we have never connected to, to test it with a feeler connection.
+    pub fn pick_feeler_address(&self) -> Option<Arc<PeerAddress>> {
+        let mut rng = OsRng;
+        let candidates = self.find_candidates(Self::PICK_CANDIDATES, true, &mut rng);
+
+        let candidates = {
+            let addresses_state = self.addresses.state();
+            candidates.into_iter()
+                .map(|(peer_address, _)| peer_address)
+                .filter(|peer_address| addresses_state.get_info(peer_address)
+                    .map(|info| info.state == PeerAddressState::New)
+                    .unwrap_or(false))
+                .collect()
+        };
+        self.pick_diversified(candidates, &mut rng)
+    }
+
+    /// Picks a random address, giving each address group the same chance regardless of how many
+    /// addresses it has in the address book. Addresses without a known group are bucketed by the group
+    /// of the peer that announced them. Groups we already have outbound connections to are avoided,
+    /// so that a single network can't take over all of our outbound connections.
+    fn pick_diversified<R: Rng>(&self, candidates: Vec<Arc<PeerAddress>>, rng: &mut R) -> Option<Arc<PeerAddress>> {
+        let outbound_groups = self.outbound_groups();
+        let addresses_state = self.addresses.state();
+
+        // Ordered, so that the pick only depends on the RNG.
+        let mut buckets: BTreeMap<Option<NetAddress>, Vec<Arc<PeerAddress>>> = BTreeMap::new();
+        let mut connected_groups = Vec::new();
+        for peer_address in candidates {
+            let info = addresses_state.get_info(&peer_address);
+            let group = info.and_then(|info| info.address_group);
+            if group.map(|group| outbound_groups.contains(&group)).unwrap_or(false) {
+                connected_groups.push(peer_address);
+                continue;
+            }
+
+            let bucket = group.or_else(|| info.and_then(|info| info.source_group));
+            buckets.entry(bucket)
+                .or_insert_with(Vec::new)
+                .push(peer_address);
+        }
+
+        let bucket = if buckets.is_empty() {
+            connected_groups
+        } else {
+            let index = rng.gen_range(0, buckets.len());
+            buckets.into_iter().nth(index).map(|(_, bucket)| bucket)?
+        };
+        if bucket.is_empty() {
+            return None;
         }
+        let index = rng.gen_range(0, bucket.len());
+        Some(Arc::clone(&bucket[index]))
+    }
+
+    /// The address groups of all outbound connections, excluding feeler connections.
+    fn outbound_groups(&self) -> HashSet<NetAddress> {
+        let state = self.connections.state();
+        state.connection_iter().into_iter()
+            .filter(|info| !info.is_feeler())
+            .filter_map(|info| match info.network_connection() {
+                Some(network_connection) if network_connection.outbound() => address_group(&network_connection.net_address()),
+                Some(_) => None,
+                // Still connecting.
+                None => info.peer_address().and_then(|peer_address| address_group(&peer_address.net_address)),
+            })
+            .collect()
     }
 
-    fn find_candidates(&self, num_candidates: usize, allow_bad_peers: bool) -> Vec<(Arc<PeerAddress>, i32)> {
+    fn find_candidates<R: Rng>(&self, num_candidates: usize, allow_bad_peers: bool, rng: &mut R) -> Vec<(Arc<PeerAddress>, i32)> {
         let addresses_state = self.addresses.state();
         let address_iterator = addresses_state.address_iter_for_protocol_mask(self.network_config.protocol_mask());
         let num_addresses = addresses_state.known_addresses_nr_for_protocol_mask(self.network_config.protocol_mask());
 
         let (start_index, end_index) = if num_addresses > num_candidates {
-            let start = OsRng.gen_range(0, num_addresses);
+            let start = rng.gen_range(0, num_addresses);
             (start, (start + num_candidates) % num_addresses)
         } else {
             (0, num_addresses)
@@ -168,20 +243,26 @@ impl<B: AbstractBlockchain + 'static> PeerScorer<B> {
     }
 
     pub fn is_good_peer(&self, peer_address: &Arc<PeerAddress>) -> bool {
-        peer_address.services.is_full_node() && (peer_address.protocol() == Protocol::Ws || peer_address.protocol() == Protocol::Wss)
+        peer_address.services.is_full_node() && peer_address.protocol().is_connectable()
     }
 
     pub fn score_connections(&mut self) {
         let mut connection_scores: Vec<(ConnectionId, Score)> = Vec::new();
 
         let state = self.connections.state();
-        let distribution: f64 = (state.peer_count_ws as f64 + state.peer_count_wss as f64) / state.peer_count() as f64;
+        let distribution: f64 = (state.peer_count_ws as f64 + state.peer_count_wss as f64 + state.peer_count_noise as f64) / state.peer_count() as f64;
         let peer_count_full_ws_outbound = state.get_peer_count_full_ws_outbound();
         let connections: Vec<(ConnectionId, &ConnectionInfo<B>)> = state.id_and_connection_iter();
 
         for connection in connections {
+            let peer_address = connection.1.peer_address().expect("No peer address");
+            // Anchor connections are never recycled.
+            if self.addresses.is_anchor(&peer_address) {
+                continue;
+            }
+
             if connection.1.state() == ConnectionState::Established
-                && connection.1.age_established() > self.get_min_age(connection.1.peer_address().expect("No peer address")) {
+                && connection.1.age_established() > self.get_min_age(peer_address) {
                 let score = Self::score_connection(connection.1, distribution, peer_count_full_ws_outbound);
                 connection_scores.push((connection.0, score));
             }
@@ -230,7 +311,7 @@ impl<B: AbstractBlockchain + 'static> PeerScorer<B> {
 
         // Protocol: Prefer WebSocket over WebRTC over Dumb.
         let score_protocol: Score = match peer_address.protocol() {
-            Protocol::Wss | Protocol::Ws => {
+            Protocol::Wss | Protocol::Ws | Protocol::Noise => {
                 // Boost WebSocket score when low on WebSocket connections.
                 if distribution < Self::BEST_PROTOCOL_WS_DISTRIBUTION || peer_count_full_ws_outbound <= Self::PEER_COUNT_MIN_FULL_WS_OUTBOUND {
                     1.0
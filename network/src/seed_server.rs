@@ -0,0 +1,223 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+
+use futures::Future;
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::service_fn_ok;
+
+use blockchain_base::AbstractBlockchain;
+use macros::upgrade_weak;
+use network_primitives::address::peer_address::PeerAddress;
+use network_primitives::protocol::Protocol;
+use utils::mutable_once::MutableOnce;
+use utils::time::systemtime_to_timestamp;
+use utils::timers::Timers;
+
+use crate::address::peer_address_state::PeerAddressState;
+use crate::connection::close_type::CloseType;
+use crate::connection::connection_info::ConnectionState;
+use crate::error::Error;
+use crate::network::Network;
+
+#[derive(Debug, Ord, PartialOrd, PartialEq, Eq, Hash)]
+enum SeedServerTimer {
+    Crawl,
+}
+
+/// Crawls the network and serves the peers it found to be reachable as a signed seed list.
+///
+/// The seed server doesn't maintain long-lived connections. It keeps opening short-lived
+/// outbound connections to learn new addresses (the network agent requests addresses after every
+/// handshake) and to re-check the addresses it already knows. Nodes connecting to it are answered
+/// with `Addr` messages as usual and disconnected once their connection expires.
+///
+/// The seed list is signed with the peer key of the seed server, so it can be configured as a seed
+/// list with the server's public key.
+pub struct SeedServer<B: AbstractBlockchain + 'static> {
+    network: Arc<Network<B>>,
+    max_seeds: usize,
+    timers: Timers<SeedServerTimer>,
+    self_weak: MutableOnce<Weak<SeedServer<B>>>,
+}
+
+impl<B: AbstractBlockchain + 'static> SeedServer<B> {
+    const CRAWL_INTERVAL: Duration = Duration::from_secs(5);
+    const CRAWLING_COUNT_MAX: usize = 8;
+    /// Long enough for a peer to answer our address request.
+    const CONNECTION_DURATION: Duration = Duration::from_secs(60);
+    const RECHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+    /// Peers that weren't reachable for this long are not served anymore.
+    const SEED_MAX_AGE: Duration = Duration::from_secs(3 * 60 * 60);
+
+    pub fn new(network: Arc<Network<B>>, max_seeds: usize) -> Arc<Self> {
+        let this = Arc::new(SeedServer {
+            network,
+            max_seeds,
+            timers: Timers::new(),
+            self_weak: MutableOnce::new(Weak::new()),
+        });
+        unsafe { this.self_weak.replace(Arc::downgrade(&this)) };
+        this
+    }
+
+    /// Starts crawling the network and accepting inbound connections.
+    pub fn start(&self) {
+        self.network.set_allow_inbound_connections(true);
+
+        let weak = self.self_weak.clone();
+        self.timers.set_interval(SeedServerTimer::Crawl, move || {
+            let this = upgrade_weak!(weak);
+            this.crawl();
+        }, Self::CRAWL_INTERVAL);
+        self.crawl();
+    }
+
+    pub fn stop(&self) {
+        self.timers.clear_interval(&SeedServerTimer::Crawl);
+        self.network.disconnect();
+    }
+
+    fn crawl(&self) {
+        self.expire_connections();
+
+        let connections = &self.network.connections;
+        for _ in connections.connecting_count()..Self::CRAWLING_COUNT_MAX {
+            let peer_address = match self.pick_address() {
+                Some(peer_address) => peer_address,
+                None => break,
+            };
+
+            trace!("Crawl {}", peer_address);
+            if !connections.connect_outbound(Arc::clone(&peer_address)) {
+                self.network.addresses.close(None, peer_address, CloseType::ConnectionFailed);
+            }
+        }
+    }
+
+    /// Closes all connections that have been established for longer than `CONNECTION_DURATION`.
+    fn expire_connections(&self) {
+        let state = self.network.connections.state();
+        for info in state.connection_iter() {
+            if info.state() == ConnectionState::Established && info.age_established() > Self::CONNECTION_DURATION {
+                if let Some(peer_channel) = info.peer_channel() {
+                    peer_channel.close(CloseType::SeedServerConnectionExpired);
+                }
+            }
+        }
+    }
+
+    /// Picks a new address first. Otherwise, picks the reachable address that was checked the
+    /// longest time ago, if that was more than `RECHECK_INTERVAL` ago.
+    fn pick_address(&self) -> Option<Arc<PeerAddress>> {
+        if let Some(peer_address) = self.network.scorer().pick_feeler_address() {
+            return Some(peer_address);
+        }
+
+        let addresses = self.network.addresses.state();
+        let connections = self.network.connections.state();
+        let recheck_before = SystemTime::now() - Self::RECHECK_INTERVAL;
+        addresses.address_info_iter()
+            .filter(|info| info.state == PeerAddressState::Tried)
+            .filter(|info| info.last_connected.map(|time| time < recheck_before).unwrap_or(true))
+            .filter(|info| self.network.network_config.can_connect(info.peer_address.protocol()))
+            .filter(|info| connections.get_connection_by_peer_address(&info.peer_address).is_none())
+            .min_by_key(|info| info.last_connected)
+            .map(|info| Arc::clone(&info.peer_address))
+    }
+
+    /// Returns the full nodes we were able to connect to within `SEED_MAX_AGE`, most recently
+    /// connected first.
+    pub fn seeds(&self) -> Vec<Arc<PeerAddress>> {
+        let max_age = SystemTime::now() - Self::SEED_MAX_AGE;
+        let own_peer_id = self.network.network_config.peer_id();
+
+        let mut seeds: Vec<(SystemTime, Arc<PeerAddress>)> = self.network.addresses.state().address_info_iter()
+            .filter(|info| info.state == PeerAddressState::Tried || info.state == PeerAddressState::Established)
+            .filter(|info| info.peer_address.services.is_full_node())
+            .filter(|info| info.peer_address.protocol().is_connectable())
+            .filter(|info| info.peer_address.is_globally_reachable(false))
+            .filter(|info| info.peer_address.peer_id() != own_peer_id)
+            .filter_map(|info| info.last_connected
+                .filter(|time| *time >= max_age)
+                .map(|time| (time, Arc::clone(&info.peer_address))))
+            .collect();
+
+        seeds.sort_by(|a, b| b.0.cmp(&a.0));
+        seeds.into_iter()
+            .take(self.max_seeds)
+            .map(|(_, peer_address)| peer_address)
+            .collect()
+    }
+
+    /// Returns the seed list in the format `PeerAddressSeeder` parses: one peer URI per line,
+    /// followed by the signature over those lines.
+    ///
+    /// Only contains `ws://` and `wss://` seeds, since seeders that don't know about Noise reject
+    /// the whole list if it contains a `noise://` URI. Those are served by `noise_seed_list`.
+    pub fn seed_list(&self) -> String {
+        self.signed_seed_list(|protocol| protocol != Protocol::Noise)
+    }
+
+    /// Returns the `noise://` seeds in the same format as `seed_list`.
+    pub fn noise_seed_list(&self) -> String {
+        self.signed_seed_list(|protocol| protocol == Protocol::Noise)
+    }
+
+    fn signed_seed_list<F: Fn(Protocol) -> bool>(&self, filter: F) -> String {
+        let seed_strings: Vec<String> = self.seeds().iter()
+            .filter(|peer_address| filter(peer_address.protocol()))
+            .filter_map(|peer_address| {
+                // Seed strings are only defined for seed addresses, which have no timestamp.
+                let mut seed_address = peer_address.as_ref().clone();
+                seed_address.timestamp = 0;
+                seed_address.to_seed_string()
+            })
+            .collect();
+        let data = seed_strings.join("\n");
+        let signature = self.network.network_config.key_pair().sign(data.as_bytes());
+
+        format!("# Generated at {}\n{}\n{}\n",
+            systemtime_to_timestamp(SystemTime::now()),
+            data,
+            hex::encode(&signature.to_bytes()[..]))
+    }
+
+    /// Serves the seed list via HTTP on `addr`: `/` serves `seed_list`, `/noise` serves
+    /// `noise_seed_list`.
+    pub fn serve(&self, addr: &SocketAddr) -> Result<impl Future<Item=(), Error=()>, Error> {
+        let weak = self.self_weak.clone();
+        let server = Server::try_bind(addr)?
+            .serve(move || {
+                let weak = weak.clone();
+                service_fn_ok(move |request: Request<Body>| {
+                    match weak.upgrade() {
+                        Some(this) => this.handle_request(&request),
+                        None => Self::response(StatusCode::SERVICE_UNAVAILABLE, String::new()),
+                    }
+                })
+            })
+            .map_err(|e| error!("Seed server failed: {}", e));
+        Ok(server)
+    }
+
+    fn handle_request(&self, request: &Request<Body>) -> Response<Body> {
+        if *request.method() != Method::GET {
+            return Self::response(StatusCode::METHOD_NOT_ALLOWED, String::new());
+        }
+        match request.uri().path() {
+            "/" => Self::response(StatusCode::OK, self.seed_list()),
+            "/noise" => Self::response(StatusCode::OK, self.noise_seed_list()),
+            _ => Self::response(StatusCode::NOT_FOUND, String::new()),
+        }
+    }
+
+    fn response(status: StatusCode, body: String) -> Response<Body> {
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from(body))
+            .expect("Failed to build response")
+    }
+}
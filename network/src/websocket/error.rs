@@ -1,5 +1,6 @@
 use failure::Fail;
 use native_tls::Error as TlsError;
+use snow::Error as NoiseError;
 use tokio::io::Error as IoError;
 use tokio::timer::Error as TimerError;
 use tungstenite::error::Error as WsError;
@@ -33,6 +34,12 @@ pub enum Error {
     NetAddressMissing(#[cause] IoError),
     #[fail(display = "Message format is incorrect and could not be parsed correctly")]
     InvalidMessageFormat,
+    #[fail(display = "Received a compressed message, but compression wasn't negotiated")]
+    UnexpectedCompression,
+    #[fail(display = "Noise handshake failed: {}", _0)]
+    NoiseError(#[cause] NoiseError),
+    #[fail(display = "Peer key in Noise handshake does not match")]
+    NoiseAuthenticationFailed,
 }
 
 impl From<IoError> for Error {
@@ -47,6 +54,12 @@ impl From<WsError> for Error {
     }
 }
 
+impl From<NoiseError> for Error {
+    fn from(e: NoiseError) -> Self {
+        Error::NoiseError(e)
+    }
+}
+
 impl From<SerializingError> for Error {
     fn from(e: SerializingError) -> Self {
         Error::ParseError(e)
@@ -74,6 +87,8 @@ pub enum ConnectError {
     WebSocket(#[cause] Error),
     #[fail(display = "Could not parse URI to connect to: {}", _0)]
     InvalidUri(#[cause] ParseError),
+    #[fail(display = "No loopback node is listening on {}", _0)]
+    Unreachable(String),
 }
 
 
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+use futures::prelude::*;
+use futures::sync::mpsc;
+use parking_lot::{Mutex, RwLock};
+use tungstenite::error::Error as WebSocketError;
+use tungstenite::protocol::Message as WebSocketMessage;
+
+use network_primitives::address::net_address::NetAddress;
+use network_primitives::address::peer_address::{PeerAddress, PeerAddressType};
+use utils::observer::PassThroughNotifier;
+
+use crate::connection::{AddressInfo, NetworkConnection};
+use crate::connection::traffic::TrafficShaper;
+use crate::websocket::{NimiqMessageStream, SharedNimiqMessageStream};
+use crate::websocket::error::{ConnectError, ServerStartError};
+use crate::websocket::websocket_connector::WebSocketConnectorEvent;
+
+type ConnectorNotifier = Arc<RwLock<PassThroughNotifier<'static, WebSocketConnectorEvent>>>;
+
+/// One end of an in-process connection that stands in for a WebSocket.
+/// Messages are passed over unbounded channels, so sending never blocks.
+pub struct LoopbackLayer {
+    sender: mpsc::UnboundedSender<WebSocketMessage>,
+    receiver: mpsc::UnboundedReceiver<WebSocketMessage>,
+    closing: bool,
+}
+
+impl LoopbackLayer {
+    /// Creates two connected ends.
+    pub fn pair() -> (LoopbackLayer, LoopbackLayer) {
+        let (tx1, rx1) = mpsc::unbounded();
+        let (tx2, rx2) = mpsc::unbounded();
+        (
+            LoopbackLayer { sender: tx1, receiver: rx2, closing: false },
+            LoopbackLayer { sender: tx2, receiver: rx1, closing: false },
+        )
+    }
+}
+
+impl Stream for LoopbackLayer {
+    type Item = WebSocketMessage;
+    type Error = WebSocketError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.receiver.poll() {
+            Ok(Async::Ready(Some(WebSocketMessage::Close(frame)))) => {
+                // Like a WebSocket, reply to a close handshake initiated by the peer.
+                if !self.closing {
+                    self.closing = true;
+                    // The peer may already be gone, which is fine.
+                    let _ = self.sender.unbounded_send(WebSocketMessage::Close(frame.clone()));
+                }
+                Ok(Async::Ready(Some(WebSocketMessage::Close(frame))))
+            },
+            Ok(r_async) => Ok(r_async),
+            Err(()) => Err(WebSocketError::ConnectionClosed),
+        }
+    }
+}
+
+impl Sink for LoopbackLayer {
+    type SinkItem = WebSocketMessage;
+    type SinkError = WebSocketError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if let WebSocketMessage::Close(_) = item {
+            self.closing = true;
+        }
+        self.sender.unbounded_send(item).map_err(|_| WebSocketError::ConnectionClosed)?;
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[derive(Default)]
+struct LoopbackHubState {
+    listeners: HashMap<(String, u16), (ConnectorNotifier, Arc<TrafficShaper>)>,
+    net_addresses: HashMap<(String, u16), NetAddress>,
+}
+
+impl LoopbackHubState {
+    fn net_address(&mut self, host: &str, port: u16) -> NetAddress {
+        let next_index = self.net_addresses.len() as u32 + 1;
+        *self.net_addresses.entry((host.to_string(), port)).or_insert_with(|| {
+            // Every node gets its own /24 subnet, so that the per subnet connection limits don't kick in.
+            NetAddress::IPv4(Ipv4Addr::from(0x0a00_0001 | (next_index << 8)))
+        })
+    }
+}
+
+/// Connects nodes running in the same process without opening any sockets.
+///
+/// Nodes sharing a hub use a `ProtocolConfig::Loopback` and are addressed like `ws` nodes by
+/// their host and port. Hosts should be host names rather than IPs, so that the addresses
+/// pass the reachability checks on `addr` messages.
+#[derive(Clone, Default)]
+pub struct LoopbackHub {
+    state: Arc<Mutex<LoopbackHubState>>,
+}
+
+impl LoopbackHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pseudo net address assigned to the node at `host:port`.
+    pub fn net_address(&self, host: &str, port: u16) -> NetAddress {
+        self.state.lock().net_address(host, port)
+    }
+
+    pub(crate) fn listen(&self, host: &str, port: u16, notifier: ConnectorNotifier, traffic_shaper: Arc<TrafficShaper>) -> Result<(), ServerStartError> {
+        let mut state = self.state.lock();
+        let key = (host.to_string(), port);
+        if state.listeners.contains_key(&key) {
+            return Err(ServerStartError::IoError(io::Error::new(io::ErrorKind::AddrInUse, format!("{}:{}", host, port))));
+        }
+        state.net_address(host, port);
+        state.listeners.insert(key, (notifier, traffic_shaper));
+        Ok(())
+    }
+
+    /// Connects the node at `host:port` to `peer_address` and hands both ends
+    /// to the respective connectors, just like an accepted WebSocket connection.
+    pub(crate) fn connect(&self, host: &str, port: u16, peer_address: Arc<PeerAddress>, notifier: &ConnectorNotifier, traffic_shaper: Arc<TrafficShaper>) -> Result<(), ConnectError> {
+        let ((remote_notifier, remote_traffic_shaper), local_net_address, remote_net_address) = {
+            let mut state = self.state.lock();
+            let (remote_host, remote_port) = match &peer_address.ty {
+                PeerAddressType::Ws(host, port) | PeerAddressType::Wss(host, port) => (host.clone(), *port),
+                _ => return Err(ConnectError::ProtocolMismatch),
+            };
+            let remote_notifier = state.listeners.get(&(remote_host.clone(), remote_port))
+                .cloned()
+                .ok_or_else(|| ConnectError::Unreachable(format!("{}:{}", remote_host, remote_port)))?;
+            (remote_notifier, state.net_address(host, port), state.net_address(&remote_host, remote_port))
+        };
+
+        let (outbound_layer, inbound_layer) = LoopbackLayer::pair();
+
+        let inbound_stream: SharedNimiqMessageStream = NimiqMessageStream::new_loopback(inbound_layer, local_net_address, false).into();
+        let (nc, ncfut) = NetworkConnection::new_connection_setup(inbound_stream, AddressInfo::new(Some(Arc::new(local_net_address)), None), remote_traffic_shaper);
+        remote_notifier.read().notify(WebSocketConnectorEvent::Connection(nc));
+        tokio::spawn(ncfut);
+
+        let outbound_stream: SharedNimiqMessageStream = NimiqMessageStream::new_loopback(outbound_layer, remote_net_address, true).into();
+        let (nc, ncfut) = NetworkConnection::new_connection_setup(outbound_stream, AddressInfo::new(Some(Arc::new(remote_net_address)), Some(peer_address)), traffic_shaper);
+        notifier.read().notify(WebSocketConnectorEvent::Connection(nc));
+        tokio::spawn(ncfut);
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LoopbackHub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "LoopbackHub {{ nodes: {} }}", self.state.lock().listeners.len())
+    }
+}
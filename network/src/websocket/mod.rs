@@ -4,6 +4,8 @@ use network_messages::Message as NimiqMessage;
 
 pub use self::client::nimiq_connect_async;
 pub use self::error::Error;
+pub use self::loopback::LoopbackHub;
+pub use self::noise::{nimiq_noise_accept_async, nimiq_noise_connect_async};
 pub use self::server::nimiq_accept_async;
 pub use self::shared_stream::SharedNimiqMessageStream;
 pub use self::stream::NimiqMessageStream;
@@ -11,6 +13,8 @@ pub use self::stream::NimiqMessageStream;
 pub mod websocket_connector;
 mod reverse_proxy;
 pub mod error;
+pub mod loopback;
+pub mod noise;
 pub mod public_state;
 pub mod stream;
 pub mod client;
@@ -0,0 +1,303 @@
+use std::borrow::Cow;
+use std::net::SocketAddr;
+
+use bytes::{BufMut, BytesMut};
+use futures::future;
+use futures::prelude::*;
+use futures::try_ready;
+use snow::{Builder, HandshakeState, TransportState};
+use tokio::codec::{Decoder, Encoder, Framed};
+use tokio::io::{read_exact, write_all};
+use tokio::net::TcpStream;
+use tungstenite::error::Error as WebSocketError;
+use tungstenite::protocol::CloseFrame;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::Message as WebSocketMessage;
+
+use keys::{KeyPair, PublicKey};
+use network_primitives::address::net_address::NetAddress;
+
+use crate::websocket::error::Error;
+use crate::websocket::NimiqMessageStream;
+
+/// Both sides transmit their static keys, which are then bound to their peer keys.
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_SHA256";
+const NOISE_PROLOGUE: &[u8] = b"nimiq";
+/// Noise limits messages to 64 KiB, hence the frame length fits into a `u16`.
+const MAX_FRAME_SIZE: usize = 65535;
+const TAG_SIZE: usize = 16;
+
+const FRAME_TYPE_BINARY: u8 = 0;
+const FRAME_TYPE_CLOSE: u8 = 1;
+
+type BoxFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// Encrypts and decrypts the WebSocket messages of a `NimiqMessageStream` with the Noise session
+/// keys. Each message is sent as a length-prefixed frame. The plaintext starts with the frame type,
+/// close frames carry the close code and reason.
+pub struct NoiseCodec {
+    transport: TransportState,
+}
+
+impl NoiseCodec {
+    fn noise_error(e: snow::Error) -> WebSocketError {
+        WebSocketError::Protocol(Cow::Owned(format!("Noise: {}", e)))
+    }
+}
+
+impl Decoder for NoiseCodec {
+    type Item = WebSocketMessage;
+    type Error = WebSocketError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < 2 {
+            return Ok(None);
+        }
+        let len = usize::from(u16::from_be_bytes([src[0], src[1]]));
+        if src.len() < 2 + len {
+            src.reserve(2 + len - src.len());
+            return Ok(None);
+        }
+        src.advance(2);
+        let frame = src.split_to(len);
+
+        let mut plaintext = vec![0u8; len];
+        let len = self.transport.read_message(&frame, &mut plaintext).map_err(Self::noise_error)?;
+        plaintext.truncate(len);
+
+        match plaintext.first() {
+            Some(&FRAME_TYPE_BINARY) => {
+                plaintext.remove(0);
+                Ok(Some(WebSocketMessage::Binary(plaintext)))
+            },
+            Some(&FRAME_TYPE_CLOSE) => {
+                let frame = if plaintext.len() >= 3 {
+                    Some(CloseFrame {
+                        code: CloseCode::from(u16::from_be_bytes([plaintext[1], plaintext[2]])),
+                        reason: Cow::Owned(String::from_utf8_lossy(&plaintext[3..]).into_owned()),
+                    })
+                } else {
+                    None
+                };
+                Ok(Some(WebSocketMessage::Close(frame)))
+            },
+            _ => Err(WebSocketError::Protocol(Cow::Borrowed("Invalid Noise frame type"))),
+        }
+    }
+}
+
+impl Encoder for NoiseCodec {
+    type Item = WebSocketMessage;
+    type Error = WebSocketError;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut plaintext = Vec::new();
+        match item {
+            WebSocketMessage::Close(frame) => {
+                plaintext.push(FRAME_TYPE_CLOSE);
+                if let Some(frame) = frame {
+                    plaintext.extend_from_slice(&u16::from(frame.code).to_be_bytes());
+                    plaintext.extend_from_slice(frame.reason.as_bytes());
+                }
+            },
+            item => {
+                plaintext.push(FRAME_TYPE_BINARY);
+                plaintext.extend(item.into_data());
+            },
+        }
+        if plaintext.len() + TAG_SIZE > MAX_FRAME_SIZE {
+            return Err(WebSocketError::Capacity(Cow::Borrowed("Noise frame too large")));
+        }
+
+        let mut frame = vec![0u8; plaintext.len() + TAG_SIZE];
+        let len = self.transport.write_message(&plaintext, &mut frame).map_err(Self::noise_error)?;
+        dst.reserve(2 + len);
+        dst.put_u16_be(len as u16);
+        dst.extend_from_slice(&frame[..len]);
+        Ok(())
+    }
+}
+
+/// The transport of a `NimiqMessageStream` over an established Noise session.
+/// Behaves like a WebSocket, i.e. a close frame initiated by the peer is answered.
+pub struct NoiseLayer {
+    inner: Framed<TcpStream, NoiseCodec>,
+    close_reply: Option<WebSocketMessage>,
+    closing: bool,
+}
+
+impl NoiseLayer {
+    fn new(stream: TcpStream, transport: TransportState) -> Self {
+        NoiseLayer {
+            inner: Framed::new(stream, NoiseCodec { transport }),
+            close_reply: None,
+            closing: false,
+        }
+    }
+
+    fn send_close_reply(&mut self) -> Poll<(), WebSocketError> {
+        if let Some(msg) = self.close_reply.take() {
+            if let AsyncSink::NotReady(msg) = self.inner.start_send(msg)? {
+                self.close_reply = Some(msg);
+                return Ok(Async::NotReady);
+            }
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl Stream for NoiseLayer {
+    type Item = WebSocketMessage;
+    type Error = WebSocketError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.send_close_reply()?.is_ready() {
+            // The peer may already be gone, which is fine.
+            let _ = self.inner.poll_complete();
+        }
+
+        match self.inner.poll()? {
+            Async::Ready(Some(WebSocketMessage::Close(frame))) => {
+                if !self.closing {
+                    self.closing = true;
+                    self.close_reply = Some(WebSocketMessage::Close(frame.clone()));
+                    if self.send_close_reply()?.is_ready() {
+                        let _ = self.inner.poll_complete();
+                    }
+                }
+                Ok(Async::Ready(Some(WebSocketMessage::Close(frame))))
+            },
+            r_async => Ok(r_async),
+        }
+    }
+}
+
+impl Sink for NoiseLayer {
+    type SinkItem = WebSocketMessage;
+    type SinkError = WebSocketError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if let WebSocketMessage::Close(_) = item {
+            self.closing = true;
+        }
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.send_close_reply());
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.send_close_reply());
+        self.inner.close()
+    }
+}
+
+fn builder(private_key: &[u8]) -> Builder {
+    Builder::new(NOISE_PARAMS.parse().expect("Invalid Noise parameters"))
+        .prologue(NOISE_PROLOGUE)
+        .local_private_key(private_key)
+}
+
+fn write_frame(stream: TcpStream, frame: Vec<u8>) -> impl Future<Item = TcpStream, Error = Error> {
+    let mut buf = Vec::with_capacity(2 + frame.len());
+    buf.extend_from_slice(&(frame.len() as u16).to_be_bytes());
+    buf.extend(frame);
+    write_all(stream, buf)
+        .map(|(stream, _)| stream)
+        .map_err(Error::from)
+}
+
+fn read_frame(stream: TcpStream) -> impl Future<Item = (TcpStream, Vec<u8>), Error = Error> {
+    read_exact(stream, [0u8; 2])
+        .and_then(|(stream, len)| read_exact(stream, vec![0u8; usize::from(u16::from_be_bytes(len))]))
+        .map_err(Error::from)
+}
+
+fn write_handshake_message(stream: TcpStream, mut handshake: HandshakeState, payload: &[u8]) -> BoxFuture<(TcpStream, HandshakeState)> {
+    let mut frame = vec![0u8; MAX_FRAME_SIZE];
+    let len = match handshake.write_message(payload, &mut frame) {
+        Ok(len) => len,
+        Err(e) => return Box::new(future::err(e.into())),
+    };
+    frame.truncate(len);
+    Box::new(write_frame(stream, frame).map(move |stream| (stream, handshake)))
+}
+
+fn read_handshake_message(stream: TcpStream, mut handshake: HandshakeState) -> BoxFuture<(TcpStream, HandshakeState, Vec<u8>)> {
+    Box::new(read_frame(stream).and_then(move |(stream, frame)| {
+        let mut payload = vec![0u8; frame.len()];
+        let len = handshake.read_message(&frame, &mut payload)?;
+        payload.truncate(len);
+        Ok((stream, handshake, payload))
+    }))
+}
+
+/// Checks that the peer key sent in `payload` belongs to the static key used in the handshake.
+fn authenticate(handshake: &HandshakeState, payload: &[u8]) -> Result<PublicKey, Error> {
+    let public_key = PublicKey::from_bytes(payload).map_err(|_| Error::NoiseAuthenticationFailed)?;
+    match (public_key.to_x25519(), handshake.get_remote_static()) {
+        (Some(expected), Some(remote_static)) if expected[..] == remote_static[..] => Ok(public_key),
+        _ => Err(Error::NoiseAuthenticationFailed),
+    }
+}
+
+fn into_message_stream(stream: TcpStream, handshake: HandshakeState, outbound: bool, remote_public_key: PublicKey) -> Result<NimiqMessageStream, Error> {
+    let net_address = match stream.peer_addr().map_err(Error::NetAddressMissing)? {
+        SocketAddr::V4(addr) => NetAddress::IPv4(*addr.ip()),
+        SocketAddr::V6(addr) => NetAddress::IPv6(*addr.ip()),
+    };
+    let transport = handshake.into_transport_mode()?;
+    Ok(NimiqMessageStream::new_noise(NoiseLayer::new(stream, transport), net_address, outbound, remote_public_key))
+}
+
+/// Connects to `addr` and runs the initiator side of the Noise handshake.
+/// Fails unless the peer proves that it holds the private key for `remote_public_key`.
+pub fn nimiq_noise_connect_async(addr: SocketAddr, key_pair: &KeyPair, remote_public_key: PublicKey) -> BoxFuture<NimiqMessageStream> {
+    let private_key = key_pair.private.to_x25519();
+    let handshake = match builder(private_key.as_bytes()).build_initiator() {
+        Ok(handshake) => handshake,
+        Err(e) => return Box::new(future::err(e.into())),
+    };
+    let local_public_key = key_pair.public;
+
+    Box::new(TcpStream::connect(&addr)
+        .map_err(Error::from)
+        // -> e
+        .and_then(|stream| write_handshake_message(stream, handshake, &[]))
+        // <- e, ee, s, es
+        .and_then(|(stream, handshake)| read_handshake_message(stream, handshake))
+        .and_then(move |(stream, handshake, payload)| {
+            let public_key = authenticate(&handshake, &payload)?;
+            if public_key != remote_public_key {
+                return Err(Error::NoiseAuthenticationFailed);
+            }
+            Ok((stream, handshake))
+        })
+        // -> s, se
+        .and_then(move |(stream, handshake)| write_handshake_message(stream, handshake, local_public_key.as_bytes()))
+        .and_then(move |(stream, handshake)| into_message_stream(stream, handshake, true, remote_public_key)))
+}
+
+/// Runs the responder side of the Noise handshake on an accepted connection.
+/// The resulting stream carries the authenticated peer key of the remote node.
+pub fn nimiq_noise_accept_async(stream: TcpStream, key_pair: &KeyPair) -> BoxFuture<NimiqMessageStream> {
+    let private_key = key_pair.private.to_x25519();
+    let handshake = match builder(private_key.as_bytes()).build_responder() {
+        Ok(handshake) => handshake,
+        Err(e) => return Box::new(future::err(e.into())),
+    };
+    let local_public_key = key_pair.public;
+
+    // -> e
+    Box::new(read_handshake_message(stream, handshake)
+        // <- e, ee, s, es
+        .and_then(move |(stream, handshake, _)| write_handshake_message(stream, handshake, local_public_key.as_bytes()))
+        // -> s, se
+        .and_then(|(stream, handshake)| read_handshake_message(stream, handshake))
+        .and_then(|(stream, handshake, payload)| {
+            let remote_public_key = authenticate(&handshake, &payload)?;
+            into_message_stream(stream, handshake, false, remote_public_key)
+        }))
+}
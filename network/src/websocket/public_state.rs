@@ -1,6 +1,7 @@
-#[cfg(feature = "metrics")]
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
+use keys::PublicKey;
 use network_primitives::address::net_address::NetAddress;
 
 #[cfg(feature = "metrics")]
@@ -13,6 +14,12 @@ pub struct PublicStreamInfo {
     // Constant info.
     pub net_address: NetAddress,
     pub outbound: bool,
+    /// The peer key the remote node authenticated with, if the transport does that.
+    pub remote_public_key: Option<PublicKey>,
+
+    // Mutable info.
+    /// Whether outgoing messages may be compressed, set once the peer advertised support for it.
+    pub compression: Arc<AtomicBool>,
 
     #[cfg(feature = "metrics")]
     pub network_metrics: Arc<NetworkMetrics>,
@@ -35,6 +42,8 @@ impl PublicStreamInfo {
         PublicStreamInfo {
             net_address,
             outbound,
+            remote_public_key: None,
+            compression: Arc::new(AtomicBool::new(false)),
 
             #[cfg(feature = "metrics")]
             network_metrics: Arc::new(NetworkMetrics::default()),
@@ -1,8 +1,9 @@
-#[cfg(feature = "metrics")]
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 use futures::prelude::*;
 
+use keys::PublicKey;
 use utils::locking::MultiLock;
 use network_primitives::address::net_address::NetAddress;
 
@@ -28,6 +29,14 @@ impl SharedNimiqMessageStream {
         self.state.outbound
     }
 
+    pub fn remote_public_key(&self) -> Option<&PublicKey> {
+        self.state.remote_public_key.as_ref()
+    }
+
+    pub fn compression(&self) -> &Arc<AtomicBool> {
+        &self.state.compression
+    }
+
     #[cfg(feature = "metrics")]
     pub fn network_metrics(&self) -> &Arc<NetworkMetrics> {
         &self.state.network_metrics
@@ -4,6 +4,7 @@ use std::fmt::Debug;
 use std::net;
 #[cfg(feature = "metrics")]
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
 use futures::prelude::*;
 use tokio::net::TcpStream;
@@ -14,17 +15,69 @@ use tungstenite::protocol::CloseFrame;
 use tungstenite::protocol::Message as WebSocketMessage;
 
 use beserial::{Deserialize, Serialize};
+use keys::PublicKey;
 use network_messages::Message as NimiqMessage;
 use network_primitives::address::net_address::NetAddress;
 
 #[cfg(feature = "metrics")]
 use crate::network_metrics::NetworkMetrics;
 use crate::websocket::error::Error;
+use crate::websocket::loopback::LoopbackLayer;
 use crate::websocket::Message;
+use crate::websocket::noise::NoiseLayer;
 use crate::websocket::public_state::PublicStreamInfo;
 
 type WebSocketLayer = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
+/// The layer that carries the WebSocket messages of a `NimiqMessageStream`.
+enum TransportLayer {
+    WebSocket(WebSocketLayer),
+    Loopback(LoopbackLayer),
+    Noise(NoiseLayer),
+}
+
+impl Stream for TransportLayer {
+    type Item = WebSocketMessage;
+    type Error = WebSocketError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self {
+            TransportLayer::WebSocket(inner) => inner.poll(),
+            TransportLayer::Loopback(inner) => inner.poll(),
+            TransportLayer::Noise(inner) => inner.poll(),
+        }
+    }
+}
+
+impl Sink for TransportLayer {
+    type SinkItem = WebSocketMessage;
+    type SinkError = WebSocketError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        match self {
+            TransportLayer::WebSocket(inner) => inner.start_send(item),
+            TransportLayer::Loopback(inner) => inner.start_send(item),
+            TransportLayer::Noise(inner) => inner.start_send(item),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        match self {
+            TransportLayer::WebSocket(inner) => inner.poll_complete(),
+            TransportLayer::Loopback(inner) => inner.poll_complete(),
+            TransportLayer::Noise(inner) => inner.poll_complete(),
+        }
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        match self {
+            TransportLayer::WebSocket(inner) => inner.close(),
+            TransportLayer::Loopback(inner) => inner.close(),
+            TransportLayer::Noise(inner) => inner.close(),
+        }
+    }
+}
+
 /// This enum describes the current state of the connection.
 #[derive(Clone, Debug)]
 pub enum WebSocketState {
@@ -58,7 +111,7 @@ const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 10; // 10 mb
 /// and instead sends/receives our own Message type encapsulating Nimiq messages.
 pub struct NimiqMessageStream {
     // Internal state.
-    inner: WebSocketLayer,
+    inner: TransportLayer,
     receiving_tag: u8,
     sending_tag: u8,
     ws_queue: VecDeque<WebSocketMessage>,
@@ -72,19 +125,38 @@ pub struct NimiqMessageStream {
 impl NimiqMessageStream {
     pub(super) fn new(ws_socket: WebSocketLayer, outbound: bool) -> Result<Self, Error> {
         let peer_addr = ws_socket.peer_addr().map_err(Error::NetAddressMissing)?;
-        Ok(NimiqMessageStream {
-            inner: ws_socket,
+        let net_address = match peer_addr.ip() {
+            net::IpAddr::V4(ip4) => NetAddress::IPv4(ip4),
+            net::IpAddr::V6(ip6) => NetAddress::IPv6(ip6),
+        };
+        Ok(Self::with_layer(TransportLayer::WebSocket(ws_socket), net_address, outbound, None))
+    }
+
+    /// Creates a stream over an in-process loopback layer.
+    /// As there is no socket, the caller has to provide the peer's net address.
+    pub fn new_loopback(layer: LoopbackLayer, net_address: NetAddress, outbound: bool) -> Self {
+        Self::with_layer(TransportLayer::Loopback(layer), net_address, outbound, None)
+    }
+
+    /// Creates a stream over an established Noise session with the peer that authenticated
+    /// itself with `remote_public_key`.
+    pub(super) fn new_noise(layer: NoiseLayer, net_address: NetAddress, outbound: bool, remote_public_key: PublicKey) -> Self {
+        Self::with_layer(TransportLayer::Noise(layer), net_address, outbound, Some(remote_public_key))
+    }
+
+    fn with_layer(inner: TransportLayer, net_address: NetAddress, outbound: bool, remote_public_key: Option<PublicKey>) -> Self {
+        let mut public_state = PublicStreamInfo::new(net_address, outbound);
+        public_state.remote_public_key = remote_public_key;
+        NimiqMessageStream {
+            inner,
             receiving_tag: 254,
             sending_tag: 0,
             ws_queue: VecDeque::new(),
             msg_buf: None,
             state: WebSocketState::Active,
 
-            public_state: PublicStreamInfo::new(match peer_addr.ip() {
-                net::IpAddr::V4(ip4) => NetAddress::IPv4(ip4),
-                net::IpAddr::V6(ip6) => NetAddress::IPv6(ip6),
-            }, outbound),
-        })
+            public_state,
+        }
     }
 
     pub fn state(&self) -> &PublicStreamInfo {
@@ -117,7 +189,11 @@ impl Sink for NimiqMessageStream {
         let (serialized_msg, tag) = match item {
             // A message needs to be serialized and send with a new tag.
             Message::Message(msg) => {
-                let serialized_msg = msg.serialize_to_vec();
+                let serialized_msg = if self.public_state.compression.load(Ordering::Acquire) {
+                    msg.serialize_compressed()
+                } else {
+                    msg.serialize_to_vec()
+                };
                 (serialized_msg, self.next_tag())
             },
             // If sending of a message was interrupted due to a full queue
@@ -222,6 +298,13 @@ impl NimiqMessageStream {
             // Detect if this is a new message.
             if self.msg_buf.is_none() {
 
+                // Only peers that advertised compression support in their version message may send
+                // compressed messages.
+                if NimiqMessage::peek_compressed(chunk).unwrap_or(false) && !self.public_state.compression.load(Ordering::Acquire) {
+                    error!("Received a compressed message, but compression wasn't negotiated");
+                    return Err(Error::UnexpectedCompression);
+                }
+
                 if let Ok(msg_size) = NimiqMessage::peek_length(chunk) {
                     if msg_size > MAX_MESSAGE_SIZE {
                         error!("Max message size exceeded ({} > {})", msg_size, MAX_MESSAGE_SIZE);
@@ -1,5 +1,6 @@
 use std::fs::File;
-use std::net::SocketAddr;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
@@ -19,16 +20,21 @@ use tungstenite::stream::Mode;
 use url::Url;
 
 use network_primitives::address::PeerAddress;
+use network_primitives::address::peer_address::PeerAddressType;
 use network_primitives::protocol::ProtocolFlags;
 use utils::observer::PassThroughNotifier;
 
 use crate::connection::{AddressInfo, NetworkConnection};
 use crate::connection::close_type::CloseType;
+use crate::connection::traffic::TrafficShaper;
 use crate::network_config::{NetworkConfig, ProtocolConfig};
 use crate::websocket::{
     Error,
+    LoopbackHub,
     nimiq_accept_async,
     nimiq_connect_async,
+    nimiq_noise_accept_async,
+    nimiq_noise_connect_async,
     NimiqMessageStream,
     reverse_proxy::ReverseProxyCallback,
     reverse_proxy::ToCallback,
@@ -122,6 +128,7 @@ fn setup_tls_acceptor(identity_file: Option<String>, identity_passphrase: Option
 
 pub struct WebSocketConnector {
     network_config: Arc<NetworkConfig>,
+    traffic_shaper: Arc<TrafficShaper>,
     pub notifier: Arc<RwLock<PassThroughNotifier<'static, WebSocketConnectorEvent>>>,
 }
 
@@ -132,11 +139,17 @@ impl WebSocketConnector {
 
     pub fn new(network_config: Arc<NetworkConfig>) -> WebSocketConnector {
         WebSocketConnector {
+            traffic_shaper: Arc::new(TrafficShaper::new(network_config.traffic_limits())),
             network_config,
             notifier: Arc::new(RwLock::new(PassThroughNotifier::new())),
         }
     }
 
+    /// The traffic accounting and limits shared by all connections.
+    pub fn traffic_shaper(&self) -> &Arc<TrafficShaper> {
+        &self.traffic_shaper
+    }
+
     pub fn start(&self) -> Result<(), ServerStartError> {
         let protocol_config = self.network_config.protocol_config();
 
@@ -147,6 +160,12 @@ impl WebSocketConnector {
             ProtocolConfig::Wss{port, identity_file, identity_password, ..} => {
                 (*port, Some(identity_file.to_string()), Some(identity_password.to_string()), Mode::Tls, None)
             },
+            ProtocolConfig::Loopback{hub, host, port} => {
+                return hub.listen(host, *port, Arc::clone(&self.notifier), Arc::clone(&self.traffic_shaper));
+            },
+            ProtocolConfig::Noise{port, ..} => {
+                return self.start_noise(*port);
+            },
             config => return Err(ServerStartError::UnsupportedProtocol(format!("{:?}", config))),
         };
 
@@ -155,6 +174,7 @@ impl WebSocketConnector {
         let addr = SocketAddr::new("::".parse().unwrap(), port);
         let socket = TcpListener::bind(&addr).map_err(ServerStartError::IoError)?;
         let notifier = Arc::clone(&self.notifier);
+        let traffic_shaper = Arc::clone(&self.traffic_shaper);
 
         let srv = socket.incoming()
             .sleep_on_error(Self::WAIT_TIME_ON_ERROR)
@@ -162,6 +182,7 @@ impl WebSocketConnector {
                 let reverse_proxy_config = reverse_proxy_config.clone();
 
                 let notifier = Arc::clone(&notifier);
+                let traffic_shaper = Arc::clone(&traffic_shaper);
                 let acceptor = tls_acceptor.clone();
                 wrap_stream(tcp, acceptor, mode).and_then(move |ss| {
                     let callback = ReverseProxyCallback::new(reverse_proxy_config.clone());
@@ -170,7 +191,7 @@ impl WebSocketConnector {
                         // Only accept connection, if net address could be determined.
                         if let Some(net_address) = callback.check_reverse_proxy(shared_stream.net_address()) {
                             let net_address = Some(Arc::new(net_address));
-                            let (nc, ncfut) = NetworkConnection::new_connection_setup(shared_stream, AddressInfo::new(net_address, None));
+                            let (nc, ncfut) = NetworkConnection::new_connection_setup(shared_stream, AddressInfo::new(net_address, None), traffic_shaper);
                             notifier.read().notify(WebSocketConnectorEvent::Connection(nc));
                             tokio::spawn(ncfut);
                         } else {
@@ -195,31 +216,84 @@ impl WebSocketConnector {
         Ok(())
     }
 
-    pub fn connect(&self, peer_address: Arc<PeerAddress>) -> Result<Arc<ConnectionHandle>, ConnectError> {
+    /// Accepts plain TCP connections and runs the Noise handshake on them.
+    fn start_noise(&self, port: u16) -> Result<(), ServerStartError> {
+        let addr = SocketAddr::new("::".parse().unwrap(), port);
+        let socket = TcpListener::bind(&addr).map_err(ServerStartError::IoError)?;
         let notifier = Arc::clone(&self.notifier);
+        let traffic_shaper = Arc::clone(&self.traffic_shaper);
+        let key_pair = self.network_config.key_pair().clone();
+
+        let srv = socket.incoming()
+            .sleep_on_error(Self::WAIT_TIME_ON_ERROR)
+            .map(move |tcp| {
+                let notifier = Arc::clone(&notifier);
+                let traffic_shaper = Arc::clone(&traffic_shaper);
+                // Don't let peers hold on to a connection without completing the handshake.
+                nimiq_noise_accept_async(tcp, &key_pair)
+                    .timeout(Self::CONNECT_TIMEOUT)
+                    .map(move |msg_stream: NimiqMessageStream| {
+                        let shared_stream: SharedNimiqMessageStream = msg_stream.into();
+                        let net_address = Some(Arc::new(shared_stream.net_address()));
+                        let (nc, ncfut) = NetworkConnection::new_connection_setup(shared_stream, AddressInfo::new(net_address, None), traffic_shaper);
+                        notifier.read().notify(WebSocketConnectorEvent::Connection(nc));
+                        tokio::spawn(ncfut);
+                    })
+                    .or_else(|err| {
+                        error!("Could not accept connection: {}", err);
+                        // Do not stop the server on inner connection errors!
+                        future::ok(())
+                    })
+            })
+            .listen(Self::CONNECTIONS_MAX)
+            .then(#[allow(unreachable_code)] |_result| {
+                panic!("Noise stream ended unexpectedly");
+                _result
+            });
 
+        tokio::spawn(srv);
+        Ok(())
+    }
+
+    pub fn connect(&self, peer_address: Arc<PeerAddress>) -> Result<Arc<ConnectionHandle>, ConnectError> {
         if !self.network_config.protocol_mask().contains(ProtocolFlags::from(peer_address.protocol())) {
             return Err(ConnectError::ProtocolMismatch);
         }
 
+        if let ProtocolConfig::Loopback{hub, host, port} = self.network_config.protocol_config() {
+            return Ok(self.connect_loopback(hub.clone(), host.clone(), *port, peer_address));
+        }
+
         // NOTE: We're not checking if we are already connecting to a peer here because
         // that check is already done by the ConnectionPool in the upper layer and doing
         // it here would imply creating data structures just for that (unlike the JavaScript
         // implementation where the data structures are there for something else and then you
         // get this check "for free")
 
+        if let PeerAddressType::Noise(host, port) = &peer_address.ty {
+            return Ok(self.connect_noise(host.clone(), *port, peer_address));
+        }
+
         let url = Url::parse(&peer_address.as_uri().to_string()).map_err(ConnectError::InvalidUri)?;
+        Ok(self.spawn_connect(nimiq_connect_async(url), peer_address))
+    }
+
+    /// Runs `connect` with a timeout and hands the resulting stream to the upper layer.
+    fn spawn_connect<F>(&self, connect: F, peer_address: Arc<PeerAddress>) -> Arc<ConnectionHandle>
+        where F: Future<Item=NimiqMessageStream, Error=Error> + Send + 'static {
+        let notifier = Arc::clone(&self.notifier);
         let error_notifier = Arc::clone(&self.notifier);
         let error_peer_address = Arc::clone(&peer_address);
         let (tx, rx) = oneshot::channel::<CloseType>();
         let connection_handle = Arc::new(ConnectionHandle::new(tx));
+        let traffic_shaper = Arc::clone(&self.traffic_shaper);
 
-        let connect = nimiq_connect_async(url)
+        let connect = connect
             .timeout(Self::CONNECT_TIMEOUT)
             .map(move |msg_stream| {
                 let shared_stream: SharedNimiqMessageStream = msg_stream.into();
                 let net_address = Some(Arc::new(shared_stream.net_address()));
-                let (nc, ncfut) = NetworkConnection::new_connection_setup(shared_stream, AddressInfo::new(net_address, Some(peer_address)));
+                let (nc, ncfut) = NetworkConnection::new_connection_setup(shared_stream, AddressInfo::new(net_address, Some(peer_address)), traffic_shaper);
                 notifier.read().notify(WebSocketConnectorEvent::Connection(nc));
                 tokio::spawn(ncfut);
             })
@@ -235,8 +309,43 @@ impl WebSocketConnector {
                 }
             });
 
-            tokio::spawn(connect.select2(rx).map(|_| ()).map_err(|_| ()));
+        tokio::spawn(connect.select2(rx).map(|_| ()).map_err(|_| ()));
+
+        connection_handle
+    }
+
+    fn connect_noise(&self, host: String, port: u16, peer_address: Arc<PeerAddress>) -> Arc<ConnectionHandle> {
+        let key_pair = self.network_config.key_pair().clone();
+        let remote_public_key = peer_address.public_key;
+
+        // Resolve the host when the future runs, the ConnectionPool is still locked here.
+        let connect = future::lazy(move || -> Box<dyn Future<Item=NimiqMessageStream, Error=Error> + Send> {
+            match (host.as_str(), port).to_socket_addrs().map(|mut addrs| addrs.next()) {
+                Ok(Some(addr)) => nimiq_noise_connect_async(addr, &key_pair, remote_public_key),
+                Ok(None) => Box::new(future::err(Error::IoError(io::Error::new(io::ErrorKind::NotFound, format!("Could not resolve {}", host))))),
+                Err(e) => Box::new(future::err(Error::IoError(e))),
+            }
+        });
+
+        self.spawn_connect(connect, peer_address)
+    }
+
+    fn connect_loopback(&self, hub: LoopbackHub, host: String, port: u16, peer_address: Arc<PeerAddress>) -> Arc<ConnectionHandle> {
+        let notifier = Arc::clone(&self.notifier);
+        let (tx, rx) = oneshot::channel::<CloseType>();
+        let connection_handle = Arc::new(ConnectionHandle::new(tx));
+        let traffic_shaper = Arc::clone(&self.traffic_shaper);
+
+        // Connect asynchronously like a WebSocket would, the ConnectionPool is still locked here.
+        let connect = future::lazy(move || {
+            if let Err(error) = hub.connect(&host, port, Arc::clone(&peer_address), &notifier, traffic_shaper) {
+                notifier.read().notify(WebSocketConnectorEvent::Error(peer_address, error));
+            }
+            future::ok::<(), ()>(())
+        });
+
+        tokio::spawn(connect.select2(rx).map(|_| ()).map_err(|_| ()));
 
-            Ok(connection_handle)
+        connection_handle
     }
 }
@@ -0,0 +1,32 @@
+//! Fixtures shared by the network tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use std::time::SystemTime;
+
+use nimiq_keys::{KeyPair, SecureGenerate};
+use nimiq_network_primitives::address::{NetAddress, PeerId};
+use nimiq_network_primitives::address::peer_address::{PeerAddress, PeerAddressType};
+use nimiq_network_primitives::services::ServiceFlags;
+use nimiq_utils::time::systemtime_to_timestamp;
+
+/// A signed address of a new peer at `net_address`, seen just now.
+pub fn peer_address_at(net_address: NetAddress) -> PeerAddress {
+    signed_peer_address(net_address, systemtime_to_timestamp(SystemTime::now()))
+}
+
+/// A signed address of a new peer at `net_address` with the given timestamp.
+pub fn signed_peer_address(net_address: NetAddress, timestamp: u64) -> PeerAddress {
+    let key_pair = KeyPair::generate_default_csprng();
+    let mut peer_address = PeerAddress {
+        ty: PeerAddressType::Wss("node.example.com".to_string(), 8443),
+        services: ServiceFlags::FULL,
+        timestamp,
+        net_address,
+        public_key: key_pair.public,
+        distance: 0,
+        signature: None,
+        peer_id: PeerId::from(&key_pair.public),
+    };
+    peer_address.signature = Some(key_pair.sign(&peer_address.get_signature_data()));
+    peer_address
+}
@@ -0,0 +1,159 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use futures::prelude::*;
+use parking_lot::Mutex;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+use tungstenite::protocol::Message as WebSocketMessage;
+
+use nimiq_block_albatross::Block;
+use nimiq_blockchain_base::genesis_chain::GenesisChain;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_network::{Network, NetworkEvent};
+use nimiq_network::address::peer_address_state::PeerAddressState;
+use nimiq_network::network_config::{NetworkConfig, ProtocolConfig, Seed};
+use nimiq_network::websocket::LoopbackHub;
+use nimiq_network::websocket::loopback::LoopbackLayer;
+use nimiq_network_primitives::address::PeerUri;
+use nimiq_network_primitives::address::net_address::NetAddress;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_network_primitives::protocol::Protocol;
+use nimiq_network_primitives::time::NetworkTime;
+
+type LoopbackNetwork = Network<GenesisChain<Block>>;
+
+fn loopback_network(hub: &LoopbackHub, host: &str, seeds: Vec<Seed>) -> Arc<LoopbackNetwork> {
+    let mut network_config = NetworkConfig::new_loopback_network_config(hub.clone(), host.to_string(), 8443, true);
+    network_config.set_additional_seeds(seeds);
+    network_config.init_volatile();
+    let env = VolatileEnvironment::new(10).unwrap();
+    let network_time = Arc::new(NetworkTime::new());
+    let blockchain = Arc::new(GenesisChain::new(env.clone(), NetworkId::DevAlbatross, Arc::clone(&network_time)).unwrap());
+    Network::new(blockchain, network_config, network_time, NetworkId::DevAlbatross, env).unwrap()
+}
+
+/// Initializes and connects `network` on the runtime, since its timers and connections need one.
+fn start(runtime: &mut Runtime, network: &Arc<LoopbackNetwork>) {
+    let network = Arc::clone(network);
+    runtime.block_on(future::lazy(move || {
+        network.initialize().unwrap();
+        network.connect().unwrap();
+        Ok::<(), ()>(())
+    })).unwrap();
+}
+
+/// Runs the runtime until `condition` holds. Returns `false` if it didn't within `timeout`.
+fn run_until<F: Fn() -> bool>(runtime: &mut Runtime, timeout: Duration, condition: F) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !condition() {
+        if Instant::now() > deadline {
+            return false;
+        }
+        runtime.block_on(Delay::new(Instant::now() + Duration::from_millis(10))).unwrap();
+    }
+    true
+}
+
+#[test]
+fn it_passes_messages_in_both_directions() {
+    let (a, b) = LoopbackLayer::pair();
+
+    let a = a.send(WebSocketMessage::binary(vec![1, 2, 3])).wait().unwrap();
+    let (msg, b) = b.into_future().wait().ok().unwrap();
+    assert_eq!(msg, Some(WebSocketMessage::binary(vec![1, 2, 3])));
+
+    let _b = b.send(WebSocketMessage::binary(vec![4])).wait().unwrap();
+    let (msg, _a) = a.into_future().wait().ok().unwrap();
+    assert_eq!(msg, Some(WebSocketMessage::binary(vec![4])));
+}
+
+#[test]
+fn it_answers_close_handshakes() {
+    let (a, b) = LoopbackLayer::pair();
+
+    let a = a.send(WebSocketMessage::Close(None)).wait().unwrap();
+    let (msg, _b) = b.into_future().wait().ok().unwrap();
+    assert_eq!(msg, Some(WebSocketMessage::Close(None)));
+
+    // The peer replied to our close frame.
+    let (msg, _a) = a.into_future().wait().ok().unwrap();
+    assert_eq!(msg, Some(WebSocketMessage::Close(None)));
+}
+
+#[test]
+fn it_fails_to_send_when_the_peer_is_gone() {
+    let (a, b) = LoopbackLayer::pair();
+    drop(b);
+
+    assert!(a.send(WebSocketMessage::binary(vec![1])).wait().is_err());
+}
+
+#[test]
+fn it_assigns_every_node_its_own_subnet() {
+    let hub = LoopbackHub::new();
+    let first = hub.net_address("node1.loopback", 8443);
+    let second = hub.net_address("node2.loopback", 8443);
+
+    assert_eq!(first, hub.net_address("node1.loopback", 8443));
+    assert_ne!(first.subnet(24), second.subnet(24));
+    assert!(!first.is_pseudo());
+    match first {
+        NetAddress::IPv4(_) => (),
+        _ => panic!("Expected an IPv4 address"),
+    }
+}
+
+#[test]
+fn it_announces_loopback_nodes_as_ws() {
+    let config = NetworkConfig::new_loopback_network_config(LoopbackHub::new(), "node1.loopback".to_string(), 8443, true);
+    assert_eq!(Protocol::from(config.protocol_config()), Protocol::Ws);
+    match config.protocol_config() {
+        ProtocolConfig::Loopback { host, port, .. } => {
+            assert_eq!(host, "node1.loopback");
+            assert_eq!(*port, 8443);
+        },
+        _ => panic!("Expected a loopback config"),
+    }
+}
+
+#[test]
+fn it_connects_two_networks() {
+    let mut runtime = Runtime::new().unwrap();
+    let hub = LoopbackHub::new();
+
+    let node1 = loopback_network(&hub, "node1.loopback", vec![]);
+    let seed = format!("ws://node1.loopback:8443/{}", node1.network_config.public_key().to_hex());
+    let node2 = loopback_network(&hub, "node2.loopback", vec![Seed::new_peer(PeerUri::from_str(&seed).unwrap())]);
+
+    // Remember the head each node announced to the other one.
+    let head_hashes = Arc::new(Mutex::new(Vec::new()));
+    for node in &[&node1, &node2] {
+        let head_hashes = Arc::clone(&head_hashes);
+        node.notifier.write().register(move |event: &NetworkEvent| {
+            if let NetworkEvent::PeerJoined(peer) = event {
+                head_hashes.lock().push(peer.head_hash.clone());
+            }
+        });
+    }
+
+    start(&mut runtime, &node1);
+    start(&mut runtime, &node2);
+    assert!(run_until(&mut runtime, Duration::from_secs(10), || node1.peer_count() == 1 && node2.peer_count() == 1));
+
+    // Both nodes are on the genesis block.
+    let genesis_hash = NetworkInfo::from_network_id(NetworkId::DevAlbatross).genesis_hash().clone();
+    assert_eq!(*head_hashes.lock(), vec![genesis_hash.clone(), genesis_hash]);
+
+    // Node 1 learned node 2's address from the handshake.
+    let node2_address = node2.network_config.peer_address();
+    assert_eq!(node1.addresses.state().get_info(&node2_address).map(|info| info.state), Some(PeerAddressState::Established));
+    let node1_address = node1.network_config.peer_address();
+    assert_eq!(node2.addresses.state().get_info(&node1_address).map(|info| info.state), Some(PeerAddressState::Established));
+
+    node2.disconnect();
+    assert!(run_until(&mut runtime, Duration::from_secs(10), || node1.peer_count() == 0));
+    node1.disconnect();
+}
@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+use std::net::SocketAddr;
+
+use futures::prelude::*;
+use futures::sync::oneshot;
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+use tungstenite::protocol::CloseFrame;
+use tungstenite::protocol::frame::coding::CloseCode;
+
+use nimiq_keys::{KeyPair, PublicKey, SecureGenerate};
+use nimiq_messages::{GetEpochTransactionsMessage, Message};
+use nimiq_network::network_config::NetworkConfig;
+use nimiq_network::websocket::{nimiq_noise_accept_async, nimiq_noise_connect_async, Error, Message as WebSocketMessage, NimiqMessageStream};
+use nimiq_network_primitives::address::peer_address::PeerAddressType;
+use nimiq_network_primitives::protocol::Protocol;
+
+/// Runs a Noise handshake over a local TCP connection on `runtime`.
+/// Returns the outbound and inbound side, or the error of the outbound side.
+fn handshake(runtime: &mut Runtime, client: KeyPair, server: KeyPair, expected: PublicKey) -> Result<(NimiqMessageStream, NimiqMessageStream), Error> {
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let accept = listener.incoming()
+        .into_future()
+        .map_err(|(e, _)| Error::from(e))
+        .and_then(move |(tcp, _)| nimiq_noise_accept_async(tcp.unwrap(), &server));
+    let connect = nimiq_noise_connect_async(addr, &client, expected);
+
+    let inbound = oneshot::spawn(accept, &runtime.executor());
+    let outbound = runtime.block_on(connect)?;
+    Ok((outbound, runtime.block_on(inbound)?))
+}
+
+/// Receives the next message on `stream`.
+fn receive(runtime: &mut Runtime, stream: NimiqMessageStream) -> (Option<WebSocketMessage>, NimiqMessageStream) {
+    runtime.block_on(stream.into_future()).map_err(|(e, _)| e).unwrap()
+}
+
+fn epoch_of(msg: Option<WebSocketMessage>) -> u32 {
+    match msg {
+        Some(WebSocketMessage::Message(Message::GetEpochTransactions(msg))) => msg.epoch,
+        _ => panic!("Expected a GetEpochTransactions message"),
+    }
+}
+
+#[test]
+fn it_authenticates_both_peers() {
+    let client = KeyPair::generate_default_csprng();
+    let server = KeyPair::generate_default_csprng();
+
+    let mut runtime = Runtime::new().unwrap();
+    let (outbound, inbound) = handshake(&mut runtime, client.clone(), server.clone(), server.public).unwrap();
+    assert_eq!(outbound.state().remote_public_key, Some(server.public));
+    assert_eq!(inbound.state().remote_public_key, Some(client.public));
+    assert!(outbound.state().outbound);
+    assert!(!inbound.state().outbound);
+}
+
+#[test]
+fn it_rejects_unexpected_peer_keys() {
+    let client = KeyPair::generate_default_csprng();
+    let server = KeyPair::generate_default_csprng();
+    let other = KeyPair::generate_default_csprng();
+
+    let mut runtime = Runtime::new().unwrap();
+    match handshake(&mut runtime, client, server, other.public) {
+        Err(Error::NoiseAuthenticationFailed) => (),
+        _ => panic!("Expected the handshake to fail"),
+    }
+}
+
+#[test]
+fn it_encrypts_messages_after_the_handshake() {
+    let mut runtime = Runtime::new().unwrap();
+    let server = KeyPair::generate_default_csprng();
+    let (outbound, inbound) = handshake(&mut runtime, KeyPair::generate_default_csprng(), server.clone(), server.public).unwrap();
+
+    // Each message is a separate frame, the nonces of both sides advance independently.
+    let outbound = runtime.block_on(outbound.send(WebSocketMessage::Message(GetEpochTransactionsMessage::new(1)))).unwrap();
+    let outbound = runtime.block_on(outbound.send(WebSocketMessage::Message(GetEpochTransactionsMessage::new(2)))).unwrap();
+    let (msg, inbound) = receive(&mut runtime, inbound);
+    assert_eq!(epoch_of(msg), 1);
+    let (msg, inbound) = receive(&mut runtime, inbound);
+    assert_eq!(epoch_of(msg), 2);
+
+    let _inbound = runtime.block_on(inbound.send(WebSocketMessage::Message(GetEpochTransactionsMessage::new(3)))).unwrap();
+    let (msg, _outbound) = receive(&mut runtime, outbound);
+    assert_eq!(epoch_of(msg), 3);
+}
+
+#[test]
+fn it_transmits_close_frames() {
+    let mut runtime = Runtime::new().unwrap();
+    let server = KeyPair::generate_default_csprng();
+    let (outbound, inbound) = handshake(&mut runtime, KeyPair::generate_default_csprng(), server.clone(), server.public).unwrap();
+
+    let frame = CloseFrame {
+        code: CloseCode::from(4000),
+        reason: Cow::Borrowed("Closed by test"),
+    };
+    let _outbound = runtime.block_on(outbound.send(WebSocketMessage::Close(Some(frame.clone())))).unwrap();
+
+    // The close code and reason arrive intact, after which the stream ends.
+    let (msg, inbound) = receive(&mut runtime, inbound);
+    match msg {
+        Some(WebSocketMessage::Close(Some(received))) => {
+            assert_eq!(received.code, frame.code);
+            assert_eq!(received.reason, frame.reason);
+        },
+        _ => panic!("Expected a close frame"),
+    }
+    let (msg, _) = receive(&mut runtime, inbound);
+    assert!(msg.is_none());
+}
+
+#[test]
+fn it_announces_noise_nodes() {
+    let mut network_config = NetworkConfig::new_noise_network_config("node.example.com".to_string(), 8445, false);
+    network_config.init_volatile();
+
+    assert_eq!(network_config.protocol(), Protocol::Noise);
+    assert!(network_config.can_connect(Protocol::Noise));
+    assert_eq!(network_config.peer_address().ty, PeerAddressType::Noise("node.example.com".to_string(), 8445));
+}
+
+#[test]
+fn it_connects_to_noise_nodes_only_if_enabled() {
+    let mut network_config = NetworkConfig::new_ws_network_config("node.example.com".to_string(), 8443, false, None);
+    assert!(!network_config.can_connect(Protocol::Noise));
+
+    network_config.set_connect_noise(true);
+    assert!(network_config.can_connect(Protocol::Noise));
+    network_config.set_connect_noise(false);
+    assert!(!network_config.can_connect(Protocol::Noise));
+
+    // Noise nodes always connect to other Noise nodes.
+    let mut network_config = NetworkConfig::new_noise_network_config("node.example.com".to_string(), 8445, false);
+    network_config.set_connect_noise(false);
+    assert!(network_config.can_connect(Protocol::Noise));
+}
+
+#[test]
+fn it_announces_noise_nodes_as_dumb_to_legacy_peers() {
+    let mut network_config = NetworkConfig::new_noise_network_config("node.example.com".to_string(), 8445, false);
+    network_config.init_volatile();
+
+    let peer_address = network_config.dumb_peer_address();
+    assert_eq!(peer_address.ty, PeerAddressType::Dumb);
+    assert!(peer_address.verify_signature());
+}
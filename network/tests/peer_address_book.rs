@@ -0,0 +1,121 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nimiq_database::Environment;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_network::address::peer_address_book::{address_group, PeerAddressBook};
+use nimiq_network::address::peer_address_state::PeerAddressState;
+use nimiq_network::address::peer_address_store::PeerAddressStore;
+use nimiq_network::network_config::NetworkConfig;
+use nimiq_network_primitives::address::NetAddress;
+use nimiq_network_primitives::address::peer_address::PeerAddress;
+use nimiq_network_primitives::networks::NetworkId;
+
+use self::common::peer_address_at;
+
+mod common;
+
+fn address_book() -> PeerAddressBook {
+    address_book_in(VolatileEnvironment::new(2).unwrap())
+}
+
+fn address_book_in(env: Environment) -> PeerAddressBook {
+    let mut network_config = NetworkConfig::new_ws_network_config("local.example.com".to_string(), 8443, false, None);
+    network_config.init_volatile();
+    PeerAddressBook::new(Arc::new(network_config), NetworkId::Dev, env).unwrap()
+}
+
+fn peer_address() -> Arc<PeerAddress> {
+    Arc::new(peer_address_at(NetAddress::Unspecified))
+}
+
+#[test]
+fn it_bans_and_unbans_peers() {
+    let address_book = address_book();
+    let peer_address = peer_address();
+
+    address_book.ban(Arc::clone(&peer_address), Duration::from_secs(3600));
+    assert!(address_book.is_banned(&peer_address));
+
+    // A shorter ban does not lift the longer one.
+    address_book.ban(Arc::clone(&peer_address), Duration::from_secs(60));
+    let banned_until = address_book.state().get_info(&peer_address).unwrap().banned_until.unwrap();
+    assert!(banned_until > Instant::now() + Duration::from_secs(3000));
+
+    assert!(address_book.unban(&peer_address));
+    assert!(!address_book.is_banned(&peer_address));
+    assert!(address_book.state().get_info(&peer_address).unwrap().state == PeerAddressState::Tried);
+
+    // Nothing to unban anymore.
+    assert!(!address_book.unban(&peer_address));
+}
+
+#[test]
+fn it_limits_addresses_per_group() {
+    let address_book = address_book();
+    let group = address_group(&NetAddress::IPv4(Ipv4Addr::new(203, 0, 113, 7))).unwrap();
+    assert_eq!(group, address_group(&NetAddress::IPv4(Ipv4Addr::new(203, 0, 1, 1))).unwrap());
+    assert!(address_group(&NetAddress::Unspecified).is_none());
+
+    // Flood the address book with addresses from a single /16.
+    let flood: Vec<PeerAddress> = (0..100u8)
+        .map(|i| peer_address_at(NetAddress::IPv4(Ipv4Addr::new(203, 0, i, 1))))
+        .collect();
+    address_book.add(None, flood);
+    assert_eq!(address_book.state().num_addresses_by_group(&group), 64);
+
+    // Other networks are unaffected.
+    let other = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(198, 51, 100, 1)));
+    address_book.add(None, vec![other.clone()]);
+    assert!(address_book.state().get_info(&other).is_some());
+}
+
+#[test]
+fn it_persists_anchors_only_when_they_change() {
+    let env = VolatileEnvironment::new(2).unwrap();
+    let address_book = address_book_in(env.clone());
+    let store = PeerAddressStore::new(env);
+
+    let anchor = Arc::new(peer_address_at(NetAddress::IPv4(Ipv4Addr::new(203, 0, 113, 7))));
+    address_book.add(None, vec![anchor.as_ref().clone()]);
+    address_book.set_anchors(vec![Arc::clone(&anchor)]);
+    assert!(address_book.is_anchor(&anchor));
+    let stored: Vec<PeerAddress> = store.load_anchors().into_iter().map(|info| info.peer_address).collect();
+    assert_eq!(stored, vec![anchor.as_ref().clone()]);
+
+    // Setting the same anchors again doesn't write.
+    store.store_anchors(&[]);
+    address_book.set_anchors(vec![Arc::clone(&anchor)]);
+    assert!(store.load_anchors().is_empty());
+
+    // New anchors are written.
+    let other = Arc::new(peer_address_at(NetAddress::IPv4(Ipv4Addr::new(198, 51, 100, 1))));
+    address_book.add(None, vec![other.as_ref().clone()]);
+    address_book.set_anchors(vec![Arc::clone(&anchor), Arc::clone(&other)]);
+    assert!(address_book.is_anchor(&other));
+    assert_eq!(store.load_anchors().len(), 2);
+}
+
+#[test]
+fn it_persists_bans_and_changed_addresses() {
+    let env = VolatileEnvironment::new(2).unwrap();
+    let address_book = address_book_in(env.clone());
+    let store = PeerAddressStore::new(env);
+
+    // Bans are written immediately.
+    let banned = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(203, 0, 113, 7)));
+    address_book.ban(Arc::new(banned.clone()), Duration::from_secs(3600));
+    let stored = store.load();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].peer_address, banned);
+    assert!(stored[0].state == PeerAddressState::Banned);
+
+    // Only the entries that changed are written on shutdown.
+    store.store(&[]);
+    let added = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(198, 51, 100, 1)));
+    address_book.add(None, vec![added.clone()]);
+    drop(address_book);
+    let stored: Vec<PeerAddress> = store.load().into_iter().map(|info| info.peer_address).collect();
+    assert_eq!(stored, vec![added]);
+}
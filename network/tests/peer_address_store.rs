@@ -0,0 +1,84 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_network::address::peer_address_book::address_group;
+use nimiq_network::address::peer_address_state::{PeerAddressInfo, PeerAddressState};
+use nimiq_network::address::peer_address_store::{PeerAddressStore, StoredPeerAddressInfo};
+use nimiq_network_primitives::address::NetAddress;
+use nimiq_network_primitives::address::peer_address::PeerAddress;
+use nimiq_utils::time::systemtime_to_timestamp;
+
+use self::common::signed_peer_address;
+
+mod common;
+
+fn peer_address(timestamp: u64) -> PeerAddress {
+    signed_peer_address(NetAddress::Unspecified, timestamp)
+}
+
+#[test]
+fn it_restores_stored_peer_addresses() {
+    let env = VolatileEnvironment::new(2).unwrap();
+    let store = PeerAddressStore::new(env);
+    let now = systemtime_to_timestamp(SystemTime::now());
+
+    let mut established = PeerAddressInfo::new(Arc::new(peer_address(now)));
+    established.state = PeerAddressState::Established;
+    established.last_connected = Some(SystemTime::now());
+    established.source_group = address_group(&NetAddress::IPv4(Ipv4Addr::new(203, 0, 113, 7)));
+
+    let mut banned = PeerAddressInfo::new(Arc::new(peer_address(now)));
+    banned.state = PeerAddressState::Banned;
+    banned.banned_until = Some(Instant::now() + Duration::from_secs(600));
+
+    let infos: Vec<StoredPeerAddressInfo> = vec![&established, &banned].into_iter()
+        .filter_map(StoredPeerAddressInfo::from_info)
+        .collect();
+    store.store(&infos);
+
+    let restored: Vec<PeerAddressInfo> = store.load().into_iter()
+        .filter_map(StoredPeerAddressInfo::into_info)
+        .collect();
+    assert_eq!(restored.len(), 2);
+
+    let restored_established = restored.iter().find(|info| info.peer_address == established.peer_address).unwrap();
+    assert!(restored_established.state == PeerAddressState::Tried);
+    assert!(restored_established.last_connected.is_some());
+    assert_eq!(restored_established.source_group, established.source_group);
+
+    let restored_banned = restored.iter().find(|info| info.peer_address == banned.peer_address).unwrap();
+    assert!(restored_banned.state == PeerAddressState::Banned);
+    assert!(restored_banned.banned_until.unwrap() > Instant::now());
+}
+
+#[test]
+fn it_prunes_stale_peer_addresses() {
+    let env = VolatileEnvironment::new(2).unwrap();
+    let store = PeerAddressStore::new(env);
+    let now = systemtime_to_timestamp(SystemTime::now());
+
+    // Exceeds the maximum age.
+    let old = PeerAddressInfo::new(Arc::new(peer_address(now - 24 * 60 * 60 * 1000)));
+
+    // Ban has expired.
+    let mut unbanned = PeerAddressInfo::new(Arc::new(peer_address(now)));
+    unbanned.state = PeerAddressState::Banned;
+    unbanned.banned_until = Some(Instant::now() - Duration::from_secs(1));
+
+    let infos: Vec<StoredPeerAddressInfo> = vec![&old, &unbanned].into_iter()
+        .filter_map(StoredPeerAddressInfo::from_info)
+        .collect();
+    store.store(&infos);
+    assert_eq!(store.load().len(), 2);
+
+    let restored: Vec<PeerAddressInfo> = store.load().into_iter()
+        .filter_map(StoredPeerAddressInfo::into_info)
+        .collect();
+    assert!(restored.is_empty());
+
+    // Storing again replaces all entries.
+    store.store(&[]);
+    assert!(store.load().is_empty());
+}
@@ -0,0 +1,68 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use nimiq_block_albatross::Block;
+use nimiq_blockchain_base::genesis_chain::GenesisChain;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_network::Network;
+use nimiq_network::address::peer_address_state::PeerAddressState;
+use nimiq_network::network_config::NetworkConfig;
+use nimiq_network_primitives::address::NetAddress;
+use nimiq_network_primitives::address::peer_address::PeerAddress;
+use nimiq_network_primitives::networks::NetworkId;
+use nimiq_network_primitives::time::NetworkTime;
+
+use self::common::peer_address_at;
+
+mod common;
+
+fn network() -> Arc<Network<GenesisChain<Block>>> {
+    let mut network_config = NetworkConfig::new_ws_network_config("local.example.com".to_string(), 8443, false, None);
+    network_config.init_volatile();
+    let env = VolatileEnvironment::new(10).unwrap();
+    let network_time = Arc::new(NetworkTime::new());
+    let blockchain = Arc::new(GenesisChain::new(env.clone(), NetworkId::DevAlbatross, Arc::clone(&network_time)).unwrap());
+    Network::new(blockchain, network_config, network_time, NetworkId::DevAlbatross, env).unwrap()
+}
+
+#[test]
+fn it_gives_every_address_group_the_same_chance() {
+    let network = network();
+
+    // Many addresses in one /16, a single one in another.
+    let flood: Vec<PeerAddress> = (0..50u8)
+        .map(|i| peer_address_at(NetAddress::IPv4(Ipv4Addr::new(203, 0, i, 1))))
+        .collect();
+    let other = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(198, 51, 100, 1)));
+    network.addresses.add(None, flood);
+    network.addresses.add(None, vec![other.clone()]);
+
+    // Picking uniformly among addresses would choose `other` about 4 times out of 200.
+    let mut rng = StdRng::seed_from_u64(42);
+    let picked_other = (0..200)
+        .filter_map(|_| network.scorer().pick_address_with_rng(&mut rng))
+        .filter(|peer_address| peer_address.as_ref() == &other)
+        .count();
+    assert!(picked_other >= 50, "picked the lone group only {} times", picked_other);
+}
+
+#[test]
+fn it_picks_only_new_addresses_as_feelers() {
+    let network = network();
+
+    let tried = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(203, 0, 113, 1)));
+    let new = peer_address_at(NetAddress::IPv4(Ipv4Addr::new(198, 51, 100, 1)));
+    network.addresses.add(None, vec![tried.clone(), new.clone()]);
+    network.addresses.state_mut().get_info_mut(&tried).unwrap().state = PeerAddressState::Tried;
+
+    for _ in 0..20 {
+        assert_eq!(network.scorer().pick_feeler_address().as_ref().map(|peer_address| peer_address.as_ref()), Some(&new));
+    }
+
+    // Nothing left to test once all addresses have been tried.
+    network.addresses.state_mut().get_info_mut(&new).unwrap().state = PeerAddressState::Tried;
+    assert!(network.scorer().pick_feeler_address().is_none());
+}
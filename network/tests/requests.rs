@@ -0,0 +1,237 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+
+use nimiq_hash::Blake2bHash;
+use nimiq_messages::{EpochTransactionsMessage, GetEpochTransactionsMessage, Message, RequestIdentifier};
+use nimiq_network::connection::{AddressInfo, NetworkConnection};
+use nimiq_network::connection::traffic::{TrafficLimits, TrafficShaper};
+use nimiq_network::peer_channel::{PeerChannel, RequestError, ResponseStream};
+use nimiq_network::websocket::{Message as WebSocketMessage, NimiqMessageStream, SharedNimiqMessageStream};
+use nimiq_network::websocket::loopback::LoopbackLayer;
+use nimiq_network_primitives::address::net_address::NetAddress;
+use nimiq_utils::merkle::partial::{Blake2bPartialMerkleProof, PartialMerkleProofBuilder};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A peer channel whose peer is played by the test through `remote`.
+struct Connection {
+    runtime: Runtime,
+    channel: PeerChannel,
+    remote: Option<NimiqMessageStream>,
+    _connection: NetworkConnection,
+}
+
+impl Connection {
+    fn new() -> Self {
+        let net_address = NetAddress::IPv4(Ipv4Addr::new(10, 0, 0, 1));
+        let (local, remote) = LoopbackLayer::pair();
+        let stream: SharedNimiqMessageStream = NimiqMessageStream::new_loopback(local, net_address, true).into();
+        let traffic_shaper = Arc::new(TrafficShaper::new(TrafficLimits::default()));
+        let (connection, future) = NetworkConnection::new_connection_setup(stream, AddressInfo::new(Some(Arc::new(net_address)), None), traffic_shaper);
+        let channel = PeerChannel::new(&connection);
+
+        let runtime = Runtime::new().unwrap();
+        runtime.executor().spawn(future);
+
+        Connection {
+            runtime,
+            channel,
+            remote: Some(NimiqMessageStream::new_loopback(remote, net_address, false)),
+            _connection: connection,
+        }
+    }
+
+    /// Returns the id of the next `GetEpochTransactions` request the peer receives.
+    fn receive_request(&mut self) -> u32 {
+        let remote = self.remote.take().unwrap();
+        let (msg, remote) = self.runtime.block_on(remote.into_future()).map_err(|(e, _)| e).unwrap();
+        self.remote = Some(remote);
+        match msg {
+            Some(WebSocketMessage::Message(Message::GetEpochTransactions(msg))) => msg.request_identifier.0,
+            _ => panic!("Expected a GetEpochTransactions request"),
+        }
+    }
+
+    /// Sends a response from the peer.
+    fn respond(&mut self, msg: Message) {
+        let remote = self.remote.take().unwrap();
+        self.remote = Some(remote.send(WebSocketMessage::Message(msg)).wait().unwrap());
+    }
+
+    fn next_response(&mut self, responses: ResponseStream) -> (Result<Option<Message>, RequestError>, ResponseStream) {
+        match self.runtime.block_on(responses.into_future()) {
+            Ok((msg, responses)) => (Ok(msg), responses),
+            Err((e, responses)) => (Err(e), responses),
+        }
+    }
+
+    /// Waits until `condition` holds, as responses are routed on the runtime.
+    fn wait_until<F: Fn(&PeerChannel) -> bool>(&mut self, condition: F) {
+        for _ in 0..500 {
+            if condition(&self.channel) {
+                return;
+            }
+            self.runtime.block_on(Delay::new(Instant::now() + Duration::from_millis(10))).unwrap();
+        }
+        panic!("Condition did not hold in time");
+    }
+
+    /// Counts the epoch transactions that are dispatched as ordinary messages.
+    fn count_dispatched(&self) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let count1 = Arc::clone(&count);
+        self.channel.msg_notifier.epoch_transactions.write().register(move |_: EpochTransactionsMessage| {
+            count1.fetch_add(1, Ordering::SeqCst);
+        });
+        count
+    }
+}
+
+fn epoch_of(msg: &Message) -> u32 {
+    match msg {
+        Message::EpochTransactions(msg) => msg.epoch,
+        _ => panic!("Expected epoch transactions"),
+    }
+}
+
+/// The last chunk of epoch transactions, it has an empty proof.
+fn final_response(epoch: u32, request_identifier: u32) -> Message {
+    EpochTransactionsMessage::new(epoch, vec![], Blake2bPartialMerkleProof::empty(0), RequestIdentifier(request_identifier))
+}
+
+/// A chunk of epoch transactions that is followed by more.
+fn partial_response(epoch: u32, request_identifier: u32) -> Message {
+    let hashes = vec![Blake2bHash::from([1u8; Blake2bHash::SIZE]), Blake2bHash::from([2u8; Blake2bHash::SIZE])];
+    let proof = PartialMerkleProofBuilder::new(&hashes, 1).unwrap().remove(0);
+    assert!(!proof.is_empty());
+    EpochTransactionsMessage::new(epoch, vec![], proof, RequestIdentifier(request_identifier))
+}
+
+#[test]
+fn it_assigns_request_ids() {
+    let mut connection = Connection::new();
+
+    let first = connection.channel.request(GetEpochTransactionsMessage::new(1), TIMEOUT);
+    let second = connection.channel.request(GetEpochTransactionsMessage::new(2), TIMEOUT);
+    assert_ne!(first.id(), 0);
+    assert_ne!(second.id(), 0);
+    assert_ne!(first.id(), second.id());
+    assert_eq!(connection.channel.pending_requests(), 2);
+
+    // The peer receives the ids with the requests.
+    assert_eq!(connection.receive_request(), first.id());
+    assert_eq!(connection.receive_request(), second.id());
+
+    // Messages that aren't requests are rejected.
+    let mut invalid = connection.channel.request(Message::Mempool, TIMEOUT);
+    assert_eq!(invalid.id(), 0);
+    assert_eq!(invalid.poll().err(), Some(RequestError::InvalidRequest));
+}
+
+#[test]
+fn it_matches_concurrent_responses_by_id() {
+    let mut connection = Connection::new();
+    let dispatched = connection.count_dispatched();
+
+    let first = connection.channel.request(GetEpochTransactionsMessage::new(1), TIMEOUT);
+    let second = connection.channel.request(GetEpochTransactionsMessage::new(2), TIMEOUT);
+    let first_id = connection.receive_request();
+    let second_id = connection.receive_request();
+
+    // The responses arrive out of order, the first one in two chunks.
+    connection.respond(final_response(2, second_id));
+    connection.respond(partial_response(1, first_id));
+    connection.respond(final_response(1, first_id));
+
+    let (msg, second) = connection.next_response(second);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(2));
+    let (msg, _) = connection.next_response(second);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), None);
+
+    let (msg, first) = connection.next_response(first);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(1));
+    let (msg, first) = connection.next_response(first);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(1));
+    let (msg, _) = connection.next_response(first);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), None);
+
+    // Responses to our requests aren't dispatched to the message notifier.
+    assert_eq!(dispatched.load(Ordering::SeqCst), 0);
+    assert_eq!(connection.channel.pending_requests(), 0);
+}
+
+#[test]
+fn it_times_out_requests() {
+    let mut connection = Connection::new();
+
+    let responses = connection.channel.request(GetEpochTransactionsMessage::new(1), Duration::from_millis(100));
+    let (msg, _) = connection.next_response(responses);
+    assert_eq!(msg.err(), Some(RequestError::Timeout));
+    assert_eq!(connection.channel.pending_requests(), 0);
+}
+
+#[test]
+fn it_cancels_requests_when_dropping_the_responses() {
+    let mut connection = Connection::new();
+    let dispatched = connection.count_dispatched();
+
+    let responses = connection.channel.request(GetEpochTransactionsMessage::new(1), TIMEOUT);
+    let id = connection.receive_request();
+    assert_eq!(connection.channel.pending_requests(), 1);
+    drop(responses);
+    assert_eq!(connection.channel.pending_requests(), 0);
+
+    // A late response is discarded.
+    connection.respond(final_response(1, id));
+    let other = connection.channel.request(GetEpochTransactionsMessage::new(2), TIMEOUT);
+    let other_id = connection.receive_request();
+    connection.respond(final_response(2, other_id));
+    let (msg, _) = connection.next_response(other);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(2));
+    assert_eq!(dispatched.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn it_matches_responses_without_id_to_the_oldest_request() {
+    let mut connection = Connection::new();
+
+    let first = connection.channel.request(GetEpochTransactionsMessage::new(1), TIMEOUT);
+    let second = connection.channel.request(GetEpochTransactionsMessage::new(2), TIMEOUT);
+    connection.receive_request();
+    connection.receive_request();
+
+    // Legacy peers answer in order and don't echo the id.
+    connection.respond(final_response(1, 0));
+    connection.respond(final_response(2, 0));
+
+    let (msg, _) = connection.next_response(first);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(1));
+    let (msg, _) = connection.next_response(second);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(2));
+}
+
+#[test]
+fn it_discards_responses_with_unknown_ids() {
+    let mut connection = Connection::new();
+    let dispatched = connection.count_dispatched();
+
+    let responses = connection.channel.request(GetEpochTransactionsMessage::new(1), TIMEOUT);
+    let id = connection.receive_request();
+
+    connection.respond(final_response(7, id + 1000));
+    connection.respond(final_response(1, id));
+
+    // Only the response with the right id is delivered and the request is still pending until then.
+    let (msg, responses) = connection.next_response(responses);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), Some(1));
+    let (msg, _) = connection.next_response(responses);
+    assert_eq!(msg.unwrap().map(|msg| epoch_of(&msg)), None);
+    connection.wait_until(|channel| channel.pending_requests() == 0);
+    assert_eq!(dispatched.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "seed-server")]
+
+use std::net::{SocketAddr, TcpListener};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use futures::future;
+use hex::FromHex;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+use url::Url;
+
+use nimiq_block_albatross::Block;
+use nimiq_blockchain_base::AbstractBlockchain;
+use nimiq_blockchain_base::genesis_chain::GenesisChain;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_keys::{KeyPair, SecureGenerate, Signature};
+use nimiq_network::Network;
+use nimiq_network::address::peer_address_seeder::{PeerAddressSeeder, PeerAddressSeederError, PeerAddressSeederEvent};
+use nimiq_network::address::peer_address_state::PeerAddressState;
+use nimiq_network::network_config::{NetworkConfig, Seed};
+use nimiq_network::seed_server::SeedServer;
+use nimiq_network_primitives::address::peer_address::PeerAddress;
+use nimiq_network_primitives::address::peer_uri::PeerUri;
+use nimiq_network_primitives::address::seed_list::SeedList;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_network_primitives::time::NetworkTime;
+
+fn seed_server() -> (Arc<Network<GenesisChain<Block>>>, Arc<SeedServer<GenesisChain<Block>>>) {
+    let mut network_config = NetworkConfig::new_ws_network_config("seed.example.com".to_string(), 8443, false, None);
+    network_config.init_volatile();
+    let env = VolatileEnvironment::new(10).unwrap();
+    let network_time = Arc::new(NetworkTime::new());
+    let blockchain = Arc::new(GenesisChain::new(env.clone(), NetworkId::DevAlbatross, Arc::clone(&network_time)).unwrap());
+    let network = Network::new(blockchain, network_config, network_time, NetworkId::DevAlbatross, env).unwrap();
+    let seed_server = SeedServer::new(Arc::clone(&network), 10);
+    (network, seed_server)
+}
+
+/// Adds nodes with the given URI schemes to the address book as recently connected peers.
+/// Returns their seed strings.
+fn add_seeds(network: &Network<GenesisChain<Block>>, schemes: &[&str]) -> Vec<String> {
+    let peer_addresses: Vec<PeerAddress> = schemes.iter()
+        .enumerate()
+        .map(|(i, scheme)| {
+            let key_pair = KeyPair::generate_default_csprng();
+            let uri = format!("{}://{}{}.example.com:8443/{}", scheme, scheme, i, key_pair.public.to_hex());
+            PeerUri::from_str(&uri).unwrap().as_seed_peer_address().unwrap()
+        })
+        .collect();
+    network.addresses.add(None, peer_addresses.clone());
+
+    let mut state = network.addresses.state_mut();
+    for peer_address in &peer_addresses {
+        let info = state.get_info_mut(peer_address).unwrap();
+        info.state = PeerAddressState::Tried;
+        info.last_connected = Some(SystemTime::now());
+    }
+    peer_addresses.iter()
+        .filter_map(PeerAddress::to_seed_string)
+        .collect()
+}
+
+fn free_local_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+fn seed_strings(peer_addresses: &[PeerAddress]) -> Vec<String> {
+    let mut seed_strings: Vec<String> = peer_addresses.iter()
+        .filter_map(PeerAddress::to_seed_string)
+        .collect();
+    seed_strings.sort();
+    seed_strings
+}
+
+#[test]
+fn it_only_knows_the_genesis_block() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let chain = GenesisChain::<Block>::new(env, NetworkId::DevAlbatross, Arc::new(NetworkTime::new())).unwrap();
+    let genesis_hash = NetworkInfo::from_network_id(NetworkId::DevAlbatross).genesis_hash().clone();
+
+    assert_eq!(chain.head_hash(), genesis_hash);
+    assert!(chain.contains(&genesis_hash, false));
+    assert!(chain.get_block(&genesis_hash, false).is_some());
+    assert_eq!(chain.get_block_locators(10), vec![genesis_hash]);
+}
+
+#[test]
+fn it_signs_the_seed_list() {
+    let (network, seed_server) = seed_server();
+    assert!(seed_server.seeds().is_empty());
+
+    let seed_list = seed_server.seed_list();
+    let lines: Vec<&str> = seed_list.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    assert_eq!(lines.len(), 1);
+
+    let signature = Signature::from_hex(lines[0]).unwrap();
+    assert!(network.network_config.public_key().verify(&signature, b""));
+}
+
+#[test]
+fn it_serves_noise_seeds_separately() {
+    let (network, seed_server) = seed_server();
+    add_seeds(&network, &["wss", "noise"]);
+    assert_eq!(seed_server.seeds().len(), 2);
+
+    let seed_list = seed_server.seed_list();
+    assert!(seed_list.contains("wss://wss0.example.com:8443/"));
+    assert!(!seed_list.contains("noise://"));
+
+    let noise_seed_list = seed_server.noise_seed_list();
+    assert!(noise_seed_list.contains("noise://noise1.example.com:8443/"));
+    assert!(!noise_seed_list.contains("wss://"));
+}
+
+#[test]
+fn it_serves_seed_lists_that_seeders_accept() {
+    let (network, seed_server) = seed_server();
+    let mut expected = add_seeds(&network, &["wss", "ws", "wss"]);
+    expected.sort();
+
+    let mut runtime = Runtime::new().unwrap();
+    let addr = free_local_addr();
+    runtime.spawn(seed_server.serve(&addr).unwrap());
+
+    // Fetch the list like a node that has the seed server configured as additional seed list.
+    let mut network_config = NetworkConfig::new_ws_network_config("node.example.com".to_string(), 8443, false, None);
+    let public_key = *network.network_config.public_key();
+    network_config.set_additional_seeds(vec![
+        Seed::new_list(SeedList::new(Url::parse(&format!("http://{}/", addr)).unwrap(), Some(public_key))),
+    ]);
+
+    let seeds = Arc::new(Mutex::new(None));
+    let seeder = PeerAddressSeeder::new();
+    let seeds1 = Arc::clone(&seeds);
+    seeder.notifier.lock().register(move |e: &PeerAddressSeederEvent| {
+        if let PeerAddressSeederEvent::Seeds(peer_addresses) = e {
+            *seeds1.lock().unwrap() = Some(seed_strings(peer_addresses));
+        }
+    });
+    runtime.block_on(future::lazy(move || {
+        seeder.collect(NetworkId::DevAlbatross, Arc::new(network_config));
+        Ok::<(), ()>(())
+    })).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while seeds.lock().unwrap().is_none() && Instant::now() < deadline {
+        runtime.block_on(Delay::new(Instant::now() + Duration::from_millis(10))).unwrap();
+    }
+    assert_eq!(seeds.lock().unwrap().take(), Some(expected));
+}
+
+#[test]
+fn it_rejects_tampered_seed_lists() {
+    let (network, seed_server) = seed_server();
+    add_seeds(&network, &["wss", "wss"]);
+    let public_key = Some(*network.network_config.public_key());
+
+    let seed_list = seed_server.seed_list();
+    assert_eq!(PeerAddressSeeder::parse_seed_list(seed_list.as_bytes(), &public_key).unwrap().len(), 2);
+
+    // Another seed node in place of the first one.
+    let tampered = seed_list.replace("wss0.example.com", "evil.example.com");
+    assert_ne!(tampered, seed_list);
+    match PeerAddressSeeder::parse_seed_list(tampered.as_bytes(), &public_key) {
+        Err(PeerAddressSeederError::SignatureVerificationFailed) => (),
+        _ => panic!("Expected the signature verification to fail"),
+    }
+
+    // A list signed by someone else.
+    let other_key = Some(KeyPair::generate_default_csprng().public);
+    match PeerAddressSeeder::parse_seed_list(seed_list.as_bytes(), &other_key) {
+        Err(PeerAddressSeederError::SignatureVerificationFailed) => (),
+        _ => panic!("Expected the signature verification to fail"),
+    }
+
+    // The signature is the last line.
+    let unsigned: Vec<&str> = seed_list.lines()
+        .filter(|line| Signature::from_hex(line).is_err())
+        .collect();
+    match PeerAddressSeeder::parse_seed_list(unsigned.join("\n").as_bytes(), &public_key) {
+        Err(PeerAddressSeederError::SignatureMissing) => (),
+        _ => panic!("Expected the signature to be missing"),
+    }
+}
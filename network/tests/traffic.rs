@@ -0,0 +1,136 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::prelude::*;
+use tokio::runtime::Runtime;
+
+use beserial::Serialize;
+use nimiq_block_albatross::Block;
+use nimiq_messages::{Message, MessageType, RejectMessage, RejectMessageCode};
+use nimiq_network::connection::{AddressInfo, NetworkConnection, ProcessConnectionFuture};
+use nimiq_network::connection::traffic::{is_priority, RateLimiter, TrafficLimits, TrafficShaper};
+use nimiq_network::websocket::{Message as WebSocketMessage, NimiqMessageStream, SharedNimiqMessageStream};
+use nimiq_network::websocket::loopback::LoopbackLayer;
+use nimiq_network_primitives::address::net_address::NetAddress;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+
+fn block_message() -> Message {
+    let block: Block = NetworkInfo::from_network_id(NetworkId::UnitAlbatross).genesis_block();
+    Message::BlockAlbatross(Box::new(block))
+}
+
+/// Sets up a connection that is shaped by `limits`. Returns the stream of the other end.
+fn shaped_connection(limits: TrafficLimits) -> (NetworkConnection, ProcessConnectionFuture, NimiqMessageStream) {
+    let net_address = NetAddress::IPv4(Ipv4Addr::new(10, 0, 0, 1));
+    let (local, remote) = LoopbackLayer::pair();
+    let stream: SharedNimiqMessageStream = NimiqMessageStream::new_loopback(local, net_address, true).into();
+    let (connection, future) = NetworkConnection::new_connection_setup(stream, AddressInfo::new(Some(Arc::new(net_address)), None), Arc::new(TrafficShaper::new(limits)));
+    (connection, future, NimiqMessageStream::new_loopback(remote, net_address, false))
+}
+
+/// Waits for the next message on `stream` and returns its type and when it arrived.
+fn receive(runtime: &mut Runtime, stream: NimiqMessageStream) -> (MessageType, Instant, NimiqMessageStream) {
+    let (msg, stream) = runtime.block_on(stream.into_future()).map_err(|(e, _)| e).unwrap();
+    match msg {
+        Some(WebSocketMessage::Message(msg)) => (msg.ty(), Instant::now(), stream),
+        _ => panic!("Expected a message"),
+    }
+}
+
+#[test]
+fn it_lets_messages_pass_until_the_bucket_is_empty() {
+    let limiter = RateLimiter::new(1000);
+
+    // The bucket starts full and may go into debt once.
+    assert!(limiter.try_consume(600).is_ok());
+    assert!(limiter.try_consume(600).is_ok());
+
+    let wait = limiter.try_consume(1).unwrap_err();
+    assert!(wait > Duration::from_millis(150));
+    assert!(wait <= Duration::from_millis(250));
+}
+
+#[test]
+fn it_never_throttles_priority_traffic() {
+    let shaper = TrafficShaper::new(TrafficLimits { upload: Some(100), download: None });
+
+    assert!(shaper.acquire_upload(1000, true).is_ok());
+    assert!(shaper.acquire_upload(1000, true).is_ok());
+    // Priority traffic still counts against the limit.
+    assert!(shaper.acquire_upload(1, false).is_err());
+
+    // No download limit configured.
+    assert!(shaper.acquire_download(1_000_000, false).is_ok());
+    assert!(shaper.acquire_download(1_000_000, false).is_ok());
+}
+
+#[test]
+fn it_prioritizes_keep_alive_messages() {
+    assert!(is_priority(&Message::Ping(1)));
+    assert!(is_priority(&Message::Pong(1)));
+    assert!(!is_priority(&Message::Mempool));
+    assert!(!is_priority(&Message::GetChainProof));
+}
+
+#[test]
+fn it_prioritizes_rejects() {
+    // Rejects share the priority channel with the close frame that follows them.
+    let reject = RejectMessage::new(MessageType::Version, RejectMessageCode::Obsolete, "obsolete".to_string(), None);
+    assert!(is_priority(&reject));
+}
+
+#[test]
+fn it_does_not_prioritize_blocks() {
+    // Syncing peers request blocks in bulk.
+    assert!(!is_priority(&block_message()));
+}
+
+#[test]
+fn it_delays_blocks_served_for_get_data() {
+    let mut runtime = Runtime::new().unwrap();
+    // The first block empties the bucket and leaves a debt of half a block, which takes one second to pay off.
+    let block_size = block_message().serialized_size();
+    let (connection, future, remote) = shaped_connection(TrafficLimits { upload: Some(block_size as u32 / 2), download: None });
+    runtime.spawn(future);
+
+    let start = Instant::now();
+    let sink = connection.peer_sink();
+    sink.send(block_message()).unwrap();
+    sink.send(block_message()).unwrap();
+    sink.send(Message::Ping(1)).unwrap();
+
+    let mut received = Vec::new();
+    let mut remote = remote;
+    for _ in 0..3 {
+        let (ty, arrival, stream) = receive(&mut runtime, remote);
+        received.push((ty, arrival.duration_since(start)));
+        remote = stream;
+    }
+
+    // The ping overtakes the second block, which has to wait for the upload limit.
+    let types: Vec<MessageType> = received.iter().map(|(ty, _)| *ty).collect();
+    assert_eq!(types.last(), Some(&MessageType::BlockAlbatross));
+    assert!(types.contains(&MessageType::Ping));
+    assert!(received[2].1 >= Duration::from_millis(900));
+    assert!(received[0].1 < Duration::from_millis(500));
+    assert!(received[1].1 < Duration::from_millis(500));
+}
+
+#[test]
+fn it_does_not_delay_relayed_blocks() {
+    let mut runtime = Runtime::new().unwrap();
+    let block_size = block_message().serialized_size();
+    let (connection, future, remote) = shaped_connection(TrafficLimits { upload: Some(block_size as u32 / 2), download: None });
+    runtime.spawn(future);
+
+    let start = Instant::now();
+    let sink = connection.peer_sink();
+    sink.send(block_message()).unwrap();
+    sink.send_priority(block_message()).unwrap();
+
+    let (_, _, remote) = receive(&mut runtime, remote);
+    let (ty, arrival, _) = receive(&mut runtime, remote);
+    assert_eq!(ty, MessageType::BlockAlbatross);
+    assert!(arrival.duration_since(start) < Duration::from_millis(500));
+}
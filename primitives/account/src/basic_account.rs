@@ -6,6 +6,7 @@ use crate::{Account, AccountError, AccountTransactionInteraction, AccountType};
 use crate::inherent::{Inherent, InherentType, AccountInherentInteraction};
 
 #[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct BasicAccount {
     pub balance: Coin
 }
@@ -11,6 +11,7 @@ use crate::{Account, AccountError, AccountTransactionInteraction};
 use crate::inherent::{AccountInherentInteraction, Inherent};
 
 #[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct HashedTimeLockedContract {
     pub balance: Coin,
     pub sender: Address,
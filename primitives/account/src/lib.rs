@@ -17,7 +17,7 @@ use std::convert::TryFrom;
 
 use failure::Fail;
 
-use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, WriteBytesExt};
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 use hash::{Hash, Hasher, HashOutput, SerializeContent};
 use keys::Address;
 pub use primitives::account::AccountType;
@@ -75,11 +75,17 @@ pub trait AccountsTreeLeave: Serialize + Deserialize + Clone {
     fn is_initial(&self) -> bool;
 }
 
-#[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Debug)]
+#[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "lowercase"))]
+#[beserial(tag_type(u8))]
 pub enum Account {
+    #[beserial(tag = 0)]
     Basic(BasicAccount),
+    #[beserial(tag = 1)]
     Vesting(VestingContract),
+    #[beserial(tag = 2)]
     HTLC(HashedTimeLockedContract),
+    #[beserial(tag = 3)]
     Staking(StakingContract),
 }
 
@@ -208,76 +214,6 @@ impl AccountInherentInteraction for Account {
     }
 }
 
-impl Serialize for Account {
-    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
-        let mut size: usize = 0;
-        size += Serialize::serialize(&self.account_type(), writer)?;
-
-        match *self {
-            Account::Basic(ref account) => {
-                size += Serialize::serialize(&account, writer)?;
-            }
-            Account::Vesting(ref account) => {
-                size += Serialize::serialize(&account, writer)?;
-            }
-            Account::HTLC(ref account) => {
-                size += Serialize::serialize(&account, writer)?;
-            }
-            Account::Staking(ref account) => {
-                size += Serialize::serialize(&account, writer)?;
-            }
-        }
-
-        Ok(size)
-    }
-
-    fn serialized_size(&self) -> usize {
-        let mut size = /*type*/ 1;
-
-        match *self {
-            Account::Basic(ref account) => {
-                size += Serialize::serialized_size(&account);
-            }
-            Account::Vesting(ref account) => {
-                size += Serialize::serialized_size(&account);
-            }
-            Account::HTLC(ref account) => {
-                size += Serialize::serialized_size(&account);
-            }
-            Account::Staking(ref account) => {
-                size += Serialize::serialized_size(&account);
-            }
-        }
-
-        size
-    }
-}
-
-impl Deserialize for Account {
-    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let account_type: AccountType = Deserialize::deserialize(reader)?;
-
-        match account_type {
-            AccountType::Basic => {
-                let account: BasicAccount = Deserialize::deserialize(reader)?;
-                Ok(Account::Basic(account))
-            }
-            AccountType::Vesting => {
-                let account: VestingContract = Deserialize::deserialize(reader)?;
-                Ok(Account::Vesting(account))
-            }
-            AccountType::HTLC => {
-                let account: HashedTimeLockedContract = Deserialize::deserialize(reader)?;
-                Ok(Account::HTLC(account))
-            }
-            AccountType::Staking => {
-                let account: StakingContract = Deserialize::deserialize(reader)?;
-                Ok(Account::Staking(account))
-            }
-        }
-    }
-}
-
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum ReceiptType {
@@ -286,16 +222,22 @@ pub enum ReceiptType {
     Inherent = 2,
 }
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[beserial(tag_type(u8))]
 pub enum Receipt {
+    #[beserial(tag = 0)]
     PrunedAccount(PrunedAccount),
+    #[beserial(tag = 1)]
     Transaction {
         index: u16,
         sender: bool, // A bit inefficient.
+        #[beserial(len_type(u16))]
         data: Vec<u8>,
     },
+    #[beserial(tag = 2)]
     Inherent {
         index: u16,
+        #[beserial(len_type(u16))]
         data: Vec<u8>,
         pre_transactions: bool,
     },
@@ -311,63 +253,6 @@ impl Receipt {
     }
 }
 
-impl Serialize for Receipt {
-    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
-        let mut size = self.receipt_type().serialize(writer)?;
-        match self {
-            Receipt::PrunedAccount(pruned_account) => size += pruned_account.serialize(writer)?,
-            Receipt::Transaction { index, sender, data } => {
-                size += index.serialize(writer)?;
-                size += sender.serialize(writer)?;
-                size += SerializeWithLength::serialize::<u16, W>(data, writer)?;
-            },
-            Receipt::Inherent { index, data, pre_transactions } => {
-                size += index.serialize(writer)?;
-                size += SerializeWithLength::serialize::<u16, W>(data, writer)?;
-                size += pre_transactions.serialize(writer)?;
-            },
-        }
-        Ok(size)
-    }
-
-    fn serialized_size(&self) -> usize {
-        let mut size = self.receipt_type().serialized_size();
-        match self {
-            Receipt::PrunedAccount(pruned_account) => size += pruned_account.serialized_size(),
-            Receipt::Transaction { index, sender, data } => {
-                size += index.serialized_size();
-                size += sender.serialized_size();
-                size += SerializeWithLength::serialized_size::<u16>(data);
-            },
-            Receipt::Inherent { index, data, pre_transactions } => {
-                size += index.serialized_size();
-                size += SerializeWithLength::serialized_size::<u16>(data);
-                size += pre_transactions.serialized_size();
-            },
-        }
-        size
-    }
-}
-
-impl Deserialize for Receipt {
-    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let ty: ReceiptType = Deserialize::deserialize(reader)?;
-        match ty {
-            ReceiptType::PrunedAccount => Ok(Receipt::PrunedAccount(Deserialize::deserialize(reader)?)),
-            ReceiptType::Transaction => Ok(Receipt::Transaction {
-                index: Deserialize::deserialize(reader)?,
-                sender: Deserialize::deserialize(reader)?,
-                data: DeserializeWithLength::deserialize::<u16, R>(reader)?,
-            }),
-            ReceiptType::Inherent => Ok(Receipt::Inherent {
-                index: Deserialize::deserialize(reader)?,
-                data: DeserializeWithLength::deserialize::<u16, R>(reader)?,
-                pre_transactions: Deserialize::deserialize(reader)?,
-            }),
-        }
-    }
-}
-
 impl SerializeContent for Receipt {
     fn serialize_content<W: io::Write>(&self, writer: &mut W) -> io::Result<usize> { Ok(self.serialize(writer)?) }
 }
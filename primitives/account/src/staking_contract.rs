@@ -17,6 +17,7 @@ use crate::{Account, AccountError, AccountTransactionInteraction, AccountType};
 use crate::inherent::{AccountInherentInteraction, Inherent, InherentType};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct ActiveStake {
     pub staker_address: Address,
     pub balance: Coin,
@@ -59,6 +60,7 @@ impl ActiveStake {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct InactiveStake {
     pub balance: Coin,
     pub retire_time: u32,
@@ -902,6 +904,88 @@ impl Default for StakingContract {
 }
 
 
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct InactiveStakeRepr {
+        staker_address: Address,
+        balance: Coin,
+        retire_time: u32,
+    }
+
+    /// The JSON representation lists the stakes instead of exposing the lookup tables. Active stakes
+    /// are ordered like `active_stake_sorted`, everything else by staker address.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct StakingContractRepr {
+        balance: Coin,
+        active_stakes: Vec<ActiveStake>,
+        inactive_stakes: Vec<InactiveStakeRepr>,
+        current_epoch_parking: Vec<Address>,
+        previous_epoch_parking: Vec<Address>,
+    }
+
+    fn sorted_addresses(addresses: &HashSet<Address>) -> Vec<Address> {
+        let mut addresses: Vec<Address> = addresses.iter().cloned().collect();
+        addresses.sort();
+        addresses
+    }
+
+    impl Serialize for StakingContract {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut inactive_stakes: Vec<InactiveStakeRepr> = self.inactive_stake_by_address.iter()
+                .map(|(staker_address, stake)| InactiveStakeRepr {
+                    staker_address: staker_address.clone(),
+                    balance: stake.balance,
+                    retire_time: stake.retire_time,
+                })
+                .collect();
+            inactive_stakes.sort_by(|a, b| a.staker_address.cmp(&b.staker_address));
+
+            StakingContractRepr {
+                balance: self.balance,
+                active_stakes: self.active_stake_sorted.iter().map(|stake| ActiveStake::clone(stake)).collect(),
+                inactive_stakes,
+                current_epoch_parking: sorted_addresses(&self.current_epoch_parking),
+                previous_epoch_parking: sorted_addresses(&self.previous_epoch_parking),
+            }.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for StakingContract {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = StakingContractRepr::deserialize(deserializer)?;
+
+            let mut active_stake_sorted = BTreeSet::new();
+            let mut active_stake_by_address = HashMap::new();
+            for active_stake in repr.active_stakes {
+                let active_stake = Arc::new(active_stake);
+                active_stake_sorted.insert(Arc::clone(&active_stake));
+                active_stake_by_address.insert(active_stake.staker_address.clone(), active_stake);
+            }
+
+            let inactive_stake_by_address = repr.inactive_stakes.into_iter()
+                .map(|stake| (stake.staker_address, InactiveStake { balance: stake.balance, retire_time: stake.retire_time }))
+                .collect();
+
+            Ok(StakingContract {
+                balance: repr.balance,
+                active_stake_sorted,
+                active_stake_by_address,
+                inactive_stake_by_address,
+                current_epoch_parking: repr.current_epoch_parking.into_iter().collect(),
+                previous_epoch_parking: repr.previous_epoch_parking.into_iter().collect(),
+            })
+        }
+    }
+}
+
+
 #[test]
 fn it_can_de_serialize_an_active_stake_receipt() {
     const ACTIVE_STAKE_RECEIPT: &str = "96b94e8a2fa79cb3d96bfde5ed2fa693aa6bec225e944b23c96b1c83dda67b34b62d105763bdf3cd378de9e4d8809fb00f815e309ec94126f22d77ef81fe00fa3a51a6c750349efda2133ca2f0e1b04094c4e2ce08b73c72fccedc33e127259f010303030303030303030303030303030303030303";
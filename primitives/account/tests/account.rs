@@ -0,0 +1,42 @@
+use std::convert::TryInto;
+
+use beserial::Deserialize;
+use nimiq_account::{Account, BasicAccount, HashedTimeLockedContract, VestingContract};
+
+const VESTING: &str = "00002fbf9bd9c800fd34ab7265a0e48c454ccbf4c9c61dfdf68f9a22000000010003f480000002632e314a0000002fbf9bd9c800";
+const HTLC: &str = "00000000000000001b215589344cf570d36bec770825eae30b73213924786862babbdb05e7c4430612135eb2a836812303daebe368963c60d22098a5e9f1ebcb8e54d0b7beca942a2a0a9d95391804fe8f01000296350000000000000001";
+
+fn assert_json_round_trip(account: Account) -> serde_json::Value {
+    let json = serde_json::to_value(&account).unwrap();
+    let account2: Account = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(account2, account);
+    json
+}
+
+#[test]
+fn it_can_convert_a_basic_account_to_json_and_back() {
+    let account = Account::Basic(BasicAccount { balance: 1000.try_into().unwrap() });
+    let json = assert_json_round_trip(account);
+    assert_eq!(json["type"], "basic");
+    assert_eq!(json["balance"], 1000);
+}
+
+#[test]
+fn it_can_convert_a_vesting_contract_to_json_and_back() {
+    let bytes: Vec<u8> = hex::decode(VESTING).unwrap();
+    let contract: VestingContract = Deserialize::deserialize(&mut &bytes[..]).unwrap();
+    let json = assert_json_round_trip(Account::Vesting(contract.clone()));
+    assert_eq!(json["type"], "vesting");
+    assert_eq!(json["owner"], contract.owner.to_user_friendly_address());
+    assert_eq!(json["stepBlocks"], 259200);
+}
+
+#[test]
+fn it_can_convert_a_htlc_to_json_and_back() {
+    let bytes: Vec<u8> = hex::decode(HTLC).unwrap();
+    let htlc: HashedTimeLockedContract = Deserialize::deserialize(&mut &bytes[..]).unwrap();
+    let json = assert_json_round_trip(Account::HTLC(htlc));
+    assert_eq!(json["type"], "htlc");
+    assert_eq!(json["hashAlgorithm"], "sha256");
+    assert_eq!(json["hashRoot"], "daebe368963c60d22098a5e9f1ebcb8e54d0b7beca942a2a0a9d95391804fe8f");
+}
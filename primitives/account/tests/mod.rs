@@ -1,5 +1,8 @@
 extern crate beserial;
 extern crate rand;
 
+#[cfg(feature = "serde-derive")]
+mod account;
 mod htlc_contract;
+mod serialization;
 mod vesting_contract;
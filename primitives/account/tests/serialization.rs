@@ -0,0 +1,42 @@
+use std::convert::TryInto;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_account::{Account, BasicAccount, PrunedAccount, Receipt};
+use nimiq_keys::Address;
+
+const BASIC: &str = "0000000000000003e8";
+const VESTING: &str = "0100002fbf9bd9c800fd34ab7265a0e48c454ccbf4c9c61dfdf68f9a22000000010003f480000002632e314a0000002fbf9bd9c800";
+const HTLC: &str = "0200000000000000001b215589344cf570d36bec770825eae30b73213924786862babbdb05e7c4430612135eb2a836812303daebe368963c60d22098a5e9f1ebcb8e54d0b7beca942a2a0a9d95391804fe8f01000296350000000000000001";
+
+const RECEIPT_PRUNED_ACCOUNT: &str = "0011111111111111111111111111111111111111110000000000000003e8";
+const RECEIPT_TRANSACTION: &str = "010001010002aabb";
+const RECEIPT_INHERENT: &str = "0200020001cc00";
+
+fn assert_golden<T: Serialize + Deserialize + PartialEq + std::fmt::Debug>(value: &T, golden: &str) {
+    assert_eq!(hex::encode(value.serialize_to_vec()), golden);
+    assert_eq!(value.serialized_size() * 2, golden.len());
+    let deserialized: T = Deserialize::deserialize_from_vec(&hex::decode(golden).unwrap()).unwrap();
+    assert_eq!(&deserialized, value);
+}
+
+#[test]
+fn it_serializes_accounts_with_their_type() {
+    assert_golden(&Account::Basic(BasicAccount { balance: 1000.try_into().unwrap() }), BASIC);
+
+    for (golden, ty) in [(VESTING, 1u8), (HTLC, 2u8)].iter() {
+        let bytes = hex::decode(golden).unwrap();
+        let account: Account = Deserialize::deserialize_from_vec(&bytes).unwrap();
+        assert_eq!(account.account_type() as u8, *ty);
+        assert_eq!(hex::encode(account.serialize_to_vec()), *golden);
+    }
+}
+
+#[test]
+fn it_serializes_receipts_with_their_type() {
+    assert_golden(&Receipt::PrunedAccount(PrunedAccount {
+        address: Address::from("1111111111111111111111111111111111111111"),
+        account: Account::Basic(BasicAccount { balance: 1000.try_into().unwrap() }),
+    }), RECEIPT_PRUNED_ACCOUNT);
+    assert_golden(&Receipt::Transaction { index: 1, sender: true, data: vec![0xaa, 0xbb] }, RECEIPT_TRANSACTION);
+    assert_golden(&Receipt::Inherent { index: 2, data: vec![0xcc], pre_transactions: false }, RECEIPT_INHERENT);
+}
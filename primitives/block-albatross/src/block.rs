@@ -1,6 +1,6 @@
 use std::fmt;
 
-use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use beserial::{Deserialize, Serialize};
 use block_base;
 use hash::{Blake2bHash, Hash, SerializeContent};
 use hash_derive::SerializeContent;
@@ -19,9 +19,13 @@ pub enum BlockType {
     Micro = 2,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "lowercase"))]
+#[beserial(tag_type(u8))]
 pub enum Block {
+    #[beserial(tag = 1)]
     Macro(MacroBlock),
+    #[beserial(tag = 2)]
     Micro(MicroBlock),
 }
 
@@ -170,39 +174,6 @@ impl Block {
     }
 }
 
-impl Serialize for Block {
-    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
-        let mut size = 0;
-        size += self.ty().serialize(writer)?;
-        size += match self {
-            Block::Macro(block) => block.serialize(writer)?,
-            Block::Micro(block) => block.serialize(writer)?,
-        };
-        Ok(size)
-    }
-
-    fn serialized_size(&self) -> usize {
-        let mut size = 0;
-        size += self.ty().serialized_size();
-        size += match self {
-            Block::Macro(block) => block.serialized_size(),
-            Block::Micro(block) => block.serialized_size(),
-        };
-        size
-    }
-}
-
-impl Deserialize for Block {
-    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let ty: BlockType = Deserialize::deserialize(reader)?;
-        let block = match ty {
-            BlockType::Macro => Block::Macro(Deserialize::deserialize(reader)?),
-            BlockType::Micro => Block::Micro(Deserialize::deserialize(reader)?),
-        };
-        Ok(block)
-    }
-}
-
 impl fmt::Display for Block {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(f, "[#{}, view {}, type {:?}]", self.block_number(), self.view_number(), self.ty())
@@ -210,9 +181,12 @@ impl fmt::Display for Block {
 }
 
 
-#[derive(Clone, Debug, Eq, PartialEq, SerializeContent)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, SerializeContent)]
+#[beserial(tag_type(u8))]
 pub enum BlockHeader {
+    #[beserial(tag = 2)]
     Micro(MicroHeader),
+    #[beserial(tag = 1)]
     Macro(MacroHeader),
 }
 
@@ -293,39 +267,6 @@ impl block_base::BlockHeader for BlockHeader {
     }
 }
 
-impl Serialize for BlockHeader {
-    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
-        let mut size = 0;
-        size += self.ty().serialize(writer)?;
-        size += match self {
-            BlockHeader::Macro(header) => header.serialize(writer)?,
-            BlockHeader::Micro(header) => header.serialize(writer)?,
-        };
-        Ok(size)
-    }
-
-    fn serialized_size(&self) -> usize {
-        let mut size = 0;
-        size += self.ty().serialized_size();
-        size += match self {
-            BlockHeader::Macro(header) => header.serialized_size(),
-            BlockHeader::Micro(header) => header.serialized_size(),
-        };
-        size
-    }
-}
-
-impl Deserialize for BlockHeader {
-    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let ty: BlockType = Deserialize::deserialize(reader)?;
-        let header = match ty {
-            BlockType::Macro => BlockHeader::Macro(Deserialize::deserialize(reader)?),
-            BlockType::Micro => BlockHeader::Micro(Deserialize::deserialize(reader)?),
-        };
-        Ok(header)
-    }
-}
-
 impl block_base::Block for Block {
     type Header = BlockHeader;
     type Error = BlockError;
@@ -9,6 +9,7 @@ use primitives::policy;
 use crate::MicroHeader;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct ForkProof {
     pub header1: MicroHeader,
     pub header2: MicroHeader,
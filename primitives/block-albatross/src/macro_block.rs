@@ -17,6 +17,7 @@ use crate::signed;
 
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MacroBlock {
     pub header: MacroHeader,
     pub justification: Option<PbftProof>,
@@ -24,6 +25,7 @@ pub struct MacroBlock {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MacroHeader {
     pub version: u16,
 
@@ -46,6 +48,7 @@ pub struct MacroHeader {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MacroExtrinsics {
     /// Slots with staker information, i.e. staker address and reward address
     pub stakers: StakeSlots,
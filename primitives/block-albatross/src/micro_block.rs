@@ -14,6 +14,7 @@ use crate::fork_proof::ForkProof;
 
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MicroBlock {
     pub header: MicroHeader,
     pub justification: MicroJustification,
@@ -21,6 +22,7 @@ pub struct MicroBlock {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, SerializeContent)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MicroHeader {
     pub version: u16,
 
@@ -37,18 +39,21 @@ pub struct MicroHeader {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MicroJustification {
     pub signature: CompressedSignature,
     pub view_change_proof: Option<ViewChangeProof>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, SerializeContent)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct MicroExtrinsics {
     #[beserial(len_type(u8))]
+    #[cfg_attr(feature = "serde-derive", serde(with = "utils::serde_hex"))]
     pub extra_data: Vec<u8>,
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = "Self::MAX_FORK_PROOFS")]
     pub fork_proofs: Vec<ForkProof>,
-    #[beserial(len_type(u16))]
+    #[beserial(len_type(u16), limit = "Self::MAX_TRANSACTIONS")]
     pub transactions: Vec<Transaction>,
 }
 
@@ -78,6 +83,11 @@ impl MicroHeader {
 }
 
 impl MicroExtrinsics {
+    /// The number of fork proofs of `ForkProof::SIZE` that fit into `MicroBlock::MAX_SIZE`.
+    pub const MAX_FORK_PROOFS: usize = MicroBlock::MAX_SIZE / ForkProof::SIZE;
+    /// The number of transactions of `Transaction::MIN_SIZE` that fit into `MicroBlock::MAX_SIZE`.
+    pub const MAX_TRANSACTIONS: usize = MicroBlock::MAX_SIZE / Transaction::MIN_SIZE;
+
     pub fn verify(&self, block_height: u32, network_id: NetworkId) -> Result<(), BlockError> {
         // Verify fork proofs.
         let mut previous_proof: Option<&ForkProof> = None;
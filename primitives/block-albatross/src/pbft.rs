@@ -135,6 +135,7 @@ impl Default for PbftProofBuilder {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct PbftProof {
     pub prepare: AggregateProof<PbftPrepareMessage>,
     pub commit: AggregateProof<PbftCommitMessage>,
@@ -175,6 +175,7 @@ impl<M: Message> Default for AggregateProofBuilder<M> {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 /// TODO: Maybe refactor this, since we only construct those from Handel MultiSignatures now.
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct AggregateProof<M: Message> {
     /// Indices of validators that signed this proof
     pub signers: BitSet,
@@ -183,6 +184,7 @@ pub struct AggregateProof<M: Message> {
     pub signature: AggregateSignature,
 
     #[beserial(skip)]
+    #[cfg_attr(feature = "serde-derive", serde(skip))]
     _message: PhantomData<M>
 }
 
@@ -1,20 +1,21 @@
 use std::convert::TryInto;
 use std::str::FromStr;
 
-use beserial::Deserialize;
-use nimiq_block_albatross::{MacroBlock, MacroExtrinsics, MacroHeader};
+use beserial::{Deserialize, Serialize};
+use nimiq_block_albatross::{Block, BlockHeader, MacroBlock, MacroExtrinsics, MacroHeader};
 use nimiq_bls::bls12_381::{Signature, CompressedPublicKey};
 use nimiq_collections::bitset::BitSet;
 use nimiq_hash::{Blake2bHasher, Hasher};
 use nimiq_keys::Address;
 use nimiq_primitives::slot::{Slots, StakeSlots, StakeSlotBand, ValidatorSlots, ValidatorSlotBand};
 
-#[test]
-fn it_can_convert_macro_block_into_slots() {
-    let hash = Blake2bHasher::default().digest(&vec![]);
-
+fn signature() -> Signature {
     let signature_bytes = hex::decode("b9674ac1bbb4770ad291acc2b860e9120c609893a3840fbfdf2946911f00255f8995974c9b0ef3835ab4442ccbac9739").unwrap();
-    let signature = Signature::deserialize_from_vec(&signature_bytes).unwrap();
+    Signature::deserialize_from_vec(&signature_bytes).unwrap()
+}
+
+fn macro_block() -> MacroBlock {
+    let hash = Blake2bHasher::default().digest(&vec![]);
 
     let slot_allocation = vec![
         (127u16, "828aa810f80b9e200bb3310a3f837a8b1642e14440ec65ba8eaf801b9e1b81e69adc706b1ba6ed844cc793621dbd5e220ab235eac6d2b03c14e7a4da200759fee5f15903b9ef07602f7d50346fb25202f399affec1878cbfaa64cccdf0054cc6"),
@@ -39,15 +40,14 @@ fn it_can_convert_macro_block_into_slots() {
         .map(|(num, address)| StakeSlotBand::new(Address::from(address), None, num))
         .collect();
 
-
-    let macro_block = MacroBlock {
+    MacroBlock {
         header: MacroHeader {
             version: 1,
-            validators: validator_slots.clone(),
+            validators: validator_slots,
             block_number: 42,
             view_number: 0,
             parent_macro_hash: hash.clone(),
-            seed: signature.compress().into(),
+            seed: signature().compress().into(),
             parent_hash: hash.clone(),
             state_root: hash.clone(),
             extrinsics_root: hash.clone(),
@@ -56,13 +56,160 @@ fn it_can_convert_macro_block_into_slots() {
         },
         justification: None,
         extrinsics: Some(MacroExtrinsics {
-            stakers: stake_slots.clone(),
+            stakers: stake_slots,
             slashed_set: BitSet::new(),
         }),
-    };
+    }
+}
 
-    let slots = Slots::new(validator_slots, stake_slots);
+#[test]
+fn it_can_convert_macro_block_into_slots() {
+    let macro_block = macro_block();
+    let slots = Slots::new(macro_block.header.validators.clone(), macro_block.extrinsics.as_ref().unwrap().stakers.clone());
     let slots_from_macro: Slots = macro_block.try_into().unwrap();
 
     assert_eq!(slots, slots_from_macro);
 }
+
+#[cfg(feature = "serde-derive")]
+/// A micro block at #42.1 with extra data `aa`, preceded by its type.
+const MICRO_BLOCK: &str = "0200010000002a0000000111111111111111111111111111111111111111111111111111111111111111112222222222222222222222222222222222222222222222222222222222222222333333333333333333333333333333333333333333333333333333333333333344444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444400000000000003e8555555555555555555555555555555555555555555555555555555555555555555555555555555555555555555555555000101aa00000000";
+/// The header of `MICRO_BLOCK`, preceded by its type.
+const MICRO_HEADER: &str = "0200010000002a0000000111111111111111111111111111111111111111111111111111111111111111112222222222222222222222222222222222222222222222222222222222222222333333333333333333333333333333333333333333333333333333333333333344444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444444400000000000003e8";
+
+#[test]
+fn it_serializes_blocks_with_their_type() {
+    let bytes = hex::decode(MICRO_BLOCK).unwrap();
+    let block: Block = Deserialize::deserialize_from_vec(&bytes).unwrap();
+    match block {
+        Block::Micro(ref micro_block) => {
+            assert_eq!(micro_block.header.block_number, 42);
+            assert_eq!(micro_block.header.view_number, 1);
+            assert_eq!(micro_block.extrinsics.as_ref().unwrap().extra_data, vec![0xaa]);
+        },
+        _ => panic!("Expected a micro block"),
+    }
+    assert_eq!(hex::encode(block.serialize_to_vec()), MICRO_BLOCK);
+    assert_eq!(block.serialized_size(), bytes.len());
+
+    let macro_block = Block::Macro(macro_block());
+    let macro_bytes = macro_block.serialize_to_vec();
+    assert_eq!(macro_bytes[0], 1);
+    assert_eq!(Block::deserialize_from_vec(&macro_bytes).unwrap(), macro_block);
+}
+
+#[test]
+fn it_serializes_block_headers_with_their_type() {
+    let bytes = hex::decode(MICRO_HEADER).unwrap();
+    let header: BlockHeader = Deserialize::deserialize_from_vec(&bytes).unwrap();
+    match header {
+        BlockHeader::Micro(ref micro_header) => assert_eq!(micro_header.timestamp, 1000),
+        _ => panic!("Expected a micro header"),
+    }
+    assert_eq!(hex::encode(header.serialize_to_vec()), MICRO_HEADER);
+
+    let block: Block = Deserialize::deserialize_from_vec(&hex::decode(MICRO_BLOCK).unwrap()).unwrap();
+    assert_eq!(block.header(), header);
+}
+
+mod json {
+    use std::convert::TryInto;
+
+    use beserial::Serialize;
+    use nimiq_block_albatross::{Block, MicroBlock, MicroExtrinsics, MicroHeader, MicroJustification, PbftProof};
+    use nimiq_block_albatross::signed::AggregateProof;
+    use nimiq_bls::bls12_381::AggregateSignature;
+    use nimiq_collections::bitset::BitSet;
+    use nimiq_primitives::slot::Slots;
+
+    use super::{macro_block, signature};
+
+    fn pbft_proof() -> PbftProof {
+        let signature = signature();
+        let mut signers = BitSet::new();
+        signers.insert(3);
+        signers.insert(200);
+
+        PbftProof {
+            prepare: AggregateProof::new(AggregateSignature::from_signatures(&[signature]), signers.clone()),
+            commit: AggregateProof::new(AggregateSignature::from_signatures(&[signature]), signers),
+        }
+    }
+
+    #[test]
+    fn it_can_convert_slots_to_json_and_back() {
+        let macro_block = macro_block();
+        let slots: Slots = macro_block.try_into().unwrap();
+
+        let json = serde_json::to_value(&slots).unwrap();
+        assert!(json["validatorSlots"][0]["publicKey"].is_string());
+        assert!(json["stakeSlots"][0]["stakerAddress"].as_str().unwrap().starts_with("NQ"));
+        assert_eq!(json["stakeSlots"][0]["rewardAddress"], serde_json::Value::Null);
+
+        let slots2: Slots = serde_json::from_value(json).unwrap();
+        assert_eq!(slots2, slots);
+    }
+
+    #[test]
+    fn it_can_convert_a_pbft_proof_to_json_and_back() {
+        let proof = pbft_proof();
+
+        let json = serde_json::to_value(&proof).unwrap();
+        assert_eq!(json["prepare"]["signers"], serde_json::json!([3, 200]));
+        assert!(json["commit"]["signature"].is_string());
+
+        let proof2: PbftProof = serde_json::from_value(json).unwrap();
+        assert_eq!(proof2, proof);
+    }
+
+    #[test]
+    fn it_can_convert_a_macro_block_to_json_and_back() {
+        let mut macro_block = macro_block();
+        macro_block.justification = Some(pbft_proof());
+        macro_block.extrinsics.as_mut().unwrap().slashed_set.insert(17);
+        let block = Block::Macro(macro_block);
+
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["type"], "macro");
+        assert_eq!(json["header"]["blockNumber"], 42);
+        assert_eq!(json["extrinsics"]["slashedSet"], serde_json::json!([17]));
+
+        let block2: Block = serde_json::from_value(json).unwrap();
+        assert_eq!(block2, block);
+        assert_eq!(block2.serialize_to_vec(), block.serialize_to_vec());
+    }
+
+    #[test]
+    fn it_can_convert_a_micro_block_to_json_and_back() {
+        let macro_block = macro_block();
+        let block = Block::Micro(MicroBlock {
+            header: MicroHeader {
+                version: 1,
+                block_number: 43,
+                view_number: 1,
+                parent_hash: macro_block.hash(),
+                extrinsics_root: macro_block.header.extrinsics_root.clone(),
+                state_root: macro_block.header.state_root.clone(),
+                seed: macro_block.header.seed.clone(),
+                timestamp: 1,
+            },
+            justification: MicroJustification {
+                signature: signature().compress(),
+                view_change_proof: None,
+            },
+            extrinsics: Some(MicroExtrinsics {
+                extra_data: vec![1, 2, 3],
+                fork_proofs: vec![],
+                transactions: vec![],
+            }),
+        });
+
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["type"], "micro");
+        assert_eq!(json["extrinsics"]["extraData"], "010203");
+        assert_eq!(json["justification"]["viewChangeProof"], serde_json::Value::Null);
+
+        let block2: Block = serde_json::from_value(json).unwrap();
+        assert_eq!(block2, block);
+    }
+}
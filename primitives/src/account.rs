@@ -1,9 +1,10 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use beserial::{Deserialize, Serialize};
 use enum_display_derive::Display;
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Display)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Display, Hash)]
 #[repr(u8)]
 pub enum AccountType {
     Basic = 0,
@@ -23,3 +24,69 @@ impl AccountType {
         }
     }
 }
+
+#[derive(Fail, Debug)]
+#[fail(display = "Input is not a valid account type: {}", _0)]
+pub struct AccountTypeParseError(String);
+
+impl FromStr for AccountType {
+    type Err = AccountTypeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "basic" => Ok(AccountType::Basic),
+            "vesting" => Ok(AccountType::Vesting),
+            "htlc" => Ok(AccountType::HTLC),
+            "staking" => Ok(AccountType::Staking),
+            _ => Err(AccountTypeParseError(String::from(s)))
+        }
+    }
+}
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use std::fmt;
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::{Error, Unexpected, Visitor};
+
+    use super::AccountType;
+
+    // Account types are written as their lowercase name, like the `type` tag of accounts.
+    // Their numeric value is accepted as well.
+    impl Serialize for AccountType {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.to_string().to_lowercase())
+        }
+    }
+
+    struct AccountTypeVisitor;
+
+    impl<'de> Visitor<'de> for AccountTypeVisitor {
+        type Value = AccountType;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "an account type")
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<AccountType, E> {
+            AccountType::from_str(value)
+                .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<AccountType, E> {
+            if value > u64::from(u8::max_value()) {
+                return Err(E::invalid_value(Unexpected::Unsigned(value), &self));
+            }
+            AccountType::from_int(value as u8)
+                .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value), &self))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AccountType {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(AccountTypeVisitor)
+        }
+    }
+}
@@ -232,3 +232,27 @@ impl FromStr for Coin {
         Ok(Coin::try_from(value)?)
     }
 }
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use std::convert::TryFrom;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+
+    use super::Coin;
+
+    // Coins are written as their value in Luna.
+    impl Serialize for Coin {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u64(self.0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Coin {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let value: u64 = Deserialize::deserialize(deserializer)?;
+            Coin::try_from(value).map_err(D::Error::custom)
+        }
+    }
+}
@@ -61,3 +61,26 @@ impl Display for NetworkId {
         })
     }
 }
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::{Error, Unexpected};
+
+    use super::NetworkId;
+
+    // Network IDs are written as their numeric value.
+    impl Serialize for NetworkId {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u8(*self as u8)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for NetworkId {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let value: u8 = Deserialize::deserialize(deserializer)?;
+            beserial::Deserialize::deserialize_from_vec(&[value])
+                .map_err(|_| D::Error::invalid_value(Unexpected::Unsigned(value.into()), &"a network id"))
+        }
+    }
+}
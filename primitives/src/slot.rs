@@ -109,6 +109,7 @@ impl Slot {
 ///   them right from the block. Maybe something like *SuperCow* works here?
 ///
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 pub struct Slots {
     pub validator_slots: ValidatorSlots,
     pub stake_slots: StakeSlots,
@@ -765,3 +766,91 @@ impl Deserialize for SlotAllocation {
         Ok(allocation)
     }
 }
+
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ValidatorSlotBandRepr {
+        public_key: String,
+        num_slots: u16,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct StakeSlotBandRepr {
+        staker_address: String,
+        reward_address: Option<String>,
+        num_slots: u16,
+    }
+
+    fn check_num_slots<E: Error, B: SlotBand>(bands: &[B]) -> Result<(), E> {
+        let num_slots: u32 = bands.iter().map(|band| u32::from(band.num_slots())).sum();
+        if num_slots != u32::from(SLOTS) {
+            return Err(E::custom(format!("Slot bands cover {} instead of {} slots", num_slots, SLOTS)));
+        }
+        Ok(())
+    }
+
+    // Validator slots are written as a list of `{publicKey, numSlots}` bands.
+    impl Serialize for ValidatorSlots {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter().map(|band| ValidatorSlotBandRepr {
+                public_key: band.public_key.to_string(),
+                num_slots: band.num_slots,
+            }))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ValidatorSlots {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bands: Vec<ValidatorSlotBandRepr> = Deserialize::deserialize(deserializer)?;
+            let bands = bands.into_iter()
+                .map(|band| {
+                    let public_key = CompressedPublicKey::from_str(&band.public_key).map_err(D::Error::custom)?;
+                    Ok(ValidatorSlotBand::new(public_key, band.num_slots))
+                })
+                .collect::<Result<Vec<ValidatorSlotBand>, D::Error>>()?;
+            check_num_slots(&bands)?;
+            Ok(ValidatorSlots::new(bands))
+        }
+    }
+
+    // Stake slots are written as a list of `{stakerAddress, rewardAddress, numSlots}` bands, the
+    // reward address is `null` if it is the staker address.
+    impl Serialize for StakeSlots {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter().map(|band| StakeSlotBandRepr {
+                staker_address: band.staker_address.to_user_friendly_address(),
+                reward_address: band.reward_address_opt.as_ref().map(Address::to_user_friendly_address),
+                num_slots: band.num_slots,
+            }))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for StakeSlots {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bands: Vec<StakeSlotBandRepr> = Deserialize::deserialize(deserializer)?;
+            let bands = bands.into_iter()
+                .map(|band| {
+                    let staker_address = Address::from_any_str(&band.staker_address).map_err(D::Error::custom)?;
+                    let reward_address = band.reward_address
+                        .map(|address| Address::from_any_str(&address))
+                        .transpose()
+                        .map_err(D::Error::custom)?;
+                    Ok(StakeSlotBand::new(staker_address, reward_address, band.num_slots))
+                })
+                .collect::<Result<Vec<StakeSlotBand>, D::Error>>()?;
+            check_num_slots(&bands)?;
+            Ok(StakeSlots::new(bands))
+        }
+    }
+}
@@ -116,6 +116,7 @@ impl AccountTransactionVerification for HashedTimeLockedContractVerifier {
 }
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Display)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 #[repr(u8)]
 pub enum HashAlgorithm {
     Blake2b = 1,
@@ -132,6 +133,8 @@ pub enum ProofType {
 
 create_typed_array!(AnyHash, u8, 32);
 add_hex_io_fns_typed_arr!(AnyHash, AnyHash::SIZE);
+#[cfg(feature = "serde-derive")]
+macros::add_serde_hex_fns!(AnyHash);
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CreationTransactionData {
@@ -1,4 +1,4 @@
-use beserial::Deserialize;
+use beserial::{Deserialize, Serialize};
 use keys::Address;
 use primitives::account::AccountType;
 use primitives::coin::Coin;
@@ -102,4 +102,15 @@ impl CreationTransactionData {
             Err(TransactionError::InvalidData)
         }
     }
+
+    /// Serializes the creation data in its full form, which is valid regardless of the transaction value.
+    pub fn to_tx_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Address::SIZE + 24);
+        Serialize::serialize(&self.owner, &mut data).unwrap();
+        Serialize::serialize(&self.start, &mut data).unwrap();
+        Serialize::serialize(&self.step_blocks, &mut data).unwrap();
+        Serialize::serialize(&self.step_amount, &mut data).unwrap();
+        Serialize::serialize(&self.total_amount, &mut data).unwrap();
+        data
+    }
 }
@@ -29,6 +29,9 @@ use primitives::policy;
 use crate::account::AccountTransactionVerification;
 
 pub mod account;
+mod short_id;
+
+pub use crate::short_id::ShortTransactionId;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TransactionsProof {
@@ -97,8 +100,10 @@ impl SignatureProof {
 }
 
 #[derive(Clone, Eq, Debug)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
 #[repr(C)]
 pub struct Transaction {
+    #[cfg_attr(feature = "serde-derive", serde(with = "utils::serde_hex"))]
     pub data: Vec<u8>,
     pub sender: Address,
     pub sender_type: AccountType,
@@ -109,7 +114,9 @@ pub struct Transaction {
     pub validity_start_height: u32,
     pub network_id: NetworkId,
     pub flags: TransactionFlags,
+    #[cfg_attr(feature = "serde-derive", serde(with = "utils::serde_hex"))]
     pub proof: Vec<u8>,
+    #[cfg_attr(feature = "serde-derive", serde(skip))]
     valid: bool
 }
 
@@ -487,3 +494,26 @@ impl From<SerializingError> for TransactionError {
         TransactionError::InvalidSerialization(e)
     }
 }
+
+#[cfg(feature = "serde-derive")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::{Error, Unexpected};
+
+    use super::TransactionFlags;
+
+    // Flags are written as their raw bits, unknown bits are rejected.
+    impl Serialize for TransactionFlags {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u8(self.bits())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for TransactionFlags {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bits: u8 = Deserialize::deserialize(deserializer)?;
+            TransactionFlags::from_bits(bits)
+                .ok_or_else(|| D::Error::invalid_value(Unexpected::Unsigned(u64::from(bits)), &"valid transaction flags"))
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::convert::TryInto;
+use std::hash::Hasher;
+
+use siphasher::sip::SipHasher24;
+
+use hash::Blake2bHash;
+use macros::create_typed_array;
+
+create_typed_array!(ShortTransactionId, u8, 8);
+
+impl ShortTransactionId {
+    /// Computes the short id of a transaction in the block with hash `block_hash`.
+    ///
+    /// Like in BIP152, short ids are SipHash-2-4 outputs keyed with the block hash. Since the key
+    /// isn't known before the block is produced, nobody can craft transactions whose short ids
+    /// collide in advance.
+    pub fn new(block_hash: &Blake2bHash, tx_hash: &Blake2bHash) -> Self {
+        let key = block_hash.as_bytes();
+        let mut hasher = SipHasher24::new_with_keys(
+            u64::from_le_bytes(key[0..8].try_into().unwrap()),
+            u64::from_le_bytes(key[8..16].try_into().unwrap()),
+        );
+        hasher.write(tx_hash.as_bytes());
+        Self::from(hasher.finish().to_le_bytes())
+    }
+}
@@ -72,3 +72,33 @@ fn it_can_serialize_basic_transaction() {
     assert_eq!(size, t.serialized_size());
     assert_eq!(hex::encode(v2), BASIC_TRANSACTION);
 }
+
+#[cfg(feature = "serde-derive")]
+#[test]
+fn it_can_convert_an_extended_transaction_to_json_and_back() {
+    let v: Vec<u8> = hex::decode(EXTENDED_TRANSACTION).unwrap();
+    let t: Transaction = Deserialize::deserialize(&mut &v[..]).unwrap();
+
+    let json: serde_json::Value = serde_json::to_value(&t).unwrap();
+    assert_eq!(json["data"], "");
+    assert_eq!(json["sender"], t.sender.to_user_friendly_address());
+    assert_eq!(json["senderType"], "basic");
+    assert_eq!(json["recipientType"], "basic");
+    assert_eq!(json["recipient"], t.recipient.to_user_friendly_address());
+    assert_eq!(json["value"], 8000000000000u64);
+    assert_eq!(json["fee"], 0);
+    assert_eq!(json["validityStartHeight"], 79555);
+    assert_eq!(json["networkId"], 42);
+    assert_eq!(json["flags"], 0);
+    assert_eq!(json["proof"], hex::encode(&t.proof));
+
+    let t2: Transaction = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(t2, t);
+    assert_eq!(t2.serialize_to_vec(), v);
+
+    // Numeric account types are accepted as well.
+    let mut json = json;
+    json["senderType"] = 0.into();
+    let t3: Transaction = serde_json::from_value(json).unwrap();
+    assert_eq!(t3, t);
+}
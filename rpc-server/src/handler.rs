@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
-use json::{Array, JsonValue};
+use json::{Array, JsonValue, object};
 
 use crate::JsonRpcConfig;
 use crate::error::AuthenticationError;
@@ -27,6 +27,8 @@ impl Method {
 
 pub struct Handler {
     pub methods: RwLock<HashMap<&'static str, Method>>,
+    /// Methods that change the node's state and are only served if RPC authentication is configured.
+    pub admin_methods: RwLock<HashSet<&'static str>>,
     pub config: Arc<JsonRpcConfig>,
 }
 
@@ -34,6 +36,7 @@ impl Handler {
     pub fn new(config: JsonRpcConfig) -> Self {
         Handler {
             methods: RwLock::new(HashMap::new()),
+            admin_methods: RwLock::new(HashSet::new()),
             config: Arc::new(config),
         }
     }
@@ -48,6 +51,18 @@ impl Handler {
             self.register_method(name, method)
         }
     }
+
+    /// Like `add_module`, but the module's methods are refused if no credentials are configured.
+    pub fn add_admin_module<M: Module>(&self, module: M) {
+        for (name, method) in module.methods() {
+            self.admin_methods.write().insert(name);
+            self.register_method(name, method)
+        }
+    }
+
+    fn requires_authentication(&self) -> bool {
+        self.config.username.is_some() && self.config.password.is_some()
+    }
 }
 
 impl jsonrpc::Handler for Handler {
@@ -60,6 +75,11 @@ impl jsonrpc::Handler for Handler {
             return None
         }
 
+        if !self.requires_authentication() && self.admin_methods.read().contains(name) {
+            info!("RPC call to admin method without authentication: {}", name);
+            return Some(Err(object!{"message" => "Method requires RPC authentication"}));
+        }
+
         self.methods.read().get(name).map(|h| h.call(&params))
     }
 
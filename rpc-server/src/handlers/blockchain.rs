@@ -3,6 +3,8 @@ use std::sync::Arc;
 
 use json::{Array, JsonValue, Null, object};
 
+use account::{Account, VestingContract};
+use beserial::Serialize;
 use block_base::{Block, BlockHeader};
 use blockchain_base::AbstractBlockchain;
 use keys::Address;
@@ -10,16 +12,28 @@ use keys::Address;
 use nimiq_hash::Blake2bHash;
 use nimiq_transaction::TransactionReceipt;
 
+use crate::serde_to_json;
 use crate::handlers::mempool::{transaction_to_obj, TransactionContext};
 
 pub struct BlockchainHandler<B: AbstractBlockchain + 'static> {
     pub blockchain: Arc<B>,
+    /// Whether account objects keep the field set of the Nimiq 1.0 RPC.
+    legacy_accounts: bool,
 }
 
 impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
     pub(crate) fn new(blockchain: Arc<B>) -> Self {
         Self {
             blockchain,
+            legacy_accounts: false,
+        }
+    }
+
+    /// Creates a handler that returns account objects in the format of the Nimiq 1.0 RPC.
+    pub(crate) fn new_legacy(blockchain: Arc<B>) -> Self {
+        Self {
+            blockchain,
+            legacy_accounts: true,
         }
     }
 
@@ -63,17 +77,25 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
     /// ```text
     /// {
     ///     hash: string,
+    ///     data: string, // hex encoded
+    ///     sender: string, // user friendly address
+    ///     senderType: string, // "basic", "vesting", "htlc" or "staking"
+    ///     recipient: string, // user friendly address
+    ///     recipientType: string,
+    ///     value: number,
+    ///     fee: number,
+    ///     validityStartHeight: number,
+    ///     networkId: number,
+    ///     flags: number,
+    ///     proof: string, // hex encoded
+    ///
+    ///     // Nimiq 1.0 fields
     ///     from: string, // hex encoded
     ///     fromAddress: string, // user friendly address
     ///     fromType: number,
     ///     to: string, // hex encoded
     ///     toAddress: string, // user friendly address
     ///     toType: number,
-    ///     value: number,
-    ///     fee: number,
-    ///     data: string,
-    ///     flags: number,
-    ///     validityStartHeight: number,
     ///
     ///     blockHash: string,
     ///     blockNumber: number,
@@ -98,17 +120,25 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
    /// ```text
    /// {
    ///     hash: string,
+   ///     data: string, // hex encoded
+   ///     sender: string, // user friendly address
+   ///     senderType: string, // "basic", "vesting", "htlc" or "staking"
+   ///     recipient: string, // user friendly address
+   ///     recipientType: string,
+   ///     value: number,
+   ///     fee: number,
+   ///     validityStartHeight: number,
+   ///     networkId: number,
+   ///     flags: number,
+   ///     proof: string, // hex encoded
+   ///
+   ///     // Nimiq 1.0 fields
    ///     from: string, // hex encoded
    ///     fromAddress: string, // user friendly address
    ///     fromType: number,
    ///     to: string, // hex encoded
    ///     toAddress: string, // user friendly address
    ///     toType: number,
-   ///     value: number,
-   ///     fee: number,
-   ///     data: string,
-   ///     flags: number,
-   ///     validityStartHeight: number,
    ///
    ///     blockHash: string,
    ///     blockNumber: number,
@@ -169,15 +199,92 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
     /// 1200000
     /// ```
     pub(crate) fn get_balance(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let address = params.get(0).and_then(JsonValue::as_str)
-            .ok_or_else(|| object!{"message" => "Invalid address"})
-            .and_then(|s| Address::from_any_str(s)
-                .map_err(|_| object!{"message" => "Invalid address"}))?;
+        let address = parse_address(params.get(0).unwrap_or(&Null))?;
 
         let account = self.blockchain.get_account(&address);
         Ok(JsonValue::from(u64::from(account.balance())))
     }
 
+    /// Returns the state of the account at an address.
+    /// Parameters:
+    /// - address (string)
+    /// - includeProof (bool, optional): Default is `false`. If set to `true`, a hex encoded
+    ///     accounts proof for the address at the current head block is included.
+    ///
+    /// The account object is the JSON representation of the account (see `docs/json.md`) with
+    /// some additional fields:
+    /// ```text
+    /// {
+    ///     type: string, // "basic", "vesting", "htlc" or "staking"
+    ///     balance: number,
+    ///     ... // fields of the account type
+    ///
+    ///     address: string, // user friendly address
+    ///
+    ///     // Vesting contracts only
+    ///     withdrawable: number, // amount that can be withdrawn in the next block
+    ///
+    ///     // If includeProof is set
+    ///     proof: string,
+    ///     proofBlockHash: string,
+    /// }
+    /// ```
+    ///
+    /// The Nimiq 1.0 RPC keeps its account object:
+    /// ```text
+    /// {
+    ///     id: string, // hex encoded
+    ///     address: string, // user friendly address
+    ///     balance: number,
+    ///     type: number,
+    ///
+    ///     // Vesting contracts only
+    ///     owner: string,
+    ///     ownerAddress: string,
+    ///     vestingStart: number,
+    ///     vestingStepBlocks: number,
+    ///     vestingStepAmount: number,
+    ///     vestingTotalAmount: number,
+    ///     withdrawable: number, // amount that can be withdrawn in the next block
+    ///
+    ///     // HTLCs only
+    ///     sender: string,
+    ///     senderAddress: string,
+    ///     recipient: string,
+    ///     recipientAddress: string,
+    ///     hashAlgorithm: string,
+    ///     hashRoot: string,
+    ///     hashCount: number,
+    ///     timeout: number,
+    ///     totalAmount: number,
+    ///
+    ///     // If includeProof is set
+    ///     proof: string,
+    ///     proofBlockHash: string,
+    /// }
+    /// ```
+    pub(crate) fn get_account(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let address = parse_address(params.get(0).unwrap_or(&Null))?;
+        let include_proof = params.get(1).and_then(JsonValue::as_bool).unwrap_or(false);
+
+        let account = self.blockchain.get_account(&address);
+        let mut obj = if self.legacy_accounts {
+            legacy_account_to_obj(&address, &account, self.blockchain.head_height())
+        } else {
+            account_to_obj(&address, &account, self.blockchain.head_height())
+        };
+
+        if include_proof {
+            let block_hash = self.blockchain.head_hash();
+            let proof = self.blockchain.get_accounts_proof(&block_hash, &[address])
+                .ok_or_else(|| object!{"message" => "Failed to create accounts proof"})?;
+            obj["proof"] = hex::encode(proof.serialize_to_vec()).into();
+            obj["proofBlockHash"] = block_hash.to_hex().into();
+        }
+
+        Ok(obj)
+    }
+
     // Helper functions
 
     pub(crate) fn block_by_number(&self, number: &JsonValue) -> Result<B::Block, JsonValue> {
@@ -246,3 +353,70 @@ pub(crate) fn parse_hash(hash: &JsonValue) -> Result<Blake2bHash, JsonValue> {
         .and_then(|s| Blake2bHash::from_str(s)
             .map_err(|_| object!{"message" => "Invalid Blake2b hash"}))
 }
+
+pub(crate) fn parse_address(address: &JsonValue) -> Result<Address, JsonValue> {
+    address.as_str()
+        .ok_or_else(|| object!{"message" => "Invalid address"})
+        .and_then(|s| Address::from_any_str(s)
+            .map_err(|_| object!{"message" => "Invalid address"}))
+}
+
+pub(crate) fn account_to_obj(address: &Address, account: &Account, head_height: u32) -> JsonValue {
+    let mut obj = serde_to_json(account);
+    obj["address"] = address.to_user_friendly_address().into();
+
+    if let Account::Vesting(contract) = account {
+        obj["withdrawable"] = vesting_withdrawable(contract, head_height).into();
+    }
+
+    obj
+}
+
+/// The account object of the Nimiq 1.0 RPC.
+pub(crate) fn legacy_account_to_obj(address: &Address, account: &Account, head_height: u32) -> JsonValue {
+    let mut obj = object!{
+        "id" => address.to_hex(),
+        "address" => address.to_user_friendly_address(),
+        "balance" => u64::from(account.balance()),
+        "type" => account.account_type() as u8,
+    };
+
+    match account {
+        Account::Vesting(contract) => {
+            obj["owner"] = contract.owner.to_hex().into();
+            obj["ownerAddress"] = contract.owner.to_user_friendly_address().into();
+            obj["vestingStart"] = contract.start.into();
+            obj["vestingStepBlocks"] = contract.step_blocks.into();
+            obj["vestingStepAmount"] = u64::from(contract.step_amount).into();
+            obj["vestingTotalAmount"] = u64::from(contract.total_amount).into();
+            obj["withdrawable"] = vesting_withdrawable(contract, head_height).into();
+        },
+        Account::HTLC(contract) => {
+            obj["sender"] = contract.sender.to_hex().into();
+            obj["senderAddress"] = contract.sender.to_user_friendly_address().into();
+            obj["recipient"] = contract.recipient.to_hex().into();
+            obj["recipientAddress"] = contract.recipient.to_user_friendly_address().into();
+            obj["hashAlgorithm"] = contract.hash_algorithm.to_string().to_lowercase().into();
+            obj["hashRoot"] = contract.hash_root.to_hex().into();
+            obj["hashCount"] = contract.hash_count.into();
+            obj["timeout"] = contract.timeout.into();
+            obj["totalAmount"] = u64::from(contract.total_amount).into();
+        },
+        Account::Basic(_) | Account::Staking(_) => {},
+    }
+
+    obj
+}
+
+/// The amount that can be withdrawn from a vesting contract by a transaction sent now, i.e.
+/// included in the block after `head_height` at the earliest.
+fn vesting_withdrawable(contract: &VestingContract, head_height: u32) -> u64 {
+    let block_height = head_height + 1;
+    // The whole amount is locked before the vesting starts.
+    let min_cap = if block_height < contract.start {
+        contract.total_amount
+    } else {
+        contract.min_cap(block_height)
+    };
+    u64::from(contract.balance.checked_sub(min_cap).unwrap_or_default())
+}
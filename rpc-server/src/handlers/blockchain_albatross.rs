@@ -1,23 +1,20 @@
-use std::borrow::Borrow;
-use std::convert::TryInto;
 use std::sync::Arc;
 
 use json::{JsonValue, Null, object};
 
-use block_albatross::{Block, ForkProof, signed};
+use block_albatross::Block;
 use account::Account;
-use account::staking_contract::{ActiveStake, InactiveStake};
 use blockchain_base::AbstractBlockchain;
 use blockchain_albatross::Blockchain;
 use hash::{Blake2bHash, Hash};
-use keys::Address;
 use network_primitives::networks::NetworkInfo;
 use primitives::policy;
-use primitives::slot::{Slot, Slots, SlotBand};
+use primitives::slot::Slot;
 
+use crate::serde_to_json;
 use crate::handler::Method;
 use crate::handlers::Module;
-use crate::handlers::blockchain::BlockchainHandler;
+use crate::handlers::blockchain::{parse_address, parse_hash, BlockchainHandler};
 use crate::handlers::mempool::{transaction_to_obj, TransactionContext};
 use crate::rpc_not_implemented;
 
@@ -46,28 +43,29 @@ impl BlockchainAlbatrossHandler {
     /// - hash (string)
     /// - includeTransactions (bool, optional): Default is false. If set to false, only hashes are included.
     ///
-    /// The block object contains:
+    /// The block object is the JSON representation of the block (see `docs/json.md`) with some
+    /// additional fields:
     /// ```text
     /// {
-    ///     number: number,
+    ///     type: "macro" | "micro",
+    ///     header: object,
+    ///     justification: object|null, (macro blocks also contain the number of votes)
+    ///     extrinsics: object|null,
+    ///
     ///     hash: string,
-    ///     pow: string,
-    ///     parentHash: string,
-    ///     nonce: number,
-    ///     bodyHash: string,
-    ///     accountsHash: string,
-    ///     miner: string,
-    ///     minerAddress: string, (user friendly address)
-    ///     difficulty: string,
-    ///     extraData: string,
-    ///     size: number,
-    ///     timestamp: number,
-    ///     timestampMillis: number,
-    ///     transactions: Array<transaction_objects> | Array<string>, (depends on includeTransactions),
+    ///     epoch: number,
+    ///     producer: slot_object|null, (micro blocks only)
     /// }
     /// ```
+    ///
+    /// The `transactions` of micro block extrinsics are transaction objects or transaction hashes,
+    /// depending on includeTransactions.
+    /// If the body of a micro block has been pruned, `extrinsics` only contains its `transactions`.
     pub(crate) fn get_block_by_hash(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let block = self.generic.block_by_hash(params.get(0).unwrap_or(&Null))?;
+        let hash = parse_hash(params.get(0).unwrap_or(&Null))?;
+        // Look up the block directly, the generic handler doesn't return blocks with pruned bodies.
+        let block = self.blockchain.get_block(&hash, false, true)
+            .ok_or_else(|| object!{"message" => "Block not found"})?;
         Ok(self.block_to_obj(&block, params.get(1).and_then(|v| v.as_bool()).unwrap_or(false)))
     }
 
@@ -76,28 +74,29 @@ impl BlockchainAlbatrossHandler {
     /// - height (number)
     /// - includeTransactions (bool, optional): Default is false. If set to false, only hashes are included.
     ///
-    /// The block object contains:
+    /// The block object is the JSON representation of the block (see `docs/json.md`) with some
+    /// additional fields:
     /// ```text
     /// {
-    ///     number: number,
+    ///     type: "macro" | "micro",
+    ///     header: object,
+    ///     justification: object|null, (macro blocks also contain the number of votes)
+    ///     extrinsics: object|null,
+    ///
     ///     hash: string,
-    ///     pow: string,
-    ///     parentHash: string,
-    ///     nonce: number,
-    ///     bodyHash: string,
-    ///     accountsHash: string,
-    ///     miner: string,
-    ///     minerAddress: string, (user friendly address)
-    ///     difficulty: string,
-    ///     extraData: string,
-    ///     size: number,
-    ///     timestamp: number,
-    ///     timestampMillis: number,
-    ///     transactions: Array<transaction_objects> | Array<string>, (depends on includeTransactions),
+    ///     epoch: number,
+    ///     producer: slot_object|null, (micro blocks only)
     /// }
     /// ```
+    ///
+    /// The `transactions` of micro block extrinsics are transaction objects or transaction hashes,
+    /// depending on includeTransactions.
+    /// If the body of a micro block has been pruned, `extrinsics` only contains its `transactions`.
     pub(crate) fn get_block_by_number(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let block = self.generic.block_by_number(params.get(0).unwrap_or(&Null))?;
+        let block_number = self.generic.parse_block_number(params.get(0).unwrap_or(&Null))?;
+        // Look up the block directly, the generic handler doesn't return blocks with pruned bodies.
+        let block = self.blockchain.get_block_at(block_number, true)
+            .ok_or_else(|| object!{"message" => "Block not found"})?;
         Ok(self.block_to_obj(&block, params.get(1).and_then(|v| v.as_bool()).unwrap_or(false)))
     }
 
@@ -238,17 +237,25 @@ impl BlockchainAlbatrossHandler {
     /// ```text
     /// {
     ///     hash: string,
+    ///     data: string, // hex encoded
+    ///     sender: string, // user friendly address
+    ///     senderType: string, // "basic", "vesting", "htlc" or "staking"
+    ///     recipient: string, // user friendly address
+    ///     recipientType: string,
+    ///     value: number,
+    ///     fee: number,
+    ///     validityStartHeight: number,
+    ///     networkId: number,
+    ///     flags: number,
+    ///     proof: string, // hex encoded
+    ///
+    ///     // Nimiq 1.0 fields
     ///     from: string, // hex encoded
     ///     fromAddress: string, // user friendly address
     ///     fromType: number,
     ///     to: string, // hex encoded
     ///     toAddress: string, // user friendly address
     ///     toType: number,
-    ///     value: number,
-    ///     fee: number,
-    ///     data: string,
-    ///     flags: number,
-    ///     validityStartHeight: number,
     ///
     ///     blockHash: string,
     ///     blockNumber: number,
@@ -302,146 +309,113 @@ impl BlockchainAlbatrossHandler {
 
     // Accounts
 
-    // Lists all stakes
+    /// Returns the state of the account at an address, see `getAccount` of the generic blockchain handler.
+    /// Additionally, if the address has staked, the object contains its stake:
+    /// ```text
+    /// {
+    ///     ...
+    ///     stake: {
+    ///         activeBalance: number,
+    ///         validatorKey: string|null,
+    ///         rewardAddress: string|null,
+    ///         inactiveBalance: number,
+    ///         retireTime: number|null,
+    ///         unstakeHeight: number|null, // first block at which the inactive stake can be unstaked
+    ///     } | null,
+    /// }
+    /// ```
+    pub(crate) fn get_account(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let address = parse_address(params.get(0).unwrap_or(&Null))?;
+        let mut obj = self.generic.get_account(params)?;
+
+        let staking_contract = NetworkInfo::from_network_id(self.blockchain.network_id)
+            .validator_registry_address().unwrap();
+        if let Account::Staking(contract) = self.blockchain.get_account(&staking_contract) {
+            let active_stake = contract.active_stake_by_address.get(&address);
+            let inactive_stake = contract.inactive_stake_by_address.get(&address);
+            obj["stake"] = if active_stake.is_some() || inactive_stake.is_some() {
+                object! {
+                    "activeBalance" => active_stake.map(|stake| u64::from(stake.balance)).unwrap_or(0),
+                    "validatorKey" => active_stake.map(|stake| hex::encode(&stake.validator_key).into()).unwrap_or(Null),
+                    "rewardAddress" => active_stake.map(|stake| stake.reward_address.as_ref()
+                        .unwrap_or(&stake.staker_address)
+                        .to_user_friendly_address().into()).unwrap_or(Null),
+                    "inactiveBalance" => inactive_stake.map(|stake| u64::from(stake.balance)).unwrap_or(0),
+                    "retireTime" => inactive_stake.map(|stake| stake.retire_time.into()).unwrap_or(Null),
+                    "unstakeHeight" => inactive_stake.map(|stake| (policy::macro_block_after(stake.retire_time) + policy::UNSTAKING_DELAY).into()).unwrap_or(Null),
+                }
+            } else {
+                Null
+            };
+        }
+
+        Ok(obj)
+    }
+
+    /// Lists all stakes, using the JSON representation of the staking contract (see `docs/json.md`):
+    /// ```text
+    /// {
+    ///     balance: number,
+    ///     activeStakes: Array<{stakerAddress, balance, validatorKey, rewardAddress}>,
+    ///     inactiveStakes: Array<{stakerAddress, balance, retireTime}>,
+    ///     currentEpochParking: Array<string>,
+    ///     previousEpochParking: Array<string>,
+    /// }
+    /// ```
     pub(crate) fn list_stakes(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
         let genesis_account = NetworkInfo::from_network_id(self.blockchain.network_id)
             .validator_registry_address().unwrap();
-        let account = self.blockchain.get_account(&genesis_account);
-        let contract = match account {
-            Account::Staking(c) => c,
-            _ => return Err("No contract at staking contract address".into()),
-        };
-        let active_stakes: Vec<JsonValue> = contract.active_stake_by_address
-            .values()
-            .map(|stake| BlockchainAlbatrossHandler::active_stake_to_obj(stake.borrow()))
-            .collect();
-        let inactive_stakes: Vec<JsonValue> = contract.inactive_stake_by_address
-            .iter()
-            .map(|(address, stake)| BlockchainAlbatrossHandler::inactive_stake_to_obj(address, stake))
-            .collect();
-        Ok(object! {
-            "activeStakes" => active_stakes,
-            "inactiveStakes" => inactive_stakes,
-        })
+        match self.blockchain.get_account(&genesis_account) {
+            Account::Staking(contract) => Ok(serde_to_json(&contract)),
+            _ => Err("No contract at staking contract address".into()),
+        }
     }
 
     // Helper functions
 
-    fn proof_to_object<M: signed::Message>(proof: &signed::AggregateProof<M>) -> JsonValue {
-        object!{
-            "signature" => format!("{}", proof.signature),
-            "signers" => proof.signers.iter().collect::<Vec<usize>>(),
-        }
-    }
-
     fn block_to_obj(&self, block: &Block, include_transactions: bool) -> JsonValue {
         let hash = block.hash().to_hex();
         let blockchain_height = self.blockchain.height();
 
+        let mut obj = serde_to_json(block);
+        obj["hash"] = hash.clone().into();
+        obj["epoch"] = policy::epoch_at(block.block_number()).into();
+
         match block {
             Block::Macro(ref block) => {
-                let slots = block.clone().try_into().ok();
-
-                let justification = block.justification.as_ref()
-                    .map(|pbft_proof| {
-                        let validators = block.header.validators.clone().into();
-                        object!{
-                            "votes" => pbft_proof.votes(&validators)
-                                .map(JsonValue::from).unwrap_or(JsonValue::Null),
-                            "prepare" => Self::proof_to_object(&pbft_proof.prepare),
-                            "commit" => Self::proof_to_object(&pbft_proof.commit),
-                        }
-                    })
-                    .unwrap_or(JsonValue::Null);
-
-                let slashed_slots = block.extrinsics.as_ref()
-                    .map(|extrinsics| {
-                        JsonValue::Array(extrinsics.slashed_set.iter()
-                            .map(|slot_number| JsonValue::Number(slot_number.into()))
-                            .collect())
-                    })
-                    .unwrap_or(JsonValue::Null);
-
-                object! {
-                    "type" => "macro",
-                    "hash" => hash.clone(),
-                    "blockNumber" => block.header.block_number,
-                    "viewNumber" => block.header.view_number,
-                    "epoch" => policy::epoch_at(block.header.block_number),
-                    "parentMacroHash" => block.header.parent_macro_hash.to_hex(),
-                    "parentHash" => block.header.parent_hash.to_hex(),
-                    "seed" => block.header.seed.to_string(),
-                    "stateRoot" => block.header.state_root.to_hex(),
-                    "extrinsicsRoot" => block.header.extrinsics_root.to_hex(),
-                    "timestamp" => block.header.timestamp,
-                    "slots" => slots.as_ref().map(Self::slots_to_obj).unwrap_or(Null),
-                    "slashedSlots" => slashed_slots,
-                    "justification" => justification,
+                if let Some(ref pbft_proof) = block.justification {
+                    let validators = block.header.validators.clone().into();
+                    obj["justification"]["votes"] = pbft_proof.votes(&validators)
+                        .map(JsonValue::from).unwrap_or(Null);
                 }
             },
             Block::Micro(ref block) => {
-                let producer = self.blockchain.get_slot_at(block.header.block_number, block.header.view_number, None)
+                obj["producer"] = self.blockchain.get_slot_at(block.header.block_number, block.header.view_number, None)
                     .map(|(slot, slot_number)| Self::slot_to_obj(&slot, slot_number))
-                    .unwrap_or(JsonValue::Null);
+                    .unwrap_or(Null);
 
-                object! {
-                    "type" => "micro",
-                    "hash" => hash.clone(),
-                    "blockNumber" => block.header.block_number,
-                    "viewNumber" => block.header.view_number,
-                    "epoch" => policy::epoch_at(block.header.block_number),
-                    "parentHash" => block.header.parent_hash.to_hex(),
-                    "stateRoot" => block.header.state_root.to_hex(),
-                    "extrinsicsRoot" => block.header.extrinsics_root.to_hex(),
-                    "seed" => block.header.seed.to_string(),
-                    "timestamp" => block.header.timestamp,
-                    "extraData" => block.extrinsics.as_ref().map(|body| hex::encode(&body.extra_data).into()).unwrap_or(Null),
-                    "forkProofs" => block.extrinsics.as_ref().map(|body| JsonValue::Array(body.fork_proofs.iter().map(Self::fork_proof_to_obj).collect())).unwrap_or(Null),
-                    "transactions" => JsonValue::Array(block.extrinsics.as_ref().map(|body| if include_transactions {
-                        body.transactions.iter().enumerate().map(|(i, tx)| transaction_to_obj(tx, Some(&TransactionContext {
+                // If the body has been pruned, the transactions are still kept per epoch.
+                let transactions = match block.extrinsics {
+                    Some(ref extrinsics) => Some(extrinsics.transactions.clone()),
+                    None => self.blockchain.chain_store.get_pruned_transactions(block.header.block_number, None),
+                };
+
+                if let Some(transactions) = transactions {
+                    obj["extrinsics"]["transactions"] = if include_transactions {
+                        transactions.iter().enumerate().map(|(i, tx)| transaction_to_obj(tx, Some(&TransactionContext {
                             block_hash: &hash,
                             block_number: block.header.block_number,
                             index: i as u16,
                             timestamp: block.header.timestamp,
-                        }), Some(blockchain_height ))).collect()
+                        }), Some(blockchain_height))).collect::<Vec<JsonValue>>().into()
                     } else {
-                        body.transactions.iter().map(|tx| tx.hash::<Blake2bHash>().to_hex().into()).collect()
-                    }).unwrap_or_else(Vec::new)),
-                    "producer" => producer,
-                    "justification" => object! {
-                        "signature" => block.justification.signature.to_hex(),
-                        "view_change_proof" => block.justification.view_change_proof.as_ref()
-                            .map(Self::proof_to_object)
-                            .unwrap_or(JsonValue::Null),
-                    },
+                        transactions.iter().map(|tx| tx.hash::<Blake2bHash>().to_hex().into()).collect::<Vec<JsonValue>>().into()
+                    };
                 }
-            }
+            },
         }
-    }
-
-    fn slots_to_obj(slots: &Slots) -> JsonValue {
-        JsonValue::Array(slots.combined().into_iter()
-            .map(|(slot, first_slot_number)| {
-                object! {
-                    "firstSlotNumber" => first_slot_number,
-                    "numSlots" => slot.stake_slot.num_slots(),
-                    "publicKey" => slot.public_key().to_string(),
-                    "stakerAddress" => slot.staker_address().to_user_friendly_address(),
-                    "rewardAddress" => slot.reward_address().to_user_friendly_address(),
-                }
-            }).collect())
-    }
 
-    fn fork_proof_to_obj(fork_proof: &ForkProof) -> JsonValue {
-        object! {
-            "blockNumber" => fork_proof.header1.block_number,
-            "viewNumber" => fork_proof.header1.view_number,
-            "parentHash" => fork_proof.header1.parent_hash.to_hex(),
-            "hashes" => vec![
-                fork_proof.header1.hash::<Blake2bHash>().to_hex(),
-                fork_proof.header2.hash::<Blake2bHash>().to_hex(),
-            ]
-        }
+        obj
     }
 
     fn slot_to_obj(slot: &Slot, slot_number: u16) -> JsonValue {
@@ -452,27 +426,6 @@ impl BlockchainAlbatrossHandler {
             "rewardAddress" => slot.reward_address().to_user_friendly_address(),
         }
     }
-
-    fn active_stake_to_obj(stake: &ActiveStake) -> JsonValue {
-        let reward_address = match stake.reward_address {
-            Some(ref a) => a,
-            None => &stake.staker_address,
-        }.to_user_friendly_address();
-        object! {
-            "stakerAddress" => stake.staker_address.to_user_friendly_address(),
-            "balance" => u64::from(stake.balance),
-            "publicKey" => hex::encode(&stake.validator_key),
-            "rewardAddress" => reward_address,
-        }
-    }
-
-    fn inactive_stake_to_obj(address: &Address, stake: &InactiveStake) -> JsonValue {
-        object! {
-            "stakerAddress" => address.to_user_friendly_address(),
-            "balance" => u64::from(stake.balance),
-            "retireTime" => stake.retire_time,
-        }
-    }
 }
 
 impl Module for BlockchainAlbatrossHandler {
@@ -497,6 +450,7 @@ impl Module for BlockchainAlbatrossHandler {
 
         // Accounts
         "getBalance" => generic.get_balance,
+        "getAccount" => get_account,
         "listStakes" => list_stakes,
     }
 }
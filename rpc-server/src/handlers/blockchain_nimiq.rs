@@ -24,7 +24,7 @@ pub struct BlockchainNimiqHandler {
 impl BlockchainNimiqHandler {
     pub fn new(blockchain: Arc<Blockchain>) -> Self {
         Self {
-            generic: BlockchainHandler::new(blockchain.clone()),
+            generic: BlockchainHandler::new_legacy(blockchain.clone()),
             blockchain,
         }
     }
@@ -138,17 +138,25 @@ impl BlockchainNimiqHandler {
     /// ```text
     /// {
     ///     hash: string,
+    ///     data: string, // hex encoded
+    ///     sender: string, // user friendly address
+    ///     senderType: string, // "basic", "vesting", "htlc" or "staking"
+    ///     recipient: string, // user friendly address
+    ///     recipientType: string,
+    ///     value: number,
+    ///     fee: number,
+    ///     validityStartHeight: number,
+    ///     networkId: number,
+    ///     flags: number,
+    ///     proof: string, // hex encoded
+    ///
+    ///     // Nimiq 1.0 fields
     ///     from: string, // hex encoded
     ///     fromAddress: string, // user friendly address
     ///     fromType: number,
     ///     to: string, // hex encoded
     ///     toAddress: string, // user friendly address
     ///     toType: number,
-    ///     value: number,
-    ///     fee: number,
-    ///     data: string,
-    ///     flags: number,
-    ///     validityStartHeight: number,
     ///
     ///     blockHash: string,
     ///     blockNumber: number,
@@ -279,5 +287,6 @@ impl Module for BlockchainNimiqHandler {
 
         // Accounts
         "getBalance" => generic.get_balance,
+        "getAccount" => generic.get_account,
     }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -12,11 +13,16 @@ use hash::{Blake2bHash, Hash};
 use keys::Address;
 use nimiq_mempool::Mempool;
 use nimiq_mempool::ReturnCode;
+use nimiq_mempool::filter::Rules;
 use primitives::account::AccountType;
 use primitives::coin::Coin;
+use nimiq_wallet::{HtlcProof, WalletAccount};
 use primitives::networks::NetworkId;
 use transaction::{Transaction, TransactionFlags};
+use transaction::account::htlc_contract::{AnyHash, CreationTransactionData as HtlcCreationData, HashAlgorithm};
+use transaction::account::vesting_contract::CreationTransactionData as VestingCreationData;
 
+use crate::{serde_from_json, serde_to_json};
 use crate::handler::Method;
 use crate::handlers::Module;
 use crate::handlers::wallet::UnlockedWalletManager;
@@ -110,9 +116,9 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     /// ```text
     /// {
     ///     from: string,
-    ///     fromType: number|null,
+    ///     fromType: string|number|null, // e.g. "vesting" or its numeric value 1
     ///     to: string|null, (null only valid for contract creation)
-    ///     toType: number|null,
+    ///     toType: string|number|null,
     ///     value: number, (in Luna)
     ///     fee: number, (in Luna)
     ///     flags: number|null,
@@ -145,9 +151,9 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     /// ```text
     /// {
     ///     from: string,
-    ///     fromType: number|null,
+    ///     fromType: string|number|null, // e.g. "vesting" or its numeric value 1
     ///     to: string|null, (null only valid for contract creation)
-    ///     toType: number|null,
+    ///     toType: string|number|null,
     ///     value: number, (in Luna)
     ///     fee: number, (in Luna)
     ///     flags: number|null,
@@ -182,17 +188,25 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     /// ```text
     /// {
     ///     hash: string,
+    ///     data: string, // hex encoded
+    ///     sender: string, // user friendly address
+    ///     senderType: string, // "basic", "vesting", "htlc" or "staking"
+    ///     recipient: string, // user friendly address
+    ///     recipientType: string,
+    ///     value: number,
+    ///     fee: number,
+    ///     validityStartHeight: number,
+    ///     networkId: number,
+    ///     flags: number,
+    ///     proof: string, // hex encoded
+    ///
+    ///     // Nimiq 1.0 fields
     ///     from: string, // hex encoded
     ///     fromAddress: string, // user friendly address
     ///     fromType: number,
     ///     to: string, // hex encoded
     ///     toAddress: string, // user friendly address
     ///     toType: number,
-    ///     value: number,
-    ///     fee: number,
-    ///     data: string,
-    ///     flags: number,
-    ///     validityStartHeight: number,
     ///
     ///     // Not applicable:
     ///     blockHash: null,
@@ -215,8 +229,242 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
         }
     }
 
+    /// Returns the current mempool filter rules.
+    /// The rules object looks like the following:
+    /// ```text
+    /// {
+    ///     txFee: number, (in Luna)
+    ///     txFeePerByte: number,
+    ///     txValue: number, (in Luna)
+    ///     txValueTotal: number, (in Luna)
+    ///     contractFee: number, (in Luna)
+    ///     contractFeePerByte: number,
+    ///     contractValue: number, (in Luna)
+    ///     creationFee: number, (in Luna)
+    ///     creationFeePerByte: number,
+    ///     creationValue: number, (in Luna)
+    ///     senderBalance: number, (in Luna)
+    ///     recipientBalance: number, (in Luna)
+    ///     denyAddresses: Array<string>,
+    ///     allowSenders: Array<string>,
+    ///     denyCreationTypes: Array<string>, (lowercase account type names, numbers are accepted as well)
+    /// }
+    /// ```
+    pub(crate) fn mempool_filter_rules(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        Ok(rules_to_obj(&self.mempool.filter_rules()))
+    }
+
+    /// Updates the mempool filter rules and returns the new rules.
+    /// Transactions in the mempool that are not accepted by the new rules are evicted.
+    /// Parameters:
+    /// - rules (object): Same format as returned by `mempoolFilterRules`.
+    ///     Fields that are omitted keep their current value.
+    pub(crate) fn set_mempool_filter_rules(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let rules = update_rules(self.mempool.filter_rules(), params.get(0).unwrap_or(&Null))?;
+        self.mempool.set_filter_rules(rules);
+        Ok(rules_to_obj(&self.mempool.filter_rules()))
+    }
+
+    /// Creates a signed transaction that creates a vesting contract.
+    /// Requires the sender account to be a basic account and to be unlocked.
+    /// The transaction is not sent; use `sendRawTransaction` to do so.
+    /// Parameters:
+    /// - contract (object)
+    ///
+    /// The contract looks like the following:
+    /// ```text
+    /// {
+    ///     from: string,
+    ///     owner: string|null, (defaults to `from`)
+    ///     start: number|null, (block number, defaults to 0)
+    ///     stepBlocks: number,
+    ///     stepAmount: number|null, (in Luna, defaults to `value`)
+    ///     totalAmount: number|null, (in Luna, defaults to `value`)
+    ///     value: number, (in Luna)
+    ///     fee: number|null, (in Luna)
+    ///     validityStartHeight: number|null,
+    /// }
+    /// ```
+    /// Fields that can be null are optional.
+    ///
+    /// Returns an object with the raw transaction (`transaction`), its `hash` and
+    /// the `contractAddress` of the vesting contract.
+    pub(crate) fn create_vesting_contract(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let obj = params.get(0).unwrap_or(&Null);
+        let from = parse_address(&obj["from"], "Sender")?;
+        let owner = match &obj["owner"] {
+            &JsonValue::Null => from.clone(),
+            value => parse_address(value, "Owner")?,
+        };
+        let start = parse_u32(&obj["start"], "start")?.unwrap_or(0);
+        let step_blocks = parse_u32(&obj["stepBlocks"], "stepBlocks")?
+            .ok_or_else(|| object! {"message" => "Invalid stepBlocks"})?;
+        let value = parse_coin(&obj["value"], "value")?
+            .ok_or_else(|| object! {"message" => "Invalid value"})?;
+        let step_amount = parse_coin(&obj["stepAmount"], "stepAmount")?.unwrap_or(value);
+        let total_amount = parse_coin(&obj["totalAmount"], "totalAmount")?.unwrap_or(value);
+        let fee = parse_coin(&obj["fee"], "fee")?.unwrap_or(Coin::ZERO);
+        let validity_start_height = parse_u32(&obj["validityStartHeight"], "validityStartHeight")?
+            .unwrap_or_else(|| self.mempool.current_height());
+
+        let data = VestingCreationData {
+            owner,
+            start,
+            step_blocks,
+            step_amount,
+            total_amount,
+        };
+        let transaction = self.with_unlocked_account(&from, |account| {
+            account.create_vesting_transaction(&data, value, fee, validity_start_height, self.mempool.network_id())
+        })?;
+
+        Ok(contract_transaction_to_obj(&transaction))
+    }
+
+    /// Creates a signed transaction that creates a hashed time-locked contract.
+    /// Requires the sender account to be a basic account and to be unlocked.
+    /// The sender is the account that can resolve the contract after the timeout.
+    /// The transaction is not sent; use `sendRawTransaction` to do so.
+    /// Parameters:
+    /// - contract (object)
+    ///
+    /// The contract looks like the following:
+    /// ```text
+    /// {
+    ///     from: string,
+    ///     recipient: string,
+    ///     hashAlgorithm: string|null, ("blake2b" or "sha256", defaults to "blake2b")
+    ///     hashRoot: string, (hex encoded)
+    ///     hashCount: number|null, (defaults to 1)
+    ///     timeout: number, (block number)
+    ///     value: number, (in Luna)
+    ///     fee: number|null, (in Luna)
+    ///     validityStartHeight: number|null,
+    /// }
+    /// ```
+    /// Fields that can be null are optional.
+    ///
+    /// Returns an object with the raw transaction (`transaction`), its `hash` and
+    /// the `contractAddress` of the HTLC.
+    pub(crate) fn create_htlc(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let obj = params.get(0).unwrap_or(&Null);
+        let from = parse_address(&obj["from"], "Sender")?;
+        let recipient = parse_address(&obj["recipient"], "Recipient")?;
+        let hash_algorithm = parse_hash_algorithm(&obj["hashAlgorithm"])?;
+        let hash_root = parse_hash(&obj["hashRoot"], "hashRoot")?;
+        let hash_count = match &obj["hashCount"] {
+            &JsonValue::Null => Some(1),
+            value => value.as_u8().filter(|&count| count > 0),
+        }.ok_or_else(|| object! {"message" => "Invalid hashCount"})?;
+        let timeout = parse_u32(&obj["timeout"], "timeout")?
+            .ok_or_else(|| object! {"message" => "Invalid timeout"})?;
+        let value = parse_coin(&obj["value"], "value")?
+            .ok_or_else(|| object! {"message" => "Invalid value"})?;
+        let fee = parse_coin(&obj["fee"], "fee")?.unwrap_or(Coin::ZERO);
+        let validity_start_height = parse_u32(&obj["validityStartHeight"], "validityStartHeight")?
+            .unwrap_or_else(|| self.mempool.current_height());
+
+        let data = HtlcCreationData {
+            sender: from.clone(),
+            recipient,
+            hash_algorithm,
+            hash_root,
+            hash_count,
+            timeout,
+        };
+        let transaction = self.with_unlocked_account(&from, |account| {
+            account.create_htlc_transaction(&data, value, fee, validity_start_height, self.mempool.network_id())
+        })?;
+
+        Ok(contract_transaction_to_obj(&transaction))
+    }
+
+    /// Creates a signed transaction that moves funds out of a hashed time-locked contract.
+    /// The accounts that have to sign the transaction depend on the proof type and must be unlocked:
+    /// - `regularTransfer`: the HTLC recipient, who reveals `preImage`.
+    /// - `earlyResolve`: both the HTLC recipient and the HTLC sender.
+    /// - `timeoutResolve`: the HTLC sender.
+    ///
+    /// The transaction is not sent; use `sendRawTransaction` to do so.
+    /// Parameters:
+    /// - redemption (object)
+    ///
+    /// The redemption looks like the following:
+    /// ```text
+    /// {
+    ///     from: string, (address of the HTLC)
+    ///     to: string,
+    ///     value: number, (in Luna)
+    ///     fee: number|null, (in Luna)
+    ///     validityStartHeight: number|null,
+    ///     proofType: string, ("regularTransfer", "earlyResolve" or "timeoutResolve")
+    ///     htlcSender: string|null, (required for "earlyResolve" and "timeoutResolve")
+    ///     htlcRecipient: string|null, (required for "regularTransfer" and "earlyResolve")
+    ///     hashAlgorithm: string|null, ("regularTransfer" only, defaults to "blake2b")
+    ///     hashDepth: number|null, ("regularTransfer" only, defaults to 1)
+    ///     hashRoot: string|null, ("regularTransfer" only, hex encoded)
+    ///     preImage: string|null, ("regularTransfer" only, hex encoded)
+    /// }
+    /// ```
+    ///
+    /// Returns an object with the raw transaction (`transaction`) and its `hash`.
+    pub(crate) fn redeem_htlc(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let obj = params.get(0).unwrap_or(&Null);
+        let from = parse_address(&obj["from"], "HTLC")?;
+        let to = parse_address(&obj["to"], "Recipient")?;
+        let value = parse_coin(&obj["value"], "value")?
+            .ok_or_else(|| object! {"message" => "Invalid value"})?;
+        let fee = parse_coin(&obj["fee"], "fee")?.unwrap_or(Coin::ZERO);
+        let validity_start_height = parse_u32(&obj["validityStartHeight"], "validityStartHeight")?
+            .unwrap_or_else(|| self.mempool.current_height());
+
+        let mut transaction = HtlcProof::create_transaction(from, to, value, fee, validity_start_height, self.mempool.network_id());
+
+        match obj["proofType"].as_str() {
+            Some("regularTransfer") => {
+                let htlc_recipient = parse_address(&obj["htlcRecipient"], "HTLC recipient")?;
+                let hash_algorithm = parse_hash_algorithm(&obj["hashAlgorithm"])?;
+                let hash_depth = match &obj["hashDepth"] {
+                    &JsonValue::Null => Some(1),
+                    value => value.as_u8(),
+                }.ok_or_else(|| object! {"message" => "Invalid hashDepth"})?;
+                let hash_root = parse_hash(&obj["hashRoot"], "hashRoot")?;
+                let pre_image = parse_hash(&obj["preImage"], "preImage")?;
+                self.with_unlocked_account(&htlc_recipient, |account| {
+                    account.sign_htlc_regular_transfer(&mut transaction, hash_algorithm, hash_depth, hash_root, pre_image)
+                })?;
+            },
+            Some("earlyResolve") => {
+                let htlc_recipient = parse_address(&obj["htlcRecipient"], "HTLC recipient")?;
+                let htlc_sender = parse_address(&obj["htlcSender"], "HTLC sender")?;
+                let recipient_proof = self.with_unlocked_account(&htlc_recipient, |account| account.create_signature_proof(&transaction))?;
+                let sender_proof = self.with_unlocked_account(&htlc_sender, |account| account.create_signature_proof(&transaction))?;
+                transaction.proof = HtlcProof::EarlyResolve { recipient_proof, sender_proof }.serialize_to_vec();
+            },
+            Some("timeoutResolve") => {
+                let htlc_sender = parse_address(&obj["htlcSender"], "HTLC sender")?;
+                self.with_unlocked_account(&htlc_sender, |account| account.sign_htlc_timeout_resolve(&mut transaction))?;
+            },
+            _ => return Err(object! {"message" => "Invalid proofType"}),
+        }
+
+        Ok(object! {
+            "transaction" => hex::encode(transaction.serialize_to_vec()),
+            "hash" => transaction.hash::<Blake2bHash>().to_hex()
+        })
+    }
+
     // Helper functions
 
+    fn with_unlocked_account<F: FnOnce(&WalletAccount) -> R, R>(&self, address: &Address, f: F) -> Result<R, JsonValue> {
+        let unlocked_wallets = self.unlocked_wallets.as_ref()
+            .ok_or_else(|| object! {"message" => "No wallets"})?;
+        let unlocked_wallets = unlocked_wallets.read();
+        let account = unlocked_wallets.get(address)
+            .ok_or_else(|| object! {"message" => format!("Account {} is locked", address.to_user_friendly_address())})?;
+        Ok(f(account))
+    }
+
     pub(crate) fn push_transaction(&self, transaction: Transaction) -> Result<JsonValue, JsonValue> {
         let txid = transaction.hash::<Blake2bHash>();
         match self.mempool.push_transaction(transaction) {
@@ -226,94 +474,211 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     }
 }
 
+/// Returns the serde representation of the transaction (see `docs/json.md`), extended by its hash
+/// and, if known, the block it was included in. The fields of the Nimiq 1.0 RPC are kept as well.
 pub(crate) fn transaction_to_obj(transaction: &Transaction, context: Option<&TransactionContext>, head_height: Option<u32>) -> JsonValue {
-    object! {
-        "hash" => transaction.hash::<Blake2bHash>().to_hex(),
-        "blockHash" => context.map(|c| c.block_hash.into()).unwrap_or(Null),
-        "blockNumber" => context.map(|c| c.block_number.into()).unwrap_or(Null),
-        "timestamp" => context.map(|c| c.timestamp.into()).unwrap_or(Null),
-        "confirmations" => context.map(|c| head_height.map(|height| (height - c.block_number).into()).unwrap_or(Null)).unwrap_or(Null),
-        "transactionIndex" => context.map(|c| c.index.into()).unwrap_or(Null),
-        "from" => transaction.sender.to_hex(),
-        "fromAddress" => transaction.sender.to_user_friendly_address(),
-        "to" => transaction.recipient.to_hex(),
-        "toAddress" => transaction.recipient.to_user_friendly_address(),
-        "value" => u64::from(transaction.value),
-        "fee" => u64::from(transaction.fee),
-        "data" => hex::encode(&transaction.data),
-        "flags" => transaction.flags.bits(),
-        "validityStartHeight" => transaction.validity_start_height
-    }
+    let mut obj = serde_to_json(transaction);
+    obj["hash"] = transaction.hash::<Blake2bHash>().to_hex().into();
+    obj["from"] = transaction.sender.to_hex().into();
+    obj["fromAddress"] = transaction.sender.to_user_friendly_address().into();
+    obj["fromType"] = (transaction.sender_type as u8).into();
+    obj["to"] = transaction.recipient.to_hex().into();
+    obj["toAddress"] = transaction.recipient.to_user_friendly_address().into();
+    obj["toType"] = (transaction.recipient_type as u8).into();
+    obj["blockHash"] = context.map(|c| c.block_hash.into()).unwrap_or(Null);
+    obj["blockNumber"] = context.map(|c| c.block_number.into()).unwrap_or(Null);
+    obj["timestamp"] = context.map(|c| c.timestamp.into()).unwrap_or(Null);
+    obj["confirmations"] = context.map(|c| head_height.map(|height| (height - c.block_number).into()).unwrap_or(Null)).unwrap_or(Null);
+    obj["transactionIndex"] = context.map(|c| c.index.into()).unwrap_or(Null);
+    obj
+}
+
+/// Transaction parameters as accepted by `createRawTransaction` and `sendTransaction`.
+/// Fields use the same representation as `Transaction`, omitted ones get default values.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionParams {
+    from: Address,
+    from_type: Option<AccountType>,
+    to: Option<Address>,
+    to_type: Option<AccountType>,
+    value: Coin,
+    fee: Coin,
+    flags: Option<TransactionFlags>,
+    #[serde(default, with = "utils::serde_hex")]
+    data: Vec<u8>,
+    validity_start_height: Option<u32>,
 }
 
 // {
 //     from: string,
-//     fromType: number|null,
-//     to: string,
-//     toType: number|null,
+//     fromType: string|number|null,
+//     to: string|null,
+//     toType: string|number|null,
 //     value: number, (in Luna)
 //     fee: number, (in Luna)
 //     flags: number|null,
-//     data: string|null,
+//     data: string, (hex, may be omitted)
 //     validityStartHeight: number|null,
 // }
 pub(crate) fn obj_to_transaction(obj: &JsonValue, current_height: u32, network_id: NetworkId) -> Result<Transaction, JsonValue> {
-    let from = Address::from_any_str(obj["from"].as_str()
-        .ok_or_else(|| object! {"message" => "Sender address must be a string"})?)
-        .map_err(|_|  object! {"message" => "Sender address invalid"})?;
-
-    let from_type = match &obj["fromType"] {
-        &JsonValue::Null => Some(AccountType::Basic),
-        n @ JsonValue::Number(_) => n.as_u8().and_then(AccountType::from_int),
-        _ => None
-    }.ok_or_else(|| object! {"message" => "Invalid sender account type"})?;
-
-    let to = match obj["to"] {
-        JsonValue::String(ref recipient) => Some(Address::from_any_str(recipient)
-            .map_err(|_|  object! {"message" => "Recipient address invalid"})?),
-        _ => None,
+    let params: TransactionParams = serde_from_json(obj)?;
+
+    let from_type = params.from_type.unwrap_or(AccountType::Basic);
+    let to_type = params.to_type.unwrap_or(AccountType::Basic);
+    let flags = params.flags.unwrap_or_else(TransactionFlags::empty);
+    let validity_start_height = params.validity_start_height.unwrap_or(current_height);
+
+    match params.to {
+        None if to_type != AccountType::Basic && flags.contains(TransactionFlags::CONTRACT_CREATION) => {
+            Ok(Transaction::new_contract_creation(params.data, params.from, from_type, to_type, params.value, params.fee, validity_start_height, network_id))
+        },
+        Some(to) if !flags.contains(TransactionFlags::CONTRACT_CREATION) => {
+            Ok(Transaction::new_extended(params.from, from_type, to, to_type, params.value, params.fee, params.data, validity_start_height, network_id))
+        },
+        _ => Err(object! {"message" => "Invalid combination of flags, toType and to."}),
+    }
+}
+
+fn contract_transaction_to_obj(transaction: &Transaction) -> JsonValue {
+    object! {
+        "transaction" => hex::encode(transaction.serialize_to_vec()),
+        "hash" => transaction.hash::<Blake2bHash>().to_hex(),
+        "contractAddress" => transaction.recipient.to_user_friendly_address()
+    }
+}
+
+fn parse_address(value: &JsonValue, name: &str) -> Result<Address, JsonValue> {
+    value.as_str()
+        .and_then(|s| Address::from_any_str(s).ok())
+        .ok_or_else(|| object! {"message" => format!("{} address invalid", name)})
+}
+
+fn parse_coin(value: &JsonValue, name: &str) -> Result<Option<Coin>, JsonValue> {
+    match value {
+        &JsonValue::Null => Ok(None),
+        value => value.as_u64()
+            .and_then(|v| Coin::try_from(v).ok())
+            .map(Some)
+            .ok_or_else(|| object! {"message" => format!("Invalid {}", name)}),
+    }
+}
+
+fn parse_u32(value: &JsonValue, name: &str) -> Result<Option<u32>, JsonValue> {
+    match value {
+        &JsonValue::Null => Ok(None),
+        value => value.as_u32()
+            .map(Some)
+            .ok_or_else(|| object! {"message" => format!("Invalid {}", name)}),
+    }
+}
+
+fn parse_hash(value: &JsonValue, name: &str) -> Result<AnyHash, JsonValue> {
+    value.as_str()
+        .and_then(|s| AnyHash::from_str(s).ok())
+        .ok_or_else(|| object! {"message" => format!("{} must be a hex encoded 32 byte hash", name)})
+}
+
+fn parse_hash_algorithm(value: &JsonValue) -> Result<HashAlgorithm, JsonValue> {
+    match value {
+        &JsonValue::Null => Ok(HashAlgorithm::Blake2b),
+        value => match value.as_str() {
+            Some("blake2b") => Ok(HashAlgorithm::Blake2b),
+            Some("sha256") => Ok(HashAlgorithm::Sha256),
+            _ => Err(object! {"message" => "Invalid hashAlgorithm"}),
+        },
+    }
+}
+
+pub(crate) fn rules_to_obj(rules: &Rules) -> JsonValue {
+    object! {
+        "txFee" => u64::from(rules.tx_fee),
+        "txFeePerByte" => rules.tx_fee_per_byte,
+        "txValue" => u64::from(rules.tx_value),
+        "txValueTotal" => u64::from(rules.tx_value_total),
+        "contractFee" => u64::from(rules.contract_fee),
+        "contractFeePerByte" => rules.contract_fee_per_byte,
+        "contractValue" => u64::from(rules.contract_value),
+        "creationFee" => u64::from(rules.creation_fee),
+        "creationFeePerByte" => rules.creation_fee_per_byte,
+        "creationValue" => u64::from(rules.creation_value),
+        "senderBalance" => u64::from(rules.sender_balance),
+        "recipientBalance" => u64::from(rules.recipient_balance),
+        "denyAddresses" => rules.denied_addresses.iter().map(|a| JsonValue::from(a.to_user_friendly_address())).collect::<Array>(),
+        "allowSenders" => rules.allowed_senders.iter().map(|a| JsonValue::from(a.to_user_friendly_address())).collect::<Array>(),
+        "denyCreationTypes" => rules.denied_creation_types.iter().map(|t| JsonValue::from(t.to_string().to_lowercase())).collect::<Array>()
+    }
+}
+
+pub(crate) fn update_rules(mut rules: Rules, obj: &JsonValue) -> Result<Rules, JsonValue> {
+    if !obj.is_object() {
+        return Err(object! {"message" => "Rules must be an object"});
+    }
+
+    let coin = |key: &str, current: Coin| -> Result<Coin, JsonValue> {
+        match &obj[key] {
+            &JsonValue::Null => Ok(current),
+            value => value.as_u64()
+                .and_then(|v| Coin::try_from(v).ok())
+                .ok_or_else(|| object! {"message" => format!("Invalid {}", key)}),
+        }
+    };
+    let fee_per_byte = |key: &str, current: f64| -> Result<f64, JsonValue> {
+        match &obj[key] {
+            &JsonValue::Null => Ok(current),
+            value => value.as_f64()
+                .ok_or_else(|| object! {"message" => format!("Invalid {}", key)}),
+        }
+    };
+    let addresses = |key: &str| -> Result<Option<HashSet<Address>>, JsonValue> {
+        match &obj[key] {
+            &JsonValue::Null => Ok(None),
+            JsonValue::Array(values) => values.iter()
+                .map(|value| value.as_str()
+                    .and_then(|s| Address::from_any_str(s).ok())
+                    .ok_or_else(|| object! {"message" => format!("Invalid address in {}", key)}))
+                .collect::<Result<HashSet<Address>, JsonValue>>()
+                .map(Some),
+            _ => Err(object! {"message" => format!("{} must be an array", key)}),
+        }
     };
 
-    let to_type = match &obj["toType"] {
-        &JsonValue::Null => Some(AccountType::Basic),
-        n @ JsonValue::Number(_) => n.as_u8().and_then(AccountType::from_int),
-        _ => None
-    }.ok_or_else(|| object! {"message" => "Invalid recipient account type"})?;
-
-    let value = Coin::try_from(obj["value"].as_u64()
-        .ok_or_else(|| object! {"message" => "Invalid transaction value"})?)
-        .map_err(|_| object! {"message" => "Invalid transaction value"})?;
-
-    // FIXME Wrong fee
-    let fee = Coin::try_from(obj["value"].as_u64()
-        .ok_or_else(|| object! {"message" => "Invalid transaction fee"})?)
-        .map_err(|_| object! {"message" => "Invalid transaction fee"})?;
-
-    let flags = obj["flags"].as_u8()
-        .map_or_else(|| Some(TransactionFlags::empty()), TransactionFlags::from_bits)
-        .ok_or_else(|| object! {"message" => "Invalid transaction flags"})?;
-
-    let data = obj["data"].as_str()
-        .map(hex::decode)
-        .transpose().map_err(|_| object! {"message" => "Invalid transaction data"})?
-        .unwrap_or_else(|| vec![]);
-
-    let validity_start_height = match &obj["validityStartHeight"] {
-        &JsonValue::Null => Some(current_height),
-        n @ JsonValue::Number(_) => n.as_u32(),
-        _ => None
-    }.ok_or_else(|| object! {"message" => "Invalid validityStartHeight"})?;
-
-    if to_type != AccountType::Basic
-        && flags.contains(TransactionFlags::CONTRACT_CREATION)
-        && to.is_none() {
-        Ok(Transaction::new_contract_creation(data, from, from_type, to_type, value, fee, validity_start_height, network_id))
-    } else if to.is_some()
-        && !flags.contains(TransactionFlags::CONTRACT_CREATION) {
-        Ok(Transaction::new_extended(from, from_type, to.unwrap(), to_type, value, fee, data, validity_start_height, network_id))
-    } else {
-        Err(object! {"message" => "Invalid combination of flags, toType and to."})
+    rules.tx_fee = coin("txFee", rules.tx_fee)?;
+    rules.tx_fee_per_byte = fee_per_byte("txFeePerByte", rules.tx_fee_per_byte)?;
+    rules.tx_value = coin("txValue", rules.tx_value)?;
+    rules.tx_value_total = coin("txValueTotal", rules.tx_value_total)?;
+    rules.contract_fee = coin("contractFee", rules.contract_fee)?;
+    rules.contract_fee_per_byte = fee_per_byte("contractFeePerByte", rules.contract_fee_per_byte)?;
+    rules.contract_value = coin("contractValue", rules.contract_value)?;
+    rules.creation_fee = coin("creationFee", rules.creation_fee)?;
+    rules.creation_fee_per_byte = fee_per_byte("creationFeePerByte", rules.creation_fee_per_byte)?;
+    rules.creation_value = coin("creationValue", rules.creation_value)?;
+    rules.sender_balance = coin("senderBalance", rules.sender_balance)?;
+    rules.recipient_balance = coin("recipientBalance", rules.recipient_balance)?;
+
+    if let Some(denied_addresses) = addresses("denyAddresses")? {
+        rules.denied_addresses = denied_addresses;
     }
+    if let Some(allowed_senders) = addresses("allowSenders")? {
+        rules.allowed_senders = allowed_senders;
+    }
+    match &obj["denyCreationTypes"] {
+        &JsonValue::Null => {},
+        JsonValue::Array(values) => {
+            rules.denied_creation_types = values.iter()
+                .map(|value| {
+                    let ty = match value.as_str() {
+                        Some(name) => AccountType::from_str(name).ok(),
+                        None => value.as_u8().and_then(AccountType::from_int),
+                    };
+                    ty.ok_or_else(|| object! {"message" => "Invalid account type in denyCreationTypes"})
+                })
+                .collect::<Result<HashSet<AccountType>, JsonValue>>()?;
+        },
+        _ => return Err(object! {"message" => "denyCreationTypes must be an array"}),
+    }
+
+    Ok(rules)
 }
 
 pub(crate) struct TransactionContext<'a> {
@@ -332,5 +697,10 @@ impl<P: ConsensusProtocol + 'static> Module for MempoolHandler<P> {
         "mempoolContent" => mempool_content,
         "mempool" => mempool,
         "getMempoolTransaction" => get_transaction,
+        "mempoolFilterRules" => mempool_filter_rules,
+        "setMempoolFilterRules" => set_mempool_filter_rules,
+        "createVestingContract" => create_vesting_contract,
+        "createHtlc" => create_htlc,
+        "redeemHtlc" => redeem_htlc,
     }
 }
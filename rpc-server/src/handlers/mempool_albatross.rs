@@ -197,5 +197,10 @@ impl Module for MempoolAlbatrossHandler {
         "retire" => retire,
         "unstake" => unstake,
         "getTransaction" => generic.get_transaction,
+        "mempoolFilterRules" => generic.mempool_filter_rules,
+        "setMempoolFilterRules" => generic.set_mempool_filter_rules,
+        "createVestingContract" => generic.create_vesting_contract,
+        "createHtlc" => generic.create_htlc,
+        "redeemHtlc" => generic.redeem_htlc,
     }
 }
@@ -33,6 +33,7 @@ pub mod blockchain_albatross;
 pub mod mempool;
 pub mod mempool_albatross;
 pub mod network;
+pub mod network_admin;
 pub mod wallet;
 
 
@@ -46,6 +47,7 @@ pub use self::blockchain_albatross::BlockchainAlbatrossHandler;
 pub use self::mempool::MempoolHandler;
 pub use self::mempool_albatross::MempoolAlbatrossHandler;
 pub use self::network::NetworkHandler;
+pub use self::network_admin::NetworkAdminHandler;
 pub use self::wallet::{WalletHandler, UnlockedWalletManager};
 
 
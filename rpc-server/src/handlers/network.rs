@@ -167,7 +167,6 @@ impl<P: ConsensusProtocol + 'static> NetworkHandler<P> {
             state.get_connection_by_peer_address(&peer_address_info.peer_address)
         });
         let peer = connection_info.and_then(|conn| conn.peer());
-        let network_connection = connection_info.and_then(|conn| conn.network_connection());
 
         object!{
             "id" => peer_address_info.peer_address.peer_id().to_hex(),
@@ -180,8 +179,8 @@ impl<P: ConsensusProtocol + 'static> NetworkHandler<P> {
             "headHash" => peer.map(|peer| peer.head_hash.to_hex().into()).unwrap_or(Null),
             "score" => score.map(|s| s.into()).unwrap_or(Null),
             "latency" => connection_info.map(|conn| conn.statistics().latency_median().into()).unwrap_or(Null),
-            "rx" => network_connection.map(|conn| conn.metrics().bytes_received().into()).unwrap_or(Null),
-            "tx" => network_connection.map(|conn| conn.metrics().bytes_sent().into()).unwrap_or(Null)
+            "rx" => connection_info.and_then(|conn| conn.traffic()).map(|traffic| traffic.bytes_received().into()).unwrap_or(Null),
+            "tx" => connection_info.and_then(|conn| conn.traffic()).map(|traffic| traffic.bytes_sent().into()).unwrap_or(Null)
         }
     }
 
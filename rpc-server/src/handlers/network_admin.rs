@@ -0,0 +1,162 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use json::{JsonValue, Null, object};
+
+use beserial::Deserialize;
+use consensus::{ConsensusProtocol, Consensus};
+use network_primitives::address::{PeerId, PeerUri};
+use network_primitives::address::peer_address::PeerAddress;
+use nimiq_network::address::peer_address_book::DEFAULT_BAN_TIME;
+use nimiq_network::connection::close_type::CloseType;
+use nimiq_network::Network;
+use utils::time::systemtime_to_timestamp;
+
+use crate::handler::Method;
+use crate::handlers::Module;
+
+/// Methods to manage the node's peers. Only available if RPC authentication is configured.
+pub struct NetworkAdminHandler<P: ConsensusProtocol + 'static> {
+    pub network: Arc<Network<P::Blockchain>>,
+}
+
+impl<P: ConsensusProtocol + 'static> NetworkAdminHandler<P> {
+    pub fn new(consensus: &Arc<Consensus<P>>) -> Self {
+        NetworkAdminHandler {
+            network: consensus.network.clone(),
+        }
+    }
+
+    /// Connects to a peer.
+    /// Parameters:
+    /// - uri (string): The URI of the peer. Unknown peers must be given with their public key.
+    ///
+    /// Returns true if the connection is being established.
+    pub(crate) fn peer_connect(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let peer_uri = Self::parse_peer_uri(params.get(0).unwrap_or(&Null))?;
+
+        let peer_address = match self.known_peer_address(&peer_uri)? {
+            Some(peer_address) => peer_address,
+            None => {
+                let mut peer_address = peer_uri.as_seed_peer_address()
+                    .map_err(|e| object!{"message" => format!("Unknown peer: {}", e)})?;
+                // Seed addresses can't be banned, so peers added manually are regular addresses.
+                // The peer's signed address replaces this one once we're connected.
+                peer_address.timestamp = systemtime_to_timestamp(SystemTime::now());
+                let peer_id = peer_address.peer_id().clone();
+                self.network.addresses.add(None, vec![peer_address]);
+                self.network.addresses.state().get_by_peer_id(&peer_id)
+                    .ok_or_else(|| object!{"message" => "Peer address was rejected"})?
+            },
+        };
+
+        if !self.network.connections.connect_outbound(peer_address) {
+            return Err(object!{"message" => "Connection request was rejected"});
+        }
+        Ok(true.into())
+    }
+
+    /// Disconnects from a peer.
+    /// Parameters:
+    /// - uri (string): The URI of the peer.
+    /// - closeType (number, optional): The numeric `CloseType`. Default: 90 (manual peer disconnect).
+    ///
+    /// Returns true if the peer was connected.
+    pub(crate) fn peer_disconnect(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let peer_address = self.peer_address(params.get(0).unwrap_or(&Null))?;
+        let ty = match params.get(1).unwrap_or(&Null) {
+            Null => CloseType::ManualPeerDisconnect,
+            ty => {
+                let code = ty.as_u16().ok_or_else(|| object!{"message" => "Invalid close type"})?;
+                CloseType::deserialize_from_vec(&code.to_be_bytes().to_vec())
+                    .map_err(|_| object!{"message" => "Unknown close type"})?
+            },
+        };
+
+        Ok(self.close(&peer_address, ty).into())
+    }
+
+    /// Bans a peer and disconnects from it.
+    /// Parameters:
+    /// - uri (string): The URI of the peer.
+    /// - duration (number, optional): The ban duration in seconds. Default: 600.
+    ///
+    /// Connected peers are banned at least for the default duration, since closing
+    /// the connection also bans the peer's IP.
+    pub(crate) fn peer_ban(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let peer_address = self.peer_address(params.get(0).unwrap_or(&Null))?;
+        let duration = match params.get(1).unwrap_or(&Null) {
+            Null => DEFAULT_BAN_TIME,
+            duration => duration.as_u64()
+                .map(Duration::from_secs)
+                .ok_or_else(|| object!{"message" => "Invalid duration"})?,
+        };
+
+        self.network.addresses.ban(Arc::clone(&peer_address), duration);
+        self.close(&peer_address, CloseType::ManualPeerBan);
+        Ok(true.into())
+    }
+
+    /// Lifts the ban on a peer and its IP address.
+    /// Parameters:
+    /// - uri (string): The URI of the peer.
+    ///
+    /// Returns true if the peer was banned.
+    pub(crate) fn peer_unban(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let peer_address = self.peer_address(params.get(0).unwrap_or(&Null))?;
+
+        let unbanned = self.network.addresses.unban(&peer_address);
+        if peer_address.net_address.is_reliable() {
+            self.network.connections.unban_ip(&peer_address.net_address);
+        }
+        Ok(unbanned.into())
+    }
+
+    fn parse_peer_uri(uri: &JsonValue) -> Result<PeerUri, JsonValue> {
+        uri.as_str()
+            .ok_or_else(|| object!{"message" => "Invalid peer URI"})
+            .and_then(|uri| PeerUri::from_str(uri)
+                .map_err(|e| object!{"message" => e.to_string()}))
+    }
+
+    fn known_peer_address(&self, peer_uri: &PeerUri) -> Result<Option<Arc<PeerAddress>>, JsonValue> {
+        let peer_id = match peer_uri.peer_id() {
+            Some(peer_id) => PeerId::from_str(peer_id).map_err(|e| object!{"message" => e.to_string()})?,
+            None => return Ok(None),
+        };
+        Ok(self.network.addresses.state().get_by_peer_id(&peer_id))
+    }
+
+    fn peer_address(&self, uri: &JsonValue) -> Result<Arc<PeerAddress>, JsonValue> {
+        let peer_uri = Self::parse_peer_uri(uri)?;
+        if peer_uri.peer_id().is_none() {
+            return Err(object!{"message" => "URI must contain peer ID"});
+        }
+        self.known_peer_address(&peer_uri)?
+            .ok_or_else(|| object!{"message" => "Unknown peer"})
+    }
+
+    /// Closes the connection to `peer_address`, if there is one.
+    fn close(&self, peer_address: &PeerAddress, ty: CloseType) -> bool {
+        let peer_channel = self.network.connections.state()
+            .get_connection_by_peer_address(peer_address)
+            .and_then(|info| info.peer_channel());
+        match peer_channel {
+            Some(peer_channel) => {
+                peer_channel.close(ty);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+impl<P: ConsensusProtocol + 'static> Module for NetworkAdminHandler<P> {
+    rpc_module_methods! {
+        "peerConnect" => peer_connect,
+        "peerDisconnect" => peer_disconnect,
+        "peerBan" => peer_ban,
+        "peerUnban" => peer_unban,
+    }
+}
@@ -1,15 +1,19 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use hex;
 use json::{JsonValue, Null, object};
 use parking_lot::RwLock;
 
 use beserial::{Deserialize, Serialize};
-use keys::{Address, KeyPair, PrivateKey, PublicKey, Signature};
+use transaction::Transaction;
+use keys::{Address, KeyPair, PrivateKey, PublicKey, SecureGenerate, Signature};
+use keys::multisig::{Commitment, CommitmentPair, PartialSignature, RandomSecret};
+use mnemonic::{Entropy, Mnemonic, WORDLIST_EN};
 use nimiq_database::Environment;
-use nimiq_wallet::{WalletAccount, WalletStore};
+use nimiq_wallet::{HdWallet, MultiSigAccount, WalletAccount, WalletStore};
 use utils::otp::{Locked, Unlocked};
 
 use crate::handler::Method;
@@ -44,13 +48,23 @@ impl UnlockedWalletManager {
 pub struct WalletHandler {
     wallet_store: WalletStore,
     pub unlocked_wallets: Arc<RwLock<UnlockedWalletManager>>,
+    /// Secrets of the multisig commitments we handed out, keyed by their commitment, with the
+    /// time they were created. They never leave the node and are removed once they were used
+    /// for signing or expired.
+    commitment_secrets: RwLock<HashMap<[u8; Commitment::SIZE], (RandomSecret, Instant)>>,
 }
 
 impl WalletHandler {
+    /// Maximum number of unused commitments the node keeps secrets for.
+    const MAX_COMMITMENT_SECRETS: usize = 1000;
+    /// Time after which an unused commitment can't be used for signing anymore.
+    const COMMITMENT_SECRET_EXPIRY: Duration = Duration::from_secs(60 * 60); // 1 hour
+
     pub fn new(env: Environment) -> Self {
         WalletHandler {
             wallet_store: WalletStore::new(env),
             unlocked_wallets: Arc::new(RwLock::new(UnlockedWalletManager::new())),
+            commitment_secrets: RwLock::new(HashMap::new()),
         }
     }
 
@@ -127,6 +141,237 @@ impl WalletHandler {
         })
     }
 
+    /// Creates a new HD wallet from a freshly generated mnemonic and derives its first account.
+    /// Parameters:
+    /// - passphrase (optional, string): The passphrase to lock the seed and derived keys with.
+    /// - mnemonicPassword (optional, string): The BIP39 password used to compute the seed.
+    ///
+    /// The return value is an object:
+    /// {
+    ///     id: string,
+    ///     mnemonic: string,
+    ///     address: string,
+    /// }
+    pub(crate) fn create_hd_wallet(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let mnemonic = Entropy::generate_default_csprng().to_mnemonic(WORDLIST_EN);
+
+        let mut result = self.store_hd_wallet(&mnemonic, params.get(0), params.get(1))?;
+        result["mnemonic"] = mnemonic.to_string().into();
+        Ok(result)
+    }
+
+    /// Imports an HD wallet from a mnemonic and derives its first account.
+    /// Fails if the wallet was already imported, use `deriveNextAddress` to derive more accounts.
+    /// Parameters:
+    /// - mnemonic (string): The 24 words, separated by spaces.
+    /// - passphrase (optional, string): The passphrase to lock the seed and derived keys with.
+    /// - mnemonicPassword (optional, string): The BIP39 password used to compute the seed.
+    ///
+    /// The return value is an object:
+    /// {
+    ///     id: string,
+    ///     address: string,
+    /// }
+    pub(crate) fn import_hd_wallet(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let mnemonic: Mnemonic = params.get(0).unwrap_or(&Null).as_str()
+            .ok_or_else(|| object!{"message" => "Mnemonic must be a string"})?
+            .parse()
+            .map_err(|_| object!{"message" => "Invalid mnemonic"})?;
+
+        self.store_hd_wallet(&mnemonic, params.get(1), params.get(2))
+    }
+
+    /// Returns the ids of all HD wallets in the store.
+    pub(crate) fn list_hd_wallets(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        Ok(JsonValue::Array(self.wallet_store.list_hd_wallets(None).iter().map(|id| {
+            JsonValue::String(id.to_user_friendly_address())
+        }).collect()))
+    }
+
+    /// Derives the next account of an HD wallet and stores it, locked with the wallet's passphrase.
+    /// Parameters:
+    /// - walletId (string)
+    /// - passphrase (optional, string)
+    /// Returns the user friendly address of the derived account.
+    pub(crate) fn derive_next_address(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let wallet_id = Address::from_any_str(params.get(0)
+            .unwrap_or_else(|| &Null).as_str()
+            .ok_or_else(|| object!{"message" => "Wallet id must be a string"})?)
+            .map_err(|_|  object!{"message" => "Wallet id invalid"})?;
+
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = params.get(1).map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+
+        let mut txn = self.wallet_store.create_write_transaction();
+        let mut hd_wallet = self.wallet_store.get_hd_wallet(&wallet_id, Some(&txn))
+            .ok_or_else(|| object!{"message" => "Wallet does not exist"})?;
+
+        let account = hd_wallet.derive_next_account(passphrase.as_bytes())
+            .map_err(|e| object!{"message" => e.to_string()})?;
+        let address = account.address.clone();
+        let locked_account = Locked::with_defaults(account, passphrase.as_bytes())
+            .map_err(|e| object!{"message" => format!("Error while deriving: {:?}", e)})?;
+
+        self.wallet_store.put(&address, &locked_account, &mut txn);
+        self.wallet_store.put_hd_wallet(&hd_wallet, &mut txn);
+        txn.commit();
+
+        Ok(JsonValue::String(address.to_user_friendly_address()))
+    }
+
+    /// Returns the accounts derived from an HD wallet.
+    /// Parameters:
+    /// - walletId (string)
+    ///
+    /// The return value is an array of objects:
+    /// {
+    ///     index: number,
+    ///     path: string,
+    ///     address: string,
+    /// }
+    pub(crate) fn list_derived_accounts(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let wallet_id = Address::from_any_str(params.get(0)
+            .unwrap_or_else(|| &Null).as_str()
+            .ok_or_else(|| object!{"message" => "Wallet id must be a string"})?)
+            .map_err(|_|  object!{"message" => "Wallet id invalid"})?;
+
+        let hd_wallet = self.wallet_store.get_hd_wallet(&wallet_id, None)
+            .ok_or_else(|| object!{"message" => "Wallet does not exist"})?;
+
+        Ok(JsonValue::Array(hd_wallet.accounts.iter().enumerate().map(|(index, address)| {
+            object!{
+                "index" => index,
+                "path" => hd_wallet.path(index as u32),
+                "address" => address.to_user_friendly_address(),
+            }
+        }).collect()))
+    }
+
+    /// Creates a multisig account that requires `minSignatures` out of the given public keys to sign.
+    /// Parameters:
+    /// - minSignatures (number)
+    /// - publicKeys (array of strings): Hex encoded public keys of all participants.
+    /// Returns the user friendly address of the multisig account.
+    pub(crate) fn create_multisig_account(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let min_signatures = params.get(0).and_then(JsonValue::as_u8)
+            .ok_or_else(|| object!{"message" => "Invalid number of signatures"})?;
+        let public_keys = Self::parse_public_keys(params.get(1).unwrap_or(&Null))?;
+
+        let account = MultiSigAccount::new(min_signatures, public_keys)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+
+        let mut txn = self.wallet_store.create_write_transaction();
+        self.wallet_store.put_multisig(&account, &mut txn);
+        txn.commit();
+
+        Ok(JsonValue::String(account.address.to_user_friendly_address()))
+    }
+
+    /// Returns all multisig accounts in the store.
+    ///
+    /// The return value is an array of objects:
+    /// {
+    ///     address: string,
+    ///     minSignatures: number,
+    ///     publicKeys: Array<string>,
+    /// }
+    pub(crate) fn list_multisig_accounts(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let txn = self.wallet_store.create_read_transaction();
+        Ok(JsonValue::Array(self.wallet_store.list_multisig(Some(&txn)).iter()
+            .filter_map(|address| self.wallet_store.get_multisig(address, Some(&txn)))
+            .map(|account| object!{
+                "address" => account.address.to_user_friendly_address(),
+                "minSignatures" => account.min_signatures,
+                "publicKeys" => account.public_keys.iter().map(|key| JsonValue::from(key.to_hex())).collect::<Vec<JsonValue>>(),
+            })
+            .collect()))
+    }
+
+    /// Creates a fresh commitment for a multisig signing round. The commitment has to be shared
+    /// with the other signers. Its secret is kept by the node and used once by
+    /// `partiallySignMultisigTransaction` within an hour.
+    /// Returns the hex encoded commitment.
+    pub(crate) fn create_multisig_commitment(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let mut commitment_secrets = self.commitment_secrets.write();
+        commitment_secrets.retain(|_, (_, created)| created.elapsed() < Self::COMMITMENT_SECRET_EXPIRY);
+        if commitment_secrets.len() >= Self::MAX_COMMITMENT_SECRETS {
+            return Err(object!{"message" => "Too many unused commitments"});
+        }
+
+        let commitment_pair = CommitmentPair::generate_default_csprng();
+        let commitment = commitment_pair.commitment().to_bytes();
+        commitment_secrets.insert(commitment, (*commitment_pair.random_secret(), Instant::now()));
+        Ok(JsonValue::String(hex::encode(commitment)))
+    }
+
+    /// Creates a partial signature for a transaction from a multisig account.
+    /// The signer account must be unlocked.
+    /// Parameters:
+    /// - multisigAddress (string)
+    /// - signerAddress (string): Address of the own (unlocked) key that participates in the signing round.
+    /// - transaction (string): The hex encoded raw transaction.
+    /// - signers (array of strings): Hex encoded public keys of all signers in this round.
+    /// - commitment (string): The own commitment returned by `createMultisigCommitment`. It can only be used once
+    ///   and expires after an hour.
+    /// - commitments (array of strings): The commitments of all signers in this round.
+    /// Returns the hex encoded partial signature.
+    pub(crate) fn partially_sign_multisig_transaction(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let account = self.get_multisig_account(params.get(0).unwrap_or(&Null))?;
+        let signer_address = Address::from_any_str(params.get(1)
+            .unwrap_or_else(|| &Null).as_str()
+            .ok_or_else(|| object!{"message" => "Signer address must be a string"})?)
+            .map_err(|_|  object!{"message" => "Signer address invalid"})?;
+        let transaction = Self::parse_raw_transaction(params.get(2).unwrap_or(&Null))?;
+        let signers = Self::parse_public_keys(params.get(3).unwrap_or(&Null))?;
+        let commitment = Self::parse_bytes(params.get(4).unwrap_or(&Null), "commitment")?;
+        let commitments = Self::parse_commitments(params.get(5).unwrap_or(&Null))?;
+
+        if !commitments.iter().any(|c| c.to_bytes() == commitment) {
+            return Err(object!{"message" => "Own commitment is not part of the commitments"});
+        }
+
+        let unlocked_wallets = self.unlocked_wallets.read();
+        let wallet_account = unlocked_wallets.get(&signer_address)
+            .ok_or_else(|| object!{"message" => "Signer account is locked"})?;
+
+        // Reusing a secret for another signature would reveal the private key.
+        let secret = self.commitment_secrets.write().remove(&commitment)
+            .filter(|(_, created)| created.elapsed() < Self::COMMITMENT_SECRET_EXPIRY)
+            .map(|(secret, _)| secret)
+            .ok_or_else(|| object!{"message" => "Unknown, expired or already used commitment"})?;
+
+        let partial_signature = account.partially_sign_transaction(&transaction, &wallet_account.key_pair, &signers, &secret, &commitments)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+
+        Ok(JsonValue::String(hex::encode(partial_signature.as_bytes())))
+    }
+
+    /// Assembles the partial signatures of all signers into the final signature proof of a
+    /// transaction from a multisig account.
+    /// Parameters:
+    /// - multisigAddress (string)
+    /// - transaction (string): The hex encoded raw transaction.
+    /// - signers (array of strings): Hex encoded public keys of all signers in this round.
+    /// - commitments (array of strings): The commitments of all signers in this round.
+    /// - partialSignatures (array of strings): The partial signatures of all signers in this round.
+    /// Returns the hex encoded signed transaction, which can be sent with `sendRawTransaction`.
+    pub(crate) fn sign_multisig_transaction(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let account = self.get_multisig_account(params.get(0).unwrap_or(&Null))?;
+        let mut transaction = Self::parse_raw_transaction(params.get(1).unwrap_or(&Null))?;
+        let signers = Self::parse_public_keys(params.get(2).unwrap_or(&Null))?;
+        let commitments = Self::parse_commitments(params.get(3).unwrap_or(&Null))?;
+        let partial_signatures = params.get(4).unwrap_or(&Null).members()
+            .map(|value| Self::parse_bytes(value, "partial signature").map(PartialSignature::from))
+            .collect::<Result<Vec<PartialSignature>, JsonValue>>()?;
+
+        account.sign_transaction(&mut transaction, &signers, &commitments, &partial_signatures)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+
+        Ok(JsonValue::String(hex::encode(transaction.serialize_to_vec())))
+    }
+
     /// Unlocks a wallet account in memory.
     /// Parameters:
     /// - address (string)
@@ -257,6 +502,85 @@ impl WalletHandler {
         Ok(JsonValue::Boolean(WalletAccount::verify_message(&public_key, &message, &signature)))
     }
 
+    fn get_multisig_account(&self, value: &JsonValue) -> Result<MultiSigAccount, JsonValue> {
+        let address = Address::from_any_str(value.as_str()
+            .ok_or_else(|| object!{"message" => "Multisig address must be a string"})?)
+            .map_err(|_|  object!{"message" => "Multisig address invalid"})?;
+        self.wallet_store.get_multisig(&address, None)
+            .ok_or_else(|| object!{"message" => "Multisig account does not exist"})
+    }
+
+    fn parse_raw_transaction(value: &JsonValue) -> Result<Transaction, JsonValue> {
+        let raw = hex::decode(value.as_str()
+            .ok_or_else(|| object!{"message" => "Raw transaction must be a string"})?)
+            .map_err(|_| object!{"message" => "Raw transaction must be a hex string"})?;
+        Deserialize::deserialize_from_vec(&raw)
+            .map_err(|_| object!{"message" => "Transaction can't be deserialized"})
+    }
+
+    fn parse_public_keys(value: &JsonValue) -> Result<Vec<PublicKey>, JsonValue> {
+        if !value.is_array() {
+            return Err(object!{"message" => "Public keys must be an array"});
+        }
+        value.members()
+            .map(|key| key.as_str()
+                .and_then(|key| PublicKey::from_str(key).ok())
+                .ok_or_else(|| object!{"message" => "Invalid public key"}))
+            .collect()
+    }
+
+    fn parse_commitments(value: &JsonValue) -> Result<Vec<Commitment>, JsonValue> {
+        value.members()
+            .map(|value| Self::parse_bytes(value, "commitment")
+                .and_then(|bytes| Commitment::from_bytes(bytes)
+                    .ok_or_else(|| object!{"message" => "Invalid commitment"})))
+            .collect()
+    }
+
+    fn parse_bytes(value: &JsonValue, kind: &str) -> Result<[u8; 32], JsonValue> {
+        let raw = value.as_str()
+            .and_then(|s| hex::decode(s).ok())
+            .filter(|raw| raw.len() == 32)
+            .ok_or_else(|| object!{"message" => format!("Invalid {}", kind)})?;
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&raw);
+        Ok(bytes)
+    }
+
+    fn store_hd_wallet(&self, mnemonic: &Mnemonic, passphrase: Option<&JsonValue>, mnemonic_password: Option<&JsonValue>) -> Result<JsonValue, JsonValue> {
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = passphrase.map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+        let mnemonic_password = mnemonic_password.map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Mnemonic password must be a string"})
+            ).transpose()?;
+
+        let mut hd_wallet = HdWallet::from_mnemonic(mnemonic, mnemonic_password, HdWallet::DEFAULT_BASE_PATH, passphrase.as_bytes())
+            .map_err(|e| object!{"message" => e.to_string()})?;
+
+        // Importing a wallet again would reset its derived accounts.
+        let mut txn = self.wallet_store.create_write_transaction();
+        if self.wallet_store.get_hd_wallet(&hd_wallet.id, Some(&txn)).is_some() {
+            return Err(object!{"message" => "Wallet already exists"});
+        }
+
+        let account = hd_wallet.derive_next_account(passphrase.as_bytes())
+            .map_err(|e| object!{"message" => e.to_string()})?;
+        let address = account.address.clone();
+        let locked_account = Locked::with_defaults(account, passphrase.as_bytes())
+            .map_err(|e| object!{"message" => format!("Error while importing: {:?}", e)})?;
+
+        self.wallet_store.put(&address, &locked_account, &mut txn);
+        self.wallet_store.put_hd_wallet(&hd_wallet, &mut txn);
+        txn.commit();
+
+        Ok(object!{
+            "id" => hd_wallet.id.to_user_friendly_address(),
+            "address" => address.to_user_friendly_address(),
+        })
+    }
+
     fn sign_message(&self, message: &[u8], wallet: &WalletAccount) -> JsonValue {
         let (public_key, signature) = wallet.sign_message(&message);
         let public_key = Serialize::serialize_to_vec(&public_key);
@@ -279,6 +603,16 @@ impl Module for WalletHandler {
         "listAccounts" => list_accounts,
         "lockAccount" => lock_account,
         "createAccount" => create_account,
+        "createHdWallet" => create_hd_wallet,
+        "importHdWallet" => import_hd_wallet,
+        "listHdWallets" => list_hd_wallets,
+        "deriveNextAddress" => derive_next_address,
+        "listDerivedAccounts" => list_derived_accounts,
+        "createMultisigAccount" => create_multisig_account,
+        "listMultisigAccounts" => list_multisig_accounts,
+        "createMultisigCommitment" => create_multisig_commitment,
+        "partiallySignMultisigTransaction" => partially_sign_multisig_transaction,
+        "signMultisigTransaction" => sign_multisig_transaction,
         "unlockAccount" => unlock_account,
 //        "sendTransaction" => send_transaction,
         "sign" => sign,
@@ -11,6 +11,7 @@ extern crate nimiq_bls as bls;
 extern crate nimiq_consensus as consensus;
 extern crate nimiq_hash as hash;
 extern crate nimiq_keys as keys;
+extern crate nimiq_mnemonic as mnemonic;
 extern crate nimiq_network_primitives as network_primitives;
 extern crate nimiq_primitives as primitives;
 extern crate nimiq_transaction as transaction;
@@ -39,6 +40,14 @@ fn rpc_not_implemented<T>() -> Result<T, JsonValue> {
     Err(object!{"message" => "Not implemented"})
 }
 
+pub(crate) use utils::json_value::serde_to_json;
+
+/// Parses a value from a `JsonValue` using its serde representation.
+pub(crate) fn serde_from_json<T: serde::de::DeserializeOwned>(value: &JsonValue) -> Result<T, JsonValue> {
+    utils::json_value::serde_from_json(value)
+        .map_err(|e| object!{"message" => format!("Invalid parameters: {}", e)})
+}
+
 
 #[derive(Debug, Clone)]
 pub struct JsonRpcConfig {
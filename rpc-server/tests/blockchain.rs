@@ -0,0 +1,111 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use json::{JsonValue, Null};
+
+use nimiq_account::{Account, VestingContract};
+use nimiq_blockchain::Blockchain;
+use nimiq_blockchain_albatross::Blockchain as AlbatrossBlockchain;
+use nimiq_blockchain_base::AbstractBlockchain;
+use nimiq_database::WriteTransaction;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_keys::Address;
+use nimiq_network_primitives::time::NetworkTime;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_rpc_server::handler::Method;
+use nimiq_rpc_server::handlers::{BlockchainAlbatrossHandler, BlockchainNimiqHandler, Module};
+
+const NOT_STARTED: [u8; Address::SIZE] = [1u8; Address::SIZE];
+const VESTING: [u8; Address::SIZE] = [2u8; Address::SIZE];
+const VESTED: [u8; Address::SIZE] = [3u8; Address::SIZE];
+
+fn call(methods: &[(&'static str, Method)], name: &str, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+    methods.iter()
+        .find(|(method_name, _)| *method_name == name)
+        .unwrap_or_else(|| panic!("Method {} is not registered", name))
+        .1.call(params)
+}
+
+fn get_account(methods: &[(&'static str, Method)], address: [u8; Address::SIZE]) -> JsonValue {
+    call(methods, "getAccount", &[Address::from(address).to_user_friendly_address().into()]).unwrap()
+}
+
+// Vesting contracts of 300 Luna whose withdrawable amount differs between the head and the next
+// block. Relative to the next block, the first one starts vesting one block later, the second one
+// has vested a third and the last one everything.
+fn vesting_contracts(head_height: u32) -> Vec<(Address, Account)> {
+    let next_height = head_height + 1;
+    let total = Coin::try_from(300).unwrap();
+    let contract = |start: u32, step_amount: u64| Account::Vesting(VestingContract::new(
+        total, Address::from([9u8; Address::SIZE]), start, 1, Coin::try_from(step_amount).unwrap(), total));
+
+    vec![
+        (Address::from(NOT_STARTED), contract(next_height + 1, 100)),
+        (Address::from(VESTING), contract(head_height, 100)),
+        (Address::from(VESTED), contract(head_height, 300)),
+    ]
+}
+
+#[test]
+fn it_returns_legacy_accounts_with_withdrawable_amount() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+    let accounts = vesting_contracts(blockchain.head_height());
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().init(&mut txn, accounts);
+    txn.commit();
+    let methods = BlockchainNimiqHandler::new(Arc::clone(&blockchain)).methods();
+
+    let account = get_account(&methods, NOT_STARTED);
+    assert_eq!(account["id"].as_str(), Some(Address::from(NOT_STARTED).to_hex().as_str()));
+    assert_eq!(account["type"].as_u8(), Some(1));
+    assert_eq!(account["balance"].as_u64(), Some(300));
+    assert_eq!(account["vestingStart"].as_u32(), Some(blockchain.head_height() + 2));
+    assert_eq!(account["vestingStepAmount"].as_u64(), Some(100));
+    assert_eq!(account["vestingTotalAmount"].as_u64(), Some(300));
+    assert_eq!(account["withdrawable"].as_u64(), Some(0));
+
+    assert_eq!(get_account(&methods, VESTING)["withdrawable"].as_u64(), Some(100));
+    assert_eq!(get_account(&methods, VESTED)["withdrawable"].as_u64(), Some(300));
+
+    let basic = get_account(&methods, [4u8; Address::SIZE]);
+    assert_eq!(basic["type"].as_u8(), Some(0));
+    assert_eq!(basic["balance"].as_u64(), Some(0));
+    assert_eq!(basic["withdrawable"], Null);
+}
+
+#[test]
+fn it_returns_accounts_with_withdrawable_amount() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(AlbatrossBlockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let accounts = vesting_contracts(blockchain.head_height());
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().init(&mut txn, accounts);
+    txn.commit();
+    let methods = BlockchainAlbatrossHandler::new(Arc::clone(&blockchain)).methods();
+
+    let account = get_account(&methods, NOT_STARTED);
+    assert_eq!(account["address"].as_str(), Some(Address::from(NOT_STARTED).to_user_friendly_address().as_str()));
+    assert_eq!(account["type"].as_str(), Some("vesting"));
+    assert_eq!(account["balance"].as_u64(), Some(300));
+    assert_eq!(account["withdrawable"].as_u64(), Some(0));
+    assert_eq!(account["stake"], Null);
+
+    assert_eq!(get_account(&methods, VESTING)["withdrawable"].as_u64(), Some(100));
+    assert_eq!(get_account(&methods, VESTED)["withdrawable"].as_u64(), Some(300));
+
+    let basic = get_account(&methods, [4u8; Address::SIZE]);
+    assert_eq!(basic["type"].as_str(), Some("basic"));
+    assert_eq!(basic["withdrawable"], Null);
+}
+
+#[test]
+fn it_rejects_invalid_addresses() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(AlbatrossBlockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let methods = BlockchainAlbatrossHandler::new(blockchain).methods();
+
+    assert!(call(&methods, "getAccount", &["NQ00 INVALID".into()]).is_err());
+    assert!(call(&methods, "getAccount", &[]).is_err());
+}
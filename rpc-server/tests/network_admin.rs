@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use json::JsonValue;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+
+use nimiq_consensus::{AlbatrossConsensusProtocol, Consensus};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_mempool::MempoolConfig;
+use nimiq_network::network_config::NetworkConfig;
+use nimiq_network::websocket::LoopbackHub;
+use nimiq_network_primitives::address::PeerId;
+use nimiq_network_primitives::networks::NetworkId;
+use nimiq_rpc_server::handler::Method;
+use nimiq_rpc_server::handlers::{Module, NetworkAdminHandler};
+
+type AlbatrossConsensus = Consensus<AlbatrossConsensusProtocol>;
+
+/// Two nodes on a loopback hub, node 0 is managed through the network admin RPC methods.
+struct Nodes {
+    runtime: Runtime,
+    nodes: Vec<Arc<AlbatrossConsensus>>,
+    methods: Arc<Vec<(&'static str, Method)>>,
+}
+
+impl Nodes {
+    fn new() -> Self {
+        let hub = LoopbackHub::new();
+        let nodes: Vec<Arc<AlbatrossConsensus>> = (0..2).map(|i| {
+            let mut network_config = NetworkConfig::new_loopback_network_config(hub.clone(), format!("node{}.loopback", i), 8443, true);
+            network_config.init_volatile();
+            let env = VolatileEnvironment::new(20).unwrap();
+            AlbatrossConsensus::new(env, NetworkId::UnitAlbatross, network_config, MempoolConfig::default()).unwrap()
+        }).collect();
+
+        let runtime = Runtime::new().unwrap();
+        for node in &nodes {
+            let network = Arc::clone(&node.network);
+            runtime.block_on(future::lazy(move || {
+                network.initialize().unwrap();
+                Ok::<(), ()>(())
+            })).unwrap();
+        }
+
+        let methods = Arc::new(NetworkAdminHandler::new(&nodes[0]).methods());
+        Nodes {
+            runtime,
+            nodes,
+            methods,
+        }
+    }
+
+    /// The URI of node 1, including its peer ID.
+    fn uri(&self) -> String {
+        format!("ws://node1.loopback:8443/{}", self.nodes[1].network.network_config.public_key().to_hex())
+    }
+
+    fn peer_id(&self) -> PeerId {
+        PeerId::from(self.nodes[1].network.network_config.public_key())
+    }
+
+    /// Calls a method on the runtime, since connecting spawns tasks.
+    fn call(&mut self, name: &'static str, params: Vec<JsonValue>) -> Result<JsonValue, JsonValue> {
+        let methods = Arc::clone(&self.methods);
+        self.runtime.block_on(future::lazy(move || {
+            let result = methods.iter()
+                .find(|(method_name, _)| *method_name == name)
+                .unwrap_or_else(|| panic!("Method {} is not registered", name))
+                .1.call(&params);
+            Ok::<_, ()>(result)
+        })).unwrap()
+    }
+
+    /// Runs the runtime until `condition` holds. Returns `false` if it didn't within `timeout`.
+    fn run_until<F: Fn(&[Arc<AlbatrossConsensus>]) -> bool>(&mut self, timeout: Duration, condition: F) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !condition(&self.nodes) {
+            if Instant::now() > deadline {
+                return false;
+            }
+            self.runtime.block_on(Delay::new(Instant::now() + Duration::from_millis(10))).unwrap();
+        }
+        true
+    }
+}
+
+#[test]
+fn it_connects_to_and_bans_unknown_peers() {
+    let mut nodes = Nodes::new();
+    let uri = nodes.uri();
+    let peer_id = nodes.peer_id();
+
+    assert_eq!(nodes.call("peerConnect", vec![uri.clone().into()]), Ok(true.into()));
+    let peer_address = nodes.nodes[0].network.addresses.state().get_by_peer_id(&peer_id).unwrap();
+    assert!(!peer_address.is_seed());
+    assert!(nodes.run_until(Duration::from_secs(10), |nodes| nodes[0].network.peer_count() == 1), "Peer did not connect");
+
+    // Banning a peer that was added manually applies and disconnects it.
+    assert_eq!(nodes.call("peerBan", vec![uri.clone().into()]), Ok(true.into()));
+    let peer_address = nodes.nodes[0].network.addresses.state().get_by_peer_id(&peer_id).unwrap();
+    assert!(nodes.nodes[0].network.addresses.is_banned(&peer_address));
+    assert!(nodes.run_until(Duration::from_secs(10), |nodes| nodes[0].network.peer_count() == 0), "Peer was not disconnected");
+
+    assert_eq!(nodes.call("peerUnban", vec![uri.clone().into()]), Ok(true.into()));
+    assert!(!nodes.nodes[0].network.addresses.is_banned(&peer_address));
+    assert_eq!(nodes.call("peerUnban", vec![uri.into()]), Ok(false.into()));
+}
+
+#[test]
+fn it_bans_manually_added_peers_before_connecting() {
+    let mut nodes = Nodes::new();
+    let uri = nodes.uri();
+    let peer_id = nodes.peer_id();
+
+    // Ban the peer right away, before the connection is established.
+    assert_eq!(nodes.call("peerConnect", vec![uri.clone().into()]), Ok(true.into()));
+    assert_eq!(nodes.call("peerBan", vec![uri.into()]), Ok(true.into()));
+
+    let peer_address = nodes.nodes[0].network.addresses.state().get_by_peer_id(&peer_id).unwrap();
+    assert!(nodes.nodes[0].network.addresses.is_banned(&peer_address));
+}
+
+#[test]
+fn it_rejects_invalid_peers() {
+    let mut nodes = Nodes::new();
+    let unknown = format!("ws://node2.loopback:8443/{}", "00".repeat(16));
+
+    assert!(nodes.call("peerConnect", vec!["not a uri".into()]).is_err());
+    assert!(nodes.call("peerConnect", vec![]).is_err());
+    // Unknown peers must be given with their public key.
+    assert!(nodes.call("peerConnect", vec!["ws://node1.loopback:8443".into()]).is_err());
+
+    assert!(nodes.call("peerBan", vec!["ws://node1.loopback:8443".into()]).is_err());
+    assert!(nodes.call("peerBan", vec![unknown.clone().into()]).is_err());
+    assert!(nodes.call("peerUnban", vec![unknown.clone().into()]).is_err());
+    assert!(nodes.call("peerDisconnect", vec![unknown.into()]).is_err());
+}
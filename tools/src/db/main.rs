@@ -0,0 +1,512 @@
+extern crate nimiq_block_albatross as block;
+extern crate nimiq_blockchain_albatross as blockchain;
+extern crate nimiq_database as database;
+extern crate nimiq_primitives as primitives;
+extern crate nimiq_transaction as transaction;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::exit;
+use std::str::FromStr;
+
+use beserial::{Deserialize, DeserializeWithLength, Serialize, SerializeWithLength};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand, crate_version, crate_authors};
+use failure::{Error, Fail};
+
+use block::Block;
+use blockchain::Blockchain;
+use blockchain::blockchain::{PushError, PushResult};
+use blockchain::snapshot::ChainSnapshot;
+use blockchain::verify::verify;
+use database::Environment;
+use database::lmdb::{LmdbEnvironment, open};
+use database::migration::migrate;
+use primitives::networks::NetworkId;
+use primitives::policy;
+use transaction::Transaction;
+
+
+/// Marks files written by this tool ("NQDB").
+const MAGIC: u32 = 0x4e51_4442;
+const FORMAT_VERSION: u8 = 1;
+
+const KIND_CHAIN: u8 = 1;
+const KIND_SNAPSHOT: u8 = 2;
+
+/// A chain export is a header followed by one record per block, starting after the genesis block.
+/// Macro blocks are followed by the transactions of their epoch.
+const RECORD_END: u8 = 0;
+const RECORD_MICRO_BLOCK: u8 = 1;
+const RECORD_MACRO_BLOCK: u8 = 2;
+
+/// Same defaults as the client. The size can be changed with `--db-size`, like `database.size`
+/// in the client's config file.
+const DB_SIZE: usize = 50 * 1024 * 1024;
+const DB_MAX_DBS: u32 = 20;
+
+
+fn run_app() -> Result<(), Error> {
+    let db_arg = Arg::with_name("db")
+        .short("d")
+        .long("db")
+        .value_name("PATH")
+        .help("Path of the consensus database, e.g. ~/.nimiq/unit-albatross-full-consensus")
+        .takes_value(true)
+        .required(true);
+    let db_size_arg = Arg::with_name("db_size")
+        .long("db-size")
+        .value_name("BYTES")
+        .help("Maximum size of the database, should match database.size of the node. Default is 50 MB")
+        .takes_value(true);
+    let network_arg = Arg::with_name("network")
+        .short("N")
+        .long("network")
+        .value_name("NETWORK")
+        .help("Network ID of the database")
+        .takes_value(true)
+        .required(true);
+    let output_arg = Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .value_name("FILE")
+        .help("File to write to")
+        .takes_value(true)
+        .required(true);
+    let input_arg = Arg::with_name("input")
+        .short("i")
+        .long("input")
+        .value_name("FILE")
+        .help("File to read from")
+        .takes_value(true)
+        .required(true);
+
+    let matches = App::new("Nimiq database tool")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Exports, imports, snapshots and verifies the Albatross chain database of a stopped node")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("export")
+            .about("Writes all main chain blocks and the transactions of each epoch to a file")
+            .arg(db_arg.clone())
+            .arg(db_size_arg.clone())
+            .arg(network_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("import")
+            .about("Replays an export into a database")
+            .arg(db_arg.clone())
+            .arg(db_size_arg.clone())
+            .arg(input_arg.clone())
+            .arg(Arg::with_name("macro_only")
+                .long("macro-only")
+                .help("Only push macro blocks together with their epoch transactions")
+                .takes_value(false)))
+        .subcommand(SubCommand::with_name("snapshot")
+            .about("Writes the state of the chain at its latest macro block to a file")
+            .arg(db_arg.clone())
+            .arg(db_size_arg.clone())
+            .arg(network_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("restore")
+            .about("Initializes an empty database from a trusted snapshot, e.g. one written by this tool")
+            .arg(db_arg.clone())
+            .arg(db_size_arg.clone())
+            .arg(input_arg.clone()))
+        .subcommand(SubCommand::with_name("verify")
+            .about("Checks the database for inconsistencies")
+            .arg(db_arg.clone())
+            .arg(db_size_arg.clone())
+            .arg(network_arg.clone())
+            .arg(Arg::with_name("repair")
+                .long("repair")
+                .help("Remove or correct dangling fork entries")
+                .takes_value(false)))
+        .get_matches();
+
+    match matches.subcommand() {
+        ("export", Some(matches)) => export(matches),
+        ("import", Some(matches)) => import(matches),
+        ("snapshot", Some(matches)) => snapshot(matches),
+        ("restore", Some(matches)) => restore(matches),
+        ("verify", Some(matches)) => verify_database(matches),
+        _ => unreachable!(),
+    }
+}
+
+fn export(matches: &ArgMatches) -> Result<(), Error> {
+    let network_id = NetworkId::from_str(matches.value_of("network").unwrap())?;
+    let blockchain = Blockchain::new(open_existing_database(matches)?, network_id)?;
+    let mut writer = create_file(matches.value_of("output").unwrap(), KIND_CHAIN, network_id)?;
+
+    let head_block_number = export_chain(&blockchain, &mut writer)?;
+    writer.flush()?;
+
+    println!("Exported blocks #1 to #{}", head_block_number);
+    Ok(())
+}
+
+/// Writes the records of all main chain blocks after the genesis block, followed by the end record.
+/// Returns the number of the last exported block.
+fn export_chain<W: Write>(blockchain: &Blockchain, writer: &mut W) -> Result<u32, Error> {
+    let head_block_number = blockchain.block_number();
+    for block_number in 1..=head_block_number {
+        let block = blockchain.get_block_at(block_number, true)
+            .ok_or(AppError::MissingBlock(block_number))?;
+        match block {
+            Block::Micro(ref micro_block) if micro_block.extrinsics.is_none() => {
+                return Err(AppError::PrunedBlock(block_number).into());
+            },
+            Block::Micro(_) => {
+                Serialize::serialize(&RECORD_MICRO_BLOCK, writer)?;
+                Serialize::serialize(&block, writer)?;
+            },
+            Block::Macro(_) => {
+                let transactions: Vec<Transaction> = blockchain.get_epoch_transactions(policy::epoch_at(block_number), None)
+                    .ok_or(AppError::MissingBlock(block_number))?
+                    .collect();
+                Serialize::serialize(&RECORD_MACRO_BLOCK, writer)?;
+                Serialize::serialize(&block, writer)?;
+                SerializeWithLength::serialize::<u32, _>(&transactions, writer)?;
+            },
+        }
+    }
+    Serialize::serialize(&RECORD_END, writer)?;
+    Ok(head_block_number)
+}
+
+fn import(matches: &ArgMatches) -> Result<(), Error> {
+    let (mut reader, network_id) = open_file(matches.value_of("input").unwrap(), KIND_CHAIN)?;
+    let blockchain = Blockchain::new(open_database(matches)?, network_id)?;
+
+    let pushed = import_chain(&blockchain, &mut reader, matches.is_present("macro_only"))?;
+
+    println!("Imported {} blocks, head is now #{}", pushed, blockchain.block_number());
+    Ok(())
+}
+
+/// Pushes the blocks of an export up to its end record. With `macro_only`, micro blocks are
+/// skipped and macro blocks are pushed with the transactions of their epoch.
+/// Returns the number of blocks that extended the chain.
+fn import_chain<R: Read>(blockchain: &Blockchain, reader: &mut R, macro_only: bool) -> Result<u32, Error> {
+    let mut pushed = 0u32;
+    loop {
+        let record: u8 = Deserialize::deserialize(reader)?;
+        let result = match record {
+            RECORD_END => break,
+            RECORD_MICRO_BLOCK => {
+                let block: Block = Deserialize::deserialize(reader)?;
+                if macro_only {
+                    continue;
+                }
+                push_result(block.block_number(), blockchain.push(block))?
+            },
+            RECORD_MACRO_BLOCK => {
+                let block: Block = Deserialize::deserialize(reader)?;
+                let transactions: Vec<Transaction> = DeserializeWithLength::deserialize::<u32, _>(reader)?;
+                let block_number = block.block_number();
+                if macro_only {
+                    push_result(block_number, blockchain.push_isolated_macro_block(block, &transactions))?
+                } else {
+                    push_result(block_number, blockchain.push(block))?
+                }
+            },
+            _ => return Err(AppError::InvalidRecord(record).into()),
+        };
+        if result == PushResult::Extended {
+            pushed += 1;
+        }
+    }
+    Ok(pushed)
+}
+
+fn snapshot(matches: &ArgMatches) -> Result<(), Error> {
+    let network_id = NetworkId::from_str(matches.value_of("network").unwrap())?;
+    let blockchain = Blockchain::new(open_existing_database(matches)?, network_id)?;
+
+    let snapshot = blockchain.snapshot()?;
+    let mut writer = create_file(matches.value_of("output").unwrap(), KIND_SNAPSHOT, network_id)?;
+    Serialize::serialize(&snapshot, &mut writer)?;
+    writer.flush()?;
+
+    println!("Wrote snapshot at macro block #{} with {} accounts", snapshot.blocks.last().unwrap().block_number(), snapshot.accounts.len());
+    Ok(())
+}
+
+fn restore(matches: &ArgMatches) -> Result<(), Error> {
+    let (mut reader, network_id) = open_file(matches.value_of("input").unwrap(), KIND_SNAPSHOT)?;
+    let snapshot: ChainSnapshot = Deserialize::deserialize(&mut reader)?;
+    if snapshot.network_id != network_id {
+        return Err(AppError::WrongNetwork(snapshot.network_id).into());
+    }
+
+    let blockchain = Blockchain::from_snapshot(open_database(matches)?, snapshot)?;
+
+    println!("Restored chain at macro block #{}", blockchain.block_number());
+    Ok(())
+}
+
+fn verify_database(matches: &ArgMatches) -> Result<(), Error> {
+    let network_id = NetworkId::from_str(matches.value_of("network").unwrap())?;
+    let env = open_existing_database(matches)?;
+    let repair = matches.is_present("repair");
+
+    let inconsistencies = verify(&env, network_id, repair);
+    let mut remaining = 0;
+    for inconsistency in &inconsistencies {
+        if repair && inconsistency.is_repairable() {
+            println!("{} (repaired)", inconsistency);
+        } else {
+            println!("{}", inconsistency);
+            remaining += 1;
+        }
+    }
+
+    if remaining > 0 {
+        return Err(AppError::Inconsistent(remaining).into());
+    }
+    println!("Database is consistent");
+    Ok(())
+}
+
+fn push_result(block_number: u32, result: Result<PushResult, PushError>) -> Result<PushResult, Error> {
+    result.map_err(|error| AppError::PushFailed { block_number, error }.into())
+}
+
+/// Opens the database given by `--db` and brings it up to the schema of this binary.
+fn open_database(matches: &ArgMatches) -> Result<Environment, Error> {
+    let path = matches.value_of("db").unwrap();
+    let size = match matches.value_of("db_size") {
+        Some(size) => usize::from_str(size).map_err(|_| AppError::InvalidDatabaseSize(size.to_string()))?,
+        None => DB_SIZE,
+    };
+    let env = LmdbEnvironment::new(path, size, DB_MAX_DBS, open::NOMETASYNC)?;
+    migrate(&env, &blockchain::schema::schemas())?;
+    Ok(env)
+}
+
+/// Like `open_database`, but doesn't create a new database.
+fn open_existing_database(matches: &ArgMatches) -> Result<Environment, Error> {
+    let path = matches.value_of("db").unwrap();
+    if !Path::new(path).is_dir() {
+        return Err(AppError::DatabaseNotFound(path.to_string()).into());
+    }
+    open_database(matches)
+}
+
+fn create_file(path: &str, kind: u8, network_id: NetworkId) -> Result<BufWriter<File>, Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_header(&mut writer, kind, network_id)?;
+    Ok(writer)
+}
+
+fn open_file(path: &str, kind: u8) -> Result<(BufReader<File>, NetworkId), Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let network_id = read_header(&mut reader, kind, path)?;
+    Ok((reader, network_id))
+}
+
+fn write_header<W: Write>(writer: &mut W, kind: u8, network_id: NetworkId) -> Result<(), Error> {
+    Serialize::serialize(&MAGIC, writer)?;
+    Serialize::serialize(&FORMAT_VERSION, writer)?;
+    Serialize::serialize(&kind, writer)?;
+    Serialize::serialize(&network_id, writer)?;
+    Ok(())
+}
+
+/// Reads the header written by `write_header` and returns the network ID. `name` identifies the
+/// file in errors.
+fn read_header<R: Read>(reader: &mut R, kind: u8, name: &str) -> Result<NetworkId, Error> {
+    let magic: u32 = Deserialize::deserialize(reader)?;
+    let version: u8 = Deserialize::deserialize(reader)?;
+    let file_kind: u8 = Deserialize::deserialize(reader)?;
+    if magic != MAGIC || file_kind != kind {
+        return Err(AppError::InvalidFile(name.to_string()).into());
+    }
+    if version != FORMAT_VERSION {
+        return Err(AppError::UnsupportedVersion(version).into());
+    }
+    Ok(Deserialize::deserialize(reader)?)
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "No database at {}", _0)]
+    DatabaseNotFound(String),
+    #[fail(display = "Invalid database size {}", _0)]
+    InvalidDatabaseSize(String),
+    #[fail(display = "Block #{} is missing from the database", _0)]
+    MissingBlock(u32),
+    #[fail(display = "The body of block #{} has been pruned", _0)]
+    PrunedBlock(u32),
+    #[fail(display = "{} is not a file of the expected kind", _0)]
+    InvalidFile(String),
+    #[fail(display = "File format version {} is not supported", _0)]
+    UnsupportedVersion(u8),
+    #[fail(display = "Invalid record type {}", _0)]
+    InvalidRecord(u8),
+    #[fail(display = "Snapshot is for network {}", _0)]
+    WrongNetwork(NetworkId),
+    #[fail(display = "Found {} inconsistencies", _0)]
+    Inconsistent(usize),
+    #[fail(display = "Failed to push block #{}: {}", block_number, error)]
+    PushFailed { block_number: u32, #[cause] error: PushError },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use block::{MacroBlock, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage};
+    use database::volatile::VolatileEnvironment;
+    use nimiq_block_production_albatross::BlockProducer;
+    use nimiq_bls::{KeyPair, SecretKey};
+    use nimiq_hash::{Blake2bHash, Hash};
+
+    use super::*;
+
+    /// Secret key of the only validator in `network-primitives/src/genesis/unit-albatross.toml`.
+    const SECRET_KEY: &str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+    fn key_pair() -> KeyPair {
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap())
+    }
+
+    fn new_blockchain() -> Arc<Blockchain> {
+        Arc::new(Blockchain::new(VolatileEnvironment::new(10).unwrap(), NetworkId::UnitAlbatross).unwrap())
+    }
+
+    fn sign_macro_block(proposal: PbftProposal) -> MacroBlock {
+        let key_pair = key_pair();
+        let block_hash = proposal.header.hash::<Blake2bHash>();
+        let prepare = SignedPbftPrepareMessage::from_message(
+            PbftPrepareMessage { block_hash: block_hash.clone() },
+            &key_pair.secret,
+            0);
+        let commit = SignedPbftCommitMessage::from_message(
+            PbftCommitMessage { block_hash },
+            &key_pair.secret,
+            0);
+
+        let mut pbft_proof = PbftProofBuilder::new();
+        pbft_proof.add_prepare_signature(&key_pair.public, policy::SLOTS, &prepare);
+        pbft_proof.add_commit_signature(&key_pair.public, policy::SLOTS, &commit);
+
+        MacroBlock {
+            header: proposal.header,
+            justification: Some(pbft_proof.build()),
+            extrinsics: None,
+        }
+    }
+
+    /// Produces blocks with the key of the unit genesis validator until `block_number`.
+    fn produce_blocks(blockchain: &Arc<Blockchain>, block_number: u32) {
+        let producer = BlockProducer::new_without_mempool(Arc::clone(blockchain), key_pair());
+        while blockchain.block_number() < block_number {
+            let height = blockchain.block_number() + 1;
+            let timestamp = 1565713920000 + u64::from(height) * 2000;
+            let result = if policy::is_macro_block_at(height) {
+                let (proposal, _extrinsics) = producer.next_macro_block_proposal(timestamp, 0, None);
+                blockchain.push_block(Block::Macro(sign_macro_block(proposal)), true)
+            } else {
+                blockchain.push(Block::Micro(producer.next_micro_block(vec![], timestamp, 0, vec![0x42], None)))
+            };
+            assert_eq!(result, Ok(PushResult::Extended));
+        }
+    }
+
+    fn export_to_vec(blockchain: &Blockchain) -> Vec<u8> {
+        let mut export = Vec::new();
+        write_header(&mut export, KIND_CHAIN, NetworkId::UnitAlbatross).unwrap();
+        export_chain(blockchain, &mut export).unwrap();
+        export
+    }
+
+    #[test]
+    fn it_exports_a_record_per_block() {
+        let blockchain = new_blockchain();
+        produce_blocks(&blockchain, policy::EPOCH_LENGTH + 2);
+        let export = export_to_vec(&blockchain);
+
+        let mut reader = &export[..];
+        assert_eq!(read_header(&mut reader, KIND_CHAIN, "export").unwrap(), NetworkId::UnitAlbatross);
+        for block_number in 1..=blockchain.block_number() {
+            let record: u8 = Deserialize::deserialize(&mut reader).unwrap();
+            let block: Block = Deserialize::deserialize(&mut reader).unwrap();
+            assert_eq!(block, blockchain.get_block_at(block_number, true).unwrap());
+
+            if policy::is_macro_block_at(block_number) {
+                assert_eq!(record, RECORD_MACRO_BLOCK);
+                let transactions: Vec<Transaction> = DeserializeWithLength::deserialize::<u32, _>(&mut reader).unwrap();
+                assert!(transactions.is_empty());
+            } else {
+                assert_eq!(record, RECORD_MICRO_BLOCK);
+            }
+        }
+        let record: u8 = Deserialize::deserialize(&mut reader).unwrap();
+        assert_eq!(record, RECORD_END);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn it_imports_an_export() {
+        let blockchain = new_blockchain();
+        produce_blocks(&blockchain, policy::EPOCH_LENGTH + 2);
+        let export = export_to_vec(&blockchain);
+
+        let imported = new_blockchain();
+        let mut reader = &export[..];
+        assert_eq!(read_header(&mut reader, KIND_CHAIN, "export").unwrap(), NetworkId::UnitAlbatross);
+        assert_eq!(import_chain(&imported, &mut reader, false).unwrap(), blockchain.block_number());
+        assert_eq!(imported.head_hash(), blockchain.head_hash());
+        assert_eq!(imported.macro_head_hash(), blockchain.macro_head_hash());
+
+        // Importing again doesn't extend the chain.
+        let mut reader = &export[..];
+        read_header(&mut reader, KIND_CHAIN, "export").unwrap();
+        assert_eq!(import_chain(&imported, &mut reader, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn it_imports_macro_blocks_only() {
+        let blockchain = new_blockchain();
+        produce_blocks(&blockchain, 2 * policy::EPOCH_LENGTH + 2);
+        let export = export_to_vec(&blockchain);
+
+        let imported = new_blockchain();
+        let mut reader = &export[..];
+        read_header(&mut reader, KIND_CHAIN, "export").unwrap();
+        assert_eq!(import_chain(&imported, &mut reader, true).unwrap(), 2);
+
+        // The chain ends at the last macro block, with the same state.
+        assert_eq!(imported.head_hash(), blockchain.macro_head_hash());
+        assert_eq!(imported.block_number(), 2 * policy::EPOCH_LENGTH);
+        assert_eq!(imported.state().accounts().hash(None), blockchain.macro_head().header.state_root);
+    }
+
+    #[test]
+    fn it_rejects_other_files() {
+        let mut snapshot = Vec::new();
+        write_header(&mut snapshot, KIND_SNAPSHOT, NetworkId::UnitAlbatross).unwrap();
+        assert!(read_header(&mut &snapshot[..], KIND_CHAIN, "snapshot").is_err());
+
+        let mut export = Vec::new();
+        write_header(&mut export, KIND_CHAIN, NetworkId::UnitAlbatross).unwrap();
+        export[4] = FORMAT_VERSION + 1;
+        assert!(read_header(&mut &export[..], KIND_CHAIN, "export").is_err());
+
+        // Unknown records abort the import.
+        let mut reader: &[u8] = &[RECORD_MACRO_BLOCK + 1];
+        assert!(import_chain(&new_blockchain(), &mut reader, false).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Converts between serde representations and `json::JsonValue`, which the RPC servers use.
+
+use json::JsonValue;
+
+/// Converts a value into a `JsonValue` using its serde representation (see `docs/json.md`).
+pub fn serde_to_json<T: serde::Serialize>(value: &T) -> JsonValue {
+    let s = serde_json::to_string(value).expect("Failed to serialize to JSON");
+    json::parse(&s).expect("Invalid JSON from serde_json")
+}
+
+/// Parses a value from a `JsonValue` using its serde representation.
+pub fn serde_from_json<T: serde::de::DeserializeOwned>(value: &JsonValue) -> Result<T, serde_json::Error> {
+    serde_json::from_str(&value.dump())
+}
@@ -45,3 +45,7 @@ pub mod math;
 pub mod key_rng;
 #[cfg(feature = "hash-rng")]
 pub mod hash_rng;
+#[cfg(feature = "serde-hex")]
+pub mod serde_hex;
+#[cfg(feature = "json-value")]
+pub mod json_value;
@@ -131,6 +131,17 @@ pub struct Locked<T: Clear + Deserialize + Serialize> {
     phantom: PhantomData<T>,
 }
 
+impl<T: Clear + Deserialize + Serialize> Clone for Locked<T> {
+    fn clone(&self) -> Self {
+        Locked {
+            lock: self.lock.clone(),
+            salt: self.salt.clone(),
+            iterations: self.iterations,
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<T: Clear + Deserialize + Serialize> Locked<T> {
     /// Calling code should make sure to clear the password from memory after use.
     pub fn new(mut secret: T, password: &[u8], iterations: u32, salt_length: usize) -> Result<Self, Argon2Error> {
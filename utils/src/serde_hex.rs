@@ -0,0 +1,13 @@
+//! Serializes byte vectors as hex strings, for use with `#[serde(with = "utils::serde_hex")]`.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use serde::de::Error;
+
+pub fn serialize<T: AsRef<[u8]>, S: Serializer>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s: String = Deserialize::deserialize(deserializer)?;
+    hex::decode(&s).map_err(D::Error::custom)
+}
@@ -29,4 +29,6 @@ pub mod error;
 pub mod slash;
 pub mod signature_aggregation;
 pub mod pool;
+#[cfg(feature = "metrics")]
+pub mod validator_metrics;
 
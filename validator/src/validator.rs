@@ -35,6 +35,7 @@ use hash::{Blake2bHash, Hash};
 use macros::upgrade_weak;
 use network_primitives::networks::NetworkInfo;
 use network_primitives::validator_info::{SignedValidatorInfo, ValidatorInfo};
+use primitives::slot::SlotCollection;
 use utils::mutable_once::MutableOnce;
 use utils::observer::ListenerHandle;
 use utils::timers::Timers;
@@ -42,6 +43,8 @@ use utils::timers::Timers;
 use crate::error::Error;
 use crate::slash::ForkProofPool;
 use crate::validator_network::{ValidatorNetwork, ValidatorNetworkEvent};
+#[cfg(feature = "metrics")]
+use crate::validator_metrics::ValidatorMetrics;
 
 #[derive(Clone, Debug)]
 pub enum SlotChange  {
@@ -319,6 +322,8 @@ impl Validator {
         match event {
             ValidatorNetworkEvent::ViewChangeComplete(event) => {
                 debug!("Completed view change to {}", event.view_change);
+                #[cfg(feature = "metrics")]
+                self.metrics().note_view_change_completed();
                 let slot_change = SlotChange::ViewChange(event.view_change.clone(), event.proof.clone());
                 self.on_slot_change(slot_change);
             },
@@ -528,6 +533,18 @@ impl Validator {
 
         info!("Starting view change to {}", message);
 
+        #[cfg(feature = "metrics")] {
+            self.metrics().note_view_change_started();
+
+            // If the block that timed out was ours, we missed it.
+            let our_public_key = self.validator_key.public.compress();
+            if let Some((slot, _)) = self.blockchain.get_slot_at(block_number, state.view_number, None) {
+                if slot.public_key().compressed() == &our_public_key {
+                    self.metrics().note_block_missed(block_number, state.view_number);
+                }
+            }
+        }
+
         let pk_idx = state.pk_idx.expect("Checked above that we are an active validator");
         let view_change_message = SignedViewChange::from_message(message.clone(), &self.validator_key.secret, pk_idx);
         state.active_view_change = Some(message);
@@ -551,6 +568,8 @@ impl Validator {
         // This may happen if a new block has been produced due to the async call.
         if self.blockchain.height() + 1 != block_number {
             trace!("Validator was too slow producing the next block.");
+            #[cfg(feature = "metrics")]
+            self.metrics().note_block_missed(block_number, view_number);
             return;
         }
 
@@ -564,9 +583,13 @@ impl Validator {
         drop(lock);
 
         let signed_proposal = SignedPbftProposal::from_message(pbft_proposal, &self.validator_key.secret, pk_idx);
-        self.validator_network.start_pbft(signed_proposal)
-            .unwrap_or_else(|e| error!("Failed to start pBFT proposal: {}", e));
-
+        match self.validator_network.start_pbft(signed_proposal) {
+            Ok(()) => {
+                #[cfg(feature = "metrics")]
+                self.metrics().note_macro_block_proposed();
+            },
+            Err(e) => error!("Failed to start pBFT proposal: {}", e),
+        }
     }
 
     fn produce_micro_block(&self, block_number: u32, view_number: u32, view_change_proof: Option<ViewChangeProof>) {
@@ -576,6 +599,8 @@ impl Validator {
         // This may happen if a new block has been produced due to the async call.
         if self.blockchain.height() + 1 != block_number {
             trace!("Validator was too slow producing the next block.");
+            #[cfg(feature = "metrics")]
+            self.metrics().note_block_missed(block_number, view_number);
             return;
         }
 
@@ -600,11 +625,46 @@ impl Validator {
 
         // Automatically relays block.
         match self.blockchain.push(Block::Micro(block)) {
-            Ok(r) => trace!("Push result: {:?}", r),
+            Ok(r) => {
+                trace!("Push result: {:?}", r);
+                #[cfg(feature = "metrics")]
+                self.metrics().note_micro_block_produced();
+            },
             Err(e) => error!("Failed to push produced micro block to blockchain: {:?}", e),
         }
     }
 
+    pub fn status(&self) -> ValidatorStatus {
+        self.state.read().status
+    }
+
+    /// The number of slots we have in the current epoch, if we're an active validator
+    pub fn slots(&self) -> Option<u16> {
+        self.state.read().slots
+    }
+
+    /// The number of our slots that are slashed in the current epoch
+    pub fn num_slashed_slots(&self) -> usize {
+        let pk_idx = match self.state.read().pk_idx {
+            Some(pk_idx) => pk_idx,
+            None => return 0,
+        };
+
+        let state = self.blockchain.state();
+        let validators = match state.current_validators() {
+            Some(validators) => validators,
+            None => return 0,
+        };
+        state.current_slashed_set().iter()
+            .filter(|&slot_number| validators.get_band_number_by_slot_number(slot_number as u16) == Some(pk_idx))
+            .count()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &ValidatorMetrics {
+        self.validator_network.metrics()
+    }
+
     fn is_potential_validator(&self) -> bool {
         let validator_registry = NetworkInfo::from_network_id(self.blockchain.network_id).validator_registry_address().expect("Albatross consensus always has the address set.");
         let contract = self.blockchain.state().accounts().get(validator_registry, None);
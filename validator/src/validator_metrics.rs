@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use parking_lot::RwLock;
+
+
+/// The signature aggregations run by the validator network
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AggregationType {
+    ViewChange,
+    PbftPrepare,
+    PbftCommit,
+}
+
+impl AggregationType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AggregationType::ViewChange => "view_change",
+            AggregationType::PbftPrepare => "pbft_prepare",
+            AggregationType::PbftCommit => "pbft_commit",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ValidatorMetrics {
+    micro_block_produced_count: AtomicUsize,
+    macro_block_proposed_count: AtomicUsize,
+    block_missed_count: AtomicUsize,
+    /// The last of our slots (block number, view number) that was counted as missed. A slot can be
+    /// noticed as missed more than once, e.g. when its view change starts and again when we are too
+    /// slow producing its block.
+    last_missed_slot: RwLock<Option<(u32, u32)>>,
+
+    view_change_started_count: AtomicUsize,
+    view_change_completed_count: AtomicUsize,
+
+    pbft_prepare_completed_count: AtomicUsize,
+    pbft_commit_completed_count: AtomicUsize,
+    /// Time from the proposal until the last completed prepare/commit phase in milliseconds
+    pbft_prepare_latency: AtomicUsize,
+    pbft_commit_latency: AtomicUsize,
+    /// Votes of the last completed prepare/commit phase
+    pbft_prepare_votes: AtomicUsize,
+    pbft_commit_votes: AtomicUsize,
+
+    fork_proof_count: AtomicUsize,
+
+    /// Time until each Handel level was completed in the last aggregation, in milliseconds
+    handel_level_completion_times: RwLock<BTreeMap<(AggregationType, usize), usize>>,
+}
+
+impl ValidatorMetrics {
+    #[inline]
+    pub fn note_micro_block_produced(&self) {
+        self.micro_block_produced_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn micro_block_produced_count(&self) -> usize {
+        self.micro_block_produced_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_macro_block_proposed(&self) {
+        self.macro_block_proposed_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn macro_block_proposed_count(&self) -> usize {
+        self.macro_block_proposed_count.load(Ordering::Acquire)
+    }
+
+    /// Counts our slot at `block_number` and `view_number` as missed, unless it already was.
+    pub fn note_block_missed(&self, block_number: u32, view_number: u32) {
+        let slot = (block_number, view_number);
+        let mut last_missed_slot = self.last_missed_slot.write();
+        // Slots are missed in order, so anything up to the last missed slot has been counted.
+        if last_missed_slot.map_or(true, |last_missed_slot| last_missed_slot < slot) {
+            *last_missed_slot = Some(slot);
+            self.block_missed_count.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    #[inline]
+    pub fn block_missed_count(&self) -> usize {
+        self.block_missed_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_view_change_started(&self) {
+        self.view_change_started_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn view_change_started_count(&self) -> usize {
+        self.view_change_started_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_view_change_completed(&self) {
+        self.view_change_completed_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn view_change_completed_count(&self) -> usize {
+        self.view_change_completed_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_pbft_prepare_completed(&self, latency: Duration, votes: usize) {
+        self.pbft_prepare_completed_count.fetch_add(1, Ordering::Release);
+        self.pbft_prepare_latency.store(latency.as_millis() as usize, Ordering::Release);
+        self.pbft_prepare_votes.store(votes, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn pbft_prepare_completed_count(&self) -> usize {
+        self.pbft_prepare_completed_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn pbft_prepare_latency(&self) -> usize {
+        self.pbft_prepare_latency.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn pbft_prepare_votes(&self) -> usize {
+        self.pbft_prepare_votes.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_pbft_commit_completed(&self, latency: Duration, votes: usize) {
+        self.pbft_commit_completed_count.fetch_add(1, Ordering::Release);
+        self.pbft_commit_latency.store(latency.as_millis() as usize, Ordering::Release);
+        self.pbft_commit_votes.store(votes, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn pbft_commit_completed_count(&self) -> usize {
+        self.pbft_commit_completed_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn pbft_commit_latency(&self) -> usize {
+        self.pbft_commit_latency.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn pbft_commit_votes(&self) -> usize {
+        self.pbft_commit_votes.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_fork_proof(&self) {
+        self.fork_proof_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn fork_proof_count(&self) -> usize {
+        self.fork_proof_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_handel_level_completed(&self, aggregation: AggregationType, level: usize, elapsed: Duration) {
+        self.handel_level_completion_times.write().insert((aggregation, level), elapsed.as_millis() as usize);
+    }
+
+    /// Returns `(aggregation, level, milliseconds)` for every level completed so far.
+    pub fn handel_level_completion_times(&self) -> Vec<(AggregationType, usize, usize)> {
+        self.handel_level_completion_times.read().iter()
+            .map(|(&(aggregation, level), &time)| (aggregation, level, time))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_counts_missed_slots_once() {
+        let metrics = ValidatorMetrics::default();
+
+        metrics.note_block_missed(10, 0);
+        metrics.note_block_missed(10, 0);
+        assert_eq!(metrics.block_missed_count(), 1);
+
+        // The same block in the next view is another slot.
+        metrics.note_block_missed(10, 1);
+        assert_eq!(metrics.block_missed_count(), 2);
+
+        // Slots that were missed before are never counted again.
+        metrics.note_block_missed(10, 0);
+        metrics.note_block_missed(9, 3);
+        assert_eq!(metrics.block_missed_count(), 2);
+
+        metrics.note_block_missed(11, 0);
+        assert_eq!(metrics.block_missed_count(), 3);
+    }
+
+    #[test]
+    fn it_records_block_production() {
+        let metrics = ValidatorMetrics::default();
+
+        metrics.note_micro_block_produced();
+        metrics.note_micro_block_produced();
+        metrics.note_macro_block_proposed();
+        metrics.note_fork_proof();
+        assert_eq!(metrics.micro_block_produced_count(), 2);
+        assert_eq!(metrics.macro_block_proposed_count(), 1);
+        assert_eq!(metrics.fork_proof_count(), 1);
+        assert_eq!(metrics.block_missed_count(), 0);
+    }
+
+    #[test]
+    fn it_records_the_last_completed_pbft_phase() {
+        let metrics = ValidatorMetrics::default();
+
+        metrics.note_view_change_started();
+        metrics.note_view_change_completed();
+        assert_eq!(metrics.view_change_started_count(), 1);
+        assert_eq!(metrics.view_change_completed_count(), 1);
+
+        metrics.note_pbft_prepare_completed(Duration::from_millis(300), 400);
+        metrics.note_pbft_prepare_completed(Duration::from_millis(120), 450);
+        assert_eq!(metrics.pbft_prepare_completed_count(), 2);
+        assert_eq!(metrics.pbft_prepare_latency(), 120);
+        assert_eq!(metrics.pbft_prepare_votes(), 450);
+
+        metrics.note_pbft_commit_completed(Duration::from_millis(250), 500);
+        assert_eq!(metrics.pbft_commit_completed_count(), 1);
+        assert_eq!(metrics.pbft_commit_latency(), 250);
+        assert_eq!(metrics.pbft_commit_votes(), 500);
+    }
+
+    #[test]
+    fn it_records_handel_level_completion_times() {
+        let metrics = ValidatorMetrics::default();
+
+        metrics.note_handel_level_completed(AggregationType::PbftCommit, 1, Duration::from_millis(20));
+        metrics.note_handel_level_completed(AggregationType::PbftPrepare, 2, Duration::from_millis(50));
+        metrics.note_handel_level_completed(AggregationType::PbftPrepare, 1, Duration::from_millis(10));
+        // A new aggregation overwrites the times of the last one.
+        metrics.note_handel_level_completed(AggregationType::PbftPrepare, 1, Duration::from_millis(15));
+
+        assert_eq!(metrics.handel_level_completion_times(), vec![
+            (AggregationType::PbftPrepare, 1, 15),
+            (AggregationType::PbftPrepare, 2, 50),
+            (AggregationType::PbftCommit, 1, 20),
+        ]);
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, BTreeMap};
 use std::sync::{Arc, Weak};
 use std::fmt;
+use std::time::Instant;
 
 use failure::Fail;
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
@@ -32,6 +33,8 @@ use crate::validator_agent::{ValidatorAgent, ValidatorAgentEvent};
 use crate::signature_aggregation::view_change::ViewChangeAggregation;
 use crate::signature_aggregation::pbft::PbftAggregation;
 use crate::pool::ValidatorPool;
+#[cfg(feature = "metrics")]
+use crate::validator_metrics::{AggregationType, ValidatorMetrics};
 use primitives::slot::SlotCollection;
 
 
@@ -109,6 +112,9 @@ struct PbftState {
 
     /// The state of the signature aggregation for pBFT prepare and commit
     aggregation: Arc<RwLock<PbftAggregation>>,
+
+    /// When we received the proposal
+    started: Instant,
 }
 
 impl PbftState {
@@ -118,6 +124,7 @@ impl PbftState {
             proposal,
             block_hash,
             aggregation,
+            started: Instant::now(),
         }
     }
 
@@ -216,6 +223,9 @@ pub struct ValidatorNetwork {
 
     self_weak: MutableOnce<Weak<ValidatorNetwork>>,
     pub notifier: RwLock<Notifier<'static, ValidatorNetworkEvent>>,
+
+    #[cfg(feature = "metrics")]
+    metrics: ValidatorMetrics,
 }
 
 impl ValidatorNetwork {
@@ -234,6 +244,8 @@ impl ValidatorNetwork {
             validators: Arc::new(RwLock::new(pool)),
             self_weak: MutableOnce::new(Weak::new()),
             notifier: RwLock::new(Notifier::new()),
+            #[cfg(feature = "metrics")]
+            metrics: ValidatorMetrics::default(),
         });
 
         Self::init_listeners(&this, network);
@@ -327,7 +339,15 @@ impl ValidatorNetwork {
         }
     }
 
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &ValidatorMetrics {
+        &self.metrics
+    }
+
     fn on_fork_proof(&self, fork_proof: ForkProof) {
+        #[cfg(feature = "metrics")]
+        self.metrics.note_fork_proof();
+
         self.notifier.read().notify(ValidatorNetworkEvent::ForkProof(Box::new(fork_proof.clone())));
         self.broadcast_fork_proof(fork_proof);
     }
@@ -568,10 +588,20 @@ impl ValidatorNetwork {
         pbft.aggregation.read().prepare_aggregation.notifier.write()
             .register(weak_passthru_listener(Weak::clone(&self.self_weak), move |this, event| {
                 match event {
+                    AggregationEvent::LevelComplete { level, elapsed } => {
+                        trace!("Prepare level {} complete after {:?}", level, elapsed);
+                        #[cfg(feature = "metrics")]
+                        this.metrics.note_handel_level_completed(AggregationType::PbftPrepare, level, elapsed);
+                    }
                     AggregationEvent::Complete { best } => {
                         let event = if let Some(pbft) = this.state.write().get_pbft_state_mut(&key) {
                             trace!("Prepare complete. Signers: {}", best.signers);
 
+                            #[cfg(feature = "metrics")] {
+                                let (prepare_votes, _) = pbft.aggregation.read().votes();
+                                this.metrics.note_pbft_prepare_completed(pbft.started.elapsed(), prepare_votes);
+                            }
+
                             // Return the event
                             Some(ValidatorNetworkEvent::PbftPrepareComplete(Box::new(pbft.block_hash.clone())))
                         } else {
@@ -592,6 +622,11 @@ impl ValidatorNetwork {
         pbft.aggregation.read().commit_aggregation.notifier.write()
             .register(weak_passthru_listener(Weak::clone(&self.self_weak), move |this, event| {
                 match event {
+                    AggregationEvent::LevelComplete { level, elapsed } => {
+                        trace!("Commit level {} complete after {:?}", level, elapsed);
+                        #[cfg(feature = "metrics")]
+                        this.metrics.note_handel_level_completed(AggregationType::PbftCommit, level, elapsed);
+                    }
                     AggregationEvent::Complete { best } => {
                         let event = if let Some(pbft) = this.state.write().get_pbft_state_mut(&key) {
                             // Build commit proof
@@ -606,6 +641,11 @@ impl ValidatorNetwork {
 
                             let pbft_proof = PbftProof { prepare: prepare_proof, commit: commit_proof };
 
+                            #[cfg(feature = "metrics")] {
+                                let (_, commit_votes) = pbft.aggregation.read().votes();
+                                this.metrics.note_pbft_commit_completed(pbft.started.elapsed(), commit_votes);
+                            }
+
                             // Return the event
                             Some(ValidatorNetworkEvent::PbftComplete(Box::new(PbftCompleteEvent {
                                 hash: pbft.block_hash.clone(),
@@ -737,6 +777,11 @@ impl ValidatorNetwork {
         // Register handler for when done and start (or use Future)
         aggregation.inner.notifier.write().register(weak_passthru_listener(Weak::clone(&self.self_weak), move |this, event| {
             match event {
+                AggregationEvent::LevelComplete { level, elapsed } => {
+                    trace!("View change level {} complete after {:?}", level, elapsed);
+                    #[cfg(feature = "metrics")]
+                    this.metrics.note_handel_level_completed(AggregationType::ViewChange, level, elapsed);
+                }
                 AggregationEvent::Complete { best } => {
                     let view_change = view_change.clone();
                     tokio::spawn(future::lazy(move || {
@@ -27,6 +27,7 @@ pub enum VrfUseCase {
 
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-derive", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct VrfSeed {
     signature: CompressedSignature,
 }
@@ -0,0 +1,184 @@
+use std::io;
+
+use failure::Fail;
+
+use clear_on_drop::clear::Clear;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use database::{FromDatabaseValue, IntoDatabaseValue};
+use key_derivation::ExtendedPrivateKey;
+use keys::{Address, KeyPair};
+use mnemonic::{Mnemonic, MnemonicType, WORDLIST_EN};
+use nimiq_utils::otp::{Locked, Verify};
+
+use crate::wallet_account::WalletAccount;
+
+#[derive(Debug, Fail)]
+pub enum HdWalletError {
+    #[fail(display = "Invalid mnemonic")]
+    InvalidMnemonic,
+    #[fail(display = "Invalid derivation path")]
+    InvalidPath,
+    #[fail(display = "Invalid passphrase")]
+    InvalidPassphrase,
+    #[fail(display = "Key derivation failed")]
+    DerivationFailed,
+    #[fail(display = "Failed to lock seed")]
+    LockFailed,
+}
+
+/// The secret part of an HD wallet: the seed computed from the mnemonic (and its optional password).
+///
+/// The seed is stored inline, so that clearing the struct (e.g. when an `Unlocked` seed is dropped)
+/// zeroes the seed itself.
+#[derive(Clone)]
+pub struct HdWalletSeed {
+    seed: [u8; HdWalletSeed::SEED_SIZE],
+}
+
+impl HdWalletSeed {
+    pub const SEED_SIZE: usize = 64;
+
+    /// Takes the seed from `bytes` and zeroes them. Returns `None` if `bytes` has the wrong length.
+    fn from_bytes_cleared(bytes: &mut [u8]) -> Option<Self> {
+        if bytes.len() != Self::SEED_SIZE {
+            Clear::clear(bytes);
+            return None;
+        }
+        let mut seed = HdWalletSeed::default();
+        seed.seed.copy_from_slice(bytes);
+        Clear::clear(bytes);
+        Some(seed)
+    }
+
+    pub fn to_master_key(&self) -> ExtendedPrivateKey {
+        ExtendedPrivateKey::from_seed_bytes(&self.seed)
+    }
+}
+
+impl Default for HdWalletSeed {
+    fn default() -> Self {
+        HdWalletSeed { seed: [0u8; Self::SEED_SIZE] }
+    }
+}
+
+impl Verify for HdWalletSeed {
+    fn verify(&self) -> bool {
+        // The length is already checked when deserializing, only reject a cleared seed.
+        self.seed.iter().any(|&b| b != 0)
+    }
+}
+
+// Serialized as a `u8` length (always `SEED_SIZE`) followed by the seed bytes.
+impl Serialize for HdWalletSeed {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = (Self::SEED_SIZE as u8).serialize(writer)?;
+        writer.write_all(&self.seed)?;
+        size += Self::SEED_SIZE;
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + Self::SEED_SIZE
+    }
+}
+
+impl Deserialize for HdWalletSeed {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let len: u8 = Deserialize::deserialize(reader)?;
+        if len as usize != Self::SEED_SIZE {
+            return Err(SerializingError::InvalidValue);
+        }
+        let mut seed = HdWalletSeed::default();
+        reader.read_exact(&mut seed.seed)?;
+        Ok(seed)
+    }
+}
+
+/// A hierarchical deterministic wallet. Accounts are derived from the master key along
+/// `<base_path>/<index>'` and their addresses are stored in derivation order.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HdWallet {
+    pub id: Address,
+    #[beserial(len_type(u8))]
+    pub base_path: String,
+    #[beserial(len_type(u32))]
+    pub accounts: Vec<Address>,
+    seed: Locked<HdWalletSeed>,
+}
+
+impl HdWallet {
+    /// The default derivation path for Nimiq accounts.
+    pub const DEFAULT_BASE_PATH: &'static str = "m/44'/242'/0'";
+
+    /// Creates a new HD wallet from a mnemonic and locks its seed with `passphrase`.
+    /// Calling code should make sure to clear the passphrase from memory after use.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, mnemonic_password: Option<&str>, base_path: &str, passphrase: &[u8]) -> Result<Self, HdWalletError> {
+        match mnemonic.get_type(WORDLIST_EN) {
+            MnemonicType::BIP39 | MnemonicType::UNKNOWN => {},
+            _ => return Err(HdWalletError::InvalidMnemonic),
+        }
+        if !ExtendedPrivateKey::is_valid_path(base_path) {
+            return Err(HdWalletError::InvalidPath);
+        }
+
+        let mut seed_bytes = mnemonic.to_seed(mnemonic_password)
+            .map_err(|_| HdWalletError::InvalidMnemonic)?;
+        let seed = HdWalletSeed::from_bytes_cleared(&mut seed_bytes)
+            .ok_or(HdWalletError::InvalidMnemonic)?;
+        let id = seed.to_master_key().to_address();
+        let seed = Locked::with_defaults(seed, passphrase)
+            .map_err(|_| HdWalletError::LockFailed)?;
+
+        Ok(HdWallet {
+            id,
+            base_path: base_path.to_string(),
+            accounts: Vec::new(),
+            seed,
+        })
+    }
+
+    /// Returns the derivation path of the account at `index`.
+    pub fn path(&self, index: u32) -> String {
+        format!("{}/{}'", self.base_path, index)
+    }
+
+    /// Returns the index of the next account to be derived.
+    pub fn next_index(&self) -> u32 {
+        self.accounts.len() as u32
+    }
+
+    /// Derives the account at `index` without adding it to the wallet.
+    pub fn derive_account(&self, index: u32, passphrase: &[u8]) -> Result<WalletAccount, HdWalletError> {
+        let seed = self.seed.clone().unlock(passphrase)
+            .map_err(|_| HdWalletError::InvalidPassphrase)?;
+        let key = seed.to_master_key()
+            .derive_path(&self.path(index))
+            .ok_or(HdWalletError::DerivationFailed)?;
+        Ok(WalletAccount::from(KeyPair::from(key.into_private_key())))
+    }
+
+    /// Derives the next account and adds its address to the wallet.
+    pub fn derive_next_account(&mut self, passphrase: &[u8]) -> Result<WalletAccount, HdWalletError> {
+        let account = self.derive_account(self.next_index(), passphrase)?;
+        self.accounts.push(account.address.clone());
+        Ok(account)
+    }
+}
+
+impl IntoDatabaseValue for HdWallet {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for HdWallet {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
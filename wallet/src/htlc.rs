@@ -0,0 +1,95 @@
+use beserial::{Serialize, SerializingError, WriteBytesExt};
+use keys::Address;
+use primitives::account::AccountType;
+use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use transaction::{SignatureProof, Transaction};
+use transaction::account::htlc_contract::{AnyHash, HashAlgorithm, ProofType};
+
+/// The proof of a transaction that moves funds out of an HTLC.
+pub enum HtlcProof {
+    /// Signed by the HTLC recipient, who reveals the pre-image of the hash root.
+    /// `hash_depth` is the number of times `pre_image` has to be hashed to obtain `hash_root`.
+    RegularTransfer {
+        hash_algorithm: HashAlgorithm,
+        hash_depth: u8,
+        hash_root: AnyHash,
+        pre_image: AnyHash,
+        signature_proof: SignatureProof,
+    },
+    /// Signed by both the HTLC recipient and the HTLC sender before the timeout.
+    EarlyResolve {
+        recipient_proof: SignatureProof,
+        sender_proof: SignatureProof,
+    },
+    /// Signed by the HTLC sender after the timeout.
+    TimeoutResolve {
+        signature_proof: SignatureProof,
+    },
+}
+
+impl HtlcProof {
+    /// Creates an unsigned transaction that moves `value` from the HTLC at `htlc_address` to `recipient`.
+    pub fn create_transaction(htlc_address: Address, recipient: Address, value: Coin, fee: Coin, validity_start_height: u32, network_id: NetworkId) -> Transaction {
+        Transaction::new_extended(
+            htlc_address, AccountType::HTLC,
+            recipient, AccountType::Basic,
+            value, fee,
+            Vec::new(),
+            validity_start_height,
+            network_id,
+        )
+    }
+
+    pub fn proof_type(&self) -> ProofType {
+        match self {
+            HtlcProof::RegularTransfer { .. } => ProofType::RegularTransfer,
+            HtlcProof::EarlyResolve { .. } => ProofType::EarlyResolve,
+            HtlcProof::TimeoutResolve { .. } => ProofType::TimeoutResolve,
+        }
+    }
+}
+
+impl Serialize for HtlcProof {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = Serialize::serialize(&self.proof_type(), writer)?;
+        match self {
+            HtlcProof::RegularTransfer { hash_algorithm, hash_depth, hash_root, pre_image, signature_proof } => {
+                size += Serialize::serialize(hash_algorithm, writer)?;
+                size += Serialize::serialize(hash_depth, writer)?;
+                size += Serialize::serialize(hash_root, writer)?;
+                size += Serialize::serialize(pre_image, writer)?;
+                size += Serialize::serialize(signature_proof, writer)?;
+            },
+            HtlcProof::EarlyResolve { recipient_proof, sender_proof } => {
+                size += Serialize::serialize(recipient_proof, writer)?;
+                size += Serialize::serialize(sender_proof, writer)?;
+            },
+            HtlcProof::TimeoutResolve { signature_proof } => {
+                size += Serialize::serialize(signature_proof, writer)?;
+            },
+        }
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let mut size = Serialize::serialized_size(&self.proof_type());
+        match self {
+            HtlcProof::RegularTransfer { hash_algorithm, hash_depth, hash_root, pre_image, signature_proof } => {
+                size += Serialize::serialized_size(hash_algorithm);
+                size += Serialize::serialized_size(hash_depth);
+                size += Serialize::serialized_size(hash_root);
+                size += Serialize::serialized_size(pre_image);
+                size += Serialize::serialized_size(signature_proof);
+            },
+            HtlcProof::EarlyResolve { recipient_proof, sender_proof } => {
+                size += Serialize::serialized_size(recipient_proof);
+                size += Serialize::serialized_size(sender_proof);
+            },
+            HtlcProof::TimeoutResolve { signature_proof } => {
+                size += Serialize::serialized_size(signature_proof);
+            },
+        }
+        size
+    }
+}
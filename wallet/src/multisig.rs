@@ -0,0 +1,185 @@
+use std::io;
+
+use failure::Fail;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError};
+use database::{FromDatabaseValue, IntoDatabaseValue};
+use keys::{Address, KeyPair, PublicKey};
+use keys::multisig::{aggregate_public_keys, Commitment, PartialSignature, RandomSecret};
+use nimiq_hash::Blake2bHasher;
+use nimiq_utils::merkle::{compute_root_from_content, Blake2bMerklePath};
+use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use transaction::{SignatureProof, Transaction};
+
+#[derive(Debug, Fail, PartialEq, Eq)]
+pub enum MultiSigError {
+    #[fail(display = "At least one public key is required")]
+    NoPublicKeys,
+    #[fail(display = "Public keys must be distinct")]
+    DuplicatePublicKeys,
+    #[fail(display = "Invalid number of required signatures")]
+    InvalidMinSignatures,
+    #[fail(display = "Signers are not a valid combination of the account's public keys")]
+    InvalidSigners,
+    #[fail(display = "Own key is not one of the signers")]
+    NotASigner,
+    #[fail(display = "Number of commitments or signatures does not match number of signers")]
+    CountMismatch,
+    #[fail(display = "Aggregated signature is invalid")]
+    InvalidSignature,
+}
+
+/// A multisig account that requires `min_signatures` out of `public_keys` to sign.
+///
+/// The address is the root of a Merkle tree over the aggregated public keys of all possible
+/// combinations of `min_signatures` signers. A transaction is signed by aggregating the signers'
+/// partial signatures and proving that their aggregated public key is part of that tree.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MultiSigAccount {
+    pub min_signatures: u8,
+    #[beserial(len_type(u8))]
+    pub public_keys: Vec<PublicKey>,
+    #[beserial(skip)]
+    pub multisig_keys: Vec<PublicKey>,
+    #[beserial(skip)]
+    pub address: Address,
+}
+
+impl MultiSigAccount {
+    pub fn new(min_signatures: u8, mut public_keys: Vec<PublicKey>) -> Result<Self, MultiSigError> {
+        if public_keys.is_empty() {
+            return Err(MultiSigError::NoPublicKeys);
+        }
+
+        public_keys.sort();
+        if public_keys.windows(2).any(|keys| keys[0] == keys[1]) {
+            return Err(MultiSigError::DuplicatePublicKeys);
+        }
+
+        if min_signatures == 0 || min_signatures as usize > public_keys.len() {
+            return Err(MultiSigError::InvalidMinSignatures);
+        }
+
+        let mut multisig_keys: Vec<PublicKey> = combinations(&public_keys, min_signatures as usize)
+            .iter()
+            .map(|signers| aggregate_public_keys(signers))
+            .collect();
+        multisig_keys.sort();
+
+        let address = Address::from(compute_root_from_content::<Blake2bHasher, PublicKey>(&multisig_keys));
+
+        Ok(MultiSigAccount {
+            min_signatures,
+            public_keys,
+            multisig_keys,
+            address,
+        })
+    }
+
+    /// Creates an unsigned basic transaction from this account.
+    pub fn create_transaction(&self, recipient: Address, value: Coin, fee: Coin, validity_start_height: u32, network_id: NetworkId) -> Transaction {
+        Transaction::new_basic(self.address.clone(), recipient, value, fee, validity_start_height, network_id)
+    }
+
+    /// Computes the aggregated public key for a set of signers.
+    pub fn signer_key(&self, signers: &[PublicKey]) -> Result<PublicKey, MultiSigError> {
+        let signers = self.sorted_signers(signers)?;
+        Ok(aggregate_public_keys(&signers))
+    }
+
+    /// Creates this signer's partial signature for `transaction`.
+    /// `commitments` must contain the commitments of all `signers`, including the own one.
+    pub fn partially_sign_transaction(&self, transaction: &Transaction, key_pair: &KeyPair, signers: &[PublicKey], secret: &RandomSecret, commitments: &[Commitment]) -> Result<PartialSignature, MultiSigError> {
+        let signers = self.sorted_signers(signers)?;
+        if !signers.contains(&key_pair.public) {
+            return Err(MultiSigError::NotASigner);
+        }
+        if commitments.len() != signers.len() {
+            return Err(MultiSigError::CountMismatch);
+        }
+
+        let (partial_signature, _, _) = key_pair.partial_sign(&signers, secret, commitments, transaction.serialize_content().as_slice());
+        Ok(partial_signature)
+    }
+
+    /// Aggregates the signers' commitments and partial signatures into the final proof of `transaction`.
+    pub fn sign_transaction(&self, transaction: &mut Transaction, signers: &[PublicKey], commitments: &[Commitment], partial_signatures: &[PartialSignature]) -> Result<(), MultiSigError> {
+        let signer_key = self.signer_key(signers)?;
+        if commitments.len() != signers.len() || partial_signatures.len() != signers.len() {
+            return Err(MultiSigError::CountMismatch);
+        }
+
+        let aggregated_commitment: Commitment = commitments.iter().sum();
+        let aggregated_signature: PartialSignature = partial_signatures.iter().sum();
+        let signature = aggregated_signature.to_signature(&aggregated_commitment);
+
+        let proof = SignatureProof {
+            public_key: signer_key,
+            merkle_path: Blake2bMerklePath::new::<Blake2bHasher, PublicKey>(&self.multisig_keys, &signer_key),
+            signature,
+        };
+        if !proof.is_signed_by(&self.address) || !proof.verify(transaction.serialize_content().as_slice()) {
+            return Err(MultiSigError::InvalidSignature);
+        }
+
+        transaction.proof = proof.serialize_to_vec();
+        Ok(())
+    }
+
+    fn sorted_signers(&self, signers: &[PublicKey]) -> Result<Vec<PublicKey>, MultiSigError> {
+        let mut signers = signers.to_vec();
+        signers.sort();
+        signers.dedup();
+
+        if signers.len() != self.min_signatures as usize || !signers.iter().all(|key| self.public_keys.contains(key)) {
+            return Err(MultiSigError::InvalidSigners);
+        }
+        Ok(signers)
+    }
+}
+
+/// Returns all combinations of `k` elements of `values`, preserving their order.
+fn combinations<T: Clone>(values: &[T], k: usize) -> Vec<Vec<T>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    if values.len() < k {
+        return Vec::new();
+    }
+
+    let mut result = Vec::new();
+    for (i, value) in values.iter().enumerate() {
+        for mut rest in combinations(&values[i + 1..], k - 1) {
+            rest.insert(0, value.clone());
+            result.push(rest);
+        }
+    }
+    result
+}
+
+impl Deserialize for MultiSigAccount {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let min_signatures: u8 = Deserialize::deserialize(reader)?;
+        let public_keys: Vec<PublicKey> = beserial::DeserializeWithLength::deserialize::<u8, _>(reader)?;
+        MultiSigAccount::new(min_signatures, public_keys)
+            .map_err(|_| SerializingError::InvalidValue)
+    }
+}
+
+impl IntoDatabaseValue for MultiSigAccount {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for MultiSigAccount {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
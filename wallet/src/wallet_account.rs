@@ -5,9 +5,14 @@ use database::{FromDatabaseValue, IntoDatabaseValue};
 use keys::{Address, KeyPair, PublicKey, SecureGenerate, Signature};
 use nimiq_hash::{Hash, Sha256Hash};
 use nimiq_utils::otp::Verify;
+use primitives::account::AccountType;
 use primitives::coin::Coin;
 use primitives::networks::NetworkId;
 use transaction::{SignatureProof, Transaction};
+use transaction::account::htlc_contract::{AnyHash, CreationTransactionData as HtlcCreationData, HashAlgorithm};
+use transaction::account::vesting_contract::CreationTransactionData as VestingCreationData;
+
+use crate::htlc::HtlcProof;
 
 pub const NIMIQ_SIGN_MESSAGE_PREFIX: &[u8] = b"\x16Nimiq Signed Message:\n";
 
@@ -36,9 +41,48 @@ impl WalletAccount {
         transaction
     }
 
+    /// Creates a signed transaction that creates a vesting contract with `value`.
+    /// The address of the new contract is the transaction's recipient.
+    pub fn create_vesting_transaction(&self, data: &VestingCreationData, value: Coin, fee: Coin, validity_start_height: u32, network_id: NetworkId) -> Transaction {
+        let mut transaction = Transaction::new_contract_creation(data.to_tx_data(), self.address.clone(), AccountType::Basic, AccountType::Vesting, value, fee, validity_start_height, network_id);
+        self.sign_transaction(&mut transaction);
+        transaction
+    }
+
+    /// Creates a signed transaction that creates an HTLC with `value`.
+    /// The address of the new contract is the transaction's recipient.
+    pub fn create_htlc_transaction(&self, data: &HtlcCreationData, value: Coin, fee: Coin, validity_start_height: u32, network_id: NetworkId) -> Transaction {
+        let mut transaction = Transaction::new_contract_creation(data.serialize_to_vec(), self.address.clone(), AccountType::Basic, AccountType::HTLC, value, fee, validity_start_height, network_id);
+        self.sign_transaction(&mut transaction);
+        transaction
+    }
+
     pub fn sign_transaction(&self, transaction: &mut Transaction) {
+        transaction.proof = self.create_signature_proof(transaction).serialize_to_vec();
+    }
+
+    pub fn create_signature_proof(&self, transaction: &Transaction) -> SignatureProof {
         let signature = self.key_pair.sign(transaction.serialize_content().as_slice());
-        let proof = SignatureProof::from(self.key_pair.public, signature);
+        SignatureProof::from(self.key_pair.public, signature)
+    }
+
+    /// Signs an HTLC redemption as the HTLC recipient by revealing `pre_image`.
+    pub fn sign_htlc_regular_transfer(&self, transaction: &mut Transaction, hash_algorithm: HashAlgorithm, hash_depth: u8, hash_root: AnyHash, pre_image: AnyHash) {
+        let proof = HtlcProof::RegularTransfer {
+            hash_algorithm,
+            hash_depth,
+            hash_root,
+            pre_image,
+            signature_proof: self.create_signature_proof(transaction),
+        };
+        transaction.proof = proof.serialize_to_vec();
+    }
+
+    /// Signs an HTLC redemption as the HTLC sender after the contract has timed out.
+    pub fn sign_htlc_timeout_resolve(&self, transaction: &mut Transaction) {
+        let proof = HtlcProof::TimeoutResolve {
+            signature_proof: self.create_signature_proof(transaction),
+        };
         transaction.proof = proof.serialize_to_vec();
     }
 
@@ -2,6 +2,8 @@ use database::{Database, Environment, ReadTransaction, Transaction, WriteTransac
 use database::cursor::ReadCursor;
 use keys::Address;
 
+use crate::hd_wallet::HdWallet;
+use crate::multisig::MultiSigAccount;
 use crate::wallet_account::WalletAccount;
 use nimiq_utils::otp::Locked;
 
@@ -9,14 +11,20 @@ use nimiq_utils::otp::Locked;
 pub struct WalletStore {
     env: Environment,
     wallet_db: Database,
+    hd_wallet_db: Database,
+    multisig_db: Database,
 }
 
 impl WalletStore {
     const WALLET_DB_NAME: &'static str = "Wallet";
+    const HD_WALLET_DB_NAME: &'static str = "HdWallet";
+    const MULTISIG_DB_NAME: &'static str = "MultiSig";
 
     pub fn new(env: Environment) -> Self {
         let wallet_db = env.open_database(Self::WALLET_DB_NAME.to_string());
-        WalletStore { env, wallet_db }
+        let hd_wallet_db = env.open_database(Self::HD_WALLET_DB_NAME.to_string());
+        let multisig_db = env.open_database(Self::MULTISIG_DB_NAME.to_string());
+        WalletStore { env, wallet_db, hd_wallet_db, multisig_db }
     }
 
     pub fn create_read_transaction(&self) -> ReadTransaction {
@@ -59,4 +67,70 @@ impl WalletStore {
     pub fn put(&self, address: &Address, wallet: &Locked<WalletAccount>, txn: &mut WriteTransaction) {
         txn.put_reserve(&self.wallet_db, address, wallet);
     }
+
+    pub fn list_hd_wallets(&self, txn_option: Option<&Transaction>) -> Vec<Address> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut wallets = Vec::new();
+        let mut cursor = txn.cursor(&self.hd_wallet_db);
+        let mut wallet: Option<(Address, HdWallet)> = cursor.first();
+
+        while let Some((id, _)) = wallet {
+            wallets.push(id);
+            wallet = cursor.next();
+        }
+
+        wallets
+    }
+
+    pub fn get_hd_wallet(&self, id: &Address, txn_option: Option<&Transaction>) -> Option<HdWallet> {
+        match txn_option {
+            Some(txn) => txn.get(&self.hd_wallet_db, id),
+            None => ReadTransaction::new(&self.env).get(&self.hd_wallet_db, id)
+        }
+    }
+
+    pub fn put_hd_wallet(&self, wallet: &HdWallet, txn: &mut WriteTransaction) {
+        txn.put_reserve(&self.hd_wallet_db, &wallet.id, wallet);
+    }
+
+    pub fn list_multisig(&self, txn_option: Option<&Transaction>) -> Vec<Address> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut accounts = Vec::new();
+        let mut cursor = txn.cursor(&self.multisig_db);
+        let mut account: Option<(Address, MultiSigAccount)> = cursor.first();
+
+        while let Some((address, _)) = account {
+            accounts.push(address);
+            account = cursor.next();
+        }
+
+        accounts
+    }
+
+    pub fn get_multisig(&self, address: &Address, txn_option: Option<&Transaction>) -> Option<MultiSigAccount> {
+        match txn_option {
+            Some(txn) => txn.get(&self.multisig_db, address),
+            None => ReadTransaction::new(&self.env).get(&self.multisig_db, address)
+        }
+    }
+
+    pub fn put_multisig(&self, account: &MultiSigAccount, txn: &mut WriteTransaction) {
+        txn.put_reserve(&self.multisig_db, &account.address, account);
+    }
 }
@@ -0,0 +1,99 @@
+extern crate nimiq_hash as hash;
+extern crate nimiq_keys as keys;
+extern crate nimiq_primitives as primitives;
+extern crate nimiq_transaction as transaction;
+extern crate nimiq_wallet as wallet;
+
+use beserial::Serialize;
+use hash::{Blake2bHasher, Hasher};
+use keys::Address;
+use primitives::account::AccountType;
+use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use transaction::account::htlc_contract::{AnyHash, CreationTransactionData as HtlcCreationData, HashAlgorithm};
+use transaction::account::vesting_contract::CreationTransactionData as VestingCreationData;
+use wallet::{HtlcProof, WalletAccount};
+
+fn htlc_data(sender: &WalletAccount, recipient: &WalletAccount, pre_image: [u8; 32]) -> HtlcCreationData {
+    let hash_root: [u8; 32] = Blake2bHasher::default().digest(&pre_image[..]).into();
+    HtlcCreationData {
+        sender: sender.address.clone(),
+        recipient: recipient.address.clone(),
+        hash_algorithm: HashAlgorithm::Blake2b,
+        hash_root: AnyHash::from(hash_root),
+        hash_count: 1,
+        timeout: 1000,
+    }
+}
+
+#[test]
+fn it_creates_vesting_contracts() {
+    let account = WalletAccount::generate();
+    let data = VestingCreationData {
+        owner: account.address.clone(),
+        start: 100,
+        step_blocks: 10,
+        step_amount: Coin::from_u64_unchecked(50),
+        total_amount: Coin::from_u64_unchecked(1000),
+    };
+
+    let transaction = account.create_vesting_transaction(&data, Coin::from_u64_unchecked(1000), Coin::ZERO, 1, NetworkId::Dummy);
+    assert_eq!(transaction.recipient_type, AccountType::Vesting);
+    assert_eq!(transaction.recipient, transaction.contract_creation_address());
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Dummy));
+
+    let parsed = VestingCreationData::parse(&transaction).unwrap();
+    assert_eq!(parsed.owner, data.owner);
+    assert_eq!(parsed.start, data.start);
+    assert_eq!(parsed.step_blocks, data.step_blocks);
+    assert_eq!(parsed.step_amount, data.step_amount);
+    assert_eq!(parsed.total_amount, data.total_amount);
+}
+
+#[test]
+fn it_creates_htlcs() {
+    let sender = WalletAccount::generate();
+    let recipient = WalletAccount::generate();
+    let data = htlc_data(&sender, &recipient, [7u8; 32]);
+
+    let transaction = sender.create_htlc_transaction(&data, Coin::from_u64_unchecked(1000), Coin::ZERO, 1, NetworkId::Dummy);
+    assert_eq!(transaction.recipient_type, AccountType::HTLC);
+    assert_eq!(transaction.recipient, transaction.contract_creation_address());
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Dummy));
+
+    let parsed = HtlcCreationData::parse(&transaction).unwrap();
+    assert_eq!(parsed.recipient, recipient.address);
+    assert!(parsed.hash_root == data.hash_root);
+}
+
+#[test]
+fn it_redeems_htlcs_with_every_proof_type() {
+    let sender = WalletAccount::generate();
+    let recipient = WalletAccount::generate();
+    let data = htlc_data(&sender, &recipient, [7u8; 32]);
+    let htlc_address = Address::from([1u8; Address::SIZE]);
+
+    let new_transaction = || HtlcProof::create_transaction(
+        htlc_address.clone(), recipient.address.clone(), Coin::from_u64_unchecked(1000), Coin::ZERO, 1, NetworkId::Dummy);
+
+    let mut transaction = new_transaction();
+    recipient.sign_htlc_regular_transfer(&mut transaction, data.hash_algorithm, 1, data.hash_root.clone(), AnyHash::from([7u8; 32]));
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Dummy));
+
+    // A wrong pre-image is rejected.
+    let mut transaction = new_transaction();
+    recipient.sign_htlc_regular_transfer(&mut transaction, data.hash_algorithm, 1, data.hash_root.clone(), AnyHash::from([8u8; 32]));
+    assert!(transaction.verify(NetworkId::Dummy).is_err());
+
+    let mut transaction = new_transaction();
+    let proof = HtlcProof::EarlyResolve {
+        recipient_proof: recipient.create_signature_proof(&transaction),
+        sender_proof: sender.create_signature_proof(&transaction),
+    };
+    transaction.proof = proof.serialize_to_vec();
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Dummy));
+
+    let mut transaction = new_transaction();
+    sender.sign_htlc_timeout_resolve(&mut transaction);
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Dummy));
+}
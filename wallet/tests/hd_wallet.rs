@@ -0,0 +1,64 @@
+extern crate nimiq_key_derivation as key_derivation;
+extern crate nimiq_mnemonic as mnemonic;
+extern crate nimiq_wallet as wallet;
+
+use beserial::{Deserialize, Serialize};
+use key_derivation::ExtendedPrivateKey;
+use mnemonic::Mnemonic;
+use wallet::{HdWallet, HdWalletError};
+
+const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+#[test]
+fn it_derives_accounts_along_path() {
+    let mnemonic = Mnemonic::from(MNEMONIC);
+    let mut hd_wallet = HdWallet::from_mnemonic(&mnemonic, None, HdWallet::DEFAULT_BASE_PATH, b"password").unwrap();
+
+    let master_key = ExtendedPrivateKey::from_seed(mnemonic.to_seed(None).unwrap());
+    assert_eq!(hd_wallet.id, master_key.to_address());
+
+    for index in 0..3 {
+        let account = hd_wallet.derive_next_account(b"password").unwrap();
+        let expected = master_key.derive_path(&format!("m/44'/242'/0'/{}'", index)).unwrap();
+        assert_eq!(account.address, expected.to_address());
+    }
+    assert_eq!(hd_wallet.next_index(), 3);
+    assert_eq!(hd_wallet.accounts.len(), 3);
+
+    let account = hd_wallet.derive_account(1, b"password").unwrap();
+    assert_eq!(account.address, hd_wallet.accounts[1]);
+}
+
+#[test]
+fn it_rejects_wrong_passphrase() {
+    let mnemonic = Mnemonic::from(MNEMONIC);
+    let mut hd_wallet = HdWallet::from_mnemonic(&mnemonic, None, HdWallet::DEFAULT_BASE_PATH, b"password").unwrap();
+
+    match hd_wallet.derive_next_account(b"wrong") {
+        Err(HdWalletError::InvalidPassphrase) => {},
+        _ => assert!(false, "Expected invalid passphrase"),
+    }
+    assert_eq!(hd_wallet.next_index(), 0);
+}
+
+#[test]
+fn it_rejects_invalid_mnemonics_and_paths() {
+    let invalid = Mnemonic::from("abandon abandon abandon");
+    assert!(HdWallet::from_mnemonic(&invalid, None, HdWallet::DEFAULT_BASE_PATH, b"").is_err());
+
+    let mnemonic = Mnemonic::from(MNEMONIC);
+    assert!(HdWallet::from_mnemonic(&mnemonic, None, "m/44/242", b"").is_err());
+}
+
+#[test]
+fn it_keeps_the_seed_when_serialized() {
+    let mnemonic = Mnemonic::from(MNEMONIC);
+    let mut hd_wallet = HdWallet::from_mnemonic(&mnemonic, None, HdWallet::DEFAULT_BASE_PATH, b"password").unwrap();
+    let account = hd_wallet.derive_next_account(b"password").unwrap();
+
+    let bytes = hd_wallet.serialize_to_vec();
+    let mut restored = HdWallet::deserialize_from_vec(&bytes).unwrap();
+    assert_eq!(restored.id, hd_wallet.id);
+    assert_eq!(restored.derive_account(0, b"password").unwrap().address, account.address);
+    assert_eq!(restored.derive_next_account(b"password").unwrap().address, hd_wallet.derive_next_account(b"password").unwrap().address);
+}
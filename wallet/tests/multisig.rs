@@ -0,0 +1,71 @@
+extern crate nimiq_keys as keys;
+extern crate nimiq_primitives as primitives;
+extern crate nimiq_wallet as wallet;
+
+use keys::{Address, KeyPair, PublicKey, SecureGenerate};
+use keys::multisig::{Commitment, CommitmentPair, PartialSignature};
+use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use wallet::{MultiSigAccount, MultiSigError};
+
+#[test]
+fn it_derives_the_same_address_regardless_of_key_order() {
+    let key_pairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate_default_csprng()).collect();
+    let public_keys: Vec<PublicKey> = key_pairs.iter().map(|key_pair| key_pair.public).collect();
+    let mut reversed = public_keys.clone();
+    reversed.reverse();
+
+    let account = MultiSigAccount::new(2, public_keys).unwrap();
+    let account_reversed = MultiSigAccount::new(2, reversed).unwrap();
+    assert_eq!(account.address, account_reversed.address);
+    assert_eq!(account.multisig_keys.len(), 3);
+}
+
+#[test]
+fn it_rejects_invalid_parameters() {
+    let public_keys: Vec<PublicKey> = (0..2).map(|_| KeyPair::generate_default_csprng().public).collect();
+    assert_eq!(MultiSigAccount::new(0, public_keys.clone()), Err(MultiSigError::InvalidMinSignatures));
+    assert_eq!(MultiSigAccount::new(3, public_keys), Err(MultiSigError::InvalidMinSignatures));
+    assert_eq!(MultiSigAccount::new(1, vec![]), Err(MultiSigError::NoPublicKeys));
+}
+
+#[test]
+fn it_rejects_duplicate_public_keys() {
+    let public_key = KeyPair::generate_default_csprng().public;
+    let other_key = KeyPair::generate_default_csprng().public;
+    // Deduplicating would have turned this into a valid 2-of-2 account.
+    assert_eq!(MultiSigAccount::new(2, vec![public_key, other_key, public_key]), Err(MultiSigError::DuplicatePublicKeys));
+    assert_eq!(MultiSigAccount::new(1, vec![public_key, public_key]), Err(MultiSigError::DuplicatePublicKeys));
+}
+
+#[test]
+fn it_can_sign_transactions() {
+    let key_pairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate_default_csprng()).collect();
+    let public_keys: Vec<PublicKey> = key_pairs.iter().map(|key_pair| key_pair.public).collect();
+    let account = MultiSigAccount::new(2, public_keys.clone()).unwrap();
+
+    let mut transaction = account.create_transaction(
+        Address::from_user_friendly_address("NQ16 C3HR 85U8 P7MK F52R E9RG SA3Y Q69C X563").unwrap(),
+        Coin::from_u64_unchecked(42),
+        Coin::ZERO,
+        0,
+        NetworkId::Main,
+    );
+
+    // Signers 0 and 2 participate in this round.
+    let signers = vec![public_keys[0], public_keys[2]];
+    let commitment_pairs = vec![CommitmentPair::generate_default_csprng(), CommitmentPair::generate_default_csprng()];
+    let commitments: Vec<Commitment> = commitment_pairs.iter().map(|pair| *pair.commitment()).collect();
+
+    let partial_signatures: Vec<PartialSignature> = vec![
+        account.partially_sign_transaction(&transaction, &key_pairs[0], &signers, commitment_pairs[0].random_secret(), &commitments).unwrap(),
+        account.partially_sign_transaction(&transaction, &key_pairs[2], &signers, commitment_pairs[1].random_secret(), &commitments).unwrap(),
+    ];
+
+    // A key that is not part of the round can't sign.
+    assert_eq!(account.partially_sign_transaction(&transaction, &key_pairs[1], &signers, commitment_pairs[0].random_secret(), &commitments),
+               Err(MultiSigError::NotASigner));
+
+    account.sign_transaction(&mut transaction, &signers, &commitments, &partial_signatures).unwrap();
+    assert_eq!(Ok(()), transaction.verify(NetworkId::Main));
+}
@@ -23,9 +23,11 @@ use tokio_tungstenite::tungstenite::{Message, Error as WsError};
 use parking_lot::RwLock;
 use json::{JsonValue, object};
 
+use utils::json_value::serde_to_json;
 use utils::unique_id::UniqueId;
 use consensus::{Consensus, AlbatrossConsensusProtocol};
 use blockchain_base::AbstractBlockchain;
+use blockchain_albatross::Blockchain;
 use blockchain_albatross::blockchain::BlockchainEvent;
 use hash::{Hash, Blake2bHash};
 #[cfg(feature="validator")]
@@ -149,11 +151,15 @@ impl WsRpcServer {
 
     pub fn register_blockchain(&self, consensus: Arc<Consensus<AlbatrossConsensusProtocol>>) {
         let connections_listener = Arc::clone(&self.connections);
+        // The blockchain owns the listener, so only keep a weak reference to it.
+        let weak_blockchain = Arc::downgrade(&consensus.blockchain);
 
         consensus.blockchain.register_listener(move |event: &BlockchainEvent| {
             if !connections_listener.read().is_empty() {
-                if let Some(message) = Self::map_blockchain_event(event) {
-                    Self::broadcast_message(&connections_listener, message)
+                if let Some(blockchain) = weak_blockchain.upgrade() {
+                    if let Some(message) = Self::map_blockchain_event(&blockchain, event) {
+                        Self::broadcast_message(&connections_listener, message)
+                    }
                 }
             }
         });
@@ -172,21 +178,24 @@ impl WsRpcServer {
         });
     }
 
-    fn map_blockchain_event(event: &BlockchainEvent) -> Option<JsonValue> {
+    /// Blockchain events carry the affected blocks in their JSON representation
+    /// (see `docs/json.md`), the same one the JSON-RPC server uses.
+    fn map_blockchain_event(blockchain: &Blockchain, event: &BlockchainEvent) -> Option<JsonValue> {
         Some(match event {
             BlockchainEvent::Extended(block_hash) => object!{
                 "eventType" => "blockchainExtended",
                 "blockHash" => block_hash.to_string(),
+                "block" => Self::block_to_json(blockchain, block_hash),
             },
             BlockchainEvent::Rebranched(reverted, rebranched) => {
                 let (old_head, _) = reverted.last()?;
-                let (new_head, _) = rebranched.last()?;
+                let (new_head, new_head_block) = rebranched.last()?;
 
-                let reverted = JsonValue::Array(reverted.into_iter()
+                let reverted = JsonValue::Array(reverted.iter()
                     .map(|(block_hash, _)|  JsonValue::String(block_hash.to_string()))
                     .collect());
 
-                let rebranched = JsonValue::Array(rebranched.into_iter()
+                let rebranched = JsonValue::Array(rebranched.iter()
                     .map(|(block_hash, _)|  JsonValue::String(block_hash.to_string()))
                     .collect());
 
@@ -196,15 +205,23 @@ impl WsRpcServer {
                     "rebranched" => rebranched,
                     "oldHead" => old_head.to_string(),
                     "newHead" => new_head.to_string(),
+                    "newHeadBlock" => serde_to_json(new_head_block),
                 }
             },
             BlockchainEvent::Finalized(block_hash) => object!{
                 "eventType" => "blockchainFinalized",
                 "blockHash" => block_hash.to_string(),
+                "block" => Self::block_to_json(blockchain, block_hash),
             },
         })
     }
 
+    fn block_to_json(blockchain: &Blockchain, block_hash: &Blake2bHash) -> JsonValue {
+        blockchain.get_block(block_hash, false, true)
+            .map(|block| serde_to_json(&block))
+            .unwrap_or(JsonValue::Null)
+    }
+
     #[cfg(feature="validator")]
     fn map_validator_event(event: &ValidatorNetworkEvent) -> Option<JsonValue> {
         Some(match event {